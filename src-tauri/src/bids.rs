@@ -0,0 +1,914 @@
+use crate::costing::{build_conversion_edges, conversion_factor, default_purch_unit, item_price};
+use crate::purchase_orders::expected_price;
+use crate::sales::normalize_date;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A vendor's quoted price for an item. `norm_price` is the quote per the
+/// item's purchase unit, through the vendor's conversions.
+pub struct Bid {
+    pub bid_id: i64,
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    pub item_id: i64,
+    pub item_name: String,
+    pub price: f64,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    pub pack: String,
+    pub valid_from: String,
+    pub valid_to: String,
+    /// "open", "awarded", "lost", "superseded" or "expired".
+    pub status: String,
+    /// "import", "sheet" or "manual".
+    pub source: String,
+    pub norm_price: Option<f64>,
+    pub purch_unit_name: String,
+}
+
+pub struct NewBid {
+    pub vendor_id: i64,
+    pub item_id: i64,
+    pub price: f64,
+    pub unit_id: Option<i64>,
+    pub pack: String,
+    pub valid_from: Option<String>,
+    pub valid_to: Option<String>,
+}
+
+#[derive(Default)]
+pub struct BidFilter {
+    pub item_id: Option<i64>,
+    pub vendor_id: Option<i64>,
+    pub status: Option<String>,
+}
+
+/// All bids valid on a date for one item, best normalized price first.
+pub struct BidComparison {
+    pub item_id: i64,
+    pub item_name: String,
+    pub purch_unit_name: String,
+    pub current_vendor: String,
+    pub current_price: Option<f64>,
+    pub best_bid_id: Option<i64>,
+    pub bids: Vec<Bid>,
+}
+
+/// One item a vendor is asked to quote, in the unit they usually sell it in.
+pub struct BidRequestLine {
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub unit_id: i64,
+    pub unit_name: String,
+    pub pack: String,
+    pub last_price: Option<f64>,
+}
+
+/// Where each bid field sits in a CSV row.
+struct BidColumns {
+    vendor: usize,
+    item: usize,
+    price: usize,
+    unit: Option<usize>,
+    pack: Option<usize>,
+    valid_from: Option<usize>,
+    valid_to: Option<usize>,
+}
+
+const BID_SELECT: &str =
+    "SELECT b.bid_id, b.vendor_id, COALESCE(v.name, ''), b.item_id, COALESCE(i.name, ''),
+            b.price, b.unit_id, COALESCE(u.sing, ''), COALESCE(b.pack, ''),
+            COALESCE(b.valid_from, ''), COALESCE(b.valid_to, ''), COALESCE(b.status, 'open'),
+            COALESCE(b.source, '')
+     FROM vendor_bids b
+     LEFT JOIN vendors v ON v.vendor_id = b.vendor_id
+     LEFT JOIN items i ON i.item_id = b.item_id
+     LEFT JOIN units u ON u.unit_id = b.unit_id";
+
+fn normalize_header(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+impl BidColumns {
+    /// Maps a header row by name; `None` unless vendor, item and price are present.
+    fn from_header(fields: &[String]) -> Option<BidColumns> {
+        let find = |names: &[&str]| {
+            fields
+                .iter()
+                .position(|f| names.contains(&normalize_header(f).as_str()))
+        };
+        Some(BidColumns {
+            vendor: find(&["vendorid"])?,
+            item: find(&["itemid"])?,
+            price: find(&["price", "quote", "quoteprice", "bidprice"])?,
+            unit: find(&["unitid"]),
+            pack: find(&["pack", "packsize"]),
+            valid_from: find(&["validfrom", "startdate", "biddate", "datefrom"]),
+            valid_to: find(&["validto", "enddate", "expdate", "expires", "dateto"]),
+        })
+    }
+
+    fn parse(&self, fields: &[String]) -> Option<NewBid> {
+        let get = |idx: Option<usize>| {
+            idx.and_then(|i| fields.get(i))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let money = |v: String| {
+            v.trim_start_matches('$')
+                .replace(',', "")
+                .parse::<f64>()
+                .ok()
+        };
+        Some(NewBid {
+            vendor_id: get(Some(self.vendor))?.parse().ok()?,
+            item_id: get(Some(self.item))?.parse().ok()?,
+            price: get(Some(self.price)).and_then(money).filter(|p| *p > 0.0)?,
+            unit_id: get(self.unit).and_then(|v| v.parse().ok()),
+            pack: get(self.pack).unwrap_or_default(),
+            valid_from: get(self.valid_from).and_then(|d| normalize_date(&d)),
+            valid_to: get(self.valid_to).and_then(|d| normalize_date(&d)),
+        })
+    }
+}
+
+fn bid_from_row(row: &rusqlite::Row) -> rusqlite::Result<Bid> {
+    Ok(Bid {
+        bid_id: row.get(0)?,
+        vendor_id: row.get(1)?,
+        vendor_name: row.get(2)?,
+        item_id: row.get(3)?,
+        item_name: row.get(4)?,
+        price: row.get(5)?,
+        unit_id: row.get(6)?,
+        unit_name: row.get(7)?,
+        pack: row.get(8)?,
+        valid_from: row.get(9)?,
+        valid_to: row.get(10)?,
+        status: row.get(11)?,
+        source: row.get(12)?,
+        norm_price: None,
+        purch_unit_name: String::new(),
+    })
+}
+
+fn unit_name(conn: &Connection, unit_id: i64) -> String {
+    conn.query_row(
+        "SELECT COALESCE(sing, '-') FROM units WHERE unit_id = ?1",
+        [unit_id],
+        |row| row.get(0),
+    )
+    .unwrap_or_else(|_| "-".to_string())
+}
+
+/// The quote converted to a price per purchase unit, using the vendor's own
+/// pack conversions as well as the item's general ones.
+fn normalized_price(conn: &Connection, bid: &Bid) -> Result<Option<f64>, String> {
+    let Some(purch_unit) = default_purch_unit(conn, bid.item_id) else {
+        return Ok(None);
+    };
+    let bid_unit = bid.unit_id.unwrap_or(purch_unit);
+    let edges = build_conversion_edges(conn, bid.item_id, Some(bid.vendor_id))?;
+    // How many bid units make one purchase unit.
+    Ok(conversion_factor(&edges, purch_unit, bid_unit, 6).map(|(f, _)| bid.price * f))
+}
+
+fn with_normalized(conn: &Connection, mut bid: Bid) -> Result<Bid, String> {
+    bid.norm_price = normalized_price(conn, &bid)?;
+    bid.purch_unit_name = default_purch_unit(conn, bid.item_id)
+        .map(|u| unit_name(conn, u))
+        .unwrap_or_else(|| "-".to_string());
+    Ok(bid)
+}
+
+pub fn load_bid(conn: &Connection, bid_id: i64) -> Result<Bid, String> {
+    let bid = conn
+        .query_row(
+            &format!("{BID_SELECT} WHERE b.bid_id = ?1"),
+            [bid_id],
+            bid_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Bid {} not found", bid_id))?;
+    with_normalized(conn, bid)
+}
+
+pub fn list_bids(conn: &Connection, filter: &BidFilter) -> Result<Vec<Bid>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{BID_SELECT}
+             WHERE (?1 IS NULL OR b.item_id = ?1)
+               AND (?2 IS NULL OR b.vendor_id = ?2)
+               AND (?3 IS NULL OR b.status = ?3)
+             ORDER BY i.name COLLATE NOCASE, b.valid_from DESC, b.price"
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![filter.item_id, filter.vendor_id, filter.status],
+            bid_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut bids = Vec::new();
+    for row in rows {
+        bids.push(with_normalized(conn, row.map_err(|e| e.to_string())?)?);
+    }
+    Ok(bids)
+}
+
+fn check_refs(conn: &Connection, bid: &NewBid) -> Result<(), String> {
+    let exists = |sql: &str, id: i64| -> Result<bool, String> {
+        conn.query_row(sql, [id], |row| row.get::<_, i64>(0))
+            .map(|n| n > 0)
+            .map_err(|e| e.to_string())
+    };
+    if !exists("SELECT COUNT(*) FROM items WHERE item_id = ?1", bid.item_id)? {
+        return Err(format!("Unknown item_id {}", bid.item_id));
+    }
+    if !exists(
+        "SELECT COUNT(*) FROM vendors WHERE vendor_id = ?1",
+        bid.vendor_id,
+    )? {
+        return Err(format!("Unknown vendor_id {}", bid.vendor_id));
+    }
+    if let (Some(from), Some(to)) = (&bid.valid_from, &bid.valid_to) {
+        if to < from {
+            return Err("Valid-to date is before valid-from".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn insert_bid(conn: &Connection, bid: &NewBid, source: &str) -> Result<i64, String> {
+    check_refs(conn, bid)?;
+    conn.execute(
+        "INSERT INTO vendor_bids (vendor_id, item_id, price, unit_id, pack, valid_from, valid_to, status, source, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'open', ?8, datetime('now', 'localtime'))",
+        rusqlite::params![
+            bid.vendor_id,
+            bid.item_id,
+            bid.price,
+            bid.unit_id,
+            bid.pack,
+            bid.valid_from,
+            bid.valid_to,
+            source
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Adds a manually entered bid, or updates an open one.
+pub fn save_bid(conn: &Connection, bid_id: Option<i64>, bid: &NewBid) -> Result<i64, String> {
+    if bid.price <= 0.0 {
+        return Err("Price must be greater than 0".to_string());
+    }
+    let Some(bid_id) = bid_id else {
+        return insert_bid(conn, bid, "manual");
+    };
+    if load_bid(conn, bid_id)?.status != "open" {
+        return Err("Only open bids can be edited".to_string());
+    }
+    check_refs(conn, bid)?;
+    conn.execute(
+        "UPDATE vendor_bids SET vendor_id = ?2, item_id = ?3, price = ?4, unit_id = ?5, pack = ?6,
+                valid_from = ?7, valid_to = ?8
+         WHERE bid_id = ?1",
+        rusqlite::params![
+            bid_id,
+            bid.vendor_id,
+            bid.item_id,
+            bid.price,
+            bid.unit_id,
+            bid.pack,
+            bid.valid_from,
+            bid.valid_to
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(bid_id)
+}
+
+pub fn delete_bid(conn: &Connection, bid_id: i64) -> Result<(), String> {
+    if load_bid(conn, bid_id)?.status == "awarded" {
+        return Err("Awarded bids can't be deleted".to_string());
+    }
+    conn.execute("DELETE FROM vendor_bids WHERE bid_id = ?1", [bid_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Rebuilds the imported bids from the raw `bids` table, whose first row
+/// must be a header naming at least the VendorID, ItemID and Price columns;
+/// without one the file is rejected and the existing bids are left alone.
+/// Returns the number of bids created and a warning per skipped row.
+pub fn import_legacy_bids(conn: &Connection) -> Result<(usize, Vec<String>), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(col1, ''), COALESCE(col2, ''), COALESCE(col3, ''), COALESCE(col4, ''),
+                    COALESCE(col5, ''), COALESCE(col6, ''), COALESCE(col7, ''), COALESCE(col8, ''),
+                    COALESCE(col9, ''), COALESCE(col10, '')
+             FROM bids ORDER BY rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            (0..10)
+                .map(|i| row.get::<_, String>(i))
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let mut raw = Vec::new();
+    for row in rows {
+        raw.push(row.map_err(|e| e.to_string())?);
+    }
+
+    let Some(columns) = raw.first().and_then(|first| BidColumns::from_header(first)) else {
+        return Err(
+            "Bids.csv needs a header row with VendorID, ItemID and Price columns".to_string(),
+        );
+    };
+    let data = &raw[1..];
+    conn.execute(
+        "DELETE FROM vendor_bids WHERE source = 'import' AND status = 'open'",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    let mut count = 0usize;
+    let mut warnings = Vec::new();
+    for (idx, fields) in data.iter().enumerate() {
+        let Some(bid) = columns.parse(fields) else {
+            warnings.push(format!(
+                "Bids row {}: missing vendor, item or price",
+                idx + 1
+            ));
+            continue;
+        };
+        match insert_bid(conn, &bid, "import") {
+            Ok(_) => count += 1,
+            Err(err) => warnings.push(format!("Bids row {}: {}", idx + 1, err)),
+        }
+    }
+    Ok((count, warnings))
+}
+
+/// Imports a returned bid sheet (the CSV from `bid_request`, or any CSV
+/// with VendorId, ItemId and Price columns). Rows without a price were not
+/// quoted and are skipped; rows without dates use the given window.
+pub fn import_bid_sheet(
+    conn: &Connection,
+    path: &Path,
+    valid_from: Option<&str>,
+    valid_to: Option<&str>,
+) -> Result<(usize, Vec<String>), String> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+    let header: Vec<String> = rdr
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    let columns = BidColumns::from_header(&header)
+        .ok_or_else(|| "The bid sheet needs VendorId, ItemId and Price columns".to_string())?;
+
+    let mut count = 0usize;
+    let mut warnings = Vec::new();
+    for (idx, result) in rdr.records().enumerate() {
+        let record = result.map_err(|e| e.to_string())?;
+        let fields: Vec<String> = record.iter().map(|f| f.to_string()).collect();
+        let quoted = fields
+            .get(columns.price)
+            .is_some_and(|p| !p.trim().is_empty());
+        if !quoted {
+            continue;
+        }
+        let Some(mut bid) = columns.parse(&fields) else {
+            warnings.push(format!("Row {}: invalid vendor, item or price", idx + 2));
+            continue;
+        };
+        bid.valid_from = bid.valid_from.or_else(|| valid_from.map(str::to_string));
+        bid.valid_to = bid.valid_to.or_else(|| valid_to.map(str::to_string));
+        match insert_bid(conn, &bid, "sheet") {
+            Ok(_) => count += 1,
+            Err(err) => warnings.push(format!("Row {}: {}", idx + 2, err)),
+        }
+    }
+    Ok((count, warnings))
+}
+
+/// Items to quote for each chosen vendor: every item in `category`, or every
+/// item that has been bought or price-listed when no category is given.
+pub fn bid_request(
+    conn: &Connection,
+    vendor_ids: &[i64],
+    category: Option<&str>,
+) -> Result<Vec<BidRequestLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.item_id, COALESCE(i.name, ''), COALESCE(i.food_category, '')
+             FROM items i
+             WHERE (?1 IS NOT NULL AND i.food_category = ?1)
+                OR (?1 IS NULL AND (EXISTS (SELECT 1 FROM inv_prices p WHERE p.item_id = i.item_id)
+                                    OR EXISTS (SELECT 1 FROM trans t WHERE t.item_id = i.item_id)))
+             ORDER BY i.food_category COLLATE NOCASE, i.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([category], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row.map_err(|e| e.to_string())?);
+    }
+
+    let mut lines = Vec::new();
+    for &vendor_id in vendor_ids {
+        let vendor_name: String = conn
+            .query_row(
+                "SELECT COALESCE(name, '') FROM vendors WHERE vendor_id = ?1",
+                [vendor_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("Vendor {} not found", vendor_id))?;
+        for (item_id, item_name, category) in &items {
+            let Some(purch_unit) = default_purch_unit(conn, *item_id) else {
+                continue;
+            };
+            let unit_id: i64 = conn
+                .query_row(
+                    "SELECT unit_id FROM trans
+                     WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id IS NOT NULL
+                     ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+                    (item_id, vendor_id),
+                    |row| row.get(0),
+                )
+                .unwrap_or(purch_unit);
+            let pack: String = conn
+                .query_row(
+                    "SELECT COALESCE(pack, '') FROM inv_prices WHERE item_id = ?1 AND vendor_id = ?2 LIMIT 1",
+                    (item_id, vendor_id),
+                    |row| row.get(0),
+                )
+                .unwrap_or_default();
+            lines.push(BidRequestLine {
+                vendor_id,
+                vendor_name: vendor_name.clone(),
+                item_id: *item_id,
+                item_name: item_name.clone(),
+                category: category.clone(),
+                unit_id,
+                unit_name: unit_name(conn, unit_id),
+                pack: if pack == "-" { String::new() } else { pack },
+                last_price: expected_price(conn, *item_id, vendor_id, Some(unit_id)),
+            });
+        }
+    }
+    Ok(lines)
+}
+
+/// Side-by-side bids valid on `on_date`, normalized to each item's purchase unit.
+pub fn compare_bids(
+    conn: &Connection,
+    on_date: &str,
+    category: Option<&str>,
+) -> Result<Vec<BidComparison>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{BID_SELECT}
+             WHERE b.status IN ('open', 'awarded')
+               AND COALESCE(b.valid_from, '') <= ?1
+               AND (COALESCE(b.valid_to, '') = '' OR b.valid_to >= ?1)
+               AND (?2 IS NULL OR i.food_category = ?2)
+             ORDER BY i.name COLLATE NOCASE"
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![on_date, category], bid_from_row)
+        .map_err(|e| e.to_string())?;
+    let mut by_item: BTreeMap<(String, i64), Vec<Bid>> = BTreeMap::new();
+    for row in rows {
+        let bid = with_normalized(conn, row.map_err(|e| e.to_string())?)?;
+        by_item
+            .entry((bid.item_name.to_lowercase(), bid.item_id))
+            .or_default()
+            .push(bid);
+    }
+
+    let mut out = Vec::new();
+    for ((_, item_id), mut bids) in by_item {
+        bids.sort_by(|a, b| {
+            a.norm_price
+                .unwrap_or(f64::INFINITY)
+                .total_cmp(&b.norm_price.unwrap_or(f64::INFINITY))
+        });
+        let current = item_price(conn, item_id);
+        let current_vendor = current
+            .map(|(_, v)| {
+                conn.query_row(
+                    "SELECT COALESCE(name, '') FROM vendors WHERE vendor_id = ?1",
+                    [v],
+                    |row| row.get(0),
+                )
+                .unwrap_or_default()
+            })
+            .unwrap_or_default();
+        out.push(BidComparison {
+            item_id,
+            item_name: bids[0].item_name.clone(),
+            purch_unit_name: bids[0].purch_unit_name.clone(),
+            current_vendor,
+            current_price: current.map(|(p, _)| p),
+            best_bid_id: bids
+                .first()
+                .filter(|b| b.norm_price.is_some())
+                .map(|b| b.bid_id),
+            bids,
+        });
+    }
+    Ok(out)
+}
+
+const OVERLAPS: &str = "COALESCE(valid_from, '') <= COALESCE(NULLIF(?3, ''), '9999-12-31')
+                AND COALESCE(NULLIF(valid_to, ''), '9999-12-31') >= COALESCE(?2, '')";
+
+/// Awards a bid: competing open bids for the same item and window lose, an
+/// earlier overlapping award is rolled back, and the winning price becomes
+/// the item's preferred vendor price while the window is current.
+pub fn award_bid(conn: &Connection, bid_id: i64) -> Result<(), String> {
+    let bid = load_bid(conn, bid_id)?;
+    if bid.status != "open" {
+        return Err(format!("Bid {} is {}", bid_id, bid.status));
+    }
+    if bid.norm_price.is_none() {
+        return Err(format!(
+            "No conversion from {} to {} for {}; add one before awarding",
+            if bid.unit_name.is_empty() {
+                "-"
+            } else {
+                &bid.unit_name
+            },
+            bid.purch_unit_name,
+            bid.item_name
+        ));
+    }
+    let valid_from = Some(bid.valid_from.clone()).filter(|d| !d.is_empty());
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT bid_id FROM vendor_bids
+             WHERE item_id = ?1 AND status = 'awarded' AND bid_id <> ?4 AND {OVERLAPS}"
+        ))
+        .map_err(|e| e.to_string())?;
+    let earlier: Vec<i64> = stmt
+        .query_map(
+            rusqlite::params![bid.item_id, valid_from, bid.valid_to, bid_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<_>>()
+        .map_err(|e| e.to_string())?;
+    for other in earlier {
+        end_award(conn, other, "superseded")?;
+    }
+
+    conn.execute(
+        &format!(
+            "UPDATE vendor_bids SET status = 'lost'
+             WHERE item_id = ?1 AND status = 'open' AND bid_id <> ?4 AND {OVERLAPS}"
+        ),
+        rusqlite::params![bid.item_id, valid_from, bid.valid_to, bid_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE vendor_bids SET status = 'awarded', awarded_at = datetime('now', 'localtime') WHERE bid_id = ?1",
+        [bid_id],
+    )
+    .map_err(|e| e.to_string())?;
+    apply_awards(conn)?;
+    Ok(())
+}
+
+/// Makes an award's vendor the preferred price for its item: the vendor's
+/// price list row takes the normalized bid price and other vendors' rows
+/// are set inactive. What was replaced is kept so `end_award` can undo it.
+fn start_award(conn: &Connection, bid: &Bid) -> Result<(), String> {
+    let Some(price) = bid.norm_price else {
+        return Ok(());
+    };
+    let replaced: Option<(Option<f64>, Option<i64>, Option<String>)> = conn
+        .query_row(
+            "SELECT price, status, pack FROM inv_prices WHERE item_id = ?1 AND vendor_id = ?2 LIMIT 1",
+            (bid.item_id, bid.vendor_id),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let added = replaced.is_none();
+    let (replaced_price, replaced_status, replaced_pack) = replaced.unwrap_or_default();
+    if added {
+        let pack = if bid.pack.is_empty() {
+            "-"
+        } else {
+            bid.pack.as_str()
+        };
+        conn.execute(
+            "INSERT INTO inv_prices (item_id, vendor_id, price, pack, status, prev_price)
+             VALUES (?1, ?2, ?3, ?4, 1, NULL)",
+            rusqlite::params![bid.item_id, bid.vendor_id, price, pack],
+        )
+        .map_err(|e| e.to_string())?;
+    } else {
+        conn.execute(
+            "UPDATE inv_prices
+             SET prev_price = price, price = ?3, pack = COALESCE(NULLIF(?4, ''), pack), status = 1
+             WHERE item_id = ?1 AND vendor_id = ?2",
+            rusqlite::params![bid.item_id, bid.vendor_id, price, bid.pack],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT vendor_id FROM inv_prices WHERE item_id = ?1 AND vendor_id <> ?2 AND COALESCE(status, 1) = 1")
+        .map_err(|e| e.to_string())?;
+    let demoted: Vec<String> = stmt
+        .query_map((bid.item_id, bid.vendor_id), |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .map(|r| r.map(|v| v.to_string()))
+        .collect::<rusqlite::Result<_>>()
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE inv_prices SET status = 0 WHERE item_id = ?1 AND vendor_id <> ?2 AND COALESCE(status, 1) = 1",
+        (bid.item_id, bid.vendor_id),
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE vendor_bids
+         SET applied_at = datetime('now', 'localtime'), replaced_price = ?2, replaced_status = ?3,
+             replaced_pack = ?4, added_price_row = ?5, demoted_vendors = ?6
+         WHERE bid_id = ?1",
+        rusqlite::params![
+            bid.bid_id,
+            replaced_price,
+            replaced_status,
+            replaced_pack,
+            added,
+            demoted.join(",")
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// What `start_award` changed, as recorded on the bid.
+struct AppliedAward {
+    item_id: i64,
+    vendor_id: i64,
+    applied: bool,
+    added_price_row: bool,
+    replaced_price: Option<f64>,
+    replaced_status: Option<i64>,
+    replaced_pack: Option<String>,
+    demoted: String,
+}
+
+/// Closes an award with `status`. If the award had taken effect, the
+/// vendor's price list row goes back to its price, status and pack (or is
+/// removed when the award added it) and the vendors it set inactive are
+/// restored.
+fn end_award(conn: &Connection, bid_id: i64, status: &str) -> Result<(), String> {
+    let award = conn
+        .query_row(
+            "SELECT item_id, vendor_id, applied_at IS NOT NULL, COALESCE(added_price_row, 0), replaced_price,
+                    replaced_status, replaced_pack, COALESCE(demoted_vendors, '')
+             FROM vendor_bids WHERE bid_id = ?1",
+            [bid_id],
+            |row| {
+                Ok(AppliedAward {
+                    item_id: row.get(0)?,
+                    vendor_id: row.get(1)?,
+                    applied: row.get(2)?,
+                    added_price_row: row.get(3)?,
+                    replaced_price: row.get(4)?,
+                    replaced_status: row.get(5)?,
+                    replaced_pack: row.get(6)?,
+                    demoted: row.get(7)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let (item_id, vendor_id, demoted) = (award.item_id, award.vendor_id, award.demoted);
+    if award.applied {
+        if award.added_price_row {
+            conn.execute(
+                "DELETE FROM inv_prices WHERE item_id = ?1 AND vendor_id = ?2",
+                (item_id, vendor_id),
+            )
+            .map_err(|e| e.to_string())?;
+        } else {
+            conn.execute(
+                "UPDATE inv_prices SET prev_price = price, price = ?3, status = ?4, pack = ?5
+                 WHERE item_id = ?1 AND vendor_id = ?2",
+                rusqlite::params![
+                    item_id,
+                    vendor_id,
+                    award.replaced_price,
+                    award.replaced_status,
+                    award.replaced_pack
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for other in demoted.split(',').filter_map(|v| v.parse::<i64>().ok()) {
+            conn.execute(
+                "UPDATE inv_prices SET status = 1 WHERE item_id = ?1 AND vendor_id = ?2",
+                (item_id, other),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    conn.execute(
+        "UPDATE vendor_bids SET status = ?2, expired_at = datetime('now', 'localtime') WHERE bid_id = ?1",
+        rusqlite::params![bid_id, status],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Brings awards in line with today's date: awards whose window has started
+/// take effect and awards whose window has ended are rolled back. Returns
+/// how many awards changed.
+pub fn apply_awards(conn: &Connection) -> Result<usize, String> {
+    let today: String = conn
+        .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let ids = |sql: &str| -> Result<Vec<i64>, String> {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&today], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<rusqlite::Result<Vec<i64>>>()
+            .map_err(|e| e.to_string())?;
+        Ok(rows)
+    };
+    let ended = ids("SELECT bid_id FROM vendor_bids
+         WHERE status = 'awarded' AND COALESCE(valid_to, '') <> '' AND valid_to < ?1")?;
+    for &bid_id in &ended {
+        end_award(conn, bid_id, "expired")?;
+    }
+    let started = ids("SELECT bid_id FROM vendor_bids
+         WHERE status = 'awarded' AND applied_at IS NULL AND COALESCE(valid_from, '') <= ?1")?;
+    for &bid_id in &started {
+        start_award(conn, &load_bid(conn, bid_id)?)?;
+    }
+    Ok(ended.len() + started.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_awards, award_bid, import_legacy_bids, insert_bid, NewBid};
+    use crate::costing::item_price;
+    use rusqlite::Connection;
+
+    fn bid_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Fryer Oil');
+             INSERT INTO vendors (vendor_id, name) VALUES (5, 'Acme'), (7, 'Sysco'), (9, 'Restaurant Depot');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1);
+             INSERT INTO inv_prices (item_id, vendor_id, price, pack, status) VALUES (1, 5, 30.0, '1/35 LB', 1), (1, 7, 32.0, '1/35 LB', 2);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn bid(vendor_id: i64, price: f64, valid_to: &str) -> NewBid {
+        NewBid {
+            vendor_id,
+            item_id: 1,
+            price,
+            unit_id: Some(3),
+            pack: "1/30 LB".to_string(),
+            valid_from: Some("2020-01-01".to_string()),
+            valid_to: Some(valid_to.to_string()),
+        }
+    }
+
+    fn price_row(conn: &Connection, vendor_id: i64) -> Option<(f64, Option<i64>, String)> {
+        conn.query_row(
+            "SELECT price, status, pack FROM inv_prices WHERE item_id = 1 AND vendor_id = ?1",
+            [vendor_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn award_sets_the_item_price_until_it_expires() {
+        let conn = bid_db();
+        let bid_id = insert_bid(&conn, &bid(7, 29.0, "2099-12-31"), "manual").unwrap();
+        award_bid(&conn, bid_id).unwrap();
+        assert_eq!(item_price(&conn, 1), Some((29.0, 7)));
+        assert_eq!(price_row(&conn, 5).unwrap().1, Some(0));
+
+        conn.execute(
+            "UPDATE vendor_bids SET valid_to = '2021-01-01' WHERE bid_id = ?1",
+            [bid_id],
+        )
+        .unwrap();
+        assert_eq!(apply_awards(&conn).unwrap(), 1);
+        assert_eq!(
+            price_row(&conn, 7),
+            Some((32.0, Some(2), "1/35 LB".to_string()))
+        );
+        assert_eq!(
+            price_row(&conn, 5),
+            Some((30.0, Some(1), "1/35 LB".to_string()))
+        );
+        assert_eq!(item_price(&conn, 1), Some((30.0, 5)));
+    }
+
+    #[test]
+    fn awards_take_effect_again_after_a_reimport() {
+        let conn = bid_db();
+        let bid_id = insert_bid(&conn, &bid(7, 29.0, "2099-12-31"), "manual").unwrap();
+        award_bid(&conn, bid_id).unwrap();
+
+        crate::db::clear_tables(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Fryer Oil');
+             INSERT INTO vendors (vendor_id, name) VALUES (5, 'Acme'), (7, 'Sysco');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1);
+             INSERT INTO inv_prices (item_id, vendor_id, price, pack, status) VALUES (1, 5, 30.0, '1/35 LB', 1), (1, 7, 32.0, '1/35 LB', 2);",
+        )
+        .unwrap();
+        crate::db::restore_user_columns(&conn).unwrap();
+        crate::db::prune_orphans(&conn).unwrap();
+        assert_eq!(apply_awards(&conn).unwrap(), 1);
+        assert_eq!(item_price(&conn, 1), Some((29.0, 7)));
+
+        conn.execute(
+            "UPDATE vendor_bids SET valid_to = '2021-01-01' WHERE bid_id = ?1",
+            [bid_id],
+        )
+        .unwrap();
+        apply_awards(&conn).unwrap();
+        assert_eq!(
+            price_row(&conn, 7),
+            Some((32.0, Some(2), "1/35 LB".to_string()))
+        );
+    }
+
+    #[test]
+    fn ending_an_award_removes_the_price_row_it_added() {
+        let conn = bid_db();
+        let bid_id = insert_bid(&conn, &bid(9, 28.0, "2099-12-31"), "manual").unwrap();
+        award_bid(&conn, bid_id).unwrap();
+        assert_eq!(item_price(&conn, 1), Some((28.0, 9)));
+
+        let rival = insert_bid(&conn, &bid(7, 27.0, "2099-12-31"), "manual").unwrap();
+        award_bid(&conn, rival).unwrap();
+        assert_eq!(price_row(&conn, 9), None);
+        assert_eq!(item_price(&conn, 1), Some((27.0, 7)));
+    }
+
+    #[test]
+    fn headerless_bid_files_are_rejected() {
+        let conn = bid_db();
+        conn.execute(
+            "INSERT INTO bids (col1, col2, col3) VALUES ('7', '1', '29.00')",
+            [],
+        )
+        .unwrap();
+        assert!(import_legacy_bids(&conn).is_err());
+
+        conn.execute_batch(
+            "DELETE FROM bids;
+             INSERT INTO bids (col1, col2, col3) VALUES ('VendorID', 'ItemID', 'Price'), ('7', '1', '29.00');",
+        )
+        .unwrap();
+        assert_eq!(import_legacy_bids(&conn).unwrap().0, 1);
+    }
+}
@@ -0,0 +1,649 @@
+use crate::counts::{count_sheet, load_session};
+use crate::spend::category_spend;
+use crate::variance::day_after;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+
+/// Reporting categories for cost of goods sold. Paper and other have no
+/// revenue of their own and are measured against total revenue.
+pub const COGS_CATEGORIES: [&str; 4] = ["food", "beverage", "paper", "other"];
+
+pub struct Period {
+    pub period_id: i64,
+    pub name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub opening_session_id: Option<i64>,
+    pub closing_session_id: Option<i64>,
+    /// "open", or "closed" once its figures are frozen.
+    pub status: String,
+    pub notes: String,
+}
+
+pub struct CogsLine {
+    pub category: String,
+    pub opening: f64,
+    pub purchases: f64,
+    pub closing: f64,
+    pub cogs: f64,
+    pub revenue: Option<f64>,
+    /// COGS over the category's revenue, or over total revenue for
+    /// categories without their own.
+    pub cost_pct: Option<f64>,
+}
+
+pub struct CogsReport {
+    pub period: Period,
+    pub opening_session: Option<(i64, String)>,
+    pub closing_session: Option<(i64, String)>,
+    pub lines: Vec<CogsLine>,
+    pub total: CogsLine,
+    /// Food categories in the counts or purchases, with the COGS category
+    /// each is reported under.
+    pub mapping: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+}
+
+fn period_from_row(row: &rusqlite::Row) -> rusqlite::Result<Period> {
+    Ok(Period {
+        period_id: row.get(0)?,
+        name: row.get(1)?,
+        start_date: row.get(2)?,
+        end_date: row.get(3)?,
+        opening_session_id: row.get(4)?,
+        closing_session_id: row.get(5)?,
+        status: row.get(6)?,
+        notes: row.get(7)?,
+    })
+}
+
+const PERIOD_SELECT: &str =
+    "SELECT period_id, name, start_date, end_date, opening_session_id, closing_session_id,
+            COALESCE(status, 'open'), COALESCE(notes, '')
+     FROM accounting_periods";
+
+pub fn load_period(conn: &Connection, period_id: i64) -> Result<Period, String> {
+    conn.query_row(
+        &format!("{} WHERE period_id = ?1", PERIOD_SELECT),
+        [period_id],
+        period_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Period {} not found", period_id))
+}
+
+/// All periods, latest first.
+pub fn list_periods(conn: &Connection) -> Result<Vec<Period>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} ORDER BY start_date DESC, period_id DESC",
+            PERIOD_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], period_from_row)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Creates a period, or updates an open one when `period_id` is set.
+/// Dates must already be normalized. Returns the period id.
+pub fn save_period(conn: &Connection, period: &Period) -> Result<i64, String> {
+    if period.name.trim().is_empty() {
+        return Err("Give the period a name".to_string());
+    }
+    if period.end_date < period.start_date {
+        return Err("The period ends before it starts".to_string());
+    }
+    let overlap: Option<String> = conn
+        .query_row(
+            "SELECT name FROM accounting_periods
+             WHERE period_id <> ?1 AND start_date <= ?3 AND end_date >= ?2 LIMIT 1",
+            rusqlite::params![period.period_id, period.start_date, period.end_date],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(name) = overlap {
+        return Err(format!("The dates overlap period '{}'", name));
+    }
+    if period.period_id > 0 {
+        if load_period(conn, period.period_id)?.status == "closed" {
+            return Err("Reopen the period before changing it".to_string());
+        }
+        conn.execute(
+            "UPDATE accounting_periods
+             SET name = ?2, start_date = ?3, end_date = ?4, opening_session_id = ?5, closing_session_id = ?6, notes = ?7
+             WHERE period_id = ?1",
+            rusqlite::params![
+                period.period_id,
+                period.name.trim(),
+                period.start_date,
+                period.end_date,
+                period.opening_session_id,
+                period.closing_session_id,
+                period.notes
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(period.period_id);
+    }
+    conn.execute(
+        "INSERT INTO accounting_periods (name, start_date, end_date, opening_session_id, closing_session_id, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            period.name.trim(),
+            period.start_date,
+            period.end_date,
+            period.opening_session_id,
+            period.closing_session_id,
+            period.notes
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn delete_period(conn: &Connection, period_id: i64) -> Result<(), String> {
+    if load_period(conn, period_id)?.status == "closed" {
+        return Err("Reopen the period before deleting it".to_string());
+    }
+    for sql in [
+        "DELETE FROM period_revenue WHERE period_id = ?1",
+        "DELETE FROM period_cogs WHERE period_id = ?1",
+        "DELETE FROM accounting_periods WHERE period_id = ?1",
+    ] {
+        conn.execute(sql, [period_id]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The COGS category a food category reports under: the saved mapping,
+/// otherwise a guess from its name.
+fn cogs_category(food_category: &str, map: &HashMap<String, String>) -> String {
+    if let Some(c) = map.get(&food_category.to_lowercase()) {
+        return c.clone();
+    }
+    let name = food_category.to_lowercase();
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has = |prefixes: &[&str]| {
+        words
+            .iter()
+            .any(|w| prefixes.iter().any(|p| w.starts_with(p)))
+    };
+    if has(&[
+        "bev", "drink", "wine", "beer", "liquor", "spirit", "cocktail", "coffee", "tea", "soda",
+        "juice",
+    ]) {
+        "beverage"
+    } else if has(&[
+        "paper",
+        "disposable",
+        "packag",
+        "togo",
+        "takeout",
+        "chemical",
+        "cleaning",
+        "suppl",
+    ]) {
+        "paper"
+    } else {
+        "food"
+    }
+    .to_string()
+}
+
+fn category_map(conn: &Connection) -> Result<HashMap<String, String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT food_category, cogs_category FROM cogs_category_map")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?.to_lowercase(),
+                row.get::<_, String>(1)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+pub fn set_category_mapping(
+    conn: &Connection,
+    food_category: &str,
+    cogs_category: &str,
+) -> Result<(), String> {
+    if !COGS_CATEGORIES.contains(&cogs_category) {
+        return Err(format!("Unknown COGS category '{}'", cogs_category));
+    }
+    conn.execute(
+        "INSERT INTO cogs_category_map (food_category, cogs_category) VALUES (?1, ?2)
+         ON CONFLICT(food_category) DO UPDATE SET cogs_category = excluded.cogs_category",
+        [food_category, cogs_category],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sets (or with `None`, clears) a period's revenue for one category.
+pub fn set_revenue(
+    conn: &Connection,
+    period_id: i64,
+    category: &str,
+    amount: Option<f64>,
+    source: &str,
+) -> Result<(), String> {
+    if !COGS_CATEGORIES.contains(&category) {
+        return Err(format!("Unknown COGS category '{}'", category));
+    }
+    if load_period(conn, period_id)?.status == "closed" {
+        return Err("Reopen the period before changing its revenue".to_string());
+    }
+    match amount {
+        Some(amount) => conn.execute(
+            "INSERT INTO period_revenue (period_id, category, amount, source) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(period_id, category) DO UPDATE SET amount = excluded.amount, source = excluded.source",
+            rusqlite::params![period_id, category, amount, source],
+        ),
+        None => conn.execute(
+            "DELETE FROM period_revenue WHERE period_id = ?1 AND category = ?2",
+            rusqlite::params![period_id, category],
+        ),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Replaces the period's food and beverage revenue with imported POS net
+/// sales in its date range. POS categories are classified like food
+/// categories. Returns the total imported.
+pub fn import_pos_revenue(conn: &Connection, period_id: i64) -> Result<f64, String> {
+    let period = load_period(conn, period_id)?;
+    if period.status == "closed" {
+        return Err("Reopen the period before changing its revenue".to_string());
+    }
+    let map = category_map(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(p.category, ''), COALESCE(SUM(s.net_sales), 0)
+             FROM sales s LEFT JOIN pos_items p ON p.pos_item_id = s.pos_item_id
+             WHERE s.sale_date >= ?1 AND s.sale_date <= ?2 AND COALESCE(p.ignored, 0) = 0
+             GROUP BY COALESCE(p.category, '')",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&period.start_date, &period.end_date], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    for row in rows {
+        let (pos_category, amount) = row.map_err(|e| e.to_string())?;
+        let category = match cogs_category(&pos_category, &map).as_str() {
+            "beverage" => "beverage",
+            _ => "food",
+        };
+        *totals.entry(category.to_string()).or_insert(0.0) += amount;
+    }
+    if totals.is_empty() {
+        return Err(format!(
+            "No POS sales between {} and {}",
+            period.start_date, period.end_date
+        ));
+    }
+    for (category, amount) in &totals {
+        set_revenue(conn, period_id, category, Some(*amount), "pos")?;
+    }
+    Ok(totals.values().sum())
+}
+
+/// The count session used for a period boundary: the one chosen on the
+/// period, otherwise the latest closed count on or before `on_or_before`
+/// (and after `after`, when given).
+fn boundary_session(
+    conn: &Connection,
+    chosen: Option<i64>,
+    on_or_before: &str,
+    after: Option<&str>,
+) -> Result<Option<i64>, String> {
+    if chosen.is_some() {
+        return Ok(chosen);
+    }
+    conn.query_row(
+        "SELECT session_id FROM count_sessions
+         WHERE status = 'closed' AND count_date <= ?1 AND (?2 IS NULL OR count_date > ?2)
+         ORDER BY count_date DESC, session_id DESC LIMIT 1",
+        rusqlite::params![on_or_before, after],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Inventory value per food category for a count session.
+fn session_values(conn: &Connection, session_id: i64) -> Result<HashMap<String, f64>, String> {
+    let (_, lines) = count_sheet(conn, session_id)?;
+    let mut values = HashMap::new();
+    for line in lines {
+        if let Some(value) = line.value {
+            let category = if line.category.is_empty() {
+                "Uncategorized".to_string()
+            } else {
+                line.category
+            };
+            *values.entry(category).or_insert(0.0) += value;
+        }
+    }
+    Ok(values)
+}
+
+fn cost_line(
+    category: &str,
+    opening: f64,
+    purchases: f64,
+    closing: f64,
+    revenue: Option<f64>,
+    total_revenue: f64,
+) -> CogsLine {
+    let cogs = opening + purchases - closing;
+    let base = match revenue {
+        Some(r) if r > 0.0 => Some(r),
+        _ if !matches!(category, "food" | "beverage") && total_revenue > 0.0 => Some(total_revenue),
+        _ => None,
+    };
+    CogsLine {
+        category: category.to_string(),
+        opening,
+        purchases,
+        closing,
+        cogs,
+        revenue,
+        cost_pct: base.map(|b| cogs / b * 100.0),
+    }
+}
+
+fn revenue_by_category(conn: &Connection, period_id: i64) -> Result<HashMap<String, f64>, String> {
+    let mut stmt = conn
+        .prepare("SELECT category, amount FROM period_revenue WHERE period_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([period_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn session_label(
+    conn: &Connection,
+    session_id: Option<i64>,
+) -> Result<Option<(i64, String)>, String> {
+    match session_id {
+        Some(id) => {
+            let s = load_session(conn, id)?;
+            Ok(Some((id, format!("{} ({})", s.name, s.count_date))))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Opening inventory + purchases − closing inventory per COGS category, with
+/// food cost % against the period's revenue. Counts are taken as of close of
+/// business, so with both counts the purchases run from the day after the
+/// opening count through the closing count, with a warning when that isn't
+/// the period. Closed periods report the figures frozen when they were
+/// closed.
+pub fn cogs_report(conn: &Connection, period_id: i64) -> Result<CogsReport, String> {
+    let period = load_period(conn, period_id)?;
+    let mut revenue = revenue_by_category(conn, period_id)?;
+    let map = category_map(conn)?;
+    let mut warnings = Vec::new();
+
+    let opening_id = boundary_session(conn, period.opening_session_id, &period.start_date, None)?;
+    let opening_date = match opening_id {
+        Some(id) => Some(load_session(conn, id)?.count_date),
+        None => None,
+    };
+    let closing_id = boundary_session(
+        conn,
+        period.closing_session_id,
+        &period.end_date,
+        opening_date.as_deref().or(Some(period.start_date.as_str())),
+    )?;
+
+    let mut sums: BTreeMap<String, (f64, f64, f64)> = BTreeMap::new();
+    let mut mapping: BTreeMap<String, String> = BTreeMap::new();
+    if period.status == "closed" {
+        let mut stmt = conn
+            .prepare("SELECT category, opening, purchases, closing, revenue FROM period_cogs WHERE period_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([period_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, f64>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, Option<f64>>(4)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        revenue.clear();
+        for row in rows {
+            let (category, opening, purchases, closing, frozen_revenue) =
+                row.map_err(|e| e.to_string())?;
+            if let Some(r) = frozen_revenue {
+                revenue.insert(category.clone(), r);
+            }
+            sums.insert(category, (opening, purchases, closing));
+        }
+    } else {
+        let opening = match opening_id {
+            Some(id) => session_values(conn, id)?,
+            None => {
+                warnings.push(format!(
+                    "No closed count on or before {} for opening inventory",
+                    period.start_date
+                ));
+                HashMap::new()
+            }
+        };
+        let closing = match closing_id {
+            Some(id) => session_values(conn, id)?,
+            None => {
+                warnings.push(format!(
+                    "No closed count by {} for closing inventory",
+                    period.end_date
+                ));
+                HashMap::new()
+            }
+        };
+        let closing_date = match closing_id {
+            Some(id) => Some(load_session(conn, id)?.count_date),
+            None => None,
+        };
+        let (purchases_from, purchases_to) = match (&opening_date, closing_date) {
+            (Some(opened), Some(closed)) => (day_after(conn, opened)?, closed),
+            _ => (period.start_date.clone(), period.end_date.clone()),
+        };
+        if (purchases_from.as_str(), purchases_to.as_str())
+            != (period.start_date.as_str(), period.end_date.as_str())
+        {
+            warnings.push(format!(
+                "Purchases are counted from {} to {} to match the opening and closing counts, not the period's {} to {}",
+                purchases_from, purchases_to, period.start_date, period.end_date
+            ));
+        }
+        let purchases = category_spend(conn, &purchases_from, &purchases_to)?;
+        for (values, slot) in [(&opening, 0), (&purchases, 1), (&closing, 2)] {
+            for (food_category, value) in values {
+                let category = cogs_category(food_category, &map);
+                mapping.insert(food_category.clone(), category.clone());
+                let entry = sums.entry(category).or_insert((0.0, 0.0, 0.0));
+                match slot {
+                    0 => entry.0 += value,
+                    1 => entry.1 += value,
+                    _ => entry.2 += value,
+                }
+            }
+        }
+        for id in [opening_id, closing_id].into_iter().flatten() {
+            let s = load_session(conn, id)?;
+            if s.status != "closed" {
+                warnings.push(format!(
+                    "Count '{}' is still open; its values may change",
+                    s.name
+                ));
+            }
+        }
+    }
+    let total_revenue: f64 = revenue.values().sum();
+    if revenue.is_empty() {
+        warnings.push("Enter or import revenue to see food cost %".to_string());
+    }
+
+    let lines: Vec<CogsLine> = COGS_CATEGORIES
+        .iter()
+        .filter(|c| sums.contains_key(**c) || revenue.contains_key(**c))
+        .map(|c| {
+            let (opening, purchases, closing) = sums.get(*c).copied().unwrap_or((0.0, 0.0, 0.0));
+            cost_line(
+                c,
+                opening,
+                purchases,
+                closing,
+                revenue.get(*c).copied(),
+                total_revenue,
+            )
+        })
+        .collect();
+    let total = cost_line(
+        "total",
+        lines.iter().map(|l| l.opening).sum(),
+        lines.iter().map(|l| l.purchases).sum(),
+        lines.iter().map(|l| l.closing).sum(),
+        (total_revenue > 0.0).then_some(total_revenue),
+        total_revenue,
+    );
+
+    Ok(CogsReport {
+        opening_session: session_label(conn, opening_id)?,
+        closing_session: session_label(conn, closing_id)?,
+        period,
+        lines,
+        total,
+        mapping: mapping.into_iter().collect(),
+        warnings,
+    })
+}
+
+/// Freezes a period's COGS figures and the count sessions they came from.
+pub fn close_period(conn: &Connection, period_id: i64) -> Result<CogsReport, String> {
+    let report = cogs_report(conn, period_id)?;
+    if report.period.status == "closed" {
+        return Err("The period is already closed".to_string());
+    }
+    let (Some((opening_id, _)), Some((closing_id, _))) =
+        (&report.opening_session, &report.closing_session)
+    else {
+        return Err("Closing a period needs both an opening and a closing count".to_string());
+    };
+    conn.execute("DELETE FROM period_cogs WHERE period_id = ?1", [period_id])
+        .map_err(|e| e.to_string())?;
+    for line in &report.lines {
+        conn.execute(
+            "INSERT INTO period_cogs (period_id, category, opening, purchases, closing, revenue)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                period_id,
+                line.category,
+                line.opening,
+                line.purchases,
+                line.closing,
+                line.revenue
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    conn.execute(
+        "UPDATE accounting_periods
+         SET status = 'closed', closed_at = datetime('now', 'localtime'), opening_session_id = ?2, closing_session_id = ?3
+         WHERE period_id = ?1",
+        rusqlite::params![period_id, opening_id, closing_id],
+    )
+    .map_err(|e| e.to_string())?;
+    cogs_report(conn, period_id)
+}
+
+pub fn reopen_period(conn: &Connection, period_id: i64) -> Result<(), String> {
+    load_period(conn, period_id)?;
+    conn.execute("DELETE FROM period_cogs WHERE period_id = ?1", [period_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE accounting_periods SET status = 'open', closed_at = NULL WHERE period_id = ?1",
+        [period_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cogs_report;
+    use crate::counts::{close_session, save_count_line};
+    use rusqlite::Connection;
+
+    #[test]
+    fn counts_bound_the_purchases_window() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name, food_category) VALUES (1, 'Fryer Oil', 'Dry Goods');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1);
+             INSERT INTO inv_prices (item_id, vendor_id, price) VALUES (1, 7, 10.0);
+             INSERT INTO count_sessions (session_id, name, count_date, status)
+             VALUES (1, 'Feb 27', '2026-02-27', 'open'), (2, 'Feb 28', '2026-02-28', 'open'),
+                    (3, 'Mar 30', '2026-03-30', 'open'), (4, 'Apr 2', '2026-04-02', 'open');
+             INSERT INTO invoices (invoice_id, invoice_date, vendor_id)
+             VALUES (1, '2026-02-28', 7), (2, '2026-03-01', 7), (3, '2026-03-30', 7), (4, '2026-03-31', 7);
+             INSERT INTO trans (trans_id, invoice_id, item_id, ext_cost) VALUES (1, 1, 1, 100), (2, 2, 1, 40), (3, 3, 1, 5), (4, 4, 1, 7);
+             INSERT INTO accounting_periods (period_id, name, start_date, end_date) VALUES (1, 'March', '2026-03-01', '2026-03-31');",
+        )
+        .unwrap();
+        for (session_id, cases) in [(1, 2.0), (2, 3.0), (3, 1.0), (4, 6.0)] {
+            save_count_line(&conn, session_id, 1, Some(cases), Some(3)).unwrap();
+            close_session(&conn, session_id).unwrap();
+        }
+
+        let report = cogs_report(&conn, 1).unwrap();
+        assert_eq!(report.opening_session.unwrap().0, 2);
+        assert_eq!(report.closing_session.unwrap().0, 3);
+        let food = &report.lines[0];
+        assert_eq!(
+            (
+                food.category.as_str(),
+                food.opening,
+                food.purchases,
+                food.closing
+            ),
+            ("food", 30.0, 45.0, 10.0)
+        );
+        assert_eq!(food.cogs, 65.0);
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.contains("from 2026-03-01 to 2026-03-30")),
+            "{:?}",
+            report.warnings
+        );
+    }
+}
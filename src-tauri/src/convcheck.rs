@@ -0,0 +1,381 @@
+use crate::costing::{
+    best_conversion_path, build_conversion_edges, format_qty, path_summary, ConvEdge,
+};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeSet, HashMap};
+
+/// Disagreement allowed between two paths before they count as a conflict.
+pub const DEFAULT_TOLERANCE_PCT: f64 = 2.0;
+
+/// Conversions claiming more than this many of one unit per unit of another
+/// (either way round) are flagged as suspicious.
+const MAX_PLAUSIBLE_RATIO: f64 = 10_000.0;
+
+pub const FIXES: [&str; 3] = ["delete", "align", "swap"];
+
+/// A `convunit` row that is broken or disagrees with the rest of its item's
+/// conversions.
+pub struct ConversionIssue {
+    /// invalid, self_loop, duplicate, conflict or magnitude.
+    pub kind: String,
+    pub row_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    pub unit1_name: String,
+    pub unit2_name: String,
+    pub qty1: Option<f64>,
+    pub qty2: Option<f64>,
+    pub detail: String,
+    /// For conflicts: how many times larger the row's factor is than the
+    /// other path's.
+    pub off_by: Option<f64>,
+    /// For conflicts: the `qty2` that would agree with the other path.
+    pub suggested_qty2: Option<f64>,
+    /// Fixes that apply, from `FIXES`.
+    pub fixes: Vec<&'static str>,
+    /// Recipes that use the item directly.
+    pub recipe_count: i64,
+}
+
+pub struct ConversionCheck {
+    pub tolerance_pct: f64,
+    pub items_checked: usize,
+    pub rows_checked: usize,
+    pub issues: Vec<ConversionIssue>,
+}
+
+struct ConvRow {
+    row_id: i64,
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: Option<i64>,
+    unit_id2: Option<i64>,
+    qty1: Option<f64>,
+    qty2: Option<f64>,
+}
+
+/// Reads `rowid, item_id, vendor_id, unit_id1, unit_id2, qty1, qty2`.
+fn read_row(row: &rusqlite::Row) -> rusqlite::Result<ConvRow> {
+    Ok(ConvRow {
+        row_id: row.get(0)?,
+        item_id: row.get(1)?,
+        vendor_id: row.get(2)?,
+        unit_id1: row.get(3)?,
+        unit_id2: row.get(4)?,
+        qty1: row.get(5)?,
+        qty2: row.get(6)?,
+    })
+}
+
+fn load_rows(conn: &Connection) -> Result<Vec<ConvRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT rowid, item_id, COALESCE(vendor_id, 0), unit_id1, unit_id2, qty1, qty2
+             FROM convunit WHERE item_id IS NOT NULL ORDER BY item_id, rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], read_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn lookup_name(conn: &Connection, sql: &str, id: Option<i64>) -> String {
+    id.and_then(|id| {
+        conn.query_row(sql, [id], |row| row.get::<_, Option<String>>(0))
+            .ok()
+            .flatten()
+    })
+    .unwrap_or_else(|| "-".to_string())
+}
+
+/// The row's factor (units of `unit_id2` per `unit_id1`), when it has one.
+fn row_factor(row: &ConvRow) -> Option<f64> {
+    match (row.qty1, row.qty2) {
+        (Some(q1), Some(q2)) if q1 > 0.0 && q2 > 0.0 => Some(q2 / q1),
+        _ => None,
+    }
+}
+
+fn within(a: f64, b: f64, tolerance: f64) -> bool {
+    (a / b - 1.0).abs() <= tolerance && (b / a - 1.0).abs() <= tolerance
+}
+
+fn path_text(conn: &Connection, edges: &[ConvEdge], from: i64, to: i64) -> Option<(f64, String)> {
+    let path = best_conversion_path(edges, from, to, 6)?;
+    Some((path.factor, path_summary(conn, &path)))
+}
+
+/// Checks every item's conversions, each vendor's together with the
+/// vendor-neutral ones, plus the standard and density conversions they
+/// connect to. Finds rows with missing, zero or negative quantities,
+/// self-loops, exact duplicates, rows that disagree by more than
+/// `tolerance_pct` with another path between the same units, and rows with
+/// implausible magnitudes. Issues are ranked by how many recipes use the item.
+pub fn check_conversions(conn: &Connection, tolerance_pct: f64) -> Result<ConversionCheck, String> {
+    if !(tolerance_pct > 0.0 && tolerance_pct < 100.0) {
+        return Err("Tolerance must be between 0 and 100%".to_string());
+    }
+    let tolerance = tolerance_pct / 100.0;
+    let rows = load_rows(conn)?;
+    let mut issues = Vec::new();
+    let mut items = BTreeSet::new();
+    let mut edges_by_group: HashMap<(i64, i64), Vec<ConvEdge>> = HashMap::new();
+    // Rows seen so far per item, vendor and unit pair, with the factor
+    // from the lower unit id to the higher.
+    let mut seen_pairs: HashMap<(i64, i64, i64, i64), Vec<f64>> = HashMap::new();
+
+    for row in &rows {
+        items.insert(row.item_id);
+        let unit1_name = lookup_name(
+            conn,
+            "SELECT sing FROM units WHERE unit_id = ?1",
+            row.unit_id1,
+        );
+        let unit2_name = lookup_name(
+            conn,
+            "SELECT sing FROM units WHERE unit_id = ?1",
+            row.unit_id2,
+        );
+        let issue = |kind: &str, detail: String, fixes: Vec<&'static str>| ConversionIssue {
+            kind: kind.to_string(),
+            row_id: row.row_id,
+            item_id: row.item_id,
+            item_name: String::new(),
+            vendor_id: row.vendor_id,
+            vendor_name: String::new(),
+            unit1_name: unit1_name.clone(),
+            unit2_name: unit2_name.clone(),
+            qty1: row.qty1,
+            qty2: row.qty2,
+            detail,
+            off_by: None,
+            suggested_qty2: None,
+            fixes,
+            recipe_count: 0,
+        };
+
+        let (Some(unit_id1), Some(unit_id2), Some(factor)) =
+            (row.unit_id1, row.unit_id2, row_factor(row))
+        else {
+            issues.push(issue(
+                "invalid",
+                "Missing unit, or a quantity that is missing, zero or negative".to_string(),
+                vec!["delete"],
+            ));
+            continue;
+        };
+        if unit_id1 == unit_id2 {
+            let detail = if (factor - 1.0).abs() <= tolerance {
+                "Converts a unit to itself".to_string()
+            } else {
+                format!("Converts a unit to itself at {} : 1", format_qty(factor))
+            };
+            issues.push(issue("self_loop", detail, vec!["delete"]));
+            continue;
+        }
+
+        let key = (
+            row.item_id,
+            row.vendor_id,
+            unit_id1.min(unit_id2),
+            unit_id1.max(unit_id2),
+        );
+        let normalized = if unit_id1 < unit_id2 {
+            factor
+        } else {
+            1.0 / factor
+        };
+        let earlier = seen_pairs.entry(key).or_default();
+        let duplicate = earlier.iter().any(|f| within(*f, normalized, tolerance));
+        earlier.push(normalized);
+        if duplicate {
+            issues.push(issue(
+                "duplicate",
+                "Repeats an earlier conversion for the same units".to_string(),
+                vec!["delete"],
+            ));
+            continue;
+        }
+
+        if let std::collections::hash_map::Entry::Vacant(slot) =
+            edges_by_group.entry((row.item_id, row.vendor_id))
+        {
+            slot.insert(build_conversion_edges(
+                conn,
+                row.item_id,
+                Some(row.vendor_id),
+            )?);
+        }
+        let others: Vec<ConvEdge> = edges_by_group[&(row.item_id, row.vendor_id)]
+            .iter()
+            .filter(|e| e.row_id != Some(row.row_id))
+            .cloned()
+            .collect();
+        if let Some((path_factor, text)) = path_text(conn, &others, unit_id1, unit_id2) {
+            if !within(factor, path_factor, tolerance) {
+                let swaps = within(1.0 / factor, path_factor, tolerance);
+                let swapped = if swaps {
+                    " Swapping the quantities would agree."
+                } else {
+                    ""
+                };
+                let fixes = if swaps {
+                    vec!["swap", "align", "delete"]
+                } else {
+                    vec!["align", "delete"]
+                };
+                let mut conflict = issue(
+                    "conflict",
+                    format!(
+                        "Off by {}× from {}.{}",
+                        format_qty(factor.max(path_factor) / factor.min(path_factor)),
+                        text,
+                        swapped
+                    ),
+                    fixes,
+                );
+                conflict.off_by = Some(factor / path_factor);
+                conflict.suggested_qty2 = row.qty1.map(|q1| q1 * path_factor);
+                issues.push(conflict);
+                continue;
+            }
+        }
+
+        if !(1.0 / MAX_PLAUSIBLE_RATIO..=MAX_PLAUSIBLE_RATIO).contains(&factor) {
+            issues.push(issue(
+                "magnitude",
+                format!(
+                    "1 {} = {} {} is implausibly large or small",
+                    unit1_name,
+                    format_qty(factor),
+                    unit2_name
+                ),
+                vec!["delete"],
+            ));
+        }
+    }
+
+    let mut recipe_counts: HashMap<i64, i64> = HashMap::new();
+    for issue in &mut issues {
+        if let std::collections::hash_map::Entry::Vacant(slot) = recipe_counts.entry(issue.item_id)
+        {
+            slot.insert(
+                conn.query_row(
+                    "SELECT COUNT(DISTINCT recipe_id) FROM recp_items WHERE item_id = ?1",
+                    [issue.item_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?,
+            );
+        }
+        issue.recipe_count = recipe_counts[&issue.item_id];
+        issue.item_name = lookup_name(
+            conn,
+            "SELECT name FROM items WHERE item_id = ?1",
+            Some(issue.item_id),
+        );
+        issue.vendor_name = if issue.vendor_id == 0 {
+            "All vendors".to_string()
+        } else {
+            lookup_name(
+                conn,
+                "SELECT name FROM vendors WHERE vendor_id = ?1",
+                Some(issue.vendor_id),
+            )
+        };
+    }
+    let severity = |kind: &str| match kind {
+        "conflict" => 0,
+        "invalid" => 1,
+        "magnitude" => 2,
+        "self_loop" => 3,
+        _ => 4,
+    };
+    issues.sort_by(|a, b| {
+        b.recipe_count
+            .cmp(&a.recipe_count)
+            .then_with(|| severity(&a.kind).cmp(&severity(&b.kind)))
+            .then_with(|| a.item_id.cmp(&b.item_id))
+            .then_with(|| a.row_id.cmp(&b.row_id))
+    });
+
+    Ok(ConversionCheck {
+        tolerance_pct,
+        items_checked: items.len(),
+        rows_checked: rows.len(),
+        issues,
+    })
+}
+
+/// Applies a fix to one `convunit` row: `delete` removes it, `swap`
+/// exchanges its quantities, and `align` sets `qty2` so the row agrees with
+/// the item's other conversions (marking it calculated).
+pub fn fix_conversion(conn: &Connection, row_id: i64, fix: &str) -> Result<String, String> {
+    if !FIXES.contains(&fix) {
+        return Err(format!("Unknown fix '{}'", fix));
+    }
+    let row = conn
+        .query_row(
+            "SELECT rowid, item_id, COALESCE(vendor_id, 0), unit_id1, unit_id2, qty1, qty2 FROM convunit WHERE rowid = ?1",
+            [row_id],
+            read_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(ConvRow {
+        item_id,
+        vendor_id,
+        unit_id1,
+        unit_id2,
+        qty1,
+        ..
+    }) = row
+    else {
+        return Err("Conversion no longer exists; run the check again".to_string());
+    };
+    match fix {
+        "delete" => {
+            conn.execute("DELETE FROM convunit WHERE rowid = ?1", [row_id])
+                .map_err(|e| e.to_string())?;
+            Ok("Deleted conversion".to_string())
+        }
+        "swap" => {
+            conn.execute(
+                "UPDATE convunit SET qty1 = qty2, qty2 = qty1 WHERE rowid = ?1",
+                [row_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok("Swapped quantities".to_string())
+        }
+        _ => {
+            let (Some(unit_id1), Some(unit_id2), Some(qty1)) =
+                (unit_id1, unit_id2, qty1.filter(|q| *q > 0.0))
+            else {
+                return Err("Conversion has no valid units or quantity to align".to_string());
+            };
+            let others: Vec<ConvEdge> = build_conversion_edges(conn, item_id, Some(vendor_id))?
+                .into_iter()
+                .filter(|e| e.row_id != Some(row_id))
+                .collect();
+            let Some(path) = best_conversion_path(&others, unit_id1, unit_id2, 6) else {
+                return Err("No other path between these units to align with".to_string());
+            };
+            let qty2 = qty1 * path.factor;
+            conn.execute(
+                "UPDATE convunit SET qty2 = ?2, is_calculated = 1 WHERE rowid = ?1",
+                rusqlite::params![row_id, qty2],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "Set to {} : {}",
+                format_qty(qty1),
+                format_qty(qty2)
+            ))
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::costing::{cost_recipe, CostContext};
+use crate::suggest;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashSet};
+
+/// Most suggestions listed for review at once.
+pub const MAX_REVIEW_ROWS: i64 = 500;
+
+/// Narrows the suggestions under review; `None` leaves that part open.
+pub struct ReviewFilter {
+    /// 0 to 1.
+    pub min_confidence: Option<f64>,
+    pub max_hops: Option<i64>,
+    /// Item food category, matched without case.
+    pub category: Option<String>,
+}
+
+/// One suggestion. A pair can have a suggestion from each source.
+pub struct SuggestionKey {
+    pub item_id: i64,
+    pub vendor_id: i64,
+    pub unit_id1: i64,
+    pub unit_id2: i64,
+    pub derived_from: String,
+}
+
+pub struct ReviewRow {
+    pub item_id: i64,
+    pub vendor_id: i64,
+    pub unit_id1: i64,
+    pub unit_id2: i64,
+    pub item_name: String,
+    pub category: String,
+    pub recipe_unit: String,
+    pub purch_unit: String,
+    pub qty1: f64,
+    pub qty2: f64,
+    pub hits: i64,
+    pub derived_from: String,
+    pub hops: i64,
+    pub path: String,
+    pub confidence: Option<f64>,
+}
+
+pub struct ReviewList {
+    pub rows: Vec<ReviewRow>,
+    /// Matching suggestions, including any past `MAX_REVIEW_ROWS`.
+    pub total: i64,
+}
+
+/// A recipe whose cost or completeness changes when suggestions are
+/// accepted.
+pub struct RecipeChange {
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub old_cost: f64,
+    pub new_cost: f64,
+    pub old_missing: i64,
+    pub new_missing: i64,
+    /// Every line has a cost now and didn't before.
+    pub now_complete: bool,
+}
+
+pub struct AcceptanceImpact {
+    pub accepted: usize,
+    /// Selected suggestions that weren't applied, with the reason.
+    pub skipped: Vec<String>,
+    pub recipes: Vec<RecipeChange>,
+    pub complete_before: usize,
+    pub complete_after: usize,
+    pub newly_complete: usize,
+}
+
+/// Suggestions matching the filter, most confident first.
+pub fn list_for_review(conn: &Connection, filter: &ReviewFilter) -> Result<ReviewList, String> {
+    let category = filter
+        .category
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    let from = "FROM conv_suggestions s
+                LEFT JOIN items i ON i.item_id = s.item_id
+                WHERE (?1 IS NULL OR COALESCE(s.confidence, 0) >= ?1)
+                  AND (?2 IS NULL OR COALESCE(s.hops, 0) <= ?2)
+                  AND (?3 IS NULL OR COALESCE(i.food_category, '') = ?3 COLLATE NOCASE)";
+    let params = rusqlite::params![filter.min_confidence, filter.max_hops, category];
+    let total: i64 = conn
+        .query_row(&format!("SELECT COUNT(*) {}", from), params, |row| {
+            row.get(0)
+        })
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT s.item_id, s.vendor_id, s.unit_id1, s.unit_id2, COALESCE(i.name, '-'), COALESCE(i.food_category, ''),
+                    COALESCE(s.recipe_unit, '-'), COALESCE(s.purch_unit, '-'), s.qty1, s.qty2, COALESCE(s.hits, 0),
+                    COALESCE(s.derived_from, ''), COALESCE(s.hops, 0), COALESCE(s.path, ''), s.confidence
+             {}
+             ORDER BY COALESCE(s.confidence, 0) DESC, COALESCE(s.hits, 0) DESC, i.name
+             LIMIT {}",
+            from, MAX_REVIEW_ROWS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok(ReviewRow {
+                item_id: row.get(0)?,
+                vendor_id: row.get(1)?,
+                unit_id1: row.get(2)?,
+                unit_id2: row.get(3)?,
+                item_name: row.get(4)?,
+                category: row.get(5)?,
+                recipe_unit: row.get(6)?,
+                purch_unit: row.get(7)?,
+                qty1: row.get(8)?,
+                qty2: row.get(9)?,
+                hits: row.get(10)?,
+                derived_from: row.get(11)?,
+                hops: row.get(12)?,
+                path: row.get(13)?,
+                confidence: row.get(14)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ReviewList { rows, total })
+}
+
+/// The suggested quantities for a key, if it's still suggested.
+fn suggested_qty(conn: &Connection, key: &SuggestionKey) -> Result<Option<(f64, f64)>, String> {
+    conn.query_row(
+        "SELECT qty1, qty2 FROM conv_suggestions
+         WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id1 = ?3 AND unit_id2 = ?4 AND derived_from = ?5
+           AND qty1 > 0 AND qty2 > 0",
+        rusqlite::params![key.item_id, key.vendor_id, key.unit_id1, key.unit_id2, key.derived_from],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// (name, total cost, lines without a cost) for every recipe with lines.
+fn recipe_states(conn: &Connection) -> Result<BTreeMap<i64, (String, f64, i64)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT recipe_id, COALESCE(name, '') FROM recipes ORDER BY recipe_id")
+        .map_err(|e| e.to_string())?;
+    let recipes: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut ctx = CostContext::default();
+    let mut states = BTreeMap::new();
+    for (recipe_id, name) in recipes {
+        let costed = cost_recipe(conn, recipe_id, &mut ctx)?;
+        if !costed.lines.is_empty() {
+            states.insert(recipe_id, (name, costed.total_cost, costed.missing_costs));
+        }
+    }
+    Ok(states)
+}
+
+/// Adds the selected suggestions as conversions and compares recipe costs
+/// before and after. Only the first selected suggestion for a pair is used.
+fn apply(conn: &Connection, keys: &[SuggestionKey]) -> Result<AcceptanceImpact, String> {
+    let before = recipe_states(conn)?;
+    let mut impact = AcceptanceImpact {
+        accepted: 0,
+        skipped: Vec::new(),
+        recipes: Vec::new(),
+        complete_before: before.values().filter(|s| s.2 == 0).count(),
+        complete_after: 0,
+        newly_complete: 0,
+    };
+    let mut pairs = HashSet::new();
+    for key in keys {
+        let label = format!("item {} ({})", key.item_id, key.derived_from);
+        let Some((qty1, qty2)) = suggested_qty(conn, key)? else {
+            impact
+                .skipped
+                .push(format!("{}: no longer suggested", label));
+            continue;
+        };
+        if !pairs.insert((key.item_id, key.vendor_id, key.unit_id1, key.unit_id2)) {
+            impact.skipped.push(format!(
+                "{}: another suggestion for the same units was selected",
+                label
+            ));
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, is_calculated)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, 1)",
+            rusqlite::params![key.item_id, key.vendor_id, key.unit_id1, key.unit_id2, qty1, qty2],
+        )
+        .map_err(|e| e.to_string())?;
+        impact.accepted += 1;
+    }
+
+    let after = recipe_states(conn)?;
+    impact.complete_after = after.values().filter(|s| s.2 == 0).count();
+    for (recipe_id, (name, new_cost, new_missing)) in after {
+        let Some((_, old_cost, old_missing)) = before.get(&recipe_id) else {
+            continue;
+        };
+        if (new_cost - old_cost).abs() < 0.005 && new_missing == *old_missing {
+            continue;
+        }
+        let now_complete = new_missing == 0 && *old_missing > 0;
+        if now_complete {
+            impact.newly_complete += 1;
+        }
+        impact.recipes.push(RecipeChange {
+            recipe_id,
+            recipe_name: name,
+            old_cost: *old_cost,
+            new_cost,
+            old_missing: *old_missing,
+            new_missing,
+            now_complete,
+        });
+    }
+    impact.recipes.sort_by(|a, b| {
+        b.now_complete
+            .cmp(&a.now_complete)
+            .then(a.recipe_name.cmp(&b.recipe_name))
+    });
+    Ok(impact)
+}
+
+/// What accepting the suggestions would do, without keeping any changes.
+pub fn preview_acceptance(
+    conn: &Connection,
+    keys: &[SuggestionKey],
+) -> Result<AcceptanceImpact, String> {
+    conn.execute_batch("SAVEPOINT review_preview")
+        .map_err(|e| e.to_string())?;
+    let impact = apply(conn, keys);
+    conn.execute_batch("ROLLBACK TO review_preview; RELEASE review_preview")
+        .map_err(|e| e.to_string())?;
+    impact
+}
+
+/// Saves the suggestions as conversions and rebuilds the suggestion
+/// tables. Run inside a transaction so a failure keeps none of them.
+pub fn accept_suggestions(
+    conn: &Connection,
+    keys: &[SuggestionKey],
+) -> Result<AcceptanceImpact, String> {
+    let impact = apply(conn, keys)?;
+    suggest::regenerate(conn)?;
+    Ok(impact)
+}
+
+/// Remembers the suggestions as rejected so they aren't suggested again and
+/// rebuilds the suggestion tables. Returns how many were rejected.
+pub fn reject_suggestions(conn: &Connection, keys: &[SuggestionKey]) -> Result<usize, String> {
+    let mut rejected = 0;
+    for key in keys {
+        let Some((qty1, qty2)) = suggested_qty(conn, key)? else {
+            continue;
+        };
+        conn.execute(
+            "INSERT INTO conv_suggestion_rejects (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, derived_from, rejected_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now', 'localtime'))",
+            rusqlite::params![key.item_id, key.vendor_id, key.unit_id1, key.unit_id2, qty1, qty2, key.derived_from],
+        )
+        .map_err(|e| e.to_string())?;
+        rejected += 1;
+    }
+    suggest::regenerate(conn)?;
+    Ok(rejected)
+}
@@ -0,0 +1,1010 @@
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+
+/// Sub-recipes nested deeper than this are reported instead of costed.
+pub const MAX_RECIPE_DEPTH: usize = 8;
+
+/// Cost breakdown for a single ingredient line. `ap_cost` is the
+/// as-purchased cost of the quantity; `extended_cost` is the edible-portion
+/// cost after dividing by the usable yield.
+pub struct LineCost {
+    pub purch_unit_id: Option<i64>,
+    pub purch_unit_name: String,
+    pub price: Option<f64>,
+    pub yield_pct: Option<f64>,
+    pub ap_cost: Option<f64>,
+    pub extended_cost: Option<f64>,
+    pub cost_status: String,
+    /// How the line's unit converts to the purchase (or yield) unit, when
+    /// it takes at least one hop.
+    pub conversion: Option<ConvPath>,
+}
+
+/// A costed recipe line, either an inventory item or a sub-recipe.
+pub struct RecipeCostLine {
+    pub recp_item_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    pub qty: Option<f64>,
+    pub sub_recipe_id: Option<i64>,
+    pub prep_id: Option<i64>,
+    pub prep_name: String,
+    pub cost: LineCost,
+}
+
+pub struct RecipeCost {
+    pub lines: Vec<RecipeCostLine>,
+    pub total_cost: f64,
+    pub missing_costs: i64,
+    /// Names of every inventory item reached, including through sub-recipes.
+    pub leaf_names: Vec<String>,
+}
+
+pub struct RecipeYield {
+    pub qty: Option<f64>,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    /// Inventory item the recipe produces (from `recp_inv`), if any.
+    pub item_id: Option<i64>,
+}
+
+struct SubRecipeTotals {
+    total_cost: f64,
+    missing_costs: i64,
+    leaf_names: Vec<String>,
+}
+
+/// Tracks the recipes currently being costed (for cycle detection) and
+/// memoizes sub-recipe totals across a single costing pass.
+#[derive(Default)]
+pub struct CostContext {
+    stack: Vec<i64>,
+    totals: HashMap<i64, SubRecipeTotals>,
+    /// What-if multipliers applied to item prices, keyed by item_id.
+    price_factors: HashMap<i64, f64>,
+}
+
+impl CostContext {
+    /// A context that costs every item in `factors` at its current price
+    /// times the given multiplier.
+    pub fn with_price_factors(factors: HashMap<i64, f64>) -> Self {
+        CostContext {
+            price_factors: factors,
+            ..Default::default()
+        }
+    }
+}
+
+/// Where a conversion edge comes from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EdgeSource {
+    /// Entered for the item without a vendor.
+    Manual,
+    /// Entered for a specific vendor's pack.
+    Vendor,
+    /// Flagged `is_calculated` in `convunit`: derived rather than checked.
+    Calculated,
+    /// A built-in weight or volume conversion.
+    Standard,
+    /// The item's volume-to-weight density.
+    Density,
+    /// Read from a vendor's pack description.
+    Pack,
+}
+
+impl EdgeSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            EdgeSource::Manual => "manual",
+            EdgeSource::Vendor => "vendor",
+            EdgeSource::Calculated => "calculated",
+            EdgeSource::Standard => "standard",
+            EdgeSource::Density => "density",
+            EdgeSource::Pack => "pack",
+        }
+    }
+}
+
+/// One conversion between two units of an item: `qty1` of `unit_id1`
+/// equals `qty2` of `unit_id2`.
+#[derive(Clone, Debug)]
+pub struct ConvEdge {
+    pub unit_id1: i64,
+    pub unit_id2: i64,
+    pub qty1: f64,
+    pub qty2: f64,
+    pub source: EdgeSource,
+    pub vendor_id: Option<i64>,
+    /// The `convunit` rowid the edge was read from.
+    pub row_id: Option<i64>,
+    /// What a density or pack edge is based on, e.g. "density 0.530 g/ml,
+    /// item" or "pack 4/5 LB".
+    pub note: Option<String>,
+    /// Cost of crossing the edge; path search takes the cheapest path.
+    pub weight: f64,
+}
+
+/// One step of a resolved conversion path.
+#[derive(Clone, Debug)]
+pub struct ConvHop {
+    pub from_unit: i64,
+    pub to_unit: i64,
+    /// Units of `to_unit` per unit of `from_unit`.
+    pub factor: f64,
+    pub source: EdgeSource,
+    pub vendor_id: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ConvPath {
+    pub factor: f64,
+    pub hops: Vec<ConvHop>,
+}
+
+impl ConvPath {
+    /// Notes of the edges the path relied on, such as a density, each once
+    /// (a pack gives two hops the same note).
+    pub fn notes(&self) -> Vec<&str> {
+        let mut notes: Vec<&str> = Vec::new();
+        for note in self.hops.iter().filter_map(|h| h.note.as_deref()) {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+        notes
+    }
+}
+
+/// Edge weights. Every hop costs at least 1, so among equally trusted paths
+/// the shorter one wins; a calculated edge costs as much as three trusted
+/// ones, and another vendor's pack size or a density counts for one and a
+/// half.
+const TRUSTED_WEIGHT: f64 = 1.0;
+const UNVERIFIED_WEIGHT: f64 = 1.5;
+const CALCULATED_WEIGHT: f64 = 3.0;
+
+/// The standard weight and volume conversions, which hold for every item.
+pub fn standard_conversion_edges(conn: &Connection) -> Result<Vec<ConvEdge>, String> {
+    Ok(crate::units::standard_edges(conn)?
+        .into_iter()
+        .map(|(unit_id1, unit_id2, qty1, qty2)| ConvEdge {
+            unit_id1,
+            unit_id2,
+            qty1,
+            qty2,
+            source: EdgeSource::Standard,
+            vendor_id: None,
+            row_id: None,
+            note: None,
+            weight: TRUSTED_WEIGHT,
+        })
+        .collect())
+}
+
+/// An item's conversion edges: its own (`vendor_id`'s and the vendor-neutral
+/// ones when a vendor is given), those read from its pack descriptions, the
+/// standard weight and volume conversions that hold for every item, and last
+/// a volume-to-weight bridge when the item has a density. Vendor edges are
+/// trusted when they belong to `vendor_id`; with no vendor given, every
+/// vendor's edges are included but weigh more.
+pub fn build_conversion_edges(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: Option<i64>,
+) -> Result<Vec<ConvEdge>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT unit_id1, unit_id2, qty1, qty2, COALESCE(vendor_id, 0), COALESCE(is_calculated, 0), rowid
+             FROM convunit
+             WHERE item_id = ?1 AND (?2 IS NULL OR vendor_id = ?2 OR vendor_id = 0)
+               AND unit_id1 IS NOT NULL AND unit_id2 IS NOT NULL AND qty1 IS NOT NULL AND qty2 IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map((item_id, vendor_id), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+                row.get::<_, i64>(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut edges = Vec::new();
+    for row in rows {
+        let (unit_id1, unit_id2, qty1, qty2, edge_vendor, calculated, row_id) =
+            row.map_err(|e| e.to_string())?;
+        let (source, weight) = if calculated != 0 {
+            (EdgeSource::Calculated, CALCULATED_WEIGHT)
+        } else if edge_vendor == 0 {
+            (EdgeSource::Manual, TRUSTED_WEIGHT)
+        } else if vendor_id == Some(edge_vendor) {
+            (EdgeSource::Vendor, TRUSTED_WEIGHT)
+        } else {
+            (EdgeSource::Vendor, UNVERIFIED_WEIGHT)
+        };
+        edges.push(ConvEdge {
+            unit_id1,
+            unit_id2,
+            qty1,
+            qty2,
+            source,
+            vendor_id: (edge_vendor != 0).then_some(edge_vendor),
+            row_id: Some(row_id),
+            note: None,
+            weight,
+        });
+    }
+    for pack in crate::pack::pack_edges(conn, item_id, vendor_id)? {
+        let trusted = pack.vendor_id == 0 || vendor_id == Some(pack.vendor_id);
+        edges.push(ConvEdge {
+            unit_id1: pack.unit_id1,
+            unit_id2: pack.unit_id2,
+            qty1: pack.qty1,
+            qty2: pack.qty2,
+            source: EdgeSource::Pack,
+            vendor_id: (pack.vendor_id != 0).then_some(pack.vendor_id),
+            row_id: None,
+            note: Some(format!("pack {}", pack.pack)),
+            weight: if trusted {
+                TRUSTED_WEIGHT
+            } else {
+                UNVERIFIED_WEIGHT
+            },
+        });
+    }
+    edges.extend(standard_conversion_edges(conn)?);
+    if let Some(density) = crate::density::item_density(conn, item_id) {
+        if let Some((unit_id1, unit_id2, qty1, qty2)) =
+            crate::density::density_edge(conn, &density)?
+        {
+            edges.push(ConvEdge {
+                unit_id1,
+                unit_id2,
+                qty1,
+                qty2,
+                source: EdgeSource::Density,
+                vendor_id: None,
+                row_id: None,
+                note: Some(format!(
+                    "density {:.3} g/ml, {}",
+                    density.g_per_ml, density.source
+                )),
+                weight: UNVERIFIED_WEIGHT,
+            });
+        }
+    }
+    Ok(edges)
+}
+
+/// The cheapest path from `from_unit` to `to_unit` of at most `max_hops`
+/// edges, by edge weight. Ties go to the path with fewer hops, then to
+/// edges listed earlier.
+pub fn best_conversion_path(
+    edges: &[ConvEdge],
+    from_unit: i64,
+    to_unit: i64,
+    max_hops: usize,
+) -> Option<ConvPath> {
+    if from_unit == to_unit {
+        return Some(ConvPath {
+            factor: 1.0,
+            hops: Vec::new(),
+        });
+    }
+
+    // Cheapest known (cost, path) per unit using at most `round` hops,
+    // relaxed one hop per round.
+    let mut best: HashMap<i64, (f64, ConvPath)> = HashMap::new();
+    best.insert(
+        from_unit,
+        (
+            0.0,
+            ConvPath {
+                factor: 1.0,
+                hops: Vec::new(),
+            },
+        ),
+    );
+    for _ in 0..max_hops {
+        let previous = best.clone();
+        let mut changed = false;
+        for edge in edges.iter().filter(|e| e.qty1 > 0.0 && e.qty2 > 0.0) {
+            for (from, to, factor) in [
+                (edge.unit_id1, edge.unit_id2, edge.qty2 / edge.qty1),
+                (edge.unit_id2, edge.unit_id1, edge.qty1 / edge.qty2),
+            ] {
+                let Some((cost, path)) = previous.get(&from) else {
+                    continue;
+                };
+                if path.hops.iter().any(|h| h.from_unit == to) {
+                    continue;
+                }
+                let cost = cost + edge.weight;
+                if best
+                    .get(&to)
+                    .is_some_and(|(known, _)| *known <= cost + 1e-9)
+                {
+                    continue;
+                }
+                let mut hops = path.hops.clone();
+                hops.push(ConvHop {
+                    from_unit: from,
+                    to_unit: to,
+                    factor,
+                    source: edge.source,
+                    vendor_id: edge.vendor_id,
+                    note: edge.note.clone(),
+                });
+                best.insert(
+                    to,
+                    (
+                        cost,
+                        ConvPath {
+                            factor: path.factor * factor,
+                            hops,
+                        },
+                    ),
+                );
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    best.remove(&to_unit).map(|(_, path)| path)
+}
+
+/// Units of `to_unit` per unit of `from_unit` along the best path, and its
+/// number of hops.
+pub fn conversion_factor(
+    edges: &[ConvEdge],
+    from_unit: i64,
+    to_unit: i64,
+    max_hops: usize,
+) -> Option<(f64, usize)> {
+    best_conversion_path(edges, from_unit, to_unit, max_hops).map(|p| (p.factor, p.hops.len()))
+}
+
+/// A conversion path spelled out with unit and vendor names for display.
+pub struct ConvStep {
+    pub from_unit_name: String,
+    pub to_unit_name: String,
+    pub factor: f64,
+    pub source: String,
+    pub vendor_name: String,
+    pub note: String,
+}
+
+pub fn describe_conversion(conn: &Connection, path: &ConvPath) -> Vec<ConvStep> {
+    path.hops
+        .iter()
+        .map(|hop| ConvStep {
+            from_unit_name: unit_name(conn, Some(hop.from_unit)),
+            to_unit_name: unit_name(conn, Some(hop.to_unit)),
+            factor: hop.factor,
+            source: hop.source.label().to_string(),
+            vendor_name: hop
+                .vendor_id
+                .and_then(|v| {
+                    conn.query_row(
+                        "SELECT name FROM vendors WHERE vendor_id = ?1",
+                        [v],
+                        |row| row.get(0),
+                    )
+                    .ok()
+                })
+                .unwrap_or_default(),
+            note: hop.note.clone().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Describes a path as "1 Case = 6 Each = 30 Pound (vendor, standard)".
+pub fn path_summary(conn: &Connection, path: &ConvPath) -> String {
+    let steps = describe_conversion(conn, path);
+    let mut text = format!(
+        "1 {}",
+        steps
+            .first()
+            .map(|s| s.from_unit_name.as_str())
+            .unwrap_or("-")
+    );
+    let mut running = 1.0;
+    for step in &steps {
+        running *= step.factor;
+        text.push_str(&format!(" = {} {}", format_qty(running), step.to_unit_name));
+    }
+    let mut sources: Vec<&str> = steps.iter().map(|s| s.source.as_str()).collect();
+    sources.sort_unstable();
+    sources.dedup();
+    text.push_str(&format!(" ({})", sources.join(", ")));
+    text
+}
+
+/// Quantities in conversion text: four decimals (six for tiny factors)
+/// without trailing zeros.
+pub fn format_qty(qty: f64) -> String {
+    let decimals = if qty.abs() < 0.01 { 6 } else { 4 };
+    let text = format!("{:.*}", decimals, qty);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn unit_name(conn: &Connection, unit_id: Option<i64>) -> String {
+    match unit_id {
+        Some(uid) => conn
+            .query_row("SELECT sing FROM units WHERE unit_id = ?1", [uid], |row| {
+                row.get(0)
+            })
+            .unwrap_or_else(|_| "-".to_string()),
+        None => "-".to_string(),
+    }
+}
+
+/// Usable yield (0-100] for an item, preferring the preparation-specific
+/// value over the item default. `None` means the item is treated as 100%.
+pub fn item_yield_pct(conn: &Connection, item_id: i64, prep_id: Option<i64>) -> Option<f64> {
+    let valid = |pct: Option<f64>| pct.filter(|p| *p > 0.0 && *p <= 100.0);
+    if let Some(prep_id) = prep_id {
+        let prep_pct: Option<f64> = conn
+            .query_row(
+                "SELECT yield_pct FROM item_preps WHERE prep_id = ?1 AND item_id = ?2",
+                (prep_id, item_id),
+                |row| row.get(0),
+            )
+            .ok()
+            .flatten();
+        if let Some(pct) = valid(prep_pct) {
+            return Some(pct);
+        }
+    }
+    valid(
+        conn.query_row(
+            "SELECT yield_pct FROM items WHERE item_id = ?1",
+            [item_id],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten(),
+    )
+}
+
+/// The item's default purchase unit, or its first purchase unit.
+pub fn default_purch_unit(conn: &Connection, item_id: i64) -> Option<i64> {
+    conn.query_row(
+        "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 AND is_default = 1 LIMIT 1",
+        [item_id],
+        |row| row.get(0),
+    )
+    .ok()
+    .or_else(|| {
+        conn.query_row(
+            "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 LIMIT 1",
+            [item_id],
+            |row| row.get(0),
+        )
+        .ok()
+    })
+}
+
+/// Price per purchase unit and the vendor it came from: the vendor price
+/// list first (the vendor of an award in effect today, otherwise the lowest
+/// vendor id), then the most recent invoice line.
+/// With landed costing on, list prices carry the vendor's latest
+/// landed-to-invoiced ratio and invoice lines use their landed price.
+pub fn item_price(conn: &Connection, item_id: i64) -> Option<(f64, i64)> {
+    let landed = crate::landed::use_landed_cost(conn);
+    conn.query_row(
+        "SELECT price, vendor_id FROM inv_prices WHERE item_id = ?1
+         ORDER BY EXISTS (
+                    SELECT 1 FROM vendor_bids b
+                    WHERE b.item_id = ?1 AND b.vendor_id = inv_prices.vendor_id
+                      AND b.status = 'awarded' AND b.applied_at IS NOT NULL
+                      AND COALESCE(b.valid_from, '') <= date('now', 'localtime')
+                      AND COALESCE(NULLIF(b.valid_to, ''), '9999-12-31') >= date('now', 'localtime')
+                  ) DESC, vendor_id
+         LIMIT 1",
+        [item_id],
+        |row| Ok((row.get::<_, f64>(0)?, row.get(1)?)),
+    )
+    .ok()
+    .map(|(price, vendor_id)| {
+        let ratio = if landed { crate::landed::landed_ratio(conn, item_id, vendor_id) } else { None };
+        (price * ratio.unwrap_or(1.0), vendor_id)
+    })
+    .or_else(|| {
+        conn.query_row(
+            "SELECT CASE WHEN ?2 THEN COALESCE(landed_price, price) ELSE price END, vendor_id FROM trans
+             WHERE item_id = ?1 AND price IS NOT NULL AND price > 0 ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+            (item_id, landed),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()
+    })
+}
+
+/// Converts `qty` of an item from one unit to another through its
+/// conversion graph. `None` when no path exists.
+pub fn convert_item_qty(
+    conn: &Connection,
+    item_id: i64,
+    qty: f64,
+    from_unit: i64,
+    to_unit: i64,
+) -> Result<Option<f64>, String> {
+    if from_unit == to_unit {
+        return Ok(Some(qty));
+    }
+    let edges = build_conversion_edges(conn, item_id, None)?;
+    Ok(conversion_factor(&edges, from_unit, to_unit, 6).map(|(f, _)| qty * f))
+}
+
+/// Costs `qty` of an inventory item in `unit_id` against its default
+/// purchase unit and first known price, grossed up by the item's yield.
+pub fn cost_item_line(
+    conn: &Connection,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    prep_id: Option<i64>,
+) -> Result<LineCost, String> {
+    let purch_unit_id = default_purch_unit(conn, item_id);
+    let purch_unit_name = unit_name(conn, purch_unit_id);
+
+    let price_vendor = item_price(conn, item_id);
+    let price = price_vendor.map(|v| v.0);
+    let price_vendor_id = price_vendor.map(|v| v.1);
+
+    let mut factor: Option<f64> = None;
+    let mut conversion = None;
+    if let (Some(recipe_unit_id), Some(purch_unit_id)) = (unit_id, purch_unit_id) {
+        if recipe_unit_id == purch_unit_id {
+            factor = Some(1.0);
+        } else {
+            let edges = build_conversion_edges(conn, item_id, price_vendor_id)?;
+            if let Some(path) = best_conversion_path(&edges, recipe_unit_id, purch_unit_id, 6) {
+                factor = Some(path.factor);
+                conversion = Some(path);
+            }
+        }
+    }
+
+    let qty_valid = qty.unwrap_or(0.0) > 0.0;
+    let yield_pct = item_yield_pct(conn, item_id, prep_id);
+
+    let (cost_status, ap_cost) = if !qty_valid {
+        ("Missing qty".to_string(), None)
+    } else if purch_unit_id.is_none() {
+        ("Missing purch unit".to_string(), None)
+    } else if price.is_none() {
+        ("Missing price".to_string(), None)
+    } else if factor.is_none() {
+        ("Needs conversion".to_string(), None)
+    } else {
+        let cost = qty.unwrap_or(0.0) * factor.unwrap_or(1.0) * price.unwrap_or(0.0);
+        (ok_status("", conversion.as_ref()), Some(cost))
+    };
+
+    let extended_cost = ap_cost.map(|c| c / (yield_pct.unwrap_or(100.0) / 100.0));
+
+    Ok(LineCost {
+        purch_unit_id,
+        purch_unit_name,
+        price,
+        yield_pct,
+        ap_cost,
+        extended_cost,
+        cost_status,
+        conversion,
+    })
+}
+
+/// "OK", with the hop count and any notes (such as a density) of the
+/// conversion it took, e.g. "OK (sub-recipe, 2 hops, density 0.530 g/ml,
+/// item)".
+pub fn ok_status(prefix: &str, conversion: Option<&ConvPath>) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if !prefix.is_empty() {
+        parts.push(prefix.to_string());
+    }
+    if let Some(path) = conversion.filter(|p| !p.hops.is_empty()) {
+        parts.push(format!("{} hops", path.hops.len()));
+        parts.extend(path.notes().into_iter().map(str::to_string));
+    }
+    if parts.is_empty() {
+        "OK".to_string()
+    } else {
+        format!("OK ({})", parts.join(", "))
+    }
+}
+
+/// Returns the declared yield of a recipe. Values set on the recipe win;
+/// otherwise the imported `recp_inv` link (recipe → produced item) is used.
+pub fn recipe_yield(conn: &Connection, recipe_id: i64) -> Result<RecipeYield, String> {
+    let (mut qty, mut unit_id): (Option<f64>, Option<i64>) = conn
+        .query_row(
+            "SELECT yield_qty, yield_unit_id FROM recipes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| Ok((row.get(0).ok().flatten(), row.get(1).ok().flatten())),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or((None, None));
+
+    let linked: Option<(Option<i64>, Option<f64>, Option<i64>)> = conn
+        .query_row(
+            "SELECT item_id, qty, unit_id FROM recp_inv WHERE recipe_id = ?1 ORDER BY recp_inv_id LIMIT 1",
+            [recipe_id],
+            |row| {
+                Ok((
+                    row.get(0).ok().flatten(),
+                    row.get(1).ok().flatten(),
+                    row.get(2).ok().flatten(),
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let mut item_id = None;
+    if let Some((linked_item, linked_qty, linked_unit)) = linked {
+        item_id = linked_item;
+        if qty.unwrap_or(0.0) <= 0.0 {
+            qty = linked_qty;
+        }
+        if unit_id.is_none() {
+            unit_id = linked_unit;
+        }
+    }
+
+    Ok(RecipeYield {
+        qty: qty.filter(|q| *q > 0.0),
+        unit_id,
+        unit_name: unit_name(conn, unit_id),
+        item_id,
+    })
+}
+
+/// Resolves which recipe a line points at: an explicit `sub_recipe_id`, or an
+/// item that another recipe produces according to `recp_inv`.
+pub fn sub_recipe_for_line(
+    conn: &Connection,
+    parent_recipe_id: i64,
+    sub_recipe_id: Option<i64>,
+    item_id: i64,
+) -> Option<i64> {
+    if sub_recipe_id.is_some() {
+        return sub_recipe_id;
+    }
+    if item_id <= 0 {
+        return None;
+    }
+    conn.query_row(
+        "SELECT recipe_id FROM recp_inv WHERE item_id = ?1 AND recipe_id <> ?2 ORDER BY recipe_id LIMIT 1",
+        (item_id, parent_recipe_id),
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// True when `target` is reachable from `from` through sub-recipe lines,
+/// i.e. adding `from` as a component of `target` would create a cycle.
+pub fn recipe_reaches(conn: &Connection, from: i64, target: i64) -> Result<bool, String> {
+    let mut stack = vec![(from, 0usize)];
+    let mut seen = std::collections::HashSet::new();
+    while let Some((recipe_id, depth)) = stack.pop() {
+        if recipe_id == target {
+            return Ok(true);
+        }
+        if depth > MAX_RECIPE_DEPTH || !seen.insert(recipe_id) {
+            continue;
+        }
+        let mut stmt = conn
+            .prepare(
+                "SELECT sub_recipe_id, COALESCE(item_id, 0) FROM recp_items WHERE recipe_id = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([recipe_id], |row| {
+                Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (sub_id, item_id) = row.map_err(|e| e.to_string())?;
+            if let Some(next) = sub_recipe_for_line(conn, recipe_id, sub_id, item_id) {
+                stack.push((next, depth + 1));
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Costs a sub-recipe line by scaling the component recipe's total cost by
+/// `qty` over its yield.
+fn cost_sub_recipe_line(
+    conn: &Connection,
+    sub_recipe_id: i64,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    ctx: &mut CostContext,
+) -> Result<(LineCost, Vec<String>), String> {
+    let yld = recipe_yield(conn, sub_recipe_id)?;
+    let mut cost = LineCost {
+        purch_unit_id: yld.unit_id,
+        purch_unit_name: yld.unit_name.clone(),
+        price: None,
+        yield_pct: None,
+        ap_cost: None,
+        extended_cost: None,
+        cost_status: String::new(),
+        conversion: None,
+    };
+
+    if ctx.stack.contains(&sub_recipe_id) {
+        cost.cost_status = "Recipe cycle".to_string();
+        return Ok((cost, Vec::new()));
+    }
+    if ctx.stack.len() >= MAX_RECIPE_DEPTH {
+        cost.cost_status = "Nested too deep".to_string();
+        return Ok((cost, Vec::new()));
+    }
+
+    if !ctx.totals.contains_key(&sub_recipe_id) {
+        let sub = cost_recipe(conn, sub_recipe_id, ctx)?;
+        ctx.totals.insert(
+            sub_recipe_id,
+            SubRecipeTotals {
+                total_cost: sub.total_cost,
+                missing_costs: sub.missing_costs,
+                leaf_names: sub.leaf_names,
+            },
+        );
+    }
+    let totals = &ctx.totals[&sub_recipe_id];
+    let leaf_names = totals.leaf_names.clone();
+
+    let yield_qty = match yld.qty {
+        Some(q) => q,
+        None => {
+            cost.cost_status = "Missing yield".to_string();
+            return Ok((cost, leaf_names));
+        }
+    };
+    cost.price = Some(totals.total_cost / yield_qty);
+
+    let mut factor: Option<f64> = None;
+    if let (Some(line_unit), Some(yield_unit)) = (unit_id, yld.unit_id) {
+        if line_unit == yield_unit {
+            factor = Some(1.0);
+        } else {
+            let edge_item = yld.item_id.unwrap_or(item_id);
+            let edges = build_conversion_edges(conn, edge_item, None)?;
+            if let Some(path) = best_conversion_path(&edges, line_unit, yield_unit, 6) {
+                factor = Some(path.factor);
+                cost.conversion = Some(path);
+            }
+        }
+    }
+
+    if qty.unwrap_or(0.0) <= 0.0 {
+        cost.cost_status = "Missing qty".to_string();
+    } else if yld.unit_id.is_none() {
+        cost.cost_status = "Missing yield".to_string();
+    } else if factor.is_none() {
+        cost.cost_status = "Needs conversion".to_string();
+    } else if totals.missing_costs > 0 {
+        cost.cost_status = format!("Sub-recipe incomplete ({} missing)", totals.missing_costs);
+    } else {
+        let per_unit = cost.price.unwrap_or(0.0);
+        cost.ap_cost = Some(qty.unwrap_or(0.0) * factor.unwrap_or(1.0) * per_unit);
+        cost.extended_cost = cost.ap_cost;
+        cost.cost_status = ok_status("sub-recipe", cost.conversion.as_ref());
+    }
+
+    Ok((cost, leaf_names))
+}
+
+/// Costs every line of a recipe, following sub-recipes recursively.
+pub fn cost_recipe(
+    conn: &Connection,
+    recipe_id: i64,
+    ctx: &mut CostContext,
+) -> Result<RecipeCost, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ri.recp_item_id, COALESCE(ri.item_id, 0), COALESCE(sr.name, i.name), ri.unit_id, u.sing, ri.qty, ri.sub_recipe_id, ri.prep_id, p.name
+             FROM recp_items ri
+             LEFT JOIN items i ON i.item_id = ri.item_id
+             LEFT JOIN recipes sr ON sr.recipe_id = ri.sub_recipe_id
+             LEFT JOIN units u ON u.unit_id = ri.unit_id
+             LEFT JOIN item_preps p ON p.prep_id = ri.prep_id
+             WHERE ri.recipe_id = ?1
+             ORDER BY COALESCE(sr.name, i.name)",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([recipe_id], |row| {
+            let recp_item_id: i64 = row.get(0)?;
+            let item_id: i64 = row.get(1)?;
+            let item_name: Option<String> = row.get(2).ok();
+            let unit_id: Option<i64> = row.get(3).ok();
+            let unit_name: Option<String> = row.get(4).ok();
+            let qty: Option<f64> = row.get(5).ok();
+            let sub_recipe_id: Option<i64> = row.get(6).ok().flatten();
+            let prep: (Option<i64>, Option<String>) =
+                (row.get(7).ok().flatten(), row.get(8).ok().flatten());
+            Ok((
+                recp_item_id,
+                item_id,
+                item_name,
+                unit_id,
+                unit_name,
+                qty,
+                sub_recipe_id,
+                prep,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut raw = Vec::new();
+    for row in rows {
+        raw.push(row.map_err(|e| e.to_string())?);
+    }
+
+    ctx.stack.push(recipe_id);
+    let mut lines = Vec::new();
+    let mut total_cost = 0.0f64;
+    let mut missing_costs = 0i64;
+    let mut leaf_names = Vec::new();
+
+    for (recp_item_id, item_id, item_name, unit_id, unit_name, qty, sub_recipe_id, prep) in raw {
+        let (prep_id, prep_name) = prep;
+        let item_name = item_name.unwrap_or_else(|| "(unknown item)".to_string());
+        let unit_name = unit_name.unwrap_or_else(|| "-".to_string());
+        let sub_recipe_id = sub_recipe_for_line(conn, recipe_id, sub_recipe_id, item_id);
+
+        let cost = match sub_recipe_id {
+            Some(sub_id) => {
+                let (cost, names) = cost_sub_recipe_line(conn, sub_id, item_id, unit_id, qty, ctx)?;
+                leaf_names.extend(names);
+                cost
+            }
+            None => {
+                leaf_names.push(item_name.clone());
+                let mut cost = cost_item_line(conn, item_id, unit_id, qty, prep_id)?;
+                if let Some(f) = ctx.price_factors.get(&item_id) {
+                    cost.price = cost.price.map(|p| p * f);
+                    cost.ap_cost = cost.ap_cost.map(|c| c * f);
+                    cost.extended_cost = cost.extended_cost.map(|c| c * f);
+                }
+                cost
+            }
+        };
+
+        if let Some(c) = cost.extended_cost {
+            total_cost += c;
+        } else {
+            missing_costs += 1;
+        }
+
+        lines.push(RecipeCostLine {
+            recp_item_id,
+            item_id,
+            item_name,
+            unit_id,
+            unit_name,
+            qty,
+            sub_recipe_id,
+            prep_id,
+            prep_name: prep_name.unwrap_or_default(),
+            cost,
+        });
+    }
+    ctx.stack.pop();
+
+    Ok(RecipeCost {
+        lines,
+        total_cost,
+        missing_costs,
+        leaf_names,
+    })
+}
+
+/// item_id, name, unit_id, qty, sub_recipe_id, prep_id
+type UsageLine = (
+    i64,
+    String,
+    Option<i64>,
+    Option<f64>,
+    Option<i64>,
+    Option<i64>,
+);
+
+/// Adds the as-purchased quantity of every inventory item used by `batches`
+/// of a recipe to `usage`, in each item's purchase unit and grossed up by
+/// yield. Sub-recipes are followed through their yield; lines that can't be
+/// brought into a purchase unit are named in `unresolved`.
+pub fn accumulate_recipe_usage(
+    conn: &Connection,
+    recipe_id: i64,
+    batches: f64,
+    depth: usize,
+    usage: &mut HashMap<i64, f64>,
+    unresolved: &mut Vec<String>,
+) -> Result<(), String> {
+    if depth > MAX_RECIPE_DEPTH {
+        unresolved.push(format!("Recipe {} nested too deep", recipe_id));
+        return Ok(());
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(ri.item_id, 0), COALESCE(sr.name, i.name, '(unknown item)'), ri.unit_id, ri.qty, ri.sub_recipe_id, ri.prep_id
+             FROM recp_items ri
+             LEFT JOIN items i ON i.item_id = ri.item_id
+             LEFT JOIN recipes sr ON sr.recipe_id = ri.sub_recipe_id
+             WHERE ri.recipe_id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let lines: Vec<UsageLine> = stmt
+        .query_map([recipe_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2).ok().flatten(),
+                row.get(3).ok().flatten(),
+                row.get(4).ok().flatten(),
+                row.get(5).ok().flatten(),
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (item_id, name, unit_id, qty, sub_recipe_id, prep_id) in lines {
+        let qty = match qty.filter(|q| *q > 0.0) {
+            Some(q) => q * batches,
+            None => continue,
+        };
+        if let Some(sub_id) = sub_recipe_for_line(conn, recipe_id, sub_recipe_id, item_id) {
+            let yld = recipe_yield(conn, sub_id)?;
+            let edge_item = yld.item_id.unwrap_or(item_id);
+            let in_yield_units = match (yld.qty, unit_id, yld.unit_id) {
+                (Some(_), Some(from), Some(to)) => {
+                    convert_item_qty(conn, edge_item, qty, from, to)?
+                }
+                _ => None,
+            };
+            match (in_yield_units, yld.qty) {
+                (Some(q), Some(yield_qty)) => accumulate_recipe_usage(
+                    conn,
+                    sub_id,
+                    q / yield_qty,
+                    depth + 1,
+                    usage,
+                    unresolved,
+                )?,
+                _ => unresolved.push(format!("{} (sub-recipe yield or conversion)", name)),
+            }
+            continue;
+        }
+        let purch_qty = match (unit_id, default_purch_unit(conn, item_id)) {
+            (Some(from), Some(to)) => convert_item_qty(conn, item_id, qty, from, to)?,
+            _ => None,
+        };
+        match purch_qty {
+            Some(q) => {
+                let yield_pct = item_yield_pct(conn, item_id, prep_id).unwrap_or(100.0);
+                *usage.entry(item_id).or_insert(0.0) += q / (yield_pct / 100.0);
+            }
+            None => unresolved.push(format!("{} (purchase unit or conversion)", name)),
+        }
+    }
+    Ok(())
+}
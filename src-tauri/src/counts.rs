@@ -0,0 +1,619 @@
+use crate::costing::{
+    build_conversion_edges, conversion_factor, convert_item_qty, default_purch_unit, item_price,
+};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub struct CountSession {
+    pub session_id: i64,
+    pub name: String,
+    pub count_date: String,
+    /// "open" while counts can be entered, "closed" once frozen.
+    pub status: String,
+    pub notes: String,
+    pub created_at: String,
+    pub closed_at: String,
+    pub item_count: i64,
+    /// Frozen value for closed sessions. Open sessions are only valued with
+    /// their count sheet; elsewhere this is `None`.
+    pub total_value: Option<f64>,
+}
+
+/// One item on a count sheet. Quantities are as entered; `purch_qty`,
+/// `unit_price` and `value` are in the item's purchase unit and are frozen
+/// when the session is closed.
+pub struct CountSheetLine {
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub storage_type: String,
+    pub shelf_order: Option<i64>,
+    pub purch_unit_id: Option<i64>,
+    pub purch_unit_name: String,
+    /// Units the item can be counted in (purchase unit first).
+    pub units: Vec<(i64, String)>,
+    pub qty: Option<f64>,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    pub purch_qty: Option<f64>,
+    pub unit_price: Option<f64>,
+    pub value: Option<f64>,
+}
+
+pub struct ValuationLine {
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub storage_type: String,
+    pub unit_name: String,
+    pub qty: Option<f64>,
+    pub unit_price: Option<f64>,
+    pub value: Option<f64>,
+    pub prior_qty: Option<f64>,
+    pub prior_value: Option<f64>,
+    pub change_value: f64,
+}
+
+pub struct ValuationGroup {
+    pub name: String,
+    pub value: f64,
+    pub prior_value: f64,
+}
+
+pub struct ValuationReport {
+    pub session: CountSession,
+    pub prior: Option<CountSession>,
+    pub lines: Vec<ValuationLine>,
+    pub storage_groups: Vec<ValuationGroup>,
+    pub categories: Vec<ValuationGroup>,
+    pub total_value: f64,
+    pub prior_total: f64,
+    /// Counted items with no price or no conversion to their purchase unit.
+    pub unpriced: Vec<String>,
+}
+
+/// A counted line with its purchase-unit quantity and value, either frozen
+/// (closed session) or computed from current conversions and prices.
+struct CountedLine {
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    purch_qty: Option<f64>,
+    unit_price: Option<f64>,
+    value: Option<f64>,
+}
+
+const SESSION_SELECT: &str =
+    "SELECT s.session_id, COALESCE(s.name, ''), COALESCE(s.count_date, ''),
+            COALESCE(s.status, 'open'), COALESCE(s.notes, ''), COALESCE(s.created_at, ''),
+            COALESCE(s.closed_at, ''), s.total_value,
+            (SELECT COUNT(*) FROM inv_counts c WHERE c.session_id = s.session_id)
+     FROM count_sessions s";
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<CountSession> {
+    let status: String = row.get(3)?;
+    let frozen: Option<f64> = row.get(7)?;
+    Ok(CountSession {
+        session_id: row.get(0)?,
+        name: row.get(1)?,
+        count_date: row.get(2)?,
+        total_value: if status == "closed" {
+            Some(frozen.unwrap_or(0.0))
+        } else {
+            None
+        },
+        status,
+        notes: row.get(4)?,
+        created_at: row.get(5)?,
+        closed_at: row.get(6)?,
+        item_count: row.get(8)?,
+    })
+}
+
+/// Total value of counted lines.
+fn lines_value(lines: &HashMap<i64, CountedLine>) -> f64 {
+    lines.values().filter_map(|l| l.value).sum()
+}
+
+pub fn load_session(conn: &Connection, session_id: i64) -> Result<CountSession, String> {
+    conn.query_row(
+        &format!("{} WHERE s.session_id = ?1", SESSION_SELECT),
+        [session_id],
+        session_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Count session {} not found", session_id))
+}
+
+/// All sessions, newest count date first.
+pub fn list_sessions(conn: &Connection) -> Result<Vec<CountSession>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} ORDER BY s.count_date DESC, s.session_id DESC",
+            SESSION_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let sessions = stmt
+        .query_map([], session_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(sessions)
+}
+
+/// The closed session counted most recently before `session`, used as the
+/// default comparison period.
+pub fn previous_closed_session(
+    conn: &Connection,
+    session: &CountSession,
+) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT session_id FROM count_sessions
+         WHERE status = 'closed' AND session_id <> ?1
+           AND (count_date < ?2 OR (count_date = ?2 AND session_id < ?1))
+         ORDER BY count_date DESC, session_id DESC LIMIT 1",
+        rusqlite::params![session.session_id, session.count_date],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn session_lines(
+    conn: &Connection,
+    session: &CountSession,
+) -> Result<HashMap<i64, CountedLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT item_id, qty, unit_id, purch_qty, unit_price, value
+             FROM inv_counts WHERE session_id = ?1 AND item_id IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    type Row = (
+        i64,
+        Option<f64>,
+        Option<i64>,
+        Option<f64>,
+        Option<f64>,
+        Option<f64>,
+    );
+    let rows: Vec<Row> = stmt
+        .query_map([session.session_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let closed = session.status == "closed";
+    let mut lines = HashMap::new();
+    for (item_id, qty, unit_id, purch_qty, unit_price, value) in rows {
+        let line = if closed {
+            CountedLine {
+                qty,
+                unit_id,
+                purch_qty,
+                unit_price,
+                value,
+            }
+        } else {
+            let (purch_qty, unit_price, value) = live_value(conn, item_id, qty, unit_id)?;
+            CountedLine {
+                qty,
+                unit_id,
+                purch_qty,
+                unit_price,
+                value,
+            }
+        };
+        lines.insert(item_id, line);
+    }
+    Ok(lines)
+}
+
+/// A counted quantity in the purchase unit, its unit price and its value.
+type LiveValue = (Option<f64>, Option<f64>, Option<f64>);
+
+/// Converts a counted quantity to the purchase unit and values it at the
+/// item's current price.
+fn live_value(
+    conn: &Connection,
+    item_id: i64,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+) -> Result<LiveValue, String> {
+    let purch_unit = match default_purch_unit(conn, item_id) {
+        Some(u) => u,
+        None => return Ok((None, None, None)),
+    };
+    let purch_qty = match qty {
+        Some(q) => convert_item_qty(conn, item_id, q, unit_id.unwrap_or(purch_unit), purch_unit)?,
+        None => None,
+    };
+    let unit_price = item_price(conn, item_id).map(|p| p.0);
+    let value = match (purch_qty, unit_price) {
+        (Some(q), Some(p)) => Some(q * p),
+        _ => None,
+    };
+    Ok((purch_qty, unit_price, value))
+}
+
+fn unit_names(conn: &Connection) -> Result<HashMap<i64, String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT unit_id, COALESCE(sing, '') FROM units")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Units an item can be counted in: its purchase unit plus every unit its
+/// conversions connect to it.
+fn countable_units(
+    conn: &Connection,
+    item_id: i64,
+    purch_unit: i64,
+    names: &HashMap<i64, String>,
+) -> Result<Vec<(i64, String)>, String> {
+    let edges = build_conversion_edges(conn, item_id, None)?;
+    let candidates: BTreeSet<i64> = edges
+        .iter()
+        .flat_map(|e| [e.unit_id1, e.unit_id2])
+        .collect();
+    let name = |u: i64| {
+        names
+            .get(&u)
+            .cloned()
+            .unwrap_or_else(|| format!("Unit {}", u))
+    };
+    let mut units = vec![(purch_unit, name(purch_unit))];
+    for unit in candidates {
+        if unit != purch_unit && conversion_factor(&edges, unit, purch_unit, 6).is_some() {
+            units.push((unit, name(unit)));
+        }
+    }
+    Ok(units)
+}
+
+/// Items ordered the way they are walked: storage type, shelf order, name.
+/// Every item with a purchase unit is listed, plus anything already counted
+/// in the session.
+pub fn count_sheet(
+    conn: &Connection,
+    session_id: i64,
+) -> Result<(CountSession, Vec<CountSheetLine>), String> {
+    let mut session = load_session(conn, session_id)?;
+    let mut counted = session_lines(conn, &session)?;
+    session.total_value = Some(lines_value(&counted));
+    let names = unit_names(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.item_id, COALESCE(i.name, ''), COALESCE(i.food_category, ''),
+                    COALESCE(i.storage_type, ''), i.shelf_order
+             FROM items i
+             WHERE EXISTS (SELECT 1 FROM inv_units u WHERE u.item_id = i.item_id)
+                OR EXISTS (SELECT 1 FROM inv_counts c WHERE c.item_id = i.item_id AND c.session_id = ?1)
+             ORDER BY COALESCE(i.storage_type, '') = '', COALESCE(i.storage_type, '') COLLATE NOCASE,
+                      i.shelf_order IS NULL, i.shelf_order, i.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<(i64, String, String, String, Option<i64>)> = stmt
+        .query_map([session_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = Vec::with_capacity(items.len());
+    for (item_id, item_name, category, storage_type, shelf_order) in items {
+        let purch_unit_id = default_purch_unit(conn, item_id);
+        let units = match purch_unit_id {
+            Some(u) => countable_units(conn, item_id, u, &names)?,
+            None => Vec::new(),
+        };
+        let count = counted.remove(&item_id);
+        let unit_id = count.as_ref().and_then(|c| c.unit_id.or(purch_unit_id));
+        lines.push(CountSheetLine {
+            item_id,
+            item_name,
+            category,
+            storage_type,
+            shelf_order,
+            purch_unit_id,
+            purch_unit_name: purch_unit_id
+                .and_then(|u| names.get(&u).cloned())
+                .unwrap_or_else(|| "-".to_string()),
+            units,
+            qty: count.as_ref().and_then(|c| c.qty),
+            unit_id,
+            unit_name: unit_id
+                .and_then(|u| names.get(&u).cloned())
+                .unwrap_or_default(),
+            purch_qty: count.as_ref().and_then(|c| c.purch_qty),
+            unit_price: count.as_ref().and_then(|c| c.unit_price),
+            value: count.as_ref().and_then(|c| c.value),
+        });
+    }
+    Ok((session, lines))
+}
+
+fn require_open(conn: &Connection, session_id: i64) -> Result<CountSession, String> {
+    let session = load_session(conn, session_id)?;
+    if session.status == "closed" {
+        return Err(format!("Count session '{}' is closed", session.name));
+    }
+    Ok(session)
+}
+
+/// Records (or clears, when `qty` is `None`) an item's count in an open
+/// session. The unit must convert to the item's purchase unit.
+pub fn save_count_line(
+    conn: &Connection,
+    session_id: i64,
+    item_id: i64,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+) -> Result<(), String> {
+    let session = require_open(conn, session_id)?;
+    let clear = |conn: &Connection| {
+        conn.execute(
+            "DELETE FROM inv_counts WHERE session_id = ?1 AND item_id = ?2",
+            (session_id, item_id),
+        )
+        .map_err(|e| e.to_string())
+    };
+    let qty = match qty {
+        Some(q) if q < 0.0 => return Err("Count cannot be negative".to_string()),
+        Some(q) => q,
+        None => return clear(conn).map(|_| ()),
+    };
+    let purch_unit = default_purch_unit(conn, item_id)
+        .ok_or_else(|| format!("Item {} has no purchase unit", item_id))?;
+    let unit_id = unit_id.unwrap_or(purch_unit);
+    if convert_item_qty(conn, item_id, 1.0, unit_id, purch_unit)?.is_none() {
+        return Err("That unit has no conversion to the item's purchase unit".to_string());
+    }
+    clear(conn)?;
+    conn.execute(
+        "INSERT INTO inv_counts (item_id, count_date, qty, unit_id, session_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![item_id, session.count_date, qty, unit_id, session_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Freezes every line's purchase-unit quantity, price and value and marks
+/// the session closed. Fails if a counted unit no longer converts.
+pub fn close_session(conn: &Connection, session_id: i64) -> Result<f64, String> {
+    let session = require_open(conn, session_id)?;
+    let mut stmt = conn
+        .prepare("SELECT count_id, item_id, qty, unit_id FROM inv_counts WHERE session_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(i64, i64, Option<f64>, Option<i64>)> = stmt
+        .query_map([session_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut total = 0.0;
+    let mut broken = Vec::new();
+    for (count_id, item_id, qty, unit_id) in rows {
+        let (purch_qty, unit_price, value) = live_value(conn, item_id, qty, unit_id)?;
+        if qty.is_some() && purch_qty.is_none() {
+            let name: String = conn
+                .query_row(
+                    "SELECT COALESCE(name, '') FROM items WHERE item_id = ?1",
+                    [item_id],
+                    |row| row.get(0),
+                )
+                .unwrap_or_else(|_| format!("Item {}", item_id));
+            broken.push(name);
+            continue;
+        }
+        total += value.unwrap_or(0.0);
+        conn.execute(
+            "UPDATE inv_counts SET purch_qty = ?2, unit_price = ?3, value = ?4 WHERE count_id = ?1",
+            rusqlite::params![count_id, purch_qty, unit_price, value],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    if !broken.is_empty() {
+        return Err(format!(
+            "Cannot close '{}': no conversion to the purchase unit for {}",
+            session.name,
+            broken.join(", ")
+        ));
+    }
+    conn.execute(
+        "UPDATE count_sessions SET status = 'closed', closed_at = datetime('now', 'localtime'), total_value = ?2
+         WHERE session_id = ?1",
+        rusqlite::params![session_id, total],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(total)
+}
+
+fn group_name(name: &str, empty: &str) -> String {
+    if name.is_empty() {
+        empty.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Values a session's counts by storage type and food category and
+/// compares them with another session (by default the previous closed one).
+pub fn valuation_report(
+    conn: &Connection,
+    session_id: i64,
+    compare_session_id: Option<i64>,
+) -> Result<ValuationReport, String> {
+    let (session, sheet) = count_sheet(conn, session_id)?;
+    let compare_id = match compare_session_id {
+        Some(id) if id != session_id => Some(id),
+        Some(_) => None,
+        None => previous_closed_session(conn, &session)?,
+    };
+    let (prior, mut prior_lines) = match compare_id {
+        Some(id) => {
+            let mut prior = load_session(conn, id)?;
+            let lines = session_lines(conn, &prior)?;
+            prior.total_value = Some(lines_value(&lines));
+            (Some(prior), lines)
+        }
+        None => (None, HashMap::new()),
+    };
+
+    let mut lines = Vec::new();
+    let mut unpriced = Vec::new();
+    for line in sheet {
+        let prior_line = prior_lines.remove(&line.item_id);
+        let prior_line = prior_line.as_ref();
+        if line.qty.is_none() && prior_line.is_none() {
+            continue;
+        }
+        if line.qty.is_some() && line.value.is_none() {
+            unpriced.push(line.item_name.clone());
+        }
+        let prior_value = prior_line.and_then(|p| p.value);
+        lines.push(ValuationLine {
+            item_id: line.item_id,
+            item_name: line.item_name,
+            category: line.category,
+            storage_type: line.storage_type,
+            unit_name: line.purch_unit_name,
+            qty: line.purch_qty,
+            unit_price: line.unit_price,
+            value: line.value,
+            prior_qty: prior_line.and_then(|p| p.purch_qty),
+            prior_value,
+            change_value: line.value.unwrap_or(0.0) - prior_value.unwrap_or(0.0),
+        });
+    }
+    // Items only in the comparison session, e.g. no longer stocked, still
+    // count toward its groups.
+    let mut prior_only: Vec<(i64, CountedLine)> = prior_lines.into_iter().collect();
+    prior_only.sort_by_key(|(item_id, _)| *item_id);
+    for (item_id, prior_line) in prior_only {
+        let (item_name, category, storage_type, unit_name): (String, String, String, String) = conn
+            .query_row(
+                "SELECT COALESCE(i.name, ''), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''),
+                        COALESCE(u.sing, '-')
+                 FROM items i
+                 LEFT JOIN units u ON u.unit_id = (SELECT purch_unit_id FROM inv_units WHERE item_id = i.item_id
+                                                   ORDER BY is_default DESC LIMIT 1)
+                 WHERE i.item_id = ?1",
+                [item_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_else(|| (format!("Item {}", item_id), String::new(), String::new(), "-".to_string()));
+        lines.push(ValuationLine {
+            item_id,
+            item_name,
+            category,
+            storage_type,
+            unit_name,
+            qty: None,
+            unit_price: None,
+            value: None,
+            prior_qty: prior_line.purch_qty,
+            prior_value: prior_line.value,
+            change_value: -prior_line.value.unwrap_or(0.0),
+        });
+    }
+
+    let mut by_storage: BTreeMap<String, ValuationGroup> = BTreeMap::new();
+    let mut by_category: BTreeMap<String, ValuationGroup> = BTreeMap::new();
+    for line in &lines {
+        for (map, key) in [
+            (
+                &mut by_storage,
+                group_name(&line.storage_type, "Unassigned"),
+            ),
+            (
+                &mut by_category,
+                group_name(&line.category, "Uncategorized"),
+            ),
+        ] {
+            let entry = map.entry(key.clone()).or_insert(ValuationGroup {
+                name: key,
+                value: 0.0,
+                prior_value: 0.0,
+            });
+            entry.value += line.value.unwrap_or(0.0);
+            entry.prior_value += line.prior_value.unwrap_or(0.0);
+        }
+    }
+    let total_value = lines.iter().filter_map(|l| l.value).sum();
+    let prior_total = prior.as_ref().and_then(|p| p.total_value).unwrap_or(0.0);
+
+    Ok(ValuationReport {
+        session,
+        prior,
+        lines,
+        storage_groups: by_storage.into_values().collect(),
+        categories: by_category.into_values().collect(),
+        total_value,
+        prior_total,
+        unpriced,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{close_session, list_sessions, save_count_line, valuation_report};
+    use rusqlite::Connection;
+
+    #[test]
+    fn groups_include_items_only_in_the_prior_count() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name, food_category, storage_type) VALUES (1, 'Fryer Oil', 'Dry', 'Dry'), (2, 'Lard', 'Dry', 'Dry');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1), (2, 3, 1);
+             INSERT INTO inv_prices (item_id, vendor_id, price) VALUES (1, 7, 30.0), (2, 7, 20.0);
+             INSERT INTO count_sessions (session_id, name, count_date, status) VALUES (1, 'Jan', '2026-01-31', 'open'), (2, 'Feb', '2026-02-28', 'open');",
+        )
+        .unwrap();
+        save_count_line(&conn, 1, 1, Some(2.0), Some(3)).unwrap();
+        save_count_line(&conn, 1, 2, Some(1.0), Some(3)).unwrap();
+        close_session(&conn, 1).unwrap();
+        conn.execute("DELETE FROM inv_units WHERE item_id = 2", [])
+            .unwrap();
+        save_count_line(&conn, 2, 1, Some(1.0), Some(3)).unwrap();
+
+        let sessions = list_sessions(&conn).unwrap();
+        assert_eq!(
+            sessions.iter().map(|s| s.total_value).collect::<Vec<_>>(),
+            vec![None, Some(80.0)]
+        );
+
+        let report = valuation_report(&conn, 2, None).unwrap();
+        assert_eq!(report.total_value, 30.0);
+        assert_eq!(report.prior_total, 80.0);
+        assert_eq!(report.categories.len(), 1);
+        assert_eq!(report.categories[0].prior_value, 80.0);
+        assert_eq!(report.storage_groups[0].prior_value, 80.0);
+    }
+}
@@ -266,15 +266,582 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
           col10 TEXT
         );
 
+        CREATE TABLE IF NOT EXISTS item_preps (
+          prep_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER,
+          name TEXT,
+          yield_pct REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS yield_tests (
+          test_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER,
+          prep_id INTEGER,
+          test_date TEXT,
+          unit_id INTEGER,
+          gross_qty REAL,
+          trim_qty REAL,
+          waste_qty REAL,
+          net_qty REAL,
+          notes TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS inv_counts (
+          count_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER,
+          count_date TEXT,
+          qty REAL,
+          unit_id INTEGER,
+          notes TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS count_sessions (
+          session_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          name TEXT,
+          count_date TEXT,
+          status TEXT DEFAULT 'open',
+          notes TEXT,
+          created_at TEXT,
+          closed_at TEXT,
+          total_value REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS par_levels (
+          par_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER,
+          storage_type TEXT,
+          day_of_week INTEGER,
+          par_qty REAL,
+          unit_id INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS vendor_bids (
+          bid_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          vendor_id INTEGER,
+          item_id INTEGER,
+          price REAL,
+          unit_id INTEGER,
+          pack TEXT,
+          valid_from TEXT,
+          valid_to TEXT,
+          status TEXT DEFAULT 'open',
+          source TEXT,
+          created_at TEXT,
+          awarded_at TEXT,
+          applied_at TEXT,
+          expired_at TEXT,
+          replaced_price REAL,
+          replaced_status INTEGER,
+          replaced_pack TEXT,
+          added_price_row INTEGER,
+          demoted_vendors TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS price_alerts (
+          alert_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER,
+          vendor_id INTEGER,
+          old_price REAL,
+          new_price REAL,
+          pct_change REAL,
+          unit_id INTEGER,
+          source TEXT,
+          invoice_id INTEGER,
+          status TEXT DEFAULT 'new',
+          note TEXT,
+          created_at TEXT,
+          resolved_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS price_alert_lines (
+          alert_id INTEGER,
+          trans_id INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS invoice_charges (
+          charge_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          invoice_id INTEGER NOT NULL,
+          kind TEXT NOT NULL,
+          description TEXT,
+          amount REAL NOT NULL,
+          allocation TEXT NOT NULL DEFAULT 'default'
+        );
+
+        CREATE TABLE IF NOT EXISTS invoice_reconciliation (
+          invoice_id INTEGER PRIMARY KEY,
+          status TEXT NOT NULL,
+          lines_total REAL,
+          charges_total REAL,
+          credits_total REAL,
+          header_total REAL,
+          difference REAL,
+          issue_count INTEGER,
+          checked_at TEXT,
+          reviewed_at TEXT,
+          note TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS invoice_discrepancies (
+          discrepancy_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          invoice_id INTEGER NOT NULL,
+          trans_id INTEGER,
+          kind TEXT NOT NULL,
+          expected REAL,
+          actual REAL,
+          message TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS credit_memos (
+          credit_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          invoice_id INTEGER NOT NULL,
+          memo_no TEXT,
+          memo_date TEXT,
+          reason TEXT NOT NULL,
+          amount REAL NOT NULL,
+          trans_id INTEGER,
+          item_id INTEGER,
+          qty REAL,
+          unit_id INTEGER,
+          note TEXT,
+          created_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS accounting_periods (
+          period_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          name TEXT NOT NULL,
+          start_date TEXT NOT NULL,
+          end_date TEXT NOT NULL,
+          opening_session_id INTEGER,
+          closing_session_id INTEGER,
+          status TEXT DEFAULT 'open',
+          notes TEXT,
+          closed_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS period_revenue (
+          period_id INTEGER NOT NULL,
+          category TEXT NOT NULL,
+          amount REAL NOT NULL,
+          source TEXT DEFAULT 'manual',
+          PRIMARY KEY (period_id, category)
+        );
+
+        CREATE TABLE IF NOT EXISTS period_cogs (
+          period_id INTEGER NOT NULL,
+          category TEXT NOT NULL,
+          opening REAL,
+          purchases REAL,
+          closing REAL,
+          revenue REAL,
+          PRIMARY KEY (period_id, category)
+        );
+
+        CREATE TABLE IF NOT EXISTS cogs_category_map (
+          food_category TEXT PRIMARY KEY COLLATE NOCASE,
+          cogs_category TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS unit_aliases (
+          alias TEXT PRIMARY KEY COLLATE NOCASE,
+          unit_id INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS unit_standards (
+          unit_id INTEGER PRIMARY KEY,
+          standard TEXT,
+          dimension TEXT,
+          base_qty REAL,
+          source TEXT DEFAULT 'auto'
+        );
+
+        CREATE TABLE IF NOT EXISTS purchase_orders (
+          po_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          po_number TEXT,
+          vendor_id INTEGER,
+          status TEXT DEFAULT 'draft',
+          order_date TEXT,
+          expected_date TEXT,
+          notes TEXT,
+          created_at TEXT,
+          sent_at TEXT,
+          closed_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS po_lines (
+          po_line_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          po_id INTEGER,
+          item_id INTEGER,
+          unit_id INTEGER,
+          qty REAL,
+          price REAL,
+          received_qty REAL DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS po_receipts (
+          receipt_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          po_id INTEGER,
+          invoice_id INTEGER,
+          received_date TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS po_receipt_lines (
+          receipt_line_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          receipt_id INTEGER,
+          po_line_id INTEGER,
+          item_id INTEGER,
+          unit_id INTEGER,
+          qty REAL,
+          price REAL,
+          trans_id INTEGER,
+          flag TEXT,
+          note TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS pos_profiles (
+          profile_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          name TEXT UNIQUE,
+          item_col TEXT,
+          qty_col TEXT,
+          net_sales_col TEXT,
+          date_col TEXT,
+          category_col TEXT,
+          builtin INTEGER DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS pos_items (
+          pos_item_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          name TEXT UNIQUE COLLATE NOCASE,
+          category TEXT,
+          recipe_id INTEGER,
+          portions REAL DEFAULT 1,
+          ignored INTEGER DEFAULT 0
+        );
+
+        CREATE TABLE IF NOT EXISTS sales_imports (
+          import_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          profile_id INTEGER,
+          source_file TEXT,
+          period_start TEXT,
+          period_end TEXT,
+          imported_at TEXT,
+          row_count INTEGER,
+          total_qty REAL,
+          total_sales REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS sales (
+          sale_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          import_id INTEGER,
+          pos_item_id INTEGER,
+          sale_date TEXT,
+          qty REAL,
+          net_sales REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS conv_suggestion_rejects (
+          reject_id INTEGER PRIMARY KEY AUTOINCREMENT,
+          item_id INTEGER NOT NULL,
+          vendor_id INTEGER NOT NULL,
+          unit_id1 INTEGER NOT NULL,
+          unit_id2 INTEGER NOT NULL,
+          qty1 REAL,
+          qty2 REAL,
+          derived_from TEXT,
+          rejected_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS item_nutrition (
+          item_id INTEGER PRIMARY KEY,
+          basis_qty REAL NOT NULL,
+          basis_unit_id INTEGER NOT NULL,
+          calories REAL,
+          fat_g REAL,
+          sat_fat_g REAL,
+          trans_fat_g REAL,
+          cholesterol_mg REAL,
+          sodium_mg REAL,
+          carbs_g REAL,
+          fiber_g REAL,
+          sugars_g REAL,
+          added_sugars_g REAL,
+          protein_g REAL,
+          vitamin_d_mcg REAL,
+          calcium_mg REAL,
+          iron_mg REAL,
+          potassium_mg REAL,
+          source TEXT,
+          fdc_id INTEGER,
+          description TEXT,
+          updated_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS fdc_foods (
+          fdc_id INTEGER PRIMARY KEY,
+          description TEXT NOT NULL,
+          data_type TEXT,
+          calories REAL,
+          fat_g REAL,
+          sat_fat_g REAL,
+          trans_fat_g REAL,
+          cholesterol_mg REAL,
+          sodium_mg REAL,
+          carbs_g REAL,
+          fiber_g REAL,
+          sugars_g REAL,
+          added_sugars_g REAL,
+          protein_g REAL,
+          vitamin_d_mcg REAL,
+          calcium_mg REAL,
+          iron_mg REAL,
+          potassium_mg REAL,
+          imported_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS recipe_costs (
+          recipe_id INTEGER PRIMARY KEY,
+          servings REAL,
+          cost_per_portion REAL,
+          menu_price REAL,
+          food_cost_pct REAL,
+          margin REAL
+        );
+
         CREATE INDEX IF NOT EXISTS idx_convunit_item_vendor ON convunit(item_id, vendor_id);
         CREATE INDEX IF NOT EXISTS idx_recp_items_item ON recp_items(item_id);
+        CREATE INDEX IF NOT EXISTS idx_recp_inv_item ON recp_inv(item_id);
         CREATE INDEX IF NOT EXISTS idx_invoices_vendor ON invoices(vendor_id);
         CREATE INDEX IF NOT EXISTS idx_trans_invoice ON trans(invoice_id);
+        CREATE INDEX IF NOT EXISTS idx_item_preps_item ON item_preps(item_id);
+        CREATE INDEX IF NOT EXISTS idx_yield_tests_item ON yield_tests(item_id);
+        CREATE INDEX IF NOT EXISTS idx_inv_counts_item_date ON inv_counts(item_id, count_date);
+        CREATE INDEX IF NOT EXISTS idx_par_levels_item ON par_levels(item_id);
+        CREATE INDEX IF NOT EXISTS idx_vendor_bids_item ON vendor_bids(item_id);
+        CREATE INDEX IF NOT EXISTS idx_price_alerts_invoice ON price_alerts(invoice_id);
+        CREATE INDEX IF NOT EXISTS idx_price_alert_lines_alert ON price_alert_lines(alert_id);
+        CREATE INDEX IF NOT EXISTS idx_invoice_charges_invoice ON invoice_charges(invoice_id);
+        CREATE INDEX IF NOT EXISTS idx_invoice_discrepancies_invoice ON invoice_discrepancies(invoice_id);
+        CREATE INDEX IF NOT EXISTS idx_credit_memos_invoice ON credit_memos(invoice_id);
+        CREATE INDEX IF NOT EXISTS idx_unit_aliases_unit ON unit_aliases(unit_id);
+        CREATE INDEX IF NOT EXISTS idx_po_lines_po ON po_lines(po_id);
+        CREATE INDEX IF NOT EXISTS idx_po_receipts_po ON po_receipts(po_id);
+        CREATE INDEX IF NOT EXISTS idx_po_receipt_lines_receipt ON po_receipt_lines(receipt_id);
+        CREATE INDEX IF NOT EXISTS idx_sales_item_date ON sales(pos_item_id, sale_date);
+        CREATE INDEX IF NOT EXISTS idx_sales_import ON sales(import_id);
+        CREATE INDEX IF NOT EXISTS idx_conv_suggestion_rejects_item ON conv_suggestion_rejects(item_id, unit_id1, unit_id2);
+        CREATE INDEX IF NOT EXISTS idx_item_nutrition_fdc ON item_nutrition(fdc_id);
         "#,
     )
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN instructions TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN prev_price REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN food_category TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN storage_type TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN yield_qty REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN yield_unit_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recp_items ADD COLUMN sub_recipe_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN yield_pct REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recp_items ADD COLUMN prep_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN servings REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN portion_size REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN portion_unit_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN target_food_cost_pct REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN menu_price REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN shelf_order INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN density REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
     .and_then(
-        |_| match conn.execute("ALTER TABLE recipes ADD COLUMN instructions TEXT", []) {
+        |_| match conn.execute("ALTER TABLE items ADD COLUMN label_name TEXT", []) {
             Ok(_) => Ok(()),
             Err(err) => {
                 let msg = err.to_string();
@@ -287,7 +854,7 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
         },
     )
     .and_then(
-        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN prev_price REAL", []) {
+        |_| match conn.execute("ALTER TABLE conv_suggestions ADD COLUMN confidence REAL", []) {
             Ok(_) => Ok(()),
             Err(err) => {
                 let msg = err.to_string();
@@ -300,7 +867,7 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
         },
     )
     .and_then(
-        |_| match conn.execute("ALTER TABLE items ADD COLUMN food_category TEXT", []) {
+        |_| match conn.execute("ALTER TABLE conv_suggestions_safe ADD COLUMN confidence REAL", []) {
             Ok(_) => Ok(()),
             Err(err) => {
                 let msg = err.to_string();
@@ -313,7 +880,189 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
         },
     )
     .and_then(
-        |_| match conn.execute("ALTER TABLE items ADD COLUMN storage_type TEXT", []) {
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_count REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_size REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_unit_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_inner_unit_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_container TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_error TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_prices ADD COLUMN pack_source TEXT", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_counts ADD COLUMN session_id INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_counts ADD COLUMN purch_qty REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_counts ADD COLUMN unit_price REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE inv_counts ADD COLUMN value REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE trans ADD COLUMN alloc_amount REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE trans ADD COLUMN landed_price REAL", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE vendors ADD COLUMN prices_include_tax INTEGER", []) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let msg = err.to_string();
+                if msg.contains("duplicate column name") {
+                    Ok(())
+                } else {
+                    Err(msg)
+                }
+            }
+        },
+    )
+    .and_then(
+        |_| match conn.execute("ALTER TABLE vendors ADD COLUMN tax_rate REAL", []) {
             Ok(_) => Ok(()),
             Err(err) => {
                 let msg = err.to_string();
@@ -325,6 +1074,14 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
             }
         },
     )
+    .and_then(|_| {
+        conn.execute_batch(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_inv_counts_session ON inv_counts(session_id, item_id);
+            "#,
+        )
+        .map_err(|e| e.to_string())
+    })
     .and_then(|_| {
         conn.execute_batch(
             r#"
@@ -332,16 +1089,203 @@ pub fn init_db(conn: &Connection) -> Result<(), String> {
               key TEXT PRIMARY KEY,
               value TEXT
             );
+
+            CREATE TABLE IF NOT EXISTS data_versions (
+              name TEXT PRIMARY KEY,
+              version INTEGER NOT NULL DEFAULT 0,
+              built_version TEXT
+            );
             "#,
         )
         .map_err(|e| e.to_string())
     })
+    .and_then(|_| {
+        // Every write to what a cache is built from bumps the cache's version.
+        for (name, tables) in VERSIONED_CACHES {
+            conn.execute("INSERT OR IGNORE INTO data_versions (name) VALUES (?1)", [name])
+                .map_err(|e| e.to_string())?;
+            for table in *tables {
+                for op in ["INSERT", "UPDATE", "DELETE"] {
+                    conn.execute_batch(&format!(
+                        "CREATE TRIGGER IF NOT EXISTS {name}_{table}_{event} AFTER {op} ON {table}
+                         BEGIN
+                           UPDATE data_versions SET version = version + 1 WHERE name = '{name}';
+                         END;",
+                        event = op.to_lowercase()
+                    ))
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    })
+    .and_then(|_| {
+        for (name, item, qty, net_sales, date, category) in crate::sales::BUILTIN_PROFILES {
+            conn.execute(
+                "INSERT OR IGNORE INTO pos_profiles (name, item_col, qty_col, net_sales_col, date_col, category_col, builtin)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1)",
+                rusqlite::params![name, item, qty, net_sales, date, category],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    })
+    .and_then(|_| {
+        // Units imported before standard conversions existed.
+        let pending: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM units) AND NOT EXISTS (SELECT 1 FROM unit_standards)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if pending {
+            crate::units::refresh_standard_units(conn)?;
+        }
+        Ok(())
+    })
+    .and_then(|_| {
+        // Awards whose window has started or ended since the last open.
+        crate::bids::apply_awards(conn).map(|_| ())
+    })
+    .and_then(|_| {
+        // Pack descriptions imported or edited since they were last read.
+        let stale: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM inv_prices WHERE pack_source IS NOT COALESCE(TRIM(pack), ''))",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if stale {
+            crate::pack::refresh_packs(conn, false)?;
+        }
+        Ok(())
+    })
+}
+
+/// Stored results rebuilt only when their inputs change, with the tables
+/// they are built from.
+const VERSIONED_CACHES: &[(&str, &[&str])] = &[
+    (
+        "recipe_costs",
+        &[
+            "units",
+            "unit_standards",
+            "items",
+            "item_preps",
+            "recipes",
+            "recp_items",
+            "recp_inv",
+            "convunit",
+            "inv_units",
+            "inv_prices",
+            "vendor_bids",
+            "trans",
+            "settings",
+        ],
+    ),
+    (
+        "suggestions",
+        &[
+            "units",
+            "unit_standards",
+            "unit_aliases",
+            "items",
+            "recipes",
+            "recp_items",
+            "recp_inv",
+            "convunit",
+            "inv_units",
+            "inv_prices",
+            "conv_suggestion_rejects",
+            "trans",
+            "settings",
+        ],
+    ),
+];
+
+/// The version a cache's inputs are at, and the one it was last built from.
+pub fn cache_versions(conn: &Connection, name: &str) -> Result<(i64, Option<String>), String> {
+    conn.query_row(
+        "SELECT version, built_version FROM data_versions WHERE name = ?1",
+        [name],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn mark_cache_built(conn: &Connection, name: &str, built_version: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE data_versions SET built_version = ?2 WHERE name = ?1",
+        [name, built_version],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
+/// Columns users set on imported rows, as (table, key columns, columns).
+/// `clear_tables` keeps a copy that `restore_user_columns` puts back on the
+/// rows the import brings back.
+const USER_COLUMNS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "items",
+        &["item_id"],
+        &["yield_pct", "shelf_order", "density", "label_name"],
+    ),
+    (
+        "recipes",
+        &["recipe_id"],
+        &[
+            "yield_qty",
+            "yield_unit_id",
+            "servings",
+            "portion_size",
+            "portion_unit_id",
+            "target_food_cost_pct",
+            "menu_price",
+        ],
+    ),
+    (
+        "recp_items",
+        &["recipe_id", "recp_item_id"],
+        &["prep_id", "sub_recipe_id"],
+    ),
+    (
+        "vendors",
+        &["vendor_id"],
+        &["prices_include_tax", "tax_rate"],
+    ),
+];
+
+/// Clears the tables loaded from the exports before a full import or a
+/// revert. What users maintain against imported ids is kept so it re-attaches
+/// when the same ids come back: the `USER_COLUMNS` of imported rows, and
+/// tables such as unit aliases, manually set unit standards, item preps and
+/// yield tests, item nutrition, rejected conversion suggestions, price
+/// alerts, count sessions, par levels, vendor bids, invoice charges, credit
+/// memos and reconciliation reviews, purchase orders with the invoices their
+/// receipts wrote, accounting periods, settings, COGS category mappings, POS
+/// profiles, POS items and sales imports. Once the import has run, call
+/// `restore_user_columns` and then `prune_orphans`. Awarded bids are marked
+/// unapplied because the price list comes back from the exports; run
+/// `bids::apply_awards` afterwards to put them back into effect, and
+/// `landed::allocate_kept` to spread kept charges over the new lines.
 pub fn clear_tables(conn: &Connection) -> Result<(), String> {
+    for (table, keys, columns) in USER_COLUMNS {
+        conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS temp.kept_{table};
+             CREATE TEMP TABLE kept_{table} AS SELECT {keys}, {columns} FROM main.{table} WHERE {any};",
+            keys = keys.join(", "),
+            columns = columns.join(", "),
+            any = columns.iter().map(|c| format!("{} IS NOT NULL", c)).collect::<Vec<_>>().join(" OR ")
+        ))
+        .map_err(|e| e.to_string())?;
+    }
     conn.execute_batch(
         r#"
         DELETE FROM units;
+        DELETE FROM unit_standards WHERE COALESCE(source, 'auto') = 'auto';
         DELETE FROM items;
         DELETE FROM vendors;
         DELETE FROM recipes;
@@ -355,10 +1299,72 @@ pub fn clear_tables(conn: &Connection) -> Result<(), String> {
         DELETE FROM missing_edges;
         DELETE FROM missing_purch_unit;
         DELETE FROM missing_data_report;
-        DELETE FROM invoices;
-        DELETE FROM trans;
+        DELETE FROM invoices WHERE invoice_id NOT IN (SELECT invoice_id FROM po_receipts WHERE invoice_id IS NOT NULL);
+        DELETE FROM trans WHERE invoice_id NOT IN (SELECT invoice_id FROM po_receipts WHERE invoice_id IS NOT NULL);
         DELETE FROM recp_inv;
         DELETE FROM bids;
+        UPDATE vendor_bids
+           SET applied_at = NULL, replaced_price = NULL, replaced_status = NULL, replaced_pack = NULL,
+               added_price_row = NULL, demoted_vendors = NULL;
+        "#,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Puts the `USER_COLUMNS` values `clear_tables` kept back on the rows the
+/// import brought back.
+pub fn restore_user_columns(conn: &Connection) -> Result<(), String> {
+    for (table, keys, columns) in USER_COLUMNS {
+        let matches = keys
+            .iter()
+            .map(|k| format!("k.{k} = {table}.{k}"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        conn.execute_batch(&format!(
+            "UPDATE {table} SET ({columns}) = (SELECT {columns} FROM temp.kept_{table} k WHERE {matches})
+             WHERE EXISTS (SELECT 1 FROM temp.kept_{table} k WHERE {matches});
+             DROP TABLE temp.kept_{table};",
+            columns = columns.join(", "),
+        ))
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Drops the rows `clear_tables` kept whose unit, item or invoice line
+/// didn't come back with the import, and unmaps POS items from recipes that
+/// are gone.
+pub fn prune_orphans(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        DELETE FROM unit_aliases WHERE NOT EXISTS (SELECT 1 FROM units u WHERE u.unit_id = unit_aliases.unit_id);
+        DELETE FROM unit_standards WHERE NOT EXISTS (SELECT 1 FROM units u WHERE u.unit_id = unit_standards.unit_id);
+        DELETE FROM item_nutrition
+         WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = item_nutrition.item_id)
+            OR NOT EXISTS (SELECT 1 FROM units u WHERE u.unit_id = item_nutrition.basis_unit_id);
+        DELETE FROM item_preps WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = item_preps.item_id);
+        DELETE FROM yield_tests WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = yield_tests.item_id);
+        DELETE FROM inv_counts WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = inv_counts.item_id);
+        DELETE FROM par_levels WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = par_levels.item_id);
+        DELETE FROM invoice_charges
+         WHERE NOT EXISTS (SELECT 1 FROM invoices i WHERE i.invoice_id = invoice_charges.invoice_id);
+        DELETE FROM credit_memos
+         WHERE NOT EXISTS (SELECT 1 FROM invoices i WHERE i.invoice_id = credit_memos.invoice_id);
+        DELETE FROM invoice_reconciliation
+         WHERE NOT EXISTS (SELECT 1 FROM invoices i WHERE i.invoice_id = invoice_reconciliation.invoice_id);
+        DELETE FROM invoice_discrepancies
+         WHERE NOT EXISTS (SELECT 1 FROM invoices i WHERE i.invoice_id = invoice_discrepancies.invoice_id);
+        DELETE FROM vendor_bids
+         WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = vendor_bids.item_id)
+            OR NOT EXISTS (SELECT 1 FROM vendors v WHERE v.vendor_id = vendor_bids.vendor_id);
+        DELETE FROM conv_suggestion_rejects
+         WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = conv_suggestion_rejects.item_id);
+        DELETE FROM price_alerts WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.item_id = price_alerts.item_id);
+        DELETE FROM price_alert_lines
+         WHERE NOT EXISTS (SELECT 1 FROM price_alerts a WHERE a.alert_id = price_alert_lines.alert_id)
+            OR NOT EXISTS (SELECT 1 FROM trans t WHERE t.trans_id = price_alert_lines.trans_id);
+        UPDATE pos_items SET recipe_id = NULL
+         WHERE recipe_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM recipes r WHERE r.recipe_id = pos_items.recipe_id);
         "#,
     )
     .map_err(|e| e.to_string())
@@ -381,3 +1387,167 @@ pub fn file_exists(path: &Path) -> bool {
 pub fn display_path(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{clear_tables, init_db, prune_orphans, restore_user_columns};
+    use rusqlite::Connection;
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn reimport_keeps_user_data_for_returning_ids() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (1, 'Each', 'Each'), (2, 'Bunch', 'Bunches');
+             INSERT INTO items (item_id, name, yield_pct, shelf_order, density, label_name)
+             VALUES (1, 'Parsley', 60, 4, NULL, NULL), (2, 'Dill', 70, 5, 0.25, 'Fresh Dill');
+             INSERT INTO item_preps (prep_id, item_id, name, yield_pct) VALUES (1, 1, 'Picked', 50), (2, 2, 'Picked', 65);
+             INSERT INTO yield_tests (item_id, prep_id, gross_qty, net_qty) VALUES (1, 1, 10, 5), (2, 2, 10, 6.5);
+             INSERT INTO recipes (recipe_id, name, yield_qty, yield_unit_id) VALUES (1, 'Salsa Verde', 2, 2), (3, 'Herb Oil', 1, 1);
+             INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty, prep_id) VALUES (1, 1, 2, 2, 1, 2);
+             INSERT INTO recp_items (recipe_id, recp_item_id, unit_id, qty, sub_recipe_id) VALUES (1, 2, 1, 1, 3);
+             INSERT INTO unit_aliases (alias, unit_id) VALUES ('ea', 1), ('bn', 2);
+             INSERT INTO unit_standards (unit_id, standard, dimension, base_qty, source) VALUES (2, 'oz', 'weight', 28.349523125, 'manual');
+             INSERT INTO item_nutrition (item_id, basis_qty, basis_unit_id) VALUES (1, 1, 1), (2, 1, 2);
+             INSERT INTO count_sessions (session_id, name, count_date) VALUES (1, 'Week 1', '2026-03-01');
+             INSERT INTO inv_counts (session_id, item_id, count_date, qty) VALUES (1, 1, '2026-03-01', 2), (1, 2, '2026-03-01', 3);
+             INSERT INTO par_levels (item_id, par_qty, unit_id) VALUES (1, 4, 1), (2, 6, 2);
+             INSERT INTO invoices (invoice_id, vendor_id, total) VALUES (7, 1, 10), (-1, 1, 12);
+             INSERT INTO trans (trans_id, invoice_id, item_id, qty) VALUES (70, 7, 2, 1), (-1, -1, 2, 3);
+             INSERT INTO purchase_orders (po_id, vendor_id, status) VALUES (1, 1, 'closed');
+             INSERT INTO po_receipts (po_id, invoice_id) VALUES (1, -1);
+             INSERT INTO vendors (vendor_id, name, prices_include_tax, tax_rate) VALUES (1, 'Sysco', 1, 8.5);
+             INSERT INTO invoice_charges (invoice_id, kind, amount) VALUES (7, 'fuel', 3), (-1, 'freight', 5);
+             INSERT INTO credit_memos (invoice_id, reason, amount) VALUES (7, 'short', 4), (-1, 'return', 6);
+             INSERT INTO invoice_reconciliation (invoice_id, status) VALUES (7, 'reviewed'), (-1, 'reviewed');",
+        )
+        .unwrap();
+
+        clear_tables(&conn).unwrap();
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM unit_aliases"), 2);
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (2, 'Bunch', 'Bunches');
+             INSERT INTO items (item_id, name) VALUES (2, 'Dill');
+             INSERT INTO vendors (vendor_id, name) VALUES (1, 'Sysco');
+             INSERT INTO recipes (recipe_id, name) VALUES (1, 'Salsa Verde'), (3, 'Herb Oil');
+             INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty) VALUES (1, 1, 2, 2, 1), (1, 2, 0, 1, 1);",
+        )
+        .unwrap();
+        restore_user_columns(&conn).unwrap();
+        prune_orphans(&conn).unwrap();
+
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT CAST(yield_pct AS INTEGER) FROM items WHERE item_id = 2"
+            ),
+            70
+        );
+        assert_eq!(
+            count(&conn, "SELECT shelf_order FROM items WHERE item_id = 2"),
+            5
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT CAST(density * 100 AS INTEGER) FROM items WHERE item_id = 2"
+            ),
+            25
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM items WHERE label_name = 'Fresh Dill'"
+            ),
+            1
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT prep_id FROM recp_items WHERE recipe_id = 1 AND recp_item_id = 1"
+            ),
+            2
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT sub_recipe_id FROM recp_items WHERE recipe_id = 1 AND recp_item_id = 2"
+            ),
+            3
+        );
+        assert_eq!(count(&conn, "SELECT CAST(yield_qty AS INTEGER) + yield_unit_id FROM recipes WHERE recipe_id = 1"), 4);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM item_preps"), 1);
+        assert_eq!(
+            count(&conn, "SELECT COUNT(*) FROM yield_tests WHERE item_id = 2"),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM yield_tests"), 1);
+
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM unit_aliases WHERE alias = 'bn'"
+            ),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM unit_aliases"), 1);
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM unit_standards WHERE unit_id = 2 AND source = 'manual'"
+            ),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM item_nutrition"), 1);
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT COUNT(*) FROM item_nutrition WHERE item_id = 2"
+            ),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM count_sessions"), 1);
+        assert_eq!(
+            count(&conn, "SELECT COUNT(*) FROM inv_counts WHERE item_id = 2"),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM inv_counts"), 1);
+        assert_eq!(
+            count(&conn, "SELECT COUNT(*) FROM par_levels WHERE item_id = 2"),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM par_levels"), 1);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM purchase_orders"), 1);
+        assert_eq!(
+            count(&conn, "SELECT COALESCE(SUM(invoice_id), 0) FROM invoices"),
+            -1
+        );
+        assert_eq!(
+            count(&conn, "SELECT COALESCE(SUM(trans_id), 0) FROM trans"),
+            -1
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT CAST(tax_rate * 10 AS INTEGER) + prices_include_tax FROM vendors"
+            ),
+            86
+        );
+        assert_eq!(
+            count(&conn, "SELECT CAST(amount AS INTEGER) FROM invoice_charges"),
+            5
+        );
+        assert_eq!(
+            count(&conn, "SELECT CAST(amount AS INTEGER) FROM credit_memos"),
+            6
+        );
+        assert_eq!(
+            count(&conn, "SELECT invoice_id FROM invoice_reconciliation"),
+            -1
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use rusqlite::{Connection, OptionalExtension};
+
+/// Typical densities of common ingredients in grams per millilitre, matched
+/// against item names by whole words in any order ("SUGAR BROWN" is brown
+/// sugar). The match with the most words wins; ties go to the earlier row.
+const REFERENCE_DENSITIES: &[(&str, f64)] = &[
+    ("bread flour", 0.55),
+    ("cake flour", 0.48),
+    ("pastry flour", 0.47),
+    ("whole wheat flour", 0.51),
+    ("rice flour", 0.62),
+    ("almond flour", 0.40),
+    ("flour", 0.53),
+    ("brown sugar", 0.93),
+    ("powdered sugar", 0.51),
+    ("confectioners sugar", 0.51),
+    ("icing sugar", 0.51),
+    ("sugar", 0.85),
+    ("kosher salt", 0.62),
+    ("salt", 1.22),
+    ("baking soda", 0.93),
+    ("baking powder", 0.81),
+    ("corn starch", 0.54),
+    ("cornstarch", 0.54),
+    ("cocoa", 0.42),
+    ("cornmeal", 0.65),
+    ("bread crumbs", 0.45),
+    ("breadcrumbs", 0.45),
+    ("panko", 0.25),
+    ("rolled oats", 0.38),
+    ("oats", 0.38),
+    ("rice", 0.85),
+    ("chocolate chips", 0.72),
+    ("grated parmesan", 0.42),
+    ("shredded cheese", 0.47),
+    ("peanut butter", 1.09),
+    ("ice cream", 0.55),
+    ("sour cream", 1.01),
+    ("heavy cream", 1.01),
+    ("cream", 1.01),
+    ("butter", 0.96),
+    ("buttermilk", 1.03),
+    ("milk", 1.03),
+    ("yogurt", 1.03),
+    ("mayonnaise", 0.96),
+    ("ketchup", 1.15),
+    ("soy sauce", 1.15),
+    ("honey", 1.42),
+    ("maple syrup", 1.32),
+    ("corn syrup", 1.38),
+    ("molasses", 1.40),
+    ("vinegar", 1.01),
+    ("lemon juice", 1.03),
+    ("lime juice", 1.03),
+    ("juice", 1.04),
+    ("olive oil", 0.92),
+    ("oil", 0.92),
+    ("wine", 0.99),
+    ("stock", 1.0),
+    ("broth", 1.0),
+    ("water", 1.0),
+];
+
+/// An item's weight per volume and where it came from.
+pub struct Density {
+    pub g_per_ml: f64,
+    /// "item" when entered on the item, otherwise "reference: <name>".
+    pub source: String,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            w.strip_suffix('s')
+                .filter(|s| s.len() > 2)
+                .unwrap_or(w)
+                .to_string()
+        })
+        .collect()
+}
+
+/// The reference ingredient an item name matches, with its density.
+pub fn reference_density(item_name: &str) -> Option<(&'static str, f64)> {
+    let name = words(item_name);
+    let mut best: Option<(&'static str, f64, usize)> = None;
+    for (ingredient, density) in REFERENCE_DENSITIES {
+        let wanted = words(ingredient);
+        if wanted.iter().all(|w| name.contains(w))
+            && best.map(|b| wanted.len() > b.2).unwrap_or(true)
+        {
+            best = Some((ingredient, *density, wanted.len()));
+        }
+    }
+    best.map(|(ingredient, density, _)| (ingredient, density))
+}
+
+/// Whether items without a density of their own fall back to the reference
+/// table. On unless turned off.
+pub fn use_reference_densities(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = 'reference_densities'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .map(|v| v != "0")
+    .unwrap_or(true)
+}
+
+pub fn set_use_reference_densities(conn: &Connection, on: bool) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES ('reference_densities', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [if on { "1" } else { "0" }],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The item's own density, otherwise its reference density when those are
+/// in use.
+pub fn item_density(conn: &Connection, item_id: i64) -> Option<Density> {
+    let (name, own): (String, Option<f64>) = conn
+        .query_row(
+            "SELECT COALESCE(name, ''), density FROM items WHERE item_id = ?1",
+            [item_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .ok()
+        .flatten()?;
+    if let Some(g_per_ml) = own.filter(|d| *d > 0.0) {
+        return Some(Density {
+            g_per_ml,
+            source: "item".to_string(),
+        });
+    }
+    if !use_reference_densities(conn) {
+        return None;
+    }
+    reference_density(&name).map(|(ingredient, g_per_ml)| Density {
+        g_per_ml,
+        source: format!("reference: {}", ingredient),
+    })
+}
+
+/// Sets or clears (`None`) an item's density in grams per millilitre.
+pub fn set_item_density(
+    conn: &Connection,
+    item_id: i64,
+    g_per_ml: Option<f64>,
+) -> Result<(), String> {
+    if g_per_ml.is_some_and(|d| d <= 0.0 || d > 5.0) {
+        return Err("Density must be more than 0 and at most 5 g/ml".to_string());
+    }
+    let updated = conn
+        .execute(
+            "UPDATE items SET density = ?2 WHERE item_id = ?1",
+            rusqlite::params![item_id, g_per_ml],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(())
+}
+
+/// A conversion edge from a standard volume unit to a standard weight unit
+/// for the given density. Standard conversions connect it to every other
+/// weight and volume. `None` when no standard units are set up.
+pub fn density_edge(
+    conn: &Connection,
+    density: &Density,
+) -> Result<Option<(i64, i64, f64, f64)>, String> {
+    let pick = |dimension: &str| {
+        conn.query_row(
+            "SELECT unit_id, base_qty FROM unit_standards
+             WHERE dimension = ?1 AND base_qty > 0
+             ORDER BY base_qty = 1 DESC, unit_id LIMIT 1",
+            [dimension],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+    };
+    let (Some((volume, ml)), Some((weight, g))) = (pick("volume")?, pick("weight")?) else {
+        return Ok(None);
+    };
+    // One volume unit holds `ml` millilitres, which weigh `ml * density` grams.
+    Ok(Some((volume, weight, g, ml * density.g_per_ml)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reference_density;
+
+    #[test]
+    fn reference_densities_match_whole_words_in_any_order() {
+        assert_eq!(
+            reference_density("SUGAR BROWN LIGHT"),
+            Some(("brown sugar", 0.93))
+        );
+        assert_eq!(
+            reference_density("Flour, All Purpose 50#"),
+            Some(("flour", 0.53))
+        );
+        assert_eq!(
+            reference_density("Flour Bread King Arthur"),
+            Some(("bread flour", 0.55))
+        );
+        assert_eq!(
+            reference_density("Peanut Butter Creamy"),
+            Some(("peanut butter", 1.09))
+        );
+        assert_eq!(
+            reference_density("Oats, Rolled"),
+            Some(("rolled oats", 0.38))
+        );
+        assert_eq!(reference_density("Saltines"), None);
+        assert_eq!(reference_density("Chicken Breast"), None);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::costing::{
+    cost_recipe, default_purch_unit, item_price, sub_recipe_for_line, CostContext,
+};
+use crate::price_alerts::per_purch_unit;
+use crate::pricing::recipe_pricing;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The price change to evaluate. Prices are per the item's purchase unit.
+pub enum PriceChange {
+    Item {
+        item_id: i64,
+        new_price: f64,
+    },
+    /// Every item on the invoice at its invoiced price.
+    Invoice(i64),
+    /// A percentage change for all items, optionally narrowed to a food
+    /// category and/or the items currently bought from a vendor.
+    Percent {
+        pct: f64,
+        category: Option<String>,
+        vendor_id: Option<i64>,
+    },
+}
+
+pub struct ChangedItem {
+    pub item_id: i64,
+    pub item_name: String,
+    pub unit_name: String,
+    pub old_price: f64,
+    pub new_price: f64,
+}
+
+/// Old vs new cost of one recipe. Portion costs and food-cost % are `None`
+/// when the recipe has no servings, no menu price or missing line costs.
+pub struct RecipeImpact {
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    /// True when the recipe uses a changed item itself rather than only
+    /// through a sub-recipe.
+    pub direct: bool,
+    pub old_cost: f64,
+    pub new_cost: f64,
+    pub cost_change: f64,
+    pub cost_change_pct: Option<f64>,
+    pub old_portion_cost: Option<f64>,
+    pub new_portion_cost: Option<f64>,
+    pub menu_price: Option<f64>,
+    pub old_food_cost_pct: Option<f64>,
+    pub new_food_cost_pct: Option<f64>,
+    pub food_cost_pct_change: Option<f64>,
+    pub missing_costs: i64,
+}
+
+pub struct ImpactReport {
+    pub description: String,
+    pub items: Vec<ChangedItem>,
+    pub recipes: Vec<RecipeImpact>,
+    /// Items that couldn't be priced, with the reason.
+    pub skipped: Vec<String>,
+}
+
+fn item_name(conn: &Connection, item_id: i64) -> String {
+    conn.query_row(
+        "SELECT name FROM items WHERE item_id = ?1",
+        [item_id],
+        |row| row.get(0),
+    )
+    .unwrap_or_else(|_| format!("Item {}", item_id))
+}
+
+fn unit_name(conn: &Connection, unit_id: Option<i64>) -> String {
+    unit_id
+        .and_then(|id| {
+            conn.query_row("SELECT sing FROM units WHERE unit_id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .ok()
+        })
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn changed_item(conn: &Connection, item_id: i64, old_price: f64, new_price: f64) -> ChangedItem {
+    ChangedItem {
+        item_id,
+        item_name: item_name(conn, item_id),
+        unit_name: unit_name(conn, default_purch_unit(conn, item_id)),
+        old_price,
+        new_price,
+    }
+}
+
+/// Resolves a change into the items it touches (current vs new price per
+/// purchase unit), plus a description and the items that were skipped.
+fn changed_items(
+    conn: &Connection,
+    change: &PriceChange,
+) -> Result<(String, Vec<ChangedItem>, Vec<String>), String> {
+    let mut items = Vec::new();
+    let mut skipped = Vec::new();
+    let description = match change {
+        PriceChange::Item { item_id, new_price } => {
+            let (old, _) = item_price(conn, *item_id)
+                .ok_or_else(|| format!("{} has no current price", item_name(conn, *item_id)))?;
+            let item = changed_item(conn, *item_id, old, *new_price);
+            let description = format!(
+                "{} at {:.2} per {}",
+                item.item_name, new_price, item.unit_name
+            );
+            items.push(item);
+            description
+        }
+        PriceChange::Invoice(invoice_id) => {
+            let invoice_no: String = conn
+                .query_row(
+                    "SELECT COALESCE(invoice_no, CAST(invoice_id AS TEXT)) FROM invoices WHERE invoice_id = ?1",
+                    [invoice_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+            let mut stmt = conn
+                .prepare(
+                    "SELECT item_id, COALESCE(vendor_id, 0), COALESCE(qty, 0), price, unit_id FROM trans
+                     WHERE invoice_id = ?1 AND item_id IS NOT NULL AND price > 0
+                     ORDER BY trans_id",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([invoice_id], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, f64>(2)?,
+                        row.get::<_, f64>(3)?,
+                        row.get::<_, Option<i64>>(4)?,
+                    ))
+                })
+                .map_err(|e| e.to_string())?;
+            // Spend and price per purchase unit, weighted by the quantity bought.
+            let mut totals: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+            for row in rows {
+                let (item_id, vendor_id, qty, price, unit_id) = row.map_err(|e| e.to_string())?;
+                match per_purch_unit(conn, item_id, vendor_id, unit_id, price) {
+                    Some((unit_price, _)) => {
+                        let weight = if qty > 0.0 {
+                            qty * price / unit_price
+                        } else {
+                            1.0
+                        };
+                        let entry = totals.entry(item_id).or_insert((0.0, 0.0));
+                        entry.0 += unit_price * weight;
+                        entry.1 += weight;
+                    }
+                    None => skipped.push(format!(
+                        "{}: no conversion from the invoiced unit to the purchase unit",
+                        item_name(conn, item_id)
+                    )),
+                }
+            }
+            for (item_id, (spend, qty)) in totals {
+                match item_price(conn, item_id) {
+                    Some((old, _)) if qty > 0.0 => {
+                        items.push(changed_item(conn, item_id, old, spend / qty))
+                    }
+                    _ => skipped.push(format!("{}: no current price", item_name(conn, item_id))),
+                }
+            }
+            format!("Invoice #{}", invoice_no)
+        }
+        PriceChange::Percent {
+            pct,
+            category,
+            vendor_id,
+        } => {
+            if *pct <= -100.0 {
+                return Err("A price can't drop by 100% or more".to_string());
+            }
+            let mut stmt = conn
+                .prepare(
+                    "SELECT item_id FROM items
+                     WHERE ?1 IS NULL OR COALESCE(food_category, '') = ?1 COLLATE NOCASE
+                     ORDER BY item_id",
+                )
+                .map_err(|e| e.to_string())?;
+            let ids = stmt
+                .query_map([category.as_deref()], |row| row.get::<_, i64>(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            for item_id in ids {
+                let Some((old, price_vendor)) = item_price(conn, item_id) else {
+                    continue;
+                };
+                if vendor_id.is_some_and(|v| v != price_vendor) {
+                    continue;
+                }
+                items.push(changed_item(conn, item_id, old, old * (1.0 + pct / 100.0)));
+            }
+            let mut description = format!("{:+}% on ", pct);
+            match category {
+                Some(c) => description.push_str(&format!("{} items", c)),
+                None => description.push_str("all items"),
+            }
+            if let Some(v) = vendor_id {
+                let vendor: String = conn
+                    .query_row(
+                        "SELECT name FROM vendors WHERE vendor_id = ?1",
+                        [v],
+                        |row| row.get(0),
+                    )
+                    .unwrap_or_else(|_| format!("vendor {}", v));
+                description.push_str(&format!(" bought from {}", vendor));
+            }
+            description
+        }
+    };
+    Ok((description, items, skipped))
+}
+
+/// Every recipe that uses one of `item_ids`, directly or through
+/// sub-recipes. The flag is true for direct use.
+fn recipes_using(conn: &Connection, item_ids: &HashSet<i64>) -> Result<HashMap<i64, bool>, String> {
+    let mut stmt = conn
+        .prepare("SELECT recipe_id, sub_recipe_id, COALESCE(item_id, 0) FROM recp_items")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut affected: HashMap<i64, bool> = HashMap::new();
+    let mut parents: HashMap<i64, Vec<i64>> = HashMap::new();
+    for row in rows {
+        let (recipe_id, sub_id, item_id) = row.map_err(|e| e.to_string())?;
+        match sub_recipe_for_line(conn, recipe_id, sub_id, item_id) {
+            Some(sub_id) => parents.entry(sub_id).or_default().push(recipe_id),
+            None if item_ids.contains(&item_id) => {
+                affected.insert(recipe_id, true);
+            }
+            None => {}
+        }
+    }
+    let mut queue: Vec<i64> = affected.keys().copied().collect();
+    while let Some(recipe_id) = queue.pop() {
+        for &parent in parents.get(&recipe_id).into_iter().flatten() {
+            if let Entry::Vacant(e) = affected.entry(parent) {
+                e.insert(false);
+                queue.push(parent);
+            }
+        }
+    }
+    Ok(affected)
+}
+
+/// Costs every affected recipe at current prices and again with the change
+/// applied, using the regular costing engine for both passes. Recipes are
+/// sorted by the change in food-cost %, then by the change in batch cost.
+pub fn cost_impact(conn: &Connection, change: &PriceChange) -> Result<ImpactReport, String> {
+    let (description, items, skipped) = changed_items(conn, change)?;
+    let factors: HashMap<i64, f64> = items
+        .iter()
+        .filter(|i| i.old_price > 0.0)
+        .map(|i| (i.item_id, i.new_price / i.old_price))
+        .collect();
+    let item_ids: HashSet<i64> = factors.keys().copied().collect();
+    let affected = recipes_using(conn, &item_ids)?;
+
+    let mut old_ctx = CostContext::default();
+    let mut new_ctx = CostContext::with_price_factors(factors);
+    let mut recipes = Vec::new();
+    for (recipe_id, direct) in affected {
+        let recipe_name: String = conn
+            .query_row(
+                "SELECT name FROM recipes WHERE recipe_id = ?1",
+                [recipe_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_else(|| format!("Recipe {}", recipe_id));
+        let old = cost_recipe(conn, recipe_id, &mut old_ctx)?;
+        let new = cost_recipe(conn, recipe_id, &mut new_ctx)?;
+        let old_pricing = recipe_pricing(conn, recipe_id, old.total_cost, old.missing_costs)?;
+        let new_pricing = recipe_pricing(conn, recipe_id, new.total_cost, new.missing_costs)?;
+        let cost_change = new.total_cost - old.total_cost;
+        recipes.push(RecipeImpact {
+            recipe_id,
+            recipe_name,
+            direct,
+            old_cost: old.total_cost,
+            new_cost: new.total_cost,
+            cost_change,
+            cost_change_pct: (old.total_cost > 0.0).then(|| cost_change / old.total_cost * 100.0),
+            old_portion_cost: old_pricing.cost_per_portion,
+            new_portion_cost: new_pricing.cost_per_portion,
+            menu_price: new_pricing.menu_price,
+            old_food_cost_pct: old_pricing.food_cost_pct,
+            new_food_cost_pct: new_pricing.food_cost_pct,
+            food_cost_pct_change: match (old_pricing.food_cost_pct, new_pricing.food_cost_pct) {
+                (Some(o), Some(n)) => Some(n - o),
+                _ => None,
+            },
+            missing_costs: new.missing_costs,
+        });
+    }
+    recipes.sort_by(|a, b| {
+        let fc = |r: &RecipeImpact| r.food_cost_pct_change.map(f64::abs).unwrap_or(-1.0);
+        fc(b)
+            .total_cmp(&fc(a))
+            .then(b.cost_change.abs().total_cmp(&a.cost_change.abs()))
+            .then_with(|| a.recipe_name.cmp(&b.recipe_name))
+    });
+
+    Ok(ImpactReport {
+        description,
+        items,
+        recipes,
+        skipped,
+    })
+}
@@ -1,7 +1,8 @@
 use crate::db::{display_path, file_exists};
 use csv::StringRecord;
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
 const INV_BASE_COLS: usize = 32;
@@ -51,12 +52,15 @@ fn is_header(record: &StringRecord, expected: &str) -> bool {
 }
 
 pub fn import_all(conn: &Connection, base: &Path, db_path: &Path) -> Result<ImportSummary, String> {
-    let mut summary = ImportSummary::default();
-    summary.db_path = display_path(db_path);
+    let mut summary = ImportSummary {
+        db_path: display_path(db_path),
+        ..Default::default()
+    };
 
     let units_path = base.join("Units.csv");
     if file_exists(&units_path) {
         summary.units = import_units(conn, &units_path, &mut summary.warnings)?;
+        crate::units::refresh_standard_units(conn)?;
     } else {
         summary.warnings.push(format!(
             "Missing Units.csv at {}",
@@ -179,6 +183,19 @@ pub fn import_all(conn: &Connection, base: &Path, db_path: &Path) -> Result<Impo
     let bids_path = base.join("Bids.csv");
     if file_exists(&bids_path) {
         summary.bids = import_bids(conn, &bids_path, &mut summary.warnings)?;
+        match crate::bids::import_legacy_bids(conn) {
+            Ok((bids, skipped)) => {
+                if let Some(first) = skipped.first() {
+                    summary.warnings.push(format!(
+                        "Bids.csv: {} bids loaded, {} rows skipped ({})",
+                        bids,
+                        skipped.len(),
+                        first
+                    ));
+                }
+            }
+            Err(err) => summary.warnings.push(err),
+        }
     }
 
     if summary.inv_prices == 0 && summary.trans > 0 {
@@ -221,7 +238,7 @@ fn import_units(
     let mut count = 0usize;
     for result in rdr.records() {
         let record = result.map_err(|e| e.to_string())?;
-        if record.len() == 0 {
+        if record.is_empty() {
             continue;
         }
         if is_header(&record, "unitid") {
@@ -318,7 +335,7 @@ fn import_convunit(
     let mut count = 0usize;
     for result in rdr.records() {
         let record = result.map_err(|e| e.to_string())?;
-        if record.len() == 0 {
+        if record.is_empty() {
             continue;
         }
         if is_header(&record, "itemid") {
@@ -859,13 +876,16 @@ pub fn import_invoices_and_trans(
     let mut invoice_ids = Vec::new();
     collect_invoice_ids(invoice_path, &mut invoice_ids)?;
     collect_invoice_ids(trans_path, &mut invoice_ids)?;
+    invoice_ids.sort();
+    invoice_ids.dedup();
 
-    if !invoice_ids.is_empty() {
-        invoice_ids.sort();
-        invoice_ids.dedup();
+    // With no invoice history yet, this import is the baseline and raises
+    // no price alerts; afterwards only new or changed invoices are checked.
+    let first_import = !has_invoices(conn)?;
+    let before = invoice_signatures(conn, &invoice_ids)?;
 
-        let placeholders = std::iter::repeat("?")
-            .take(invoice_ids.len())
+    if !invoice_ids.is_empty() {
+        let placeholders = std::iter::repeat_n("?", invoice_ids.len())
             .collect::<Vec<_>>()
             .join(", ");
 
@@ -880,9 +900,70 @@ pub fn import_invoices_and_trans(
 
     let invoices = import_invoices(conn, invoice_path, warnings)?;
     let trans = import_trans(conn, trans_path, warnings)?;
+    let after = invoice_signatures(conn, &invoice_ids)?;
+    let changed = invoice_ids
+        .iter()
+        .copied()
+        .filter(|id| after.contains_key(id) && before.get(id) != after.get(id))
+        .collect::<Vec<_>>();
+    let alerts = if first_import {
+        0
+    } else {
+        crate::price_alerts::check_invoices(conn, &changed)?
+    };
+    crate::landed::allocate_invoices(conn, &invoice_ids)?;
+    let unbalanced = crate::reconcile::reconcile_invoices(conn, &invoice_ids)?;
+    if first_import && !changed.is_empty() {
+        warnings.push("First invoice import; price alerts start with the next import".to_string());
+    }
+    if alerts > 0 {
+        warnings.push(format!("{} price increase alerts raised", alerts));
+    }
+    if unbalanced > 0 {
+        warnings.push(format!(
+            "{} invoices don't balance; see Invoice Reconciliation",
+            unbalanced
+        ));
+    }
     Ok((invoices, trans))
 }
 
+fn has_invoices(conn: &Connection) -> Result<bool, String> {
+    conn.query_row("SELECT EXISTS(SELECT 1 FROM invoices)", [], |row| {
+        row.get(0)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// A fingerprint of each stored invoice among `invoice_ids`: its header and
+/// every line's item, vendor, price, quantity and unit.
+fn invoice_signatures(
+    conn: &Connection,
+    invoice_ids: &[i64],
+) -> Result<HashMap<i64, String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(i.invoice_date, '') || '|' || COALESCE(i.vendor_id, '') || '|' || COALESCE(i.total, '') || '|' ||
+                    COALESCE((SELECT group_concat(line, ';') FROM (
+                        SELECT COALESCE(t.trans_id, '') || ',' || COALESCE(t.item_id, '') || ',' || COALESCE(t.vendor_id, '') || ',' ||
+                               COALESCE(t.price, '') || ',' || COALESCE(t.qty, '') || ',' || COALESCE(t.unit_id, '') AS line
+                        FROM trans t WHERE t.invoice_id = i.invoice_id ORDER BY t.trans_id)), '')
+             FROM invoices i WHERE i.invoice_id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut out = HashMap::new();
+    for &invoice_id in invoice_ids {
+        let signature: Option<String> = stmt
+            .query_row([invoice_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(signature) = signature {
+            out.insert(invoice_id, signature);
+        }
+    }
+    Ok(out)
+}
+
 fn collect_invoice_ids(path: &Path, out: &mut Vec<i64>) -> Result<(), String> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
@@ -1131,7 +1212,7 @@ fn import_bids(
     let mut count = 0usize;
     for result in rdr.records() {
         let record = result.map_err(|e| e.to_string())?;
-        if record.len() == 0 {
+        if record.is_empty() {
             continue;
         }
         let mut cols = vec![String::new(); 10];
@@ -1173,9 +1254,7 @@ fn backfill_prices_from_trans(conn: &Connection) -> Result<usize, String> {
         WHERE t.price IS NOT NULL AND t.price > 0
     "#;
 
-    conn.execute(sql, [])
-        .map_err(|e| e.to_string())
-        .map(|count| count as usize)
+    conn.execute(sql, []).map_err(|e| e.to_string())
 }
 
 fn looks_like_recipe_row_start(line: &str) -> bool {
@@ -1375,9 +1454,7 @@ fn strip_rtf_groups(input: &str) -> String {
             if ignore_stack.is_empty() {
                 out.push(c);
             }
-            if depth > 0 {
-                depth -= 1;
-            }
+            depth = depth.saturating_sub(1);
         } else {
             if ignore_stack.is_empty() {
                 out.push(c);
@@ -1410,16 +1487,16 @@ fn find_numbered_start(text: &str) -> Option<usize> {
     for i in 0..bytes.len().saturating_sub(1) {
         let c = bytes[i] as char;
         let next = bytes[i + 1] as char;
-        if c == '1' && (next == ')' || next == '.' || next == ':') {
-            if i == 0
+        if c == '1'
+            && (next == ')' || next == '.' || next == ':')
+            && (i == 0
                 || text[..i]
                     .chars()
                     .last()
                     .map(|ch| ch.is_whitespace())
-                    .unwrap_or(false)
-            {
-                return Some(i);
-            }
+                    .unwrap_or(false))
+        {
+            return Some(i);
         }
     }
     None
@@ -1546,9 +1623,7 @@ fn should_trim_rtf_prefix(prefix: &str) -> bool {
         return true;
     }
 
-    prefix
-        .split_whitespace()
-        .any(|token| is_rtf_control_token(token))
+    prefix.split_whitespace().any(is_rtf_control_token)
 }
 
 fn insert_numbered_breaks(text: &str) -> String {
@@ -0,0 +1,1066 @@
+use crate::costing::MAX_RECIPE_DEPTH;
+use crate::nutrition::{recipe_nutrition, NUTRIENTS};
+use crate::scaling::format_fraction;
+use printpdf::image_crate::{ImageFormat, Rgb, RgbImage};
+use printpdf::{BuiltinFont, Mm, PdfDocument, Rect};
+use rusqlite::{Connection, OptionalExtension};
+use rusttype::{point, Font, Scale};
+use std::collections::HashMap;
+use std::io::BufWriter;
+
+/// FDA Daily Values for adults and children 4 years and older, by nutrient
+/// key. Trans fat, total sugars and protein aren't given a %DV.
+const DAILY_VALUES: &[(&str, f64)] = &[
+    ("fat_g", 78.0),
+    ("sat_fat_g", 20.0),
+    ("cholesterol_mg", 300.0),
+    ("sodium_mg", 2300.0),
+    ("carbs_g", 275.0),
+    ("fiber_g", 28.0),
+    ("added_sugars_g", 50.0),
+    ("vitamin_d_mcg", 20.0),
+    ("calcium_mg", 1300.0),
+    ("iron_mg", 18.0),
+    ("potassium_mg", 4700.0),
+];
+
+/// Nutrients above the thick bar, in label order, with their indent.
+const MACRONUTRIENTS: &[(&str, u8)] = &[
+    ("fat_g", 0),
+    ("sat_fat_g", 1),
+    ("trans_fat_g", 1),
+    ("cholesterol_mg", 0),
+    ("sodium_mg", 0),
+    ("carbs_g", 0),
+    ("fiber_g", 1),
+    ("sugars_g", 1),
+    ("added_sugars_g", 2),
+    ("protein_g", 0),
+];
+
+/// Vitamins and minerals declared below the thick bar.
+const VITAMINS: &[&str] = &["vitamin_d_mcg", "calcium_mg", "iron_mg", "potassium_mg"];
+
+/// Major food allergens as named on the "Contains" line. Fish, crustacean
+/// shellfish and tree nuts must be declared by kind, so each kind is listed.
+const MAJOR_ALLERGENS: &[(&str, &[&str])] = &[
+    (
+        "Milk",
+        &[
+            "milk",
+            "cream",
+            "butter",
+            "cheese",
+            "yogurt",
+            "yoghurt",
+            "whey",
+            "casein",
+            "curd",
+            "ghee",
+            "ricotta",
+            "mozzarella",
+            "parmesan",
+            "cheddar",
+            "brie",
+            "gouda",
+            "swiss",
+            "provolone",
+            "gruyere",
+            "mascarpone",
+            "half and half",
+            "half & half",
+            "buttermilk",
+        ],
+    ),
+    (
+        "Egg",
+        &[
+            "egg",
+            "meringue",
+            "mayo",
+            "mayonnaise",
+            "aioli",
+            "custard",
+            "hollandaise",
+        ],
+    ),
+    ("Anchovy", &["anchov"]),
+    ("Bass", &["bass"]),
+    ("Cod", &["cod"]),
+    ("Halibut", &["halibut"]),
+    ("Mahi Mahi", &["mahi"]),
+    ("Salmon", &["salmon", "lox"]),
+    ("Sardine", &["sardine"]),
+    ("Snapper", &["snapper"]),
+    ("Swordfish", &["swordfish"]),
+    ("Tilapia", &["tilapia"]),
+    ("Trout", &["trout"]),
+    ("Tuna", &["tuna"]),
+    ("Crab", &["crab"]),
+    ("Crawfish", &["crawfish", "crayfish"]),
+    ("Lobster", &["lobster"]),
+    ("Shrimp", &["shrimp", "prawn"]),
+    ("Almond", &["almond", "marzipan"]),
+    ("Brazil Nut", &["brazil nut"]),
+    ("Cashew", &["cashew"]),
+    ("Chestnut", &["chestnut"]),
+    ("Hazelnut", &["hazelnut", "filbert"]),
+    ("Macadamia", &["macadamia"]),
+    ("Pecan", &["pecan"]),
+    ("Pine Nut", &["pine nut"]),
+    ("Pistachio", &["pistachio"]),
+    ("Walnut", &["walnut"]),
+    ("Peanuts", &["peanut"]),
+    (
+        "Wheat",
+        &[
+            "wheat",
+            "flour",
+            "bread",
+            "pasta",
+            "noodle",
+            "couscous",
+            "tortilla",
+            "crouton",
+            "breadcrumb",
+            "panko",
+            "pita",
+            "baguette",
+            "semolina",
+            "farina",
+            "spelt",
+            "cracker",
+            "pastry",
+            "croissant",
+            "brioche",
+            "phyllo",
+            "wonton",
+            "seitan",
+        ],
+    ),
+    (
+        "Soy",
+        &[
+            "soy", "soya", "tofu", "tempeh", "edamame", "miso", "tamari", "teriyaki",
+        ],
+    ),
+    ("Sesame", &["sesame", "tahini"]),
+];
+
+/// Foods whose names contain an allergen keyword without being that
+/// allergen, and what to check in their place.
+const ALLERGEN_REWRITES: &[(&str, &str)] = &[
+    ("peanut butter", "peanut"),
+    ("almond milk", "almond"),
+    ("almond flour", "almond"),
+    ("cashew milk", "cashew"),
+    ("soy milk", "soy"),
+    ("oat milk", "oat"),
+    ("rice milk", "rice"),
+    ("rice flour", "rice"),
+    ("rice noodle", "rice"),
+    ("coconut milk", "coconut"),
+    ("coconut cream", "coconut"),
+    ("coconut flour", "coconut"),
+    ("corn flour", "corn"),
+    ("corn tortilla", "corn"),
+    ("chickpea flour", "chickpea"),
+    ("potato flour", "potato"),
+    ("tapioca flour", "tapioca"),
+    ("cocoa butter", "cocoa"),
+    ("apple butter", "apple"),
+    ("cream of tartar", "tartar"),
+    ("water chestnut", "water"),
+    ("butternut", "squash"),
+    ("eggplant", "aubergine"),
+];
+
+/// Allergen groups that can't be declared without knowing the kind.
+const UNNAMED_ALLERGENS: &[(&str, &str)] = &[
+    ("fish", "fish"),
+    ("shellfish", "shellfish"),
+    ("tree nut", "tree nuts"),
+    ("mixed nut", "tree nuts"),
+];
+
+const FOOTNOTE: &str = "* The % Daily Value (DV) tells you how much a nutrient in a serving of food \
+                        contributes to a daily diet. 2,000 calories a day is used for general nutrition advice.";
+
+/// One nutrient line on the label.
+pub struct LabelRow {
+    /// Nutrient name, or the whole line for "Includes 10g Added Sugars".
+    pub name: String,
+    pub amount: String,
+    pub daily_value: Option<String>,
+    /// 0 for the bold nutrients, 1 and 2 for those nested under them.
+    pub indent: u8,
+}
+
+/// A Nutrition Facts panel with every amount already rounded for display.
+pub struct NutritionLabel {
+    pub recipe_name: String,
+    pub servings_per_container: String,
+    pub serving_size: String,
+    pub calories: String,
+    pub nutrients: Vec<LabelRow>,
+    pub vitamins: Vec<LabelRow>,
+    /// Ingredient statement, heaviest first.
+    pub ingredients: String,
+    /// Major food allergens for the "Contains" line.
+    pub allergens: Vec<String>,
+    /// Things to check before printing; the label can still be exported.
+    pub warnings: Vec<String>,
+}
+
+struct Ingredient {
+    name: String,
+    grams: Option<f64>,
+    parts: Vec<Ingredient>,
+}
+
+fn round_to(value: f64, step: f64) -> f64 {
+    (value / step).round() * step
+}
+
+fn trim_number(value: f64) -> String {
+    if (value - value.round()).abs() < 1e-6 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+/// Calories rounded as declared: under 5 is 0, to 5 up to 50, then to 10.
+fn declared_calories(value: f64) -> String {
+    let rounded = if value < 5.0 {
+        0.0
+    } else if value <= 50.0 {
+        round_to(value, 5.0)
+    } else {
+        round_to(value, 10.0)
+    };
+    trim_number(rounded)
+}
+
+/// The declared amount under the FDA rounding rules, and whether it's
+/// declared as zero.
+fn declared_amount(key: &str, unit: &str, value: f64) -> (String, bool) {
+    let rounded = match key {
+        "fat_g" | "sat_fat_g" | "trans_fat_g" => {
+            if value < 0.5 {
+                0.0
+            } else if value < 5.0 {
+                round_to(value, 0.5)
+            } else {
+                round_to(value, 1.0)
+            }
+        }
+        "cholesterol_mg" => {
+            if value < 2.0 {
+                0.0
+            } else if value < 5.0 {
+                return ("<5mg".to_string(), false);
+            } else {
+                round_to(value, 5.0)
+            }
+        }
+        "sodium_mg" | "potassium_mg" => {
+            if value < 5.0 {
+                0.0
+            } else if value <= 140.0 {
+                round_to(value, 5.0)
+            } else {
+                round_to(value, 10.0)
+            }
+        }
+        "calcium_mg" => round_to(value, 10.0),
+        "vitamin_d_mcg" | "iron_mg" => round_to(value, 0.1),
+        _ => {
+            if value < 0.5 {
+                0.0
+            } else if value < 1.0 {
+                return (format!("<1{}", unit), false);
+            } else {
+                round_to(value, 1.0)
+            }
+        }
+    };
+    (format!("{}{}", trim_number(rounded), unit), rounded == 0.0)
+}
+
+/// %DV from the unrounded amount. Vitamins and minerals step by 2, 5 and
+/// 10 percent as they grow; everything else is a whole percent.
+fn percent_daily_value(key: &str, value: f64, declared_zero: bool) -> Option<String> {
+    let daily = DAILY_VALUES.iter().find(|(k, _)| *k == key)?.1;
+    let pct = if declared_zero {
+        0.0
+    } else {
+        value / daily * 100.0
+    };
+    let pct = if !VITAMINS.contains(&key) {
+        pct.round()
+    } else if pct < 2.0 {
+        0.0
+    } else if pct <= 10.0 {
+        round_to(pct, 2.0)
+    } else if pct <= 50.0 {
+        round_to(pct, 5.0)
+    } else {
+        round_to(pct, 10.0)
+    };
+    Some(format!("{:.0}%", pct))
+}
+
+/// 2 to 5 servings round to the nearest half, more to a whole number, with
+/// "About" when rounding changed the count.
+fn servings_per_container_text(servings: f64) -> String {
+    let rounded = (if servings <= 5.0 {
+        round_to(servings, 0.5)
+    } else {
+        servings.round()
+    })
+    .max(1.0);
+    let about = if (rounded - servings).abs() > 1e-6 {
+        "About "
+    } else {
+        ""
+    };
+    let noun = if rounded == 1.0 {
+        "serving"
+    } else {
+        "servings"
+    };
+    format!("{}{} {} per container", about, trim_number(rounded), noun)
+}
+
+/// Household measure from the recipe's portion, with grams in parentheses.
+fn serving_size_text(portion: Option<(f64, String, String)>, grams_per_serving: f64) -> String {
+    let grams = format!("{:.0}g", grams_per_serving);
+    match portion {
+        Some((_, sing, _)) if matches!(sing.to_lowercase().as_str(), "g" | "gram") => grams,
+        Some((size, sing, plur)) => {
+            let unit = if size > 1.0 && !plur.is_empty() {
+                plur
+            } else {
+                sing
+            };
+            format!("{} {} ({})", format_fraction(size), unit, grams)
+        }
+        None => format!("1 serving ({})", grams),
+    }
+}
+
+/// Item names, and label names where set, by item id.
+fn item_names(conn: &Connection) -> Result<HashMap<i64, (String, Option<String>)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT item_id, COALESCE(name, ''), NULLIF(TRIM(label_name), '') FROM items")
+        .map_err(|e| e.to_string())?;
+    let names = stmt
+        .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(names)
+}
+
+/// Ingredients by weight, heaviest first, with repeated names combined.
+/// Sub-recipes list their own ingredients; `leaves` collects the names of
+/// every item underneath for allergen checks.
+fn ingredient_list(
+    conn: &Connection,
+    recipe_id: i64,
+    names: &HashMap<i64, (String, Option<String>)>,
+    depth: usize,
+    leaves: &mut Vec<String>,
+) -> Result<Vec<Ingredient>, String> {
+    if depth > MAX_RECIPE_DEPTH {
+        return Err("Sub-recipes are nested too deeply for an ingredient statement".to_string());
+    }
+    let rolled = recipe_nutrition(conn, recipe_id)?;
+    let mut list: Vec<Ingredient> = Vec::new();
+    for line in rolled.lines {
+        let (item_name, label_name) = names.get(&line.item_id).cloned().unwrap_or_default();
+        let name = label_name.clone().unwrap_or(line.item_name);
+        let parts = match line.sub_recipe_id {
+            Some(sub_id) => ingredient_list(conn, sub_id, names, depth + 1, leaves)?,
+            None => {
+                leaves.push(format!("{} {}", item_name, label_name.unwrap_or_default()));
+                Vec::new()
+            }
+        };
+        match list
+            .iter_mut()
+            .find(|i| i.parts.is_empty() && parts.is_empty() && i.name.eq_ignore_ascii_case(&name))
+        {
+            Some(same) => same.grams = same.grams.zip(line.grams).map(|(a, b)| a + b),
+            None => list.push(Ingredient {
+                name,
+                grams: line.grams,
+                parts,
+            }),
+        }
+    }
+    list.sort_by(|a, b| b.grams.unwrap_or(-1.0).total_cmp(&a.grams.unwrap_or(-1.0)));
+    Ok(list)
+}
+
+fn ingredient_statement(list: &[Ingredient]) -> String {
+    list.iter()
+        .map(|i| {
+            if i.parts.is_empty() {
+                i.name.clone()
+            } else {
+                format!("{} ({})", i.name, ingredient_statement(&i.parts))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Major allergens in the ingredient names, and warnings for names that
+/// suggest an allergen without saying which kind.
+fn contains_allergens(leaves: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut found = Vec::new();
+    let mut warnings = Vec::new();
+    for leaf in leaves {
+        let mut name = leaf.to_lowercase();
+        for (from, to) in ALLERGEN_REWRITES {
+            name = name.replace(from, to);
+        }
+        let mut named = false;
+        for (allergen, keywords) in MAJOR_ALLERGENS {
+            if keywords.iter().any(|kw| crate::keyword_matches(&name, kw)) {
+                named = true;
+                if !found.contains(allergen) {
+                    found.push(*allergen);
+                }
+            }
+        }
+        if named {
+            continue;
+        }
+        if let Some((_, group)) = UNNAMED_ALLERGENS
+            .iter()
+            .find(|(kw, _)| crate::keyword_matches(&name, kw))
+        {
+            warnings.push(format!(
+                "{} may contain {}; give it a label name with the kind so it can be declared",
+                leaf.trim(),
+                group
+            ));
+        }
+    }
+    let allergens = MAJOR_ALLERGENS
+        .iter()
+        .map(|(allergen, _)| *allergen)
+        .filter(|a| found.contains(a))
+        .map(str::to_string)
+        .collect();
+    (allergens, warnings)
+}
+
+/// Builds the Nutrition Facts panel for one serving of a recipe. Every
+/// ingredient must be counted and convert to weight, since the serving size
+/// and ingredient order depend on grams.
+pub fn nutrition_label(
+    conn: &Connection,
+    recipe_id: i64,
+    servings_per_container: f64,
+) -> Result<NutritionLabel, String> {
+    if !servings_per_container.is_finite() || servings_per_container <= 0.0 {
+        return Err("Servings per package must be more than 0".to_string());
+    }
+    type RecipeRow = (String, Option<f64>, Option<String>, Option<String>);
+    let (recipe_name, portion_size, sing, plur): RecipeRow = conn
+        .query_row(
+            "SELECT COALESCE(r.name, ''), r.portion_size, u.sing, COALESCE(u.plur, u.sing)
+             FROM recipes r
+             LEFT JOIN units u ON u.unit_id = r.portion_unit_id
+             WHERE r.recipe_id = ?1",
+            [recipe_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1).ok().flatten(),
+                    row.get(2)?,
+                    row.get(3)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Recipe {} not found", recipe_id))?;
+
+    let rolled = recipe_nutrition(conn, recipe_id)?;
+    if rolled.missing_lines > 0 {
+        return Err(format!(
+            "{} ingredient{} can't be counted yet; finish the recipe's nutrition before making a label",
+            rolled.missing_lines,
+            if rolled.missing_lines == 1 { "" } else { "s" }
+        ));
+    }
+    let (Some(servings), Some(per_serving)) = (rolled.servings, rolled.per_serving.as_ref()) else {
+        return Err("Set the recipe's servings or portion size before making a label".to_string());
+    };
+    let total_grams = rolled.total_grams.filter(|g| *g > 0.0).ok_or_else(|| {
+        "Every ingredient needs a conversion to weight for the serving size and ingredient order".to_string()
+    })?;
+
+    let values: HashMap<&str, f64> = NUTRIENTS
+        .iter()
+        .map(|n| n.key)
+        .zip(per_serving.iter().copied())
+        .collect();
+    let value = |key: &str| values.get(key).copied().unwrap_or(0.0);
+    let row = |key: &str, indent: u8| {
+        let nutrient = NUTRIENTS.iter().find(|n| n.key == key);
+        let label = nutrient.map(|n| n.label).unwrap_or(key);
+        let unit = nutrient.map(|n| n.unit).unwrap_or("");
+        let (amount, zero) = declared_amount(key, unit, value(key));
+        let daily_value = percent_daily_value(key, value(key), zero);
+        if key == "added_sugars_g" {
+            LabelRow {
+                name: format!("Includes {} {}", amount, label),
+                amount: String::new(),
+                daily_value,
+                indent,
+            }
+        } else {
+            LabelRow {
+                name: label.to_string(),
+                amount,
+                daily_value,
+                indent,
+            }
+        }
+    };
+
+    let mut warnings: Vec<String> = NUTRIENTS
+        .iter()
+        .zip(&rolled.gaps)
+        .filter(|(_, gap)| **gap > 0)
+        .map(|(n, gap)| {
+            format!(
+                "{} isn't known for {} ingredient{}; it's counted as 0",
+                n.label,
+                gap,
+                if *gap == 1 { "" } else { "s" }
+            )
+        })
+        .collect();
+
+    let names = item_names(conn)?;
+    let mut leaves = Vec::new();
+    let ingredients =
+        ingredient_statement(&ingredient_list(conn, recipe_id, &names, 0, &mut leaves)?);
+    let (allergens, allergen_warnings) = contains_allergens(&leaves);
+    warnings.extend(allergen_warnings);
+
+    let portion = portion_size
+        .filter(|s| *s > 0.0)
+        .zip(sing)
+        .map(|(size, sing)| (size, sing, plur.unwrap_or_default()));
+    Ok(NutritionLabel {
+        recipe_name,
+        servings_per_container: servings_per_container_text(servings_per_container),
+        serving_size: serving_size_text(portion, total_grams / servings),
+        calories: declared_calories(value("calories")),
+        nutrients: MACRONUTRIENTS
+            .iter()
+            .map(|(key, indent)| row(key, *indent))
+            .collect(),
+        vitamins: VITAMINS.iter().map(|key| row(key, 0)).collect(),
+        ingredients,
+        allergens,
+        warnings,
+    })
+}
+
+/// The name an item goes by in ingredient statements, when set.
+pub fn label_name(conn: &Connection, item_id: i64) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT label_name FROM items WHERE item_id = ?1",
+        [item_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| e.to_string())
+}
+
+/// Sets or, when blank, clears the item's name for ingredient statements.
+pub fn set_label_name(conn: &Connection, item_id: i64, name: &str) -> Result<(), String> {
+    let name = Some(name.trim()).filter(|n| !n.is_empty());
+    let changed = conn
+        .execute(
+            "UPDATE items SET label_name = ?1 WHERE item_id = ?2",
+            rusqlite::params![name, item_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(())
+}
+
+/// Label width in points (3 inches).
+const LABEL_WIDTH: f32 = 216.0;
+/// Space between the border and the panel contents.
+const PADDING: f32 = 7.0;
+/// Border inset from the edge of the label.
+const BORDER: f32 = 2.0;
+/// Resolution of PNG exports.
+const PNG_DPI: f32 = 300.0;
+
+/// Something to draw, in points from the top left. Text sits on `y`.
+enum Mark {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        text: String,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+}
+
+struct Pen<'a> {
+    marks: Vec<Mark>,
+    /// Text width in points for a font size and weight.
+    measure: &'a dyn Fn(&str, f32, bool) -> f32,
+}
+
+impl Pen<'_> {
+    /// Draws text and returns where it ends.
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, text: &str) -> f32 {
+        let end = x + (self.measure)(text, size, bold);
+        self.marks.push(Mark::Text {
+            x,
+            y,
+            size,
+            bold,
+            text: text.to_string(),
+        });
+        end
+    }
+
+    fn text_right(&mut self, right: f32, y: f32, size: f32, bold: bool, text: &str) {
+        let x = right - (self.measure)(text, size, bold);
+        self.text(x, y, size, bold, text);
+    }
+
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.marks.push(Mark::Rect { x, y, w, h });
+    }
+
+    /// Breaks text into lines, leaving `indent` free on the first line.
+    fn wrap(&self, text: &str, size: f32, width: f32, indent: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            let room = if lines.is_empty() {
+                width - indent
+            } else {
+                width
+            };
+            if !line.is_empty() && (self.measure)(&candidate, size, false) > room {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// A paragraph led by a bold heading, e.g. "Ingredients:".
+    fn paragraph(&mut self, x: f32, y: &mut f32, width: f32, heading: &str, text: &str) {
+        let size = 7.5;
+        let indent = (self.measure)(&format!("{} ", heading), size, true);
+        for (i, line) in self.wrap(text, size, width, indent).iter().enumerate() {
+            *y += 9.0;
+            if i == 0 {
+                self.text(x, *y, size, true, heading);
+                self.text(x + indent, *y, size, false, line);
+            } else {
+                self.text(x, *y, size, false, line);
+            }
+        }
+    }
+}
+
+/// Lays out the standard vertical Nutrition Facts format, with the
+/// ingredient statement and "Contains" line underneath. Returns the marks
+/// and the label height.
+fn layout(label: &NutritionLabel, measure: &dyn Fn(&str, f32, bool) -> f32) -> (Vec<Mark>, f32) {
+    let mut pen = Pen {
+        marks: Vec::new(),
+        measure,
+    };
+    let left = BORDER + PADDING;
+    let right = LABEL_WIDTH - BORDER - PADDING;
+    let inner = right - left;
+    let row_size = 8.5;
+
+    let mut y = BORDER + PADDING + 20.0;
+    // The heading spans the panel, so wider fonts get a smaller size.
+    let title = "Nutrition Facts";
+    let title_size = 25.0f32.min(25.0 * inner / measure(title, 25.0, true));
+    pen.text(left, y, title_size, true, title);
+    y += 4.0;
+    pen.rect(left, y, inner, 0.5);
+    y += 11.0;
+    pen.text(left, y, 9.0, false, &label.servings_per_container);
+    y += 12.0;
+    pen.text(left, y, 10.0, true, "Serving size");
+    pen.text_right(right, y, 10.0, true, &label.serving_size);
+    y += 3.0;
+    pen.rect(left, y, inner, 7.0);
+    y += 16.0;
+    pen.text(left, y, 7.5, true, "Amount per serving");
+    y += 19.0;
+    pen.text(left, y, 18.0, true, "Calories");
+    pen.text_right(right, y, 26.0, true, &label.calories);
+    y += 3.0;
+    pen.rect(left, y, inner, 3.5);
+    y += 12.5;
+    pen.text_right(right, y, 7.5, true, "% Daily Value*");
+
+    for row in &label.nutrients {
+        let x = left + f32::from(row.indent) * 10.0;
+        y += 2.5;
+        // Only the rule above "Includes ... Added Sugars" is indented.
+        let rule_x = if row.indent == 2 { x } else { left };
+        pen.rect(rule_x, y, right - rule_x, 0.5);
+        y += 10.0;
+        let end = pen.text(x, y, row_size, row.indent == 0, &row.name);
+        if !row.amount.is_empty() {
+            pen.text(end + 3.0, y, row_size, false, &row.amount);
+        }
+        if let Some(dv) = &row.daily_value {
+            pen.text_right(right, y, row_size, true, dv);
+        }
+    }
+    y += 3.0;
+    pen.rect(left, y, inner, 7.0);
+    y += 7.0;
+    for (i, row) in label.vitamins.iter().enumerate() {
+        if i > 0 {
+            y += 2.5;
+            pen.rect(left, y, inner, 0.5);
+        }
+        y += 10.0;
+        let end = pen.text(left, y, row_size, false, &row.name);
+        pen.text(end + 3.0, y, row_size, false, &row.amount);
+        if let Some(dv) = &row.daily_value {
+            pen.text_right(right, y, row_size, false, dv);
+        }
+    }
+    y += 3.0;
+    pen.rect(left, y, inner, 3.5);
+    y += 3.5;
+    for line in pen.wrap(FOOTNOTE, 6.5, inner, 0.0) {
+        y += 8.0;
+        pen.text(left, y, 6.5, false, &line);
+    }
+    y += PADDING;
+
+    let border = 1.0;
+    let outer = LABEL_WIDTH - 2.0 * BORDER;
+    pen.rect(BORDER, BORDER, outer, border);
+    pen.rect(BORDER, y - border, outer, border);
+    pen.rect(BORDER, BORDER, border, y - BORDER);
+    pen.rect(LABEL_WIDTH - BORDER - border, BORDER, border, y - BORDER);
+
+    y += 3.0;
+    pen.paragraph(BORDER, &mut y, outer, "Ingredients:", &label.ingredients);
+    if !label.allergens.is_empty() {
+        y += 2.0;
+        pen.paragraph(
+            BORDER,
+            &mut y,
+            outer,
+            "Contains:",
+            &label.allergens.join(", "),
+        );
+    }
+    (pen.marks, y + 4.0)
+}
+
+/// Helvetica advance widths (per 1000 em) for printable ASCII.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths (per 1000 em) for printable ASCII.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+fn helvetica_width(text: &str, size: f32, bold: bool) -> f32 {
+    let widths = if bold {
+        &HELVETICA_BOLD_WIDTHS
+    } else {
+        &HELVETICA_WIDTHS
+    };
+    let units: u32 = text
+        .chars()
+        .map(|c| match c as u32 {
+            code @ 32..=126 => u32::from(widths[(code - 32) as usize]),
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+fn pt_to_mm(pt: f32) -> Mm {
+    Mm(pt * 25.4 / 72.0)
+}
+
+/// Writes the label as a one-page PDF the size of the label.
+pub fn write_label_pdf(label: &NutritionLabel, output_path: &str) -> Result<(), String> {
+    let (marks, height) = layout(label, &helvetica_width);
+    let (doc, page, layer) = PdfDocument::new(
+        format!("Nutrition Facts — {}", label.recipe_name),
+        pt_to_mm(LABEL_WIDTH),
+        pt_to_mm(height),
+        "Layer 1",
+    );
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let font_bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+    let layer = doc.get_page(page).get_layer(layer);
+    for mark in marks {
+        match mark {
+            Mark::Text {
+                x,
+                y,
+                size,
+                bold,
+                text,
+            } => {
+                layer.use_text(
+                    text,
+                    size,
+                    pt_to_mm(x),
+                    pt_to_mm(height - y),
+                    if bold { &font_bold } else { &font },
+                );
+            }
+            Mark::Rect { x, y, w, h } => {
+                layer.add_rect(Rect::new(
+                    pt_to_mm(x),
+                    pt_to_mm(height - y - h),
+                    pt_to_mm(x + w),
+                    pt_to_mm(height - y),
+                ));
+            }
+        }
+    }
+    let file = std::fs::File::create(output_path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|e| e.to_string())
+}
+
+/// DejaVu Sans, bundled so PNG labels look the same on every machine. The
+/// PDF's builtin Helvetica has no outlines to rasterize.
+const PNG_FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+const PNG_FONT_BOLD: &[u8] = include_bytes!("../assets/DejaVuSans-Bold.ttf");
+
+fn load_png_fonts() -> Result<(Font<'static>, Font<'static>), String> {
+    let load = |bytes: &'static [u8]| {
+        Font::try_from_bytes(bytes).ok_or_else(|| "Bundled label font is unreadable".to_string())
+    };
+    Ok((load(PNG_FONT)?, load(PNG_FONT_BOLD)?))
+}
+
+/// rusttype scales by line height; this scales by em like PDF font sizes.
+fn em_scale(font: &Font, size: f32) -> Scale {
+    let v = font.v_metrics_unscaled();
+    Scale::uniform(size * (v.ascent - v.descent) / f32::from(font.units_per_em()))
+}
+
+fn font_width(font: &Font, text: &str, size: f32) -> f32 {
+    font.layout(text, em_scale(font, size), point(0.0, 0.0))
+        .last()
+        .map(|g| g.position().x + g.unpositioned().h_metrics().advance_width)
+        .unwrap_or(0.0)
+}
+
+/// Writes the label as a 300 DPI PNG.
+pub fn write_label_png(label: &NutritionLabel, output_path: &str) -> Result<(), String> {
+    let (regular, bold) = load_png_fonts()?;
+    let font_for = |is_bold: bool| if is_bold { &bold } else { &regular };
+    let measure = |text: &str, size: f32, is_bold: bool| font_width(font_for(is_bold), text, size);
+    let (marks, height) = layout(label, &measure);
+
+    let px = PNG_DPI / 72.0;
+    let mut image = RgbImage::from_pixel(
+        (LABEL_WIDTH * px).ceil() as u32,
+        (height * px).ceil() as u32,
+        Rgb([255, 255, 255]),
+    );
+    let (width_px, height_px) = image.dimensions();
+    for mark in marks {
+        match mark {
+            Mark::Text {
+                x,
+                y,
+                size,
+                bold: is_bold,
+                text,
+            } => {
+                let font = font_for(is_bold);
+                for glyph in font.layout(&text, em_scale(font, size * px), point(x * px, y * px)) {
+                    let Some(bounds) = glyph.pixel_bounding_box() else {
+                        continue;
+                    };
+                    glyph.draw(|gx, gy, coverage| {
+                        let (ix, iy) = (bounds.min.x + gx as i32, bounds.min.y + gy as i32);
+                        if ix < 0 || iy < 0 || ix as u32 >= width_px || iy as u32 >= height_px {
+                            return;
+                        }
+                        let pixel = image.get_pixel_mut(ix as u32, iy as u32);
+                        for channel in pixel.0.iter_mut() {
+                            *channel = (f32::from(*channel) * (1.0 - coverage.min(1.0))) as u8;
+                        }
+                    });
+                }
+            }
+            Mark::Rect { x, y, w, h } => {
+                let x0 = (x * px).round() as u32;
+                let y0 = (y * px).round() as u32;
+                let x1 = (((x + w) * px).round() as u32).max(x0 + 1).min(width_px);
+                let y1 = (((y + h) * px).round() as u32).max(y0 + 1).min(height_px);
+                for iy in y0..y1 {
+                    for ix in x0..x1 {
+                        image.put_pixel(ix, iy, Rgb([0, 0, 0]));
+                    }
+                }
+            }
+        }
+    }
+    image
+        .save_with_format(output_path, ImageFormat::Png)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{declared_amount, declared_calories, load_png_fonts, percent_daily_value};
+
+    #[test]
+    fn calories_round_by_size() {
+        assert_eq!(declared_calories(4.9), "0");
+        assert_eq!(declared_calories(47.0), "45");
+        assert_eq!(declared_calories(50.0), "50");
+        assert_eq!(declared_calories(52.0), "50");
+        assert_eq!(declared_calories(236.0), "240");
+    }
+
+    #[test]
+    fn amounts_follow_the_rounding_rules() {
+        assert_eq!(declared_amount("fat_g", "g", 0.4), ("0g".to_string(), true));
+        assert_eq!(
+            declared_amount("fat_g", "g", 2.3),
+            ("2.5g".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("fat_g", "g", 7.6),
+            ("8g".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("cholesterol_mg", "mg", 1.0),
+            ("0mg".to_string(), true)
+        );
+        assert_eq!(
+            declared_amount("cholesterol_mg", "mg", 3.0),
+            ("<5mg".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("cholesterol_mg", "mg", 23.0),
+            ("25mg".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("sodium_mg", "mg", 138.0),
+            ("140mg".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("sodium_mg", "mg", 142.0),
+            ("140mg".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("protein_g", "g", 0.7),
+            ("<1g".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("sugars_g", "g", 3.4),
+            ("3g".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("iron_mg", "mg", 2.34),
+            ("2.3mg".to_string(), false)
+        );
+        assert_eq!(
+            declared_amount("calcium_mg", "mg", 0.0),
+            ("0mg".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn daily_values_step_for_vitamins_and_minerals() {
+        assert_eq!(
+            percent_daily_value("fat_g", 10.0, false).as_deref(),
+            Some("13%")
+        );
+        assert_eq!(
+            percent_daily_value("sodium_mg", 4.0, true).as_deref(),
+            Some("0%")
+        );
+        assert_eq!(
+            percent_daily_value("calcium_mg", 20.0, false).as_deref(),
+            Some("0%")
+        );
+        assert_eq!(
+            percent_daily_value("calcium_mg", 130.0, false).as_deref(),
+            Some("10%")
+        );
+        assert_eq!(
+            percent_daily_value("iron_mg", 3.1, false).as_deref(),
+            Some("15%")
+        );
+        assert_eq!(
+            percent_daily_value("vitamin_d_mcg", 15.0, false).as_deref(),
+            Some("80%")
+        );
+        assert_eq!(percent_daily_value("trans_fat_g", 1.0, false), None);
+    }
+
+    #[test]
+    fn bundled_fonts_load() {
+        assert!(load_png_fonts().is_ok());
+    }
+}
@@ -0,0 +1,465 @@
+use crate::costing::convert_item_qty;
+use crate::pricing::setting;
+use rusqlite::{Connection, OptionalExtension};
+
+pub const CHARGE_KINDS: [&str; 4] = ["freight", "fuel", "fee", "tax"];
+/// "default" follows the `landed_alloc_method` setting.
+pub const ALLOCATIONS: [&str; 5] = ["default", "value", "weight", "qty", "none"];
+
+/// A header-level charge on an invoice. The invoice's own `freight` column
+/// is reported as a freight charge with `charge_id` 0.
+pub struct Charge {
+    pub charge_id: i64,
+    pub invoice_id: i64,
+    pub kind: String,
+    pub description: String,
+    pub amount: f64,
+    pub allocation: String,
+}
+
+/// How a charge was spread, or why it wasn't.
+pub struct ChargeResult {
+    pub charge: Charge,
+    pub method: String,
+    pub allocated: f64,
+    pub note: String,
+}
+
+pub struct InvoiceAllocation {
+    pub charges: Vec<ChargeResult>,
+    pub allocated: f64,
+    pub unallocated: f64,
+    pub lines: usize,
+}
+
+/// Allocation method for charges left on "default": value, weight or qty.
+pub fn default_method(conn: &Connection) -> String {
+    let method = setting(conn, "landed_alloc_method");
+    if matches!(method.as_str(), "value" | "weight" | "qty") {
+        method
+    } else {
+        "value".to_string()
+    }
+}
+
+/// Whether sales tax is a cost to the business (not recoverable).
+pub fn tax_is_cost(conn: &Connection) -> bool {
+    setting(conn, "landed_include_tax") == "1"
+}
+
+/// Whether costing should use landed rather than invoiced prices.
+pub fn use_landed_cost(conn: &Connection) -> bool {
+    setting(conn, "costing_use_landed") == "1"
+}
+
+/// Recognises header charges that arrive as invoice lines, e.g. a
+/// "Fuel Surcharge" or "Sales Tax" line on a scanned invoice.
+pub fn charge_kind(description: &str) -> Option<&'static str> {
+    let d = description.to_lowercase();
+    let has = |words: &[&str]| {
+        words
+            .iter()
+            .any(|w| d.split(|c: char| !c.is_alphanumeric()).any(|t| t == *w))
+    };
+    if has(&["tax", "vat", "gst", "hst"]) {
+        Some("tax")
+    } else if has(&["fuel", "surcharge"]) {
+        Some("fuel")
+    } else if has(&["freight", "delivery", "shipping"]) {
+        Some("freight")
+    } else if has(&["fee", "deposit", "handling"]) {
+        Some("fee")
+    } else {
+        None
+    }
+}
+
+pub fn list_charges(conn: &Connection, invoice_id: i64) -> Result<Vec<Charge>, String> {
+    let mut charges = Vec::new();
+    let freight: Option<f64> = conn
+        .query_row(
+            "SELECT freight FROM invoices WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    if let Some(amount) = freight.filter(|f| f.abs() > 1e-9) {
+        charges.push(Charge {
+            charge_id: 0,
+            invoice_id,
+            kind: "freight".to_string(),
+            description: "Invoice freight".to_string(),
+            amount,
+            allocation: "default".to_string(),
+        });
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT charge_id, kind, COALESCE(description, ''), amount, allocation
+             FROM invoice_charges WHERE invoice_id = ?1 ORDER BY charge_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok(Charge {
+                charge_id: row.get(0)?,
+                invoice_id,
+                kind: row.get(1)?,
+                description: row.get(2)?,
+                amount: row.get(3)?,
+                allocation: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        charges.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(charges)
+}
+
+/// Adds or updates a charge (`charge_id` 0 adds) and returns its id.
+pub fn save_charge(conn: &Connection, charge: &Charge) -> Result<i64, String> {
+    if !CHARGE_KINDS.contains(&charge.kind.as_str()) {
+        return Err(format!("Unknown charge type '{}'", charge.kind));
+    }
+    if !ALLOCATIONS.contains(&charge.allocation.as_str()) {
+        return Err(format!("Unknown allocation '{}'", charge.allocation));
+    }
+    if charge.charge_id > 0 {
+        let updated = conn
+            .execute(
+                "UPDATE invoice_charges SET kind = ?2, description = ?3, amount = ?4, allocation = ?5 WHERE charge_id = ?1",
+                rusqlite::params![charge.charge_id, charge.kind, charge.description, charge.amount, charge.allocation],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Charge {} not found", charge.charge_id));
+        }
+        return Ok(charge.charge_id);
+    }
+    conn.execute(
+        "INSERT INTO invoice_charges (invoice_id, kind, description, amount, allocation) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![charge.invoice_id, charge.kind, charge.description, charge.amount, charge.allocation],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+struct Line {
+    trans_id: i64,
+    item_id: Option<i64>,
+    qty: f64,
+    unit_id: Option<i64>,
+    /// Line value net of any tax included in the vendor's prices.
+    value: f64,
+    allocated: f64,
+}
+
+fn weight_unit(conn: &Connection) -> Option<i64> {
+    crate::units::resolve_unit(conn, "lb").ok().flatten()
+}
+
+/// Each line's share basis for `method`, or `None` when some line can't be
+/// measured that way (e.g. no conversion to pounds).
+fn basis(conn: &Connection, lines: &[Line], method: &str) -> Result<Option<Vec<f64>>, String> {
+    match method {
+        "qty" => Ok(Some(lines.iter().map(|l| l.qty).collect())),
+        "weight" => {
+            let Some(lb) = weight_unit(conn) else {
+                return Ok(None);
+            };
+            let mut weights = Vec::new();
+            for line in lines {
+                let weight = match (line.item_id, line.unit_id) {
+                    (Some(item_id), Some(unit_id)) => {
+                        convert_item_qty(conn, item_id, line.qty, unit_id, lb)?
+                    }
+                    _ => None,
+                };
+                match weight {
+                    Some(w) => weights.push(w),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(weights))
+        }
+        _ => Ok(Some(lines.iter().map(|l| l.value).collect())),
+    }
+}
+
+/// Spreads the invoice's freight and charges over its lines and stores
+/// each line's allocated amount and landed unit price (per the line's
+/// unit). Tax charges are only allocated when tax is a cost and the
+/// vendor's prices don't already include it.
+pub fn allocate_invoice(conn: &Connection, invoice_id: i64) -> Result<InvoiceAllocation, String> {
+    let (tax_inclusive, tax_rate): (bool, f64) = conn
+        .query_row(
+            "SELECT COALESCE(v.prices_include_tax, 0), COALESCE(v.tax_rate, 0)
+             FROM invoices i LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+             WHERE i.invoice_id = ?1",
+            [invoice_id],
+            |row| Ok((row.get::<_, i64>(0)? != 0, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+    let tax_cost = tax_is_cost(conn);
+    let net_factor = if tax_inclusive && !tax_cost && tax_rate > 0.0 {
+        1.0 / (1.0 + tax_rate / 100.0)
+    } else {
+        1.0
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT trans_id, item_id, COALESCE(qty, 0), unit_id,
+                    CASE WHEN COALESCE(ext_cost, 0) > 0 THEN ext_cost ELSE COALESCE(qty, 0) * COALESCE(price, 0) END
+             FROM trans WHERE invoice_id = ?1 ORDER BY trans_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok(Line {
+                trans_id: row.get(0)?,
+                item_id: row.get(1)?,
+                qty: row.get(2)?,
+                unit_id: row.get(3)?,
+                value: row.get::<_, f64>(4)? * net_factor,
+                allocated: 0.0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut lines = Vec::new();
+    let mut skipped_lines = Vec::new();
+    for row in rows {
+        let line = row.map_err(|e| e.to_string())?;
+        if line.qty > 0.0 && line.value > 0.0 {
+            lines.push(line);
+        } else {
+            skipped_lines.push(line.trans_id);
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut allocated_total = 0.0;
+    let mut unallocated = 0.0;
+    let default = default_method(conn);
+    for charge in list_charges(conn, invoice_id)? {
+        let mut method = if charge.allocation == "default" {
+            default.clone()
+        } else {
+            charge.allocation.clone()
+        };
+        let mut note = String::new();
+        if charge.kind == "tax" && (!tax_cost || tax_inclusive) {
+            method = "none".to_string();
+            note = if tax_inclusive {
+                "Vendor prices include tax".to_string()
+            } else {
+                "Tax is not a cost".to_string()
+            };
+        }
+        let mut allocated = 0.0;
+        if method != "none" && !lines.is_empty() {
+            let mut shares = basis(conn, &lines, &method)?;
+            if shares.is_none() && method == "weight" {
+                note = "Some lines have no weight; allocated by value".to_string();
+                method = "value".to_string();
+                shares = basis(conn, &lines, &method)?;
+            }
+            let shares = shares.unwrap_or_default();
+            let sum: f64 = shares.iter().sum();
+            if sum > 0.0 {
+                for (line, share) in lines.iter_mut().zip(&shares) {
+                    line.allocated += charge.amount * share / sum;
+                }
+                allocated = charge.amount;
+            } else {
+                note = format!("No line {} to allocate by", method);
+            }
+        } else if method != "none" {
+            note = "No priced lines".to_string();
+        }
+        allocated_total += allocated;
+        if method == "none" || allocated == 0.0 {
+            unallocated += charge.amount;
+        }
+        results.push(ChargeResult {
+            charge,
+            method,
+            allocated,
+            note,
+        });
+    }
+
+    for line in &lines {
+        conn.execute(
+            "UPDATE trans SET alloc_amount = ?2, landed_price = ?3 WHERE trans_id = ?1",
+            (
+                line.trans_id,
+                line.allocated,
+                (line.value + line.allocated) / line.qty,
+            ),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for trans_id in skipped_lines {
+        conn.execute(
+            "UPDATE trans SET alloc_amount = NULL, landed_price = NULL WHERE trans_id = ?1",
+            [trans_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(InvoiceAllocation {
+        charges: results,
+        allocated: allocated_total,
+        unallocated,
+        lines: lines.len(),
+    })
+}
+
+pub fn allocate_invoices(conn: &Connection, invoice_ids: &[i64]) -> Result<(), String> {
+    for &invoice_id in invoice_ids {
+        allocate_invoice(conn, invoice_id)?;
+    }
+    Ok(())
+}
+
+/// Re-allocates every invoice, optionally only one vendor's. Used after
+/// the allocation settings or a vendor's tax terms change.
+pub fn allocate_all(conn: &Connection, vendor_id: Option<i64>) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare("SELECT invoice_id FROM invoices WHERE ?1 IS NULL OR vendor_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([vendor_id], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    allocate_invoices(conn, &ids)?;
+    Ok(ids.len())
+}
+
+/// Re-allocates the invoices whose allocation depends on what is kept across
+/// a re-import: those with entered charges and those from vendors whose
+/// prices include tax.
+pub fn allocate_kept(conn: &Connection) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.invoice_id FROM invoices i LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+             WHERE COALESCE(v.prices_include_tax, 0) <> 0
+                OR EXISTS (SELECT 1 FROM invoice_charges c WHERE c.invoice_id = i.invoice_id)",
+        )
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    allocate_invoices(conn, &ids)?;
+    Ok(ids.len())
+}
+
+/// Landed over invoiced price on the vendor's latest invoice line for the
+/// item, used to carry freight and fees onto list prices.
+pub fn landed_ratio(conn: &Connection, item_id: i64, vendor_id: i64) -> Option<f64> {
+    conn.query_row(
+        "SELECT landed_price / price FROM trans
+         WHERE item_id = ?1 AND vendor_id = ?2 AND price > 0 AND landed_price > 0
+         ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+        (item_id, vendor_id),
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate_invoice, save_charge, Charge};
+    use rusqlite::Connection;
+
+    /// Invoice 1 with $10 freight over a $60 case of item 1 (40 lb) and three
+    /// $40 total cases of item 2 (10 lb each).
+    fn invoice_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (2, 'lb', 'lbs'), (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Fryer Oil'), (2, 'Flour');
+             INSERT INTO vendors (vendor_id, name) VALUES (7, 'Sysco');
+             INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status)
+             VALUES (1, 0, 3, 2, 1, 40, 1), (2, 0, 3, 2, 1, 10, 1);
+             INSERT INTO invoices (invoice_id, vendor_id, freight) VALUES (1, 7, 10);
+             INSERT INTO trans (trans_id, invoice_id, item_id, vendor_id, qty, unit_id, price, ext_cost)
+             VALUES (1, 1, 1, 7, 1, 3, 60, 60), (2, 1, 2, 7, 3, 3, 13.3333, 40);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn allocated(conn: &Connection, method: &str) -> Vec<f64> {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('landed_alloc_method', ?1)",
+            [method],
+        )
+        .unwrap();
+        allocate_invoice(conn, 1).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT alloc_amount FROM trans ORDER BY trans_id")
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get::<_, f64>(0)).unwrap();
+        rows.map(|r| (r.unwrap() * 100.0).round() / 100.0).collect()
+    }
+
+    #[test]
+    fn freight_follows_the_allocation_method() {
+        let conn = invoice_db();
+        assert_eq!(allocated(&conn, "value"), vec![6.0, 4.0]);
+        assert_eq!(allocated(&conn, "qty"), vec![2.5, 7.5]);
+        assert_eq!(allocated(&conn, "weight"), vec![5.71, 4.29]);
+
+        conn.execute("DELETE FROM convunit WHERE item_id = 2", [])
+            .unwrap();
+        let result = allocate_invoice(&conn, 1).unwrap();
+        assert_eq!(result.charges[0].method, "value");
+        assert_eq!(allocated(&conn, "weight"), vec![6.0, 4.0]);
+    }
+
+    #[test]
+    fn tax_inclusive_prices_are_netted_and_their_tax_not_allocated() {
+        let conn = invoice_db();
+        conn.execute(
+            "UPDATE vendors SET prices_include_tax = 1, tax_rate = 25 WHERE vendor_id = 7",
+            [],
+        )
+        .unwrap();
+        save_charge(
+            &conn,
+            &Charge {
+                charge_id: 0,
+                invoice_id: 1,
+                kind: "tax".to_string(),
+                description: "Sales tax".to_string(),
+                amount: 20.0,
+                allocation: "default".to_string(),
+            },
+        )
+        .unwrap();
+
+        let result = allocate_invoice(&conn, 1).unwrap();
+        assert_eq!(result.allocated, 10.0);
+        assert_eq!(result.unallocated, 20.0);
+        assert_eq!(result.charges[1].note, "Vendor prices include tax");
+        let landed: f64 = conn
+            .query_row(
+                "SELECT landed_price FROM trans WHERE trans_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!((landed - 54.0).abs() < 1e-9, "{}", landed);
+    }
+}
@@ -9,10 +9,37 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
+mod bids;
+mod cogs;
+mod convcheck;
+mod convreview;
+mod costing;
+mod counts;
 mod db;
+mod density;
+mod impact;
 mod importer;
-
-use db::{clear_tables, db_path, fallback_db_path, init_db, open_db, open_initialized_db, with_tx};
+mod label;
+mod landed;
+mod nutrition;
+mod order_guide;
+mod pack;
+mod price_alerts;
+mod pricing;
+mod purchase_orders;
+mod reconcile;
+mod sales;
+mod scaling;
+mod spend;
+mod suggest;
+mod units;
+mod variance;
+
+use costing::{cost_item_line, cost_recipe, recipe_reaches, recipe_yield, CostContext};
+use db::{
+    clear_tables, db_path, fallback_db_path, init_db, open_db, open_initialized_db, prune_orphans,
+    restore_user_columns, with_tx,
+};
 use importer::{import_all, import_invoices_and_trans, ImportSummary};
 
 #[derive(Serialize)]
@@ -59,6 +86,8 @@ struct InventoryDetailPrice {
     prev_price: Option<f64>,
     diff_pct: Option<f64>,
     pack: String,
+    /// What the pack was read as, e.g. "4 × 5 Pound", or why it couldn't be.
+    pack_reading: String,
     status: Option<i64>,
 }
 
@@ -99,6 +128,65 @@ struct InventoryDetailResponse {
     conversions: Vec<InventoryDetailConversion>,
     usage: Vec<InventoryDetailUsage>,
     missing_edges: Vec<InventoryDetailMissingEdge>,
+    yield_pct: Option<f64>,
+    /// Grams per millilitre entered on the item.
+    density: Option<f64>,
+    /// The density conversions use, own or reference, and where it came from.
+    effective_density: Option<f64>,
+    density_source: String,
+    preps: Vec<ItemPrep>,
+    yield_tests: Vec<YieldTest>,
+    counts: Vec<InventoryCount>,
+    par_levels: Vec<ParLevelResponse>,
+}
+
+#[derive(Serialize)]
+struct ParLevelResponse {
+    par_id: i64,
+    storage_type: String,
+    day_of_week: Option<i64>,
+    day_name: String,
+    par_qty: f64,
+    unit_id: Option<i64>,
+    unit_name: String,
+}
+
+#[derive(Serialize)]
+struct InventoryCount {
+    count_id: i64,
+    count_date: String,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    notes: String,
+}
+
+#[derive(Serialize)]
+struct ItemPrep {
+    prep_id: i64,
+    item_id: i64,
+    name: String,
+    yield_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ItemPrepResponse {
+    preps: Vec<ItemPrep>,
+}
+
+#[derive(Serialize)]
+struct YieldTest {
+    test_id: i64,
+    prep_id: Option<i64>,
+    prep_name: String,
+    test_date: String,
+    unit_name: String,
+    gross_qty: Option<f64>,
+    trim_qty: Option<f64>,
+    waste_qty: Option<f64>,
+    net_qty: Option<f64>,
+    yield_pct: Option<f64>,
+    notes: String,
 }
 
 #[derive(Serialize, Clone)]
@@ -130,6 +218,8 @@ struct VendorDetailResponse {
     price_items: Vec<VendorPriceItem>,
     invoice_count: i64,
     trans_count: i64,
+    prices_include_tax: bool,
+    tax_rate: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -170,6 +260,11 @@ struct RecipeListItem {
     recipe_id: i64,
     name: String,
     item_count: i64,
+    servings: Option<f64>,
+    cost_per_portion: Option<f64>,
+    menu_price: Option<f64>,
+    food_cost_pct: Option<f64>,
+    margin: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -177,6 +272,9 @@ struct RecipeListResponse {
     recipes: Vec<RecipeListItem>,
     total: i64,
     filtered: i64,
+    /// Costs were read from before the latest change; `refresh_recipe_costs`
+    /// brings them up to date.
+    costs_stale: bool,
 }
 
 #[derive(Serialize)]
@@ -192,6 +290,37 @@ struct RecipeIngredient {
     price: Option<f64>,
     extended_cost: Option<f64>,
     cost_status: String,
+    sub_recipe_id: Option<i64>,
+    prep_id: Option<i64>,
+    prep_name: String,
+    yield_pct: Option<f64>,
+    ap_cost: Option<f64>,
+    /// "1 Each = 0.333 Pound" when the line converts to its purchase unit.
+    conversion_summary: String,
+    conversion_path: Vec<ConversionStepResponse>,
+}
+
+#[derive(Serialize)]
+struct ConversionStepResponse {
+    from_unit_name: String,
+    to_unit_name: String,
+    factor: f64,
+    source: String,
+    vendor_name: String,
+    note: String,
+}
+
+impl From<costing::ConvStep> for ConversionStepResponse {
+    fn from(step: costing::ConvStep) -> Self {
+        ConversionStepResponse {
+            from_unit_name: step.from_unit_name,
+            to_unit_name: step.to_unit_name,
+            factor: step.factor,
+            source: step.source,
+            vendor_name: step.vendor_name,
+            note: step.note,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -204,6 +333,59 @@ struct RecipeDetailResponse {
     instructions: String,
     ingredients: Vec<RecipeIngredient>,
     allergens: Vec<String>,
+    yield_qty: Option<f64>,
+    yield_unit_id: Option<i64>,
+    yield_unit_name: String,
+    cost_per_yield_unit: Option<f64>,
+    servings: Option<f64>,
+    portion_size: Option<f64>,
+    portion_unit_id: Option<i64>,
+    portion_unit_name: String,
+    cost_per_portion: Option<f64>,
+    target_food_cost_pct: Option<f64>,
+    effective_target_pct: f64,
+    suggested_price: Option<f64>,
+    menu_price: Option<f64>,
+    food_cost_pct: Option<f64>,
+    margin: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ScaledRecipeLine {
+    recp_item_id: i64,
+    item_name: String,
+    orig_qty: Option<f64>,
+    orig_unit_name: String,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    display_qty: String,
+    extended_cost: Option<f64>,
+    cost_status: String,
+}
+
+#[derive(Serialize)]
+struct ScaledRecipeResponse {
+    recipe_id: i64,
+    name: String,
+    factor: f64,
+    servings: Option<f64>,
+    yield_qty: Option<f64>,
+    yield_unit_name: String,
+    total_cost: f64,
+    missing_costs: i64,
+    lines: Vec<ScaledRecipeLine>,
+}
+
+#[derive(Serialize)]
+struct RecipeSimple {
+    recipe_id: i64,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct RecipeSimpleResponse {
+    recipes: Vec<RecipeSimple>,
 }
 
 #[derive(Serialize)]
@@ -220,6 +402,7 @@ struct ConversionSuggestionRow {
     derived_from: String,
     hops: Option<i64>,
     path: String,
+    confidence: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -328,6 +511,48 @@ struct InvoiceLineItem {
     unit_name: String,
     price: Option<f64>,
     ext_cost: Option<f64>,
+    alloc_amount: Option<f64>,
+    landed_price: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct InvoiceChargeResponse {
+    charge_id: i64,
+    kind: String,
+    description: String,
+    amount: f64,
+    allocation: String,
+}
+
+#[derive(Serialize)]
+struct CreditMemoResponse {
+    credit_id: i64,
+    memo_no: String,
+    memo_date: String,
+    reason: String,
+    amount: f64,
+    trans_id: Option<i64>,
+    item_name: String,
+    qty: Option<f64>,
+    unit_name: String,
+    note: String,
+}
+
+impl From<reconcile::CreditMemo> for CreditMemoResponse {
+    fn from(c: reconcile::CreditMemo) -> Self {
+        Self {
+            credit_id: c.credit_id,
+            memo_no: c.memo_no,
+            memo_date: c.memo_date,
+            reason: c.reason,
+            amount: c.amount,
+            trans_id: c.trans_id,
+            item_name: c.item_name,
+            qty: c.qty,
+            unit_name: c.unit_name,
+            note: c.note,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -335,6 +560,9 @@ struct InvoiceDetailResponse {
     invoice: InvoiceListItem,
     freight: Option<f64>,
     lines: Vec<InvoiceLineItem>,
+    charges: Vec<InvoiceChargeResponse>,
+    credits: Vec<CreditMemoResponse>,
+    recon_status: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -385,6 +613,8 @@ fn fallback_data_root() -> PathBuf {
     env::temp_dir().join("4chef")
 }
 
+// Each platform compiles only its own block, so every block returns.
+#[allow(clippy::needless_return)]
 fn open_in_file_browser(target: &str) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
@@ -469,7 +699,12 @@ fn import_from_base_dir(app: &tauri::AppHandle, base: &Path) -> Result<ImportSum
 
     with_tx(&mut conn, |tx| {
         clear_tables(tx)?;
-        import_all(tx, base, &db_path)
+        let summary = import_all(tx, base, &db_path)?;
+        restore_user_columns(tx)?;
+        prune_orphans(tx)?;
+        bids::apply_awards(tx)?;
+        landed::allocate_kept(tx)?;
+        Ok(summary)
     })
 }
 
@@ -1057,7 +1292,7 @@ fn find_latest_csv(dir: &Path, target_name: &str) -> Result<PathBuf, String> {
         let modified = entry
             .metadata()
             .and_then(|m| m.modified())
-            .unwrap_or_else(|_| std::time::SystemTime::UNIX_EPOCH);
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
 
         if best
             .as_ref()
@@ -1193,6 +1428,23 @@ fn get_inventory_detail(
             |row| Ok((row.get(0)?, row.get(1).ok(), row.get(2).unwrap_or_default(), row.get(3).unwrap_or_default())),
         )
         .map_err(|e| e.to_string())?;
+    let yield_pct: Option<f64> = conn
+        .query_row(
+            "SELECT yield_pct FROM items WHERE item_id = ?1",
+            [item_id],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten();
+    let density: Option<f64> = conn
+        .query_row(
+            "SELECT density FROM items WHERE item_id = ?1",
+            [item_id],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten();
+    let effective = density::item_density(&conn, item_id);
 
     let mut purch_units = Vec::new();
     let mut stmt = conn
@@ -1221,7 +1473,7 @@ fn get_inventory_detail(
     let mut prices = Vec::new();
     let mut stmt = conn
         .prepare(
-            "SELECT ip.vendor_id, v.name, ip.price, ip.pack, ip.status, ip.prev_price
+            "SELECT ip.vendor_id, v.name, ip.price, ip.pack, ip.status, ip.prev_price, ip.rowid, ip.pack_error
              FROM inv_prices ip
              LEFT JOIN vendors v ON v.vendor_id = ip.vendor_id
              WHERE ip.item_id = ?1
@@ -1236,6 +1488,13 @@ fn get_inventory_detail(
                 (Some(p), Some(pp)) if pp > 0.0 => Some(((p - pp) / pp) * 100.0),
                 _ => None,
             };
+            let pack_error: Option<String> = row.get(7)?;
+            let pack_reading = match pack::stored_pack(&conn, row.get(6)?).ok().flatten() {
+                Some(read) => read.describe(&conn),
+                None => pack_error
+                    .map(|e| format!("Unreadable: {}", e))
+                    .unwrap_or_default(),
+            };
             Ok(InventoryDetailPrice {
                 vendor_id: row.get(0).ok(),
                 vendor_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
@@ -1243,6 +1502,7 @@ fn get_inventory_detail(
                 prev_price,
                 diff_pct,
                 pack: row.get(3).unwrap_or_else(|_| "-".to_string()),
+                pack_reading,
                 status: row.get(4).ok(),
             })
         })
@@ -1325,6 +1585,90 @@ fn get_inventory_detail(
         missing_edges.push(row.map_err(|e| e.to_string())?);
     }
 
+    let preps = load_item_preps(&conn, item_id)?;
+
+    let mut yield_tests = Vec::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT yt.test_id, yt.prep_id, p.name, yt.test_date, u.sing, yt.gross_qty, yt.trim_qty, yt.waste_qty, yt.net_qty, yt.notes
+             FROM yield_tests yt
+             LEFT JOIN item_preps p ON p.prep_id = yt.prep_id
+             LEFT JOIN units u ON u.unit_id = yt.unit_id
+             WHERE yt.item_id = ?1
+             ORDER BY yt.test_date DESC, yt.test_id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([item_id], |row| {
+            let gross_qty: Option<f64> = row.get(5).ok().flatten();
+            let net_qty: Option<f64> = row.get(8).ok().flatten();
+            let yield_pct = match (gross_qty, net_qty) {
+                (Some(g), Some(n)) if g > 0.0 => Some(n / g * 100.0),
+                _ => None,
+            };
+            Ok(YieldTest {
+                test_id: row.get(0)?,
+                prep_id: row.get(1).ok().flatten(),
+                prep_name: row.get(2).unwrap_or_else(|_| "-".to_string()),
+                test_date: row.get(3).unwrap_or_else(|_| "-".to_string()),
+                unit_name: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                gross_qty,
+                trim_qty: row.get(6).ok().flatten(),
+                waste_qty: row.get(7).ok().flatten(),
+                net_qty,
+                yield_pct,
+                notes: row.get(9).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        yield_tests.push(row.map_err(|e| e.to_string())?);
+    }
+
+    let mut counts = Vec::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.count_id, c.count_date, c.qty, c.unit_id, u.sing, c.notes
+             FROM inv_counts c
+             LEFT JOIN units u ON u.unit_id = c.unit_id
+             WHERE c.item_id = ?1
+             ORDER BY c.count_date DESC, c.count_id DESC
+             LIMIT 20",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([item_id], |row| {
+            Ok(InventoryCount {
+                count_id: row.get(0)?,
+                count_date: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                qty: row.get(2).ok().flatten(),
+                unit_id: row.get(3).ok().flatten(),
+                unit_name: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                notes: row.get(5).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        counts.push(row.map_err(|e| e.to_string())?);
+    }
+
+    let par_levels = order_guide::item_par_levels(&conn, item_id)?
+        .into_iter()
+        .map(|p| ParLevelResponse {
+            par_id: p.par_id,
+            storage_type: p.storage_type,
+            day_of_week: p.day_of_week,
+            day_name: p
+                .day_of_week
+                .and_then(|d| order_guide::DAY_NAMES.get(d as usize))
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "Every day".to_string()),
+            par_qty: p.par_qty,
+            unit_id: p.unit_id,
+            unit_name: p.unit_name,
+        })
+        .collect();
+
     Ok(InventoryDetailResponse {
         item_id,
         name,
@@ -1336,6 +1680,14 @@ fn get_inventory_detail(
         conversions,
         usage,
         missing_edges,
+        yield_pct,
+        density,
+        effective_density: effective.as_ref().map(|d| d.g_per_ml),
+        density_source: effective.map(|d| d.source).unwrap_or_default(),
+        preps,
+        yield_tests,
+        counts,
+        par_levels,
     })
 }
 
@@ -1355,8 +1707,7 @@ fn search_vendors(
             .split_whitespace()
             .collect::<Vec<_>>()
             .join(" ")
-            .replace('.', "")
-            .replace(',', "")
+            .replace(['.', ','], "")
     }
 
     let mut raw = Vec::new();
@@ -1427,7 +1778,7 @@ fn search_vendors(
     }
 
     let mut vendors: Vec<VendorListItem> = map.into_values().collect();
-    vendors.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    vendors.sort_by_key(|a| a.name.to_lowercase());
 
     let total = vendors.len() as i64;
     let start = offset as usize;
@@ -1452,11 +1803,11 @@ fn get_vendor_detail(
 ) -> Result<VendorDetailResponse, String> {
     let conn = open_initialized_db(&app)?;
 
-    let name: String = conn
+    let (name, prices_include_tax, tax_rate): (String, bool, Option<f64>) = conn
         .query_row(
-            "SELECT name FROM vendors WHERE vendor_id = ?1",
+            "SELECT name, COALESCE(prices_include_tax, 0), tax_rate FROM vendors WHERE vendor_id = ?1",
             [vendor_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get::<_, i64>(1)? != 0, row.get(2)?)),
         )
         .map_err(|e| e.to_string())?;
 
@@ -1505,6 +1856,8 @@ fn get_vendor_detail(
         price_items,
         invoice_count,
         trans_count,
+        prices_include_tax,
+        tax_rate,
     })
 }
 
@@ -1529,8 +1882,7 @@ fn list_vendors_simple(app: tauri::AppHandle) -> Result<VendorSimpleResponse, St
             .split_whitespace()
             .collect::<Vec<_>>()
             .join(" ")
-            .replace('.', "")
-            .replace(',', "")
+            .replace(['.', ','], "")
     }
 
     let mut map: HashMap<String, VendorSimple> = HashMap::new();
@@ -1548,7 +1900,7 @@ fn list_vendors_simple(app: tauri::AppHandle) -> Result<VendorSimpleResponse, St
         }
     }
     let mut vendors: Vec<VendorSimple> = map.into_values().collect();
-    vendors.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    vendors.sort_by_key(|a| a.name.to_lowercase());
     Ok(VendorSimpleResponse { vendors })
 }
 
@@ -1620,6 +1972,27 @@ fn list_items_simple(app: tauri::AppHandle) -> Result<ItemSimpleResponse, String
     Ok(ItemSimpleResponse { items })
 }
 
+#[tauri::command]
+fn list_recipes_simple(app: tauri::AppHandle) -> Result<RecipeSimpleResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let mut recipes = Vec::new();
+    let mut stmt = conn
+        .prepare("SELECT recipe_id, name FROM recipes ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(RecipeSimple {
+                recipe_id: row.get(0)?,
+                name: row.get(1).unwrap_or_else(|_| "-".to_string()),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        recipes.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(RecipeSimpleResponse { recipes })
+}
+
 #[tauri::command]
 fn set_item_purch_unit(
     app: tauri::AppHandle,
@@ -1786,6 +2159,74 @@ fn update_recipe(
     })
 }
 
+#[tauri::command]
+fn update_recipe_yield(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    yield_qty: Option<f64>,
+    yield_unit_id: Option<i64>,
+    servings: Option<f64>,
+) -> Result<PatchResponse, String> {
+    if yield_qty.map(|q| q <= 0.0).unwrap_or(false) {
+        return Err("Yield quantity must be greater than 0".to_string());
+    }
+    if servings.map(|s| s <= 0.0).unwrap_or(false) {
+        return Err("Servings must be greater than 0".to_string());
+    }
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE recipes SET yield_qty = ?2, yield_unit_id = ?3, servings = ?4 WHERE recipe_id = ?1",
+            (recipe_id, yield_qty, yield_unit_id, servings),
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Recipe {} not found", recipe_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Updated recipe yield".to_string(),
+    })
+}
+
+#[tauri::command]
+fn update_recipe_pricing(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    portion_size: Option<f64>,
+    portion_unit_id: Option<i64>,
+    target_food_cost_pct: Option<f64>,
+    menu_price: Option<f64>,
+) -> Result<PatchResponse, String> {
+    if portion_size.map(|q| q <= 0.0).unwrap_or(false) {
+        return Err("Portion size must be greater than 0".to_string());
+    }
+    if target_food_cost_pct
+        .map(|p| p <= 0.0 || p >= 100.0)
+        .unwrap_or(false)
+    {
+        return Err("Target food cost % must be between 0 and 100".to_string());
+    }
+    if menu_price.map(|p| p < 0.0).unwrap_or(false) {
+        return Err("Menu price cannot be negative".to_string());
+    }
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE recipes SET portion_size = ?2, portion_unit_id = ?3, target_food_cost_pct = ?4, menu_price = ?5
+             WHERE recipe_id = ?1",
+            (recipe_id, portion_size, portion_unit_id, target_food_cost_pct, menu_price),
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Recipe {} not found", recipe_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Updated recipe pricing".to_string(),
+    })
+}
+
 #[tauri::command]
 fn add_recp_item(
     app: tauri::AppHandle,
@@ -1794,9 +2235,19 @@ fn add_recp_item(
     item_id: i64,
     unit_id: Option<i64>,
     qty: Option<f64>,
+    sub_recipe_id: Option<i64>,
 ) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
 
+    if let Some(sub_id) = sub_recipe_id {
+        if qty.unwrap_or(0.0) > 0.0 && recipe_reaches(&conn, sub_id, recipe_id)? {
+            return Err(format!(
+                "Recipe {} already uses recipe {}; adding it would create a cycle",
+                sub_id, recipe_id
+            ));
+        }
+    }
+
     if let Some(recp_item_id) = recp_item_id {
         if qty.unwrap_or(0.0) <= 0.0 {
             let deleted = conn
@@ -1816,14 +2267,14 @@ fn add_recp_item(
 
         let updated = conn
             .execute(
-                "UPDATE recp_items SET item_id = ?3, unit_id = ?4, qty = ?5 WHERE recipe_id = ?1 AND recp_item_id = ?2",
-                (recipe_id, recp_item_id, item_id, unit_id, qty),
+                "UPDATE recp_items SET item_id = ?3, unit_id = ?4, qty = ?5, sub_recipe_id = ?6 WHERE recipe_id = ?1 AND recp_item_id = ?2",
+                (recipe_id, recp_item_id, item_id, unit_id, qty, sub_recipe_id),
             )
             .map_err(|e| e.to_string())?;
         if updated == 0 {
             conn.execute(
-                "INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty) VALUES (?1, ?2, ?3, ?4, ?5)",
-                (recipe_id, recp_item_id, item_id, unit_id, qty),
+                "INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty, sub_recipe_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                (recipe_id, recp_item_id, item_id, unit_id, qty, sub_recipe_id),
             )
             .map_err(|e| e.to_string())?;
         }
@@ -1842,8 +2293,8 @@ fn add_recp_item(
         .map_err(|e| e.to_string())?;
 
     conn.execute(
-        "INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty) VALUES (?1, ?2, ?3, ?4, ?5)",
-        (recipe_id, next_id, item_id, unit_id, qty),
+        "INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty, sub_recipe_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (recipe_id, next_id, item_id, unit_id, qty, sub_recipe_id),
     )
     .map_err(|e| e.to_string())?;
 
@@ -1854,6 +2305,7 @@ fn add_recp_item(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn upsert_convunit(
     app: tauri::AppHandle,
     item_id: i64,
@@ -1872,7 +2324,7 @@ fn upsert_convunit(
     let updated = conn
         .execute(
             "UPDATE convunit
-             SET qty1 = ?5, qty2 = ?6, status = COALESCE(?7, status), is_calculated = COALESCE(is_calculated, 1)
+             SET qty1 = ?5, qty2 = ?6, status = COALESCE(?7, status), is_calculated = 0
              WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id1 = ?3 AND unit_id2 = ?4",
             (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status),
         )
@@ -1882,7 +2334,7 @@ fn upsert_convunit(
         conn.execute(
             "INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, is_calculated)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, Some(1i64)),
+            (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, Some(0i64)),
         )
         .map_err(|e| e.to_string())?;
     }
@@ -1894,6 +2346,7 @@ fn upsert_convunit(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn update_invoice(
     app: tauri::AppHandle,
     invoice_id: i64,
@@ -1926,6 +2379,8 @@ fn update_invoice(
     if updated == 0 {
         return Err(format!("Invoice {} not found", invoice_id));
     }
+    landed::allocate_invoice(&conn, invoice_id)?;
+    reconcile::reconcile_invoice(&conn, invoice_id)?;
 
     Ok(PatchResponse {
         ok: true,
@@ -1961,6 +2416,17 @@ fn update_trans_line(
     if updated == 0 {
         return Err(format!("Transaction {} not found", trans_id));
     }
+    let invoice_id: Option<i64> = conn
+        .query_row(
+            "SELECT invoice_id FROM trans WHERE trans_id = ?1",
+            [trans_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if let Some(invoice_id) = invoice_id {
+        landed::allocate_invoice(&conn, invoice_id)?;
+        reconcile::reconcile_invoice(&conn, invoice_id)?;
+    }
 
     Ok(PatchResponse {
         ok: true,
@@ -1970,40 +2436,30 @@ fn update_trans_line(
 
 #[tauri::command]
 fn recalculate_reports(app: tauri::AppHandle) -> Result<PatchResponse, String> {
-    let conn = open_initialized_db(&app)?;
-
-    conn.execute(
-        "DELETE FROM missing_purch_unit
-         WHERE item_id IN (
-           SELECT DISTINCT item_id
-           FROM inv_units
-           WHERE purch_unit_id IS NOT NULL
-         )",
-        [],
-    )
-    .map_err(|e| e.to_string())?;
-
-    conn.execute(
-        "DELETE FROM missing_data_report
-         WHERE recipe_id NOT IN (SELECT recipe_id FROM recipes)",
-        [],
-    )
-    .map_err(|e| e.to_string())?;
-
-    let overview = get_conversion_overview(app)?;
+    let mut conn = open_initialized_db(&app)?;
+    let summary = with_tx(&mut conn, suggest::regenerate)?;
     Ok(PatchResponse {
         ok: true,
         message: format!(
-            "Recalculated reports. suggestions={}, todo={}, missing_edges={}, missing_purch={}, missing_data={}",
-            overview.suggestions,
-            overview.todo,
-            overview.missing_edges,
-            overview.missing_purch,
-            overview.missing_data
+            "Recalculated reports. suggestions={} ({} safe), todo={}, missing_edges={}, missing_purch={}, missing_data={}",
+            summary.suggestions,
+            summary.safe,
+            summary.todo,
+            summary.missing_edges,
+            summary.missing_purch,
+            summary.incomplete_recipes
         ),
     })
 }
 
+/// Opens the database with the conversion suggestions and missing-data
+/// reports brought up to date.
+fn open_with_fresh_suggestions(app: &tauri::AppHandle) -> Result<rusqlite::Connection, String> {
+    let mut conn = open_initialized_db(app)?;
+    with_tx(&mut conn, suggest::ensure_fresh)?;
+    Ok(conn)
+}
+
 #[tauri::command]
 fn revert_db(app: tauri::AppHandle) -> Result<PatchResponse, String> {
     let path = db_path(&app)?;
@@ -2021,6 +2477,9 @@ fn revert_db(app: tauri::AppHandle) -> Result<PatchResponse, String> {
     init_db(&conn)?;
     with_tx(&mut conn, |tx| {
         clear_tables(tx)?;
+        prune_orphans(tx)?;
+        tx.execute("DELETE FROM recipe_costs", [])
+            .map_err(|e| e.to_string())?;
         Ok(())
     })?;
 
@@ -2070,6 +2529,7 @@ fn upsert_manual_price(
         pack.trim().to_string()
     };
 
+    let alerted = price_alerts::check_manual_price(&conn, item_id, vendor_id, price)?;
     let updated = conn
         .execute(
             "UPDATE inv_prices
@@ -2090,12 +2550,22 @@ fn upsert_manual_price(
     Ok(PatchResponse {
         ok: true,
         message: format!(
-            "Saved price {} for item {} vendor {}",
-            price, item_id, vendor_id
+            "Saved price {} for item {} vendor {}{}",
+            price,
+            item_id,
+            vendor_id,
+            if alerted {
+                " (price increase alert raised)"
+            } else {
+                ""
+            }
         ),
     })
 }
 
+/// rowid, qty1, qty2, status, is_calculated
+type ConvunitRow = (i64, f64, f64, Option<i64>, Option<i64>);
+
 #[tauri::command]
 fn merge_vendor(
     app: tauri::AppHandle,
@@ -2241,7 +2711,7 @@ fn merge_vendor(
         for (row_id, item_id, unit_id1, unit_id2, qty1, qty2, status, is_calculated) in
             source_conversions
         {
-            let target_row: Option<(i64, f64, f64, Option<i64>, Option<i64>)> = tx
+            let target_row: Option<ConvunitRow> = tx
                 .query_row(
                     "SELECT rowid, qty1, qty2, status, is_calculated
                          FROM convunit
@@ -2354,181 +2824,80 @@ fn merge_vendor(
 fn search_recipes(
     app: tauri::AppHandle,
     query: String,
+    order_by: Option<String>,
     limit: u32,
     offset: u32,
 ) -> Result<RecipeListResponse, String> {
     let conn = open_initialized_db(&app)?;
     let trimmed = query.trim();
     let like = format!("%{}%", trimmed);
+    // Recipes without a value sort last regardless of direction.
+    let order = match order_by.unwrap_or_default().trim() {
+        "margin" => "c.margin IS NULL, c.margin DESC, r.name",
+        "food_cost_pct" => "c.food_cost_pct IS NULL, c.food_cost_pct, r.name",
+        "cost_per_portion" => "c.cost_per_portion IS NULL, c.cost_per_portion, r.name",
+        _ => "r.name",
+    };
 
     let total: i64 = conn
         .query_row("SELECT COUNT(*) FROM recipes", [], |row| row.get(0))
         .map_err(|e| e.to_string())?;
 
-    let mut recipes = Vec::new();
-    let filtered = if trimmed.is_empty() {
-        let filtered = total;
-        let mut stmt = conn
-            .prepare("SELECT recipe_id, name FROM recipes ORDER BY name LIMIT ?1 OFFSET ?2")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map((limit, offset), |row| {
-                let recipe_id: i64 = row.get(0)?;
-                let name: String = row.get(1)?;
-                Ok((recipe_id, name))
-            })
-            .map_err(|e| e.to_string())?;
-
-        for row in rows {
-            let (recipe_id, name) = row.map_err(|e| e.to_string())?;
-            let item_count: i64 = conn
-                .query_row(
-                    "SELECT COUNT(*) FROM recp_items WHERE recipe_id = ?1",
-                    [recipe_id],
-                    |r| r.get(0),
-                )
-                .map_err(|e| e.to_string())?;
-            recipes.push(RecipeListItem {
-                recipe_id,
-                name,
-                item_count,
-            });
-        }
-        filtered
+    let filtered: i64 = if trimmed.is_empty() {
+        total
     } else {
-        let filtered: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM recipes WHERE name LIKE ?1 OR CAST(recipe_id AS TEXT) LIKE ?1",
-                [&like],
-                |row| row.get(0),
-            )
-            .map_err(|e| e.to_string())?;
-        let mut stmt = conn
-            .prepare(
-                "SELECT recipe_id, name FROM recipes WHERE name LIKE ?1 OR CAST(recipe_id AS TEXT) LIKE ?1 ORDER BY name LIMIT ?2 OFFSET ?3",
-            )
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map((&like, limit, offset), |row| {
-                let recipe_id: i64 = row.get(0)?;
-                let name: String = row.get(1)?;
-                Ok((recipe_id, name))
-            })
-            .map_err(|e| e.to_string())?;
-
-        for row in rows {
-            let (recipe_id, name) = row.map_err(|e| e.to_string())?;
-            let item_count: i64 = conn
-                .query_row(
-                    "SELECT COUNT(*) FROM recp_items WHERE recipe_id = ?1",
-                    [recipe_id],
-                    |r| r.get(0),
-                )
-                .map_err(|e| e.to_string())?;
-            recipes.push(RecipeListItem {
-                recipe_id,
-                name,
-                item_count,
-            });
-        }
-        filtered
+        conn.query_row(
+            "SELECT COUNT(*) FROM recipes WHERE name LIKE ?1 OR CAST(recipe_id AS TEXT) LIKE ?1",
+            [&like],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?
     };
 
+    let sql = format!(
+        "SELECT r.recipe_id, r.name,
+                (SELECT COUNT(*) FROM recp_items ri WHERE ri.recipe_id = r.recipe_id),
+                c.servings, c.cost_per_portion, c.menu_price, c.food_cost_pct, c.margin
+         FROM recipes r
+         LEFT JOIN recipe_costs c ON c.recipe_id = r.recipe_id
+         WHERE ?1 = '' OR r.name LIKE ?2 OR CAST(r.recipe_id AS TEXT) LIKE ?2
+         ORDER BY {} LIMIT ?3 OFFSET ?4",
+        order
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let recipes = stmt
+        .query_map((trimmed, &like, limit as i64, offset as i64), |row| {
+            Ok(RecipeListItem {
+                recipe_id: row.get(0)?,
+                name: row.get(1)?,
+                item_count: row.get(2)?,
+                servings: row.get(3)?,
+                cost_per_portion: row.get(4)?,
+                menu_price: row.get(5)?,
+                food_cost_pct: row.get(6)?,
+                margin: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
     Ok(RecipeListResponse {
         recipes,
         total,
         filtered,
+        costs_stale: pricing::costs_stale(&conn)?,
     })
 }
 
-fn build_conversion_edges(
-    conn: &rusqlite::Connection,
-    item_id: i64,
-    vendor_id: Option<i64>,
-) -> Result<Vec<(i64, i64, f64, f64)>, String> {
-    let mut edges = Vec::new();
-    if let Some(vendor_id) = vendor_id {
-        let mut stmt = conn
-            .prepare(
-                "SELECT unit_id1, unit_id2, qty1, qty2
-                 FROM convunit
-                 WHERE item_id = ?1 AND (vendor_id = ?2 OR vendor_id = 0)",
-            )
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map((item_id, vendor_id), |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-            })
-            .map_err(|e| e.to_string())?;
-        for row in rows {
-            edges.push(row.map_err(|e| e.to_string())?);
-        }
-    } else {
-        let mut stmt = conn
-            .prepare(
-                "SELECT unit_id1, unit_id2, qty1, qty2
-                 FROM convunit
-                 WHERE item_id = ?1",
-            )
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([item_id], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-            })
-            .map_err(|e| e.to_string())?;
-        for row in rows {
-            edges.push(row.map_err(|e| e.to_string())?);
-        }
-    }
-    Ok(edges)
-}
-
-fn bfs_conversion_factor(
-    edges: &[(i64, i64, f64, f64)],
-    from_unit: i64,
-    to_unit: i64,
-    max_hops: usize,
-) -> Option<(f64, usize)> {
-    use std::collections::{HashMap, VecDeque};
-
-    if from_unit == to_unit {
-        return Some((1.0, 0));
-    }
-
-    let mut graph: HashMap<i64, Vec<(i64, f64)>> = HashMap::new();
-    for (u1, u2, q1, q2) in edges.iter() {
-        if *q1 > 0.0 && *q2 > 0.0 {
-            graph.entry(*u1).or_default().push((*u2, q2 / q1));
-            graph.entry(*u2).or_default().push((*u1, q1 / q2));
-        }
-    }
-
-    let mut queue = VecDeque::new();
-    let mut visited: HashMap<i64, usize> = HashMap::new();
-    queue.push_back((from_unit, 1.0f64, 0usize));
-    visited.insert(from_unit, 0);
-
-    while let Some((unit, factor, hops)) = queue.pop_front() {
-        if hops >= max_hops {
-            continue;
-        }
-        if let Some(neighbors) = graph.get(&unit) {
-            for (next, edge_factor) in neighbors {
-                let next_hops = hops + 1;
-                if visited.get(next).map(|h| *h <= next_hops).unwrap_or(false) {
-                    continue;
-                }
-                let new_factor = factor * edge_factor;
-                if *next == to_unit {
-                    return Some((new_factor, next_hops));
-                }
-                visited.insert(*next, next_hops);
-                queue.push_back((*next, new_factor, next_hops));
-            }
-        }
-    }
-
-    None
+#[tauri::command]
+fn refresh_recipe_costs(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let priced = with_tx(&mut conn, pricing::refresh_costs)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Recosted {} recipes", priced),
+    })
 }
 
 #[tauri::command]
@@ -2555,154 +2924,130 @@ fn get_recipe_detail(
         )
         .map_err(|e| e.to_string())?;
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT ri.recp_item_id, ri.item_id, i.name, ri.unit_id, u.sing, ri.qty
-             FROM recp_items ri
-             LEFT JOIN items i ON i.item_id = ri.item_id
-             LEFT JOIN units u ON u.unit_id = ri.unit_id
-             WHERE ri.recipe_id = ?1
-             ORDER BY i.name",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map([recipe_id], |row| {
-            let recp_item_id: i64 = row.get(0)?;
-            let item_id: i64 = row.get(1)?;
-            let item_name: Option<String> = row.get(2).ok();
-            let unit_id: Option<i64> = row.get(3).ok();
-            let unit_name: Option<String> = row.get(4).ok();
-            let qty: Option<f64> = row.get(5).ok();
-            Ok((recp_item_id, item_id, item_name, unit_id, unit_name, qty))
+    let costed = cost_recipe(&conn, recipe_id, &mut CostContext::default())?;
+    let yld = recipe_yield(&conn, recipe_id)?;
+    let pricing =
+        pricing::recipe_pricing(&conn, recipe_id, costed.total_cost, costed.missing_costs)?;
+
+    let ingredients: Vec<RecipeIngredient> = costed
+        .lines
+        .into_iter()
+        .map(|line| RecipeIngredient {
+            conversion_summary: line
+                .cost
+                .conversion
+                .as_ref()
+                .map(|p| {
+                    format!(
+                        "1 {} = {} {}",
+                        line.unit_name,
+                        costing::format_qty(p.factor),
+                        line.cost.purch_unit_name
+                    )
+                })
+                .unwrap_or_default(),
+            conversion_path: line
+                .cost
+                .conversion
+                .as_ref()
+                .map(|p| {
+                    costing::describe_conversion(&conn, p)
+                        .into_iter()
+                        .map(Into::into)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            recp_item_id: line.recp_item_id,
+            item_id: line.item_id,
+            item_name: line.item_name,
+            unit_id: line.unit_id,
+            unit_name: line.unit_name,
+            qty: line.qty,
+            purch_unit_id: line.cost.purch_unit_id,
+            purch_unit_name: line.cost.purch_unit_name,
+            price: line.cost.price,
+            extended_cost: line.cost.extended_cost,
+            cost_status: line.cost.cost_status,
+            sub_recipe_id: line.sub_recipe_id,
+            prep_id: line.prep_id,
+            prep_name: line.prep_name,
+            yield_pct: line.cost.yield_pct,
+            ap_cost: line.cost.ap_cost,
         })
-        .map_err(|e| e.to_string())?;
-
-    let mut ingredients = Vec::new();
-    let mut total_cost = 0.0f64;
-    let mut missing_costs = 0i64;
-
-    for row in rows {
-        let (recp_item_id, item_id, item_name, unit_id, unit_name, qty) = row.map_err(|e| e.to_string())?;
-
-        let item_name = item_name.unwrap_or_else(|| "(unknown item)".to_string());
-        let unit_name = unit_name.unwrap_or_else(|| "-".to_string());
-
-        let purch_unit_id: Option<i64> = conn
-            .query_row(
-                "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 AND is_default = 1 LIMIT 1",
-                [item_id],
-                |row| row.get(0),
-            )
-            .ok();
-
-        let purch_unit_id = purch_unit_id.or_else(|| {
-            conn.query_row(
-                "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 LIMIT 1",
-                [item_id],
-                |row| row.get(0),
-            )
-            .ok()
-        });
-
-        let purch_unit_name = if let Some(pid) = purch_unit_id {
-            conn.query_row("SELECT sing FROM units WHERE unit_id = ?1", [pid], |row| {
-                row.get(0)
-            })
-            .unwrap_or_else(|_| "-".to_string())
-        } else {
-            "-".to_string()
-        };
-
-        let price_vendor: Option<(f64, i64)> = conn
-            .query_row(
-                "SELECT price, vendor_id FROM inv_prices WHERE item_id = ?1 ORDER BY vendor_id LIMIT 1",
-                [item_id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok()
-            .or_else(|| {
-                conn.query_row(
-                    "SELECT price, vendor_id FROM trans WHERE item_id = ?1 AND price IS NOT NULL AND price > 0 ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
-                    [item_id],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
-                )
-                .ok()
-            });
-        let price = price_vendor.map(|v| v.0);
-        let price_vendor_id = price_vendor.map(|v| v.1);
-
-        let mut factor: Option<f64> = None;
-        let mut hops = 0usize;
-        if let (Some(recipe_unit_id), Some(purch_unit_id)) = (unit_id, purch_unit_id) {
-            if recipe_unit_id == purch_unit_id {
-                factor = Some(1.0);
-            } else {
-                let edges = build_conversion_edges(&conn, item_id, price_vendor_id)?;
-                if let Some((found_factor, found_hops)) =
-                    bfs_conversion_factor(&edges, recipe_unit_id, purch_unit_id, 6)
-                {
-                    factor = Some(found_factor);
-                    hops = found_hops;
-                }
-            }
-        }
-
-        let qty_valid = qty.unwrap_or(0.0) > 0.0;
-
-        let (cost_status, extended_cost) = if !qty_valid {
-            ("Missing qty".to_string(), None)
-        } else if purch_unit_id.is_none() {
-            ("Missing purch unit".to_string(), None)
-        } else if price.is_none() {
-            ("Missing price".to_string(), None)
-        } else if factor.is_none() {
-            ("Needs conversion".to_string(), None)
-        } else {
-            let cost = qty.unwrap_or(0.0) * factor.unwrap_or(1.0) * price.unwrap_or(0.0);
-            if hops > 0 {
-                (format!("OK ({} hops)", hops), Some(cost))
-            } else {
-                ("OK".to_string(), Some(cost))
-            }
-        };
-
-        if let Some(cost) = extended_cost {
-            total_cost += cost;
-        } else {
-            missing_costs += 1;
-        }
-
-        ingredients.push(RecipeIngredient {
-            recp_item_id,
-            item_id,
-            item_name,
-            unit_id,
-            unit_name,
-            qty,
-            purch_unit_id,
-            purch_unit_name,
-            price,
-            extended_cost,
-            cost_status,
-        });
-    }
+        .collect();
 
-    let allergens = detect_allergens(&ingredients);
+    let allergens = detect_allergens(&costed.leaf_names);
+    let cost_per_yield_unit = match yld.qty {
+        Some(q) if costed.missing_costs == 0 => Some(costed.total_cost / q),
+        _ => None,
+    };
 
     Ok(RecipeDetailResponse {
         recipe_id,
         name,
         item_count,
-        total_cost,
-        missing_costs,
+        total_cost: costed.total_cost,
+        missing_costs: costed.missing_costs,
         instructions,
         ingredients,
         allergens,
+        yield_qty: yld.qty,
+        yield_unit_id: yld.unit_id,
+        yield_unit_name: yld.unit_name,
+        cost_per_yield_unit,
+        servings: pricing.servings,
+        portion_size: pricing.portion_size,
+        portion_unit_id: pricing.portion_unit_id,
+        portion_unit_name: pricing.portion_unit_name,
+        cost_per_portion: pricing.cost_per_portion,
+        target_food_cost_pct: pricing.recipe_target_pct,
+        effective_target_pct: pricing.target_food_cost_pct,
+        suggested_price: pricing.suggested_price,
+        menu_price: pricing.menu_price,
+        food_cost_pct: pricing.food_cost_pct,
+        margin: pricing.margin,
+    })
+}
+
+#[tauri::command]
+fn scale_recipe(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    mode: String,
+    target: f64,
+) -> Result<ScaledRecipeResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let factor = scaling::scale_factor(&conn, recipe_id, &mode, target)?;
+    let scaled = scaling::scale_recipe(&conn, recipe_id, factor)?;
+    Ok(ScaledRecipeResponse {
+        recipe_id,
+        name: scaled.name,
+        factor: scaled.factor,
+        servings: scaled.servings,
+        yield_qty: scaled.yield_qty,
+        yield_unit_name: scaled.yield_unit_name,
+        total_cost: scaled.total_cost,
+        missing_costs: scaled.missing_costs,
+        lines: scaled
+            .lines
+            .into_iter()
+            .map(|line| ScaledRecipeLine {
+                recp_item_id: line.recp_item_id,
+                item_name: line.item_name,
+                orig_qty: line.orig_qty,
+                orig_unit_name: line.orig_unit_name,
+                qty: line.qty,
+                unit_id: line.unit_id,
+                unit_name: line.unit_name,
+                display_qty: line.display_qty,
+                extended_cost: line.extended_cost,
+                cost_status: line.cost_status,
+            })
+            .collect(),
     })
 }
 
-fn detect_allergens(ingredients: &[RecipeIngredient]) -> Vec<String> {
+fn detect_allergens(item_names: &[String]) -> Vec<String> {
     const ALLERGEN_RULES: &[(&str, &[&str])] = &[
         ("Eggs", &["egg", "eggs", "meringue", "mayo", "mayonnaise", "aioli", "custard", "hollandaise"]),
         ("Fish/Shellfish", &["fish", "salmon", "tuna", "cod", "halibut", "trout", "tilapia", "bass",
@@ -2731,8 +3076,8 @@ fn detect_allergens(ingredients: &[RecipeIngredient]) -> Vec<String> {
 
     for (label, keywords) in ALLERGEN_RULES {
         let mut detected = false;
-        'outer: for ingredient in ingredients {
-            let name_lower = ingredient.item_name.to_lowercase();
+        'outer: for item_name in item_names {
+            let name_lower = item_name.to_lowercase();
             for kw in *keywords {
                 // Check word-boundary-like match to avoid false positives
                 // e.g. "bass" shouldn't match "basil"
@@ -2768,7 +3113,7 @@ fn keyword_matches(haystack: &str, keyword: &str) -> bool {
 
 #[tauri::command]
 fn get_conversion_overview(app: tauri::AppHandle) -> Result<ConversionOverview, String> {
-    let conn = open_initialized_db(&app)?;
+    let conn = open_with_fresh_suggestions(&app)?;
     let suggestions: i64 = conn
         .query_row("SELECT COUNT(*) FROM conv_suggestions", [], |r| r.get(0))
         .unwrap_or(0);
@@ -2807,7 +3152,7 @@ fn list_conv_suggestions(
     limit: u32,
     offset: u32,
 ) -> Result<ConversionSuggestionResponse, String> {
-    let conn = open_initialized_db(&app)?;
+    let conn = open_with_fresh_suggestions(&app)?;
     let table = match table.as_str() {
         "conv_suggestions" => "conv_suggestions",
         "conv_suggestions_safe" => "conv_suggestions_safe",
@@ -2819,9 +3164,9 @@ fn list_conv_suggestions(
 
     let mut rows = Vec::new();
     let sql = format!(
-        "SELECT item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, recipe_unit, purch_unit, hits, derived_from, hops, path
+        "SELECT item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, recipe_unit, purch_unit, hits, derived_from, hops, path, confidence
          FROM {}
-         ORDER BY hits DESC
+         ORDER BY hits DESC, confidence DESC
          LIMIT ?1 OFFSET ?2",
         table
     );
@@ -2841,6 +3186,7 @@ fn list_conv_suggestions(
                 derived_from: row.get(9).unwrap_or_else(|_| "-".to_string()),
                 hops: row.get(10).ok(),
                 path: row.get(11).unwrap_or_else(|_| "-".to_string()),
+                confidence: row.get(12).ok().flatten(),
             })
         })
         .map_err(|e| e.to_string())?;
@@ -2856,7 +3202,7 @@ fn list_conv_todo(
     limit: u32,
     offset: u32,
 ) -> Result<ConversionTodoResponse, String> {
-    let conn = open_initialized_db(&app)?;
+    let conn = open_with_fresh_suggestions(&app)?;
     let total: i64 = conn
         .query_row("SELECT COUNT(*) FROM conv_todo", [], |r| r.get(0))
         .unwrap_or(0);
@@ -2889,756 +3235,3943 @@ fn list_conv_todo(
     Ok(ConversionTodoResponse { rows, total })
 }
 
-#[tauri::command]
-fn list_missing_edges(
-    app: tauri::AppHandle,
-    limit: u32,
-    offset: u32,
-) -> Result<MissingEdgeResponse, String> {
-    let conn = open_initialized_db(&app)?;
-    let total: i64 = conn
-        .query_row("SELECT COUNT(*) FROM missing_edges", [], |r| r.get(0))
-        .unwrap_or(0);
-    let mut rows = Vec::new();
-    let mut stmt = conn
-        .prepare(
-            "SELECT item_id, item_name, vendor_id, recipe_unit_id, recipe_unit, purch_unit_id, purch_unit, hits
-             FROM missing_edges
-             ORDER BY hits DESC
-             LIMIT ?1 OFFSET ?2",
-        )
-        .map_err(|e| e.to_string())?;
-    let iter = stmt
-        .query_map((limit, offset), |row| {
-            Ok(MissingEdgeRow {
-                item_id: row.get(0)?,
-                item_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
-                vendor_id: row.get(2).unwrap_or(0),
-                recipe_unit_id: row.get(3)?,
-                recipe_unit: row.get(4).unwrap_or_else(|_| "-".to_string()),
-                purch_unit_id: row.get(5)?,
-                purch_unit: row.get(6).unwrap_or_else(|_| "-".to_string()),
-                hits: row.get(7).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    for row in iter {
-        rows.push(row.map_err(|e| e.to_string())?);
+#[derive(Serialize)]
+struct UnitStandardResponse {
+    unit_id: i64,
+    name: String,
+    plural: String,
+    unit_type: Option<i64>,
+    unit_kind: Option<i64>,
+    standard: Option<String>,
+    dimension: Option<String>,
+    base_qty: Option<f64>,
+    source: String,
+    aliases: Vec<String>,
+}
+
+impl From<units::UnitStandard> for UnitStandardResponse {
+    fn from(u: units::UnitStandard) -> Self {
+        UnitStandardResponse {
+            unit_id: u.unit_id,
+            name: u.name,
+            plural: u.plural,
+            unit_type: u.unit_type,
+            unit_kind: u.unit_kind,
+            standard: u.standard,
+            dimension: u.dimension,
+            base_qty: u.base_qty,
+            source: u.source,
+            aliases: u.aliases,
+        }
     }
-    Ok(MissingEdgeResponse { rows, total })
+}
+
+#[derive(Serialize)]
+struct StandardMeasureResponse {
+    name: String,
+    dimension: String,
+    base_qty: f64,
+}
+
+#[derive(Serialize)]
+struct UnitStandardsResponse {
+    units: Vec<UnitStandardResponse>,
+    measures: Vec<StandardMeasureResponse>,
+    /// Conversion todo and local conversion rows that now convert.
+    covered_todo: usize,
+    covered_missing: usize,
+    reference_densities: bool,
 }
 
 #[tauri::command]
-fn list_missing_purch_unit(
-    app: tauri::AppHandle,
-    limit: u32,
-    offset: u32,
-) -> Result<MissingPurchResponse, String> {
+fn list_unit_standards(app: tauri::AppHandle) -> Result<UnitStandardsResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let total: i64 = conn
-        .query_row("SELECT COUNT(*) FROM missing_purch_unit", [], |r| r.get(0))
-        .unwrap_or(0);
-    let mut rows = Vec::new();
-    let mut stmt = conn
-        .prepare(
-            "SELECT item_id, item_name, usage_count
-             FROM missing_purch_unit
-             ORDER BY usage_count DESC
-             LIMIT ?1 OFFSET ?2",
-        )
-        .map_err(|e| e.to_string())?;
-    let iter = stmt
-        .query_map((limit, offset), |row| {
-            Ok(MissingPurchRow {
-                item_id: row.get(0)?,
-                item_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
-                usage_count: row.get(2).ok(),
+    Ok(UnitStandardsResponse {
+        units: units::list_unit_standards(&conn)?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        measures: units::standard_measures()
+            .map(|(name, dimension, base_qty)| StandardMeasureResponse {
+                name: name.to_string(),
+                dimension: dimension.to_string(),
+                base_qty,
             })
-        })
-        .map_err(|e| e.to_string())?;
-    for row in iter {
-        rows.push(row.map_err(|e| e.to_string())?);
-    }
-    Ok(MissingPurchResponse { rows, total })
+            .collect(),
+        covered_todo: units::covered_rows(&conn, "conv_todo")?.len(),
+        covered_missing: units::covered_rows(&conn, "missing_edges")?.len(),
+        reference_densities: density::use_reference_densities(&conn),
+    })
 }
 
 #[tauri::command]
-fn list_missing_data_report(
+fn set_unit_standard(
     app: tauri::AppHandle,
-    limit: u32,
-    offset: u32,
-) -> Result<MissingDataResponse, String> {
-    let conn = open_initialized_db(&app)?;
-    let total: i64 = conn
-        .query_row("SELECT COUNT(*) FROM missing_data_report", [], |r| r.get(0))
-        .unwrap_or(0);
-    let mut rows = Vec::new();
-    let mut stmt = conn
-        .prepare(
-            "SELECT recipe_id, recipe_name, missing_a, missing_b, missing_c
-             FROM missing_data_report
-             ORDER BY missing_a DESC
-             LIMIT ?1 OFFSET ?2",
-        )
-        .map_err(|e| e.to_string())?;
-    let iter = stmt
-        .query_map((limit, offset), |row| {
-            Ok(MissingDataRow {
-                recipe_id: row.get(0)?,
-                recipe_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
-                missing_a: row.get(2).ok(),
-                missing_b: row.get(3).ok(),
-                missing_c: row.get(4).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    for row in iter {
-        rows.push(row.map_err(|e| e.to_string())?);
-    }
-    Ok(MissingDataResponse { rows, total })
+    unit_id: i64,
+    standard: Option<String>,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| {
+        units::set_unit_standard(tx, unit_id, standard.as_deref().map(str::trim))
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: match standard.as_deref().map(str::trim) {
+            None => "Unit standard will be recognised from its names".to_string(),
+            Some("") => "Unit marked as not a standard measure".to_string(),
+            Some(s) => format!("Unit now converts as {}", s),
+        },
+    })
 }
 
 #[tauri::command]
-fn list_invoices(
+fn save_unit_alias(
     app: tauri::AppHandle,
-    query: String,
-    vendor_id: Option<i64>,
-    date_from: String,
-    date_to: String,
-    limit: u32,
-    offset: u32,
-) -> Result<InvoiceListResponse, String> {
-    let conn = open_initialized_db(&app)?;
-    let trimmed = query.trim();
-    let like = format!("%{}%", trimmed);
+    alias: String,
+    unit_id: i64,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| units::save_unit_alias(tx, &alias, unit_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Saved alias '{}'", alias.trim()),
+    })
+}
 
-    let mut where_clauses = Vec::new();
-    let mut params: Vec<rusqlite::types::Value> = Vec::new();
-    if !trimmed.is_empty() {
-        where_clauses.push("i.invoice_no LIKE ?1 OR CAST(i.invoice_id AS TEXT) LIKE ?1 OR CAST(i.vendor_id AS TEXT) LIKE ?1");
-        params.push(rusqlite::types::Value::from(like.clone()));
-    }
-    if let Some(vendor_id) = vendor_id {
-        where_clauses.push("i.vendor_id = ?");
-        params.push(rusqlite::types::Value::from(vendor_id));
-    }
-    if !date_from.trim().is_empty() {
-        where_clauses.push("i.invoice_date >= ?");
-        params.push(rusqlite::types::Value::from(date_from.trim().to_string()));
-    }
-    if !date_to.trim().is_empty() {
-        where_clauses.push("i.invoice_date <= ?");
-        params.push(rusqlite::types::Value::from(date_to.trim().to_string()));
-    }
+#[tauri::command]
+fn delete_unit_alias(app: tauri::AppHandle, alias: String) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| units::delete_unit_alias(tx, &alias))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Deleted alias '{}'", alias.trim()),
+    })
+}
 
-    let where_sql = if where_clauses.is_empty() {
-        "".to_string()
-    } else {
-        format!("WHERE {}", where_clauses.join(" AND "))
-    };
+#[tauri::command]
+fn refresh_standard_units(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let count = with_tx(&mut conn, units::refresh_standard_units)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("{} units convert as standard weights or volumes", count),
+    })
+}
 
-    let total_sql = format!("SELECT COUNT(*) FROM invoices i {}", where_sql);
-    let total: i64 = conn
-        .query_row(
-            &total_sql,
-            rusqlite::params_from_iter(params.clone()),
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn clear_covered_conv_todo(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let (todo, missing) = with_tx(&mut conn, |tx| {
+        Ok((
+            units::clear_covered(tx, "conv_todo")?,
+            units::clear_covered(tx, "missing_edges")?,
+        ))
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Cleared {} todo and {} local conversions that now convert",
+            todo, missing
+        ),
+    })
+}
 
-    let mut invoices = Vec::new();
-    let mut params_for_query = params.clone();
-    params_for_query.push(rusqlite::types::Value::from(limit as i64));
-    params_for_query.push(rusqlite::types::Value::from(offset as i64));
-    let list_sql = format!(
-        "SELECT i.invoice_id, i.invoice_no, i.vendor_id, v.name, i.invoice_date, i.total
-         FROM invoices i
-         LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
-         {}
-         ORDER BY i.invoice_date DESC
-         LIMIT ? OFFSET ?",
-        where_sql
-    );
-    let mut stmt = conn.prepare(&list_sql).map_err(|e| e.to_string())?;
-    let iter = stmt
-        .query_map(rusqlite::params_from_iter(params_for_query), |row| {
-            Ok(InvoiceListItem {
-                invoice_id: row.get(0)?,
-                invoice_no: row.get(1).unwrap_or_else(|_| "-".to_string()),
-                vendor_id: row.get(2).ok(),
-                vendor_name: row.get(3).unwrap_or_else(|_| "-".to_string()),
-                invoice_date: row.get(4).unwrap_or_else(|_| "-".to_string()),
-                total: row.get(5).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    for row in iter {
-        invoices.push(row.map_err(|e| e.to_string())?);
+#[derive(Serialize)]
+struct ConversionIssueResponse {
+    kind: String,
+    row_id: i64,
+    item_id: i64,
+    item_name: String,
+    vendor_id: i64,
+    vendor_name: String,
+    unit1_name: String,
+    unit2_name: String,
+    qty1: Option<f64>,
+    qty2: Option<f64>,
+    detail: String,
+    off_by: Option<f64>,
+    suggested_qty2: Option<f64>,
+    fixes: Vec<String>,
+    recipe_count: i64,
+}
+
+impl From<convcheck::ConversionIssue> for ConversionIssueResponse {
+    fn from(issue: convcheck::ConversionIssue) -> Self {
+        ConversionIssueResponse {
+            kind: issue.kind,
+            row_id: issue.row_id,
+            item_id: issue.item_id,
+            item_name: issue.item_name,
+            vendor_id: issue.vendor_id,
+            vendor_name: issue.vendor_name,
+            unit1_name: issue.unit1_name,
+            unit2_name: issue.unit2_name,
+            qty1: issue.qty1,
+            qty2: issue.qty2,
+            detail: issue.detail,
+            off_by: issue.off_by,
+            suggested_qty2: issue.suggested_qty2,
+            fixes: issue.fixes.into_iter().map(str::to_string).collect(),
+            recipe_count: issue.recipe_count,
+        }
     }
+}
 
-    Ok(InvoiceListResponse {
-        invoices,
-        total,
-        filtered: total,
+#[derive(Serialize)]
+struct ConversionCheckResponse {
+    tolerance_pct: f64,
+    items_checked: usize,
+    rows_checked: usize,
+    issues: Vec<ConversionIssueResponse>,
+}
+
+#[tauri::command]
+fn check_conversions(
+    app: tauri::AppHandle,
+    tolerance_pct: Option<f64>,
+) -> Result<ConversionCheckResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let check = convcheck::check_conversions(
+        &conn,
+        tolerance_pct.unwrap_or(convcheck::DEFAULT_TOLERANCE_PCT),
+    )?;
+    Ok(ConversionCheckResponse {
+        tolerance_pct: check.tolerance_pct,
+        items_checked: check.items_checked,
+        rows_checked: check.rows_checked,
+        issues: check.issues.into_iter().map(Into::into).collect(),
     })
 }
 
 #[tauri::command]
-fn get_invoice_detail(
+fn fix_conversion_issue(
     app: tauri::AppHandle,
-    invoice_id: i64,
-) -> Result<InvoiceDetailResponse, String> {
+    row_id: i64,
+    fix: String,
+) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let invoice = conn
-        .query_row(
-            "SELECT i.invoice_id, i.invoice_no, i.vendor_id, v.name, i.invoice_date, i.total, i.freight
-             FROM invoices i
-             LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
-             WHERE i.invoice_id = ?1",
-            [invoice_id],
-            |row| {
-                Ok((
-                    InvoiceListItem {
-                        invoice_id: row.get(0)?,
-                        invoice_no: row.get(1).unwrap_or_else(|_| "-".to_string()),
-                        vendor_id: row.get(2).ok(),
-                        vendor_name: row.get(3).unwrap_or_else(|_| "-".to_string()),
-                        invoice_date: row.get(4).unwrap_or_else(|_| "-".to_string()),
-                        total: row.get(5).ok(),
-                    },
-                    row.get::<_, Option<f64>>(6).ok().flatten(),
-                ))
-            },
-        )
-        .map_err(|e| e.to_string())?;
+    let message = convcheck::fix_conversion(&conn, row_id, &fix)?;
+    Ok(PatchResponse { ok: true, message })
+}
 
-    let mut lines = Vec::new();
-    let mut stmt = conn
-        .prepare(
-            "SELECT t.trans_id, t.item_id, i.name, t.qty, u.sing, t.price, t.ext_cost
-             FROM trans t
-             LEFT JOIN items i ON i.item_id = t.item_id
-             LEFT JOIN units u ON u.unit_id = t.unit_id
-             WHERE t.invoice_id = ?1
-             ORDER BY i.name",
-        )
-        .map_err(|e| e.to_string())?;
-    let iter = stmt
-        .query_map([invoice_id], |row| {
-            Ok(InvoiceLineItem {
-                trans_id: row.get(0).ok(),
-                item_id: row.get(1).ok(),
-                item_name: row.get(2).unwrap_or_else(|_| "-".to_string()),
-                qty: row.get(3).ok(),
-                unit_name: row.get(4).unwrap_or_else(|_| "-".to_string()),
-                price: row.get(5).ok(),
-                ext_cost: row.get(6).ok(),
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    for row in iter {
-        lines.push(row.map_err(|e| e.to_string())?);
+#[derive(Serialize)]
+struct PackIssueResponse {
+    row_id: i64,
+    item_id: i64,
+    item_name: String,
+    vendor_id: i64,
+    vendor_name: String,
+    pack: String,
+    error: String,
+}
+
+impl From<pack::PackIssue> for PackIssueResponse {
+    fn from(issue: pack::PackIssue) -> Self {
+        PackIssueResponse {
+            row_id: issue.row_id,
+            item_id: issue.item_id,
+            item_name: issue.item_name,
+            vendor_id: issue.vendor_id,
+            vendor_name: issue.vendor_name,
+            pack: issue.pack,
+            error: issue.error,
+        }
     }
+}
 
-    Ok(InvoiceDetailResponse {
-        invoice: invoice.0,
-        freight: invoice.1,
-        lines,
-    })
+#[derive(Serialize)]
+struct PackReportResponse {
+    read: i64,
+    unreadable: i64,
+    issues: Vec<PackIssueResponse>,
 }
 
 #[tauri::command]
-fn export_invoice_lines_csv(
-    app: tauri::AppHandle,
-    query: String,
-    vendor_id: Option<i64>,
-    date_from: String,
-    date_to: String,
-    output_path: Option<String>,
-) -> Result<ExportCsvResponse, String> {
+fn get_pack_report(app: tauri::AppHandle) -> Result<PackReportResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let trimmed = query.trim();
-    let like = format!("%{}%", trimmed);
+    let report = pack::pack_report(&conn)?;
+    Ok(PackReportResponse {
+        read: report.read,
+        unreadable: report.unreadable,
+        issues: report.issues.into_iter().map(Into::into).collect(),
+    })
+}
 
-    let mut where_clauses = Vec::new();
-    let mut params: Vec<rusqlite::types::Value> = Vec::new();
+#[tauri::command]
+fn update_pack(app: tauri::AppHandle, row_id: i64, pack: String) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let message = with_tx(&mut conn, |tx| pack::set_pack(tx, row_id, &pack))?;
+    Ok(PatchResponse { ok: true, message })
+}
 
-    if !trimmed.is_empty() {
-        where_clauses.push("(i.invoice_no LIKE ? OR CAST(i.invoice_id AS TEXT) LIKE ?)");
-        params.push(rusqlite::types::Value::from(like.clone()));
-        params.push(rusqlite::types::Value::from(like));
+#[tauri::command]
+fn reread_packs(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let read = with_tx(&mut conn, |tx| pack::refresh_packs(tx, true))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Read {} pack descriptions", read),
+    })
+}
+
+#[derive(Serialize)]
+struct ReviewSuggestionResponse {
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: i64,
+    unit_id2: i64,
+    item_name: String,
+    category: String,
+    recipe_unit: String,
+    purch_unit: String,
+    qty1: f64,
+    qty2: f64,
+    hits: i64,
+    derived_from: String,
+    hops: i64,
+    path: String,
+    confidence: Option<f64>,
+}
+
+impl From<convreview::ReviewRow> for ReviewSuggestionResponse {
+    fn from(row: convreview::ReviewRow) -> Self {
+        ReviewSuggestionResponse {
+            item_id: row.item_id,
+            vendor_id: row.vendor_id,
+            unit_id1: row.unit_id1,
+            unit_id2: row.unit_id2,
+            item_name: row.item_name,
+            category: row.category,
+            recipe_unit: row.recipe_unit,
+            purch_unit: row.purch_unit,
+            qty1: row.qty1,
+            qty2: row.qty2,
+            hits: row.hits,
+            derived_from: row.derived_from,
+            hops: row.hops,
+            path: row.path,
+            confidence: row.confidence,
+        }
     }
-    if let Some(vendor_id) = vendor_id {
-        where_clauses.push("i.vendor_id = ?");
-        params.push(rusqlite::types::Value::from(vendor_id));
+}
+
+#[derive(Serialize)]
+struct ReviewSuggestionsResponse {
+    rows: Vec<ReviewSuggestionResponse>,
+    total: i64,
+}
+
+#[derive(serde::Deserialize)]
+struct SuggestionKeyInput {
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: i64,
+    unit_id2: i64,
+    derived_from: String,
+}
+
+impl From<SuggestionKeyInput> for convreview::SuggestionKey {
+    fn from(key: SuggestionKeyInput) -> Self {
+        convreview::SuggestionKey {
+            item_id: key.item_id,
+            vendor_id: key.vendor_id,
+            unit_id1: key.unit_id1,
+            unit_id2: key.unit_id2,
+            derived_from: key.derived_from,
+        }
     }
-    if !date_from.trim().is_empty() {
-        where_clauses.push("i.invoice_date >= ?");
-        params.push(rusqlite::types::Value::from(date_from.trim().to_string()));
+}
+
+#[derive(Serialize)]
+struct RecipeChangeResponse {
+    recipe_id: i64,
+    recipe_name: String,
+    old_cost: f64,
+    new_cost: f64,
+    old_missing: i64,
+    new_missing: i64,
+    now_complete: bool,
+}
+
+impl From<convreview::RecipeChange> for RecipeChangeResponse {
+    fn from(change: convreview::RecipeChange) -> Self {
+        RecipeChangeResponse {
+            recipe_id: change.recipe_id,
+            recipe_name: change.recipe_name,
+            old_cost: change.old_cost,
+            new_cost: change.new_cost,
+            old_missing: change.old_missing,
+            new_missing: change.new_missing,
+            now_complete: change.now_complete,
+        }
     }
-    if !date_to.trim().is_empty() {
-        where_clauses.push("i.invoice_date <= ?");
-        params.push(rusqlite::types::Value::from(date_to.trim().to_string()));
+}
+
+#[derive(Serialize)]
+struct AcceptanceImpactResponse {
+    accepted: usize,
+    skipped: Vec<String>,
+    recipes: Vec<RecipeChangeResponse>,
+    complete_before: usize,
+    complete_after: usize,
+    newly_complete: usize,
+}
+
+impl From<convreview::AcceptanceImpact> for AcceptanceImpactResponse {
+    fn from(impact: convreview::AcceptanceImpact) -> Self {
+        AcceptanceImpactResponse {
+            accepted: impact.accepted,
+            skipped: impact.skipped,
+            recipes: impact.recipes.into_iter().map(Into::into).collect(),
+            complete_before: impact.complete_before,
+            complete_after: impact.complete_after,
+            newly_complete: impact.newly_complete,
+        }
     }
+}
 
-    let where_sql = if where_clauses.is_empty() {
-        "".to_string()
-    } else {
-        format!("WHERE {}", where_clauses.join(" AND "))
+#[tauri::command]
+fn review_conv_suggestions(
+    app: tauri::AppHandle,
+    min_confidence: Option<f64>,
+    max_hops: Option<i64>,
+    category: Option<String>,
+) -> Result<ReviewSuggestionsResponse, String> {
+    let conn = open_with_fresh_suggestions(&app)?;
+    let filter = convreview::ReviewFilter {
+        min_confidence,
+        max_hops,
+        category,
     };
+    let list = convreview::list_for_review(&conn, &filter)?;
+    Ok(ReviewSuggestionsResponse {
+        rows: list.rows.into_iter().map(Into::into).collect(),
+        total: list.total,
+    })
+}
 
-    let sql = format!(
-        "SELECT i.invoice_id, i.invoice_no, i.invoice_date, i.vendor_id, v.name,
-                t.trans_id, t.item_id, it.name, t.qty, u.sing, t.price, t.ext_cost
-         FROM invoices i
-         LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
-         LEFT JOIN trans t ON t.invoice_id = i.invoice_id
-         LEFT JOIN items it ON it.item_id = t.item_id
-         LEFT JOIN units u ON u.unit_id = t.unit_id
-         {}
-         ORDER BY i.invoice_date DESC, i.invoice_id DESC",
-        where_sql
-    );
+#[tauri::command]
+fn preview_conv_suggestions(
+    app: tauri::AppHandle,
+    suggestions: Vec<SuggestionKeyInput>,
+) -> Result<AcceptanceImpactResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let keys: Vec<convreview::SuggestionKey> = suggestions.into_iter().map(Into::into).collect();
+    Ok(convreview::preview_acceptance(&conn, &keys)?.into())
+}
 
-    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
-    let rows = stmt
-        .query_map(rusqlite::params_from_iter(params), |row| {
-            Ok((
-                row.get::<_, i64>(0)?,
-                row.get::<_, String>(1).unwrap_or_else(|_| "-".to_string()),
-                row.get::<_, String>(2).unwrap_or_else(|_| "-".to_string()),
-                row.get::<_, Option<i64>>(3).unwrap_or(None),
-                row.get::<_, String>(4).unwrap_or_else(|_| "-".to_string()),
-                row.get::<_, Option<i64>>(5).unwrap_or(None),
-                row.get::<_, Option<i64>>(6).unwrap_or(None),
-                row.get::<_, String>(7).unwrap_or_else(|_| "-".to_string()),
-                row.get::<_, Option<f64>>(8).unwrap_or(None),
-                row.get::<_, String>(9).unwrap_or_else(|_| "-".to_string()),
-                row.get::<_, Option<f64>>(10).unwrap_or(None),
-                row.get::<_, Option<f64>>(11).unwrap_or(None),
-            ))
-        })
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn accept_conv_suggestions(
+    app: tauri::AppHandle,
+    suggestions: Vec<SuggestionKeyInput>,
+) -> Result<AcceptanceImpactResponse, String> {
+    let keys: Vec<convreview::SuggestionKey> = suggestions.into_iter().map(Into::into).collect();
+    let mut conn = open_initialized_db(&app)?;
+    let impact = with_tx(&mut conn, |tx| convreview::accept_suggestions(tx, &keys))?;
+    Ok(impact.into())
+}
 
-    let path = if let Some(path) = output_path {
-        path
-    } else {
-        let ts = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| e.to_string())?
-            .as_secs();
+#[tauri::command]
+fn reject_conv_suggestions(
+    app: tauri::AppHandle,
+    suggestions: Vec<SuggestionKeyInput>,
+) -> Result<PatchResponse, String> {
+    let keys: Vec<convreview::SuggestionKey> = suggestions.into_iter().map(Into::into).collect();
+    let mut conn = open_initialized_db(&app)?;
+    let rejected = with_tx(&mut conn, |tx| convreview::reject_suggestions(tx, &keys))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Rejected {} suggestions; they won't be suggested again",
+            rejected
+        ),
+    })
+}
 
-        let data_root = db_path(&app)
-            .ok()
-            .and_then(|db| db.parent().map(|p| p.to_path_buf()))
-            .unwrap_or_else(fallback_data_root);
-        let reports_dir = data_root.join("reports");
-        std::fs::create_dir_all(&reports_dir).map_err(|e| e.to_string())?;
-        reports_dir
-            .join(format!("invoice_lines_{}.csv", ts))
-            .to_string_lossy()
-            .to_string()
-    };
+#[tauri::command]
+fn set_reference_densities(app: tauri::AppHandle, enabled: bool) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    density::set_use_reference_densities(&conn, enabled)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: if enabled {
+            "Items without a density use the reference table".to_string()
+        } else {
+            "Only densities entered on items are used".to_string()
+        },
+    })
+}
 
-    if let Some(parent) = std::path::Path::new(&path).parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
+#[tauri::command]
+fn update_item_density(
+    app: tauri::AppHandle,
+    item_id: i64,
+    density: Option<f64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    density::set_item_density(&conn, item_id, density)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: match density {
+            Some(d) => format!("Density set to {} g/ml", d),
+            None => "Cleared item density".to_string(),
+        },
+    })
+}
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&path)
-        .map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct NutrientInfoResponse {
+    key: String,
+    label: String,
+    unit: String,
+}
 
-    writeln!(
-        file,
-        "InvoiceID,InvoiceNo,InvoiceDate,VendorID,VendorName,TransID,ItemID,ItemName,Qty,Unit,Price,ExtCost"
-    )
-    .map_err(|e| e.to_string())?;
+fn nutrient_info() -> Vec<NutrientInfoResponse> {
+    nutrition::NUTRIENTS
+        .iter()
+        .map(|n| NutrientInfoResponse {
+            key: n.key.to_string(),
+            label: n.label.to_string(),
+            unit: n.unit.to_string(),
+        })
+        .collect()
+}
 
-    let mut count = 0usize;
-    for row in rows {
-        let (
-            invoice_id,
-            invoice_no,
-            invoice_date,
-            vendor_id,
-            vendor_name,
-            trans_id,
-            item_id,
-            item_name,
-            qty,
-            unit_name,
-            price,
-            ext_cost,
-        ) = row.map_err(|e| e.to_string())?;
-        writeln!(
-            file,
-            "{},{},{},{},{},{},{},{},{},{},{},{}",
-            invoice_id,
-            invoice_no,
-            invoice_date,
-            vendor_id
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            vendor_name,
-            trans_id
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            item_id
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            item_name,
-            qty.map(|v| v.to_string()).unwrap_or_else(|| "".to_string()),
-            unit_name,
-            price
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-            ext_cost
-                .map(|v| v.to_string())
-                .unwrap_or_else(|| "".to_string()),
-        )
-        .map_err(|e| e.to_string())?;
-        count += 1;
+#[derive(Serialize)]
+struct FoodMatchResponse {
+    fdc_id: i64,
+    description: String,
+    data_type: String,
+    score: f64,
+}
+
+impl From<nutrition::FoodMatch> for FoodMatchResponse {
+    fn from(food: nutrition::FoodMatch) -> Self {
+        FoodMatchResponse {
+            fdc_id: food.fdc_id,
+            description: food.description,
+            data_type: food.data_type,
+            score: food.score,
+        }
     }
+}
 
-    Ok(ExportCsvResponse { path, rows: count })
+#[derive(Serialize)]
+struct ItemNutritionProfile {
+    basis_qty: f64,
+    basis_unit_id: i64,
+    basis_unit_name: String,
+    values: Vec<Option<f64>>,
+    source: String,
+    source_label: String,
+    fdc_id: Option<i64>,
+    updated_at: String,
+}
+
+#[derive(Serialize)]
+struct ItemNutritionResponse {
+    item_id: i64,
+    nutrients: Vec<NutrientInfoResponse>,
+    profile: Option<ItemNutritionProfile>,
+    foods_imported: i64,
+    matches: Vec<FoodMatchResponse>,
+    label_name: Option<String>,
 }
 
 #[tauri::command]
-fn patch_convunit(
+fn get_item_nutrition(
     app: tauri::AppHandle,
-    base_path: String,
     item_id: i64,
-    vendor_id: i64,
-    unit_id1: i64,
-    unit_id2: i64,
-    qty1: f64,
-    qty2: f64,
+    query: Option<String>,
+) -> Result<ItemNutritionResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let profile = nutrition::item_nutrition(&conn, item_id)?.map(|p| ItemNutritionProfile {
+        source_label: p.source_label(),
+        basis_qty: p.basis_qty,
+        basis_unit_id: p.basis_unit_id,
+        basis_unit_name: p.basis_unit_name,
+        values: p.values,
+        source: p.source,
+        fdc_id: p.fdc_id,
+        updated_at: p.updated_at,
+    });
+    let foods_imported: i64 = conn
+        .query_row("SELECT COUNT(*) FROM fdc_foods", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let query = match query
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+    {
+        Some(q) => q,
+        None => conn
+            .query_row(
+                "SELECT COALESCE(name, '') FROM items WHERE item_id = ?1",
+                [item_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default(),
+    };
+    let matches = if foods_imported > 0 {
+        nutrition::search_foods(&conn, &query, 8)?
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(ItemNutritionResponse {
+        item_id,
+        nutrients: nutrient_info(),
+        profile,
+        foods_imported,
+        matches,
+        label_name: label::label_name(&conn, item_id)?,
+    })
+}
+
+#[tauri::command]
+fn save_item_nutrition(
+    app: tauri::AppHandle,
+    item_id: i64,
+    basis_qty: f64,
+    basis_unit_id: i64,
+    values: Vec<Option<f64>>,
 ) -> Result<PatchResponse, String> {
-    let base = resolve_base_path(&base_path)?;
-    if !base.exists() {
-        return Err(format!("Base path does not exist: {}", base.display()));
-    }
+    let conn = open_initialized_db(&app)?;
+    nutrition::set_item_nutrition(&conn, item_id, basis_qty, basis_unit_id, &values)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Saved nutrition".to_string(),
+    })
+}
 
-    let patch_path = base.join("ConvUnit_patch.csv");
-    let new_file = !patch_path.exists();
-    if let Some(parent) = patch_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&patch_path)
-        .map_err(|e| e.to_string())?;
-    if new_file {
-        writeln!(
-            file,
-            "ItemID,VendorID,UnitID1,UnitID2,Quantity1,Quantity2,Status,IsCalculated"
-        )
-        .map_err(|e| e.to_string())?;
-    }
-    writeln!(
-        file,
-        "{},{},{},{},{},{},,1",
-        item_id, vendor_id, unit_id1, unit_id2, qty1, qty2
-    )
-    .map_err(|e| e.to_string())?;
+#[tauri::command]
+fn clear_item_nutrition(app: tauri::AppHandle, item_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let cleared = nutrition::clear_item_nutrition(&conn, item_id)?;
+    Ok(PatchResponse {
+        ok: cleared,
+        message: if cleared {
+            "Cleared nutrition".to_string()
+        } else {
+            "Item had no nutrition".to_string()
+        },
+    })
+}
 
+#[tauri::command]
+fn save_item_label_name(
+    app: tauri::AppHandle,
+    item_id: i64,
+    label_name: String,
+) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
-    conn.execute(
-        "INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, is_calculated)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, None::<i64>, Some(1i64)),
-    )
-    .map_err(|e| e.to_string())?;
+    label::set_label_name(&conn, item_id, &label_name)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: if label_name.trim().is_empty() {
+            "Ingredient statements will use the item name".to_string()
+        } else {
+            "Saved label name".to_string()
+        },
+    })
+}
 
+#[tauri::command]
+fn link_item_food(
+    app: tauri::AppHandle,
+    item_id: i64,
+    fdc_id: i64,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let description = nutrition::link_item(&conn, item_id, fdc_id)?;
     Ok(PatchResponse {
         ok: true,
-        message: format!("Patched {}", patch_path.display()),
+        message: format!("Linked to {}", description),
     })
 }
 
+#[tauri::command]
+fn import_fdc_foods(app: tauri::AppHandle, path: String) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let imported = with_tx(&mut conn, |tx| nutrition::import_fdc(tx, &path))?;
+    let mut message = format!("Imported {} foods", imported.foods);
+    if imported.skipped > 0 {
+        message.push_str(&format!(
+            ", skipped {} without nutrient data",
+            imported.skipped
+        ));
+    }
+    if imported.refreshed > 0 {
+        message.push_str(&format!(", updated {} linked items", imported.refreshed));
+    }
+    Ok(PatchResponse { ok: true, message })
+}
+
 #[derive(Serialize)]
-struct BrowseTableResponse {
-    columns: Vec<String>,
-    rows: Vec<Vec<String>>,
-    total: i64,
+struct NutritionMatchResponse {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    best: Option<FoodMatchResponse>,
 }
 
-const ALLOWED_TABLES: &[&str] = &[
-    "units",
-    "items",
-    "vendors",
-    "recipes",
-    "recp_items",
-    "convunit",
-    "inv_units",
-    "inv_prices",
-    "conv_suggestions",
-    "conv_suggestions_safe",
-    "conv_todo",
-    "missing_edges",
-    "missing_purch_unit",
-    "missing_data_report",
-    "invoices",
-    "trans",
-    "recp_inv",
-    "bids",
-];
+#[derive(Serialize)]
+struct NutritionAutoLinkResponse {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    fdc_id: i64,
+    description: String,
+}
 
-#[tauri::command]
-fn browse_table(
-    app: tauri::AppHandle,
-    table_name: String,
-    limit: u32,
-    offset: u32,
-) -> Result<BrowseTableResponse, String> {
-    if !ALLOWED_TABLES.contains(&table_name.as_str()) {
-        return Err(format!("Table '{}' is not browsable", table_name));
-    }
+#[derive(Serialize)]
+struct NutritionCoverageResponse {
+    foods: i64,
+    recipe_items: i64,
+    with_nutrition: i64,
+    unmatched: Vec<NutritionMatchResponse>,
+    auto_linked: Vec<NutritionAutoLinkResponse>,
+}
 
+#[tauri::command]
+fn get_nutrition_coverage(app: tauri::AppHandle) -> Result<NutritionCoverageResponse, String> {
     let conn = open_initialized_db(&app)?;
+    let coverage = nutrition::coverage(&conn)?;
+    Ok(NutritionCoverageResponse {
+        foods: coverage.foods,
+        recipe_items: coverage.recipe_items,
+        with_nutrition: coverage.with_nutrition,
+        unmatched: coverage
+            .unmatched
+            .into_iter()
+            .map(|m| NutritionMatchResponse {
+                item_id: m.item_id,
+                item_name: m.item_name,
+                category: m.category,
+                best: m.best.map(Into::into),
+            })
+            .collect(),
+        auto_linked: coverage
+            .auto_linked
+            .into_iter()
+            .map(|a| NutritionAutoLinkResponse {
+                item_id: a.item_id,
+                item_name: a.item_name,
+                category: a.category,
+                fdc_id: a.fdc_id,
+                description: a.description,
+            })
+            .collect(),
+    })
+}
 
-    let total: i64 = conn
-        .query_row(&format!("SELECT COUNT(*) FROM {}", table_name), [], |r| {
-            r.get(0)
-        })
-        .map_err(|e| e.to_string())?;
-
-    let query = format!(
-        "SELECT * FROM {} LIMIT {} OFFSET {}",
-        table_name, limit, offset
-    );
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
-
-    let columns: Vec<String> = stmt
-        .column_names()
-        .iter()
-        .map(|c| c.to_string())
-        .collect();
-
-    let col_count = columns.len();
-    let rows: Vec<Vec<String>> = stmt
-        .query_map([], |row| {
-            let mut vals = Vec::with_capacity(col_count);
-            for i in 0..col_count {
-                let val: String = match row.get_ref(i) {
-                    Ok(rusqlite::types::ValueRef::Null) => String::new(),
-                    Ok(rusqlite::types::ValueRef::Integer(n)) => n.to_string(),
-                    Ok(rusqlite::types::ValueRef::Real(f)) => format!("{:.4}", f),
-                    Ok(rusqlite::types::ValueRef::Text(t)) => {
-                        String::from_utf8_lossy(t).to_string()
-                    }
-                    Ok(rusqlite::types::ValueRef::Blob(b)) => format!("[blob {} bytes]", b.len()),
-                    Err(_) => String::new(),
-                };
-                vals.push(val);
-            }
-            Ok(vals)
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    Ok(BrowseTableResponse {
-        columns,
-        rows,
-        total,
+#[tauri::command]
+fn link_nutrition_matches(app: tauri::AppHandle, min_score: f64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let linked = with_tx(&mut conn, |tx| nutrition::link_best_matches(tx, min_score))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Linked {} items at {:.0}% or better; review them below",
+            linked,
+            min_score * 100.0
+        ),
     })
 }
 
-// ──────────────────────────────────────────
-// Export helpers: PDF + DOCX for inventory & recipes
-// ──────────────────────────────────────────
+#[tauri::command]
+fn confirm_nutrition_link(app: tauri::AppHandle, item_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let confirmed = nutrition::confirm_auto_link(&conn, item_id)?;
+    Ok(PatchResponse {
+        ok: confirmed,
+        message: if confirmed {
+            "Kept the USDA link".to_string()
+        } else {
+            "Item has no automatic link".to_string()
+        },
+    })
+}
 
-use printpdf::*;
-use std::io::BufWriter;
+#[derive(Serialize)]
+struct NutritionLineResponse {
+    recp_item_id: i64,
+    item_id: i64,
+    item_name: String,
+    qty: Option<f64>,
+    unit_name: String,
+    sub_recipe_id: Option<i64>,
+    grams: Option<f64>,
+    values: Vec<Option<f64>>,
+    status: String,
+    source: String,
+    complete: bool,
+}
+
+impl From<nutrition::NutritionLine> for NutritionLineResponse {
+    fn from(line: nutrition::NutritionLine) -> Self {
+        NutritionLineResponse {
+            recp_item_id: line.recp_item_id,
+            item_id: line.item_id,
+            item_name: line.item_name,
+            qty: line.qty,
+            unit_name: line.unit_name,
+            sub_recipe_id: line.sub_recipe_id,
+            grams: line.grams,
+            values: line.values,
+            status: line.status,
+            source: line.source,
+            complete: line.complete,
+        }
+    }
+}
 
-/// Embedded 4chef logo PNG for export headers
-const LOGO_PNG: &[u8] = include_bytes!("../assets/4chef-logo.png");
+#[derive(Serialize)]
+struct RecipeNutritionResponse {
+    recipe_id: i64,
+    nutrients: Vec<NutrientInfoResponse>,
+    servings: Option<f64>,
+    lines: Vec<NutritionLineResponse>,
+    totals: Vec<f64>,
+    per_serving: Option<Vec<f64>>,
+    total_grams: Option<f64>,
+    missing_lines: i64,
+    gaps: Vec<i64>,
+}
 
-/// Adds the 4chef logo to a PDF layer at the given (x, y) position.
-/// The logo renders at approximately 20 mm wide.
-fn add_logo_to_pdf_layer(layer: &PdfLayerReference) {
-    let margin_left = 15.0f32;
-    if let Ok(img) = printpdf::image_crate::load_from_memory(LOGO_PNG) {
-        let width_px = img.width() as f32;
-        let target_mm = 20.0f32;
-        let dpi = width_px / (target_mm / 25.4);
-        let pdf_img = Image::from_dynamic_image(&img);
-        pdf_img.add_to_layer(layer.clone(), ImageTransform {
-            translate_x: Some(Mm(margin_left)),
-            translate_y: Some(Mm(274.0)),
-            dpi: Some(dpi),
-            ..Default::default()
-        });
-    }
+#[tauri::command]
+fn get_recipe_nutrition(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+) -> Result<RecipeNutritionResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let rolled = nutrition::recipe_nutrition(&conn, recipe_id)?;
+    Ok(RecipeNutritionResponse {
+        recipe_id,
+        nutrients: nutrient_info(),
+        servings: rolled.servings,
+        lines: rolled.lines.into_iter().map(Into::into).collect(),
+        totals: rolled.totals,
+        per_serving: rolled.per_serving,
+        total_grams: rolled.total_grams,
+        missing_lines: rolled.missing_lines,
+        gaps: rolled.gaps,
+    })
 }
 
-/// Wraps long text into lines of at most `max_chars` characters, breaking on
-/// whitespace boundaries.
-fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
-    let mut lines = Vec::new();
-    for raw_line in text.lines() {
-        if raw_line.len() <= max_chars {
-            lines.push(raw_line.to_string());
-            continue;
-        }
-        let mut current = String::new();
-        for word in raw_line.split_whitespace() {
-            if current.is_empty() {
-                current = word.to_string();
-            } else if current.len() + 1 + word.len() <= max_chars {
-                current.push(' ');
-                current.push_str(word);
-            } else {
-                lines.push(current);
-                current = word.to_string();
-            }
-        }
-        if !current.is_empty() {
-            lines.push(current);
+#[derive(Serialize)]
+struct LabelRowResponse {
+    name: String,
+    amount: String,
+    daily_value: Option<String>,
+    indent: u8,
+}
+
+impl From<label::LabelRow> for LabelRowResponse {
+    fn from(row: label::LabelRow) -> Self {
+        LabelRowResponse {
+            name: row.name,
+            amount: row.amount,
+            daily_value: row.daily_value,
+            indent: row.indent,
         }
     }
-    if lines.is_empty() {
-        lines.push(String::new());
-    }
-    lines
 }
 
-fn fmt_money(v: f64) -> String {
-    format!("${:.2}", v)
+#[derive(Serialize)]
+struct NutritionLabelResponse {
+    recipe_id: i64,
+    servings_per_container: String,
+    serving_size: String,
+    calories: String,
+    nutrients: Vec<LabelRowResponse>,
+    vitamins: Vec<LabelRowResponse>,
+    ingredients: String,
+    allergens: Vec<String>,
+    warnings: Vec<String>,
 }
 
 #[tauri::command]
-fn export_inventory_pdf(
+fn get_nutrition_label(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    servings_per_container: f64,
+) -> Result<NutritionLabelResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let panel = label::nutrition_label(&conn, recipe_id, servings_per_container)?;
+    Ok(NutritionLabelResponse {
+        recipe_id,
+        servings_per_container: panel.servings_per_container,
+        serving_size: panel.serving_size,
+        calories: panel.calories,
+        nutrients: panel.nutrients.into_iter().map(Into::into).collect(),
+        vitamins: panel.vitamins.into_iter().map(Into::into).collect(),
+        ingredients: panel.ingredients,
+        allergens: panel.allergens,
+        warnings: panel.warnings,
+    })
+}
+
+#[tauri::command]
+fn export_label_pdf(
     app: tauri::AppHandle,
+    recipe_id: i64,
+    servings_per_container: f64,
     output_path: String,
-    group_by: Option<String>,
 ) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let group_by = group_by.unwrap_or_default();
-
-    let use_vendor_join = group_by == "vendor";
-    let order_clause = match group_by.as_str() {
-        "food_category" => "COALESCE(i.food_category, '') COLLATE NOCASE, i.name COLLATE NOCASE",
-        "storage" => "COALESCE(i.storage_type, '') COLLATE NOCASE, i.name COLLATE NOCASE",
-        "vendor" => "COALESCE(v.name, 'zzz') COLLATE NOCASE, i.name COLLATE NOCASE",
-        _ => "i.name COLLATE NOCASE",
-    };
-
-    let from_sql = if use_vendor_join {
-        "items i LEFT JOIN inv_prices ip ON i.item_id = ip.item_id AND ip.status = 1 LEFT JOIN vendors v ON ip.vendor_id = v.vendor_id"
-    } else {
-        "items i"
-    };
+    let panel = label::nutrition_label(&conn, recipe_id, servings_per_container)?;
+    label::write_label_pdf(&panel, &output_path)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported label for '{}' to {}",
+            panel.recipe_name, output_path
+        ),
+    })
+}
 
-    let select_cols = if use_vendor_join {
-        "DISTINCT i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), COALESCE(v.name, '')"
-    } else {
-        "i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), ''"
-    };
+#[tauri::command]
+fn export_label_png(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    servings_per_container: f64,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let panel = label::nutrition_label(&conn, recipe_id, servings_per_container)?;
+    label::write_label_png(&panel, &output_path)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported label for '{}' to {}",
+            panel.recipe_name, output_path
+        ),
+    })
+}
 
+#[tauri::command]
+fn list_missing_edges(
+    app: tauri::AppHandle,
+    limit: u32,
+    offset: u32,
+) -> Result<MissingEdgeResponse, String> {
+    let conn = open_with_fresh_suggestions(&app)?;
+    let total: i64 = conn
+        .query_row("SELECT COUNT(*) FROM missing_edges", [], |r| r.get(0))
+        .unwrap_or(0);
+    let mut rows = Vec::new();
     let mut stmt = conn
-        .prepare(&format!(
-            "SELECT {} FROM {} ORDER BY {}",
-            select_cols, from_sql, order_clause
-        ))
+        .prepare(
+            "SELECT item_id, item_name, vendor_id, recipe_unit_id, recipe_unit, purch_unit_id, purch_unit, hits
+             FROM missing_edges
+             ORDER BY hits DESC
+             LIMIT ?1 OFFSET ?2",
+        )
         .map_err(|e| e.to_string())?;
-    let items: Vec<(i64, String, i64, String, String, String)> = stmt
-        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3).unwrap_or_default(), row.get(4).unwrap_or_default(), row.get(5).unwrap_or_default())))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    let (doc, page1, layer1) = PdfDocument::new("4chef Inventory", Mm(210.0), Mm(297.0), "Layer 1");
-    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
-    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
-
-    let mut current_layer = doc.get_page(page1).get_layer(layer1);
-    let line_height = 5.0f32;
-    let margin_left = 15.0f32;
-
-    // Logo
+    let iter = stmt
+        .query_map((limit, offset), |row| {
+            Ok(MissingEdgeRow {
+                item_id: row.get(0)?,
+                item_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                vendor_id: row.get(2).unwrap_or(0),
+                recipe_unit_id: row.get(3)?,
+                recipe_unit: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                purch_unit_id: row.get(5)?,
+                purch_unit: row.get(6).unwrap_or_else(|_| "-".to_string()),
+                hits: row.get(7).ok(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in iter {
+        rows.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(MissingEdgeResponse { rows, total })
+}
+
+#[tauri::command]
+fn list_missing_purch_unit(
+    app: tauri::AppHandle,
+    limit: u32,
+    offset: u32,
+) -> Result<MissingPurchResponse, String> {
+    let conn = open_with_fresh_suggestions(&app)?;
+    let total: i64 = conn
+        .query_row("SELECT COUNT(*) FROM missing_purch_unit", [], |r| r.get(0))
+        .unwrap_or(0);
+    let mut rows = Vec::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT item_id, item_name, usage_count
+             FROM missing_purch_unit
+             ORDER BY usage_count DESC
+             LIMIT ?1 OFFSET ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let iter = stmt
+        .query_map((limit, offset), |row| {
+            Ok(MissingPurchRow {
+                item_id: row.get(0)?,
+                item_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                usage_count: row.get(2).ok(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in iter {
+        rows.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(MissingPurchResponse { rows, total })
+}
+
+#[tauri::command]
+fn list_missing_data_report(
+    app: tauri::AppHandle,
+    limit: u32,
+    offset: u32,
+) -> Result<MissingDataResponse, String> {
+    let conn = open_with_fresh_suggestions(&app)?;
+    let total: i64 = conn
+        .query_row("SELECT COUNT(*) FROM missing_data_report", [], |r| r.get(0))
+        .unwrap_or(0);
+    let mut rows = Vec::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT recipe_id, recipe_name, missing_a, missing_b, missing_c
+             FROM missing_data_report
+             ORDER BY missing_a DESC
+             LIMIT ?1 OFFSET ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let iter = stmt
+        .query_map((limit, offset), |row| {
+            Ok(MissingDataRow {
+                recipe_id: row.get(0)?,
+                recipe_name: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                missing_a: row.get(2).ok(),
+                missing_b: row.get(3).ok(),
+                missing_c: row.get(4).ok(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in iter {
+        rows.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(MissingDataResponse { rows, total })
+}
+
+#[tauri::command]
+fn list_invoices(
+    app: tauri::AppHandle,
+    query: String,
+    vendor_id: Option<i64>,
+    date_from: String,
+    date_to: String,
+    limit: u32,
+    offset: u32,
+) -> Result<InvoiceListResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let trimmed = query.trim();
+    let like = format!("%{}%", trimmed);
+
+    let mut where_clauses = Vec::new();
+    let mut params: Vec<rusqlite::types::Value> = Vec::new();
+    if !trimmed.is_empty() {
+        where_clauses.push("i.invoice_no LIKE ?1 OR CAST(i.invoice_id AS TEXT) LIKE ?1 OR CAST(i.vendor_id AS TEXT) LIKE ?1");
+        params.push(rusqlite::types::Value::from(like.clone()));
+    }
+    if let Some(vendor_id) = vendor_id {
+        where_clauses.push("i.vendor_id = ?");
+        params.push(rusqlite::types::Value::from(vendor_id));
+    }
+    if !date_from.trim().is_empty() {
+        where_clauses.push("i.invoice_date >= ?");
+        params.push(rusqlite::types::Value::from(date_from.trim().to_string()));
+    }
+    if !date_to.trim().is_empty() {
+        where_clauses.push("i.invoice_date <= ?");
+        params.push(rusqlite::types::Value::from(date_to.trim().to_string()));
+    }
+
+    let where_sql = if where_clauses.is_empty() {
+        "".to_string()
+    } else {
+        format!("WHERE {}", where_clauses.join(" AND "))
+    };
+
+    let total_sql = format!("SELECT COUNT(*) FROM invoices i {}", where_sql);
+    let total: i64 = conn
+        .query_row(
+            &total_sql,
+            rusqlite::params_from_iter(params.clone()),
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut invoices = Vec::new();
+    let mut params_for_query = params.clone();
+    params_for_query.push(rusqlite::types::Value::from(limit as i64));
+    params_for_query.push(rusqlite::types::Value::from(offset as i64));
+    let list_sql = format!(
+        "SELECT i.invoice_id, i.invoice_no, i.vendor_id, v.name, i.invoice_date, i.total
+         FROM invoices i
+         LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+         {}
+         ORDER BY i.invoice_date DESC
+         LIMIT ? OFFSET ?",
+        where_sql
+    );
+    let mut stmt = conn.prepare(&list_sql).map_err(|e| e.to_string())?;
+    let iter = stmt
+        .query_map(rusqlite::params_from_iter(params_for_query), |row| {
+            Ok(InvoiceListItem {
+                invoice_id: row.get(0)?,
+                invoice_no: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                vendor_id: row.get(2).ok(),
+                vendor_name: row.get(3).unwrap_or_else(|_| "-".to_string()),
+                invoice_date: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                total: row.get(5).ok(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in iter {
+        invoices.push(row.map_err(|e| e.to_string())?);
+    }
+
+    Ok(InvoiceListResponse {
+        invoices,
+        total,
+        filtered: total,
+    })
+}
+
+#[tauri::command]
+fn get_invoice_detail(
+    app: tauri::AppHandle,
+    invoice_id: i64,
+) -> Result<InvoiceDetailResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let invoice = conn
+        .query_row(
+            "SELECT i.invoice_id, i.invoice_no, i.vendor_id, v.name, i.invoice_date, i.total, i.freight
+             FROM invoices i
+             LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+             WHERE i.invoice_id = ?1",
+            [invoice_id],
+            |row| {
+                Ok((
+                    InvoiceListItem {
+                        invoice_id: row.get(0)?,
+                        invoice_no: row.get(1).unwrap_or_else(|_| "-".to_string()),
+                        vendor_id: row.get(2).ok(),
+                        vendor_name: row.get(3).unwrap_or_else(|_| "-".to_string()),
+                        invoice_date: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                        total: row.get(5).ok(),
+                    },
+                    row.get::<_, Option<f64>>(6).ok().flatten(),
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut lines = Vec::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.trans_id, t.item_id, i.name, t.qty, u.sing, t.price, t.ext_cost, t.alloc_amount, t.landed_price
+             FROM trans t
+             LEFT JOIN items i ON i.item_id = t.item_id
+             LEFT JOIN units u ON u.unit_id = t.unit_id
+             WHERE t.invoice_id = ?1
+             ORDER BY i.name",
+        )
+        .map_err(|e| e.to_string())?;
+    let iter = stmt
+        .query_map([invoice_id], |row| {
+            Ok(InvoiceLineItem {
+                trans_id: row.get(0).ok(),
+                item_id: row.get(1).ok(),
+                item_name: row.get(2).unwrap_or_else(|_| "-".to_string()),
+                qty: row.get(3).ok(),
+                unit_name: row.get(4).unwrap_or_else(|_| "-".to_string()),
+                price: row.get(5).ok(),
+                ext_cost: row.get(6).ok(),
+                alloc_amount: row.get(7).ok().flatten(),
+                landed_price: row.get(8).ok().flatten(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in iter {
+        lines.push(row.map_err(|e| e.to_string())?);
+    }
+    let charges = landed::list_charges(&conn, invoice_id)?
+        .into_iter()
+        .map(|c| InvoiceChargeResponse {
+            charge_id: c.charge_id,
+            kind: c.kind,
+            description: c.description,
+            amount: c.amount,
+            allocation: c.allocation,
+        })
+        .collect();
+
+    let credits = reconcile::list_credit_memos(&conn, invoice_id)?
+        .into_iter()
+        .map(CreditMemoResponse::from)
+        .collect();
+    let recon_status: Option<String> = conn
+        .query_row(
+            "SELECT status FROM invoice_reconciliation WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(InvoiceDetailResponse {
+        invoice: invoice.0,
+        freight: invoice.1,
+        lines,
+        charges,
+        credits,
+        recon_status,
+    })
+}
+
+#[tauri::command]
+fn export_invoice_lines_csv(
+    app: tauri::AppHandle,
+    query: String,
+    vendor_id: Option<i64>,
+    date_from: String,
+    date_to: String,
+    output_path: Option<String>,
+) -> Result<ExportCsvResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let trimmed = query.trim();
+    let like = format!("%{}%", trimmed);
+
+    let mut where_clauses = Vec::new();
+    let mut params: Vec<rusqlite::types::Value> = Vec::new();
+
+    if !trimmed.is_empty() {
+        where_clauses.push("(i.invoice_no LIKE ? OR CAST(i.invoice_id AS TEXT) LIKE ?)");
+        params.push(rusqlite::types::Value::from(like.clone()));
+        params.push(rusqlite::types::Value::from(like));
+    }
+    if let Some(vendor_id) = vendor_id {
+        where_clauses.push("i.vendor_id = ?");
+        params.push(rusqlite::types::Value::from(vendor_id));
+    }
+    if !date_from.trim().is_empty() {
+        where_clauses.push("i.invoice_date >= ?");
+        params.push(rusqlite::types::Value::from(date_from.trim().to_string()));
+    }
+    if !date_to.trim().is_empty() {
+        where_clauses.push("i.invoice_date <= ?");
+        params.push(rusqlite::types::Value::from(date_to.trim().to_string()));
+    }
+
+    let where_sql = if where_clauses.is_empty() {
+        "".to_string()
+    } else {
+        format!("WHERE {}", where_clauses.join(" AND "))
+    };
+
+    let sql = format!(
+        "SELECT i.invoice_id, i.invoice_no, i.invoice_date, i.vendor_id, v.name,
+                t.trans_id, t.item_id, it.name, t.qty, u.sing, t.price, t.ext_cost
+         FROM invoices i
+         LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+         LEFT JOIN trans t ON t.invoice_id = i.invoice_id
+         LEFT JOIN items it ON it.item_id = t.item_id
+         LEFT JOIN units u ON u.unit_id = t.unit_id
+         {}
+         ORDER BY i.invoice_date DESC, i.invoice_id DESC",
+        where_sql
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1).unwrap_or_else(|_| "-".to_string()),
+                row.get::<_, String>(2).unwrap_or_else(|_| "-".to_string()),
+                row.get::<_, Option<i64>>(3).unwrap_or(None),
+                row.get::<_, String>(4).unwrap_or_else(|_| "-".to_string()),
+                row.get::<_, Option<i64>>(5).unwrap_or(None),
+                row.get::<_, Option<i64>>(6).unwrap_or(None),
+                row.get::<_, String>(7).unwrap_or_else(|_| "-".to_string()),
+                row.get::<_, Option<f64>>(8).unwrap_or(None),
+                row.get::<_, String>(9).unwrap_or_else(|_| "-".to_string()),
+                row.get::<_, Option<f64>>(10).unwrap_or(None),
+                row.get::<_, Option<f64>>(11).unwrap_or(None),
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let path = if let Some(path) = output_path {
+        path
+    } else {
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_secs();
+
+        let data_root = db_path(&app)
+            .ok()
+            .and_then(|db| db.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(fallback_data_root);
+        let reports_dir = data_root.join("reports");
+        std::fs::create_dir_all(&reports_dir).map_err(|e| e.to_string())?;
+        reports_dir
+            .join(format!("invoice_lines_{}.csv", ts))
+            .to_string_lossy()
+            .to_string()
+    };
+
+    if let Some(parent) = std::path::Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+
+    writeln!(
+        file,
+        "InvoiceID,InvoiceNo,InvoiceDate,VendorID,VendorName,TransID,ItemID,ItemName,Qty,Unit,Price,ExtCost"
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut count = 0usize;
+    for row in rows {
+        let (
+            invoice_id,
+            invoice_no,
+            invoice_date,
+            vendor_id,
+            vendor_name,
+            trans_id,
+            item_id,
+            item_name,
+            qty,
+            unit_name,
+            price,
+            ext_cost,
+        ) = row.map_err(|e| e.to_string())?;
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            invoice_id,
+            invoice_no,
+            invoice_date,
+            vendor_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            vendor_name,
+            trans_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            item_id
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            item_name,
+            qty.map(|v| v.to_string()).unwrap_or_else(|| "".to_string()),
+            unit_name,
+            price
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+            ext_cost
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "".to_string()),
+        )
+        .map_err(|e| e.to_string())?;
+        count += 1;
+    }
+
+    Ok(ExportCsvResponse { path, rows: count })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn patch_convunit(
+    app: tauri::AppHandle,
+    base_path: String,
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: i64,
+    unit_id2: i64,
+    qty1: f64,
+    qty2: f64,
+) -> Result<PatchResponse, String> {
+    let base = resolve_base_path(&base_path)?;
+    if !base.exists() {
+        return Err(format!("Base path does not exist: {}", base.display()));
+    }
+
+    let patch_path = base.join("ConvUnit_patch.csv");
+    let new_file = !patch_path.exists();
+    if let Some(parent) = patch_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&patch_path)
+        .map_err(|e| e.to_string())?;
+    if new_file {
+        writeln!(
+            file,
+            "ItemID,VendorID,UnitID1,UnitID2,Quantity1,Quantity2,Status,IsCalculated"
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    writeln!(
+        file,
+        "{},{},{},{},{},{},,1",
+        item_id, vendor_id, unit_id1, unit_id2, qty1, qty2
+    )
+    .map_err(|e| e.to_string())?;
+
+    let conn = open_initialized_db(&app)?;
+    conn.execute(
+        "INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status, is_calculated)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, None::<i64>, Some(1i64)),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Patched {}", patch_path.display()),
+    })
+}
+
+#[derive(Serialize)]
+struct BrowseTableResponse {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    total: i64,
+}
+
+const ALLOWED_TABLES: &[&str] = &[
+    "units",
+    "items",
+    "vendors",
+    "recipes",
+    "recp_items",
+    "convunit",
+    "inv_units",
+    "inv_prices",
+    "conv_suggestions",
+    "conv_suggestions_safe",
+    "conv_todo",
+    "missing_edges",
+    "missing_purch_unit",
+    "missing_data_report",
+    "invoices",
+    "trans",
+    "recp_inv",
+    "bids",
+    "vendor_bids",
+    "price_alerts",
+    "price_alert_lines",
+    "invoice_charges",
+    "invoice_reconciliation",
+    "invoice_discrepancies",
+    "credit_memos",
+    "accounting_periods",
+    "period_revenue",
+    "period_cogs",
+    "cogs_category_map",
+    "unit_aliases",
+    "unit_standards",
+    "item_preps",
+    "yield_tests",
+    "inv_counts",
+    "count_sessions",
+    "par_levels",
+    "purchase_orders",
+    "po_lines",
+    "po_receipts",
+    "po_receipt_lines",
+    "pos_profiles",
+    "pos_items",
+    "sales_imports",
+    "sales",
+];
+
+#[tauri::command]
+fn browse_table(
+    app: tauri::AppHandle,
+    table_name: String,
+    limit: u32,
+    offset: u32,
+) -> Result<BrowseTableResponse, String> {
+    if !ALLOWED_TABLES.contains(&table_name.as_str()) {
+        return Err(format!("Table '{}' is not browsable", table_name));
+    }
+
+    let conn = open_initialized_db(&app)?;
+
+    let total: i64 = conn
+        .query_row(&format!("SELECT COUNT(*) FROM {}", table_name), [], |r| {
+            r.get(0)
+        })
+        .map_err(|e| e.to_string())?;
+
+    let query = format!(
+        "SELECT * FROM {} LIMIT {} OFFSET {}",
+        table_name, limit, offset
+    );
+    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+
+    let columns: Vec<String> = stmt
+        .column_names()
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+
+    let col_count = columns.len();
+    let rows: Vec<Vec<String>> = stmt
+        .query_map([], |row| {
+            let mut vals = Vec::with_capacity(col_count);
+            for i in 0..col_count {
+                let val: String = match row.get_ref(i) {
+                    Ok(rusqlite::types::ValueRef::Null) => String::new(),
+                    Ok(rusqlite::types::ValueRef::Integer(n)) => n.to_string(),
+                    Ok(rusqlite::types::ValueRef::Real(f)) => format!("{:.4}", f),
+                    Ok(rusqlite::types::ValueRef::Text(t)) => {
+                        String::from_utf8_lossy(t).to_string()
+                    }
+                    Ok(rusqlite::types::ValueRef::Blob(b)) => format!("[blob {} bytes]", b.len()),
+                    Err(_) => String::new(),
+                };
+                vals.push(val);
+            }
+            Ok(vals)
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(BrowseTableResponse {
+        columns,
+        rows,
+        total,
+    })
+}
+
+// ──────────────────────────────────────────
+// Export helpers: PDF + DOCX for inventory & recipes
+// ──────────────────────────────────────────
+
+use printpdf::*;
+use std::io::BufWriter;
+
+/// Embedded 4chef logo PNG for export headers
+const LOGO_PNG: &[u8] = include_bytes!("../assets/4chef-logo.png");
+
+/// Adds the 4chef logo to a PDF layer at the given (x, y) position.
+/// The logo renders at approximately 20 mm wide.
+fn add_logo_to_pdf_layer(layer: &PdfLayerReference) {
+    let margin_left = 15.0f32;
+    if let Ok(img) = printpdf::image_crate::load_from_memory(LOGO_PNG) {
+        let width_px = img.width() as f32;
+        let target_mm = 20.0f32;
+        let dpi = width_px / (target_mm / 25.4);
+        let pdf_img = Image::from_dynamic_image(&img);
+        pdf_img.add_to_layer(layer.clone(), ImageTransform {
+            translate_x: Some(Mm(margin_left)),
+            translate_y: Some(Mm(274.0)),
+            dpi: Some(dpi),
+            ..Default::default()
+        });
+    }
+}
+
+/// Wraps long text into lines of at most `max_chars` characters, breaking on
+/// whitespace boundaries.
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for raw_line in text.lines() {
+        if raw_line.len() <= max_chars {
+            lines.push(raw_line.to_string());
+            continue;
+        }
+        let mut current = String::new();
+        for word in raw_line.split_whitespace() {
+            if current.is_empty() {
+                current = word.to_string();
+            } else if current.len() + 1 + word.len() <= max_chars {
+                current.push(' ');
+                current.push_str(word);
+            } else {
+                lines.push(current);
+                current = word.to_string();
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+fn fmt_money(v: f64) -> String {
+    format!("${:.2}", v)
+}
+
+/// A column of a tabular PDF report: header, offset from the left margin,
+/// font size and optional truncation length.
+struct PdfColumn {
+    header: &'static str,
+    x: f32,
+    size: f32,
+    max_chars: Option<usize>,
+}
+
+/// One row of a tabular PDF report. When grouping, a change of `group`
+/// starts a new section header.
+struct PdfRow {
+    group: String,
+    cells: Vec<String>,
+    bold: bool,
+}
+
+/// Writes the inventory report layout (logo, title, column headers, section
+/// headers and page breaks) for any set of columns and rows.
+fn write_table_pdf(
+    output_path: &str,
+    doc_title: &str,
+    title: &str,
+    subtitle: Option<&str>,
+    columns: &[PdfColumn],
+    rows: &[PdfRow],
+    grouped: bool,
+) -> Result<(), String> {
+    let (doc, page1, layer1) = PdfDocument::new(doc_title, Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+
+    let mut current_layer = doc.get_page(page1).get_layer(layer1);
+    let line_height = 5.0f32;
+    let margin_left = 15.0f32;
+
+    // Logo
+    add_logo_to_pdf_layer(&current_layer);
+
+    // Title (shifted down to leave room for logo)
+    let mut y = 270.0f32;
+    current_layer.use_text(title, 16.0, Mm(margin_left), Mm(y), &font_bold);
+    y -= 10.0;
+    if let Some(subtitle) = subtitle {
+        current_layer.use_text(subtitle, 10.0, Mm(margin_left), Mm(y + 3.0), &font);
+        y -= 4.0;
+    }
+
+    // Column headers
+    for col in columns {
+        current_layer.use_text(col.header, 9.0, Mm(margin_left + col.x), Mm(y), &font_bold);
+    }
+    y -= 3.0;
+
+    // Draw a line
+    let line_pts = vec![
+        (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
+        (printpdf::Point::new(Mm(195.0), Mm(y)), false),
+    ];
+    current_layer.add_line(printpdf::Line {
+        points: line_pts,
+        is_closed: false,
+    });
+    y -= line_height;
+
+    let mut last_group = String::new();
+    for row in rows {
+        // Section header for grouped views
+        if grouped && row.group != last_group {
+            if y < 25.0 {
+                let (new_page, new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+                current_layer = doc.get_page(new_page).get_layer(new_layer);
+                y = 285.0;
+            }
+            y -= 3.0;
+            current_layer.use_text(&row.group, 11.0, Mm(margin_left), Mm(y), &font_bold);
+            y -= 2.0;
+            let sep_pts = vec![
+                (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
+                (printpdf::Point::new(Mm(195.0), Mm(y)), false),
+            ];
+            current_layer.add_line(printpdf::Line {
+                points: sep_pts,
+                is_closed: false,
+            });
+            y -= line_height;
+            last_group = row.group.clone();
+        }
+
+        if y < 15.0 {
+            let (new_page, new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+            current_layer = doc.get_page(new_page).get_layer(new_layer);
+            y = 285.0;
+        }
+        for (col, cell) in columns.iter().zip(&row.cells) {
+            let text: String = match col.max_chars {
+                Some(max) => cell.chars().take(max).collect(),
+                None => cell.clone(),
+            };
+            let f = if row.bold { &font_bold } else { &font };
+            current_layer.use_text(text, col.size, Mm(margin_left + col.x), Mm(y), f);
+        }
+        y -= line_height;
+    }
+
+    let file = std::fs::File::create(output_path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn export_inventory_pdf(
+    app: tauri::AppHandle,
+    output_path: String,
+    group_by: Option<String>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let group_by = group_by.unwrap_or_default();
+
+    let use_vendor_join = group_by == "vendor";
+    let order_clause = match group_by.as_str() {
+        "food_category" => "COALESCE(i.food_category, '') COLLATE NOCASE, i.name COLLATE NOCASE",
+        "storage" => "COALESCE(i.storage_type, '') COLLATE NOCASE, i.name COLLATE NOCASE",
+        "vendor" => "COALESCE(v.name, 'zzz') COLLATE NOCASE, i.name COLLATE NOCASE",
+        _ => "i.name COLLATE NOCASE",
+    };
+
+    let from_sql = if use_vendor_join {
+        "items i LEFT JOIN inv_prices ip ON i.item_id = ip.item_id AND ip.status = 1 LEFT JOIN vendors v ON ip.vendor_id = v.vendor_id"
+    } else {
+        "items i"
+    };
+
+    let select_cols = if use_vendor_join {
+        "DISTINCT i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), COALESCE(v.name, '')"
+    } else {
+        "i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), ''"
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM {} ORDER BY {}",
+            select_cols, from_sql, order_clause
+        ))
+        .map_err(|e| e.to_string())?;
+    let items: Vec<(i64, String, i64, String, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3).unwrap_or_default(), row.get(4).unwrap_or_default(), row.get(5).unwrap_or_default())))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let title = match group_by.as_str() {
+        "food_category" => "4chef — Inventory Report (by Food Category)",
+        "storage" => "4chef — Inventory Report (by Storage Type)",
+        "vendor" => "4chef — Inventory Report (by Vendor)",
+        _ => "4chef — Inventory Report",
+    };
+    let columns = [
+        PdfColumn {
+            header: "ID",
+            x: 0.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Name",
+            x: 20.0,
+            size: 8.0,
+            max_chars: Some(45),
+        },
+        PdfColumn {
+            header: "Category",
+            x: 110.0,
+            size: 7.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Storage",
+            x: 145.0,
+            size: 7.0,
+            max_chars: None,
+        },
+    ];
+    let dash = |v: &String| {
+        if v.is_empty() {
+            "-".to_string()
+        } else {
+            v.clone()
+        }
+    };
+    let rows: Vec<PdfRow> = items
+        .iter()
+        .map(
+            |(item_id, name, _status, food_cat, storage_t, vendor_name)| PdfRow {
+                group: match group_by.as_str() {
+                    "food_category" => {
+                        if food_cat.is_empty() {
+                            "Uncategorized".to_string()
+                        } else {
+                            food_cat.clone()
+                        }
+                    }
+                    "storage" => {
+                        if storage_t.is_empty() {
+                            "Uncategorized".to_string()
+                        } else {
+                            storage_t.clone()
+                        }
+                    }
+                    "vendor" => {
+                        if vendor_name.is_empty() {
+                            "No Vendor".to_string()
+                        } else {
+                            vendor_name.clone()
+                        }
+                    }
+                    _ => String::new(),
+                },
+                cells: vec![
+                    item_id.to_string(),
+                    name.clone(),
+                    dash(food_cat),
+                    dash(storage_t),
+                ],
+                bold: false,
+            },
+        )
+        .collect();
+    let is_grouped = group_by == "food_category" || group_by == "storage" || group_by == "vendor";
+    write_table_pdf(
+        &output_path,
+        "4chef Inventory",
+        title,
+        None,
+        &columns,
+        &rows,
+        is_grouped,
+    )?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} items to {}", items.len(), output_path),
+    })
+}
+
+type RecipeCard = (
+    String,
+    String,
+    Option<String>,
+    Vec<(String, String, String)>,
+);
+
+/// Name, instructions, optional subtitle and (item, qty, unit) rows for a
+/// printable recipe card, scaled when a scale mode and target are given.
+fn load_recipe_card(
+    conn: &rusqlite::Connection,
+    recipe_id: i64,
+    scale_mode: Option<&str>,
+    scale_target: Option<f64>,
+) -> Result<RecipeCard, String> {
+    let (name, instructions): (String, String) = conn
+        .query_row(
+            "SELECT name, COALESCE(instructions, '') FROM recipes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    if let (Some(mode), Some(target)) = (scale_mode, scale_target) {
+        let factor = scaling::scale_factor(conn, recipe_id, mode, target)?;
+        let scaled = scaling::scale_recipe(conn, recipe_id, factor)?;
+        let mut subtitle = format!("Scaled x{:.2}", factor);
+        if let Some(servings) = scaled.servings {
+            subtitle.push_str(&format!(" — {:.0} servings", servings));
+        }
+        if let Some(q) = scaled.yield_qty {
+            subtitle.push_str(&format!(" — yield {:.2} {}", q, scaled.yield_unit_name));
+        }
+        if scaled.missing_costs == 0 {
+            subtitle.push_str(&format!(" — batch cost {}", fmt_money(scaled.total_cost)));
+        }
+        let rows = scaled
+            .lines
+            .into_iter()
+            .map(|l| (l.item_name, l.display_qty, l.unit_name))
+            .collect();
+        return Ok((name, instructions, Some(subtitle), rows));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(sr.name, i.name, '?'), ri.qty, COALESCE(u.sing, '?')
+             FROM recp_items ri
+             LEFT JOIN items i ON i.item_id = ri.item_id
+             LEFT JOIN recipes sr ON sr.recipe_id = ri.sub_recipe_id
+             LEFT JOIN units u ON u.unit_id = ri.unit_id
+             WHERE ri.recipe_id = ?1
+             ORDER BY ri.recp_item_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([recipe_id], |row| {
+            let qty: Option<f64> = row.get(1)?;
+            Ok((
+                row.get(0)?,
+                qty.map(|q| format!("{:.2}", q))
+                    .unwrap_or_else(|| "-".to_string()),
+                row.get(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok((name, instructions, None, rows))
+}
+
+#[tauri::command]
+fn export_recipe_pdf(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    output_path: String,
+    scale_mode: Option<String>,
+    scale_target: Option<f64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+
+    let (name, instructions, subtitle, ingredients) =
+        load_recipe_card(&conn, recipe_id, scale_mode.as_deref(), scale_target)?;
+
+    let (doc, page1, layer1) = PdfDocument::new(
+        format!("Recipe — {}", name),
+        Mm(210.0),
+        Mm(297.0),
+        "Layer 1",
+    );
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let font_bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+
+    let mut current_layer = doc.get_page(page1).get_layer(layer1);
+    let margin_left = 15.0f32;
+
+    // Logo
+    add_logo_to_pdf_layer(&current_layer);
+
+    // Title (shifted down to leave room for logo)
+    let mut y = 270.0f32;
+    current_layer.use_text(&name, 18.0, Mm(margin_left), Mm(y), &font_bold);
+    y -= 10.0;
+    if let Some(subtitle) = &subtitle {
+        current_layer.use_text(subtitle, 10.0, Mm(margin_left), Mm(y), &font);
+        y -= 8.0;
+    }
+
+    // Instructions
+    if !instructions.trim().is_empty() {
+        current_layer.use_text("Instructions", 11.0, Mm(margin_left), Mm(y), &font_bold);
+        y -= 6.0;
+        for line in wrap_text(&instructions, 90) {
+            if y < 15.0 {
+                let (np, nl) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+                current_layer = doc.get_page(np).get_layer(nl);
+                y = 285.0;
+            }
+            current_layer.use_text(&line, 9.0, Mm(margin_left), Mm(y), &font);
+            y -= 5.0;
+        }
+        y -= 4.0;
+    }
+
+    // Ingredients header
+    current_layer.use_text("Ingredients", 11.0, Mm(margin_left), Mm(y), &font_bold);
+    y -= 7.0;
+    current_layer.use_text("Item", 9.0, Mm(margin_left), Mm(y), &font_bold);
+    current_layer.use_text("Qty", 9.0, Mm(margin_left + 100.0), Mm(y), &font_bold);
+    current_layer.use_text("Unit", 9.0, Mm(margin_left + 130.0), Mm(y), &font_bold);
+    y -= 3.0;
+    let line_pts = vec![
+        (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
+        (printpdf::Point::new(Mm(195.0), Mm(y)), false),
+    ];
+    current_layer.add_line(printpdf::Line {
+        points: line_pts,
+        is_closed: false,
+    });
+    y -= 5.0;
+
+    for (item_name, qty_str, unit) in &ingredients {
+        if y < 15.0 {
+            let (np, nl) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+            current_layer = doc.get_page(np).get_layer(nl);
+            y = 285.0;
+        }
+        let display_name = if item_name.len() > 50 { &item_name[..50] } else { item_name.as_str() };
+        current_layer.use_text(display_name, 8.0, Mm(margin_left), Mm(y), &font);
+        current_layer.use_text(qty_str, 8.0, Mm(margin_left + 100.0), Mm(y), &font);
+        current_layer.use_text(unit, 8.0, Mm(margin_left + 130.0), Mm(y), &font);
+        y -= 5.0;
+    }
+
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported recipe '{}' to {}", name, output_path),
+    })
+}
+
+#[tauri::command]
+fn export_inventory_docx(
+    app: tauri::AppHandle,
+    output_path: String,
+    group_by: Option<String>,
+) -> Result<PatchResponse, String> {
+    use docx_rs::*;
+
+    let conn = open_initialized_db(&app)?;
+    let group_by = group_by.unwrap_or_default();
+
+    let use_vendor_join = group_by == "vendor";
+    let order_clause = match group_by.as_str() {
+        "food_category" => "COALESCE(i.food_category, '') COLLATE NOCASE, i.name COLLATE NOCASE",
+        "storage" => "COALESCE(i.storage_type, '') COLLATE NOCASE, i.name COLLATE NOCASE",
+        "vendor" => "COALESCE(v.name, 'zzz') COLLATE NOCASE, i.name COLLATE NOCASE",
+        _ => "i.name COLLATE NOCASE",
+    };
+
+    let from_sql = if use_vendor_join {
+        "items i LEFT JOIN inv_prices ip ON i.item_id = ip.item_id AND ip.status = 1 LEFT JOIN vendors v ON ip.vendor_id = v.vendor_id"
+    } else {
+        "items i"
+    };
+
+    let select_cols = if use_vendor_join {
+        "DISTINCT i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), COALESCE(v.name, '')"
+    } else {
+        "i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), ''"
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM {} ORDER BY {}",
+            select_cols, from_sql, order_clause
+        ))
+        .map_err(|e| e.to_string())?;
+    let items: Vec<(i64, String, i64, String, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3).unwrap_or_default(), row.get(4).unwrap_or_default(), row.get(5).unwrap_or_default())))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut docx = Docx::new();
+
+    // Logo (~20 mm ≈ 720 000 EMU)
+    {
+        let pic = Pic::new(LOGO_PNG).size(720_000, 720_000);
+        docx = docx.add_paragraph(
+            Paragraph::new().add_run(Run::new().add_image(pic)),
+        );
+    }
+
+    // Title
+    let title = match group_by.as_str() {
+        "food_category" => "4chef — Inventory Report (by Food Category)",
+        "storage" => "4chef — Inventory Report (by Storage Type)",
+        "vendor" => "4chef — Inventory Report (by Vendor)",
+        _ => "4chef — Inventory Report",
+    };
+    docx = docx.add_paragraph(
+        Paragraph::new()
+            .add_run(Run::new().add_text(title).bold().size(32)),
+    );
+    docx = docx.add_paragraph(Paragraph::new()); // spacer
+
+    let is_grouped = group_by == "food_category" || group_by == "storage" || group_by == "vendor";
+    let mut last_group = String::new();
+
+    // Build table
+    let header_row = TableRow::new(vec![
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("ID").bold())),
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Name").bold())),
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Category").bold())),
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Storage").bold())),
+    ]);
+    let mut rows = vec![header_row];
+    for (item_id, name, _status, food_cat, storage_t, vendor_name) in &items {
+        let current_group = match group_by.as_str() {
+            "food_category" => if food_cat.is_empty() { "Uncategorized".to_string() } else { food_cat.clone() },
+            "storage" => if storage_t.is_empty() { "Uncategorized".to_string() } else { storage_t.clone() },
+            "vendor" => if vendor_name.is_empty() { "No Vendor".to_string() } else { vendor_name.clone() },
+            _ => String::new(),
+        };
+
+        if is_grouped && current_group != last_group {
+            // Section header row spanning all columns
+            rows.push(TableRow::new(vec![
+                TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&current_group).bold().size(22))),
+                TableCell::new().add_paragraph(Paragraph::new()),
+                TableCell::new().add_paragraph(Paragraph::new()),
+                TableCell::new().add_paragraph(Paragraph::new()),
+            ]));
+            last_group = current_group;
+        }
+
+        let fc_display = if food_cat.is_empty() { "-".to_string() } else { food_cat.clone() };
+        let st_display = if storage_t.is_empty() { "-".to_string() } else { storage_t.clone() };
+        rows.push(TableRow::new(vec![
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(item_id.to_string()))),
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(name))),
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&fc_display))),
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&st_display))),
+        ]));
+    }
+    docx = docx.add_table(Table::new(rows));
+
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    docx.build().pack(file).map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} items to {}", items.len(), output_path),
+    })
+}
+
+#[tauri::command]
+fn export_recipe_docx(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    output_path: String,
+    scale_mode: Option<String>,
+    scale_target: Option<f64>,
+) -> Result<PatchResponse, String> {
+    use docx_rs::*;
+
+    let conn = open_initialized_db(&app)?;
+
+    let (name, instructions, subtitle, ingredients) =
+        load_recipe_card(&conn, recipe_id, scale_mode.as_deref(), scale_target)?;
+
+    let mut docx = Docx::new();
+
+    // Logo (~20 mm ≈ 720 000 EMU)
+    {
+        let pic = Pic::new(LOGO_PNG).size(720_000, 720_000);
+        docx = docx.add_paragraph(
+            Paragraph::new().add_run(Run::new().add_image(pic)),
+        );
+    }
+
+    // Title
+    docx = docx.add_paragraph(
+        Paragraph::new().add_run(Run::new().add_text(&name).bold().size(36)),
+    );
+    if let Some(subtitle) = &subtitle {
+        docx = docx.add_paragraph(Paragraph::new().add_run(Run::new().add_text(subtitle).italic()));
+    }
+
+    // Instructions
+    if !instructions.trim().is_empty() {
+        docx = docx.add_paragraph(
+            Paragraph::new().add_run(Run::new().add_text("Instructions").bold().size(24)),
+        );
+        for line in instructions.lines() {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                docx = docx.add_paragraph(
+                    Paragraph::new().add_run(Run::new().add_text(trimmed)),
+                );
+            }
+        }
+        docx = docx.add_paragraph(Paragraph::new()); // spacer
+    }
+
+    // Ingredients table
+    docx = docx.add_paragraph(
+        Paragraph::new().add_run(Run::new().add_text("Ingredients").bold().size(24)),
+    );
+    let header_row = TableRow::new(vec![
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Item").bold())),
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Qty").bold())),
+        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Unit").bold())),
+    ]);
+    let mut rows = vec![header_row];
+    for (item_name, qty_str, unit) in &ingredients {
+        rows.push(TableRow::new(vec![
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(item_name))),
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(qty_str))),
+            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(unit))),
+        ]));
+    }
+    docx = docx.add_table(Table::new(rows));
+
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    docx.build().pack(file).map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported recipe '{}' to {}", name, output_path),
+    })
+}
+
+// ──────────────────────────────────────────
+// FDA Guidelines PDF export
+// ──────────────────────────────────────────
+
+#[tauri::command]
+fn export_fda_pdf(output_path: String) -> Result<PatchResponse, String> {
+    let (doc, page1, layer1) =
+        PdfDocument::new("FDA Food Safety Guidelines", Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+
+    let mut current_layer = doc.get_page(page1).get_layer(layer1);
+    let margin_left = 15.0f32;
+    let line_height = 5.0f32;
+
+    // Logo
+    add_logo_to_pdf_layer(&current_layer);
+
+    let mut y = 270.0f32;
+    current_layer.use_text("FDA Food Safety Guidelines", 18.0, Mm(margin_left), Mm(y), &font_bold);
+    y -= 10.0;
+    current_layer.use_text("Quick-reference for commercial kitchen operations", 9.0, Mm(margin_left), Mm(y), &font);
+    y -= 10.0;
+
+    // helper: new page if needed
+    macro_rules! check_page {
+        ($y:expr, $doc:expr, $layer:expr, $need:expr) => {
+            if $y < $need {
+                let (np, nl) = $doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+                $layer = $doc.get_page(np).get_layer(nl);
+                $y = 285.0;
+            }
+        };
+    }
+
+    // helper: section heading
+    macro_rules! section {
+        ($y:expr, $layer:expr, $doc:expr, $title:expr, $font_bold:expr) => {
+            check_page!($y, $doc, $layer, 30.0);
+            $layer.use_text($title, 13.0, Mm(margin_left), Mm($y), $font_bold);
+            $y -= 7.0;
+        };
+    }
+
+    // helper: table row
+    macro_rules! row2 {
+        ($y:expr, $layer:expr, $doc:expr, $a:expr, $b:expr, $font:expr) => {
+            check_page!($y, $doc, $layer, 12.0);
+            $layer.use_text($a, 8.0, Mm(margin_left), Mm($y), $font);
+            $layer.use_text($b, 8.0, Mm(margin_left + 80.0), Mm($y), $font);
+            $y -= line_height;
+        };
+    }
+    macro_rules! row3 {
+        ($y:expr, $layer:expr, $doc:expr, $a:expr, $b:expr, $c:expr, $font:expr) => {
+            check_page!($y, $doc, $layer, 12.0);
+            $layer.use_text($a, 8.0, Mm(margin_left), Mm($y), $font);
+            $layer.use_text($b, 8.0, Mm(margin_left + 60.0), Mm($y), $font);
+            $layer.use_text($c, 8.0, Mm(margin_left + 120.0), Mm($y), $font);
+            $y -= line_height;
+        };
+    }
+    macro_rules! bullet {
+        ($y:expr, $layer:expr, $doc:expr, $text:expr, $font:expr) => {
+            check_page!($y, $doc, $layer, 12.0);
+            for line in wrap_text($text, 100) {
+                check_page!($y, $doc, $layer, 12.0);
+                $layer.use_text(&format!("•  {}", line), 8.0, Mm(margin_left), Mm($y), $font);
+                $y -= line_height;
+            }
+        };
+    }
+    macro_rules! note {
+        ($y:expr, $layer:expr, $doc:expr, $text:expr, $font:expr) => {
+            check_page!($y, $doc, $layer, 12.0);
+            for line in wrap_text(&format!("⚠  {}", $text), 100) {
+                check_page!($y, $doc, $layer, 12.0);
+                $layer.use_text(&line, 7.5, Mm(margin_left + 2.0), Mm($y), $font);
+                $y -= line_height;
+            }
+        };
+    }
+
+    // ── Temperature Control ──
+    section!(y, current_layer, doc, "Temperature Control", &font_bold);
+    row3!(y, current_layer, doc, "ZONE", "RANGE", "GUIDELINE", &font_bold);
+    row3!(y, current_layer, doc, "Danger Zone", "41-135 °F (5-57 °C)", "Max 4 hours total", &font);
+    row3!(y, current_layer, doc, "Cold holding", "≤ 41 °F (5 °C)", "Keep cold foods at/below 41 °F", &font);
+    row3!(y, current_layer, doc, "Hot holding", "≥ 135 °F (57 °C)", "Keep hot foods at/above 135 °F", &font);
+    row3!(y, current_layer, doc, "Receiving", "≤ 41 °F / frozen solid", "Reject above 41 °F or partially thawed", &font);
+    y -= 4.0;
+
+    // ── Minimum Cooking Temperatures ──
+    section!(y, current_layer, doc, "Minimum Cooking Temperatures", &font_bold);
+    row3!(y, current_layer, doc, "FOOD", "INTERNAL TEMP", "HOLD TIME", &font_bold);
+    row3!(y, current_layer, doc, "Poultry", "165 °F (74 °C)", "Instantaneous", &font);
+    row3!(y, current_layer, doc, "Ground meat", "155 °F (68 °C)", "17 seconds", &font);
+    row3!(y, current_layer, doc, "Seafood, steaks, eggs", "145 °F (63 °C)", "15 seconds", &font);
+    row3!(y, current_layer, doc, "Roasts (beef/pork/lamb)", "145 °F (63 °C)", "4 minutes", &font);
+    row3!(y, current_layer, doc, "Vegetables, grains (hot)", "135 °F (57 °C)", "Instantaneous", &font);
+    row3!(y, current_layer, doc, "Reheated leftovers", "165 °F (74 °C)", "Within 2 hours", &font);
+    y -= 4.0;
+
+    // ── Two-Stage Cooling ──
+    section!(y, current_layer, doc, "Two-Stage Cooling", &font_bold);
+    row3!(y, current_layer, doc, "STAGE", "TARGET", "TIME LIMIT", &font_bold);
+    row3!(y, current_layer, doc, "Stage 1", "135 °F -> 70 °F", "Within 2 hours", &font);
+    row3!(y, current_layer, doc, "Stage 2", "70 °F -> 41 °F", "Within 4 hours (6 total)", &font);
+    note!(y, current_layer, doc, "If food does not reach 70 °F within 2 hours, reheat to 165 °F and restart.", &font);
+    y -= 4.0;
+
+    // ── Approved Thawing Methods ──
+    section!(y, current_layer, doc, "Approved Thawing Methods", &font_bold);
+    bullet!(y, current_layer, doc, "Refrigerator thawing — at 41 °F or below", &font);
+    bullet!(y, current_layer, doc, "Cold running water — submerged, ≤ 70 °F, used within 4 hours", &font);
+    bullet!(y, current_layer, doc, "Microwave — only if cooked immediately after", &font);
+    bullet!(y, current_layer, doc, "Cooking from frozen — as part of the cooking process", &font);
+    note!(y, current_layer, doc, "Never thaw food at room temperature on a counter.", &font);
+    y -= 4.0;
+
+    // ── Handwashing ──
+    section!(y, current_layer, doc, "Handwashing", &font_bold);
+    bullet!(y, current_layer, doc, "Wet hands with warm water (≥ 100 °F / 38 °C)", &font);
+    bullet!(y, current_layer, doc, "Apply soap and scrub for at least 20 seconds", &font);
+    bullet!(y, current_layer, doc, "Rinse and dry with single-use towel or air dryer", &font);
+    note!(y, current_layer, doc, "Required: before handling food, after touching raw meat, after using restroom, after sneezing/coughing, after handling trash.", &font);
+    y -= 4.0;
+
+    // ── Cross-Contamination Prevention ──
+    section!(y, current_layer, doc, "Cross-Contamination Prevention", &font_bold);
+    bullet!(y, current_layer, doc, "Store raw meats below ready-to-eat foods in the cooler", &font);
+    bullet!(y, current_layer, doc, "Cooler order (top to bottom): ready-to-eat, seafood, whole cuts, ground meat, poultry", &font);
+    bullet!(y, current_layer, doc, "Use separate cutting boards and utensils for raw and cooked foods", &font);
+    bullet!(y, current_layer, doc, "Sanitize surfaces between tasks — use approved sanitizer at correct concentration", &font);
+    y -= 4.0;
+
+    // ── Maximum Cold Storage ──
+    section!(y, current_layer, doc, "Maximum Cold Storage (at 41 °F)", &font_bold);
+    row2!(y, current_layer, doc, "ITEM", "MAX DAYS", &font_bold);
+    row2!(y, current_layer, doc, "Fresh poultry, ground meat, fish", "1 – 2 days", &font);
+    row2!(y, current_layer, doc, "Fresh steaks, chops, roasts", "3 – 5 days", &font);
+    row2!(y, current_layer, doc, "Cooked leftovers", "7 days (date-mark required)", &font);
+    row2!(y, current_layer, doc, "Deli meats (opened)", "3 – 5 days", &font);
+    row2!(y, current_layer, doc, "Eggs (shell)", "3 – 5 weeks", &font);
+    note!(y, current_layer, doc, "All ready-to-eat TCS foods held longer than 24 hours must be date-marked.", &font);
+    y -= 4.0;
+
+    // ── Big 6 Pathogens ──
+    section!(y, current_layer, doc, "Big 6 Foodborne Pathogens", &font_bold);
+    row2!(y, current_layer, doc, "PATHOGEN", "COMMON SOURCES", &font_bold);
+    row2!(y, current_layer, doc, "Norovirus", "Infected workers, ready-to-eat foods", &font);
+    row2!(y, current_layer, doc, "Salmonella Typhi", "Beverages, ready-to-eat foods", &font);
+    row2!(y, current_layer, doc, "Shigella spp.", "Ready-to-eat foods, contaminated water", &font);
+    row2!(y, current_layer, doc, "E. coli O157:H7", "Undercooked beef, unpasteurized juices", &font);
+    row2!(y, current_layer, doc, "Hepatitis A", "Shellfish, ready-to-eat foods", &font);
+    row2!(y, current_layer, doc, "Non-typhoidal Salmonella", "Poultry, eggs, produce", &font);
+    note!(y, current_layer, doc, "Employees diagnosed with any Big 6 illness must be excluded or restricted per FDA Food Code.", &font);
+
+    let _ = y; // suppress unused warning
+    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported FDA guidelines to {}", output_path),
+    })
+}
+
+// ──────────────────────────────────────────
+// Global search across all data
+// ──────────────────────────────────────────
+
+#[derive(Serialize, Clone)]
+struct GlobalSearchHit {
+    category: String,    // "item", "recipe", "vendor", "invoice"
+    id: i64,
+    label: String,
+    detail: String,
+}
+
+#[tauri::command]
+fn global_search(
+    app: tauri::AppHandle,
+    query: String,
+) -> Result<Vec<GlobalSearchHit>, String> {
+    let conn = open_initialized_db(&app)?;
+    let pattern = format!("%{}%", query.trim());
+    let mut hits: Vec<GlobalSearchHit> = Vec::new();
+
+    // Items
+    {
+        let mut stmt = conn
+            .prepare("SELECT item_id, name FROM items WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&pattern], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows.flatten() {
+            hits.push(GlobalSearchHit {
+                category: "item".into(),
+                id: r.0,
+                label: r.1,
+                detail: "Inventory item".into(),
+            });
+        }
+    }
+
+    // Recipes
+    {
+        let mut stmt = conn
+            .prepare("SELECT recipe_id, name FROM recipes WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&pattern], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows.flatten() {
+            hits.push(GlobalSearchHit {
+                category: "recipe".into(),
+                id: r.0,
+                label: r.1,
+                detail: "Recipe".into(),
+            });
+        }
+    }
+
+    // Vendors
+    {
+        let mut stmt = conn
+            .prepare("SELECT vendor_id, name FROM vendors WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&pattern], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows.flatten() {
+            hits.push(GlobalSearchHit {
+                category: "vendor".into(),
+                id: r.0,
+                label: r.1,
+                detail: "Vendor".into(),
+            });
+        }
+    }
+
+    // Invoices
+    {
+        let mut stmt = conn
+            .prepare(
+                "SELECT inv_id, COALESCE(invoice_no, ''), COALESCE(v.name, '')
+                 FROM invoices i LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+                 WHERE i.invoice_no LIKE ?1 OR v.name LIKE ?1
+                 ORDER BY i.inv_id DESC LIMIT 20"
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&pattern], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for r in rows.flatten() {
+            hits.push(GlobalSearchHit {
+                category: "invoice".into(),
+                id: r.0,
+                label: format!("#{} — {}", r.1, r.2),
+                detail: "Invoice".into(),
+            });
+        }
+    }
+
+    Ok(hits)
+}
+
+// ──────────────────────────────────────────
+// PDF Invoice Import — extract text-based PDF invoices
+// ──────────────────────────────────────────
+
+#[derive(Serialize)]
+struct PdfInvoiceLine {
+    item_name: String,
+    qty: Option<f64>,
+    unit: String,
+    unit_price: Option<f64>,
+    ext_cost: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct PdfInvoicePreview {
+    vendor_guess: String,
+    invoice_no: String,
+    invoice_date: String,
+    lines: Vec<PdfInvoiceLine>,
+    raw_text: String,
+}
+
+/// Try to parse a dollar value from a string like "$12.50" or "12.50".
+fn parse_dollar(s: &str) -> Option<f64> {
+    let cleaned = s.trim().trim_start_matches('$').replace(',', "");
+    cleaned.parse::<f64>().ok()
+}
+
+#[tauri::command]
+fn preview_pdf_invoice(pdf_path: String) -> Result<PdfInvoicePreview, String> {
+    let bytes = std::fs::read(&pdf_path).map_err(|e| format!("Cannot read file: {e}"))?;
+    let text = pdf_extract::extract_text_from_mem(&bytes)
+        .map_err(|e| format!("PDF extraction failed: {e}"))?;
+
+    // Heuristic parsing for common invoice formats
+    let mut vendor_guess = String::new();
+    let mut invoice_no = String::new();
+    let mut invoice_date = String::new();
+    let mut lines = Vec::new();
+
+    let text_lines: Vec<&str> = text.lines().collect();
+
+    // First non-empty line is often the vendor name
+    for line in &text_lines {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && vendor_guess.is_empty() {
+            vendor_guess = trimmed.to_string();
+            break;
+        }
+    }
+
+    for line in &text_lines {
+        let lower = line.to_lowercase();
+        // Invoice number
+        if invoice_no.is_empty() {
+            if let Some(pos) = lower.find("invoice") {
+                let after = &line[pos..];
+                let parts: Vec<&str> = after.split_whitespace().collect();
+                // Look for a number-like token after "invoice"
+                for part in parts.iter().skip(1) {
+                    let cleaned = part.trim_matches(|c: char| !c.is_alphanumeric());
+                    if !cleaned.is_empty() {
+                        invoice_no = cleaned.to_string();
+                        break;
+                    }
+                }
+            }
+        }
+        // Date (look for patterns like MM/DD/YYYY or YYYY-MM-DD)
+        if invoice_date.is_empty() {
+            if let Some(pos) = lower.find("date") {
+                let after = &line[pos..];
+                let parts: Vec<&str> = after.split_whitespace().collect();
+                for part in parts.iter().skip(1) {
+                    let cleaned = part.trim_matches(|c: char| c == ':' || c == ' ');
+                    if cleaned.len() >= 8 && cleaned.contains('/') || cleaned.contains('-') {
+                        invoice_date = cleaned.to_string();
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Try to extract line items — look for lines with dollar amounts
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() >= 3 {
+            // Find dollar-like values from the right
+            let mut dollar_vals: Vec<f64> = Vec::new();
+            let mut name_end = tokens.len();
+            for (i, token) in tokens.iter().enumerate().rev() {
+                if let Some(val) = parse_dollar(token) {
+                    dollar_vals.push(val);
+                    name_end = i;
+                } else if !dollar_vals.is_empty() {
+                    break;
+                }
+            }
+            // Also look for a qty at the beginning or near the dollar values
+            if !dollar_vals.is_empty() && name_end > 0 {
+                dollar_vals.reverse();
+                let name_parts: Vec<&str> = tokens[..name_end].to_vec();
+                // Try to find qty — first numeric token in name_parts from the left or right
+                let mut qty: Option<f64> = None;
+                let mut unit = String::new();
+                let mut actual_name_parts = name_parts.clone();
+
+                // Check if first token is a number (qty)
+                if let Ok(q) = name_parts[0].parse::<f64>() {
+                    qty = Some(q);
+                    actual_name_parts = name_parts[1..].to_vec();
+                    // Check if second token looks like a unit (short word)
+                    if actual_name_parts.len() > 1 && actual_name_parts[0].len() <= 5 {
+                        unit = actual_name_parts[0].to_string();
+                        actual_name_parts = actual_name_parts[1..].to_vec();
+                    }
+                }
+
+                let item_name = actual_name_parts.join(" ");
+                if !item_name.is_empty() && item_name.len() > 2 {
+                    let unit_price = if dollar_vals.len() >= 2 {
+                        Some(dollar_vals[0])
+                    } else {
+                        None
+                    };
+                    let ext_cost = dollar_vals.last().copied();
+                    lines.push(PdfInvoiceLine {
+                        item_name,
+                        qty,
+                        unit,
+                        unit_price,
+                        ext_cost,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(PdfInvoicePreview {
+        vendor_guess,
+        invoice_no,
+        invoice_date,
+        lines,
+        raw_text: text,
+    })
+}
+
+#[tauri::command]
+fn import_pdf_invoice(
+    app: tauri::AppHandle,
+    vendor_id: i64,
+    invoice_no: String,
+    invoice_date: String,
+    lines: Vec<serde_json::Value>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+
+    // Find next invoice_id
+    let next_id: i64 = conn
+        .query_row("SELECT COALESCE(MAX(invoice_id), 0) + 1 FROM invoices", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+
+    // Insert invoice header
+    conn.execute(
+        "INSERT INTO invoices (status, invoice_id, invoice_date, vendor_id, invoice_no, freight, total)
+         VALUES (1, ?1, ?2, ?3, ?4, 0, 0)",
+        (next_id, &invoice_date, vendor_id, &invoice_no),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut trans_count = 0i64;
+    let next_trans_base: i64 = conn
+        .query_row("SELECT COALESCE(MAX(trans_id), 0) + 1 FROM trans", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut total = 0.0f64;
+    let mut charge_count = 0usize;
+    for (i, line) in lines.iter().enumerate() {
+        let item_name = line.get("item_name").and_then(|v| v.as_str()).unwrap_or("");
+        let qty = line.get("qty").and_then(|v| v.as_f64());
+        let unit_price = line.get("unit_price").and_then(|v| v.as_f64());
+        let ext_cost = line.get("ext_cost").and_then(|v| v.as_f64());
+
+        if item_name.is_empty() {
+            continue;
+        }
+
+        // Freight, surcharge and tax lines become invoice charges
+        if let Some(kind) = landed::charge_kind(item_name) {
+            let amount = ext_cost.or_else(|| Some(qty? * unit_price?)).unwrap_or(0.0);
+            if amount != 0.0 {
+                landed::save_charge(
+                    &conn,
+                    &landed::Charge {
+                        charge_id: 0,
+                        invoice_id: next_id,
+                        kind: kind.to_string(),
+                        description: item_name.to_string(),
+                        amount,
+                        allocation: "default".to_string(),
+                    },
+                )?;
+                total += amount;
+                charge_count += 1;
+            }
+            continue;
+        }
+
+        // Try to match item by name
+        let item_id: Option<i64> = conn
+            .query_row(
+                "SELECT item_id FROM items WHERE LOWER(name) = LOWER(?1) LIMIT 1",
+                [item_name],
+                |r| r.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        // If no match, create a new item
+        let final_item_id = match item_id {
+            Some(id) => id,
+            None => {
+                let new_id: i64 = conn
+                    .query_row("SELECT COALESCE(MAX(item_id), 0) + 1 FROM items", [], |r| r.get(0))
+                    .map_err(|e| e.to_string())?;
+                conn.execute(
+                    "INSERT INTO items (item_id, name, status) VALUES (?1, ?2, 1)",
+                    (new_id, item_name),
+                )
+                .map_err(|e| e.to_string())?;
+                new_id
+            }
+        };
+
+        let line_cost = ext_cost.unwrap_or(0.0);
+        total += line_cost;
+
+        let trans_id = next_trans_base + i as i64;
+        conn.execute(
+            "INSERT INTO trans (status, invoice_id, trans_id, item_id, trans_date, vendor_id, price, qty, unit_id, ext_cost)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, ?8)",
+            (
+                next_id,
+                trans_id,
+                final_item_id,
+                &invoice_date,
+                vendor_id,
+                unit_price.unwrap_or(0.0),
+                qty.unwrap_or(0.0),
+                line_cost,
+            ),
+        )
+        .map_err(|e| e.to_string())?;
+        trans_count += 1;
+    }
+
+    // Update invoice total
+    conn.execute(
+        "UPDATE invoices SET total = ?2 WHERE invoice_id = ?1",
+        (next_id, total),
+    )
+    .map_err(|e| e.to_string())?;
+    let alerts = price_alerts::check_invoice(&conn, next_id)?;
+    landed::allocate_invoice(&conn, next_id)?;
+    let balanced = reconcile::reconcile_invoice(&conn, next_id)? == "balanced";
+
+    let mut extras = String::new();
+    if charge_count > 0 {
+        extras.push_str(&format!("; {} charges allocated to lines", charge_count));
+    }
+    if alerts > 0 {
+        extras.push_str(&format!("; {} price increase alerts", alerts));
+    }
+    if !balanced {
+        extras.push_str("; invoice doesn't balance, see Invoice Reconciliation");
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Imported invoice #{} with {} lines (total {}){}",
+            invoice_no,
+            trans_count,
+            fmt_money(total),
+            extras
+        ),
+    })
+}
+
+// ── Food cost calculator ──
+
+#[derive(serde::Deserialize)]
+struct FoodCostLineInput {
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: f64,
+    #[serde(default)]
+    prep_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct FoodCostLineOutput {
+    item_id: i64,
+    item_name: String,
+    unit_id: Option<i64>,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    yield_pct: Option<f64>,
+    ap_cost: Option<f64>,
+    extended_cost: Option<f64>,
+    cost_status: String,
+}
+
+#[derive(Serialize)]
+struct FoodCostResponse {
+    lines: Vec<FoodCostLineOutput>,
+    total_cost: f64,
+    missing_costs: i64,
+}
+
+#[tauri::command]
+fn calculate_food_cost(
+    app: tauri::AppHandle,
+    lines: Vec<FoodCostLineInput>,
+) -> Result<FoodCostResponse, String> {
+    let conn = open_initialized_db(&app)?;
+
+    let mut out_lines = Vec::new();
+    let mut total_cost = 0.0f64;
+    let mut missing_costs = 0i64;
+
+    for line in &lines {
+        let item_name: String = conn
+            .query_row(
+                "SELECT name FROM items WHERE item_id = ?1",
+                [line.item_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| "(unknown)".to_string());
+
+        let unit_name: String = if let Some(uid) = line.unit_id {
+            conn.query_row("SELECT sing FROM units WHERE unit_id = ?1", [uid], |row| {
+                row.get(0)
+            })
+            .unwrap_or_else(|_| "-".to_string())
+        } else {
+            "-".to_string()
+        };
+
+        let cost = cost_item_line(
+            &conn,
+            line.item_id,
+            line.unit_id,
+            Some(line.qty),
+            line.prep_id,
+        )?;
+        let price = cost.price;
+        let extended_cost = cost.extended_cost;
+        let cost_status = cost.cost_status;
+
+        if let Some(c) = extended_cost {
+            total_cost += c;
+        } else {
+            missing_costs += 1;
+        }
+
+        out_lines.push(FoodCostLineOutput {
+            item_id: line.item_id,
+            item_name,
+            unit_id: line.unit_id,
+            unit_name,
+            qty: line.qty,
+            price,
+            yield_pct: cost.yield_pct,
+            ap_cost: cost.ap_cost,
+            extended_cost,
+            cost_status,
+        });
+    }
+
+    Ok(FoodCostResponse {
+        lines: out_lines,
+        total_cost,
+        missing_costs,
+    })
+}
+
+#[derive(Serialize)]
+struct SettingsResponse {
+    company_name: String,
+    logo_path: String,
+    service_category: String,
+    operation_size: String,
+    target_food_cost_pct: String,
+    price_rounding: String,
+    price_alert_pct: String,
+    landed_alloc_method: String,
+    landed_include_tax: bool,
+    costing_use_landed: bool,
+}
+
+/// Purchasing and landed-cost settings, saved together.
+#[derive(serde::Deserialize)]
+struct PurchasingSettingsInput {
+    price_alert_pct: String,
+    landed_alloc_method: String,
+    landed_include_tax: bool,
+    costing_use_landed: bool,
+}
+
+#[tauri::command]
+fn get_settings(app: tauri::AppHandle) -> Result<SettingsResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    fn get_val(conn: &rusqlite::Connection, key: &str) -> String {
+        conn.query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .unwrap_or_default()
+    }
+    Ok(SettingsResponse {
+        company_name: get_val(&conn, "company_name"),
+        logo_path: get_val(&conn, "logo_path"),
+        service_category: get_val(&conn, "service_category"),
+        operation_size: get_val(&conn, "operation_size"),
+        target_food_cost_pct: get_val(&conn, "target_food_cost_pct"),
+        price_rounding: get_val(&conn, "price_rounding"),
+        price_alert_pct: get_val(&conn, "price_alert_pct"),
+        landed_alloc_method: landed::default_method(&conn),
+        landed_include_tax: landed::tax_is_cost(&conn),
+        costing_use_landed: landed::use_landed_cost(&conn),
+    })
+}
+
+#[tauri::command]
+fn save_settings(
+    app: tauri::AppHandle,
+    company_name: String,
+    service_category: String,
+    operation_size: String,
+    target_food_cost_pct: Option<String>,
+    price_rounding: Option<String>,
+    purchasing: Option<PurchasingSettingsInput>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let mut pairs = vec![
+        ("company_name", company_name.trim().to_string()),
+        ("service_category", service_category.trim().to_string()),
+        ("operation_size", operation_size.trim().to_string()),
+    ];
+    if let Some(pct) = target_food_cost_pct {
+        let pct = pct.trim().to_string();
+        if !pct.is_empty()
+            && !pct
+                .parse::<f64>()
+                .map(|p| p > 0.0 && p < 100.0)
+                .unwrap_or(false)
+        {
+            return Err("Target food cost % must be between 0 and 100".to_string());
+        }
+        pairs.push(("target_food_cost_pct", pct));
+    }
+    if let Some(rounding) = price_rounding {
+        pairs.push(("price_rounding", rounding.trim().to_string()));
+    }
+    let mut reallocate = false;
+    if let Some(purchasing) = purchasing {
+        let pct = purchasing.price_alert_pct.trim().to_string();
+        if !pct.is_empty() && !pct.parse::<f64>().map(|p| p >= 0.0).unwrap_or(false) {
+            return Err("Price alert threshold must be a percentage of 0 or more".to_string());
+        }
+        if !matches!(
+            purchasing.landed_alloc_method.as_str(),
+            "value" | "weight" | "qty"
+        ) {
+            return Err(format!(
+                "Unknown allocation method '{}'",
+                purchasing.landed_alloc_method
+            ));
+        }
+        reallocate = purchasing.landed_alloc_method != landed::default_method(&conn)
+            || purchasing.landed_include_tax != landed::tax_is_cost(&conn);
+        let flag = |on: bool| if on { "1" } else { "" }.to_string();
+        pairs.push(("price_alert_pct", pct));
+        pairs.push(("landed_alloc_method", purchasing.landed_alloc_method));
+        pairs.push(("landed_include_tax", flag(purchasing.landed_include_tax)));
+        pairs.push(("costing_use_landed", flag(purchasing.costing_use_landed)));
+    }
+    for (key, value) in &pairs {
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            (key, value),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let message = if reallocate {
+        let count = landed::allocate_all(&conn, None)?;
+        format!(
+            "Settings saved; landed costs recalculated for {} invoices",
+            count
+        )
+    } else {
+        "Settings saved".to_string()
+    };
+    Ok(PatchResponse { ok: true, message })
+}
+
+#[tauri::command]
+fn upload_logo(app: tauri::AppHandle, source_path: String) -> Result<PatchResponse, String> {
+    let source = std::path::Path::new(&source_path);
+    if !source.exists() || !source.is_file() {
+        return Err(format!("File not found: {}", source_path));
+    }
+
+    let ext = source
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_else(|| "png".to_string());
+    if !matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp") {
+        return Err(format!("Unsupported image format: .{}", ext));
+    }
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| fallback_data_root());
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    let dest = data_dir.join(format!("company_logo.{}", ext));
+    std::fs::copy(source, &dest).map_err(|e| e.to_string())?;
+
+    let dest_str = dest.to_string_lossy().to_string();
+    let conn = open_initialized_db(&app)?;
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES ('logo_path', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        [&dest_str],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Logo saved to {}", dest_str),
+    })
+}
+
+#[tauri::command]
+fn remove_logo(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let logo_path: String = conn
+        .query_row(
+            "SELECT value FROM settings WHERE key = 'logo_path'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or_default();
+
+    if !logo_path.is_empty() {
+        let p = std::path::Path::new(&logo_path);
+        if p.exists() {
+            let _ = std::fs::remove_file(p);
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES ('logo_path', '')
+         ON CONFLICT(key) DO UPDATE SET value = ''",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: "Logo removed".to_string(),
+    })
+}
+
+#[tauri::command]
+fn update_item_category(
+    app: tauri::AppHandle,
+    item_id: i64,
+    food_category: String,
+    storage_type: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let fc: Option<String> = if food_category.trim().is_empty() { None } else { Some(food_category.trim().to_string()) };
+    let st: Option<String> = if storage_type.trim().is_empty() { None } else { Some(storage_type.trim().to_string()) };
+
+    let updated = conn
+        .execute(
+            "UPDATE items SET food_category = ?2, storage_type = ?3 WHERE item_id = ?1",
+            (item_id, &fc, &st),
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Categories updated".to_string(),
+    })
+}
+
+// ── Yields and trim loss ──
+
+fn load_item_preps(conn: &rusqlite::Connection, item_id: i64) -> Result<Vec<ItemPrep>, String> {
+    let mut preps = Vec::new();
+    let mut stmt = conn
+        .prepare("SELECT prep_id, item_id, name, yield_pct FROM item_preps WHERE item_id = ?1 ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([item_id], |row| {
+            Ok(ItemPrep {
+                prep_id: row.get(0)?,
+                item_id: row.get(1)?,
+                name: row.get(2).unwrap_or_else(|_| "-".to_string()),
+                yield_pct: row.get(3).ok().flatten(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        preps.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(preps)
+}
+
+fn validate_yield_pct(yield_pct: Option<f64>) -> Result<(), String> {
+    match yield_pct {
+        Some(p) if p <= 0.0 || p > 100.0 => {
+            Err("Yield % must be greater than 0 and at most 100".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn update_item_yield(
+    app: tauri::AppHandle,
+    item_id: i64,
+    yield_pct: Option<f64>,
+) -> Result<PatchResponse, String> {
+    validate_yield_pct(yield_pct)?;
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE items SET yield_pct = ?2 WHERE item_id = ?1",
+            (item_id, yield_pct),
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Updated item yield".to_string(),
+    })
+}
+
+#[tauri::command]
+fn list_item_preps(app: tauri::AppHandle, item_id: i64) -> Result<ItemPrepResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    Ok(ItemPrepResponse {
+        preps: load_item_preps(&conn, item_id)?,
+    })
+}
+
+#[tauri::command]
+fn upsert_item_prep(
+    app: tauri::AppHandle,
+    prep_id: Option<i64>,
+    item_id: i64,
+    name: String,
+    yield_pct: Option<f64>,
+) -> Result<PatchResponse, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preparation name is required".to_string());
+    }
+    validate_yield_pct(yield_pct)?;
+    let conn = open_initialized_db(&app)?;
+    if let Some(prep_id) = prep_id {
+        let updated = conn
+            .execute(
+                "UPDATE item_preps SET name = ?3, yield_pct = ?4 WHERE prep_id = ?1 AND item_id = ?2",
+                (prep_id, item_id, &name, yield_pct),
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Preparation {} not found", prep_id));
+        }
+        return Ok(PatchResponse {
+            ok: true,
+            message: "Updated preparation".to_string(),
+        });
+    }
+    conn.execute(
+        "INSERT INTO item_preps (item_id, name, yield_pct) VALUES (?1, ?2, ?3)",
+        (item_id, &name, yield_pct),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Added preparation".to_string(),
+    })
+}
+
+#[tauri::command]
+fn delete_item_prep(app: tauri::AppHandle, prep_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| {
+        tx.execute(
+            "UPDATE recp_items SET prep_id = NULL WHERE prep_id = ?1",
+            [prep_id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE yield_tests SET prep_id = NULL WHERE prep_id = ?1",
+            [prep_id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM item_preps WHERE prep_id = ?1", [prep_id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Deleted preparation".to_string(),
+    })
+}
+
+#[tauri::command]
+fn set_recp_item_prep(
+    app: tauri::AppHandle,
+    recipe_id: i64,
+    recp_item_id: i64,
+    prep_id: Option<i64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE recp_items SET prep_id = ?3 WHERE recipe_id = ?1 AND recp_item_id = ?2",
+            (recipe_id, recp_item_id, prep_id),
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err("Recipe item not found".to_string());
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Updated preparation".to_string(),
+    })
+}
+
+#[derive(serde::Deserialize)]
+struct YieldTestInput {
+    item_id: i64,
+    prep_id: Option<i64>,
+    test_date: Option<String>,
+    unit_id: Option<i64>,
+    gross_qty: f64,
+    #[serde(default)]
+    trim_qty: Option<f64>,
+    #[serde(default)]
+    waste_qty: Option<f64>,
+    #[serde(default)]
+    net_qty: Option<f64>,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+/// Records a butchering/trim test and applies the resulting yield to the
+/// preparation (or the item when no preparation is given).
+#[tauri::command]
+fn record_yield_test(app: tauri::AppHandle, test: YieldTestInput) -> Result<PatchResponse, String> {
+    if test.gross_qty <= 0.0 {
+        return Err("Gross quantity must be greater than 0".to_string());
+    }
+    let trim = test.trim_qty.unwrap_or(0.0);
+    let waste = test.waste_qty.unwrap_or(0.0);
+    if trim < 0.0 || waste < 0.0 {
+        return Err("Trim and waste cannot be negative".to_string());
+    }
+    let net = test.net_qty.unwrap_or(test.gross_qty - trim - waste);
+    if net <= 0.0 || net > test.gross_qty {
+        return Err("Net quantity must be greater than 0 and no more than gross".to_string());
+    }
+    let yield_pct = net / test.gross_qty * 100.0;
+    let test_date = test.test_date.filter(|d| !d.trim().is_empty());
+
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| {
+        tx.execute(
+            "INSERT INTO yield_tests (item_id, prep_id, test_date, unit_id, gross_qty, trim_qty, waste_qty, net_qty, notes)
+             VALUES (?1, ?2, COALESCE(?3, date('now', 'localtime')), ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                test.item_id,
+                test.prep_id,
+                test_date,
+                test.unit_id,
+                test.gross_qty,
+                test.trim_qty,
+                test.waste_qty,
+                net,
+                test.notes
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        match test.prep_id {
+            Some(prep_id) => tx.execute(
+                "UPDATE item_preps SET yield_pct = ?2 WHERE prep_id = ?1",
+                (prep_id, yield_pct),
+            ),
+            None => tx.execute(
+                "UPDATE items SET yield_pct = ?2 WHERE item_id = ?1",
+                (test.item_id, yield_pct),
+            ),
+        }
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Recorded yield test ({:.1}% yield)", yield_pct),
+    })
+}
+
+#[derive(Serialize)]
+struct PosProfileRow {
+    profile_id: i64,
+    name: String,
+    item_col: String,
+    qty_col: String,
+    net_sales_col: String,
+    date_col: String,
+    category_col: String,
+    builtin: bool,
+}
+
+#[derive(Serialize)]
+struct PosProfileListResponse {
+    profiles: Vec<PosProfileRow>,
+}
+
+#[tauri::command]
+fn list_pos_profiles(app: tauri::AppHandle) -> Result<PosProfileListResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let mut stmt = conn
+        .prepare("SELECT profile_id FROM pos_profiles ORDER BY builtin DESC, name COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+    let ids: Vec<i64> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let mut profiles = Vec::new();
+    for id in ids {
+        let p = sales::load_profile(&conn, id)?;
+        profiles.push(PosProfileRow {
+            profile_id: p.profile_id,
+            name: p.name,
+            item_col: p.item_col,
+            qty_col: p.qty_col,
+            net_sales_col: p.net_sales_col,
+            date_col: p.date_col,
+            category_col: p.category_col,
+            builtin: p.builtin,
+        });
+    }
+    Ok(PosProfileListResponse { profiles })
+}
+
+#[derive(serde::Deserialize)]
+struct PosProfileInput {
+    profile_id: Option<i64>,
+    name: String,
+    item_col: String,
+    qty_col: String,
+    #[serde(default)]
+    net_sales_col: String,
+    #[serde(default)]
+    date_col: String,
+    #[serde(default)]
+    category_col: String,
+}
+
+/// Creates or updates a custom POS column mapping. Built-in profiles are
+/// read-only; copy one under a new name to adjust it.
+#[tauri::command]
+fn save_pos_profile(
+    app: tauri::AppHandle,
+    profile: PosProfileInput,
+) -> Result<PatchResponse, String> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() || profile.item_col.trim().is_empty() || profile.qty_col.trim().is_empty() {
+        return Err("Profile name, item column and quantity column are required".to_string());
+    }
+    let conn = open_initialized_db(&app)?;
+    match profile.profile_id {
+        Some(profile_id) => {
+            if sales::load_profile(&conn, profile_id)?.builtin {
+                return Err("Built-in profiles cannot be edited".to_string());
+            }
+            conn.execute(
+                "UPDATE pos_profiles SET name = ?2, item_col = ?3, qty_col = ?4, net_sales_col = ?5,
+                        date_col = ?6, category_col = ?7
+                 WHERE profile_id = ?1",
+                rusqlite::params![
+                    profile_id,
+                    name,
+                    profile.item_col.trim(),
+                    profile.qty_col.trim(),
+                    profile.net_sales_col.trim(),
+                    profile.date_col.trim(),
+                    profile.category_col.trim()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO pos_profiles (name, item_col, qty_col, net_sales_col, date_col, category_col, builtin)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+                rusqlite::params![
+                    name,
+                    profile.item_col.trim(),
+                    profile.qty_col.trim(),
+                    profile.net_sales_col.trim(),
+                    profile.date_col.trim(),
+                    profile.category_col.trim()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Saved mapping profile '{}'", name),
+    })
+}
+
+#[tauri::command]
+fn delete_pos_profile(app: tauri::AppHandle, profile_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    if sales::load_profile(&conn, profile_id)?.builtin {
+        return Err("Built-in profiles cannot be deleted".to_string());
+    }
+    conn.execute(
+        "DELETE FROM pos_profiles WHERE profile_id = ?1",
+        [profile_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Mapping profile deleted".to_string(),
+    })
+}
+
+#[derive(Serialize)]
+struct SalesImportResponse {
+    import_id: i64,
+    rows: usize,
+    skipped: usize,
+    new_items: usize,
+    unmapped_items: usize,
+    total_qty: f64,
+    total_sales: f64,
+}
+
+#[tauri::command]
+fn import_sales_csv(
+    app: tauri::AppHandle,
+    csv_path: String,
+    profile_id: i64,
+    period_start: String,
+    period_end: String,
+) -> Result<SalesImportResponse, String> {
+    let period_start = sales::normalize_date(&period_start)
+        .ok_or_else(|| "Enter a valid period start date".to_string())?;
+    let period_end = sales::normalize_date(&period_end).unwrap_or_else(|| period_start.clone());
+    if period_end < period_start {
+        return Err("Period end is before period start".to_string());
+    }
+    let path = PathBuf::from(csv_path.trim());
+    if !db::file_exists(&path) {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    let mut conn = open_initialized_db(&app)?;
+    let profile = sales::load_profile(&conn, profile_id)?;
+    let result = with_tx(&mut conn, |tx| {
+        sales::import_sales_csv(tx, &path, &profile, &period_start, &period_end)
+    })?;
+    Ok(SalesImportResponse {
+        import_id: result.import_id,
+        rows: result.rows,
+        skipped: result.skipped,
+        new_items: result.new_items,
+        unmapped_items: result.unmapped_items,
+        total_qty: result.total_qty,
+        total_sales: result.total_sales,
+    })
+}
+
+#[derive(Serialize)]
+struct SalesImportRow {
+    import_id: i64,
+    profile_name: String,
+    source_file: String,
+    period_start: String,
+    period_end: String,
+    imported_at: String,
+    row_count: i64,
+    total_qty: f64,
+    total_sales: f64,
+}
+
+#[derive(Serialize)]
+struct SalesImportListResponse {
+    imports: Vec<SalesImportRow>,
+}
+
+#[tauri::command]
+fn list_sales_imports(app: tauri::AppHandle) -> Result<SalesImportListResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT si.import_id, COALESCE(p.name, '-'), COALESCE(si.source_file, ''),
+                    COALESCE(si.period_start, ''), COALESCE(si.period_end, ''), COALESCE(si.imported_at, ''),
+                    COALESCE(si.row_count, 0), COALESCE(si.total_qty, 0), COALESCE(si.total_sales, 0)
+             FROM sales_imports si
+             LEFT JOIN pos_profiles p ON p.profile_id = si.profile_id
+             ORDER BY si.period_start DESC, si.import_id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let imports = stmt
+        .query_map([], |row| {
+            Ok(SalesImportRow {
+                import_id: row.get(0)?,
+                profile_name: row.get(1)?,
+                source_file: row.get(2)?,
+                period_start: row.get(3)?,
+                period_end: row.get(4)?,
+                imported_at: row.get(5)?,
+                row_count: row.get(6)?,
+                total_qty: row.get(7)?,
+                total_sales: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(SalesImportListResponse { imports })
+}
+
+#[tauri::command]
+fn delete_sales_import(app: tauri::AppHandle, import_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let removed = with_tx(&mut conn, |tx| {
+        let removed = tx
+            .execute("DELETE FROM sales WHERE import_id = ?1", [import_id])
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM sales_imports WHERE import_id = ?1",
+            [import_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(removed)
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Removed import {} ({} sales rows)", import_id, removed),
+    })
+}
+
+#[derive(Serialize)]
+struct PosItemRow {
+    pos_item_id: i64,
+    name: String,
+    category: String,
+    recipe_id: Option<i64>,
+    recipe_name: String,
+    portions: f64,
+    ignored: bool,
+    units_sold: f64,
+    net_sales: f64,
+}
+
+#[derive(Serialize)]
+struct PosItemListResponse {
+    items: Vec<PosItemRow>,
+    unmapped: usize,
+}
+
+#[tauri::command]
+fn list_pos_items(
+    app: tauri::AppHandle,
+    unmapped_only: bool,
+) -> Result<PosItemListResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.pos_item_id, p.name, COALESCE(p.category, ''), p.recipe_id, COALESCE(r.name, ''),
+                    COALESCE(p.portions, 1), COALESCE(p.ignored, 0),
+                    (SELECT COALESCE(SUM(qty), 0) FROM sales s WHERE s.pos_item_id = p.pos_item_id),
+                    (SELECT COALESCE(SUM(net_sales), 0) FROM sales s WHERE s.pos_item_id = p.pos_item_id)
+             FROM pos_items p
+             LEFT JOIN recipes r ON r.recipe_id = p.recipe_id
+             WHERE (?1 = 0 OR (p.recipe_id IS NULL AND COALESCE(p.ignored, 0) = 0))
+             ORDER BY p.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<PosItemRow> = stmt
+        .query_map([unmapped_only as i64], |row| {
+            Ok(PosItemRow {
+                pos_item_id: row.get(0)?,
+                name: row.get(1)?,
+                category: row.get(2)?,
+                recipe_id: row.get(3)?,
+                recipe_name: row.get(4)?,
+                portions: row.get(5)?,
+                ignored: row.get::<_, i64>(6)? != 0,
+                units_sold: row.get(7)?,
+                net_sales: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let unmapped = items
+        .iter()
+        .filter(|i| i.recipe_id.is_none() && !i.ignored)
+        .count();
+    Ok(PosItemListResponse { items, unmapped })
+}
+
+/// Links a POS menu item to the recipe it sells. `portions` is how many
+/// recipe portions one sale uses (e.g. 2 for a double). Ignored items
+/// (drinks, modifiers, gift cards) stay out of menu engineering.
+#[tauri::command]
+fn map_pos_item(
+    app: tauri::AppHandle,
+    pos_item_id: i64,
+    recipe_id: Option<i64>,
+    portions: Option<f64>,
+    ignored: bool,
+) -> Result<PatchResponse, String> {
+    let portions = portions.unwrap_or(1.0);
+    if portions <= 0.0 {
+        return Err("Portions must be greater than 0".to_string());
+    }
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE pos_items SET recipe_id = ?2, portions = ?3, ignored = ?4 WHERE pos_item_id = ?1",
+            rusqlite::params![pos_item_id, recipe_id, portions, ignored as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("POS item {} not found", pos_item_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: if ignored {
+            "POS item ignored".to_string()
+        } else if recipe_id.is_some() {
+            "POS item mapped".to_string()
+        } else {
+            "POS item unmapped".to_string()
+        },
+    })
+}
+
+#[derive(Serialize)]
+struct MenuEngineeringRowResponse {
+    pos_item_id: i64,
+    name: String,
+    category: String,
+    recipe_id: i64,
+    recipe_name: String,
+    units_sold: f64,
+    menu_price: Option<f64>,
+    plate_cost: Option<f64>,
+    contribution_margin: Option<f64>,
+    revenue: f64,
+    total_cost: Option<f64>,
+    total_margin: Option<f64>,
+    mix_pct: f64,
+    popularity_index: f64,
+    classification: String,
+}
+
+#[derive(Serialize)]
+struct MenuEngineeringResponse {
+    date_from: String,
+    date_to: String,
+    category: String,
+    categories: Vec<String>,
+    rows: Vec<MenuEngineeringRowResponse>,
+    total_units: f64,
+    total_revenue: f64,
+    total_cost: f64,
+    total_margin: f64,
+    avg_contribution_margin: Option<f64>,
+    popularity_threshold_pct: f64,
+    food_cost_pct: Option<f64>,
+    uncosted_items: usize,
+    unmapped_items: usize,
+    unmapped_units: f64,
+}
+
+#[tauri::command]
+fn get_menu_engineering(
+    app: tauri::AppHandle,
+    date_from: String,
+    date_to: String,
+    category: String,
+) -> Result<MenuEngineeringResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = sales::menu_engineering(&conn, date_from.trim(), date_to.trim(), category.trim())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT category FROM pos_items
+             WHERE category IS NOT NULL AND category != '' ORDER BY category COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let categories = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(MenuEngineeringResponse {
+        date_from: report.date_from,
+        date_to: report.date_to,
+        category: report.category,
+        categories,
+        rows: report
+            .rows
+            .into_iter()
+            .map(|r| MenuEngineeringRowResponse {
+                pos_item_id: r.pos_item_id,
+                name: r.name,
+                category: r.category,
+                recipe_id: r.recipe_id,
+                recipe_name: r.recipe_name,
+                units_sold: r.units_sold,
+                menu_price: r.menu_price,
+                plate_cost: r.plate_cost,
+                contribution_margin: r.contribution_margin,
+                revenue: r.revenue,
+                total_cost: r.total_cost,
+                total_margin: r.total_margin,
+                mix_pct: r.mix_pct,
+                popularity_index: r.popularity_index,
+                classification: r.classification,
+            })
+            .collect(),
+        total_units: report.total_units,
+        total_revenue: report.total_revenue,
+        total_cost: report.total_cost,
+        total_margin: report.total_margin,
+        avg_contribution_margin: report.avg_contribution_margin,
+        popularity_threshold_pct: report.popularity_threshold_pct,
+        food_cost_pct: report.food_cost_pct,
+        uncosted_items: report.uncosted_items,
+        unmapped_items: report.unmapped_items,
+        unmapped_units: report.unmapped_units,
+    })
+}
+
+fn menu_engineering_period_label(report: &sales::MenuEngineeringReport) -> String {
+    let period = match (report.date_from.is_empty(), report.date_to.is_empty()) {
+        (true, true) => "All sales".to_string(),
+        (false, true) => format!("From {}", report.date_from),
+        (true, false) => format!("Through {}", report.date_to),
+        (false, false) => format!("{} to {}", report.date_from, report.date_to),
+    };
+    if report.category.is_empty() {
+        period
+    } else {
+        format!("{} — {}", period, report.category)
+    }
+}
+
+#[tauri::command]
+fn export_menu_engineering_csv(
+    app: tauri::AppHandle,
+    date_from: String,
+    date_to: String,
+    category: String,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = sales::menu_engineering(&conn, date_from.trim(), date_to.trim(), category.trim())?;
+    let opt = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "MenuItem",
+        "Category",
+        "Recipe",
+        "UnitsSold",
+        "MixPct",
+        "PopularityIndex",
+        "MenuPrice",
+        "PlateCost",
+        "ContributionMargin",
+        "Revenue",
+        "TotalCost",
+        "TotalMargin",
+        "Class",
+    ])
+    .map_err(|e| e.to_string())?;
+    for r in &report.rows {
+        wtr.write_record([
+            r.name.clone(),
+            r.category.clone(),
+            r.recipe_name.clone(),
+            format!("{}", r.units_sold),
+            format!("{:.2}", r.mix_pct),
+            format!("{:.2}", r.popularity_index),
+            opt(r.menu_price),
+            opt(r.plate_cost),
+            opt(r.contribution_margin),
+            format!("{:.2}", r.revenue),
+            opt(r.total_cost),
+            opt(r.total_margin),
+            r.classification.clone(),
+        ])
+        .map_err(|e| e.to_string())?;
+    }
+    wtr.flush().map_err(|e| e.to_string())?;
+
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported {} menu items to {}",
+            report.rows.len(),
+            output_path
+        ),
+    })
+}
+
+#[tauri::command]
+fn export_menu_engineering_pdf(
+    app: tauri::AppHandle,
+    date_from: String,
+    date_to: String,
+    category: String,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = sales::menu_engineering(&conn, date_from.trim(), date_to.trim(), category.trim())?;
+    let opt = |v: Option<f64>| v.map(fmt_money).unwrap_or_else(|| "-".to_string());
+
+    let (doc, page1, layer1) =
+        PdfDocument::new("4chef Menu Engineering", Mm(210.0), Mm(297.0), "Layer 1");
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let font_bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+
+    let mut current_layer = doc.get_page(page1).get_layer(layer1);
+    let line_height = 5.0f32;
+    let margin_left = 15.0f32;
+    let cols = [0.0f32, 62.0, 80.0, 96.0, 114.0, 132.0, 152.0];
+
     add_logo_to_pdf_layer(&current_layer);
 
-    // Title (shifted down to leave room for logo)
     let mut y = 270.0f32;
-    let title = match group_by.as_str() {
-        "food_category" => "4chef — Inventory Report (by Food Category)",
-        "storage" => "4chef — Inventory Report (by Storage Type)",
-        "vendor" => "4chef — Inventory Report (by Vendor)",
-        _ => "4chef — Inventory Report",
-    };
-    current_layer.use_text(title, 16.0, Mm(margin_left), Mm(y), &font_bold);
-    y -= 10.0;
+    current_layer.use_text(
+        "4chef — Menu Engineering",
+        16.0,
+        Mm(margin_left),
+        Mm(y),
+        &font_bold,
+    );
+    y -= 6.0;
+    current_layer.use_text(
+        menu_engineering_period_label(&report),
+        9.0,
+        Mm(margin_left),
+        Mm(y),
+        &font,
+    );
+    y -= 5.0;
+    let summary = format!(
+        "Units {} | Revenue {} | Food cost {} | Avg CM {} | Popularity threshold {:.1}%",
+        report.total_units,
+        fmt_money(report.total_revenue),
+        report
+            .food_cost_pct
+            .map(|p| format!("{:.1}%", p))
+            .unwrap_or_else(|| "-".to_string()),
+        opt(report.avg_contribution_margin),
+        report.popularity_threshold_pct
+    );
+    current_layer.use_text(summary, 8.0, Mm(margin_left), Mm(y), &font);
+    y -= 8.0;
 
-    // Column headers
-    current_layer.use_text("ID", 9.0, Mm(margin_left), Mm(y), &font_bold);
-    current_layer.use_text("Name", 9.0, Mm(margin_left + 20.0), Mm(y), &font_bold);
-    current_layer.use_text("Category", 9.0, Mm(margin_left + 110.0), Mm(y), &font_bold);
-    current_layer.use_text("Storage", 9.0, Mm(margin_left + 145.0), Mm(y), &font_bold);
+    let headers = ["Menu Item", "Sold", "Mix %", "Price", "Cost", "CM", "Class"];
+    for (x, h) in cols.iter().zip(headers.iter()) {
+        current_layer.use_text(*h, 9.0, Mm(margin_left + x), Mm(y), &font_bold);
+    }
     y -= 3.0;
-
-    // Draw a line
-    let line_pts = vec![
-        (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
-        (printpdf::Point::new(Mm(195.0), Mm(y)), false),
-    ];
     current_layer.add_line(printpdf::Line {
-        points: line_pts,
+        points: vec![
+            (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
+            (printpdf::Point::new(Mm(195.0), Mm(y)), false),
+        ],
         is_closed: false,
     });
     y -= line_height;
 
-    let mut last_group = String::new();
-    let is_grouped = group_by == "food_category" || group_by == "storage" || group_by == "vendor";
-
-    for (item_id, name, _status, food_cat, storage_t, vendor_name) in &items {
-        let current_group = match group_by.as_str() {
-            "food_category" => if food_cat.is_empty() { "Uncategorized".to_string() } else { food_cat.clone() },
-            "storage" => if storage_t.is_empty() { "Uncategorized".to_string() } else { storage_t.clone() },
-            "vendor" => if vendor_name.is_empty() { "No Vendor".to_string() } else { vendor_name.clone() },
-            _ => String::new(),
-        };
-
-        // Section header for grouped views
-        if is_grouped && current_group != last_group {
-            if y < 25.0 {
-                let (new_page, new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                current_layer = doc.get_page(new_page).get_layer(new_layer);
-                y = 285.0;
-            }
-            y -= 3.0;
-            current_layer.use_text(&current_group, 11.0, Mm(margin_left), Mm(y), &font_bold);
-            y -= 2.0;
-            let sep_pts = vec![
-                (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
-                (printpdf::Point::new(Mm(195.0), Mm(y)), false),
-            ];
-            current_layer.add_line(printpdf::Line {
-                points: sep_pts,
-                is_closed: false,
-            });
-            y -= line_height;
-            last_group = current_group;
+    for r in &report.rows {
+        if y < 15.0 {
+            let (new_page, new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
+            current_layer = doc.get_page(new_page).get_layer(new_layer);
+            y = 285.0;
+        }
+        let name: String = r.name.chars().take(34).collect();
+        let values = [
+            name,
+            format!("{}", r.units_sold),
+            format!("{:.1}", r.mix_pct),
+            opt(r.menu_price),
+            opt(r.plate_cost),
+            opt(r.contribution_margin),
+            r.classification.clone(),
+        ];
+        for (x, v) in cols.iter().zip(values.iter()) {
+            current_layer.use_text(v.as_str(), 8.0, Mm(margin_left + x), Mm(y), &font);
         }
+        y -= line_height;
+    }
 
+    if report.unmapped_items > 0 {
+        y -= 3.0;
         if y < 15.0 {
             let (new_page, new_layer) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
             current_layer = doc.get_page(new_page).get_layer(new_layer);
             y = 285.0;
         }
-        current_layer.use_text(&item_id.to_string(), 8.0, Mm(margin_left), Mm(y), &font);
-        let display_name = if name.len() > 45 { &name[..45] } else { name.as_str() };
-        current_layer.use_text(display_name, 8.0, Mm(margin_left + 20.0), Mm(y), &font);
-        let fc_display = if food_cat.is_empty() { "-" } else { food_cat.as_str() };
-        current_layer.use_text(fc_display, 7.0, Mm(margin_left + 110.0), Mm(y), &font);
-        let st_display = if storage_t.is_empty() { "-" } else { storage_t.as_str() };
-        current_layer.use_text(st_display, 7.0, Mm(margin_left + 145.0), Mm(y), &font);
-        y -= line_height;
+        current_layer.use_text(
+            format!(
+                "{} POS items ({} units) are not mapped to recipes and are excluded.",
+                report.unmapped_items, report.unmapped_units
+            ),
+            8.0,
+            Mm(margin_left),
+            Mm(y),
+            &font,
+        );
     }
 
     let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
@@ -3646,1144 +7179,3208 @@ fn export_inventory_pdf(
 
     Ok(PatchResponse {
         ok: true,
-        message: format!("Exported {} items to {}", items.len(), output_path),
+        message: format!(
+            "Exported {} menu items to {}",
+            report.rows.len(),
+            output_path
+        ),
+    })
+}
+
+// ── Inventory counts and usage variance ──
+
+/// Records an on-hand count for an item. Counts are stored in the unit they
+/// were taken in and converted when usage is calculated.
+#[tauri::command]
+fn record_inventory_count(
+    app: tauri::AppHandle,
+    item_id: i64,
+    count_date: Option<String>,
+    qty: f64,
+    unit_id: Option<i64>,
+) -> Result<PatchResponse, String> {
+    if qty < 0.0 {
+        return Err("Count cannot be negative".to_string());
+    }
+    let count_date = match count_date.filter(|d| !d.trim().is_empty()) {
+        Some(d) => {
+            Some(sales::normalize_date(&d).ok_or_else(|| format!("Invalid count date '{}'", d))?)
+        }
+        None => None,
+    };
+    let conn = open_initialized_db(&app)?;
+    let unit_id = unit_id.or_else(|| costing::default_purch_unit(&conn, item_id));
+    conn.execute(
+        "INSERT INTO inv_counts (item_id, count_date, qty, unit_id)
+         VALUES (?1, COALESCE(?2, date('now', 'localtime')), ?3, ?4)",
+        rusqlite::params![item_id, count_date, qty, unit_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Count recorded".to_string(),
+    })
+}
+
+#[tauri::command]
+fn delete_inventory_count(app: tauri::AppHandle, count_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let frozen: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM inv_counts c JOIN count_sessions s ON s.session_id = c.session_id
+                            WHERE c.count_id = ?1 AND s.status = 'closed')",
+            [count_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if frozen {
+        return Err("This count belongs to a closed count session".to_string());
+    }
+    conn.execute("DELETE FROM inv_counts WHERE count_id = ?1", [count_id])
+        .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Count deleted".to_string(),
+    })
+}
+
+#[derive(Serialize)]
+struct VarianceLineResponse {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    unit_name: String,
+    theoretical_qty: f64,
+    purchased_qty: f64,
+    begin_qty: Option<f64>,
+    end_qty: Option<f64>,
+    actual_qty: f64,
+    variance_qty: f64,
+    price: Option<f64>,
+    theoretical_cost: Option<f64>,
+    actual_cost: Option<f64>,
+    variance_cost: Option<f64>,
+    variance_pct: Option<f64>,
+    basis: String,
+    usage_from: String,
+    usage_to: String,
+}
+
+#[derive(Serialize)]
+struct CategoryVarianceResponse {
+    category: String,
+    theoretical_cost: f64,
+    actual_cost: f64,
+    variance_cost: f64,
+}
+
+#[derive(Serialize)]
+struct VarianceReportResponse {
+    date_from: String,
+    date_to: String,
+    net_sales: f64,
+    theoretical_cost: f64,
+    actual_cost: f64,
+    theoretical_pct: Option<f64>,
+    actual_pct: Option<f64>,
+    lines: Vec<VarianceLineResponse>,
+    categories: Vec<CategoryVarianceResponse>,
+    unresolved: Vec<String>,
+}
+
+#[tauri::command]
+fn get_variance_report(
+    app: tauri::AppHandle,
+    date_from: String,
+    date_to: String,
+) -> Result<VarianceReportResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let date_from = sales::normalize_date(&date_from).unwrap_or_default();
+    let date_to = sales::normalize_date(&date_to).unwrap_or_default();
+    let report = variance::variance_report(&conn, &date_from, &date_to)?;
+    Ok(VarianceReportResponse {
+        date_from: report.date_from,
+        date_to: report.date_to,
+        net_sales: report.net_sales,
+        theoretical_cost: report.theoretical_cost,
+        actual_cost: report.actual_cost,
+        theoretical_pct: report.theoretical_pct,
+        actual_pct: report.actual_pct,
+        lines: report
+            .lines
+            .into_iter()
+            .map(|l| VarianceLineResponse {
+                item_id: l.item_id,
+                item_name: l.item_name,
+                category: l.category,
+                unit_name: l.unit_name,
+                theoretical_qty: l.theoretical_qty,
+                purchased_qty: l.purchased_qty,
+                begin_qty: l.begin_qty,
+                end_qty: l.end_qty,
+                actual_qty: l.actual_qty,
+                variance_qty: l.variance_qty,
+                price: l.price,
+                theoretical_cost: l.theoretical_cost,
+                actual_cost: l.actual_cost,
+                variance_cost: l.variance_cost,
+                variance_pct: l.variance_pct,
+                basis: l.basis,
+                usage_from: l.usage_from,
+                usage_to: l.usage_to,
+            })
+            .collect(),
+        categories: report
+            .categories
+            .into_iter()
+            .map(|c| CategoryVarianceResponse {
+                category: c.category,
+                theoretical_cost: c.theoretical_cost,
+                actual_cost: c.actual_cost,
+                variance_cost: c.variance_cost,
+            })
+            .collect(),
+        unresolved: report.unresolved,
+    })
+}
+
+// ── Count sessions and inventory valuation ──
+
+#[derive(Serialize)]
+struct CountSessionResponse {
+    session_id: i64,
+    name: String,
+    count_date: String,
+    status: String,
+    notes: String,
+    created_at: String,
+    closed_at: String,
+    item_count: i64,
+    total_value: Option<f64>,
+}
+
+impl From<counts::CountSession> for CountSessionResponse {
+    fn from(s: counts::CountSession) -> Self {
+        CountSessionResponse {
+            session_id: s.session_id,
+            name: s.name,
+            count_date: s.count_date,
+            status: s.status,
+            notes: s.notes,
+            created_at: s.created_at,
+            closed_at: s.closed_at,
+            item_count: s.item_count,
+            total_value: s.total_value,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CountUnitOption {
+    unit_id: i64,
+    unit_name: String,
+}
+
+#[derive(Serialize)]
+struct CountSheetLineResponse {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    storage_type: String,
+    shelf_order: Option<i64>,
+    purch_unit_id: Option<i64>,
+    purch_unit_name: String,
+    units: Vec<CountUnitOption>,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    purch_qty: Option<f64>,
+    unit_price: Option<f64>,
+    value: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct CountSheetResponse {
+    session: CountSessionResponse,
+    lines: Vec<CountSheetLineResponse>,
+}
+
+#[derive(Serialize)]
+struct ValuationLineResponse {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    storage_type: String,
+    unit_name: String,
+    qty: Option<f64>,
+    unit_price: Option<f64>,
+    value: Option<f64>,
+    prior_qty: Option<f64>,
+    prior_value: Option<f64>,
+    change_value: f64,
+}
+
+#[derive(Serialize)]
+struct ValuationGroupResponse {
+    name: String,
+    value: f64,
+    prior_value: f64,
+}
+
+#[derive(Serialize)]
+struct ValuationReportResponse {
+    session: CountSessionResponse,
+    prior: Option<CountSessionResponse>,
+    lines: Vec<ValuationLineResponse>,
+    storage_groups: Vec<ValuationGroupResponse>,
+    categories: Vec<ValuationGroupResponse>,
+    total_value: f64,
+    prior_total: f64,
+    unpriced: Vec<String>,
+}
+
+#[tauri::command]
+fn list_count_sessions(app: tauri::AppHandle) -> Result<Vec<CountSessionResponse>, String> {
+    let conn = open_initialized_db(&app)?;
+    Ok(counts::list_sessions(&conn)?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+#[tauri::command]
+fn create_count_session(
+    app: tauri::AppHandle,
+    name: String,
+    count_date: Option<String>,
+    notes: Option<String>,
+) -> Result<PatchResponse, String> {
+    let count_date = match count_date.filter(|d| !d.trim().is_empty()) {
+        Some(d) => {
+            Some(sales::normalize_date(&d).ok_or_else(|| format!("Invalid count date '{}'", d))?)
+        }
+        None => None,
+    };
+    let conn = open_initialized_db(&app)?;
+    let name = name.trim();
+    conn.execute(
+        "INSERT INTO count_sessions (name, count_date, status, notes, created_at)
+         VALUES (?1, COALESCE(?2, date('now', 'localtime')), 'open', ?3, datetime('now', 'localtime'))",
+        rusqlite::params![name, count_date, notes.filter(|n| !n.trim().is_empty())],
+    )
+    .map_err(|e| e.to_string())?;
+    let session_id = conn.last_insert_rowid();
+    if name.is_empty() {
+        conn.execute(
+            "UPDATE count_sessions SET name = 'Count ' || count_date WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Count session {} started", session_id),
+    })
+}
+
+#[tauri::command]
+fn get_count_sheet(app: tauri::AppHandle, session_id: i64) -> Result<CountSheetResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let (session, lines) = counts::count_sheet(&conn, session_id)?;
+    Ok(CountSheetResponse {
+        session: session.into(),
+        lines: lines
+            .into_iter()
+            .map(|l| CountSheetLineResponse {
+                item_id: l.item_id,
+                item_name: l.item_name,
+                category: l.category,
+                storage_type: l.storage_type,
+                shelf_order: l.shelf_order,
+                purch_unit_id: l.purch_unit_id,
+                purch_unit_name: l.purch_unit_name,
+                units: l
+                    .units
+                    .into_iter()
+                    .map(|(unit_id, unit_name)| CountUnitOption { unit_id, unit_name })
+                    .collect(),
+                qty: l.qty,
+                unit_id: l.unit_id,
+                unit_name: l.unit_name,
+                purch_qty: l.purch_qty,
+                unit_price: l.unit_price,
+                value: l.value,
+            })
+            .collect(),
+    })
+}
+
+#[tauri::command]
+fn save_count_line(
+    app: tauri::AppHandle,
+    session_id: i64,
+    item_id: i64,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| {
+        counts::save_count_line(tx, session_id, item_id, qty, unit_id)
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: if qty.is_some() {
+            "Count saved".to_string()
+        } else {
+            "Count cleared".to_string()
+        },
+    })
+}
+
+#[tauri::command]
+fn set_item_shelf_order(
+    app: tauri::AppHandle,
+    item_id: i64,
+    shelf_order: Option<i64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let updated = conn
+        .execute(
+            "UPDATE items SET shelf_order = ?2 WHERE item_id = ?1",
+            rusqlite::params![item_id, shelf_order],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(PatchResponse {
+        ok: true,
+        message: "Shelf order updated".to_string(),
+    })
+}
+
+#[tauri::command]
+fn close_count_session(app: tauri::AppHandle, session_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let total = with_tx(&mut conn, |tx| counts::close_session(tx, session_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Session closed at {}", fmt_money(total)),
+    })
+}
+
+#[tauri::command]
+fn delete_count_session(app: tauri::AppHandle, session_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| {
+        let session = counts::load_session(tx, session_id)?;
+        if session.status == "closed" {
+            return Err("Closed sessions are kept for period comparisons".to_string());
+        }
+        tx.execute("DELETE FROM inv_counts WHERE session_id = ?1", [session_id])
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM count_sessions WHERE session_id = ?1",
+            [session_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Count session deleted".to_string(),
+    })
+}
+
+#[tauri::command]
+fn get_inventory_valuation(
+    app: tauri::AppHandle,
+    session_id: i64,
+    compare_session_id: Option<i64>,
+) -> Result<ValuationReportResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = counts::valuation_report(&conn, session_id, compare_session_id)?;
+    let group = |g: counts::ValuationGroup| ValuationGroupResponse {
+        name: g.name,
+        value: g.value,
+        prior_value: g.prior_value,
+    };
+    Ok(ValuationReportResponse {
+        session: report.session.into(),
+        prior: report.prior.map(Into::into),
+        lines: report
+            .lines
+            .into_iter()
+            .map(|l| ValuationLineResponse {
+                item_id: l.item_id,
+                item_name: l.item_name,
+                category: l.category,
+                storage_type: l.storage_type,
+                unit_name: l.unit_name,
+                qty: l.qty,
+                unit_price: l.unit_price,
+                value: l.value,
+                prior_qty: l.prior_qty,
+                prior_value: l.prior_value,
+                change_value: l.change_value,
+            })
+            .collect(),
+        storage_groups: report.storage_groups.into_iter().map(group).collect(),
+        categories: report.categories.into_iter().map(group).collect(),
+        total_value: report.total_value,
+        prior_total: report.prior_total,
+        unpriced: report.unpriced,
+    })
+}
+
+fn session_label(session: &counts::CountSession) -> String {
+    format!(
+        "{} — {} ({})",
+        session.name, session.count_date, session.status
+    )
+}
+
+#[tauri::command]
+fn export_count_sheet_pdf(
+    app: tauri::AppHandle,
+    session_id: i64,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let (session, lines) = counts::count_sheet(&conn, session_id)?;
+    let columns = [
+        PdfColumn {
+            header: "ID",
+            x: 0.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Name",
+            x: 15.0,
+            size: 8.0,
+            max_chars: Some(40),
+        },
+        PdfColumn {
+            header: "Shelf",
+            x: 95.0,
+            size: 7.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Unit",
+            x: 110.0,
+            size: 7.0,
+            max_chars: Some(14),
+        },
+        PdfColumn {
+            header: "Count",
+            x: 135.0,
+            size: 8.0,
+            max_chars: Some(22),
+        },
+        PdfColumn {
+            header: "Value",
+            x: 165.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let rows: Vec<PdfRow> = lines
+        .iter()
+        .map(|l| PdfRow {
+            group: if l.storage_type.is_empty() {
+                "Unassigned".to_string()
+            } else {
+                l.storage_type.clone()
+            },
+            cells: vec![
+                l.item_id.to_string(),
+                l.item_name.clone(),
+                l.shelf_order
+                    .map(|o| o.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                l.purch_unit_name.clone(),
+                match l.qty {
+                    Some(q) => format!("{:.2} {}", q, l.unit_name),
+                    None => "__________".to_string(),
+                },
+                l.value.map(fmt_money).unwrap_or_default(),
+            ],
+            bold: false,
+        })
+        .collect();
+    write_table_pdf(
+        &output_path,
+        "4chef Count Sheet",
+        "4chef — Count Sheet",
+        Some(&session_label(&session)),
+        &columns,
+        &rows,
+        true,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} items to {}", rows.len(), output_path),
     })
 }
 
 #[tauri::command]
-fn export_recipe_pdf(
+fn export_inventory_valuation_pdf(
     app: tauri::AppHandle,
-    recipe_id: i64,
+    session_id: i64,
+    compare_session_id: Option<i64>,
     output_path: String,
 ) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
+    let report = counts::valuation_report(&conn, session_id, compare_session_id)?;
+    let columns = [
+        PdfColumn {
+            header: "Item",
+            x: 0.0,
+            size: 8.0,
+            max_chars: Some(38),
+        },
+        PdfColumn {
+            header: "Qty",
+            x: 72.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Unit",
+            x: 88.0,
+            size: 7.0,
+            max_chars: Some(12),
+        },
+        PdfColumn {
+            header: "Price",
+            x: 108.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Value",
+            x: 128.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Prior",
+            x: 148.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Change",
+            x: 165.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let money = |v: Option<f64>| v.map(fmt_money).unwrap_or_else(|| "-".to_string());
+    let total_row = |group: &str, label: &str, value: f64, prior: f64| PdfRow {
+        group: group.to_string(),
+        cells: vec![
+            label.to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            fmt_money(value),
+            fmt_money(prior),
+            fmt_money(value - prior),
+        ],
+        bold: true,
+    };
+    let mut rows = Vec::new();
+    for group in &report.storage_groups {
+        for l in report.lines.iter().filter(|l| {
+            l.storage_type == group.name
+                || (l.storage_type.is_empty() && group.name == "Unassigned")
+        }) {
+            rows.push(PdfRow {
+                group: group.name.clone(),
+                cells: vec![
+                    l.item_name.clone(),
+                    l.qty
+                        .map(|q| format!("{:.2}", q))
+                        .unwrap_or_else(|| "-".to_string()),
+                    l.unit_name.clone(),
+                    money(l.unit_price),
+                    money(l.value),
+                    money(l.prior_value),
+                    fmt_money(l.change_value),
+                ],
+                bold: false,
+            });
+        }
+        rows.push(total_row(
+            &group.name,
+            "Subtotal",
+            group.value,
+            group.prior_value,
+        ));
+    }
+    let last_group = rows.last().map(|r| r.group.clone()).unwrap_or_default();
+    rows.push(total_row(
+        &last_group,
+        "Total",
+        report.total_value,
+        report.prior_total,
+    ));
 
-    let (name, instructions): (String, String) = conn
-        .query_row(
-            "SELECT name, COALESCE(instructions, '') FROM recipes WHERE recipe_id = ?1",
-            [recipe_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|e| e.to_string())?;
+    let subtitle = match &report.prior {
+        Some(prior) => format!(
+            "{}   vs   {}",
+            session_label(&report.session),
+            session_label(prior)
+        ),
+        None => session_label(&report.session),
+    };
+    write_table_pdf(
+        &output_path,
+        "4chef Inventory Valuation",
+        "4chef — Inventory Valuation",
+        Some(&subtitle),
+        &columns,
+        &rows,
+        true,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} items to {}", report.lines.len(), output_path),
+    })
+}
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT ri.item_id, COALESCE(i.name, '?'), ri.qty, COALESCE(u.sing, '?')
-             FROM recp_items ri
-             LEFT JOIN items i ON i.item_id = ri.item_id
-             LEFT JOIN units u ON u.unit_id = ri.unit_id
-             WHERE ri.recipe_id = ?1
-             ORDER BY ri.recp_item_id",
-        )
-        .map_err(|e| e.to_string())?;
-    let ingredients: Vec<(i64, String, Option<f64>, String)> = stmt
-        .query_map([recipe_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+// ── Par levels and order guides ──
 
-    let (doc, page1, layer1) =
-        PdfDocument::new(&format!("Recipe — {}", name), Mm(210.0), Mm(297.0), "Layer 1");
-    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
-    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct OrderGuideLineResponse {
+    item_id: i64,
+    item_name: String,
+    storage_type: String,
+    purch_unit_name: String,
+    par_qty: f64,
+    on_hand: Option<f64>,
+    on_hand_basis: String,
+    need_qty: f64,
+    order_unit_id: i64,
+    order_unit_name: String,
+    order_qty: f64,
+    price: Option<f64>,
+    est_cost: Option<f64>,
+}
 
-    let mut current_layer = doc.get_page(page1).get_layer(layer1);
-    let margin_left = 15.0f32;
+#[derive(Serialize)]
+struct VendorOrderResponse {
+    vendor_id: Option<i64>,
+    vendor_name: String,
+    lines: Vec<OrderGuideLineResponse>,
+    total_cost: f64,
+}
 
-    // Logo
-    add_logo_to_pdf_layer(&current_layer);
+#[derive(Serialize)]
+struct OrderGuideResponse {
+    order_date: String,
+    day_name: String,
+    theoretical: bool,
+    vendors: Vec<VendorOrderResponse>,
+    unresolved: Vec<String>,
+}
 
-    // Title (shifted down to leave room for logo)
-    let mut y = 270.0f32;
-    current_layer.use_text(&name, 18.0, Mm(margin_left), Mm(y), &font_bold);
-    y -= 10.0;
+#[tauri::command]
+fn save_par_level(
+    app: tauri::AppHandle,
+    item_id: i64,
+    storage_type: Option<String>,
+    day_of_week: Option<i64>,
+    par_qty: f64,
+    unit_id: Option<i64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let storage_type = storage_type.unwrap_or_default();
+    order_guide::save_par_level(
+        &conn,
+        item_id,
+        storage_type.trim(),
+        day_of_week,
+        par_qty,
+        unit_id,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Par level saved".to_string(),
+    })
+}
 
-    // Instructions
-    if !instructions.trim().is_empty() {
-        current_layer.use_text("Instructions", 11.0, Mm(margin_left), Mm(y), &font_bold);
-        y -= 6.0;
-        for line in wrap_text(&instructions, 90) {
-            if y < 15.0 {
-                let (np, nl) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                current_layer = doc.get_page(np).get_layer(nl);
-                y = 285.0;
-            }
-            current_layer.use_text(&line, 9.0, Mm(margin_left), Mm(y), &font);
-            y -= 5.0;
+#[tauri::command]
+fn delete_par_level(app: tauri::AppHandle, par_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    conn.execute("DELETE FROM par_levels WHERE par_id = ?1", [par_id])
+        .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Par level deleted".to_string(),
+    })
+}
+
+/// Runs the order guide for a date (today when blank).
+fn load_order_guide(
+    conn: &rusqlite::Connection,
+    order_date: Option<String>,
+    theoretical: bool,
+    vendor_id: Option<i64>,
+) -> Result<order_guide::OrderGuide, String> {
+    let order_date = match order_date.filter(|d| !d.trim().is_empty()) {
+        Some(d) => {
+            sales::normalize_date(&d).ok_or_else(|| format!("Invalid order date '{}'", d))?
         }
-        y -= 4.0;
-    }
+        None => conn
+            .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?,
+    };
+    order_guide::order_guide(conn, &order_date, theoretical, vendor_id)
+}
 
-    // Ingredients header
-    current_layer.use_text("Ingredients", 11.0, Mm(margin_left), Mm(y), &font_bold);
-    y -= 7.0;
-    current_layer.use_text("Item", 9.0, Mm(margin_left), Mm(y), &font_bold);
-    current_layer.use_text("Qty", 9.0, Mm(margin_left + 100.0), Mm(y), &font_bold);
-    current_layer.use_text("Unit", 9.0, Mm(margin_left + 130.0), Mm(y), &font_bold);
-    y -= 3.0;
-    let line_pts = vec![
-        (printpdf::Point::new(Mm(margin_left), Mm(y)), false),
-        (printpdf::Point::new(Mm(195.0), Mm(y)), false),
-    ];
-    current_layer.add_line(printpdf::Line {
-        points: line_pts,
-        is_closed: false,
-    });
-    y -= 5.0;
+#[tauri::command]
+fn get_order_guide(
+    app: tauri::AppHandle,
+    order_date: Option<String>,
+    theoretical: bool,
+    vendor_id: Option<i64>,
+) -> Result<OrderGuideResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let guide = load_order_guide(&conn, order_date, theoretical, vendor_id)?;
+    Ok(OrderGuideResponse {
+        order_date: guide.order_date,
+        day_name: guide.day_name,
+        theoretical: guide.theoretical,
+        vendors: guide
+            .vendors
+            .into_iter()
+            .map(|v| VendorOrderResponse {
+                vendor_id: v.vendor_id,
+                vendor_name: v.vendor_name,
+                total_cost: v.total_cost,
+                lines: v
+                    .lines
+                    .into_iter()
+                    .map(|l| OrderGuideLineResponse {
+                        item_id: l.item_id,
+                        item_name: l.item_name,
+                        storage_type: l.storage_type,
+                        purch_unit_name: l.purch_unit_name,
+                        par_qty: l.par_qty,
+                        on_hand: l.on_hand,
+                        on_hand_basis: l.on_hand_basis,
+                        need_qty: l.need_qty,
+                        order_unit_id: l.order_unit_id,
+                        order_unit_name: l.order_unit_name,
+                        order_qty: l.order_qty,
+                        price: l.price,
+                        est_cost: l.est_cost,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        unresolved: guide.unresolved,
+    })
+}
 
-    for (_, item_name, qty, unit) in &ingredients {
-        if y < 15.0 {
-            let (np, nl) = doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-            current_layer = doc.get_page(np).get_layer(nl);
-            y = 285.0;
+#[tauri::command]
+fn export_order_guide_csv(
+    app: tauri::AppHandle,
+    order_date: Option<String>,
+    theoretical: bool,
+    vendor_id: Option<i64>,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let guide = load_order_guide(&conn, order_date, theoretical, vendor_id)?;
+    let opt = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "Vendor",
+        "ItemId",
+        "Item",
+        "Storage",
+        "PurchaseUnit",
+        "Par",
+        "OnHand",
+        "OnHandBasis",
+        "Need",
+        "OrderQty",
+        "OrderUnit",
+        "Price",
+        "EstCost",
+    ])
+    .map_err(|e| e.to_string())?;
+    let mut count = 0;
+    for vendor in &guide.vendors {
+        for l in &vendor.lines {
+            wtr.write_record([
+                vendor.vendor_name.clone(),
+                l.item_id.to_string(),
+                l.item_name.clone(),
+                l.storage_type.clone(),
+                l.purch_unit_name.clone(),
+                format!("{:.2}", l.par_qty),
+                opt(l.on_hand),
+                l.on_hand_basis.clone(),
+                format!("{:.2}", l.need_qty),
+                format!("{}", l.order_qty),
+                l.order_unit_name.clone(),
+                opt(l.price),
+                opt(l.est_cost),
+            ])
+            .map_err(|e| e.to_string())?;
+            count += 1;
         }
-        let display_name = if item_name.len() > 50 { &item_name[..50] } else { item_name.as_str() };
-        current_layer.use_text(display_name, 8.0, Mm(margin_left), Mm(y), &font);
-        let qty_str = qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string());
-        current_layer.use_text(&qty_str, 8.0, Mm(margin_left + 100.0), Mm(y), &font);
-        current_layer.use_text(unit, 8.0, Mm(margin_left + 130.0), Mm(y), &font);
-        y -= 5.0;
     }
-
-    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())?;
+    wtr.flush().map_err(|e| e.to_string())?;
 
     Ok(PatchResponse {
         ok: true,
-        message: format!("Exported recipe '{}' to {}", name, output_path),
+        message: format!("Exported {} order lines to {}", count, output_path),
     })
 }
 
 #[tauri::command]
-fn export_inventory_docx(
+fn export_order_guide_pdf(
     app: tauri::AppHandle,
+    order_date: Option<String>,
+    theoretical: bool,
+    vendor_id: Option<i64>,
     output_path: String,
-    group_by: Option<String>,
 ) -> Result<PatchResponse, String> {
-    use docx_rs::*;
-
     let conn = open_initialized_db(&app)?;
-    let group_by = group_by.unwrap_or_default();
-
-    let use_vendor_join = group_by == "vendor";
-    let order_clause = match group_by.as_str() {
-        "food_category" => "COALESCE(i.food_category, '') COLLATE NOCASE, i.name COLLATE NOCASE",
-        "storage" => "COALESCE(i.storage_type, '') COLLATE NOCASE, i.name COLLATE NOCASE",
-        "vendor" => "COALESCE(v.name, 'zzz') COLLATE NOCASE, i.name COLLATE NOCASE",
-        _ => "i.name COLLATE NOCASE",
-    };
-
-    let from_sql = if use_vendor_join {
-        "items i LEFT JOIN inv_prices ip ON i.item_id = ip.item_id AND ip.status = 1 LEFT JOIN vendors v ON ip.vendor_id = v.vendor_id"
-    } else {
-        "items i"
-    };
-
-    let select_cols = if use_vendor_join {
-        "DISTINCT i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), COALESCE(v.name, '')"
-    } else {
-        "i.item_id, i.name, COALESCE(i.status, 0), COALESCE(i.food_category, ''), COALESCE(i.storage_type, ''), ''"
-    };
-
-    let mut stmt = conn
-        .prepare(&format!(
-            "SELECT {} FROM {} ORDER BY {}",
-            select_cols, from_sql, order_clause
-        ))
-        .map_err(|e| e.to_string())?;
-    let items: Vec<(i64, String, i64, String, String, String)> = stmt
-        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3).unwrap_or_default(), row.get(4).unwrap_or_default(), row.get(5).unwrap_or_default())))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    let mut docx = Docx::new();
-
-    // Logo (~20 mm ≈ 720 000 EMU)
-    {
-        let pic = Pic::new(LOGO_PNG).size(720_000, 720_000);
-        docx = docx.add_paragraph(
-            Paragraph::new().add_run(Run::new().add_image(pic)),
-        );
-    }
-
-    // Title
-    let title = match group_by.as_str() {
-        "food_category" => "4chef — Inventory Report (by Food Category)",
-        "storage" => "4chef — Inventory Report (by Storage Type)",
-        "vendor" => "4chef — Inventory Report (by Vendor)",
-        _ => "4chef — Inventory Report",
-    };
-    docx = docx.add_paragraph(
-        Paragraph::new()
-            .add_run(Run::new().add_text(title).bold().size(32)),
-    );
-    docx = docx.add_paragraph(Paragraph::new()); // spacer
-
-    let is_grouped = group_by == "food_category" || group_by == "storage" || group_by == "vendor";
-    let mut last_group = String::new();
-
-    // Build table
-    let header_row = TableRow::new(vec![
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("ID").bold())),
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Name").bold())),
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Category").bold())),
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Storage").bold())),
-    ]);
-    let mut rows = vec![header_row];
-    for (item_id, name, _status, food_cat, storage_t, vendor_name) in &items {
-        let current_group = match group_by.as_str() {
-            "food_category" => if food_cat.is_empty() { "Uncategorized".to_string() } else { food_cat.clone() },
-            "storage" => if storage_t.is_empty() { "Uncategorized".to_string() } else { storage_t.clone() },
-            "vendor" => if vendor_name.is_empty() { "No Vendor".to_string() } else { vendor_name.clone() },
-            _ => String::new(),
-        };
-
-        if is_grouped && current_group != last_group {
-            // Section header row spanning all columns
-            rows.push(TableRow::new(vec![
-                TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&current_group).bold().size(22))),
-                TableCell::new().add_paragraph(Paragraph::new()),
-                TableCell::new().add_paragraph(Paragraph::new()),
-                TableCell::new().add_paragraph(Paragraph::new()),
-            ]));
-            last_group = current_group;
+    let guide = load_order_guide(&conn, order_date, theoretical, vendor_id)?;
+    let columns = [
+        PdfColumn {
+            header: "Item",
+            x: 0.0,
+            size: 8.0,
+            max_chars: Some(36),
+        },
+        PdfColumn {
+            header: "Par",
+            x: 68.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "On Hand",
+            x: 84.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Need",
+            x: 102.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Order",
+            x: 118.0,
+            size: 8.0,
+            max_chars: Some(18),
+        },
+        PdfColumn {
+            header: "Price",
+            x: 150.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Est.",
+            x: 168.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let money = |v: Option<f64>| v.map(fmt_money).unwrap_or_else(|| "-".to_string());
+    let mut rows = Vec::new();
+    for vendor in &guide.vendors {
+        for l in &vendor.lines {
+            rows.push(PdfRow {
+                group: vendor.vendor_name.clone(),
+                cells: vec![
+                    format!("{} ({})", l.item_name, l.purch_unit_name),
+                    format!("{:.2}", l.par_qty),
+                    l.on_hand
+                        .map(|q| format!("{:.2}", q))
+                        .unwrap_or_else(|| "-".to_string()),
+                    format!("{:.2}", l.need_qty),
+                    format!("{} {}", l.order_qty, l.order_unit_name),
+                    money(l.price),
+                    money(l.est_cost),
+                ],
+                bold: false,
+            });
         }
-
-        let fc_display = if food_cat.is_empty() { "-".to_string() } else { food_cat.clone() };
-        let st_display = if storage_t.is_empty() { "-".to_string() } else { storage_t.clone() };
-        rows.push(TableRow::new(vec![
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(item_id.to_string()))),
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(name))),
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&fc_display))),
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(&st_display))),
-        ]));
+        rows.push(PdfRow {
+            group: vendor.vendor_name.clone(),
+            cells: vec![
+                "Vendor total".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                fmt_money(vendor.total_cost),
+            ],
+            bold: true,
+        });
     }
-    docx = docx.add_table(Table::new(rows));
-
-    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-    docx.build().pack(file).map_err(|e| e.to_string())?;
-
+    let subtitle = format!(
+        "{} ({}) — on hand from {}",
+        guide.order_date,
+        guide.day_name,
+        if guide.theoretical {
+            "latest count adjusted by purchases and sales"
+        } else {
+            "latest count"
+        }
+    );
+    write_table_pdf(
+        &output_path,
+        "4chef Order Guide",
+        "4chef — Order Guide",
+        Some(&subtitle),
+        &columns,
+        &rows,
+        true,
+    )?;
     Ok(PatchResponse {
         ok: true,
-        message: format!("Exported {} items to {}", items.len(), output_path),
+        message: format!(
+            "Exported {} vendor order(s) to {}",
+            guide.vendors.len(),
+            output_path
+        ),
     })
 }
 
-#[tauri::command]
-fn export_recipe_docx(
-    app: tauri::AppHandle,
-    recipe_id: i64,
-    output_path: String,
-) -> Result<PatchResponse, String> {
-    use docx_rs::*;
-
-    let conn = open_initialized_db(&app)?;
-
-    let (name, instructions): (String, String) = conn
-        .query_row(
-            "SELECT name, COALESCE(instructions, '') FROM recipes WHERE recipe_id = ?1",
-            [recipe_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
-        )
-        .map_err(|e| e.to_string())?;
+// ── Purchase orders and receiving ──
 
-    let mut stmt = conn
-        .prepare(
-            "SELECT ri.item_id, COALESCE(i.name, '?'), ri.qty, COALESCE(u.sing, '?')
-             FROM recp_items ri
-             LEFT JOIN items i ON i.item_id = ri.item_id
-             LEFT JOIN units u ON u.unit_id = ri.unit_id
-             WHERE ri.recipe_id = ?1
-             ORDER BY ri.recp_item_id",
-        )
-        .map_err(|e| e.to_string())?;
-    let ingredients: Vec<(i64, String, Option<f64>, String)> = stmt
-        .query_map([recipe_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+#[derive(Serialize)]
+struct PurchaseOrderResponse {
+    po_id: i64,
+    po_number: String,
+    vendor_id: i64,
+    vendor_name: String,
+    status: String,
+    order_date: String,
+    expected_date: String,
+    notes: String,
+    line_count: i64,
+    total: f64,
+}
+
+impl From<purchase_orders::PurchaseOrder> for PurchaseOrderResponse {
+    fn from(o: purchase_orders::PurchaseOrder) -> Self {
+        PurchaseOrderResponse {
+            po_id: o.po_id,
+            po_number: o.po_number,
+            vendor_id: o.vendor_id,
+            vendor_name: o.vendor_name,
+            status: o.status,
+            order_date: o.order_date,
+            expected_date: o.expected_date,
+            notes: o.notes,
+            line_count: o.line_count,
+            total: o.total,
+        }
+    }
+}
 
-    let mut docx = Docx::new();
+#[derive(Serialize)]
+struct PoLineResponse {
+    po_line_id: i64,
+    item_id: i64,
+    item_name: String,
+    unit_id: Option<i64>,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    ext_cost: Option<f64>,
+    received_qty: f64,
+}
 
-    // Logo (~20 mm ≈ 720 000 EMU)
-    {
-        let pic = Pic::new(LOGO_PNG).size(720_000, 720_000);
-        docx = docx.add_paragraph(
-            Paragraph::new().add_run(Run::new().add_image(pic)),
-        );
-    }
+#[derive(Serialize)]
+struct ReceiptLineResponse {
+    po_line_id: Option<i64>,
+    item_name: String,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    flag: String,
+    note: String,
+}
 
-    // Title
-    docx = docx.add_paragraph(
-        Paragraph::new().add_run(Run::new().add_text(&name).bold().size(36)),
-    );
+#[derive(Serialize)]
+struct ReceiptResponse {
+    receipt_id: i64,
+    invoice_id: i64,
+    invoice_no: String,
+    received_date: String,
+    lines: Vec<ReceiptLineResponse>,
+}
 
-    // Instructions
-    if !instructions.trim().is_empty() {
-        docx = docx.add_paragraph(
-            Paragraph::new().add_run(Run::new().add_text("Instructions").bold().size(24)),
-        );
-        for line in instructions.lines() {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                docx = docx.add_paragraph(
-                    Paragraph::new().add_run(Run::new().add_text(trimmed)),
-                );
-            }
-        }
-        docx = docx.add_paragraph(Paragraph::new()); // spacer
-    }
+#[derive(Serialize)]
+struct PurchaseOrderDetailResponse {
+    order: PurchaseOrderResponse,
+    lines: Vec<PoLineResponse>,
+    receipts: Vec<ReceiptResponse>,
+}
 
-    // Ingredients table
-    docx = docx.add_paragraph(
-        Paragraph::new().add_run(Run::new().add_text("Ingredients").bold().size(24)),
-    );
-    let header_row = TableRow::new(vec![
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Item").bold())),
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Qty").bold())),
-        TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text("Unit").bold())),
-    ]);
-    let mut rows = vec![header_row];
-    for (_, item_name, qty, unit) in &ingredients {
-        let qty_str = qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string());
-        rows.push(TableRow::new(vec![
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(item_name))),
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(qty_str))),
-            TableCell::new().add_paragraph(Paragraph::new().add_run(Run::new().add_text(unit))),
-        ]));
-    }
-    docx = docx.add_table(Table::new(rows));
+#[derive(serde::Deserialize)]
+struct ReceiveLineInput {
+    po_line_id: Option<i64>,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: f64,
+    price: Option<f64>,
+}
 
-    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-    docx.build().pack(file).map_err(|e| e.to_string())?;
+#[tauri::command]
+fn list_purchase_orders(
+    app: tauri::AppHandle,
+    status: Option<String>,
+    vendor_id: Option<i64>,
+) -> Result<Vec<PurchaseOrderResponse>, String> {
+    let conn = open_initialized_db(&app)?;
+    let status = status.filter(|s| !s.trim().is_empty());
+    Ok(
+        purchase_orders::list_orders(&conn, status.as_deref(), vendor_id)?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    )
+}
 
+#[tauri::command]
+fn create_purchase_order(
+    app: tauri::AppHandle,
+    vendor_id: i64,
+    expected_date: Option<String>,
+    notes: Option<String>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let expected_date = expected_date.filter(|d| !d.trim().is_empty());
+    let notes = notes.filter(|n| !n.trim().is_empty());
+    let po_id = purchase_orders::create_order(
+        &conn,
+        vendor_id,
+        expected_date.as_deref(),
+        notes.as_deref(),
+    )?;
+    let order = purchase_orders::load_order(&conn, po_id)?;
     Ok(PatchResponse {
         ok: true,
-        message: format!("Exported recipe '{}' to {}", name, output_path),
+        message: format!("Created {}", order.po_number),
     })
 }
 
-// ──────────────────────────────────────────
-// FDA Guidelines PDF export
-// ──────────────────────────────────────────
-
 #[tauri::command]
-fn export_fda_pdf(output_path: String) -> Result<PatchResponse, String> {
-    let (doc, page1, layer1) =
-        PdfDocument::new("FDA Food Safety Guidelines", Mm(210.0), Mm(297.0), "Layer 1");
-    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
-    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+fn create_pos_from_order_guide(
+    app: tauri::AppHandle,
+    order_date: Option<String>,
+    theoretical: bool,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let guide = load_order_guide(&conn, order_date, theoretical, None)?;
+    let created = with_tx(&mut conn, |tx| {
+        purchase_orders::orders_from_guide(tx, &guide)
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: if created.is_empty() {
+            "Nothing to order from a known vendor".to_string()
+        } else {
+            format!("Created draft {}", created.join(", "))
+        },
+    })
+}
 
-    let mut current_layer = doc.get_page(page1).get_layer(layer1);
-    let margin_left = 15.0f32;
-    let line_height = 5.0f32;
+#[tauri::command]
+fn get_purchase_order(
+    app: tauri::AppHandle,
+    po_id: i64,
+) -> Result<PurchaseOrderDetailResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let order = purchase_orders::load_order(&conn, po_id)?;
+    let lines = purchase_orders::order_lines(&conn, po_id)?
+        .into_iter()
+        .map(|l| PoLineResponse {
+            po_line_id: l.po_line_id,
+            item_id: l.item_id,
+            item_name: l.item_name,
+            unit_id: l.unit_id,
+            unit_name: l.unit_name,
+            qty: l.qty,
+            price: l.price,
+            ext_cost: l.price.map(|p| p * l.qty),
+            received_qty: l.received_qty,
+        })
+        .collect();
+    let receipts = purchase_orders::order_receipts(&conn, po_id)?
+        .into_iter()
+        .map(|r| ReceiptResponse {
+            receipt_id: r.receipt_id,
+            invoice_id: r.invoice_id,
+            invoice_no: r.invoice_no,
+            received_date: r.received_date,
+            lines: r
+                .lines
+                .into_iter()
+                .map(|l| ReceiptLineResponse {
+                    po_line_id: l.po_line_id,
+                    item_name: l.item_name,
+                    unit_name: l.unit_name,
+                    qty: l.qty,
+                    price: l.price,
+                    flag: l.flag,
+                    note: l.note,
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(PurchaseOrderDetailResponse {
+        order: order.into(),
+        lines,
+        receipts,
+    })
+}
 
-    // Logo
-    add_logo_to_pdf_layer(&current_layer);
+#[tauri::command]
+fn save_po_line(
+    app: tauri::AppHandle,
+    po_id: i64,
+    po_line_id: Option<i64>,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: f64,
+    price: Option<f64>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    purchase_orders::save_line(&conn, po_id, po_line_id, item_id, unit_id, qty, price)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Line saved".to_string(),
+    })
+}
 
-    let mut y = 270.0f32;
-    current_layer.use_text("FDA Food Safety Guidelines", 18.0, Mm(margin_left), Mm(y), &font_bold);
-    y -= 10.0;
-    current_layer.use_text("Quick-reference for commercial kitchen operations", 9.0, Mm(margin_left), Mm(y), &font);
-    y -= 10.0;
+#[tauri::command]
+fn delete_po_line(app: tauri::AppHandle, po_line_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    purchase_orders::delete_line(&conn, po_line_id)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Line deleted".to_string(),
+    })
+}
 
-    // helper: new page if needed
-    macro_rules! check_page {
-        ($y:expr, $doc:expr, $layer:expr, $need:expr) => {
-            if $y < $need {
-                let (np, nl) = $doc.add_page(Mm(210.0), Mm(297.0), "Layer 1");
-                $layer = $doc.get_page(np).get_layer(nl);
-                $y = 285.0;
-            }
-        };
-    }
+#[tauri::command]
+fn set_purchase_order_status(
+    app: tauri::AppHandle,
+    po_id: i64,
+    status: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    purchase_orders::set_status(&conn, po_id, status.trim())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Purchase order marked {}", status.trim()),
+    })
+}
 
-    // helper: section heading
-    macro_rules! section {
-        ($y:expr, $layer:expr, $doc:expr, $title:expr, $font_bold:expr) => {
-            check_page!($y, $doc, $layer, 30.0);
-            $layer.use_text($title, 13.0, Mm(margin_left), Mm($y), $font_bold);
-            $y -= 7.0;
-        };
-    }
+#[tauri::command]
+fn delete_purchase_order(app: tauri::AppHandle, po_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| purchase_orders::delete_order(tx, po_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Purchase order deleted".to_string(),
+    })
+}
 
-    // helper: table row
-    macro_rules! row2 {
-        ($y:expr, $layer:expr, $doc:expr, $a:expr, $b:expr, $font:expr) => {
-            check_page!($y, $doc, $layer, 12.0);
-            $layer.use_text($a, 8.0, Mm(margin_left), Mm($y), $font);
-            $layer.use_text($b, 8.0, Mm(margin_left + 80.0), Mm($y), $font);
-            $y -= line_height;
-        };
-    }
-    macro_rules! row3 {
-        ($y:expr, $layer:expr, $doc:expr, $a:expr, $b:expr, $c:expr, $font:expr) => {
-            check_page!($y, $doc, $layer, 12.0);
-            $layer.use_text($a, 8.0, Mm(margin_left), Mm($y), $font);
-            $layer.use_text($b, 8.0, Mm(margin_left + 60.0), Mm($y), $font);
-            $layer.use_text($c, 8.0, Mm(margin_left + 120.0), Mm($y), $font);
-            $y -= line_height;
-        };
-    }
-    macro_rules! bullet {
-        ($y:expr, $layer:expr, $doc:expr, $text:expr, $font:expr) => {
-            check_page!($y, $doc, $layer, 12.0);
-            for line in wrap_text($text, 100) {
-                check_page!($y, $doc, $layer, 12.0);
-                $layer.use_text(&format!("•  {}", line), 8.0, Mm(margin_left), Mm($y), $font);
-                $y -= line_height;
-            }
-        };
-    }
-    macro_rules! note {
-        ($y:expr, $layer:expr, $doc:expr, $text:expr, $font:expr) => {
-            check_page!($y, $doc, $layer, 12.0);
-            for line in wrap_text(&format!("⚠  {}", $text), 100) {
-                check_page!($y, $doc, $layer, 12.0);
-                $layer.use_text(&line, 7.5, Mm(margin_left + 2.0), Mm($y), $font);
-                $y -= line_height;
-            }
-        };
+#[tauri::command]
+fn receive_purchase_order(
+    app: tauri::AppHandle,
+    po_id: i64,
+    invoice_no: String,
+    invoice_date: Option<String>,
+    lines: Vec<ReceiveLineInput>,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let invoice_date = match invoice_date.filter(|d| !d.trim().is_empty()) {
+        Some(d) => {
+            sales::normalize_date(&d).ok_or_else(|| format!("Invalid invoice date '{}'", d))?
+        }
+        None => conn
+            .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?,
+    };
+    let lines: Vec<purchase_orders::ReceiveLine> = lines
+        .into_iter()
+        .map(|l| purchase_orders::ReceiveLine {
+            po_line_id: l.po_line_id,
+            item_id: l.item_id,
+            unit_id: l.unit_id,
+            qty: l.qty,
+            price: l.price,
+        })
+        .collect();
+    let invoice_id = with_tx(&mut conn, |tx| {
+        let invoice_id =
+            purchase_orders::receive(tx, po_id, invoice_no.trim(), &invoice_date, &lines)?;
+        landed::allocate_invoice(tx, invoice_id)?;
+        reconcile::reconcile_invoice(tx, invoice_id)?;
+        Ok(invoice_id)
+    })?;
+    let order = purchase_orders::load_order(&conn, po_id)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Received into invoice {}; {} is {}",
+            invoice_id, order.po_number, order.status
+        ),
+    })
+}
+
+#[tauri::command]
+fn export_purchase_order_csv(
+    app: tauri::AppHandle,
+    po_id: i64,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let order = purchase_orders::load_order(&conn, po_id)?;
+    let lines = purchase_orders::order_lines(&conn, po_id)?;
+    let opt = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "PoNumber",
+        "Vendor",
+        "OrderDate",
+        "ExpectedDate",
+        "ItemId",
+        "Item",
+        "Qty",
+        "Unit",
+        "Price",
+        "ExtCost",
+    ])
+    .map_err(|e| e.to_string())?;
+    for l in &lines {
+        wtr.write_record([
+            order.po_number.clone(),
+            order.vendor_name.clone(),
+            order.order_date.clone(),
+            order.expected_date.clone(),
+            l.item_id.to_string(),
+            l.item_name.clone(),
+            format!("{}", l.qty),
+            l.unit_name.clone(),
+            opt(l.price),
+            opt(l.price.map(|p| p * l.qty)),
+        ])
+        .map_err(|e| e.to_string())?;
     }
+    wtr.flush().map_err(|e| e.to_string())?;
 
-    // ── Temperature Control ──
-    section!(y, current_layer, doc, "Temperature Control", &font_bold);
-    row3!(y, current_layer, doc, "ZONE", "RANGE", "GUIDELINE", &font_bold);
-    row3!(y, current_layer, doc, "Danger Zone", "41-135 °F (5-57 °C)", "Max 4 hours total", &font);
-    row3!(y, current_layer, doc, "Cold holding", "≤ 41 °F (5 °C)", "Keep cold foods at/below 41 °F", &font);
-    row3!(y, current_layer, doc, "Hot holding", "≥ 135 °F (57 °C)", "Keep hot foods at/above 135 °F", &font);
-    row3!(y, current_layer, doc, "Receiving", "≤ 41 °F / frozen solid", "Reject above 41 °F or partially thawed", &font);
-    y -= 4.0;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} to {}", order.po_number, output_path),
+    })
+}
 
-    // ── Minimum Cooking Temperatures ──
-    section!(y, current_layer, doc, "Minimum Cooking Temperatures", &font_bold);
-    row3!(y, current_layer, doc, "FOOD", "INTERNAL TEMP", "HOLD TIME", &font_bold);
-    row3!(y, current_layer, doc, "Poultry", "165 °F (74 °C)", "Instantaneous", &font);
-    row3!(y, current_layer, doc, "Ground meat", "155 °F (68 °C)", "17 seconds", &font);
-    row3!(y, current_layer, doc, "Seafood, steaks, eggs", "145 °F (63 °C)", "15 seconds", &font);
-    row3!(y, current_layer, doc, "Roasts (beef/pork/lamb)", "145 °F (63 °C)", "4 minutes", &font);
-    row3!(y, current_layer, doc, "Vegetables, grains (hot)", "135 °F (57 °C)", "Instantaneous", &font);
-    row3!(y, current_layer, doc, "Reheated leftovers", "165 °F (74 °C)", "Within 2 hours", &font);
-    y -= 4.0;
+#[tauri::command]
+fn export_purchase_order_pdf(
+    app: tauri::AppHandle,
+    po_id: i64,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let order = purchase_orders::load_order(&conn, po_id)?;
+    let lines = purchase_orders::order_lines(&conn, po_id)?;
+    let columns = [
+        PdfColumn {
+            header: "ID",
+            x: 0.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Item",
+            x: 15.0,
+            size: 8.0,
+            max_chars: Some(48),
+        },
+        PdfColumn {
+            header: "Qty",
+            x: 110.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Unit",
+            x: 125.0,
+            size: 8.0,
+            max_chars: Some(16),
+        },
+        PdfColumn {
+            header: "Price",
+            x: 150.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Ext.",
+            x: 168.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let money = |v: Option<f64>| v.map(fmt_money).unwrap_or_else(|| "-".to_string());
+    let mut rows: Vec<PdfRow> = lines
+        .iter()
+        .map(|l| PdfRow {
+            group: String::new(),
+            cells: vec![
+                l.item_id.to_string(),
+                l.item_name.clone(),
+                format!("{}", l.qty),
+                l.unit_name.clone(),
+                money(l.price),
+                money(l.price.map(|p| p * l.qty)),
+            ],
+            bold: false,
+        })
+        .collect();
+    rows.push(PdfRow {
+        group: String::new(),
+        cells: vec![
+            String::new(),
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            fmt_money(order.total),
+        ],
+        bold: true,
+    });
+    let mut subtitle = format!("{} — ordered {}", order.vendor_name, order.order_date);
+    if !order.expected_date.is_empty() {
+        subtitle.push_str(&format!(", deliver by {}", order.expected_date));
+    }
+    write_table_pdf(
+        &output_path,
+        "4chef Purchase Order",
+        &format!("4chef — Purchase Order {}", order.po_number),
+        Some(&subtitle),
+        &columns,
+        &rows,
+        false,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} to {}", order.po_number, output_path),
+    })
+}
 
-    // ── Two-Stage Cooling ──
-    section!(y, current_layer, doc, "Two-Stage Cooling", &font_bold);
-    row3!(y, current_layer, doc, "STAGE", "TARGET", "TIME LIMIT", &font_bold);
-    row3!(y, current_layer, doc, "Stage 1", "135 °F -> 70 °F", "Within 2 hours", &font);
-    row3!(y, current_layer, doc, "Stage 2", "70 °F -> 41 °F", "Within 4 hours (6 total)", &font);
-    note!(y, current_layer, doc, "If food does not reach 70 °F within 2 hours, reheat to 165 °F and restart.", &font);
-    y -= 4.0;
+// ── Vendor bids ──
 
-    // ── Approved Thawing Methods ──
-    section!(y, current_layer, doc, "Approved Thawing Methods", &font_bold);
-    bullet!(y, current_layer, doc, "Refrigerator thawing — at 41 °F or below", &font);
-    bullet!(y, current_layer, doc, "Cold running water — submerged, ≤ 70 °F, used within 4 hours", &font);
-    bullet!(y, current_layer, doc, "Microwave — only if cooked immediately after", &font);
-    bullet!(y, current_layer, doc, "Cooking from frozen — as part of the cooking process", &font);
-    note!(y, current_layer, doc, "Never thaw food at room temperature on a counter.", &font);
-    y -= 4.0;
+#[derive(Serialize)]
+struct VendorBidResponse {
+    bid_id: i64,
+    vendor_id: i64,
+    vendor_name: String,
+    item_id: i64,
+    item_name: String,
+    price: f64,
+    unit_id: Option<i64>,
+    unit_name: String,
+    pack: String,
+    valid_from: String,
+    valid_to: String,
+    status: String,
+    source: String,
+    norm_price: Option<f64>,
+    purch_unit_name: String,
+}
 
-    // ── Handwashing ──
-    section!(y, current_layer, doc, "Handwashing", &font_bold);
-    bullet!(y, current_layer, doc, "Wet hands with warm water (≥ 100 °F / 38 °C)", &font);
-    bullet!(y, current_layer, doc, "Apply soap and scrub for at least 20 seconds", &font);
-    bullet!(y, current_layer, doc, "Rinse and dry with single-use towel or air dryer", &font);
-    note!(y, current_layer, doc, "Required: before handling food, after touching raw meat, after using restroom, after sneezing/coughing, after handling trash.", &font);
-    y -= 4.0;
+impl From<bids::Bid> for VendorBidResponse {
+    fn from(b: bids::Bid) -> Self {
+        VendorBidResponse {
+            bid_id: b.bid_id,
+            vendor_id: b.vendor_id,
+            vendor_name: b.vendor_name,
+            item_id: b.item_id,
+            item_name: b.item_name,
+            price: b.price,
+            unit_id: b.unit_id,
+            unit_name: b.unit_name,
+            pack: b.pack,
+            valid_from: b.valid_from,
+            valid_to: b.valid_to,
+            status: b.status,
+            source: b.source,
+            norm_price: b.norm_price,
+            purch_unit_name: b.purch_unit_name,
+        }
+    }
+}
 
-    // ── Cross-Contamination Prevention ──
-    section!(y, current_layer, doc, "Cross-Contamination Prevention", &font_bold);
-    bullet!(y, current_layer, doc, "Store raw meats below ready-to-eat foods in the cooler", &font);
-    bullet!(y, current_layer, doc, "Cooler order (top to bottom): ready-to-eat, seafood, whole cuts, ground meat, poultry", &font);
-    bullet!(y, current_layer, doc, "Use separate cutting boards and utensils for raw and cooked foods", &font);
-    bullet!(y, current_layer, doc, "Sanitize surfaces between tasks — use approved sanitizer at correct concentration", &font);
-    y -= 4.0;
+#[derive(Serialize)]
+struct BidComparisonResponse {
+    item_id: i64,
+    item_name: String,
+    purch_unit_name: String,
+    current_vendor: String,
+    current_price: Option<f64>,
+    best_bid_id: Option<i64>,
+    bids: Vec<VendorBidResponse>,
+}
 
-    // ── Maximum Cold Storage ──
-    section!(y, current_layer, doc, "Maximum Cold Storage (at 41 °F)", &font_bold);
-    row2!(y, current_layer, doc, "ITEM", "MAX DAYS", &font_bold);
-    row2!(y, current_layer, doc, "Fresh poultry, ground meat, fish", "1 – 2 days", &font);
-    row2!(y, current_layer, doc, "Fresh steaks, chops, roasts", "3 – 5 days", &font);
-    row2!(y, current_layer, doc, "Cooked leftovers", "7 days (date-mark required)", &font);
-    row2!(y, current_layer, doc, "Deli meats (opened)", "3 – 5 days", &font);
-    row2!(y, current_layer, doc, "Eggs (shell)", "3 – 5 weeks", &font);
-    note!(y, current_layer, doc, "All ready-to-eat TCS foods held longer than 24 hours must be date-marked.", &font);
-    y -= 4.0;
+#[derive(serde::Deserialize)]
+struct VendorBidInput {
+    bid_id: Option<i64>,
+    vendor_id: i64,
+    item_id: i64,
+    price: f64,
+    unit_id: Option<i64>,
+    pack: Option<String>,
+    valid_from: Option<String>,
+    valid_to: Option<String>,
+}
 
-    // ── Big 6 Pathogens ──
-    section!(y, current_layer, doc, "Big 6 Foodborne Pathogens", &font_bold);
-    row2!(y, current_layer, doc, "PATHOGEN", "COMMON SOURCES", &font_bold);
-    row2!(y, current_layer, doc, "Norovirus", "Infected workers, ready-to-eat foods", &font);
-    row2!(y, current_layer, doc, "Salmonella Typhi", "Beverages, ready-to-eat foods", &font);
-    row2!(y, current_layer, doc, "Shigella spp.", "Ready-to-eat foods, contaminated water", &font);
-    row2!(y, current_layer, doc, "E. coli O157:H7", "Undercooked beef, unpasteurized juices", &font);
-    row2!(y, current_layer, doc, "Hepatitis A", "Shellfish, ready-to-eat foods", &font);
-    row2!(y, current_layer, doc, "Non-typhoidal Salmonella", "Poultry, eggs, produce", &font);
-    note!(y, current_layer, doc, "Employees diagnosed with any Big 6 illness must be excluded or restricted per FDA Food Code.", &font);
+fn optional_date(value: Option<String>, label: &str) -> Result<Option<String>, String> {
+    match value.filter(|d| !d.trim().is_empty()) {
+        Some(d) => sales::normalize_date(&d)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid {} '{}'", label, d)),
+        None => Ok(None),
+    }
+}
 
-    let _ = y; // suppress unused warning
-    let file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
-    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())?;
+fn bid_import_message(count: usize, warnings: &[String]) -> String {
+    match warnings.first() {
+        Some(first) => format!(
+            "Loaded {} bids; {} rows skipped ({})",
+            count,
+            warnings.len(),
+            first
+        ),
+        None => format!("Loaded {} bids", count),
+    }
+}
+
+#[tauri::command]
+fn list_vendor_bids(
+    app: tauri::AppHandle,
+    item_id: Option<i64>,
+    vendor_id: Option<i64>,
+    status: Option<String>,
+) -> Result<Vec<VendorBidResponse>, String> {
+    let conn = open_initialized_db(&app)?;
+    let filter = bids::BidFilter {
+        item_id,
+        vendor_id,
+        status: status.filter(|s| !s.trim().is_empty()),
+    };
+    Ok(bids::list_bids(&conn, &filter)?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
 
+#[tauri::command]
+fn save_vendor_bid(app: tauri::AppHandle, bid: VendorBidInput) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let new_bid = bids::NewBid {
+        vendor_id: bid.vendor_id,
+        item_id: bid.item_id,
+        price: bid.price,
+        unit_id: bid.unit_id,
+        pack: bid.pack.unwrap_or_default().trim().to_string(),
+        valid_from: optional_date(bid.valid_from, "valid-from date")?,
+        valid_to: optional_date(bid.valid_to, "valid-to date")?,
+    };
+    bids::save_bid(&conn, bid.bid_id, &new_bid)?;
     Ok(PatchResponse {
         ok: true,
-        message: format!("Exported FDA guidelines to {}", output_path),
+        message: "Bid saved".to_string(),
     })
 }
 
-// ──────────────────────────────────────────
-// Global search across all data
-// ──────────────────────────────────────────
+#[tauri::command]
+fn delete_vendor_bid(app: tauri::AppHandle, bid_id: i64) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    bids::delete_bid(&conn, bid_id)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Bid deleted".to_string(),
+    })
+}
 
-#[derive(Serialize, Clone)]
-struct GlobalSearchHit {
-    category: String,    // "item", "recipe", "vendor", "invoice"
-    id: i64,
-    label: String,
-    detail: String,
+#[tauri::command]
+fn import_legacy_bids(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let (count, warnings) = with_tx(&mut conn, bids::import_legacy_bids)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: bid_import_message(count, &warnings),
+    })
 }
 
 #[tauri::command]
-fn global_search(
+fn import_bid_sheet(
     app: tauri::AppHandle,
-    query: String,
-) -> Result<Vec<GlobalSearchHit>, String> {
-    let conn = open_initialized_db(&app)?;
-    let pattern = format!("%{}%", query.trim());
-    let mut hits: Vec<GlobalSearchHit> = Vec::new();
+    csv_path: String,
+    valid_from: Option<String>,
+    valid_to: Option<String>,
+) -> Result<PatchResponse, String> {
+    let valid_from = optional_date(valid_from, "valid-from date")?;
+    let valid_to = optional_date(valid_to, "valid-to date")?;
+    let mut conn = open_initialized_db(&app)?;
+    let (count, warnings) = with_tx(&mut conn, |tx| {
+        bids::import_bid_sheet(
+            tx,
+            Path::new(&csv_path),
+            valid_from.as_deref(),
+            valid_to.as_deref(),
+        )
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: bid_import_message(count, &warnings),
+    })
+}
 
-    // Items
-    {
-        let mut stmt = conn
-            .prepare("SELECT item_id, name FROM items WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([&pattern], |row| {
-                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-            })
-            .map_err(|e| e.to_string())?;
-        for r in rows.flatten() {
-            hits.push(GlobalSearchHit {
-                category: "item".into(),
-                id: r.0,
-                label: r.1,
-                detail: "Inventory item".into(),
-            });
-        }
-    }
+#[tauri::command]
+fn get_bid_comparison(
+    app: tauri::AppHandle,
+    on_date: Option<String>,
+    category: Option<String>,
+) -> Result<Vec<BidComparisonResponse>, String> {
+    let conn = open_initialized_db(&app)?;
+    let on_date = match optional_date(on_date, "date")? {
+        Some(d) => d,
+        None => conn
+            .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?,
+    };
+    let category = category.filter(|c| !c.trim().is_empty());
+    Ok(bids::compare_bids(&conn, &on_date, category.as_deref())?
+        .into_iter()
+        .map(|c| BidComparisonResponse {
+            item_id: c.item_id,
+            item_name: c.item_name,
+            purch_unit_name: c.purch_unit_name,
+            current_vendor: c.current_vendor,
+            current_price: c.current_price,
+            best_bid_id: c.best_bid_id,
+            bids: c.bids.into_iter().map(Into::into).collect(),
+        })
+        .collect())
+}
 
-    // Recipes
-    {
-        let mut stmt = conn
-            .prepare("SELECT recipe_id, name FROM recipes WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([&pattern], |row| {
-                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-            })
-            .map_err(|e| e.to_string())?;
-        for r in rows.flatten() {
-            hits.push(GlobalSearchHit {
-                category: "recipe".into(),
-                id: r.0,
-                label: r.1,
-                detail: "Recipe".into(),
-            });
-        }
-    }
+#[tauri::command]
+fn award_vendor_bid(app: tauri::AppHandle, bid_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| bids::award_bid(tx, bid_id))?;
+    let bid = bids::load_bid(&conn, bid_id)?;
+    let window = match (bid.valid_from.as_str(), bid.valid_to.as_str()) {
+        ("", "") => "with no end date".to_string(),
+        (from, "") => format!("from {}", from),
+        ("", to) => format!("until {}", to),
+        (from, to) => format!("from {} to {}", from, to),
+    };
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Awarded {} to {} {}",
+            bid.item_name, bid.vendor_name, window
+        ),
+    })
+}
 
-    // Vendors
-    {
-        let mut stmt = conn
-            .prepare("SELECT vendor_id, name FROM vendors WHERE name LIKE ?1 ORDER BY name COLLATE NOCASE LIMIT 20")
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([&pattern], |row| {
-                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
-            })
-            .map_err(|e| e.to_string())?;
-        for r in rows.flatten() {
-            hits.push(GlobalSearchHit {
-                category: "vendor".into(),
-                id: r.0,
-                label: r.1,
-                detail: "Vendor".into(),
-            });
-        }
+fn load_bid_request(
+    conn: &rusqlite::Connection,
+    vendor_ids: &[i64],
+    category: Option<String>,
+) -> Result<Vec<bids::BidRequestLine>, String> {
+    if vendor_ids.is_empty() {
+        return Err("Choose at least one vendor".to_string());
+    }
+    let category = category.filter(|c| !c.trim().is_empty());
+    let lines = bids::bid_request(conn, vendor_ids, category.as_deref())?;
+    if lines.is_empty() {
+        return Err("No items with a purchase unit to quote".to_string());
     }
+    Ok(lines)
+}
 
-    // Invoices
-    {
-        let mut stmt = conn
-            .prepare(
-                "SELECT inv_id, COALESCE(invoice_no, ''), COALESCE(v.name, '')
-                 FROM invoices i LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
-                 WHERE i.invoice_no LIKE ?1 OR v.name LIKE ?1
-                 ORDER BY i.inv_id DESC LIMIT 20"
-            )
-            .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([&pattern], |row| {
-                Ok((
-                    row.get::<_, i64>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, String>(2)?,
-                ))
-            })
-            .map_err(|e| e.to_string())?;
-        for r in rows.flatten() {
-            hits.push(GlobalSearchHit {
-                category: "invoice".into(),
-                id: r.0,
-                label: format!("#{} — {}", r.1, r.2),
-                detail: "Invoice".into(),
-            });
-        }
+#[tauri::command]
+fn export_bid_request_csv(
+    app: tauri::AppHandle,
+    vendor_ids: Vec<i64>,
+    category: Option<String>,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let lines = load_bid_request(&conn, &vendor_ids, category)?;
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "VendorId",
+        "Vendor",
+        "ItemId",
+        "Item",
+        "Category",
+        "UnitId",
+        "Unit",
+        "Pack",
+        "LastPrice",
+        "Price",
+        "ValidFrom",
+        "ValidTo",
+    ])
+    .map_err(|e| e.to_string())?;
+    for l in &lines {
+        wtr.write_record([
+            l.vendor_id.to_string(),
+            l.vendor_name.clone(),
+            l.item_id.to_string(),
+            l.item_name.clone(),
+            l.category.clone(),
+            l.unit_id.to_string(),
+            l.unit_name.clone(),
+            l.pack.clone(),
+            l.last_price
+                .map(|p| format!("{:.2}", p))
+                .unwrap_or_default(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ])
+        .map_err(|e| e.to_string())?;
     }
+    wtr.flush().map_err(|e| e.to_string())?;
 
-    Ok(hits)
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} lines to {}", lines.len(), output_path),
+    })
 }
 
-// ──────────────────────────────────────────
-// PDF Invoice Import — extract text-based PDF invoices
-// ──────────────────────────────────────────
+#[tauri::command]
+fn export_bid_request_pdf(
+    app: tauri::AppHandle,
+    vendor_ids: Vec<i64>,
+    category: Option<String>,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let lines = load_bid_request(&conn, &vendor_ids, category)?;
+    let columns = [
+        PdfColumn {
+            header: "ID",
+            x: 0.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Item",
+            x: 15.0,
+            size: 8.0,
+            max_chars: Some(44),
+        },
+        PdfColumn {
+            header: "Unit",
+            x: 100.0,
+            size: 8.0,
+            max_chars: Some(14),
+        },
+        PdfColumn {
+            header: "Pack",
+            x: 122.0,
+            size: 8.0,
+            max_chars: Some(14),
+        },
+        PdfColumn {
+            header: "Last",
+            x: 145.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Quote",
+            x: 163.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let rows: Vec<PdfRow> = lines
+        .iter()
+        .map(|l| PdfRow {
+            group: l.vendor_name.clone(),
+            cells: vec![
+                l.item_id.to_string(),
+                l.item_name.clone(),
+                l.unit_name.clone(),
+                l.pack.clone(),
+                l.last_price
+                    .map(fmt_money)
+                    .unwrap_or_else(|| "-".to_string()),
+                "________".to_string(),
+            ],
+            bold: false,
+        })
+        .collect();
+    write_table_pdf(
+        &output_path,
+        "4chef Bid Request",
+        "4chef — Bid Request",
+        Some("Please quote a price per unit shown, with the dates your prices are valid."),
+        &columns,
+        &rows,
+        true,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} lines to {}", lines.len(), output_path),
+    })
+}
+
+// ── Price increase alerts ──
 
 #[derive(Serialize)]
-struct PdfInvoiceLine {
-    item_name: String,
-    qty: Option<f64>,
-    unit: String,
-    unit_price: Option<f64>,
-    ext_cost: Option<f64>,
+struct PriceAlertLineResponse {
+    trans_id: i64,
+    qty: f64,
+    price: f64,
+    unit_name: String,
 }
 
 #[derive(Serialize)]
-struct PdfInvoicePreview {
-    vendor_guess: String,
+struct PriceAlertResponse {
+    alert_id: i64,
+    item_id: i64,
+    item_name: String,
+    vendor_id: i64,
+    vendor_name: String,
+    old_price: f64,
+    new_price: f64,
+    pct_change: f64,
+    unit_name: String,
+    source: String,
+    invoice_id: Option<i64>,
     invoice_no: String,
     invoice_date: String,
-    lines: Vec<PdfInvoiceLine>,
-    raw_text: String,
+    status: String,
+    note: String,
+    created_at: String,
+    lines: Vec<PriceAlertLineResponse>,
 }
 
-/// Try to parse a dollar value from a string like "$12.50" or "12.50".
-fn parse_dollar(s: &str) -> Option<f64> {
-    let cleaned = s.trim().trim_start_matches('$').replace(',', "");
-    cleaned.parse::<f64>().ok()
+#[derive(Serialize)]
+struct PriceAlertsResponse {
+    threshold_pct: f64,
+    new_count: i64,
+    alerts: Vec<PriceAlertResponse>,
 }
 
 #[tauri::command]
-fn preview_pdf_invoice(pdf_path: String) -> Result<PdfInvoicePreview, String> {
-    let bytes = std::fs::read(&pdf_path).map_err(|e| format!("Cannot read file: {e}"))?;
-    let text = pdf_extract::extract_text_from_mem(&bytes)
-        .map_err(|e| format!("PDF extraction failed: {e}"))?;
-
-    // Heuristic parsing for common invoice formats
-    let mut vendor_guess = String::new();
-    let mut invoice_no = String::new();
-    let mut invoice_date = String::new();
-    let mut lines = Vec::new();
-
-    let text_lines: Vec<&str> = text.lines().collect();
-
-    // First non-empty line is often the vendor name
-    for line in &text_lines {
-        let trimmed = line.trim();
-        if !trimmed.is_empty() && vendor_guess.is_empty() {
-            vendor_guess = trimmed.to_string();
-            break;
-        }
-    }
-
-    for line in &text_lines {
-        let lower = line.to_lowercase();
-        // Invoice number
-        if invoice_no.is_empty() {
-            if let Some(pos) = lower.find("invoice") {
-                let after = &line[pos..];
-                let parts: Vec<&str> = after.split_whitespace().collect();
-                // Look for a number-like token after "invoice"
-                for part in parts.iter().skip(1) {
-                    let cleaned = part.trim_matches(|c: char| !c.is_alphanumeric());
-                    if !cleaned.is_empty() {
-                        invoice_no = cleaned.to_string();
-                        break;
-                    }
-                }
-            }
-        }
-        // Date (look for patterns like MM/DD/YYYY or YYYY-MM-DD)
-        if invoice_date.is_empty() {
-            if let Some(pos) = lower.find("date") {
-                let after = &line[pos..];
-                let parts: Vec<&str> = after.split_whitespace().collect();
-                for part in parts.iter().skip(1) {
-                    let cleaned = part.trim_matches(|c: char| c == ':' || c == ' ');
-                    if cleaned.len() >= 8 && cleaned.contains('/') || cleaned.contains('-') {
-                        invoice_date = cleaned.to_string();
-                        break;
-                    }
-                }
-            }
-        }
-
-        // Try to extract line items — look for lines with dollar amounts
-        let tokens: Vec<&str> = line.split_whitespace().collect();
-        if tokens.len() >= 3 {
-            // Find dollar-like values from the right
-            let mut dollar_vals: Vec<f64> = Vec::new();
-            let mut name_end = tokens.len();
-            for (i, token) in tokens.iter().enumerate().rev() {
-                if let Some(val) = parse_dollar(token) {
-                    dollar_vals.push(val);
-                    name_end = i;
-                } else if !dollar_vals.is_empty() {
-                    break;
-                }
-            }
-            // Also look for a qty at the beginning or near the dollar values
-            if !dollar_vals.is_empty() && name_end > 0 {
-                dollar_vals.reverse();
-                let name_parts: Vec<&str> = tokens[..name_end].to_vec();
-                // Try to find qty — first numeric token in name_parts from the left or right
-                let mut qty: Option<f64> = None;
-                let mut unit = String::new();
-                let mut actual_name_parts = name_parts.clone();
-
-                // Check if first token is a number (qty)
-                if let Ok(q) = name_parts[0].parse::<f64>() {
-                    qty = Some(q);
-                    actual_name_parts = name_parts[1..].to_vec();
-                    // Check if second token looks like a unit (short word)
-                    if actual_name_parts.len() > 1 && actual_name_parts[0].len() <= 5 {
-                        unit = actual_name_parts[0].to_string();
-                        actual_name_parts = actual_name_parts[1..].to_vec();
-                    }
-                }
-
-                let item_name = actual_name_parts.join(" ");
-                if !item_name.is_empty() && item_name.len() > 2 {
-                    let unit_price = if dollar_vals.len() >= 2 {
-                        Some(dollar_vals[0])
-                    } else {
-                        None
-                    };
-                    let ext_cost = dollar_vals.last().copied();
-                    lines.push(PdfInvoiceLine {
-                        item_name,
-                        qty,
-                        unit,
-                        unit_price,
-                        ext_cost,
-                    });
-                }
-            }
-        }
-    }
-
-    Ok(PdfInvoicePreview {
-        vendor_guess,
-        invoice_no,
-        invoice_date,
-        lines,
-        raw_text: text,
+fn list_price_alerts(
+    app: tauri::AppHandle,
+    status: Option<String>,
+) -> Result<PriceAlertsResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let status = status.filter(|s| !s.trim().is_empty());
+    let new_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM price_alerts WHERE status = 'new'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let alerts = price_alerts::list_alerts(&conn, status.as_deref())?
+        .into_iter()
+        .map(|a| PriceAlertResponse {
+            alert_id: a.alert_id,
+            item_id: a.item_id,
+            item_name: a.item_name,
+            vendor_id: a.vendor_id,
+            vendor_name: a.vendor_name,
+            old_price: a.old_price,
+            new_price: a.new_price,
+            pct_change: a.pct_change,
+            unit_name: a.unit_name,
+            source: a.source,
+            invoice_id: a.invoice_id,
+            invoice_no: a.invoice_no,
+            invoice_date: a.invoice_date,
+            status: a.status,
+            note: a.note,
+            created_at: a.created_at,
+            lines: a
+                .lines
+                .into_iter()
+                .map(|l| PriceAlertLineResponse {
+                    trans_id: l.trans_id,
+                    qty: l.qty,
+                    price: l.price,
+                    unit_name: l.unit_name,
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(PriceAlertsResponse {
+        threshold_pct: price_alerts::threshold_pct(&conn),
+        new_count,
+        alerts,
     })
 }
 
 #[tauri::command]
-fn import_pdf_invoice(
+fn set_price_alert_status(
     app: tauri::AppHandle,
-    vendor_id: i64,
-    invoice_no: String,
-    invoice_date: String,
-    lines: Vec<serde_json::Value>,
+    alert_id: i64,
+    status: String,
+    note: Option<String>,
 ) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
+    let note = note.map(|n| n.trim().to_string());
+    price_alerts::set_alert_status(&conn, alert_id, status.trim(), note.as_deref())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Alert marked {}", status.trim()),
+    })
+}
 
-    // Find next invoice_id
-    let next_id: i64 = conn
-        .query_row("SELECT COALESCE(MAX(invoice_id), 0) + 1 FROM invoices", [], |r| r.get(0))
-        .map_err(|e| e.to_string())?;
+// ── Recipe cost impact ──
 
-    // Insert invoice header
-    conn.execute(
-        "INSERT INTO invoices (status, invoice_id, invoice_date, vendor_id, invoice_no, freight, total)
-         VALUES (1, ?1, ?2, ?3, ?4, 0, 0)",
-        (next_id, &invoice_date, vendor_id, &invoice_no),
-    )
-    .map_err(|e| e.to_string())?;
+#[derive(serde::Deserialize)]
+struct CostImpactInput {
+    /// "item", "invoice" or "percent".
+    mode: String,
+    item_id: Option<i64>,
+    new_price: Option<f64>,
+    invoice_id: Option<i64>,
+    pct: Option<f64>,
+    category: Option<String>,
+    vendor_id: Option<i64>,
+}
 
-    let mut trans_count = 0i64;
-    let next_trans_base: i64 = conn
-        .query_row("SELECT COALESCE(MAX(trans_id), 0) + 1 FROM trans", [], |r| r.get(0))
-        .map_err(|e| e.to_string())?;
+impl CostImpactInput {
+    fn into_change(self) -> Result<impact::PriceChange, String> {
+        match self.mode.as_str() {
+            "item" => {
+                let item_id = self.item_id.ok_or("Choose an item")?;
+                let new_price = self
+                    .new_price
+                    .filter(|p| *p >= 0.0)
+                    .ok_or("Enter the new price per purchase unit")?;
+                Ok(impact::PriceChange::Item { item_id, new_price })
+            }
+            "invoice" => Ok(impact::PriceChange::Invoice(
+                self.invoice_id.ok_or("Choose an invoice")?,
+            )),
+            "percent" => Ok(impact::PriceChange::Percent {
+                pct: self.pct.ok_or("Enter a percentage change")?,
+                category: self
+                    .category
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty()),
+                vendor_id: self.vendor_id,
+            }),
+            other => Err(format!("Unknown impact mode '{}'", other)),
+        }
+    }
+}
 
-    let mut total = 0.0f64;
-    for (i, line) in lines.iter().enumerate() {
-        let item_name = line.get("item_name").and_then(|v| v.as_str()).unwrap_or("");
-        let qty = line.get("qty").and_then(|v| v.as_f64());
-        let unit_price = line.get("unit_price").and_then(|v| v.as_f64());
-        let ext_cost = line.get("ext_cost").and_then(|v| v.as_f64());
+#[derive(Serialize)]
+struct ChangedItemResponse {
+    item_id: i64,
+    item_name: String,
+    unit_name: String,
+    old_price: f64,
+    new_price: f64,
+}
 
-        if item_name.is_empty() {
-            continue;
+#[derive(Serialize)]
+struct RecipeImpactResponse {
+    recipe_id: i64,
+    recipe_name: String,
+    direct: bool,
+    old_cost: f64,
+    new_cost: f64,
+    cost_change: f64,
+    cost_change_pct: Option<f64>,
+    old_portion_cost: Option<f64>,
+    new_portion_cost: Option<f64>,
+    menu_price: Option<f64>,
+    old_food_cost_pct: Option<f64>,
+    new_food_cost_pct: Option<f64>,
+    food_cost_pct_change: Option<f64>,
+    missing_costs: i64,
+}
+
+impl From<impact::RecipeImpact> for RecipeImpactResponse {
+    fn from(r: impact::RecipeImpact) -> Self {
+        RecipeImpactResponse {
+            recipe_id: r.recipe_id,
+            recipe_name: r.recipe_name,
+            direct: r.direct,
+            old_cost: r.old_cost,
+            new_cost: r.new_cost,
+            cost_change: r.cost_change,
+            cost_change_pct: r.cost_change_pct,
+            old_portion_cost: r.old_portion_cost,
+            new_portion_cost: r.new_portion_cost,
+            menu_price: r.menu_price,
+            old_food_cost_pct: r.old_food_cost_pct,
+            new_food_cost_pct: r.new_food_cost_pct,
+            food_cost_pct_change: r.food_cost_pct_change,
+            missing_costs: r.missing_costs,
         }
+    }
+}
 
-        // Try to match item by name
-        let item_id: Option<i64> = conn
-            .query_row(
-                "SELECT item_id FROM items WHERE LOWER(name) = LOWER(?1) LIMIT 1",
-                [item_name],
-                |r| r.get(0),
-            )
-            .optional()
-            .map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct CostImpactResponse {
+    description: String,
+    items: Vec<ChangedItemResponse>,
+    recipes: Vec<RecipeImpactResponse>,
+    skipped: Vec<String>,
+}
 
-        // If no match, create a new item
-        let final_item_id = match item_id {
-            Some(id) => id,
-            None => {
-                let new_id: i64 = conn
-                    .query_row("SELECT COALESCE(MAX(item_id), 0) + 1 FROM items", [], |r| r.get(0))
-                    .map_err(|e| e.to_string())?;
-                conn.execute(
-                    "INSERT INTO items (item_id, name, status) VALUES (?1, ?2, 1)",
-                    (new_id, item_name),
-                )
-                .map_err(|e| e.to_string())?;
-                new_id
-            }
-        };
+#[tauri::command]
+fn get_cost_impact(
+    app: tauri::AppHandle,
+    change: CostImpactInput,
+) -> Result<CostImpactResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = impact::cost_impact(&conn, &change.into_change()?)?;
+    Ok(CostImpactResponse {
+        description: report.description,
+        items: report
+            .items
+            .into_iter()
+            .map(|i| ChangedItemResponse {
+                item_id: i.item_id,
+                item_name: i.item_name,
+                unit_name: i.unit_name,
+                old_price: i.old_price,
+                new_price: i.new_price,
+            })
+            .collect(),
+        recipes: report
+            .recipes
+            .into_iter()
+            .map(RecipeImpactResponse::from)
+            .collect(),
+        skipped: report.skipped,
+    })
+}
 
-        let line_cost = ext_cost.unwrap_or(0.0);
-        total += line_cost;
+fn fmt_pct(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.1}%", v)).unwrap_or_default()
+}
 
-        let trans_id = next_trans_base + i as i64;
-        conn.execute(
-            "INSERT INTO trans (status, invoice_id, trans_id, item_id, trans_date, vendor_id, price, qty, unit_id, ext_cost)
-             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, NULL, ?8)",
-            (
-                next_id,
-                trans_id,
-                final_item_id,
-                &invoice_date,
-                vendor_id,
-                unit_price.unwrap_or(0.0),
-                qty.unwrap_or(0.0),
-                line_cost,
-            ),
-        )
+#[tauri::command]
+fn export_cost_impact_csv(
+    app: tauri::AppHandle,
+    change: CostImpactInput,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = impact::cost_impact(&conn, &change.into_change()?)?;
+    let opt = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "RecipeID",
+        "Recipe",
+        "Uses",
+        "OldCost",
+        "NewCost",
+        "Change",
+        "ChangePct",
+        "OldPortionCost",
+        "NewPortionCost",
+        "MenuPrice",
+        "OldFoodCostPct",
+        "NewFoodCostPct",
+        "FoodCostPctChange",
+        "MissingCosts",
+    ])
+    .map_err(|e| e.to_string())?;
+    for r in &report.recipes {
+        wtr.write_record([
+            r.recipe_id.to_string(),
+            r.recipe_name.clone(),
+            if r.direct { "Direct" } else { "Sub-recipe" }.to_string(),
+            format!("{:.2}", r.old_cost),
+            format!("{:.2}", r.new_cost),
+            format!("{:.2}", r.cost_change),
+            opt(r.cost_change_pct),
+            opt(r.old_portion_cost),
+            opt(r.new_portion_cost),
+            opt(r.menu_price),
+            opt(r.old_food_cost_pct),
+            opt(r.new_food_cost_pct),
+            opt(r.food_cost_pct_change),
+            r.missing_costs.to_string(),
+        ])
         .map_err(|e| e.to_string())?;
-        trans_count += 1;
     }
+    wtr.flush().map_err(|e| e.to_string())?;
 
-    // Update invoice total
-    conn.execute(
-        "UPDATE invoices SET total = ?2 WHERE invoice_id = ?1",
-        (next_id, total),
-    )
-    .map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported {} recipes to {}",
+            report.recipes.len(),
+            output_path
+        ),
+    })
+}
 
+#[tauri::command]
+fn export_cost_impact_pdf(
+    app: tauri::AppHandle,
+    change: CostImpactInput,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = impact::cost_impact(&conn, &change.into_change()?)?;
+    let money = |v: Option<f64>| v.map(fmt_money).unwrap_or_else(|| "-".to_string());
+    let columns = [
+        PdfColumn {
+            header: "Recipe",
+            x: 0.0,
+            size: 8.0,
+            max_chars: Some(38),
+        },
+        PdfColumn {
+            header: "Old Cost",
+            x: 70.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "New Cost",
+            x: 90.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Change",
+            x: 110.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Portion",
+            x: 130.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "FC % Old",
+            x: 148.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "FC % New",
+            x: 166.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let rows: Vec<PdfRow> = report
+        .recipes
+        .iter()
+        .map(|r| PdfRow {
+            group: String::new(),
+            cells: vec![
+                if r.direct {
+                    r.recipe_name.clone()
+                } else {
+                    format!("{} (via sub-recipe)", r.recipe_name)
+                },
+                fmt_money(r.old_cost),
+                fmt_money(r.new_cost),
+                format!("{:+.2}", r.cost_change),
+                money(r.new_portion_cost),
+                fmt_pct(r.old_food_cost_pct),
+                fmt_pct(r.new_food_cost_pct),
+            ],
+            bold: r.food_cost_pct_change.is_some_and(|c| c >= 1.0),
+        })
+        .collect();
+    let subtitle = format!(
+        "{} — {} items, {} recipes affected",
+        report.description,
+        report.items.len(),
+        report.recipes.len()
+    );
+    write_table_pdf(
+        &output_path,
+        "4chef Cost Impact",
+        "4chef — Recipe Cost Impact",
+        Some(&subtitle),
+        &columns,
+        &rows,
+        false,
+    )?;
     Ok(PatchResponse {
         ok: true,
         message: format!(
-            "Imported invoice #{} with {} lines (total {})",
-            invoice_no,
-            trans_count,
-            fmt_money(total)
+            "Exported {} recipes to {}",
+            report.recipes.len(),
+            output_path
         ),
     })
 }
 
-// ── Food cost calculator ──
+// ── Spend analytics ──
 
 #[derive(serde::Deserialize)]
-struct FoodCostLineInput {
-    item_id: i64,
-    unit_id: Option<i64>,
-    qty: f64,
+struct SpendQueryInput {
+    group_by: String,
+    period: String,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    vendor_id: Option<i64>,
+    category: Option<String>,
+}
+
+impl SpendQueryInput {
+    fn into_query(self) -> Result<spend::SpendQuery, String> {
+        let date = |d: Option<String>| match d.filter(|d| !d.trim().is_empty()) {
+            Some(d) => sales::normalize_date(&d)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid date '{}'", d)),
+            None => Ok(None),
+        };
+        Ok(spend::SpendQuery {
+            group_by: self.group_by.trim().to_lowercase(),
+            period: self.period.trim().to_lowercase(),
+            date_from: date(self.date_from)?,
+            date_to: date(self.date_to)?,
+            vendor_id: self.vendor_id,
+            category: self
+                .category
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
+        })
+    }
 }
 
 #[derive(Serialize)]
-struct FoodCostLineOutput {
-    item_id: i64,
-    item_name: String,
-    unit_id: Option<i64>,
-    unit_name: String,
-    qty: f64,
-    price: Option<f64>,
-    extended_cost: Option<f64>,
-    cost_status: String,
+struct SpendRowResponse {
+    key: String,
+    label: String,
+    by_period: Vec<f64>,
+    total: f64,
+    share_pct: f64,
+    current: f64,
+    previous: f64,
+    change: f64,
+    change_pct: Option<f64>,
+}
+
+impl From<spend::SpendRow> for SpendRowResponse {
+    fn from(r: spend::SpendRow) -> Self {
+        SpendRowResponse {
+            key: r.key,
+            label: r.label,
+            by_period: r.by_period,
+            total: r.total,
+            share_pct: r.share_pct,
+            current: r.current,
+            previous: r.previous,
+            change: r.change,
+            change_pct: r.change_pct,
+        }
+    }
 }
 
 #[derive(Serialize)]
-struct FoodCostResponse {
-    lines: Vec<FoodCostLineOutput>,
-    total_cost: f64,
-    missing_costs: i64,
+struct VendorShareResponse {
+    vendor_name: String,
+    total: f64,
+    share_pct: f64,
+    current_share_pct: Option<f64>,
+    previous_share_pct: Option<f64>,
+    top_category: String,
+}
+
+#[derive(Serialize)]
+struct SpendReportResponse {
+    group_by: String,
+    period: String,
+    date_from: String,
+    date_to: String,
+    periods: Vec<String>,
+    period_totals: Vec<f64>,
+    total: f64,
+    invoice_count: usize,
+    line_count: usize,
+    rows: Vec<SpendRowResponse>,
+    movers: Vec<SpendRowResponse>,
+    vendor_share: Vec<VendorShareResponse>,
 }
 
 #[tauri::command]
-fn calculate_food_cost(
+fn get_spend_report(
     app: tauri::AppHandle,
-    lines: Vec<FoodCostLineInput>,
-) -> Result<FoodCostResponse, String> {
+    query: SpendQueryInput,
+) -> Result<SpendReportResponse, String> {
     let conn = open_initialized_db(&app)?;
+    let report = spend::spend_report(&conn, &query.into_query()?)?;
+    Ok(SpendReportResponse {
+        group_by: report.group_by,
+        period: report.period,
+        date_from: report.date_from,
+        date_to: report.date_to,
+        periods: report.periods,
+        period_totals: report.period_totals,
+        total: report.total,
+        invoice_count: report.invoice_count,
+        line_count: report.line_count,
+        rows: report
+            .rows
+            .into_iter()
+            .map(SpendRowResponse::from)
+            .collect(),
+        movers: report
+            .movers
+            .into_iter()
+            .map(SpendRowResponse::from)
+            .collect(),
+        vendor_share: report
+            .vendor_share
+            .into_iter()
+            .map(|v| VendorShareResponse {
+                vendor_name: v.vendor_name,
+                total: v.total,
+                share_pct: v.share_pct,
+                current_share_pct: v.current_share_pct,
+                previous_share_pct: v.previous_share_pct,
+                top_category: v.top_category,
+            })
+            .collect(),
+    })
+}
 
-    let mut out_lines = Vec::new();
-    let mut total_cost = 0.0f64;
-    let mut missing_costs = 0i64;
+#[tauri::command]
+fn export_spend_csv(
+    app: tauri::AppHandle,
+    query: SpendQueryInput,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = spend::spend_report(&conn, &query.into_query()?)?;
+
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    let mut header = vec!["Key".to_string(), "Name".to_string()];
+    header.extend(report.periods.iter().cloned());
+    header.extend(
+        [
+            "Total",
+            "SharePct",
+            "Previous",
+            "Current",
+            "Change",
+            "ChangePct",
+        ]
+        .map(str::to_string),
+    );
+    wtr.write_record(&header).map_err(|e| e.to_string())?;
+    for r in &report.rows {
+        let mut record = vec![r.key.clone(), r.label.clone()];
+        record.extend(r.by_period.iter().map(|v| format!("{:.2}", v)));
+        record.extend([
+            format!("{:.2}", r.total),
+            format!("{:.1}", r.share_pct),
+            format!("{:.2}", r.previous),
+            format!("{:.2}", r.current),
+            format!("{:.2}", r.change),
+            r.change_pct
+                .map(|v| format!("{:.1}", v))
+                .unwrap_or_default(),
+        ]);
+        wtr.write_record(&record).map_err(|e| e.to_string())?;
+    }
+    let mut totals = vec![String::new(), "Total".to_string()];
+    totals.extend(report.period_totals.iter().map(|v| format!("{:.2}", v)));
+    totals.push(format!("{:.2}", report.total));
+    totals.resize(header.len(), String::new());
+    wtr.write_record(&totals).map_err(|e| e.to_string())?;
+    wtr.flush().map_err(|e| e.to_string())?;
 
-    for line in &lines {
-        let item_name: String = conn
-            .query_row(
-                "SELECT name FROM items WHERE item_id = ?1",
-                [line.item_id],
-                |row| row.get(0),
-            )
-            .unwrap_or_else(|_| "(unknown)".to_string());
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} rows to {}", report.rows.len(), output_path),
+    })
+}
 
-        let unit_name: String = if let Some(uid) = line.unit_id {
-            conn.query_row("SELECT sing FROM units WHERE unit_id = ?1", [uid], |row| {
-                row.get(0)
-            })
-            .unwrap_or_else(|_| "-".to_string())
-        } else {
-            "-".to_string()
-        };
+#[tauri::command]
+fn export_spend_pdf(
+    app: tauri::AppHandle,
+    query: SpendQueryInput,
+    output_path: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let report = spend::spend_report(&conn, &query.into_query()?)?;
+    let columns = [
+        PdfColumn {
+            header: "Name",
+            x: 0.0,
+            size: 8.0,
+            max_chars: Some(40),
+        },
+        PdfColumn {
+            header: "Total",
+            x: 75.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Share",
+            x: 97.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Previous",
+            x: 113.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Current",
+            x: 135.0,
+            size: 8.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Change",
+            x: 157.0,
+            size: 8.0,
+            max_chars: None,
+        },
+    ];
+    let spend_row = |group: &str, r: &spend::SpendRow| PdfRow {
+        group: group.to_string(),
+        cells: vec![
+            r.label.clone(),
+            fmt_money(r.total),
+            format!("{:.1}%", r.share_pct),
+            fmt_money(r.previous),
+            fmt_money(r.current),
+            match r.change_pct {
+                Some(pct) => format!("{:+.2} ({:+.1}%)", r.change, pct),
+                None => format!("{:+.2}", r.change),
+            },
+        ],
+        bold: false,
+    };
+    let by = format!("By {}", report.group_by);
+    let mut rows: Vec<PdfRow> = report.rows.iter().map(|r| spend_row(&by, r)).collect();
+    rows.push(PdfRow {
+        group: by.clone(),
+        cells: vec![
+            "Total".to_string(),
+            fmt_money(report.total),
+            "100.0%".to_string(),
+            report
+                .period_totals
+                .iter()
+                .rev()
+                .nth(1)
+                .copied()
+                .map(fmt_money)
+                .unwrap_or_default(),
+            report
+                .period_totals
+                .last()
+                .copied()
+                .map(fmt_money)
+                .unwrap_or_default(),
+            String::new(),
+        ],
+        bold: true,
+    });
+    rows.extend(report.movers.iter().map(|r| spend_row("Top movers", r)));
+    rows.extend(report.vendor_share.iter().map(|v| PdfRow {
+        group: "Share of wallet (share of each period's spend)".to_string(),
+        cells: vec![
+            format!("{} (mostly {})", v.vendor_name, v.top_category),
+            fmt_money(v.total),
+            format!("{:.1}%", v.share_pct),
+            fmt_pct(v.previous_share_pct),
+            fmt_pct(v.current_share_pct),
+            match (v.previous_share_pct, v.current_share_pct) {
+                (Some(p), Some(c)) => format!("{:+.1} pts", c - p),
+                _ => String::new(),
+            },
+        ],
+        bold: false,
+    }));
+    let compared = match report.periods.len() {
+        0 => String::new(),
+        1 => format!("; current {} {}", report.period, report.periods[0]),
+        n => format!(
+            "; current {} {} vs {}",
+            report.period,
+            report.periods[n - 1],
+            report.periods[n - 2]
+        ),
+    };
+    let subtitle = format!(
+        "{} to {} — {} across {} invoices{}",
+        report.date_from,
+        report.date_to,
+        fmt_money(report.total),
+        report.invoice_count,
+        compared
+    );
+    write_table_pdf(
+        &output_path,
+        "4chef Spend Analytics",
+        "4chef — Spend Analytics",
+        Some(&subtitle),
+        &columns,
+        &rows,
+        true,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Exported {} rows to {}", report.rows.len(), output_path),
+    })
+}
 
-        let purch_unit_id: Option<i64> = conn
-            .query_row(
-                "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 AND is_default = 1 LIMIT 1",
-                [line.item_id],
-                |row| row.get(0),
-            )
-            .ok()
-            .or_else(|| {
-                conn.query_row(
-                    "SELECT purch_unit_id FROM inv_units WHERE item_id = ?1 LIMIT 1",
-                    [line.item_id],
-                    |row| row.get(0),
-                )
-                .ok()
-            });
+// ── Accounting periods and COGS ──
 
-        let price_vendor: Option<(f64, i64)> = conn
-            .query_row(
-                "SELECT price, vendor_id FROM inv_prices WHERE item_id = ?1 ORDER BY vendor_id LIMIT 1",
-                [line.item_id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
-            )
-            .ok()
-            .or_else(|| {
-                conn.query_row(
-                    "SELECT price, vendor_id FROM trans WHERE item_id = ?1 AND price IS NOT NULL AND price > 0 ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
-                    [line.item_id],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
-                )
-                .ok()
-            });
-        let price = price_vendor.map(|v| v.0);
-        let price_vendor_id = price_vendor.map(|v| v.1);
+#[derive(Serialize)]
+struct AccountingPeriodResponse {
+    period_id: i64,
+    name: String,
+    start_date: String,
+    end_date: String,
+    opening_session_id: Option<i64>,
+    closing_session_id: Option<i64>,
+    status: String,
+    notes: String,
+}
+
+impl From<cogs::Period> for AccountingPeriodResponse {
+    fn from(p: cogs::Period) -> Self {
+        AccountingPeriodResponse {
+            period_id: p.period_id,
+            name: p.name,
+            start_date: p.start_date,
+            end_date: p.end_date,
+            opening_session_id: p.opening_session_id,
+            closing_session_id: p.closing_session_id,
+            status: p.status,
+            notes: p.notes,
+        }
+    }
+}
 
-        let mut factor: Option<f64> = None;
-        if let (Some(recipe_unit_id), Some(pu_id)) = (line.unit_id, purch_unit_id) {
-            if recipe_unit_id == pu_id {
-                factor = Some(1.0);
-            } else {
-                let edges = build_conversion_edges(&conn, line.item_id, price_vendor_id)?;
-                if let Some((f, _)) = bfs_conversion_factor(&edges, recipe_unit_id, pu_id, 6) {
-                    factor = Some(f);
-                }
-            }
+#[derive(serde::Deserialize)]
+struct AccountingPeriodInput {
+    period_id: Option<i64>,
+    name: String,
+    start_date: String,
+    end_date: String,
+    opening_session_id: Option<i64>,
+    closing_session_id: Option<i64>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CogsLineResponse {
+    category: String,
+    opening: f64,
+    purchases: f64,
+    closing: f64,
+    cogs: f64,
+    revenue: Option<f64>,
+    cost_pct: Option<f64>,
+}
+
+impl From<cogs::CogsLine> for CogsLineResponse {
+    fn from(l: cogs::CogsLine) -> Self {
+        CogsLineResponse {
+            category: l.category,
+            opening: l.opening,
+            purchases: l.purchases,
+            closing: l.closing,
+            cogs: l.cogs,
+            revenue: l.revenue,
+            cost_pct: l.cost_pct,
         }
+    }
+}
 
-        let (cost_status, extended_cost) = if line.qty <= 0.0 {
-            ("Missing qty".to_string(), None)
-        } else if purch_unit_id.is_none() {
-            ("Missing purch unit".to_string(), None)
-        } else if price.is_none() {
-            ("Missing price".to_string(), None)
-        } else if factor.is_none() {
-            ("Needs conversion".to_string(), None)
-        } else {
-            let cost = line.qty * factor.unwrap_or(1.0) * price.unwrap_or(0.0);
-            ("OK".to_string(), Some(cost))
-        };
+#[derive(Serialize)]
+struct CogsMappingResponse {
+    food_category: String,
+    cogs_category: String,
+}
 
-        if let Some(c) = extended_cost {
-            total_cost += c;
-        } else {
-            missing_costs += 1;
+#[derive(Serialize)]
+struct CogsReportResponse {
+    period: AccountingPeriodResponse,
+    opening_session: Option<String>,
+    closing_session: Option<String>,
+    lines: Vec<CogsLineResponse>,
+    total: CogsLineResponse,
+    mapping: Vec<CogsMappingResponse>,
+    warnings: Vec<String>,
+}
+
+impl From<cogs::CogsReport> for CogsReportResponse {
+    fn from(r: cogs::CogsReport) -> Self {
+        CogsReportResponse {
+            period: r.period.into(),
+            opening_session: r.opening_session.map(|(_, label)| label),
+            closing_session: r.closing_session.map(|(_, label)| label),
+            lines: r.lines.into_iter().map(Into::into).collect(),
+            total: r.total.into(),
+            mapping: r
+                .mapping
+                .into_iter()
+                .map(|(food_category, cogs_category)| CogsMappingResponse {
+                    food_category,
+                    cogs_category,
+                })
+                .collect(),
+            warnings: r.warnings,
         }
+    }
+}
+
+#[tauri::command]
+fn list_accounting_periods(app: tauri::AppHandle) -> Result<Vec<AccountingPeriodResponse>, String> {
+    let conn = open_initialized_db(&app)?;
+    Ok(cogs::list_periods(&conn)?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+#[tauri::command]
+fn save_accounting_period(
+    app: tauri::AppHandle,
+    period: AccountingPeriodInput,
+) -> Result<PatchResponse, String> {
+    let date = |d: &str| sales::normalize_date(d).ok_or_else(|| format!("Invalid date '{}'", d));
+    let period = cogs::Period {
+        period_id: period.period_id.unwrap_or(0),
+        name: period.name.trim().to_string(),
+        start_date: date(&period.start_date)?,
+        end_date: date(&period.end_date)?,
+        opening_session_id: period.opening_session_id,
+        closing_session_id: period.closing_session_id,
+        status: "open".to_string(),
+        notes: period.notes.unwrap_or_default().trim().to_string(),
+    };
+    let conn = open_initialized_db(&app)?;
+    let period_id = cogs::save_period(&conn, &period)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Saved period {}", period_id),
+    })
+}
+
+#[tauri::command]
+fn delete_accounting_period(
+    app: tauri::AppHandle,
+    period_id: i64,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| cogs::delete_period(tx, period_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Deleted period".to_string(),
+    })
+}
+
+#[tauri::command]
+fn get_cogs_report(app: tauri::AppHandle, period_id: i64) -> Result<CogsReportResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    Ok(cogs::cogs_report(&conn, period_id)?.into())
+}
+
+#[tauri::command]
+fn set_period_revenue(
+    app: tauri::AppHandle,
+    period_id: i64,
+    category: String,
+    amount: Option<f64>,
+) -> Result<PatchResponse, String> {
+    if amount.is_some_and(|a| a < 0.0) {
+        return Err("Revenue can't be negative".to_string());
+    }
+    let conn = open_initialized_db(&app)?;
+    cogs::set_revenue(&conn, period_id, category.trim(), amount, "manual")?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Saved {} revenue", category.trim()),
+    })
+}
+
+#[tauri::command]
+fn import_period_revenue(app: tauri::AppHandle, period_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let total = with_tx(&mut conn, |tx| cogs::import_pos_revenue(tx, period_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!("Imported {} of POS sales as revenue", fmt_money(total)),
+    })
+}
+
+#[tauri::command]
+fn set_cogs_category(
+    app: tauri::AppHandle,
+    food_category: String,
+    cogs_category: String,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    cogs::set_category_mapping(&conn, food_category.trim(), cogs_category.trim())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "{} now reports as {}",
+            food_category.trim(),
+            cogs_category.trim()
+        ),
+    })
+}
 
-        out_lines.push(FoodCostLineOutput {
-            item_id: line.item_id,
-            item_name,
-            unit_id: line.unit_id,
-            unit_name,
-            qty: line.qty,
-            price,
-            extended_cost,
-            cost_status,
-        });
-    }
+#[tauri::command]
+fn close_accounting_period(app: tauri::AppHandle, period_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let report = with_tx(&mut conn, |tx| cogs::close_period(tx, period_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Closed {}: COGS {}{}",
+            report.period.name,
+            fmt_money(report.total.cogs),
+            report
+                .total
+                .cost_pct
+                .map(|p| format!(", {:.1}% of revenue", p))
+                .unwrap_or_default()
+        ),
+    })
+}
 
-    Ok(FoodCostResponse {
-        lines: out_lines,
-        total_cost,
-        missing_costs,
+#[tauri::command]
+fn reopen_accounting_period(
+    app: tauri::AppHandle,
+    period_id: i64,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    cogs::reopen_period(&conn, period_id)?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Reopened period".to_string(),
     })
 }
 
-#[derive(Serialize)]
-struct SettingsResponse {
-    company_name: String,
-    logo_path: String,
-    service_category: String,
-    operation_size: String,
+fn cogs_cells(line: &cogs::CogsLine) -> [String; 7] {
+    [
+        format!("{:.2}", line.opening),
+        format!("{:.2}", line.purchases),
+        format!("{:.2}", line.closing),
+        format!("{:.2}", line.cogs),
+        line.revenue
+            .map(|r| format!("{:.2}", r))
+            .unwrap_or_default(),
+        line.cost_pct
+            .map(|p| format!("{:.1}", p))
+            .unwrap_or_default(),
+        String::new(),
+    ]
 }
 
 #[tauri::command]
-fn get_settings(app: tauri::AppHandle) -> Result<SettingsResponse, String> {
+fn export_cogs_csv(
+    app: tauri::AppHandle,
+    period_id: i64,
+    output_path: String,
+) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
-    fn get_val(conn: &rusqlite::Connection, key: &str) -> String {
-        conn.query_row(
-            "SELECT value FROM settings WHERE key = ?1",
-            [key],
-            |row| row.get(0),
-        )
-        .unwrap_or_default()
-    }
-    Ok(SettingsResponse {
-        company_name: get_val(&conn, "company_name"),
-        logo_path: get_val(&conn, "logo_path"),
-        service_category: get_val(&conn, "service_category"),
-        operation_size: get_val(&conn, "operation_size"),
+    let report = cogs::cogs_report(&conn, period_id)?;
+    let mut wtr = csv::Writer::from_path(&output_path).map_err(|e| e.to_string())?;
+    wtr.write_record([
+        "Period",
+        "Start",
+        "End",
+        "Category",
+        "OpeningInventory",
+        "Purchases",
+        "ClosingInventory",
+        "COGS",
+        "Revenue",
+        "CostPct",
+    ])
+    .map_err(|e| e.to_string())?;
+    for line in report.lines.iter().chain(std::iter::once(&report.total)) {
+        let mut record = vec![
+            report.period.name.clone(),
+            report.period.start_date.clone(),
+            report.period.end_date.clone(),
+            line.category.clone(),
+        ];
+        record.extend(cogs_cells(line).into_iter().take(6));
+        wtr.write_record(&record).map_err(|e| e.to_string())?;
+    }
+    wtr.flush().map_err(|e| e.to_string())?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported COGS for {} to {}",
+            report.period.name, output_path
+        ),
     })
 }
 
 #[tauri::command]
-fn save_settings(
+fn export_cogs_pdf(
     app: tauri::AppHandle,
-    company_name: String,
-    service_category: String,
-    operation_size: String,
+    period_id: i64,
+    output_path: String,
 ) -> Result<PatchResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let pairs = [
-        ("company_name", company_name.trim().to_string()),
-        ("service_category", service_category.trim().to_string()),
-        ("operation_size", operation_size.trim().to_string()),
+    let report = cogs::cogs_report(&conn, period_id)?;
+    let columns = [
+        PdfColumn {
+            header: "Category",
+            x: 0.0,
+            size: 9.0,
+            max_chars: Some(20),
+        },
+        PdfColumn {
+            header: "Opening",
+            x: 32.0,
+            size: 9.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Purchases",
+            x: 58.0,
+            size: 9.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Closing",
+            x: 84.0,
+            size: 9.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "COGS",
+            x: 110.0,
+            size: 9.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Revenue",
+            x: 136.0,
+            size: 9.0,
+            max_chars: None,
+        },
+        PdfColumn {
+            header: "Cost %",
+            x: 162.0,
+            size: 9.0,
+            max_chars: None,
+        },
     ];
-    for (key, value) in &pairs {
-        conn.execute(
-            "INSERT INTO settings (key, value) VALUES (?1, ?2)
-             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
-            (key, value),
+    let row = |line: &cogs::CogsLine, bold: bool| PdfRow {
+        group: String::new(),
+        cells: vec![
+            if bold {
+                "Total".to_string()
+            } else {
+                line.category.clone()
+            },
+            fmt_money(line.opening),
+            fmt_money(line.purchases),
+            fmt_money(line.closing),
+            fmt_money(line.cogs),
+            line.revenue
+                .map(fmt_money)
+                .unwrap_or_else(|| "-".to_string()),
+            fmt_pct(line.cost_pct),
+        ],
+        bold,
+    };
+    let mut rows: Vec<PdfRow> = report.lines.iter().map(|l| row(l, false)).collect();
+    rows.push(row(&report.total, true));
+    let subtitle = format!(
+        "{} ({} to {}, {}) — opening {}, closing {}",
+        report.period.name,
+        report.period.start_date,
+        report.period.end_date,
+        report.period.status,
+        report
+            .opening_session
+            .as_ref()
+            .map(|(_, l)| l.as_str())
+            .unwrap_or("none"),
+        report
+            .closing_session
+            .as_ref()
+            .map(|(_, l)| l.as_str())
+            .unwrap_or("none"),
+    );
+    write_table_pdf(
+        &output_path,
+        "4chef Cost of Goods Sold",
+        "4chef — Cost of Goods Sold",
+        Some(&subtitle),
+        &columns,
+        &rows,
+        false,
+    )?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Exported COGS for {} to {}",
+            report.period.name, output_path
+        ),
+    })
+}
+
+// ── Landed cost ──
+
+#[derive(serde::Deserialize)]
+struct InvoiceChargeInput {
+    charge_id: Option<i64>,
+    invoice_id: i64,
+    kind: String,
+    description: Option<String>,
+    amount: f64,
+    allocation: Option<String>,
+}
+
+fn allocation_message(allocation: &landed::InvoiceAllocation) -> String {
+    let mut message = format!(
+        "Allocated {} across {} lines",
+        fmt_money(allocation.allocated),
+        allocation.lines
+    );
+    if allocation.unallocated.abs() > 0.005 {
+        message.push_str(&format!(
+            "; {} not allocated",
+            fmt_money(allocation.unallocated)
+        ));
+    }
+    for c in &allocation.charges {
+        if c.allocated != 0.0 {
+            message.push_str(&format!("; {} by {}", c.charge.description, c.method));
+        }
+        if !c.note.is_empty() {
+            message.push_str(&format!(" ({}: {})", c.charge.description, c.note));
+        }
+    }
+    message
+}
+
+#[tauri::command]
+fn save_invoice_charge(
+    app: tauri::AppHandle,
+    charge: InvoiceChargeInput,
+) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let kind = charge.kind.trim().to_lowercase();
+    let description = charge
+        .description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| {
+            let mut label = kind.clone();
+            if let Some(first) = label.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            label
+        });
+    let charge = landed::Charge {
+        charge_id: charge.charge_id.unwrap_or(0),
+        invoice_id: charge.invoice_id,
+        kind,
+        description,
+        amount: charge.amount,
+        allocation: charge.allocation.unwrap_or_else(|| "default".to_string()),
+    };
+    let allocation = with_tx(&mut conn, |tx| {
+        landed::save_charge(tx, &charge)?;
+        let allocation = landed::allocate_invoice(tx, charge.invoice_id)?;
+        reconcile::reconcile_invoice(tx, charge.invoice_id)?;
+        Ok(allocation)
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: allocation_message(&allocation),
+    })
+}
+
+#[tauri::command]
+fn delete_invoice_charge(app: tauri::AppHandle, charge_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let allocation = with_tx(&mut conn, |tx| {
+        let invoice_id: i64 = tx
+            .query_row(
+                "SELECT invoice_id FROM invoice_charges WHERE charge_id = ?1",
+                [charge_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("Charge {} not found", charge_id))?;
+        tx.execute(
+            "DELETE FROM invoice_charges WHERE charge_id = ?1",
+            [charge_id],
         )
         .map_err(|e| e.to_string())?;
-    }
+        let allocation = landed::allocate_invoice(tx, invoice_id)?;
+        reconcile::reconcile_invoice(tx, invoice_id)?;
+        Ok(allocation)
+    })?;
     Ok(PatchResponse {
         ok: true,
-        message: "Settings saved".to_string(),
+        message: format!("Deleted charge. {}", allocation_message(&allocation)),
     })
 }
 
 #[tauri::command]
-fn upload_logo(app: tauri::AppHandle, source_path: String) -> Result<PatchResponse, String> {
-    let source = std::path::Path::new(&source_path);
-    if !source.exists() || !source.is_file() {
-        return Err(format!("File not found: {}", source_path));
+fn set_vendor_tax(
+    app: tauri::AppHandle,
+    vendor_id: i64,
+    prices_include_tax: bool,
+    tax_rate: Option<f64>,
+) -> Result<PatchResponse, String> {
+    if tax_rate.is_some_and(|r| !(0.0..100.0).contains(&r)) {
+        return Err("Tax rate must be between 0 and 100".to_string());
     }
+    let mut conn = open_initialized_db(&app)?;
+    let count = with_tx(&mut conn, |tx| {
+        let updated = tx
+            .execute(
+                "UPDATE vendors SET prices_include_tax = ?2, tax_rate = ?3 WHERE vendor_id = ?1",
+                rusqlite::params![vendor_id, prices_include_tax as i64, tax_rate],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Vendor {} not found", vendor_id));
+        }
+        landed::allocate_all(tx, Some(vendor_id))
+    })?;
+    Ok(PatchResponse {
+        ok: true,
+        message: format!(
+            "Saved tax terms; landed costs recalculated for {} invoices",
+            count
+        ),
+    })
+}
 
-    let ext = source
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .unwrap_or_else(|| "png".to_string());
-    if !matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp") {
-        return Err(format!("Unsupported image format: .{}", ext));
-    }
+#[derive(Serialize)]
+struct DiscrepancyResponse {
+    trans_id: Option<i64>,
+    kind: String,
+    expected: Option<f64>,
+    actual: Option<f64>,
+    message: String,
+}
 
-    let data_dir = app
-        .path()
-        .app_data_dir()
-        .unwrap_or_else(|_| fallback_data_root());
-    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
-    let dest = data_dir.join(format!("company_logo.{}", ext));
-    std::fs::copy(source, &dest).map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct InvoiceReconResponse {
+    invoice_id: i64,
+    invoice_no: String,
+    invoice_date: String,
+    vendor_name: String,
+    status: String,
+    lines_total: f64,
+    charges_total: f64,
+    credits_total: f64,
+    header_total: Option<f64>,
+    difference: Option<f64>,
+    checked_at: String,
+    note: String,
+    discrepancies: Vec<DiscrepancyResponse>,
+}
+
+impl From<reconcile::InvoiceRecon> for InvoiceReconResponse {
+    fn from(r: reconcile::InvoiceRecon) -> Self {
+        Self {
+            invoice_id: r.invoice_id,
+            invoice_no: r.invoice_no,
+            invoice_date: r.invoice_date,
+            vendor_name: r.vendor_name,
+            status: r.status,
+            lines_total: r.lines_total,
+            charges_total: r.charges_total,
+            credits_total: r.credits_total,
+            header_total: r.header_total,
+            difference: r.difference,
+            checked_at: r.checked_at,
+            note: r.note,
+            discrepancies: r
+                .discrepancies
+                .into_iter()
+                .map(|d| DiscrepancyResponse {
+                    trans_id: d.trans_id,
+                    kind: d.kind,
+                    expected: d.expected,
+                    actual: d.actual,
+                    message: d.message,
+                })
+                .collect(),
+        }
+    }
+}
 
-    let dest_str = dest.to_string_lossy().to_string();
-    let conn = open_initialized_db(&app)?;
-    conn.execute(
-        "INSERT INTO settings (key, value) VALUES ('logo_path', ?1)
-         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
-        [&dest_str],
-    )
-    .map_err(|e| e.to_string())?;
+#[derive(Serialize)]
+struct ReconciliationResponse {
+    balanced: i64,
+    unbalanced: i64,
+    reviewed: i64,
+    matching: i64,
+    invoices: Vec<InvoiceReconResponse>,
+}
 
+#[tauri::command]
+fn run_reconciliation(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    let summary = with_tx(&mut conn, reconcile::reconcile_all)?;
     Ok(PatchResponse {
         ok: true,
-        message: format!("Logo saved to {}", dest_str),
+        message: format!(
+            "Checked {} invoices: {} balanced, {} unbalanced, {} reviewed",
+            summary.checked, summary.balanced, summary.unbalanced, summary.reviewed
+        ),
     })
 }
 
 #[tauri::command]
-fn remove_logo(app: tauri::AppHandle) -> Result<PatchResponse, String> {
+fn list_invoice_reconciliation(
+    app: tauri::AppHandle,
+    status: Option<String>,
+) -> Result<ReconciliationResponse, String> {
     let conn = open_initialized_db(&app)?;
-    let logo_path: String = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'logo_path'",
-            [],
+    let status = status
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty() && s != "all");
+    let count = |s: &str| -> Result<i64, String> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM invoice_reconciliation WHERE status = ?1",
+            [s],
             |row| row.get(0),
         )
-        .unwrap_or_default();
-
-    if !logo_path.is_empty() {
-        let p = std::path::Path::new(&logo_path);
-        if p.exists() {
-            let _ = std::fs::remove_file(p);
-        }
-    }
-
-    conn.execute(
-        "INSERT INTO settings (key, value) VALUES ('logo_path', '')
-         ON CONFLICT(key) DO UPDATE SET value = ''",
-        [],
-    )
-    .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())
+    };
+    let (invoices, matching) = reconcile::list_reconciliation(&conn, status.as_deref(), 200)?;
+    Ok(ReconciliationResponse {
+        balanced: count("balanced")?,
+        unbalanced: count("unbalanced")?,
+        reviewed: count("reviewed")?,
+        matching,
+        invoices: invoices
+            .into_iter()
+            .map(InvoiceReconResponse::from)
+            .collect(),
+    })
+}
 
+#[tauri::command]
+fn set_invoice_reviewed(
+    app: tauri::AppHandle,
+    invoice_id: i64,
+    reviewed: bool,
+    note: Option<String>,
+) -> Result<PatchResponse, String> {
+    let conn = open_initialized_db(&app)?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let status = reconcile::set_reviewed(&conn, invoice_id, reviewed, note.as_deref())?;
     Ok(PatchResponse {
         ok: true,
-        message: "Logo removed".to_string(),
+        message: format!("Invoice is {}", status),
     })
 }
 
+#[derive(serde::Deserialize)]
+struct CreditMemoInput {
+    invoice_id: i64,
+    memo_no: Option<String>,
+    memo_date: Option<String>,
+    reason: String,
+    amount: Option<f64>,
+    trans_id: Option<i64>,
+    qty: Option<f64>,
+    note: Option<String>,
+}
+
 #[tauri::command]
-fn update_item_category(
+fn save_credit_memo(
     app: tauri::AppHandle,
-    item_id: i64,
-    food_category: String,
-    storage_type: String,
+    credit: CreditMemoInput,
 ) -> Result<PatchResponse, String> {
-    let conn = open_initialized_db(&app)?;
-    let fc: Option<String> = if food_category.trim().is_empty() { None } else { Some(food_category.trim().to_string()) };
-    let st: Option<String> = if storage_type.trim().is_empty() { None } else { Some(storage_type.trim().to_string()) };
-
-    let updated = conn
-        .execute(
-            "UPDATE items SET food_category = ?2, storage_type = ?3 WHERE item_id = ?1",
-            (item_id, &fc, &st),
+    let memo_date = match credit.memo_date.filter(|d| !d.trim().is_empty()) {
+        Some(d) => {
+            Some(sales::normalize_date(&d).ok_or_else(|| format!("Invalid credit date '{}'", d))?)
+        }
+        None => None,
+    };
+    let memo = reconcile::NewCreditMemo {
+        invoice_id: credit.invoice_id,
+        memo_no: credit.memo_no.unwrap_or_default().trim().to_string(),
+        memo_date,
+        reason: credit.reason.trim().to_lowercase(),
+        amount: credit.amount,
+        trans_id: credit.trans_id,
+        qty: credit.qty,
+        note: credit.note.unwrap_or_default().trim().to_string(),
+    };
+    let mut conn = open_initialized_db(&app)?;
+    let status = with_tx(&mut conn, |tx| {
+        reconcile::add_credit_memo(tx, &memo)?;
+        tx.query_row(
+            "SELECT status FROM invoice_reconciliation WHERE invoice_id = ?1",
+            [memo.invoice_id],
+            |row| row.get::<_, String>(0),
         )
-        .map_err(|e| e.to_string())?;
-    if updated == 0 {
-        return Err(format!("Item {} not found", item_id));
-    }
+        .map_err(|e| e.to_string())
+    })?;
     Ok(PatchResponse {
         ok: true,
-        message: "Categories updated".to_string(),
+        message: format!("Recorded credit memo; invoice is {}", status),
+    })
+}
+
+#[tauri::command]
+fn delete_credit_memo(app: tauri::AppHandle, credit_id: i64) -> Result<PatchResponse, String> {
+    let mut conn = open_initialized_db(&app)?;
+    with_tx(&mut conn, |tx| reconcile::delete_credit_memo(tx, credit_id))?;
+    Ok(PatchResponse {
+        ok: true,
+        message: "Deleted credit memo".to_string(),
     })
 }
 
@@ -4807,9 +10404,12 @@ pub fn run() {
             list_vendors_all,
             list_units_simple,
             list_items_simple,
+            list_recipes_simple,
             search_inventory,
             search_recipes,
+            refresh_recipe_costs,
             get_recipe_detail,
+            scale_recipe,
             get_conversion_overview,
             list_conv_suggestions,
             list_conv_todo,
@@ -4825,6 +10425,8 @@ pub fn run() {
             update_item,
             update_vendor,
             update_recipe,
+            update_recipe_yield,
+            update_recipe_pricing,
             add_recp_item,
             upsert_convunit,
             update_invoice,
@@ -4845,10 +10447,122 @@ pub fn run() {
             remove_logo,
             export_fda_pdf,
             global_search,
-            update_item_category
+            update_item_category,
+            update_item_yield,
+            list_item_preps,
+            upsert_item_prep,
+            delete_item_prep,
+            set_recp_item_prep,
+            record_yield_test,
+            list_pos_profiles,
+            save_pos_profile,
+            delete_pos_profile,
+            import_sales_csv,
+            list_sales_imports,
+            delete_sales_import,
+            list_pos_items,
+            map_pos_item,
+            get_menu_engineering,
+            export_menu_engineering_csv,
+            export_menu_engineering_pdf,
+            record_inventory_count,
+            delete_inventory_count,
+            get_variance_report,
+            list_count_sessions,
+            create_count_session,
+            get_count_sheet,
+            save_count_line,
+            set_item_shelf_order,
+            close_count_session,
+            delete_count_session,
+            get_inventory_valuation,
+            export_count_sheet_pdf,
+            export_inventory_valuation_pdf,
+            save_par_level,
+            delete_par_level,
+            get_order_guide,
+            export_order_guide_csv,
+            export_order_guide_pdf,
+            list_purchase_orders,
+            create_purchase_order,
+            create_pos_from_order_guide,
+            get_purchase_order,
+            save_po_line,
+            delete_po_line,
+            set_purchase_order_status,
+            delete_purchase_order,
+            receive_purchase_order,
+            export_purchase_order_csv,
+            export_purchase_order_pdf,
+            list_vendor_bids,
+            save_vendor_bid,
+            delete_vendor_bid,
+            import_legacy_bids,
+            import_bid_sheet,
+            get_bid_comparison,
+            award_vendor_bid,
+            export_bid_request_csv,
+            export_bid_request_pdf,
+            list_price_alerts,
+            set_price_alert_status,
+            get_cost_impact,
+            export_cost_impact_csv,
+            export_cost_impact_pdf,
+            save_invoice_charge,
+            delete_invoice_charge,
+            set_vendor_tax,
+            run_reconciliation,
+            list_invoice_reconciliation,
+            set_invoice_reviewed,
+            save_credit_memo,
+            delete_credit_memo,
+            get_spend_report,
+            export_spend_csv,
+            export_spend_pdf,
+            list_accounting_periods,
+            save_accounting_period,
+            delete_accounting_period,
+            get_cogs_report,
+            set_period_revenue,
+            import_period_revenue,
+            set_cogs_category,
+            close_accounting_period,
+            reopen_accounting_period,
+            export_cogs_csv,
+            export_cogs_pdf,
+            list_unit_standards,
+            set_unit_standard,
+            save_unit_alias,
+            delete_unit_alias,
+            refresh_standard_units,
+            clear_covered_conv_todo,
+            set_reference_densities,
+            update_item_density,
+            check_conversions,
+            fix_conversion_issue,
+            review_conv_suggestions,
+            preview_conv_suggestions,
+            accept_conv_suggestions,
+            reject_conv_suggestions,
+            get_pack_report,
+            update_pack,
+            reread_packs,
+            get_item_nutrition,
+            save_item_nutrition,
+            clear_item_nutrition,
+            link_item_food,
+            import_fdc_foods,
+            get_nutrition_coverage,
+            link_nutrition_matches,
+            confirm_nutrition_link,
+            get_recipe_nutrition,
+            save_item_label_name,
+            get_nutrition_label,
+            export_label_pdf,
+            export_label_png
         ])
         .setup(|app| {
-            let path = db_path(&app.handle())?;
+            let path = db_path(app.handle())?;
             if let Ok(conn) = open_db(&path) {
                 let _ = init_db(&conn);
             }
@@ -0,0 +1,1207 @@
+use crate::costing::{
+    best_conversion_path, build_conversion_edges, conversion_factor, ok_status, recipe_yield,
+    sub_recipe_for_line, ConvEdge, MAX_RECIPE_DEPTH,
+};
+use crate::pricing::recipe_servings;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A nutrient tracked per item. `sources` are the FoodData Central nutrient
+/// ids and numbers it is read from, preferred first.
+pub struct Nutrient {
+    /// Column name in `item_nutrition` and `fdc_foods`.
+    pub key: &'static str,
+    pub label: &'static str,
+    pub unit: &'static str,
+    sources: &'static [(i64, &'static str)],
+}
+
+/// Every nutrient, in the order values are stored and returned.
+pub const NUTRIENTS: &[Nutrient] = &[
+    Nutrient {
+        key: "calories",
+        label: "Calories",
+        unit: "kcal",
+        sources: &[(1008, "208"), (2048, "958"), (2047, "957")],
+    },
+    Nutrient {
+        key: "fat_g",
+        label: "Total Fat",
+        unit: "g",
+        sources: &[(1004, "204"), (1085, "298")],
+    },
+    Nutrient {
+        key: "sat_fat_g",
+        label: "Saturated Fat",
+        unit: "g",
+        sources: &[(1258, "606")],
+    },
+    Nutrient {
+        key: "trans_fat_g",
+        label: "Trans Fat",
+        unit: "g",
+        sources: &[(1257, "605")],
+    },
+    Nutrient {
+        key: "cholesterol_mg",
+        label: "Cholesterol",
+        unit: "mg",
+        sources: &[(1253, "601")],
+    },
+    Nutrient {
+        key: "sodium_mg",
+        label: "Sodium",
+        unit: "mg",
+        sources: &[(1093, "307")],
+    },
+    Nutrient {
+        key: "carbs_g",
+        label: "Total Carbohydrate",
+        unit: "g",
+        sources: &[(1005, "205"), (1050, "205.2")],
+    },
+    Nutrient {
+        key: "fiber_g",
+        label: "Dietary Fiber",
+        unit: "g",
+        sources: &[(1079, "291")],
+    },
+    Nutrient {
+        key: "sugars_g",
+        label: "Total Sugars",
+        unit: "g",
+        sources: &[(2000, "269"), (1063, "269.3")],
+    },
+    Nutrient {
+        key: "added_sugars_g",
+        label: "Added Sugars",
+        unit: "g",
+        sources: &[(1235, "539")],
+    },
+    Nutrient {
+        key: "protein_g",
+        label: "Protein",
+        unit: "g",
+        sources: &[(1003, "203")],
+    },
+    Nutrient {
+        key: "vitamin_d_mcg",
+        label: "Vitamin D",
+        unit: "mcg",
+        sources: &[(1114, "328")],
+    },
+    Nutrient {
+        key: "calcium_mg",
+        label: "Calcium",
+        unit: "mg",
+        sources: &[(1087, "301")],
+    },
+    Nutrient {
+        key: "iron_mg",
+        label: "Iron",
+        unit: "mg",
+        sources: &[(1089, "303")],
+    },
+    Nutrient {
+        key: "potassium_mg",
+        label: "Potassium",
+        unit: "mg",
+        sources: &[(1092, "306")],
+    },
+];
+
+/// Words left out when matching item names to foods: joiners and
+/// packaging that say nothing about the food itself.
+const IGNORED_WORDS: &[&str] = &[
+    "a", "and", "of", "or", "the", "with", "in", "for", "bag", "box", "bulk", "case", "cs", "ct",
+    "ea", "each", "gal", "lb", "oz", "pack", "pk",
+];
+
+/// Nutrient columns in `NUTRIENTS` order, for building queries.
+fn nutrient_columns() -> String {
+    NUTRIENTS
+        .iter()
+        .map(|n| n.key)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn nutrient_values(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Vec<Option<f64>>> {
+    (0..NUTRIENTS.len()).map(|i| row.get(offset + i)).collect()
+}
+
+/// The `NUTRIENTS` index and preference rank of a FoodData Central nutrient.
+fn nutrient_slot(id: Option<i64>, number: Option<&str>) -> Option<(usize, usize)> {
+    let number = number.map(str::trim).filter(|n| !n.is_empty());
+    NUTRIENTS.iter().enumerate().find_map(|(slot, nutrient)| {
+        nutrient
+            .sources
+            .iter()
+            .position(|(source_id, source_number)| {
+                Some(*source_id) == id || Some(*source_number) == number
+            })
+            .map(|rank| (slot, rank))
+    })
+}
+
+/// The weight unit nutrient amounts are based on, with its size in grams.
+/// Grams when set up, otherwise the first standard weight unit.
+fn gram_unit(conn: &Connection) -> Result<Option<(i64, f64)>, String> {
+    conn.query_row(
+        "SELECT unit_id, base_qty FROM unit_standards
+         WHERE dimension = 'weight' AND base_qty > 0
+         ORDER BY base_qty = 1 DESC, unit_id LIMIT 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// An item's nutrient amounts per `basis_qty` of `basis_unit_id`.
+pub struct ItemNutrition {
+    pub basis_qty: f64,
+    pub basis_unit_id: i64,
+    pub basis_unit_name: String,
+    /// In `NUTRIENTS` order; `None` where the source doesn't report it.
+    pub values: Vec<Option<f64>>,
+    /// "manual", "usda", or "usda-auto" for a best-match link nobody has
+    /// reviewed yet.
+    pub source: String,
+    pub fdc_id: Option<i64>,
+    /// FoodData Central description of the linked food.
+    pub description: String,
+    pub updated_at: String,
+}
+
+impl ItemNutrition {
+    /// "USDA 170000: Onions, raw", or "Entered by hand".
+    pub fn source_label(&self) -> String {
+        match (self.fdc_id, self.source.as_str()) {
+            (Some(fdc_id), "usda") => format!("USDA {}: {}", fdc_id, self.description),
+            (Some(fdc_id), "usda-auto") => format!(
+                "USDA {}: {} (auto-matched, not reviewed)",
+                fdc_id, self.description
+            ),
+            _ => "Entered by hand".to_string(),
+        }
+    }
+}
+
+pub fn item_nutrition(conn: &Connection, item_id: i64) -> Result<Option<ItemNutrition>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT n.basis_qty, n.basis_unit_id, COALESCE(u.sing, '-'), COALESCE(n.source, 'manual'),
+                    n.fdc_id, COALESCE(n.description, ''), COALESCE(n.updated_at, ''), {}
+             FROM item_nutrition n LEFT JOIN units u ON u.unit_id = n.basis_unit_id
+             WHERE n.item_id = ?1",
+            NUTRIENTS.iter().map(|n| format!("n.{}", n.key)).collect::<Vec<_>>().join(", ")
+        ),
+        [item_id],
+        |row| {
+            Ok(ItemNutrition {
+                basis_qty: row.get(0)?,
+                basis_unit_id: row.get(1)?,
+                basis_unit_name: row.get(2)?,
+                source: row.get(3)?,
+                fdc_id: row.get(4)?,
+                description: row.get(5)?,
+                updated_at: row.get(6)?,
+                values: nutrient_values(row, 7)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn require_item(conn: &Connection, item_id: i64) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM items WHERE item_id = ?1)",
+            [item_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Item {} not found", item_id));
+    }
+    Ok(())
+}
+
+fn store_nutrition(
+    conn: &Connection,
+    item_id: i64,
+    basis: (f64, i64),
+    values: &[Option<f64>],
+    source: &str,
+    food: Option<(i64, &str)>,
+) -> Result<(), String> {
+    let mut params: Vec<rusqlite::types::Value> =
+        vec![item_id.into(), basis.0.into(), basis.1.into()];
+    params.extend(values.iter().map(|v| rusqlite::types::Value::from(*v)));
+    params.push(source.to_string().into());
+    params.push(food.map(|f| f.0).into());
+    params.push(food.map(|f| f.1.to_string()).into());
+    let placeholders = (1..=params.len())
+        .map(|i| format!("?{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO item_nutrition
+               (item_id, basis_qty, basis_unit_id, {}, source, fdc_id, description, updated_at)
+             VALUES ({}, datetime('now', 'localtime'))",
+            nutrient_columns(),
+            placeholders
+        ),
+        rusqlite::params_from_iter(params),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Saves nutrient amounts entered by hand for `basis_qty` of a unit,
+/// replacing any linked food.
+pub fn set_item_nutrition(
+    conn: &Connection,
+    item_id: i64,
+    basis_qty: f64,
+    basis_unit_id: i64,
+    values: &[Option<f64>],
+) -> Result<(), String> {
+    if values.len() != NUTRIENTS.len() {
+        return Err(format!(
+            "Expected {} nutrient values, got {}",
+            NUTRIENTS.len(),
+            values.len()
+        ));
+    }
+    if basis_qty <= 0.0 {
+        return Err("Enter the amount the nutrients are for".to_string());
+    }
+    if values.iter().flatten().any(|v| *v < 0.0) {
+        return Err("Nutrient amounts can't be negative".to_string());
+    }
+    if values.iter().all(Option::is_none) {
+        return Err("Enter at least one nutrient amount".to_string());
+    }
+    require_item(conn, item_id)?;
+    store_nutrition(
+        conn,
+        item_id,
+        (basis_qty, basis_unit_id),
+        values,
+        "manual",
+        None,
+    )
+}
+
+pub fn clear_item_nutrition(conn: &Connection, item_id: i64) -> Result<bool, String> {
+    conn.execute("DELETE FROM item_nutrition WHERE item_id = ?1", [item_id])
+        .map(|n| n > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Copies a FoodData Central food's nutrients (per 100 g) onto an item.
+/// Returns the food's description.
+pub fn link_item(conn: &Connection, item_id: i64, fdc_id: i64) -> Result<String, String> {
+    link_food(conn, item_id, fdc_id, "usda")
+}
+
+fn link_food(conn: &Connection, item_id: i64, fdc_id: i64, source: &str) -> Result<String, String> {
+    require_item(conn, item_id)?;
+    let food: Option<(String, Vec<Option<f64>>)> = conn
+        .query_row(
+            &format!(
+                "SELECT description, {} FROM fdc_foods WHERE fdc_id = ?1",
+                nutrient_columns()
+            ),
+            [fdc_id],
+            |row| Ok((row.get(0)?, nutrient_values(row, 1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some((description, values)) = food else {
+        return Err(format!("Food {} hasn't been imported", fdc_id));
+    };
+    let Some((gram_id, grams)) = gram_unit(conn)? else {
+        return Err("Set up a standard weight unit such as grams before linking foods".to_string());
+    };
+    store_nutrition(
+        conn,
+        item_id,
+        (100.0 / grams, gram_id),
+        &values,
+        source,
+        Some((fdc_id, &description)),
+    )?;
+    Ok(description)
+}
+
+/// What an import read.
+pub struct FdcImport {
+    pub foods: usize,
+    /// Foods without any nutrient we track.
+    pub skipped: usize,
+    /// Linked items whose nutrients were updated from the new data.
+    pub refreshed: usize,
+}
+
+struct FdcFood {
+    description: String,
+    data_type: String,
+    values: Vec<Option<f64>>,
+    ranks: Vec<usize>,
+}
+
+impl FdcFood {
+    fn new(description: String, data_type: String) -> Self {
+        FdcFood {
+            description,
+            data_type,
+            values: vec![None; NUTRIENTS.len()],
+            ranks: vec![usize::MAX; NUTRIENTS.len()],
+        }
+    }
+
+    /// Keeps the amount unless a preferred source already gave one.
+    fn put(&mut self, id: Option<i64>, number: Option<&str>, amount: Option<f64>) {
+        let (Some((slot, rank)), Some(amount)) = (nutrient_slot(id, number), amount) else {
+            return;
+        };
+        if rank < self.ranks[slot] {
+            self.values[slot] = Some(amount);
+            self.ranks[slot] = rank;
+        }
+    }
+}
+
+/// Labels data types the way the JSON downloads do.
+fn data_type_label(raw: &str) -> String {
+    match raw.trim() {
+        "foundation_food" => "Foundation".to_string(),
+        "sr_legacy_food" => "SR Legacy".to_string(),
+        "survey_fndds_food" => "Survey (FNDDS)".to_string(),
+        "branded_food" => "Branded".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Generic foods first; branded products last.
+fn data_type_rank(data_type: &str) -> usize {
+    match data_type {
+        "Foundation" => 0,
+        "SR Legacy" => 1,
+        "Survey (FNDDS)" => 2,
+        "Branded" => 4,
+        _ => 3,
+    }
+}
+
+/// Reads a FoodData Central JSON download (Foundation, SR Legacy, Survey or
+/// Branded). Nutrients may be nested (`nutrient.id`) or flat (`nutrientId`,
+/// `number`).
+fn read_fdc_json(path: &Path) -> Result<HashMap<i64, FdcFood>, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let json: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| format!("Not a FoodData Central JSON file: {}", e))?;
+    let lists: Vec<&Vec<serde_json::Value>> = match &json {
+        serde_json::Value::Array(foods) => vec![foods],
+        serde_json::Value::Object(map) => map.values().filter_map(|v| v.as_array()).collect(),
+        _ => Vec::new(),
+    };
+    let mut foods = HashMap::new();
+    for entry in lists.into_iter().flatten() {
+        let (Some(fdc_id), Some(description)) =
+            (entry["fdcId"].as_i64(), entry["description"].as_str())
+        else {
+            continue;
+        };
+        let mut food = FdcFood::new(
+            description.trim().to_string(),
+            data_type_label(entry["dataType"].as_str().unwrap_or("")),
+        );
+        for n in entry["foodNutrients"].as_array().into_iter().flatten() {
+            let nested = &n["nutrient"];
+            let id = nested["id"].as_i64().or_else(|| n["nutrientId"].as_i64());
+            let number = nested["number"]
+                .as_str()
+                .or_else(|| n["number"].as_str())
+                .or_else(|| n["nutrientNumber"].as_str());
+            let amount = n["amount"].as_f64().or_else(|| n["value"].as_f64());
+            food.put(id, number, amount);
+        }
+        foods.insert(fdc_id, food);
+    }
+    if foods.is_empty() {
+        return Err("No foods found; expected a FoodData Central JSON download".to_string());
+    }
+    Ok(foods)
+}
+
+fn csv_reader(path: &Path) -> Result<(csv::Reader<std::fs::File>, csv::StringRecord), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    Ok((reader, headers))
+}
+
+fn csv_column(headers: &csv::StringRecord, name: &str) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| h.trim().eq_ignore_ascii_case(name))
+}
+
+/// Reads a FoodData Central CSV download: `food.csv`, `food_nutrient.csv`
+/// and, when present, `nutrient.csv` for nutrient numbers.
+fn read_fdc_csv(dir: &Path) -> Result<HashMap<i64, FdcFood>, String> {
+    let food_path = dir.join("food.csv");
+    let food_nutrient_path = dir.join("food_nutrient.csv");
+    if !food_path.is_file() || !food_nutrient_path.is_file() {
+        return Err(format!(
+            "Expected food.csv and food_nutrient.csv from a FoodData Central CSV download in {}",
+            dir.display()
+        ));
+    }
+
+    let (mut reader, headers) = csv_reader(&food_path)?;
+    let (Some(id_col), Some(desc_col)) = (
+        csv_column(&headers, "fdc_id"),
+        csv_column(&headers, "description"),
+    ) else {
+        return Err("food.csv needs fdc_id and description columns".to_string());
+    };
+    let type_col = csv_column(&headers, "data_type");
+    let mut foods = HashMap::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let Some(fdc_id) = record
+            .get(id_col)
+            .and_then(|v| v.trim().parse::<i64>().ok())
+        else {
+            continue;
+        };
+        let description = record.get(desc_col).unwrap_or("").trim().to_string();
+        let data_type = data_type_label(type_col.and_then(|c| record.get(c)).unwrap_or(""));
+        foods.insert(fdc_id, FdcFood::new(description, data_type));
+    }
+
+    let mut numbers: HashMap<i64, String> = HashMap::new();
+    let nutrient_path = dir.join("nutrient.csv");
+    if nutrient_path.is_file() {
+        let (mut reader, headers) = csv_reader(&nutrient_path)?;
+        if let (Some(id_col), Some(nbr_col)) = (
+            csv_column(&headers, "id"),
+            csv_column(&headers, "nutrient_nbr"),
+        ) {
+            for record in reader.records() {
+                let record = record.map_err(|e| e.to_string())?;
+                if let (Some(id), Some(nbr)) = (
+                    record
+                        .get(id_col)
+                        .and_then(|v| v.trim().parse::<i64>().ok()),
+                    record.get(nbr_col).map(str::trim).filter(|v| !v.is_empty()),
+                ) {
+                    numbers.insert(id, nbr.to_string());
+                }
+            }
+        }
+    }
+
+    let (mut reader, headers) = csv_reader(&food_nutrient_path)?;
+    let (Some(fdc_col), Some(nutrient_col), Some(amount_col)) = (
+        csv_column(&headers, "fdc_id"),
+        csv_column(&headers, "nutrient_id"),
+        csv_column(&headers, "amount"),
+    ) else {
+        return Err("food_nutrient.csv needs fdc_id, nutrient_id and amount columns".to_string());
+    };
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let parse_id = |col: usize| record.get(col).and_then(|v| v.trim().parse::<i64>().ok());
+        let (Some(fdc_id), Some(nutrient_id)) = (parse_id(fdc_col), parse_id(nutrient_col)) else {
+            continue;
+        };
+        if let Some(food) = foods.get_mut(&fdc_id) {
+            let amount = record
+                .get(amount_col)
+                .and_then(|v| v.trim().parse::<f64>().ok());
+            food.put(
+                Some(nutrient_id),
+                numbers.get(&nutrient_id).map(String::as_str),
+                amount,
+            );
+        }
+    }
+    Ok(foods)
+}
+
+/// Imports an offline FoodData Central download into `fdc_foods`: a JSON
+/// file, or a CSV folder given as the folder or any file in it. Foods
+/// already imported are replaced, and items linked to them pick up the new
+/// amounts.
+pub fn import_fdc(conn: &Connection, path: &str) -> Result<FdcImport, String> {
+    let path = Path::new(path);
+    let is_json = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+    let foods = if is_json {
+        read_fdc_json(path)?
+    } else if path.is_dir() {
+        read_fdc_csv(path)?
+    } else {
+        read_fdc_csv(path.parent().unwrap_or(Path::new(".")))?
+    };
+
+    let columns = nutrient_columns();
+    let placeholders = (1..=NUTRIENTS.len() + 3)
+        .map(|i| format!("?{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "INSERT OR REPLACE INTO fdc_foods (fdc_id, description, data_type, {}, imported_at)
+             VALUES ({}, datetime('now', 'localtime'))",
+            columns, placeholders
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut result = FdcImport {
+        foods: 0,
+        skipped: 0,
+        refreshed: 0,
+    };
+    let mut imported = HashSet::new();
+    for (fdc_id, food) in foods {
+        if food.description.is_empty() || food.values.iter().all(Option::is_none) {
+            result.skipped += 1;
+            continue;
+        }
+        let mut params: Vec<rusqlite::types::Value> = vec![
+            fdc_id.into(),
+            food.description.into(),
+            food.data_type.into(),
+        ];
+        params.extend(food.values.into_iter().map(rusqlite::types::Value::from));
+        stmt.execute(rusqlite::params_from_iter(params))
+            .map_err(|e| e.to_string())?;
+        imported.insert(fdc_id);
+        result.foods += 1;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT item_id, fdc_id, source FROM item_nutrition
+             WHERE source IN ('usda', 'usda-auto') AND fdc_id IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let linked: Vec<(i64, i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    for (item_id, fdc_id, source) in linked {
+        if imported.contains(&fdc_id) {
+            link_food(conn, item_id, fdc_id, &source)?;
+            result.refreshed += 1;
+        }
+    }
+    Ok(result)
+}
+
+/// Lowercased words of a name for matching, singular, without numbers or
+/// ignored words.
+fn match_words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in text.to_lowercase().split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty()
+            || word.chars().all(|c| c.is_ascii_digit())
+            || IGNORED_WORDS.contains(&word)
+        {
+            continue;
+        }
+        let word = if let Some(stem) = word.strip_suffix("ies").filter(|s| s.len() > 2) {
+            format!("{}y", stem)
+        } else if let Some(stem) = word.strip_suffix("oes").filter(|s| s.len() > 2) {
+            format!("{}o", stem)
+        } else if word.ends_with('s') && !word.ends_with("ss") && word.len() > 3 {
+            word[..word.len() - 1].to_string()
+        } else {
+            word.to_string()
+        };
+        if !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// An imported food that matches a name.
+pub struct FoodMatch {
+    pub fdc_id: i64,
+    pub description: String,
+    pub data_type: String,
+    /// 0 to 1.
+    pub score: f64,
+}
+
+/// Imported foods with their words, indexed by word.
+struct FoodIndex {
+    foods: Vec<(i64, String, String, Vec<String>)>,
+    by_word: HashMap<String, Vec<usize>>,
+}
+
+impl FoodIndex {
+    fn load(conn: &Connection) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare("SELECT fdc_id, description, COALESCE(data_type, '') FROM fdc_foods")
+            .map_err(|e| e.to_string())?;
+        let rows: Vec<(i64, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        let mut index = FoodIndex {
+            foods: Vec::with_capacity(rows.len()),
+            by_word: HashMap::new(),
+        };
+        for (fdc_id, description, data_type) in rows {
+            let words = match_words(&description);
+            for word in &words {
+                index
+                    .by_word
+                    .entry(word.clone())
+                    .or_default()
+                    .push(index.foods.len());
+            }
+            index.foods.push((fdc_id, description, data_type, words));
+        }
+        Ok(index)
+    }
+
+    /// The closest foods to a name. The score weighs how much of the name
+    /// the food covers over how much of the food is in the name, with a
+    /// bonus when the food's leading word (e.g. "Onions" in "Onions, raw")
+    /// is in the name.
+    fn best(&self, name: &str, limit: usize) -> Vec<FoodMatch> {
+        let wanted = match_words(name);
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for word in &wanted {
+            for idx in self.by_word.get(word).into_iter().flatten() {
+                *shared.entry(*idx).or_default() += 1;
+            }
+        }
+        let mut matches: Vec<(f64, usize, usize)> = shared
+            .into_iter()
+            .map(|(idx, count)| {
+                let words = &self.foods[idx].3;
+                let coverage = count as f64 / wanted.len() as f64;
+                let precision = count as f64 / words.len().max(1) as f64;
+                let lead = words.first().is_some_and(|w| wanted.contains(w));
+                let score =
+                    (0.7 * coverage + 0.3 * precision + if lead { 0.1 } else { 0.0 }).min(1.0);
+                (score, data_type_rank(&self.foods[idx].2), idx)
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then(a.1.cmp(&b.1))
+                .then(self.foods[a.2].1.len().cmp(&self.foods[b.2].1.len()))
+                .then(self.foods[a.2].0.cmp(&self.foods[b.2].0))
+        });
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(score, _, idx)| {
+                let (fdc_id, description, data_type, _) = &self.foods[idx];
+                FoodMatch {
+                    fdc_id: *fdc_id,
+                    description: description.clone(),
+                    data_type: data_type.clone(),
+                    score,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Imported foods closest to the text, best first.
+pub fn search_foods(conn: &Connection, text: &str, limit: usize) -> Result<Vec<FoodMatch>, String> {
+    Ok(FoodIndex::load(conn)?.best(text, limit))
+}
+
+/// An item used in recipes that has no nutrition yet, with its closest food.
+pub struct ItemMatch {
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub best: Option<FoodMatch>,
+}
+
+/// An item linked by `link_best_matches` that hasn't been reviewed.
+pub struct AutoLink {
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub fdc_id: i64,
+    pub description: String,
+}
+
+pub struct NutritionCoverage {
+    pub foods: i64,
+    /// Items on recipe lines.
+    pub recipe_items: i64,
+    pub with_nutrition: i64,
+    pub unmatched: Vec<ItemMatch>,
+    pub auto_linked: Vec<AutoLink>,
+}
+
+/// item_id, name, category, has nutrition, source, fdc_id, description
+type CoverageRow = (i64, String, String, bool, String, Option<i64>, String);
+
+/// How many recipe items have nutrition, the closest food for each one that
+/// doesn't, and the automatic links still to review.
+pub fn coverage(conn: &Connection) -> Result<NutritionCoverage, String> {
+    let foods: i64 = conn
+        .query_row("SELECT COUNT(*) FROM fdc_foods", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT i.item_id, COALESCE(i.name, ''), COALESCE(i.food_category, ''), n.item_id IS NOT NULL,
+                    COALESCE(n.source, ''), n.fdc_id, COALESCE(n.description, '')
+             FROM items i LEFT JOIN item_nutrition n ON n.item_id = i.item_id
+             WHERE i.item_id IN (SELECT item_id FROM recp_items WHERE sub_recipe_id IS NULL)
+               AND i.item_id NOT IN (SELECT item_id FROM recp_inv WHERE item_id IS NOT NULL)
+             ORDER BY i.name",
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<CoverageRow> = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let index = FoodIndex::load(conn)?;
+    let mut result = NutritionCoverage {
+        foods,
+        recipe_items: items.len() as i64,
+        with_nutrition: 0,
+        unmatched: Vec::new(),
+        auto_linked: Vec::new(),
+    };
+    for (item_id, item_name, category, has_nutrition, source, fdc_id, description) in items {
+        if has_nutrition {
+            result.with_nutrition += 1;
+            if let Some(fdc_id) = fdc_id.filter(|_| source == "usda-auto") {
+                result.auto_linked.push(AutoLink {
+                    item_id,
+                    item_name,
+                    category,
+                    fdc_id,
+                    description,
+                });
+            }
+            continue;
+        }
+        let best = index.best(&item_name, 1).into_iter().next();
+        result.unmatched.push(ItemMatch {
+            item_id,
+            item_name,
+            category,
+            best,
+        });
+    }
+    Ok(result)
+}
+
+/// Links every recipe item without nutrition to its closest food when the
+/// match scores at least `min_score` and the food's leading word (e.g.
+/// "Onions" in "Onions, raw") is in the item name. Links are marked
+/// "usda-auto" until reviewed. Returns how many were linked.
+pub fn link_best_matches(conn: &Connection, min_score: f64) -> Result<usize, String> {
+    let mut linked = 0;
+    for item in coverage(conn)?.unmatched {
+        let Some(food) = item.best.filter(|f| f.score + 1e-9 >= min_score) else {
+            continue;
+        };
+        let lead = match_words(&food.description).into_iter().next();
+        if !lead.is_some_and(|w| match_words(&item.item_name).contains(&w)) {
+            continue;
+        }
+        link_food(conn, item.item_id, food.fdc_id, "usda-auto")?;
+        linked += 1;
+    }
+    Ok(linked)
+}
+
+/// Marks an automatic link as reviewed. Returns whether there was one.
+pub fn confirm_auto_link(conn: &Connection, item_id: i64) -> Result<bool, String> {
+    let changed = conn
+        .execute(
+            "UPDATE item_nutrition SET source = 'usda' WHERE item_id = ?1 AND source = 'usda-auto'",
+            [item_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(changed > 0)
+}
+
+/// One recipe line's share of the batch's nutrients.
+pub struct NutritionLine {
+    pub recp_item_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub qty: Option<f64>,
+    pub unit_name: String,
+    pub sub_recipe_id: Option<i64>,
+    /// Weight of the line in grams, when it converts to weight.
+    pub grams: Option<f64>,
+    /// In `NUTRIENTS` order; `None` when the line or its source lacks it.
+    pub values: Vec<Option<f64>>,
+    pub status: String,
+    /// Where the nutrients came from, e.g. "USDA 170000: Onions, raw".
+    pub source: String,
+    /// Counted with every ingredient underneath it.
+    pub complete: bool,
+}
+
+/// Nutrients for a recipe batch and per serving.
+pub struct RecipeNutrition {
+    pub servings: Option<f64>,
+    pub lines: Vec<NutritionLine>,
+    /// Batch totals of what could be counted.
+    pub totals: Vec<f64>,
+    /// Only once every ingredient is counted.
+    pub per_serving: Option<Vec<f64>>,
+    /// Batch weight in grams when every line converts to weight.
+    pub total_grams: Option<f64>,
+    /// Ingredients (through sub-recipes) that couldn't be counted.
+    pub missing_lines: i64,
+    /// Ingredients counted whose source doesn't report each nutrient.
+    pub gaps: Vec<i64>,
+}
+
+struct SubRecipeTotals {
+    totals: Vec<f64>,
+    missing_lines: i64,
+    gaps: Vec<i64>,
+    total_grams: Option<f64>,
+}
+
+/// Recipes being rolled up (for cycle detection) and sub-recipe totals
+/// already worked out in this pass.
+#[derive(Default)]
+struct NutritionContext {
+    stack: Vec<i64>,
+    totals: HashMap<i64, SubRecipeTotals>,
+}
+
+/// Grams in `qty` of a unit, through the item's conversions.
+fn line_grams(edges: &[ConvEdge], gram: Option<(i64, f64)>, unit_id: i64, qty: f64) -> Option<f64> {
+    let (gram_id, grams) = gram?;
+    if unit_id == gram_id {
+        return Some(qty * grams);
+    }
+    conversion_factor(edges, unit_id, gram_id, 6).map(|(f, _)| qty * f * grams)
+}
+
+fn item_line(
+    conn: &Connection,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    gram: Option<(i64, f64)>,
+    line: &mut NutritionLine,
+) -> Result<(), String> {
+    let profile = item_nutrition(conn, item_id)?;
+    let edges = build_conversion_edges(conn, item_id, None)?;
+    if let (Some(unit_id), Some(qty)) = (unit_id, qty) {
+        line.grams = line_grams(&edges, gram, unit_id, qty);
+    }
+    let Some(profile) = profile else {
+        line.status = "No nutrition data".to_string();
+        return Ok(());
+    };
+    line.source = profile.source_label();
+    let Some(qty) = qty.filter(|q| *q > 0.0) else {
+        line.status = "Missing qty".to_string();
+        return Ok(());
+    };
+    let Some(unit_id) = unit_id else {
+        line.status = "Missing unit".to_string();
+        return Ok(());
+    };
+    let (factor, conversion) = if unit_id == profile.basis_unit_id {
+        (1.0, None)
+    } else {
+        match best_conversion_path(&edges, unit_id, profile.basis_unit_id, 6) {
+            Some(path) => (path.factor, Some(path)),
+            None => {
+                line.status = format!("Needs conversion to {}", profile.basis_unit_name);
+                return Ok(());
+            }
+        }
+    };
+    let share = qty * factor / profile.basis_qty;
+    line.values = profile
+        .values
+        .iter()
+        .map(|v| v.map(|v| v * share))
+        .collect();
+    line.status = ok_status("", conversion.as_ref());
+    line.complete = true;
+    Ok(())
+}
+
+/// Scales a sub-recipe's batch totals by the line's share of its yield.
+/// Returns how many ingredients couldn't be counted: those missing inside
+/// the sub-recipe, or the line itself when it can't be scaled.
+#[allow(clippy::too_many_arguments)]
+fn sub_recipe_line(
+    conn: &Connection,
+    sub_recipe_id: i64,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    ctx: &mut NutritionContext,
+    gaps: &mut [i64],
+    line: &mut NutritionLine,
+) -> Result<i64, String> {
+    line.source = "Sub-recipe".to_string();
+    if ctx.stack.contains(&sub_recipe_id) {
+        line.status = "Recipe cycle".to_string();
+        return Ok(1);
+    }
+    if ctx.stack.len() >= MAX_RECIPE_DEPTH {
+        line.status = "Nested too deep".to_string();
+        return Ok(1);
+    }
+    if !ctx.totals.contains_key(&sub_recipe_id) {
+        let sub = rollup(conn, sub_recipe_id, ctx)?;
+        ctx.totals.insert(
+            sub_recipe_id,
+            SubRecipeTotals {
+                totals: sub.totals,
+                missing_lines: sub.missing_lines,
+                gaps: sub.gaps,
+                total_grams: sub.total_grams,
+            },
+        );
+    }
+    let totals = &ctx.totals[&sub_recipe_id];
+
+    let yld = recipe_yield(conn, sub_recipe_id)?;
+    let (Some(yield_qty), Some(yield_unit)) = (yld.qty, yld.unit_id) else {
+        line.status = "Missing yield".to_string();
+        return Ok(1);
+    };
+    let Some(qty) = qty.filter(|q| *q > 0.0) else {
+        line.status = "Missing qty".to_string();
+        return Ok(1);
+    };
+    let (factor, conversion) = match unit_id {
+        Some(unit_id) if unit_id == yield_unit => (1.0, None),
+        Some(unit_id) => {
+            let edges = build_conversion_edges(conn, yld.item_id.unwrap_or(item_id), None)?;
+            match best_conversion_path(&edges, unit_id, yield_unit, 6) {
+                Some(path) => (path.factor, Some(path)),
+                None => {
+                    line.status = format!("Needs conversion to {}", yld.unit_name);
+                    return Ok(1);
+                }
+            }
+        }
+        None => {
+            line.status = "Missing unit".to_string();
+            return Ok(1);
+        }
+    };
+    let share = qty * factor / yield_qty;
+    line.values = totals.totals.iter().map(|v| Some(v * share)).collect();
+    line.grams = totals.total_grams.map(|g| g * share);
+    for (gap, sub_gap) in gaps.iter_mut().zip(&totals.gaps) {
+        *gap += sub_gap;
+    }
+    if totals.missing_lines > 0 {
+        line.status = format!("Sub-recipe incomplete ({} missing)", totals.missing_lines);
+    } else {
+        line.status = ok_status("sub-recipe", conversion.as_ref());
+        line.complete = true;
+    }
+    Ok(totals.missing_lines)
+}
+
+fn rollup(
+    conn: &Connection,
+    recipe_id: i64,
+    ctx: &mut NutritionContext,
+) -> Result<RecipeNutrition, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ri.recp_item_id, COALESCE(ri.item_id, 0), COALESCE(sr.name, i.name, '(unknown item)'), ri.unit_id,
+                    COALESCE(u.sing, '-'), ri.qty, ri.sub_recipe_id
+             FROM recp_items ri
+             LEFT JOIN items i ON i.item_id = ri.item_id
+             LEFT JOIN recipes sr ON sr.recipe_id = ri.sub_recipe_id
+             LEFT JOIN units u ON u.unit_id = ri.unit_id
+             WHERE ri.recipe_id = ?1
+             ORDER BY COALESCE(sr.name, i.name)",
+        )
+        .map_err(|e| e.to_string())?;
+    type RawLine = (
+        i64,
+        i64,
+        String,
+        Option<i64>,
+        String,
+        Option<f64>,
+        Option<i64>,
+    );
+    let raw: Vec<RawLine> = stmt
+        .query_map([recipe_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let gram = gram_unit(conn)?;
+    ctx.stack.push(recipe_id);
+    let mut result = RecipeNutrition {
+        servings: None,
+        lines: Vec::new(),
+        totals: vec![0.0; NUTRIENTS.len()],
+        per_serving: None,
+        total_grams: Some(0.0),
+        missing_lines: 0,
+        gaps: vec![0; NUTRIENTS.len()],
+    };
+    for (recp_item_id, item_id, item_name, unit_id, unit_name, qty, sub_recipe_id) in raw {
+        let sub_recipe_id = sub_recipe_for_line(conn, recipe_id, sub_recipe_id, item_id);
+        let mut line = NutritionLine {
+            recp_item_id,
+            item_id,
+            item_name,
+            qty,
+            unit_name,
+            sub_recipe_id,
+            grams: None,
+            values: vec![None; NUTRIENTS.len()],
+            status: String::new(),
+            source: String::new(),
+            complete: false,
+        };
+        match sub_recipe_id {
+            Some(sub_id) => {
+                result.missing_lines += sub_recipe_line(
+                    conn,
+                    sub_id,
+                    item_id,
+                    unit_id,
+                    qty,
+                    ctx,
+                    &mut result.gaps,
+                    &mut line,
+                )?;
+            }
+            None => {
+                item_line(conn, item_id, unit_id, qty, gram, &mut line)?;
+                if line.complete {
+                    for (gap, value) in result.gaps.iter_mut().zip(&line.values) {
+                        if value.is_none() {
+                            *gap += 1;
+                        }
+                    }
+                } else {
+                    result.missing_lines += 1;
+                }
+            }
+        }
+        for (total, value) in result.totals.iter_mut().zip(&line.values) {
+            *total += value.unwrap_or(0.0);
+        }
+        result.total_grams = result.total_grams.zip(line.grams).map(|(t, g)| t + g);
+        result.lines.push(line);
+    }
+    ctx.stack.pop();
+    if result.lines.is_empty() {
+        result.total_grams = None;
+    }
+    Ok(result)
+}
+
+/// Rolls nutrients up from a recipe's ingredients, converting each line to
+/// the unit its nutrients are given for (through densities and packs when
+/// needed) and following sub-recipes through their yield.
+pub fn recipe_nutrition(conn: &Connection, recipe_id: i64) -> Result<RecipeNutrition, String> {
+    let mut result = rollup(conn, recipe_id, &mut NutritionContext::default())?;
+    result.servings = recipe_servings(conn, recipe_id)?;
+    // Partial totals would understate every serving, so they stay batch-only.
+    if result.missing_lines == 0 {
+        result.per_serving = result
+            .servings
+            .map(|s| result.totals.iter().map(|t| t / s).collect());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{confirm_auto_link, coverage, item_nutrition, link_best_matches, match_words};
+    use rusqlite::Connection;
+
+    #[test]
+    fn match_words_are_singular_without_numbers() {
+        assert_eq!(match_words("Onions, raw"), vec!["onion", "raw"]);
+        assert_eq!(match_words("Cherries, 2 lb case"), vec!["cherry"]);
+        assert_eq!(match_words("Tomatoes, Roma"), vec!["tomato", "roma"]);
+        assert_eq!(match_words("Swiss cheese, swiss"), vec!["swiss", "cheese"]);
+        assert_eq!(match_words("Peas"), vec!["pea"]);
+    }
+
+    #[test]
+    fn automatic_links_need_the_leading_word_and_wait_for_review() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing) VALUES (1, 'g');
+             INSERT INTO unit_standards (unit_id, standard, dimension, base_qty, source) VALUES (1, 'g', 'weight', 1, 'manual');
+             INSERT INTO items (item_id, name) VALUES (10, 'Yellow Onions'), (11, 'Garlic Powder');
+             INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty) VALUES (1, 1, 10, 1, 100), (1, 2, 11, 1, 5);
+             INSERT INTO fdc_foods (fdc_id, description, calories) VALUES (1, 'Onions, raw', 40), (2, 'Spices, garlic powder', 331);",
+        )
+        .unwrap();
+
+        assert_eq!(link_best_matches(&conn, 0.5).unwrap(), 1);
+        let onions = item_nutrition(&conn, 10).unwrap().unwrap();
+        assert_eq!(onions.source, "usda-auto");
+        assert!(item_nutrition(&conn, 11).unwrap().is_none());
+        let linked = coverage(&conn).unwrap().auto_linked;
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].item_id, 10);
+
+        assert!(confirm_auto_link(&conn, 10).unwrap());
+        assert_eq!(item_nutrition(&conn, 10).unwrap().unwrap().source, "usda");
+        assert!(coverage(&conn).unwrap().auto_linked.is_empty());
+    }
+}
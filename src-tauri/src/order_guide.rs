@@ -0,0 +1,370 @@
+use crate::costing::{
+    build_conversion_edges, conversion_factor, convert_item_qty, default_purch_unit, item_price,
+};
+use crate::variance::{item_count, purchased_qty, received_lines, theoretical_usage};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+
+pub const DAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// A par for one storage area. `day_of_week` (0 = Sunday) overrides the
+/// every-day par (`None`) for that area on that day.
+pub struct ParLevel {
+    pub par_id: i64,
+    pub storage_type: String,
+    pub day_of_week: Option<i64>,
+    pub par_qty: f64,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+}
+
+pub struct OrderGuideLine {
+    pub item_id: i64,
+    pub item_name: String,
+    pub storage_type: String,
+    pub purch_unit_name: String,
+    /// Par, on hand and need are in the item's purchase unit.
+    pub par_qty: f64,
+    pub on_hand: Option<f64>,
+    /// Where `on_hand` came from, e.g. "Count 2026-10-01".
+    pub on_hand_basis: String,
+    pub need_qty: f64,
+    pub order_unit_id: i64,
+    pub order_unit_name: String,
+    /// Whole vendor packs to order.
+    pub order_qty: f64,
+    pub price: Option<f64>,
+    pub est_cost: Option<f64>,
+}
+
+pub struct VendorOrder {
+    pub vendor_id: Option<i64>,
+    pub vendor_name: String,
+    pub lines: Vec<OrderGuideLine>,
+    pub total_cost: f64,
+}
+
+pub struct OrderGuide {
+    pub order_date: String,
+    pub day_name: String,
+    pub theoretical: bool,
+    pub vendors: Vec<VendorOrder>,
+    /// Items with pars that couldn't be converted or have no purchase unit.
+    pub unresolved: Vec<String>,
+}
+
+/// Theoretical usage and purchases per item after a count date.
+type UsageSince = (HashMap<i64, f64>, HashMap<i64, f64>);
+
+pub fn item_par_levels(conn: &Connection, item_id: i64) -> Result<Vec<ParLevel>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.par_id, COALESCE(p.storage_type, ''), p.day_of_week, COALESCE(p.par_qty, 0),
+                    p.unit_id, COALESCE(u.sing, '-')
+             FROM par_levels p
+             LEFT JOIN units u ON u.unit_id = p.unit_id
+             WHERE p.item_id = ?1
+             ORDER BY COALESCE(p.storage_type, '') COLLATE NOCASE, p.day_of_week IS NOT NULL, p.day_of_week",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([item_id], |row| {
+            Ok(ParLevel {
+                par_id: row.get(0)?,
+                storage_type: row.get(1)?,
+                day_of_week: row.get(2)?,
+                par_qty: row.get(3)?,
+                unit_id: row.get(4)?,
+                unit_name: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Sets the par for an item in a storage area, for one weekday or every day
+/// (`day_of_week` = `None`). The unit must convert to the purchase unit.
+pub fn save_par_level(
+    conn: &Connection,
+    item_id: i64,
+    storage_type: &str,
+    day_of_week: Option<i64>,
+    par_qty: f64,
+    unit_id: Option<i64>,
+) -> Result<(), String> {
+    if par_qty < 0.0 {
+        return Err("Par cannot be negative".to_string());
+    }
+    if let Some(day) = day_of_week {
+        if !(0..7).contains(&day) {
+            return Err(format!("Invalid day of week {}", day));
+        }
+    }
+    let purch_unit = default_purch_unit(conn, item_id)
+        .ok_or_else(|| format!("Item {} has no purchase unit", item_id))?;
+    let unit_id = unit_id.unwrap_or(purch_unit);
+    if convert_item_qty(conn, item_id, 1.0, unit_id, purch_unit)?.is_none() {
+        return Err("That unit has no conversion to the item's purchase unit".to_string());
+    }
+    conn.execute(
+        "DELETE FROM par_levels WHERE item_id = ?1 AND COALESCE(storage_type, '') = ?2 AND day_of_week IS ?3",
+        rusqlite::params![item_id, storage_type, day_of_week],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO par_levels (item_id, storage_type, day_of_week, par_qty, unit_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![item_id, storage_type, day_of_week, par_qty, unit_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The item's par on a weekday, summed across storage areas, in its
+/// purchase unit. `None` when a par unit no longer converts.
+fn par_for_day(
+    conn: &Connection,
+    item_id: i64,
+    purch_unit: i64,
+    day: i64,
+) -> Result<Option<f64>, String> {
+    let mut by_area: BTreeMap<String, (f64, Option<i64>)> = BTreeMap::new();
+    for par in item_par_levels(conn, item_id)? {
+        // Every-day rows sort first, so a matching weekday row replaces them.
+        if par.day_of_week.is_none() || par.day_of_week == Some(day) {
+            by_area.insert(par.storage_type, (par.par_qty, par.unit_id));
+        }
+    }
+    let mut total = 0.0;
+    for (qty, unit_id) in by_area.into_values() {
+        match convert_item_qty(
+            conn,
+            item_id,
+            qty,
+            unit_id.unwrap_or(purch_unit),
+            purch_unit,
+        )? {
+            Some(q) => total += q,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(total))
+}
+
+/// The vendor an item is ordered from and the unit and price it is sold in:
+/// the price-list vendor first, using its most recent invoice line for the
+/// pack; otherwise the item's purchase unit at the price-list price.
+fn vendor_source(
+    conn: &Connection,
+    item_id: i64,
+    purch_unit: i64,
+) -> (Option<i64>, i64, Option<f64>) {
+    let listed = item_price(conn, item_id);
+    let vendor_id = listed.map(|p| p.1).or_else(|| {
+        conn.query_row(
+            "SELECT vendor_id FROM trans WHERE item_id = ?1 AND vendor_id IS NOT NULL
+             ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+            [item_id],
+            |row| row.get(0),
+        )
+        .ok()
+    });
+    let last_line: Option<(i64, Option<f64>)> = vendor_id.and_then(|v| {
+        conn.query_row(
+            "SELECT unit_id, price FROM trans
+             WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id IS NOT NULL
+             ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+            (item_id, v),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()
+    });
+    match last_line {
+        Some((unit_id, price)) => (vendor_id, unit_id, price.filter(|p| *p > 0.0)),
+        None => (vendor_id, purch_unit, listed.map(|p| p.0)),
+    }
+}
+
+fn unit_name(conn: &Connection, unit_id: i64) -> String {
+    conn.query_row(
+        "SELECT COALESCE(sing, '-') FROM units WHERE unit_id = ?1",
+        [unit_id],
+        |row| row.get(0),
+    )
+    .unwrap_or_else(|_| "-".to_string())
+}
+
+/// Builds the order guide for `order_date`: for every item with a par,
+/// par minus on hand (latest count, or latest count + purchases − sales
+/// usage since when `theoretical`), converted to the vendor's pack and
+/// rounded up to whole packs, grouped by vendor.
+pub fn order_guide(
+    conn: &Connection,
+    order_date: &str,
+    theoretical: bool,
+    vendor_filter: Option<i64>,
+) -> Result<OrderGuide, String> {
+    let day: i64 = conn
+        .query_row(
+            "SELECT CAST(strftime('%w', ?1) AS INTEGER)",
+            [order_date],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .ok_or_else(|| format!("Invalid order date '{}'", order_date))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT i.item_id, COALESCE(i.name, ''), COALESCE(i.storage_type, '')
+             FROM par_levels p
+             JOIN items i ON i.item_id = p.item_id
+             ORDER BY i.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let items: Vec<(i64, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut unresolved = Vec::new();
+    // Usage and purchases since each count date, shared by items counted together.
+    let mut since_count: HashMap<String, UsageSince> = HashMap::new();
+    let mut by_vendor: BTreeMap<Option<i64>, Vec<OrderGuideLine>> = BTreeMap::new();
+
+    for (item_id, item_name, storage_type) in items {
+        let purch_unit = match default_purch_unit(conn, item_id) {
+            Some(u) => u,
+            None => {
+                unresolved.push(format!("{} (no purchase unit)", item_name));
+                continue;
+            }
+        };
+        let par_qty = match par_for_day(conn, item_id, purch_unit, day)? {
+            Some(q) => q,
+            None => {
+                unresolved.push(format!("{} (par unit has no conversion)", item_name));
+                continue;
+            }
+        };
+        if par_qty <= 0.0 {
+            continue;
+        }
+
+        let (on_hand, on_hand_basis) =
+            match item_count(conn, item_id, purch_unit, None, order_date)? {
+                Some((date, Some(counted))) if theoretical => {
+                    if !since_count.contains_key(&date) {
+                        let next_day: String = conn
+                            .query_row("SELECT date(?1, '+1 day')", [&date], |row| row.get(0))
+                            .map_err(|e| e.to_string())?;
+                        let usage =
+                            theoretical_usage(conn, &next_day, order_date, &mut unresolved)?;
+                        let received = received_lines(conn, &next_day, order_date)?;
+                        let purchased = purchased_qty(conn, &received, &mut unresolved)?;
+                        since_count.insert(date.clone(), (usage, purchased));
+                    }
+                    let (usage, purchased) = &since_count[&date];
+                    let qty = counted + purchased.get(&item_id).copied().unwrap_or(0.0)
+                        - usage.get(&item_id).copied().unwrap_or(0.0);
+                    (
+                        Some(qty.max(0.0)),
+                        format!("Theoretical from count {}", date),
+                    )
+                }
+                Some((date, Some(counted))) => (Some(counted), format!("Count {}", date)),
+                Some((date, None)) => {
+                    unresolved.push(format!(
+                        "{} (count on {} has no conversion)",
+                        item_name, date
+                    ));
+                    continue;
+                }
+                None => (None, "No count".to_string()),
+            };
+        let need_qty = par_qty - on_hand.unwrap_or(0.0);
+        if need_qty <= 1e-9 {
+            continue;
+        }
+
+        let (vendor_id, order_unit_id, price) = vendor_source(conn, item_id, purch_unit);
+        if vendor_filter.is_some() && vendor_filter != vendor_id {
+            continue;
+        }
+        let edges = build_conversion_edges(conn, item_id, vendor_id)?;
+        let per_order_unit = match conversion_factor(&edges, purch_unit, order_unit_id, 6) {
+            Some((factor, _)) => factor,
+            None => {
+                unresolved.push(format!(
+                    "{} (no conversion to the vendor's unit)",
+                    item_name
+                ));
+                continue;
+            }
+        };
+        let order_qty = (need_qty * per_order_unit - 1e-9).ceil().max(1.0);
+        by_vendor
+            .entry(vendor_id)
+            .or_default()
+            .push(OrderGuideLine {
+                item_id,
+                item_name,
+                storage_type,
+                purch_unit_name: unit_name(conn, purch_unit),
+                par_qty,
+                on_hand,
+                on_hand_basis,
+                need_qty,
+                order_unit_id,
+                order_unit_name: unit_name(conn, order_unit_id),
+                order_qty,
+                price,
+                est_cost: price.map(|p| p * order_qty),
+            });
+    }
+
+    let mut vendors: Vec<VendorOrder> = by_vendor
+        .into_iter()
+        .map(|(vendor_id, lines)| {
+            let vendor_name = vendor_id
+                .and_then(|v| {
+                    conn.query_row(
+                        "SELECT name FROM vendors WHERE vendor_id = ?1",
+                        [v],
+                        |row| row.get(0),
+                    )
+                    .ok()
+                    .flatten()
+                })
+                .unwrap_or_else(|| "No Vendor".to_string());
+            VendorOrder {
+                vendor_id,
+                vendor_name,
+                total_cost: lines.iter().filter_map(|l| l.est_cost).sum(),
+                lines,
+            }
+        })
+        .collect();
+    vendors.sort_by_key(|v| v.vendor_name.to_lowercase());
+    unresolved.sort();
+    unresolved.dedup();
+
+    Ok(OrderGuide {
+        order_date: order_date.to_string(),
+        day_name: DAY_NAMES[day.rem_euclid(7) as usize].to_string(),
+        theoretical,
+        vendors,
+        unresolved,
+    })
+}
@@ -0,0 +1,581 @@
+use crate::costing::{default_purch_unit, format_qty};
+use crate::units::{resolve_unit, unit_dimension};
+use rusqlite::{Connection, OptionalExtension};
+
+/// Words on pack descriptions that don't change the quantity, such as the
+/// "AVG" on random-weight packs.
+const QUALIFIERS: &[&str] = &[
+    "avg", "av", "approx", "apx", "random", "rw", "net", "wt", "case", "cs",
+];
+
+/// Words that count pieces, with pieces per word.
+const COUNT_WORDS: &[(&str, f64)] = &[
+    ("ct", 1.0),
+    ("cnt", 1.0),
+    ("count", 1.0),
+    ("ea", 1.0),
+    ("each", 1.0),
+    ("pc", 1.0),
+    ("pcs", 1.0),
+    ("piece", 1.0),
+    ("dz", 12.0),
+    ("doz", 12.0),
+    ("dozen", 12.0),
+];
+
+/// Container abbreviations and the name they're shown and matched to a unit
+/// by.
+const CONTAINERS: &[(&str, &[&str])] = &[
+    ("can", &["cn", "can"]),
+    ("bottle", &["btl", "bt", "bottle"]),
+    ("bag", &["bg", "bag"]),
+    ("box", &["bx", "box"]),
+    ("jar", &["jr", "jar"]),
+    ("pack", &["pk", "pkg", "pack", "package"]),
+    ("carton", &["ctn", "carton"]),
+    ("tub", &["tub"]),
+    ("tray", &["tray", "tr"]),
+    ("sleeve", &["slv", "sleeve"]),
+];
+
+/// A pack description read into its parts: `count` inner units per purchase
+/// unit, each holding `size` of `unit_text`. Count-only packs ("200 CT",
+/// "6/#10 CN") have no size.
+#[derive(Debug, Default)]
+pub struct Pack {
+    pub count: f64,
+    pub size: Option<f64>,
+    pub unit_text: String,
+    /// Set by `resolve_units` when the size's unit is known.
+    pub unit_id: Option<i64>,
+    /// What the inner units are, e.g. "#10 can", when the pack says.
+    pub container: Option<String>,
+    /// The container's unit, or each, set by `resolve_units`.
+    pub inner_unit_id: Option<i64>,
+}
+
+impl Pack {
+    /// "4 × 5 Pound", "6 × #10 can", "200 each".
+    pub fn describe(&self, conn: &Connection) -> String {
+        let unit = |id: Option<i64>, fallback: &str| {
+            id.and_then(|id| {
+                conn.query_row("SELECT sing FROM units WHERE unit_id = ?1", [id], |row| {
+                    row.get::<_, Option<String>>(0)
+                })
+                .ok()
+                .flatten()
+            })
+            .unwrap_or_else(|| fallback.to_string())
+        };
+        let count = format_qty(self.count);
+        match (self.size, &self.container) {
+            (Some(size), _) => {
+                let size = format!(
+                    "{} {}",
+                    format_qty(size),
+                    unit(self.unit_id, &self.unit_text)
+                );
+                if self.count == 1.0 {
+                    size
+                } else {
+                    format!("{} × {}", count, size)
+                }
+            }
+            (None, Some(container)) => format!("{} × {}", count, container),
+            (None, None) => format!("{} {}", count, unit(self.inner_unit_id, "each")),
+        }
+    }
+}
+
+fn singular(word: &str) -> &str {
+    word.strip_suffix('s')
+        .filter(|w| w.len() > 1)
+        .unwrap_or(word)
+}
+
+fn count_word(word: &str) -> Option<f64> {
+    COUNT_WORDS
+        .iter()
+        .find(|(w, _)| *w == word || *w == singular(word))
+        .map(|(_, per)| *per)
+}
+
+fn container_name(word: &str) -> Option<&'static str> {
+    CONTAINERS
+        .iter()
+        .find(|(_, spellings)| spellings.contains(&word) || spellings.contains(&singular(word)))
+        .map(|(name, _)| *name)
+}
+
+/// A quantity such as "5", "2.5" or the middle of a range "5-6".
+fn read_number(text: &str) -> Option<f64> {
+    let value = match text.split_once('-') {
+        Some((low, high)) => (low.parse::<f64>().ok()? + high.parse::<f64>().ok()?) / 2.0,
+        None => text.parse::<f64>().ok()?,
+    };
+    (value > 0.0 && value.is_finite()).then_some(value)
+}
+
+/// Reads a vendor pack description such as "6/#10 CN", "4/5 LB",
+/// "12/32 OZ", "1/50#", "4x1 GAL", "200 CT" or "50 LB". Leading counts
+/// multiply ("6/2/5 LB" is 12 × 5 lb). Units are resolved separately by
+/// `resolve_units`. The error says why the pack couldn't be read.
+pub fn parse_pack(text: &str) -> Result<Pack, String> {
+    let lower = text.trim().to_lowercase();
+    let words: Vec<&str> = lower
+        .split_whitespace()
+        .filter(|w| !QUALIFIERS.contains(&w.trim_end_matches('.')))
+        .collect();
+    if words.is_empty() {
+        return Err("empty".to_string());
+    }
+    // "4x5 lb" and "4 x 5 lb" are "4/5 lb".
+    let mut text = String::new();
+    for word in words {
+        let split = word.split_once('x').filter(|(count, rest)| {
+            !count.is_empty()
+                && count.chars().all(|c| c.is_ascii_digit())
+                && rest.starts_with(|c: char| c.is_ascii_digit())
+        });
+        match (word, split) {
+            ("x", _) => text.push('/'),
+            (_, Some((count, rest))) => text.push_str(&format!("{}/{}", count, rest)),
+            _ => {
+                if !text.is_empty() && !text.ends_with('/') {
+                    text.push(' ');
+                }
+                text.push_str(word);
+            }
+        }
+    }
+
+    let mut parts: Vec<&str> = text.split('/').map(str::trim).collect();
+    let last = parts.pop().unwrap_or_default();
+    let mut count = 1.0;
+    for part in parts {
+        count *= read_number(part).ok_or_else(|| format!("'{}' is not a count", part))?;
+    }
+
+    // Can sizes: "#10 CN" or just "#10".
+    if let Some(rest) = last.strip_prefix('#') {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits > 0 {
+            let container = container_name(rest[digits..].trim()).unwrap_or("can");
+            return Ok(Pack {
+                count,
+                container: Some(format!("#{} {}", &rest[..digits], container)),
+                ..Default::default()
+            });
+        }
+    }
+
+    let end = last
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(last.len());
+    let (number, unit) = (&last[..end], last[end..].trim());
+    if number.is_empty() {
+        return Err(format!("no size in '{}'", last));
+    }
+    let size = read_number(number).ok_or_else(|| format!("'{}' is not a size", number))?;
+    if unit.is_empty() {
+        return Err(format!("no unit after '{}'", number));
+    }
+    let first = unit.split_whitespace().next().unwrap_or(unit);
+    if let Some(per) = count_word(first) {
+        return Ok(Pack {
+            count: count * size * per,
+            ..Default::default()
+        });
+    }
+    if let Some(container) = container_name(first) {
+        return Ok(Pack {
+            count: count * size,
+            container: Some(container.to_string()),
+            ..Default::default()
+        });
+    }
+    Ok(Pack {
+        count,
+        size: Some(size),
+        unit_text: unit.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Looks up the pack's size unit and its inner unit (the container's unit
+/// when there is one, otherwise each). A size unit followed by a container,
+/// as in "5 LB BAG", takes the container from the trailing word.
+pub fn resolve_units(conn: &Connection, pack: &mut Pack) -> Result<(), String> {
+    if pack.size.is_some() {
+        let words: Vec<&str> = pack.unit_text.split_whitespace().collect();
+        for take in (1..=words.len()).rev() {
+            if let Some(unit_id) = resolve_unit(conn, &words[..take].join(" "))? {
+                pack.unit_id = Some(unit_id);
+                if pack.container.is_none() {
+                    pack.container = words[take..]
+                        .iter()
+                        .find_map(|w| container_name(w))
+                        .map(str::to_string);
+                }
+                break;
+            }
+        }
+    }
+    let container_word = pack
+        .container
+        .as_deref()
+        .and_then(|c| c.split_whitespace().last())
+        .map(str::to_string);
+    if let Some(word) = container_word {
+        pack.inner_unit_id = resolve_unit(conn, &word)?;
+    }
+    if pack.inner_unit_id.is_none() {
+        pack.inner_unit_id = match resolve_unit(conn, "each")? {
+            Some(id) => Some(id),
+            None => resolve_unit(conn, "ea")?,
+        };
+    }
+    Ok(())
+}
+
+/// Re-reads pack descriptions that changed since they were last read, or
+/// all of them when `all`. Returns how many were read.
+pub fn refresh_packs(conn: &Connection, all: bool) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT rowid, COALESCE(TRIM(pack), '') FROM inv_prices
+             WHERE ?1 OR pack_source IS NOT COALESCE(TRIM(pack), '')",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(i64, String)> = stmt
+        .query_map([all], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    for (row_id, text) in &rows {
+        let (pack, error) = if text.is_empty() {
+            (None, None)
+        } else {
+            match parse_pack(text) {
+                Ok(mut pack) => {
+                    resolve_units(conn, &mut pack)?;
+                    if pack.size.is_some() && pack.unit_id.is_none() {
+                        let error = format!("unknown unit '{}'", pack.unit_text);
+                        (None, Some(error))
+                    } else {
+                        (Some(pack), None)
+                    }
+                }
+                Err(error) => (None, Some(error)),
+            }
+        };
+        conn.execute(
+            "UPDATE inv_prices
+             SET pack_count = ?2, pack_size = ?3, pack_unit_id = ?4, pack_inner_unit_id = ?5, pack_container = ?6,
+                 pack_error = ?7, pack_source = ?8
+             WHERE rowid = ?1",
+            rusqlite::params![
+                row_id,
+                pack.as_ref().map(|p| p.count),
+                pack.as_ref().and_then(|p| p.size),
+                pack.as_ref().and_then(|p| p.unit_id),
+                pack.as_ref().and_then(|p| p.inner_unit_id),
+                pack.as_ref().and_then(|p| p.container.clone()),
+                error,
+                text
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(rows.len())
+}
+
+/// The stored reading of a price row's pack, if it was read.
+pub fn stored_pack(conn: &Connection, row_id: i64) -> Result<Option<Pack>, String> {
+    conn.query_row(
+        "SELECT pack_count, pack_size, pack_unit_id, pack_inner_unit_id, pack_container FROM inv_prices
+         WHERE rowid = ?1 AND pack_count IS NOT NULL",
+        [row_id],
+        |row| {
+            Ok(Pack {
+                count: row.get(0)?,
+                size: row.get(1)?,
+                unit_text: String::new(),
+                unit_id: row.get(2)?,
+                inner_unit_id: row.get(3)?,
+                container: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// A conversion read from a pack: `qty1` of `unit_id1` is `qty2` of
+/// `unit_id2`.
+pub struct PackEdge {
+    pub vendor_id: i64,
+    pub unit_id1: i64,
+    pub unit_id2: i64,
+    pub qty1: f64,
+    pub qty2: f64,
+    pub pack: String,
+}
+
+/// Conversions from an item's read packs: a purchase unit holds `count`
+/// inner units (each, or the container's unit) and an inner unit holds
+/// `size` of the size unit. A single-unit pack converts the purchase unit
+/// straight to its size. Only `vendor_id`'s and vendor-neutral packs when a
+/// vendor is given, every vendor's otherwise.
+///
+/// A pack describes a case or container, so an item bought by a weight or
+/// volume measure (priced per lb with pack "4/10 LB AVG") gets none: the
+/// pack would claim 1 lb holds 4 each.
+pub fn pack_edges(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: Option<i64>,
+) -> Result<Vec<PackEdge>, String> {
+    let Some(purch_unit_id) = default_purch_unit(conn, item_id) else {
+        return Ok(Vec::new());
+    };
+    if unit_dimension(conn, purch_unit_id)?.is_some() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(vendor_id, 0), pack_count, pack_size, pack_unit_id, pack_inner_unit_id, pack_source
+             FROM inv_prices
+             WHERE item_id = ?1 AND pack_count > 0 AND (?2 IS NULL OR vendor_id = ?2 OR COALESCE(vendor_id, 0) = 0)",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map((item_id, vendor_id), |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, Option<f64>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut edges = Vec::new();
+    for row in rows {
+        let (vendor_id, count, size, unit_id, inner_unit_id, pack) =
+            row.map_err(|e| e.to_string())?;
+        let edge = |unit_id1: i64, unit_id2: i64, qty2: f64| PackEdge {
+            vendor_id,
+            unit_id1,
+            unit_id2,
+            qty1: 1.0,
+            qty2,
+            pack: pack.clone(),
+        };
+        let measure = size.zip(unit_id).filter(|(size, _)| *size > 0.0);
+        let inner = inner_unit_id.filter(|u| *u != purch_unit_id);
+        match (inner, measure) {
+            (Some(inner), measure) if count > 1.0 || measure.is_none() => {
+                edges.push(edge(purch_unit_id, inner, count));
+                if let Some((size, unit_id)) = measure.filter(|(_, u)| *u != inner) {
+                    edges.push(edge(inner, unit_id, size));
+                }
+            }
+            (_, Some((size, unit_id))) if unit_id != purch_unit_id => {
+                edges.push(edge(purch_unit_id, unit_id, count * size));
+            }
+            _ => {}
+        }
+    }
+    Ok(edges)
+}
+
+/// A price row whose pack couldn't be read.
+pub struct PackIssue {
+    pub row_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    pub pack: String,
+    pub error: String,
+}
+
+pub struct PackReport {
+    pub read: i64,
+    pub unreadable: i64,
+    pub issues: Vec<PackIssue>,
+}
+
+/// Packs that couldn't be read, for manual cleanup, items used in recipes
+/// first.
+pub fn pack_report(conn: &Connection) -> Result<PackReport, String> {
+    let (read, unreadable): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(pack_count), COUNT(pack_error) FROM inv_prices",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.rowid, COALESCE(p.item_id, 0), COALESCE(i.name, '-'), COALESCE(p.vendor_id, 0), COALESCE(v.name, '-'),
+                    COALESCE(p.pack, ''), p.pack_error
+             FROM inv_prices p
+             LEFT JOIN items i ON i.item_id = p.item_id
+             LEFT JOIN vendors v ON v.vendor_id = p.vendor_id
+             WHERE p.pack_error IS NOT NULL
+             ORDER BY EXISTS (SELECT 1 FROM recp_items ri WHERE ri.item_id = p.item_id) DESC, i.name, v.name",
+        )
+        .map_err(|e| e.to_string())?;
+    let issues = stmt
+        .query_map([], |row| {
+            Ok(PackIssue {
+                row_id: row.get(0)?,
+                item_id: row.get(1)?,
+                item_name: row.get(2)?,
+                vendor_id: row.get(3)?,
+                vendor_name: row.get(4)?,
+                pack: row.get(5)?,
+                error: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(PackReport {
+        read,
+        unreadable,
+        issues,
+    })
+}
+
+/// Replaces a price row's pack description and reads it. Returns what it
+/// was read as, or why it couldn't be.
+pub fn set_pack(conn: &Connection, row_id: i64, pack: &str) -> Result<String, String> {
+    let updated = conn
+        .execute(
+            "UPDATE inv_prices SET pack = ?2 WHERE rowid = ?1",
+            rusqlite::params![row_id, pack.trim()],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Price row {} not found", row_id));
+    }
+    refresh_packs(conn, false)?;
+    if let Some(read) = stored_pack(conn, row_id)? {
+        return Ok(format!("Read '{}' as {}", pack.trim(), read.describe(conn)));
+    }
+    let error: Option<String> = conn
+        .query_row(
+            "SELECT pack_error FROM inv_prices WHERE rowid = ?1",
+            [row_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(match error {
+        Some(error) => format!("Saved, but couldn't read '{}': {}", pack.trim(), error),
+        None => "Cleared pack".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pack_edges, parse_pack, refresh_packs};
+    use rusqlite::Connection;
+
+    #[test]
+    fn reads_sized_packs() {
+        let pack = parse_pack("4/5 LB").unwrap();
+        assert_eq!(
+            (pack.count, pack.size, pack.unit_text.as_str()),
+            (4.0, Some(5.0), "lb")
+        );
+        let pack = parse_pack("6/2/5 LB").unwrap();
+        assert_eq!((pack.count, pack.size), (12.0, Some(5.0)));
+        let pack = parse_pack("4x1 GAL").unwrap();
+        assert_eq!(
+            (pack.count, pack.size, pack.unit_text.as_str()),
+            (4.0, Some(1.0), "gal")
+        );
+        let pack = parse_pack("4/10 LB AVG").unwrap();
+        assert_eq!((pack.count, pack.size), (4.0, Some(10.0)));
+        let pack = parse_pack("1/50#").unwrap();
+        assert_eq!(
+            (pack.count, pack.size, pack.unit_text.as_str()),
+            (1.0, Some(50.0), "#")
+        );
+        let pack = parse_pack("2/5-6 LB").unwrap();
+        assert_eq!(pack.size, Some(5.5));
+    }
+
+    #[test]
+    fn reads_counts_and_containers() {
+        let pack = parse_pack("200 CT").unwrap();
+        assert_eq!((pack.count, pack.size), (200.0, None));
+        let pack = parse_pack("2 DZ").unwrap();
+        assert_eq!(pack.count, 24.0);
+        let pack = parse_pack("6/#10 CN").unwrap();
+        assert_eq!(
+            (pack.count, pack.container.as_deref()),
+            (6.0, Some("#10 can"))
+        );
+        let pack = parse_pack("24/12 BTL").unwrap();
+        assert_eq!(
+            (pack.count, pack.container.as_deref()),
+            (288.0, Some("bottle"))
+        );
+    }
+
+    #[test]
+    fn rejects_unreadable_packs() {
+        assert!(parse_pack("").is_err());
+        assert!(parse_pack("CASE").is_err());
+        assert!(parse_pack("LB").is_err());
+        assert!(parse_pack("12").is_err());
+        assert!(parse_pack("a/5 LB").is_err());
+    }
+
+    fn pack_db(purch_unit_id: i64, pack: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (1, 'Each', 'Each'), (2, 'Pound', 'Pounds'), (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Chicken Thigh');",
+        )
+        .unwrap();
+        crate::units::refresh_standard_units(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, ?1, 1)",
+            [purch_unit_id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO inv_prices (item_id, vendor_id, price, pack) VALUES (1, 7, 40.0, ?1)",
+            [pack],
+        )
+        .unwrap();
+        refresh_packs(&conn, true).unwrap();
+        conn
+    }
+
+    #[test]
+    fn case_packs_convert_through_inner_units() {
+        let conn = pack_db(3, "4/10 LB AVG");
+        let edges: Vec<_> = pack_edges(&conn, 1, None)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.unit_id1, e.unit_id2, e.qty2))
+            .collect();
+        assert_eq!(edges, vec![(3, 1, 4.0), (1, 2, 10.0)]);
+    }
+
+    #[test]
+    fn measured_purchase_units_get_no_pack_edges() {
+        let conn = pack_db(2, "4/10 LB AVG");
+        assert!(pack_edges(&conn, 1, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::costing::{build_conversion_edges, conversion_factor, default_purch_unit};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::BTreeMap;
+
+/// Percentage increase that raises an alert when `price_alert_pct` isn't set.
+pub const DEFAULT_THRESHOLD_PCT: f64 = 5.0;
+
+/// A price that went up by more than the threshold for an item from one
+/// vendor. Prices are per `unit_name`, the item's purchase unit when the
+/// line's unit converts to it.
+pub struct PriceAlert {
+    pub alert_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    pub old_price: f64,
+    pub new_price: f64,
+    pub pct_change: f64,
+    pub unit_name: String,
+    /// "invoice" or "manual".
+    pub source: String,
+    pub invoice_id: Option<i64>,
+    pub invoice_no: String,
+    pub invoice_date: String,
+    /// "new", "acknowledged" or "disputed".
+    pub status: String,
+    pub note: String,
+    pub created_at: String,
+    pub lines: Vec<AlertLine>,
+}
+
+/// An invoice line that carried the higher price.
+pub struct AlertLine {
+    pub trans_id: i64,
+    pub qty: f64,
+    pub price: f64,
+    pub unit_name: String,
+}
+
+struct PricedLine {
+    trans_id: i64,
+    price: f64,
+    unit_id: Option<i64>,
+}
+
+/// A price and the unit it is quoted in.
+type UnitPrice = (f64, Option<i64>);
+
+pub fn threshold_pct(conn: &Connection) -> f64 {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = 'price_alert_pct'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|v| v.trim().parse::<f64>().ok())
+    .filter(|p| *p >= 0.0)
+    .unwrap_or(DEFAULT_THRESHOLD_PCT)
+}
+
+/// `price` per `unit_id` restated per the item's purchase unit through the
+/// vendor's conversions. A missing unit is taken as the purchase unit.
+pub fn per_purch_unit(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: i64,
+    unit_id: Option<i64>,
+    price: f64,
+) -> Option<UnitPrice> {
+    let purch_unit = default_purch_unit(conn, item_id)?;
+    let unit_id = unit_id.unwrap_or(purch_unit);
+    let edges = build_conversion_edges(conn, item_id, Some(vendor_id)).ok()?;
+    conversion_factor(&edges, purch_unit, unit_id, 6).map(|(f, _)| (price * f, Some(purch_unit)))
+}
+
+/// The vendor's most recent earlier invoice price for the item, in the same
+/// terms as `unit`: per purchase unit when `unit` is the purchase unit,
+/// otherwise only lines in that exact unit.
+fn last_invoice_price(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: i64,
+    invoice_id: i64,
+    unit: Option<i64>,
+) -> Result<Option<f64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.price, t.unit_id FROM trans t
+             WHERE t.item_id = ?1 AND t.vendor_id = ?2 AND t.invoice_id <> ?3 AND t.price > 0
+               AND COALESCE(t.trans_date, '') <= COALESCE((SELECT invoice_date FROM invoices WHERE invoice_id = ?3), '9999-12-31')
+             ORDER BY t.trans_date DESC, t.trans_id DESC
+             LIMIT 20",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map((item_id, vendor_id, invoice_id), |row| {
+            Ok((row.get::<_, f64>(0)?, row.get::<_, Option<i64>>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let purch_unit = default_purch_unit(conn, item_id);
+    for row in rows {
+        let (price, line_unit) = row.map_err(|e| e.to_string())?;
+        if unit.is_some() && unit == purch_unit {
+            if let Some((p, _)) = per_purch_unit(conn, item_id, vendor_id, line_unit, price) {
+                return Ok(Some(p));
+            }
+        } else if line_unit == unit {
+            return Ok(Some(price));
+        }
+    }
+    Ok(None)
+}
+
+fn listed_price(conn: &Connection, item_id: i64, vendor_id: i64) -> Result<Option<f64>, String> {
+    conn.query_row(
+        "SELECT price FROM inv_prices WHERE item_id = ?1 AND vendor_id = ?2 AND price > 0 LIMIT 1",
+        (item_id, vendor_id),
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn record_alert(
+    conn: &Connection,
+    key: (i64, i64),
+    old: f64,
+    new: UnitPrice,
+    invoice_id: Option<i64>,
+) -> Result<i64, String> {
+    let (item_id, vendor_id) = key;
+    conn.execute(
+        "INSERT INTO price_alerts (item_id, vendor_id, old_price, new_price, pct_change, unit_id, source, invoice_id, status, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'new', datetime('now', 'localtime'))",
+        rusqlite::params![
+            item_id,
+            vendor_id,
+            old,
+            new.0,
+            (new.0 - old) / old * 100.0,
+            new.1,
+            if invoice_id.is_some() { "invoice" } else { "manual" },
+            invoice_id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Compares every priced line on an invoice with the vendor's previous
+/// price for the item and records an alert, with the lines involved, for
+/// each item whose price rose past the threshold. Re-checking an invoice
+/// updates its alerts in place, keyed by item and vendor, so triaged
+/// alerts keep their status and note; untriaged alerts that no longer
+/// apply are dropped. Returns the number of new alerts raised.
+pub fn check_invoice(conn: &Connection, invoice_id: i64) -> Result<usize, String> {
+    let mut existing: BTreeMap<(i64, i64), (i64, String)> = BTreeMap::new();
+    {
+        let mut stmt = conn
+            .prepare(
+                "SELECT alert_id, item_id, vendor_id, COALESCE(status, 'new') FROM price_alerts
+                 WHERE invoice_id = ?1 ORDER BY alert_id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([invoice_id], |row| {
+                Ok((
+                    (row.get::<_, i64>(1)?, row.get::<_, i64>(2)?),
+                    (row.get::<_, i64>(0)?, row.get::<_, String>(3)?),
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (key, alert) = row.map_err(|e| e.to_string())?;
+            existing.entry(key).or_insert(alert);
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT trans_id, item_id, vendor_id, price, unit_id FROM trans
+             WHERE invoice_id = ?1 AND item_id IS NOT NULL AND vendor_id IS NOT NULL AND price > 0
+             ORDER BY trans_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok((
+                (row.get::<_, i64>(1)?, row.get::<_, i64>(2)?),
+                PricedLine {
+                    trans_id: row.get(0)?,
+                    price: row.get(3)?,
+                    unit_id: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut by_item: BTreeMap<(i64, i64), Vec<PricedLine>> = BTreeMap::new();
+    for row in rows {
+        let (key, line) = row.map_err(|e| e.to_string())?;
+        by_item.entry(key).or_default().push(line);
+    }
+
+    let threshold = threshold_pct(conn);
+    let mut raised = 0usize;
+    for ((item_id, vendor_id), lines) in by_item {
+        // The highest price on the invoice, per purchase unit where possible.
+        let Some(new) = lines
+            .iter()
+            .map(|l| {
+                per_purch_unit(conn, item_id, vendor_id, l.unit_id, l.price)
+                    .unwrap_or((l.price, l.unit_id))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+        else {
+            continue;
+        };
+        let purch_unit = default_purch_unit(conn, item_id);
+        let mut old = last_invoice_price(conn, item_id, vendor_id, invoice_id, new.1)?;
+        if old.is_none() && new.1 == purch_unit {
+            old = listed_price(conn, item_id, vendor_id)?;
+        }
+        let Some(old) = old.filter(|p| *p > 0.0) else {
+            continue;
+        };
+        if new.0 <= old * (1.0 + threshold / 100.0) + 1e-9 {
+            continue;
+        }
+        let alert_id = match existing.remove(&(item_id, vendor_id)) {
+            Some((alert_id, _)) => {
+                conn.execute(
+                    "UPDATE price_alerts SET old_price = ?2, new_price = ?3, pct_change = ?4, unit_id = ?5
+                     WHERE alert_id = ?1",
+                    rusqlite::params![alert_id, old, new.0, (new.0 - old) / old * 100.0, new.1],
+                )
+                .map_err(|e| e.to_string())?;
+                conn.execute(
+                    "DELETE FROM price_alert_lines WHERE alert_id = ?1",
+                    [alert_id],
+                )
+                .map_err(|e| e.to_string())?;
+                alert_id
+            }
+            None => {
+                raised += 1;
+                record_alert(conn, (item_id, vendor_id), old, new, Some(invoice_id))?
+            }
+        };
+        for line in &lines {
+            conn.execute(
+                "INSERT INTO price_alert_lines (alert_id, trans_id) VALUES (?1, ?2)",
+                (alert_id, line.trans_id),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    // Alerts the invoice no longer supports: drop them unless someone has
+    // already acknowledged or disputed them.
+    for (alert_id, status) in existing.into_values() {
+        if status != "new" {
+            continue;
+        }
+        conn.execute(
+            "DELETE FROM price_alert_lines WHERE alert_id = ?1",
+            [alert_id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM price_alerts WHERE alert_id = ?1", [alert_id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(raised)
+}
+
+pub fn check_invoices(conn: &Connection, invoice_ids: &[i64]) -> Result<usize, String> {
+    let mut raised = 0;
+    for &invoice_id in invoice_ids {
+        raised += check_invoice(conn, invoice_id)?;
+    }
+    Ok(raised)
+}
+
+/// Checks a manually entered list price (per purchase unit) against the
+/// previous list price, or the last invoice price when there was none.
+/// Call before the new price is saved. Returns whether an alert was raised.
+pub fn check_manual_price(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: i64,
+    price: f64,
+) -> Result<bool, String> {
+    let purch_unit = default_purch_unit(conn, item_id);
+    let old = match listed_price(conn, item_id, vendor_id)? {
+        Some(p) => Some(p),
+        None if purch_unit.is_some() => {
+            last_invoice_price(conn, item_id, vendor_id, 0, purch_unit)?
+        }
+        None => None,
+    };
+    let Some(old) = old.filter(|p| *p > 0.0) else {
+        return Ok(false);
+    };
+    if price <= old * (1.0 + threshold_pct(conn) / 100.0) + 1e-9 {
+        return Ok(false);
+    }
+    record_alert(conn, (item_id, vendor_id), old, (price, purch_unit), None)?;
+    Ok(true)
+}
+
+fn alert_lines(conn: &Connection, alert_id: i64) -> Result<Vec<AlertLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.trans_id, COALESCE(t.qty, 0), COALESCE(t.price, 0), COALESCE(u.sing, '-')
+             FROM price_alert_lines l
+             JOIN trans t ON t.trans_id = l.trans_id
+             LEFT JOIN units u ON u.unit_id = t.unit_id
+             WHERE l.alert_id = ?1
+             ORDER BY t.trans_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([alert_id], |row| {
+            Ok(AlertLine {
+                trans_id: row.get(0)?,
+                qty: row.get(1)?,
+                price: row.get(2)?,
+                unit_name: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(lines)
+}
+
+pub fn list_alerts(conn: &Connection, status: Option<&str>) -> Result<Vec<PriceAlert>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.alert_id, a.item_id, COALESCE(i.name, ''), a.vendor_id, COALESCE(v.name, ''),
+                    a.old_price, a.new_price, a.pct_change, COALESCE(u.sing, '-'), COALESCE(a.source, ''),
+                    a.invoice_id, COALESCE(inv.invoice_no, ''), COALESCE(inv.invoice_date, ''),
+                    COALESCE(a.status, 'new'), COALESCE(a.note, ''), COALESCE(a.created_at, '')
+             FROM price_alerts a
+             LEFT JOIN items i ON i.item_id = a.item_id
+             LEFT JOIN vendors v ON v.vendor_id = a.vendor_id
+             LEFT JOIN units u ON u.unit_id = a.unit_id
+             LEFT JOIN invoices inv ON inv.invoice_id = a.invoice_id
+             WHERE (?1 IS NULL OR a.status = ?1)
+             ORDER BY a.status = 'new' DESC, a.created_at DESC, a.alert_id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([status], |row| {
+            Ok(PriceAlert {
+                alert_id: row.get(0)?,
+                item_id: row.get(1)?,
+                item_name: row.get(2)?,
+                vendor_id: row.get(3)?,
+                vendor_name: row.get(4)?,
+                old_price: row.get(5)?,
+                new_price: row.get(6)?,
+                pct_change: row.get(7)?,
+                unit_name: row.get(8)?,
+                source: row.get(9)?,
+                invoice_id: row.get(10)?,
+                invoice_no: row.get(11)?,
+                invoice_date: row.get(12)?,
+                status: row.get(13)?,
+                note: row.get(14)?,
+                created_at: row.get(15)?,
+                lines: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut alerts = Vec::new();
+    for row in rows {
+        let mut alert = row.map_err(|e| e.to_string())?;
+        alert.lines = alert_lines(conn, alert.alert_id)?;
+        alerts.push(alert);
+    }
+    Ok(alerts)
+}
+
+/// Acknowledges or disputes an alert, or puts it back in the inbox.
+pub fn set_alert_status(
+    conn: &Connection,
+    alert_id: i64,
+    status: &str,
+    note: Option<&str>,
+) -> Result<(), String> {
+    if !matches!(status, "new" | "acknowledged" | "disputed") {
+        return Err(format!("Unknown alert status '{}'", status));
+    }
+    let updated = conn
+        .execute(
+            "UPDATE price_alerts
+             SET status = ?2, note = COALESCE(?3, note),
+                 resolved_at = CASE WHEN ?2 = 'new' THEN NULL ELSE datetime('now', 'localtime') END
+             WHERE alert_id = ?1",
+            rusqlite::params![alert_id, status, note],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Alert {} not found", alert_id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_invoice, list_alerts, set_alert_status};
+    use rusqlite::Connection;
+
+    fn invoice_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO invoices (invoice_id, invoice_date, vendor_id) VALUES (1, '2026-01-05', 7), (2, '2026-02-05', 7);
+             INSERT INTO trans (invoice_id, trans_id, item_id, trans_date, vendor_id, price, qty)
+             VALUES (1, 10, 1, '2026-01-05', 7, 10.0, 1), (2, 20, 1, '2026-02-05', 7, 12.0, 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn rechecking_keeps_triaged_alerts() {
+        let conn = invoice_db();
+        assert_eq!(check_invoice(&conn, 2).unwrap(), 1);
+        let alert_id = list_alerts(&conn, None).unwrap()[0].alert_id;
+        set_alert_status(&conn, alert_id, "disputed", Some("quoted 10")).unwrap();
+
+        conn.execute("UPDATE trans SET price = 13.0 WHERE trans_id = 20", [])
+            .unwrap();
+        assert_eq!(check_invoice(&conn, 2).unwrap(), 0);
+        let alerts = list_alerts(&conn, None).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_id, alert_id);
+        assert_eq!(alerts[0].status, "disputed");
+        assert_eq!(alerts[0].note, "quoted 10");
+        assert_eq!(alerts[0].new_price, 13.0);
+        assert_eq!(alerts[0].lines.len(), 1);
+    }
+
+    #[test]
+    fn rechecking_drops_untriaged_alerts_that_no_longer_apply() {
+        let conn = invoice_db();
+        assert_eq!(check_invoice(&conn, 2).unwrap(), 1);
+        conn.execute("UPDATE trans SET price = 10.0 WHERE trans_id = 20", [])
+            .unwrap();
+        assert_eq!(check_invoice(&conn, 2).unwrap(), 0);
+        assert!(list_alerts(&conn, None).unwrap().is_empty());
+    }
+}
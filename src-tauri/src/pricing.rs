@@ -0,0 +1,330 @@
+use crate::costing::{cost_recipe, recipe_yield, CostContext};
+use crate::db::{cache_versions, mark_cache_built};
+use rusqlite::{Connection, OptionalExtension};
+
+/// Used when neither the recipe nor settings define a target.
+pub const DEFAULT_TARGET_FOOD_COST_PCT: f64 = 30.0;
+
+/// Per-portion cost and menu pricing for a recipe.
+pub struct RecipePricing {
+    pub servings: Option<f64>,
+    pub portion_size: Option<f64>,
+    pub portion_unit_id: Option<i64>,
+    pub portion_unit_name: String,
+    pub cost_per_portion: Option<f64>,
+    /// Target set on the recipe itself, if any.
+    pub recipe_target_pct: Option<f64>,
+    /// Target actually applied (recipe, then settings, then default).
+    pub target_food_cost_pct: f64,
+    pub suggested_price: Option<f64>,
+    pub menu_price: Option<f64>,
+    pub food_cost_pct: Option<f64>,
+    pub margin: Option<f64>,
+}
+
+pub fn setting(conn: &Connection, key: &str) -> String {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .unwrap_or_default()
+}
+
+pub fn global_target_pct(conn: &Connection) -> f64 {
+    setting(conn, "target_food_cost_pct")
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|p| *p > 0.0 && *p < 100.0)
+        .unwrap_or(DEFAULT_TARGET_FOOD_COST_PCT)
+}
+
+/// Rounds a raw price up to a menu-friendly value. Modes: "none", an
+/// increment such as "0.05" / "0.25", or charm endings like ".95", ".99"
+/// and ".49/.95" (the nearest of either ending at or above the price).
+pub fn round_menu_price(price: f64, mode: &str) -> f64 {
+    let mode = mode.trim();
+    if price <= 0.0 || mode.is_empty() || mode == "none" {
+        return (price * 100.0).round() / 100.0;
+    }
+    if mode.starts_with('.') {
+        let endings: Vec<f64> = mode
+            .split('/')
+            .filter_map(|e| format!("0{}", e.trim()).parse::<f64>().ok())
+            .filter(|e| *e >= 0.0 && *e < 1.0)
+            .collect();
+        if endings.is_empty() {
+            return (price * 100.0).round() / 100.0;
+        }
+        let base = price.floor();
+        let mut best: Option<f64> = None;
+        for whole in [base, base + 1.0] {
+            for ending in &endings {
+                let candidate = whole + ending;
+                if candidate + 1e-9 >= price && best.map(|b| candidate < b).unwrap_or(true) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        return best.map(|b| (b * 100.0).round() / 100.0).unwrap_or(price);
+    }
+    match mode.parse::<f64>() {
+        Ok(step) if step > 0.0 => ((price / step - 1e-9).ceil() * step * 100.0).round() / 100.0,
+        _ => (price * 100.0).round() / 100.0,
+    }
+}
+
+/// Servings per batch. Without an explicit count, portions of the yield unit
+/// give servings.
+fn effective_servings(
+    conn: &Connection,
+    recipe_id: i64,
+    servings: Option<f64>,
+    portion_size: Option<f64>,
+    portion_unit_id: Option<i64>,
+) -> Result<Option<f64>, String> {
+    if let Some(s) = servings.filter(|s| *s > 0.0) {
+        return Ok(Some(s));
+    }
+    let yld = recipe_yield(conn, recipe_id)?;
+    Ok(
+        match (yld.qty, yld.unit_id, portion_size, portion_unit_id) {
+            (Some(q), Some(yu), Some(ps), Some(pu)) if yu == pu && ps > 0.0 => Some(q / ps),
+            _ => None,
+        },
+    )
+}
+
+/// Servings per batch for a recipe, as used for portion costing.
+pub fn recipe_servings(conn: &Connection, recipe_id: i64) -> Result<Option<f64>, String> {
+    let row: Option<(Option<f64>, Option<f64>, Option<i64>)> = conn
+        .query_row(
+            "SELECT servings, portion_size, portion_unit_id FROM recipes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| {
+                Ok((
+                    row.get(0).ok().flatten(),
+                    row.get(1).ok().flatten(),
+                    row.get(2).ok().flatten(),
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match row {
+        Some((servings, portion_size, portion_unit_id)) => {
+            effective_servings(conn, recipe_id, servings, portion_size, portion_unit_id)
+        }
+        None => Ok(None),
+    }
+}
+
+/// servings, portion_size, portion_unit_id, target_food_cost_pct, menu_price
+type PricingRow = (
+    Option<f64>,
+    Option<f64>,
+    Option<i64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+/// Prices a recipe from an already computed batch cost.
+pub fn recipe_pricing(
+    conn: &Connection,
+    recipe_id: i64,
+    total_cost: f64,
+    missing_costs: i64,
+) -> Result<RecipePricing, String> {
+    let row: Option<PricingRow> = conn
+        .query_row(
+            "SELECT servings, portion_size, portion_unit_id, target_food_cost_pct, menu_price
+             FROM recipes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| {
+                Ok((
+                    row.get(0).ok().flatten(),
+                    row.get(1).ok().flatten(),
+                    row.get(2).ok().flatten(),
+                    row.get(3).ok().flatten(),
+                    row.get(4).ok().flatten(),
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (servings, portion_size, portion_unit_id, recipe_target_pct, menu_price) =
+        row.ok_or_else(|| format!("Recipe {} not found", recipe_id))?;
+
+    let portion_unit_name = match portion_unit_id {
+        Some(uid) => conn
+            .query_row("SELECT sing FROM units WHERE unit_id = ?1", [uid], |row| {
+                row.get(0)
+            })
+            .unwrap_or_else(|_| "-".to_string()),
+        None => "-".to_string(),
+    };
+
+    let servings = effective_servings(conn, recipe_id, servings, portion_size, portion_unit_id)?;
+
+    let cost_per_portion = match servings {
+        Some(s) if missing_costs == 0 => Some(total_cost / s),
+        _ => None,
+    };
+    let recipe_target_pct = recipe_target_pct.filter(|p| *p > 0.0 && *p < 100.0);
+    let target_food_cost_pct = recipe_target_pct.unwrap_or_else(|| global_target_pct(conn));
+    let rounding = setting(conn, "price_rounding");
+    let suggested_price =
+        cost_per_portion.map(|c| round_menu_price(c / (target_food_cost_pct / 100.0), &rounding));
+
+    let menu_price = menu_price.filter(|p| *p > 0.0);
+    let (food_cost_pct, margin) = match (menu_price, cost_per_portion) {
+        (Some(p), Some(c)) => (Some(c / p * 100.0), Some(p - c)),
+        _ => (None, None),
+    };
+
+    Ok(RecipePricing {
+        servings,
+        portion_size,
+        portion_unit_id,
+        portion_unit_name,
+        cost_per_portion,
+        recipe_target_pct,
+        target_food_cost_pct,
+        suggested_price,
+        menu_price,
+        food_cost_pct,
+        margin,
+    })
+}
+
+/// Costs and prices a recipe in one call, sharing `ctx` across recipes.
+pub fn price_recipe(
+    conn: &Connection,
+    recipe_id: i64,
+    ctx: &mut CostContext,
+) -> Result<RecipePricing, String> {
+    let costed = cost_recipe(conn, recipe_id, ctx)?;
+    recipe_pricing(conn, recipe_id, costed.total_cost, costed.missing_costs)
+}
+
+/// The `recipe_costs` build a stamp stands for: the version of their inputs
+/// and today's date, since awards in effect change with the date.
+fn costs_stamp(conn: &Connection) -> Result<(String, Option<String>), String> {
+    let (version, built) = cache_versions(conn, "recipe_costs")?;
+    let today: String = conn
+        .query_row("SELECT date('now', 'localtime')", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok((format!("{}:{}", version, today), built))
+}
+
+/// Whether anything recipe costs are built from changed since `refresh_costs`
+/// last ran.
+pub fn costs_stale(conn: &Connection) -> Result<bool, String> {
+    let (stamp, built) = costs_stamp(conn)?;
+    Ok(built.as_deref() != Some(stamp.as_str()))
+}
+
+/// Re-prices every recipe into `recipe_costs` so recipe lists can read costs
+/// instead of recosting on every search. Returns how many were priced.
+pub fn refresh_costs(conn: &Connection) -> Result<usize, String> {
+    let (stamp, _) = costs_stamp(conn)?;
+    let mut stmt = conn
+        .prepare("SELECT recipe_id FROM recipes ORDER BY recipe_id")
+        .map_err(|e| e.to_string())?;
+    let recipe_ids: Vec<i64> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    conn.execute("DELETE FROM recipe_costs", [])
+        .map_err(|e| e.to_string())?;
+    let mut ctx = CostContext::default();
+    for &recipe_id in &recipe_ids {
+        let priced = price_recipe(conn, recipe_id, &mut ctx)?;
+        conn.execute(
+            "INSERT INTO recipe_costs (recipe_id, servings, cost_per_portion, menu_price, food_cost_pct, margin)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                recipe_id,
+                priced.servings,
+                priced.cost_per_portion,
+                priced.menu_price,
+                priced.food_cost_pct,
+                priced.margin
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    mark_cache_built(conn, "recipe_costs", &stamp)?;
+    Ok(recipe_ids.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{costs_stale, refresh_costs, round_menu_price};
+    use rusqlite::Connection;
+
+    #[test]
+    fn rounds_menu_prices_up() {
+        assert_eq!(round_menu_price(12.34, "none"), 12.34);
+        assert_eq!(round_menu_price(12.34, "0.25"), 12.5);
+        assert_eq!(round_menu_price(12.5, "0.25"), 12.5);
+        assert_eq!(round_menu_price(12.34, ".95"), 12.95);
+        assert_eq!(round_menu_price(12.97, ".95"), 13.95);
+        assert_eq!(round_menu_price(12.34, ".49/.95"), 12.49);
+        assert_eq!(round_menu_price(12.51, ".49/.95"), 12.95);
+        assert_eq!(round_menu_price(12.96, ".49/.95"), 13.49);
+        assert_eq!(round_menu_price(0.0, ".95"), 0.0);
+    }
+
+    #[test]
+    fn stored_costs_follow_recipe_changes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO recipes (recipe_id, name, servings, menu_price) VALUES (1, 'Soup', 4, 5)",
+            [],
+        )
+        .unwrap();
+        let menu_price = || -> Option<f64> {
+            conn.query_row(
+                "SELECT menu_price FROM recipe_costs WHERE recipe_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        assert!(costs_stale(&conn).unwrap());
+        assert_eq!(refresh_costs(&conn).unwrap(), 1);
+        assert_eq!(menu_price(), Some(5.0));
+        crate::db::init_db(&conn).unwrap();
+        assert!(!costs_stale(&conn).unwrap());
+
+        conn.execute("UPDATE recipes SET menu_price = 6 WHERE recipe_id = 1", [])
+            .unwrap();
+        assert!(costs_stale(&conn).unwrap());
+        refresh_costs(&conn).unwrap();
+        assert_eq!(menu_price(), Some(6.0));
+
+        // Swapped values leave any sum unchanged; the version still moves.
+        conn.execute_batch(
+            "INSERT INTO items (item_id, name) VALUES (1, 'Leeks'), (2, 'Onions');
+             INSERT INTO item_preps (prep_id, item_id, name, yield_pct) VALUES (1, 1, 'Sliced', 60), (2, 2, 'Diced', 80);",
+        )
+        .unwrap();
+        refresh_costs(&conn).unwrap();
+        conn.execute("UPDATE item_preps SET yield_pct = 140 - yield_pct", [])
+            .unwrap();
+        assert!(costs_stale(&conn).unwrap());
+        conn.execute(
+            "INSERT INTO recp_items (recipe_id, recp_item_id, item_id, qty) VALUES (1, 1, 1, 1)",
+            [],
+        )
+        .unwrap();
+        refresh_costs(&conn).unwrap();
+        conn.execute("UPDATE recp_items SET prep_id = 2 WHERE recipe_id = 1", [])
+            .unwrap();
+        assert!(costs_stale(&conn).unwrap());
+    }
+}
@@ -0,0 +1,666 @@
+use crate::costing::{convert_item_qty, default_purch_unit};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+
+pub struct PurchaseOrder {
+    pub po_id: i64,
+    pub po_number: String,
+    pub vendor_id: i64,
+    pub vendor_name: String,
+    /// "draft", "sent", "partial" or "closed".
+    pub status: String,
+    pub order_date: String,
+    pub expected_date: String,
+    pub notes: String,
+    pub line_count: i64,
+    pub total: f64,
+}
+
+pub struct PoLine {
+    pub po_line_id: i64,
+    pub item_id: i64,
+    pub item_name: String,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    pub qty: f64,
+    pub price: Option<f64>,
+    pub received_qty: f64,
+}
+
+/// A received invoice line and how it compares with the PO.
+pub struct ReceiptLine {
+    pub po_line_id: Option<i64>,
+    pub item_name: String,
+    pub unit_name: String,
+    pub qty: f64,
+    pub price: Option<f64>,
+    /// "OK", "Short", "Over", "Substitution", "Price" or "Not on PO".
+    pub flag: String,
+    pub note: String,
+}
+
+pub struct Receipt {
+    pub receipt_id: i64,
+    pub invoice_id: i64,
+    pub invoice_no: String,
+    pub received_date: String,
+    pub lines: Vec<ReceiptLine>,
+}
+
+pub struct ReceiveLine {
+    pub po_line_id: Option<i64>,
+    pub item_id: i64,
+    pub unit_id: Option<i64>,
+    pub qty: f64,
+    pub price: Option<f64>,
+}
+
+const PO_SELECT: &str = "SELECT p.po_id, COALESCE(p.po_number, ''), p.vendor_id, COALESCE(v.name, ''),
+            COALESCE(p.status, 'draft'), COALESCE(p.order_date, ''), COALESCE(p.expected_date, ''),
+            COALESCE(p.notes, ''),
+            (SELECT COUNT(*) FROM po_lines l WHERE l.po_id = p.po_id),
+            (SELECT COALESCE(SUM(l.qty * COALESCE(l.price, 0)), 0) FROM po_lines l WHERE l.po_id = p.po_id)
+     FROM purchase_orders p
+     LEFT JOIN vendors v ON v.vendor_id = p.vendor_id";
+
+fn po_from_row(row: &rusqlite::Row) -> rusqlite::Result<PurchaseOrder> {
+    Ok(PurchaseOrder {
+        po_id: row.get(0)?,
+        po_number: row.get(1)?,
+        vendor_id: row.get(2)?,
+        vendor_name: row.get(3)?,
+        status: row.get(4)?,
+        order_date: row.get(5)?,
+        expected_date: row.get(6)?,
+        notes: row.get(7)?,
+        line_count: row.get(8)?,
+        total: row.get(9)?,
+    })
+}
+
+pub fn list_orders(
+    conn: &Connection,
+    status: Option<&str>,
+    vendor_id: Option<i64>,
+) -> Result<Vec<PurchaseOrder>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE (?1 IS NULL OR p.status = ?1) AND (?2 IS NULL OR p.vendor_id = ?2)
+             ORDER BY p.order_date DESC, p.po_id DESC",
+            PO_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![status, vendor_id], po_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+pub fn load_order(conn: &Connection, po_id: i64) -> Result<PurchaseOrder, String> {
+    conn.query_row(
+        &format!("{} WHERE p.po_id = ?1", PO_SELECT),
+        [po_id],
+        po_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Purchase order {} not found", po_id))
+}
+
+pub fn order_lines(conn: &Connection, po_id: i64) -> Result<Vec<PoLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.po_line_id, l.item_id, COALESCE(i.name, ''), l.unit_id, COALESCE(u.sing, '-'),
+                    COALESCE(l.qty, 0), l.price, COALESCE(l.received_qty, 0)
+             FROM po_lines l
+             LEFT JOIN items i ON i.item_id = l.item_id
+             LEFT JOIN units u ON u.unit_id = l.unit_id
+             WHERE l.po_id = ?1
+             ORDER BY l.po_line_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([po_id], |row| {
+            Ok(PoLine {
+                po_line_id: row.get(0)?,
+                item_id: row.get(1)?,
+                item_name: row.get(2)?,
+                unit_id: row.get(3)?,
+                unit_name: row.get(4)?,
+                qty: row.get(5)?,
+                price: row.get(6)?,
+                received_qty: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+pub fn order_receipts(conn: &Connection, po_id: i64) -> Result<Vec<Receipt>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.receipt_id, r.invoice_id, COALESCE(i.invoice_no, ''), COALESCE(r.received_date, '')
+             FROM po_receipts r
+             LEFT JOIN invoices i ON i.invoice_id = r.invoice_id
+             WHERE r.po_id = ?1
+             ORDER BY r.received_date, r.receipt_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let headers: Vec<(i64, i64, String, String)> = stmt
+        .query_map([po_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut line_stmt = conn
+        .prepare(
+            "SELECT r.po_line_id, COALESCE(i.name, ''), COALESCE(u.sing, '-'), COALESCE(r.qty, 0), r.price,
+                    COALESCE(r.flag, 'OK'), COALESCE(r.note, '')
+             FROM po_receipt_lines r
+             LEFT JOIN items i ON i.item_id = r.item_id
+             LEFT JOIN units u ON u.unit_id = r.unit_id
+             WHERE r.receipt_id = ?1
+             ORDER BY r.receipt_line_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut receipts = Vec::new();
+    for (receipt_id, invoice_id, invoice_no, received_date) in headers {
+        let lines = line_stmt
+            .query_map([receipt_id], |row| {
+                Ok(ReceiptLine {
+                    po_line_id: row.get(0)?,
+                    item_name: row.get(1)?,
+                    unit_name: row.get(2)?,
+                    qty: row.get(3)?,
+                    price: row.get(4)?,
+                    flag: row.get(5)?,
+                    note: row.get(6)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        receipts.push(Receipt {
+            receipt_id,
+            invoice_id,
+            invoice_no,
+            received_date,
+            lines,
+        });
+    }
+    Ok(receipts)
+}
+
+/// The price a vendor last charged for an item in `unit_id`, falling back
+/// to the price list when the unit is the item's purchase unit.
+pub fn expected_price(
+    conn: &Connection,
+    item_id: i64,
+    vendor_id: i64,
+    unit_id: Option<i64>,
+) -> Option<f64> {
+    let unit_id = unit_id.or_else(|| default_purch_unit(conn, item_id))?;
+    conn.query_row(
+        "SELECT price FROM trans
+         WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id = ?3 AND price > 0
+         ORDER BY trans_date DESC, trans_id DESC LIMIT 1",
+        (item_id, vendor_id, unit_id),
+        |row| row.get(0),
+    )
+    .ok()
+    .or_else(|| {
+        if default_purch_unit(conn, item_id) != Some(unit_id) {
+            return None;
+        }
+        conn.query_row(
+            "SELECT price FROM inv_prices WHERE item_id = ?1 AND vendor_id = ?2 AND price > 0 LIMIT 1",
+            (item_id, vendor_id),
+            |row| row.get(0),
+        )
+        .ok()
+    })
+}
+
+/// Creates a draft PO and returns its id. The PO number is derived from
+/// the id so it is unique without a separate counter.
+pub fn create_order(
+    conn: &Connection,
+    vendor_id: i64,
+    expected_date: Option<&str>,
+    notes: Option<&str>,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO purchase_orders (vendor_id, status, order_date, expected_date, notes, created_at)
+         VALUES (?1, 'draft', date('now', 'localtime'), ?2, ?3, datetime('now', 'localtime'))",
+        rusqlite::params![vendor_id, expected_date, notes],
+    )
+    .map_err(|e| e.to_string())?;
+    let po_id = conn.last_insert_rowid();
+    conn.execute(
+        "UPDATE purchase_orders SET po_number = printf('PO-%05d', po_id) WHERE po_id = ?1",
+        [po_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(po_id)
+}
+
+fn require_status(order: &PurchaseOrder, allowed: &[&str], action: &str) -> Result<(), String> {
+    if allowed.contains(&order.status.as_str()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is {}; it can't be {}",
+            order.po_number, order.status, action
+        ))
+    }
+}
+
+/// Adds a line to a draft PO, or updates it when `po_line_id` is given.
+/// A missing price is filled from the vendor's last price.
+pub fn save_line(
+    conn: &Connection,
+    po_id: i64,
+    po_line_id: Option<i64>,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: f64,
+    price: Option<f64>,
+) -> Result<(), String> {
+    let order = load_order(conn, po_id)?;
+    require_status(&order, &["draft"], "edited")?;
+    if qty <= 0.0 {
+        return Err("Quantity must be greater than zero".to_string());
+    }
+    let unit_id = unit_id.or_else(|| default_purch_unit(conn, item_id));
+    let price = price.or_else(|| expected_price(conn, item_id, order.vendor_id, unit_id));
+    match po_line_id {
+        Some(line_id) => conn.execute(
+            "UPDATE po_lines SET item_id = ?3, unit_id = ?4, qty = ?5, price = ?6 WHERE po_line_id = ?1 AND po_id = ?2",
+            rusqlite::params![line_id, po_id, item_id, unit_id, qty, price],
+        ),
+        None => conn.execute(
+            "INSERT INTO po_lines (po_id, item_id, unit_id, qty, price, received_qty) VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+            rusqlite::params![po_id, item_id, unit_id, qty, price],
+        ),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn delete_line(conn: &Connection, po_line_id: i64) -> Result<(), String> {
+    let po_id: i64 = conn
+        .query_row(
+            "SELECT po_id FROM po_lines WHERE po_line_id = ?1",
+            [po_line_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("PO line {} not found", po_line_id))?;
+    require_status(&load_order(conn, po_id)?, &["draft"], "edited")?;
+    conn.execute("DELETE FROM po_lines WHERE po_line_id = ?1", [po_line_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Moves a PO through draft → sent → partial → closed. Sent orders can go
+/// back to draft until something is received; open orders can be closed
+/// short.
+pub fn set_status(conn: &Connection, po_id: i64, status: &str) -> Result<(), String> {
+    let order = load_order(conn, po_id)?;
+    match status {
+        "sent" => {
+            require_status(&order, &["draft"], "sent")?;
+            if order.line_count == 0 {
+                return Err(format!("{} has no lines", order.po_number));
+            }
+        }
+        "draft" => require_status(&order, &["sent"], "reopened")?,
+        "closed" => require_status(&order, &["sent", "partial"], "closed")?,
+        other => return Err(format!("Unknown PO status '{}'", other)),
+    }
+    conn.execute(
+        "UPDATE purchase_orders SET status = ?2,
+                sent_at = CASE WHEN ?2 = 'sent' THEN datetime('now', 'localtime') ELSE sent_at END,
+                closed_at = CASE WHEN ?2 = 'closed' THEN datetime('now', 'localtime') ELSE closed_at END
+         WHERE po_id = ?1",
+        rusqlite::params![po_id, status],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn delete_order(conn: &Connection, po_id: i64) -> Result<(), String> {
+    require_status(&load_order(conn, po_id)?, &["draft"], "deleted")?;
+    conn.execute("DELETE FROM po_lines WHERE po_id = ?1", [po_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM purchase_orders WHERE po_id = ?1", [po_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Receives a shipment against a sent PO: writes an `invoices` header and
+/// one `trans` row per received line, records each line's comparison with
+/// the PO (short, over, substitution, price difference or not on the PO),
+/// and moves the PO to partial or closed. Returns the new invoice id.
+pub fn receive(
+    conn: &Connection,
+    po_id: i64,
+    invoice_no: &str,
+    invoice_date: &str,
+    lines: &[ReceiveLine],
+) -> Result<i64, String> {
+    let order = load_order(conn, po_id)?;
+    require_status(&order, &["sent", "partial"], "received")?;
+    if lines.iter().all(|l| l.qty <= 0.0) {
+        return Err("Enter at least one received quantity".to_string());
+    }
+    let po_lines = order_lines(conn, po_id)?;
+
+    // Receipts count down from -1 so their invoices and trans never collide
+    // with the ids a re-import of the exports brings back.
+    let invoice_id: i64 = conn
+        .query_row(
+            "SELECT MIN(COALESCE(MIN(invoice_id), 0), 0) - 1 FROM invoices",
+            [],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO invoices (status, invoice_id, invoice_date, vendor_id, invoice_no, freight, total)
+         VALUES (1, ?1, ?2, ?3, ?4, 0, 0)",
+        rusqlite::params![invoice_id, invoice_date, order.vendor_id, invoice_no],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO po_receipts (po_id, invoice_id, received_date) VALUES (?1, ?2, ?3)",
+        rusqlite::params![po_id, invoice_id, invoice_date],
+    )
+    .map_err(|e| e.to_string())?;
+    let receipt_id = conn.last_insert_rowid();
+    let first_trans: i64 = conn
+        .query_row(
+            "SELECT MIN(COALESCE(MIN(trans_id), 0), 0) - 1 FROM trans",
+            [],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // Convert what arrived into each PO line's unit, through the received
+    // item's own conversions so substitutions count too, and total it per
+    // PO line before judging short or over.
+    let mut received: HashMap<i64, f64> = HashMap::new();
+    let mut arrived = Vec::new();
+    for line in lines.iter().filter(|l| l.qty > 0.0) {
+        let po_line = line
+            .po_line_id
+            .and_then(|id| po_lines.iter().find(|p| p.po_line_id == id));
+        if line.po_line_id.is_some() && po_line.is_none() {
+            return Err(format!(
+                "Line {} is not on {}",
+                line.po_line_id.unwrap_or(0),
+                order.po_number
+            ));
+        }
+        let unit_id = line.unit_id.or(po_line.and_then(|p| p.unit_id));
+        let in_po_unit = match (po_line, unit_id) {
+            (Some(p), Some(from)) => match p.unit_id {
+                Some(to) => convert_item_qty(conn, line.item_id, line.qty, from, to)?,
+                None => Some(line.qty),
+            },
+            _ => Some(line.qty),
+        };
+        if let Some(p) = po_line {
+            *received.entry(p.po_line_id).or_insert(p.received_qty) += in_po_unit.unwrap_or(0.0);
+        }
+        arrived.push((line, po_line, unit_id, in_po_unit));
+    }
+
+    let mut total = 0.0;
+    for (trans_id, (line, po_line, unit_id, in_po_unit)) in
+        (0..).map(|n| first_trans - n).zip(arrived)
+    {
+        // Without an invoiced price, the PO price is carried over into the received unit.
+        let price = line.price.or_else(|| {
+            let p = po_line.filter(|p| p.item_id == line.item_id)?;
+            Some(p.price? * in_po_unit? / line.qty)
+        });
+        let ext_cost = price.unwrap_or(0.0) * line.qty;
+        total += ext_cost;
+
+        let mut flags = Vec::new();
+        let mut notes = Vec::new();
+        match po_line {
+            None => flags.push("Not on PO"),
+            Some(p) => {
+                if line.item_id != p.item_id {
+                    flags.push("Substitution");
+                    notes.push(format!("ordered {}", p.item_name));
+                }
+                let received = received[&p.po_line_id];
+                if in_po_unit.is_none() {
+                    notes.push("received unit has no conversion to the PO unit".to_string());
+                } else if received < p.qty - 1e-9 {
+                    flags.push("Short");
+                    notes.push(format!(
+                        "{:.2} of {:.2} {} received",
+                        received, p.qty, p.unit_name
+                    ));
+                } else if received > p.qty + 1e-9 {
+                    flags.push("Over");
+                    notes.push(format!(
+                        "{:.2} of {:.2} {} received",
+                        received, p.qty, p.unit_name
+                    ));
+                }
+                if let (Some(paid), Some(expected)) = (price, p.price) {
+                    if line.item_id == p.item_id
+                        && unit_id == p.unit_id
+                        && (paid - expected).abs() > 0.005
+                    {
+                        flags.push("Price");
+                        notes.push(format!("${:.2} vs PO ${:.2}", paid, expected));
+                    }
+                }
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO trans (status, invoice_id, trans_id, item_id, trans_date, vendor_id, price, qty, unit_id, ext_cost)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                invoice_id,
+                trans_id,
+                line.item_id,
+                invoice_date,
+                order.vendor_id,
+                price.unwrap_or(0.0),
+                line.qty,
+                unit_id,
+                ext_cost
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO po_receipt_lines (receipt_id, po_line_id, item_id, unit_id, qty, price, trans_id, flag, note)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                receipt_id,
+                po_line.map(|p| p.po_line_id),
+                line.item_id,
+                unit_id,
+                line.qty,
+                price,
+                trans_id,
+                if flags.is_empty() { "OK".to_string() } else { flags.join(", ") },
+                notes.join("; ")
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for (po_line_id, qty) in &received {
+        conn.execute(
+            "UPDATE po_lines SET received_qty = ?2 WHERE po_line_id = ?1",
+            rusqlite::params![po_line_id, qty],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Ordered lines missing from this shipment are short.
+    for p in po_lines
+        .iter()
+        .filter(|p| !received.contains_key(&p.po_line_id) && p.received_qty < p.qty - 1e-9)
+    {
+        conn.execute(
+            "INSERT INTO po_receipt_lines (receipt_id, po_line_id, item_id, unit_id, qty, price, flag, note)
+             VALUES (?1, ?2, ?3, ?4, 0, NULL, 'Short', ?5)",
+            rusqlite::params![
+                receipt_id,
+                p.po_line_id,
+                p.item_id,
+                p.unit_id,
+                format!("{:.2} of {:.2} {} received", p.received_qty, p.qty, p.unit_name)
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute(
+        "UPDATE invoices SET total = ?2 WHERE invoice_id = ?1",
+        rusqlite::params![invoice_id, total],
+    )
+    .map_err(|e| e.to_string())?;
+    let outstanding: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM po_lines WHERE po_id = ?1 AND COALESCE(received_qty, 0) < qty - 1e-9",
+            [po_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if outstanding == 0 {
+        set_status(conn, po_id, "closed")?;
+    } else {
+        conn.execute(
+            "UPDATE purchase_orders SET status = 'partial' WHERE po_id = ?1",
+            [po_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(invoice_id)
+}
+
+/// Creates one draft PO per vendor from an order guide, skipping lines with
+/// no vendor. Returns the new PO numbers.
+pub fn orders_from_guide(
+    conn: &Connection,
+    guide: &crate::order_guide::OrderGuide,
+) -> Result<Vec<String>, String> {
+    let mut created = Vec::new();
+    for vendor in &guide.vendors {
+        let vendor_id = match vendor.vendor_id {
+            Some(v) => v,
+            None => continue,
+        };
+        let po_id = create_order(
+            conn,
+            vendor_id,
+            None,
+            Some(&format!("Order guide {}", guide.order_date)),
+        )?;
+        for line in &vendor.lines {
+            save_line(
+                conn,
+                po_id,
+                None,
+                line.item_id,
+                Some(line.order_unit_id),
+                line.order_qty,
+                line.price,
+            )?;
+        }
+        created.push(load_order(conn, po_id)?.po_number);
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        create_order, order_lines, order_receipts, receive, save_line, set_status, ReceiveLine,
+    };
+    use rusqlite::Connection;
+
+    /// A sent PO for 4 cases of item 1. Item 2, the usual substitute, comes
+    /// in 40 lb cases.
+    fn po_db() -> (Connection, i64, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (2, 'Pound', 'Pounds'), (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Chicken Thigh'), (2, 'Chicken Leg');
+             INSERT INTO vendors (vendor_id, name) VALUES (7, 'Sysco');
+             INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status) VALUES (2, 0, 3, 2, 1, 40, 1);",
+        )
+        .unwrap();
+        let po_id = create_order(&conn, 7, None, None).unwrap();
+        save_line(&conn, po_id, None, 1, Some(3), 4.0, Some(50.0)).unwrap();
+        set_status(&conn, po_id, "sent").unwrap();
+        let po_line_id = order_lines(&conn, po_id).unwrap()[0].po_line_id;
+        (conn, po_id, po_line_id)
+    }
+
+    fn line(po_line_id: i64, item_id: i64, unit_id: i64, qty: f64) -> ReceiveLine {
+        ReceiveLine {
+            po_line_id: Some(po_line_id),
+            item_id,
+            unit_id: Some(unit_id),
+            qty,
+            price: None,
+        }
+    }
+
+    #[test]
+    fn split_lines_add_up_against_one_po_line() {
+        let (conn, po_id, po_line_id) = po_db();
+        let invoice_id = receive(
+            &conn,
+            po_id,
+            "A1",
+            "2026-03-02",
+            &[line(po_line_id, 1, 3, 2.0), line(po_line_id, 1, 3, 2.0)],
+        )
+        .unwrap();
+        assert_eq!(invoice_id, -1);
+        let trans_ids: Vec<i64> = conn
+            .prepare("SELECT trans_id FROM trans ORDER BY trans_id DESC")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(trans_ids, vec![-1, -2]);
+        assert_eq!(order_lines(&conn, po_id).unwrap()[0].received_qty, 4.0);
+        let receipt = &order_receipts(&conn, po_id).unwrap()[0];
+        assert!(receipt.lines.iter().all(|l| l.flag == "OK"));
+    }
+
+    #[test]
+    fn substitutions_count_in_the_po_unit() {
+        let (conn, po_id, po_line_id) = po_db();
+        receive(
+            &conn,
+            po_id,
+            "A1",
+            "2026-03-02",
+            &[line(po_line_id, 2, 2, 80.0)],
+        )
+        .unwrap();
+        assert_eq!(order_lines(&conn, po_id).unwrap()[0].received_qty, 2.0);
+        let receipt = &order_receipts(&conn, po_id).unwrap()[0];
+        assert_eq!(receipt.lines[0].flag, "Substitution, Short");
+    }
+}
@@ -0,0 +1,628 @@
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+
+/// Differences up to this many dollars are rounding, not discrepancies.
+pub const TOLERANCE: f64 = 0.02;
+
+pub const CREDIT_REASONS: [&str; 5] = ["short", "return", "damaged", "price", "other"];
+
+pub struct Discrepancy {
+    pub trans_id: Option<i64>,
+    /// header_total, line_math, duplicate_line, zero_price or missing_item.
+    pub kind: String,
+    pub expected: Option<f64>,
+    pub actual: Option<f64>,
+    pub message: String,
+}
+
+/// Reconciliation result for one invoice. `difference` is the header total
+/// less credits, minus lines and charges.
+pub struct InvoiceRecon {
+    pub invoice_id: i64,
+    pub invoice_no: String,
+    pub invoice_date: String,
+    pub vendor_name: String,
+    /// balanced, unbalanced or reviewed.
+    pub status: String,
+    pub lines_total: f64,
+    pub charges_total: f64,
+    pub credits_total: f64,
+    pub header_total: Option<f64>,
+    pub difference: Option<f64>,
+    pub checked_at: String,
+    pub note: String,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+pub struct ReconSummary {
+    pub checked: usize,
+    pub balanced: usize,
+    pub unbalanced: usize,
+    pub reviewed: usize,
+}
+
+pub struct CreditMemo {
+    pub credit_id: i64,
+    pub memo_no: String,
+    pub memo_date: String,
+    pub reason: String,
+    pub amount: f64,
+    pub trans_id: Option<i64>,
+    pub item_name: String,
+    pub qty: Option<f64>,
+    pub unit_name: String,
+    pub note: String,
+}
+
+pub struct NewCreditMemo {
+    pub invoice_id: i64,
+    pub memo_no: String,
+    pub memo_date: Option<String>,
+    pub reason: String,
+    /// Defaults to `qty` at the invoiced price of `trans_id`.
+    pub amount: Option<f64>,
+    pub trans_id: Option<i64>,
+    pub qty: Option<f64>,
+    pub note: String,
+}
+
+struct CreditedLine {
+    invoice_id: Option<i64>,
+    item_id: Option<i64>,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    price: Option<f64>,
+}
+
+struct LineRow {
+    trans_id: i64,
+    item_id: Option<i64>,
+    item_known: bool,
+    unit_id: Option<i64>,
+    qty: Option<f64>,
+    price: Option<f64>,
+    ext_cost: Option<f64>,
+}
+
+fn issue(
+    trans_id: Option<i64>,
+    kind: &str,
+    expected: Option<f64>,
+    actual: Option<f64>,
+    message: String,
+) -> Discrepancy {
+    Discrepancy {
+        trans_id,
+        kind: kind.to_string(),
+        expected,
+        actual,
+        message,
+    }
+}
+
+/// Checks one invoice and stores its status and discrepancies. An invoice
+/// marked reviewed stays reviewed while its discrepancies are unchanged.
+pub fn reconcile_invoice(conn: &Connection, invoice_id: i64) -> Result<String, String> {
+    let (header_total, freight): (Option<f64>, f64) = conn
+        .query_row(
+            "SELECT total, COALESCE(freight, 0) FROM invoices WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invoice {} not found", invoice_id))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.trans_id, t.item_id, i.item_id IS NOT NULL, t.unit_id, t.qty, t.price, t.ext_cost
+             FROM trans t LEFT JOIN items i ON i.item_id = t.item_id
+             WHERE t.invoice_id = ?1 ORDER BY t.trans_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok(LineRow {
+                trans_id: row.get(0)?,
+                item_id: row.get(1)?,
+                item_known: row.get(2)?,
+                unit_id: row.get(3)?,
+                qty: row.get(4)?,
+                price: row.get(5)?,
+                ext_cost: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut issues = Vec::new();
+    let mut lines_total = 0.0;
+    let mut seen: HashMap<(Option<i64>, Option<i64>, u64, u64), i64> = HashMap::new();
+    for row in rows {
+        let line = row.map_err(|e| e.to_string())?;
+        let id = Some(line.trans_id);
+        let computed = match (line.qty, line.price) {
+            (Some(q), Some(p)) => Some(q * p),
+            _ => None,
+        };
+        lines_total += line.ext_cost.or(computed).unwrap_or(0.0);
+
+        if !line.item_known {
+            let message = match line.item_id {
+                Some(item_id) => format!("Item {} is not in inventory", item_id),
+                None => "Line has no item".to_string(),
+            };
+            issues.push(issue(id, "missing_item", None, None, message));
+        }
+        if line.qty.unwrap_or(0.0) > 0.0
+            && line.price.unwrap_or(0.0) == 0.0
+            && line.ext_cost.unwrap_or(0.0) == 0.0
+        {
+            issues.push(issue(
+                id,
+                "zero_price",
+                None,
+                line.price,
+                "Quantity received at no price".to_string(),
+            ));
+        }
+        if let (Some(expected), Some(ext)) = (computed, line.ext_cost) {
+            if (expected - ext).abs() > TOLERANCE {
+                issues.push(issue(
+                    id,
+                    "line_math",
+                    Some(expected),
+                    Some(ext),
+                    format!(
+                        "Qty × price is {:.2} but the line extends to {:.2}",
+                        expected, ext
+                    ),
+                ));
+            }
+        }
+        let key = (
+            line.item_id,
+            line.unit_id,
+            line.qty.unwrap_or(0.0).to_bits(),
+            line.price.unwrap_or(0.0).to_bits(),
+        );
+        match seen.get(&key) {
+            Some(first) if line.item_id.is_some() => issues.push(issue(
+                id,
+                "duplicate_line",
+                None,
+                None,
+                format!("Same item, quantity and price as line {}", first),
+            )),
+            Some(_) => {}
+            None => {
+                seen.insert(key, line.trans_id);
+            }
+        }
+    }
+
+    let other_charges: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM invoice_charges WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let charges_total = freight + other_charges;
+    let credits_total: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM credit_memos WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let difference = header_total.map(|t| t - credits_total - (lines_total + charges_total));
+    if let (Some(total), Some(diff)) = (header_total, difference) {
+        if diff.abs() > TOLERANCE {
+            issues.push(issue(
+                None,
+                "header_total",
+                Some(lines_total + charges_total),
+                Some(total - credits_total),
+                format!(
+                    "Invoice total {:.2}{} vs lines and charges {:.2} ({:+.2})",
+                    total,
+                    if credits_total != 0.0 {
+                        format!(" less credits {:.2}", credits_total)
+                    } else {
+                        String::new()
+                    },
+                    lines_total + charges_total,
+                    diff
+                ),
+            ));
+        }
+    }
+
+    let previous: Option<(String, i64, Option<f64>)> = conn
+        .query_row(
+            "SELECT status, COALESCE(issue_count, 0), difference FROM invoice_reconciliation WHERE invoice_id = ?1",
+            [invoice_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let still_reviewed = matches!(
+        &previous,
+        Some((status, count, diff)) if status == "reviewed"
+            && *count == issues.len() as i64
+            && (diff.unwrap_or(0.0) - difference.unwrap_or(0.0)).abs() <= TOLERANCE
+    );
+    let status = if issues.is_empty() {
+        "balanced"
+    } else if still_reviewed {
+        "reviewed"
+    } else {
+        "unbalanced"
+    };
+
+    conn.execute(
+        "DELETE FROM invoice_discrepancies WHERE invoice_id = ?1",
+        [invoice_id],
+    )
+    .map_err(|e| e.to_string())?;
+    for d in &issues {
+        conn.execute(
+            "INSERT INTO invoice_discrepancies (invoice_id, trans_id, kind, expected, actual, message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![invoice_id, d.trans_id, d.kind, d.expected, d.actual, d.message],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    conn.execute(
+        "INSERT INTO invoice_reconciliation
+           (invoice_id, status, lines_total, charges_total, credits_total, header_total, difference, issue_count, checked_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now', 'localtime'))
+         ON CONFLICT(invoice_id) DO UPDATE SET
+           status = excluded.status, lines_total = excluded.lines_total, charges_total = excluded.charges_total,
+           credits_total = excluded.credits_total, header_total = excluded.header_total,
+           difference = excluded.difference, issue_count = excluded.issue_count, checked_at = excluded.checked_at,
+           reviewed_at = CASE WHEN excluded.status = 'reviewed' THEN reviewed_at END,
+           note = CASE WHEN excluded.status = 'reviewed' THEN note END",
+        rusqlite::params![
+            invoice_id,
+            status,
+            lines_total,
+            charges_total,
+            credits_total,
+            header_total,
+            difference,
+            issues.len() as i64
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(status.to_string())
+}
+
+/// Reconciles the given invoices and returns how many don't balance.
+pub fn reconcile_invoices(conn: &Connection, invoice_ids: &[i64]) -> Result<usize, String> {
+    let mut unbalanced = 0;
+    for &invoice_id in invoice_ids {
+        if reconcile_invoice(conn, invoice_id)? == "unbalanced" {
+            unbalanced += 1;
+        }
+    }
+    Ok(unbalanced)
+}
+
+pub fn reconcile_all(conn: &Connection) -> Result<ReconSummary, String> {
+    let mut stmt = conn
+        .prepare("SELECT invoice_id FROM invoices ORDER BY invoice_id")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM invoice_reconciliation WHERE invoice_id NOT IN (SELECT invoice_id FROM invoices)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM invoice_discrepancies WHERE invoice_id NOT IN (SELECT invoice_id FROM invoices)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    let mut summary = ReconSummary {
+        checked: 0,
+        balanced: 0,
+        unbalanced: 0,
+        reviewed: 0,
+    };
+    for invoice_id in ids {
+        match reconcile_invoice(conn, invoice_id)?.as_str() {
+            "balanced" => summary.balanced += 1,
+            "reviewed" => summary.reviewed += 1,
+            _ => summary.unbalanced += 1,
+        }
+        summary.checked += 1;
+    }
+    Ok(summary)
+}
+
+fn load_discrepancies(conn: &Connection, invoice_id: i64) -> Result<Vec<Discrepancy>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT trans_id, kind, expected, actual, COALESCE(message, '')
+             FROM invoice_discrepancies WHERE invoice_id = ?1 ORDER BY discrepancy_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok(Discrepancy {
+                trans_id: row.get(0)?,
+                kind: row.get(1)?,
+                expected: row.get(2)?,
+                actual: row.get(3)?,
+                message: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Reconciled invoices, newest first, optionally filtered by status.
+/// Returns at most `limit` rows and the number matching.
+pub fn list_reconciliation(
+    conn: &Connection,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<(Vec<InvoiceRecon>, i64), String> {
+    let total: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM invoice_reconciliation WHERE ?1 IS NULL OR status = ?1",
+            [status],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.invoice_id, COALESCE(i.invoice_no, ''), COALESCE(i.invoice_date, ''), COALESCE(v.name, '-'),
+                    r.status, COALESCE(r.lines_total, 0), COALESCE(r.charges_total, 0), COALESCE(r.credits_total, 0),
+                    r.header_total, r.difference, COALESCE(r.checked_at, ''), COALESCE(r.note, '')
+             FROM invoice_reconciliation r
+             JOIN invoices i ON i.invoice_id = r.invoice_id
+             LEFT JOIN vendors v ON v.vendor_id = i.vendor_id
+             WHERE ?1 IS NULL OR r.status = ?1
+             ORDER BY i.invoice_date DESC, r.invoice_id DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![status, limit], |row| {
+            Ok(InvoiceRecon {
+                invoice_id: row.get(0)?,
+                invoice_no: row.get(1)?,
+                invoice_date: row.get(2)?,
+                vendor_name: row.get(3)?,
+                status: row.get(4)?,
+                lines_total: row.get(5)?,
+                charges_total: row.get(6)?,
+                credits_total: row.get(7)?,
+                header_total: row.get(8)?,
+                difference: row.get(9)?,
+                checked_at: row.get(10)?,
+                note: row.get(11)?,
+                discrepancies: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut list = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for recon in &mut list {
+        recon.discrepancies = load_discrepancies(conn, recon.invoice_id)?;
+    }
+    Ok((list, total))
+}
+
+/// Marks an unbalanced invoice as reviewed, or re-checks it when
+/// `reviewed` is false.
+pub fn set_reviewed(
+    conn: &Connection,
+    invoice_id: i64,
+    reviewed: bool,
+    note: Option<&str>,
+) -> Result<String, String> {
+    if !reviewed {
+        conn.execute(
+            "UPDATE invoice_reconciliation SET status = 'unbalanced', reviewed_at = NULL WHERE invoice_id = ?1",
+            [invoice_id],
+        )
+        .map_err(|e| e.to_string())?;
+        return reconcile_invoice(conn, invoice_id);
+    }
+    let status = reconcile_invoice(conn, invoice_id)?;
+    if status == "balanced" {
+        return Ok(status);
+    }
+    conn.execute(
+        "UPDATE invoice_reconciliation
+         SET status = 'reviewed', reviewed_at = datetime('now', 'localtime'), note = COALESCE(?2, note)
+         WHERE invoice_id = ?1",
+        rusqlite::params![invoice_id, note],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok("reviewed".to_string())
+}
+
+pub fn list_credit_memos(conn: &Connection, invoice_id: i64) -> Result<Vec<CreditMemo>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.credit_id, COALESCE(c.memo_no, ''), COALESCE(c.memo_date, ''), c.reason, c.amount,
+                    c.trans_id, COALESCE(i.name, ''), c.qty, COALESCE(u.sing, ''), COALESCE(c.note, '')
+             FROM credit_memos c
+             LEFT JOIN items i ON i.item_id = c.item_id
+             LEFT JOIN units u ON u.unit_id = c.unit_id
+             WHERE c.invoice_id = ?1 ORDER BY c.credit_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([invoice_id], |row| {
+            Ok(CreditMemo {
+                credit_id: row.get(0)?,
+                memo_no: row.get(1)?,
+                memo_date: row.get(2)?,
+                reason: row.get(3)?,
+                amount: row.get(4)?,
+                trans_id: row.get(5)?,
+                item_name: row.get(6)?,
+                qty: row.get(7)?,
+                unit_name: row.get(8)?,
+                note: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Records a credit memo against an invoice (for a short shipment, return
+/// etc.) and re-reconciles the invoice. Returns the new credit id.
+pub fn add_credit_memo(conn: &Connection, memo: &NewCreditMemo) -> Result<i64, String> {
+    if !CREDIT_REASONS.contains(&memo.reason.as_str()) {
+        return Err(format!("Unknown credit reason '{}'", memo.reason));
+    }
+    let line = match memo.trans_id {
+        Some(trans_id) => Some(
+            conn.query_row(
+                "SELECT invoice_id, item_id, unit_id, qty, price FROM trans WHERE trans_id = ?1",
+                [trans_id],
+                |row| {
+                    Ok(CreditedLine {
+                        invoice_id: row.get(0)?,
+                        item_id: row.get(1)?,
+                        unit_id: row.get(2)?,
+                        qty: row.get(3)?,
+                        price: row.get(4)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Line {} not found", trans_id))?,
+        ),
+        None => None,
+    };
+    if let Some(line) = &line {
+        if line.invoice_id != Some(memo.invoice_id) {
+            return Err("That line is on a different invoice".to_string());
+        }
+        if let (Some(qty), Some(line_qty)) = (memo.qty, line.qty) {
+            if qty > line_qty + 1e-9 {
+                return Err(format!("Only {} were invoiced on that line", line_qty));
+            }
+        }
+    }
+    let amount = match (memo.amount, line.as_ref().and_then(|l| l.price), memo.qty) {
+        (Some(a), ..) => a,
+        (None, Some(price), Some(qty)) => qty * price,
+        _ => return Err("Enter the credit amount, or a line and quantity".to_string()),
+    };
+    if amount <= 0.0 {
+        return Err("Credit amount must be more than zero".to_string());
+    }
+    let item_id = line.as_ref().and_then(|l| l.item_id);
+    let unit_id = line.as_ref().and_then(|l| l.unit_id);
+    conn.execute(
+        "INSERT INTO credit_memos (invoice_id, memo_no, memo_date, reason, amount, trans_id, item_id, qty, unit_id, note, created_at)
+         VALUES (?1, ?2, COALESCE(?3, date('now', 'localtime')), ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now', 'localtime'))",
+        rusqlite::params![
+            memo.invoice_id,
+            memo.memo_no,
+            memo.memo_date,
+            memo.reason,
+            amount,
+            memo.trans_id,
+            item_id,
+            memo.qty,
+            unit_id,
+            memo.note
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let credit_id = conn.last_insert_rowid();
+    reconcile_invoice(conn, memo.invoice_id)?;
+    Ok(credit_id)
+}
+
+/// Deletes a credit memo and re-reconciles its invoice, returning the
+/// invoice id.
+pub fn delete_credit_memo(conn: &Connection, credit_id: i64) -> Result<i64, String> {
+    let invoice_id: i64 = conn
+        .query_row(
+            "SELECT invoice_id FROM credit_memos WHERE credit_id = ?1",
+            [credit_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Credit memo {} not found", credit_id))?;
+    conn.execute("DELETE FROM credit_memos WHERE credit_id = ?1", [credit_id])
+        .map_err(|e| e.to_string())?;
+    reconcile_invoice(conn, invoice_id)?;
+    Ok(invoice_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_credit_memo, reconcile_invoice, set_reviewed, NewCreditMemo};
+    use rusqlite::Connection;
+
+    fn credit(amount: f64) -> NewCreditMemo {
+        NewCreditMemo {
+            invoice_id: 1,
+            memo_no: String::new(),
+            memo_date: None,
+            reason: "short".to_string(),
+            amount: Some(amount),
+            trans_id: None,
+            qty: None,
+            note: String::new(),
+        }
+    }
+
+    fn difference(conn: &Connection) -> f64 {
+        conn.query_row(
+            "SELECT difference FROM invoice_reconciliation WHERE invoice_id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn credits_reduce_the_header_total_and_reviews_last_until_it_changes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO items (item_id, name) VALUES (1, 'Fryer Oil');
+             INSERT INTO invoices (invoice_id, vendor_id, freight, total) VALUES (1, 7, 5, 100);
+             INSERT INTO trans (trans_id, invoice_id, item_id, qty, price, ext_cost) VALUES (1, 1, 1, 2, 40, 80);",
+        )
+        .unwrap();
+        assert_eq!(reconcile_invoice(&conn, 1).unwrap(), "unbalanced");
+        assert_eq!(difference(&conn), 15.0);
+
+        assert_eq!(
+            set_reviewed(&conn, 1, true, Some("Driver tip")).unwrap(),
+            "reviewed"
+        );
+        assert_eq!(reconcile_invoice(&conn, 1).unwrap(), "reviewed");
+
+        add_credit_memo(&conn, &credit(5.0)).unwrap();
+        assert_eq!(difference(&conn), 10.0);
+        assert_eq!(reconcile_invoice(&conn, 1).unwrap(), "unbalanced");
+
+        add_credit_memo(&conn, &credit(10.0)).unwrap();
+        assert_eq!(reconcile_invoice(&conn, 1).unwrap(), "balanced");
+        assert_eq!(difference(&conn), 0.0);
+    }
+}
@@ -0,0 +1,619 @@
+use crate::costing::CostContext;
+use crate::pricing::price_recipe;
+use csv::StringRecord;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Built-in mapping profiles: name, item, qty, net sales, date, category.
+/// Each column lists the header names it accepts, separated by '|'.
+pub const BUILTIN_PROFILES: &[(&str, &str, &str, &str, &str, &str)] = &[
+    (
+        "Toast",
+        "Menu Item|Item",
+        "Item Qty|Qty|Quantity",
+        "Net Amount|Net Sales|Net Price",
+        "Order Date|Business Date|Date",
+        "Menu Group|Sales Category|Menu",
+    ),
+    (
+        "Square",
+        "Item|Item Name",
+        "Qty|Items Sold|Quantity",
+        "Net Sales|Gross Sales",
+        "Date",
+        "Category",
+    ),
+    (
+        "Clover",
+        "Name|Item Name|Item",
+        "Quantity|Items Sold|Sold|Qty",
+        "Net Sales|Amount|Total",
+        "Order Date|Date",
+        "Category|Categories",
+    ),
+];
+
+/// Share of the even-split popularity that still counts as popular (70% rule).
+const POPULARITY_FACTOR: f64 = 0.7;
+
+pub struct PosProfile {
+    pub profile_id: i64,
+    pub name: String,
+    pub item_col: String,
+    pub qty_col: String,
+    pub net_sales_col: String,
+    pub date_col: String,
+    pub category_col: String,
+    pub builtin: bool,
+}
+
+pub fn load_profile(conn: &Connection, profile_id: i64) -> Result<PosProfile, String> {
+    conn.query_row(
+        "SELECT profile_id, name, item_col, qty_col, COALESCE(net_sales_col, ''),
+                COALESCE(date_col, ''), COALESCE(category_col, ''), COALESCE(builtin, 0)
+         FROM pos_profiles WHERE profile_id = ?1",
+        [profile_id],
+        |row| {
+            Ok(PosProfile {
+                profile_id: row.get(0)?,
+                name: row.get(1)?,
+                item_col: row.get(2)?,
+                qty_col: row.get(3)?,
+                net_sales_col: row.get(4)?,
+                date_col: row.get(5)?,
+                category_col: row.get(6)?,
+                builtin: row.get::<_, i64>(7)? != 0,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Mapping profile {} not found", profile_id))
+}
+
+fn normalize_header(value: &str) -> String {
+    value
+        .replace('\u{feff}', "")
+        .trim()
+        .trim_matches('"')
+        .to_lowercase()
+}
+
+/// Finds the column whose header matches one of the `|`-separated names.
+fn find_column(header: &StringRecord, spec: &str) -> Option<usize> {
+    spec.split('|')
+        .map(normalize_header)
+        .filter(|name| !name.is_empty())
+        .find_map(|name| header.iter().position(|h| normalize_header(h) == name))
+}
+
+/// Parses POS amounts like "$1,234.50", "(12.00)" or "12".
+pub fn parse_amount(value: &str) -> Option<f64> {
+    let trimmed = value.trim();
+    let negative = (trimmed.starts_with('(') && trimmed.ends_with(')')) || trimmed.starts_with('-');
+    let cleaned: String = trimmed
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let amount = cleaned.parse::<f64>().ok()?;
+    Some(if negative { -amount } else { amount })
+}
+
+/// Converts M/D/YYYY, M/D/YY or YYYY-MM-DD (with or without a time part)
+/// into YYYY-MM-DD.
+pub fn normalize_date(value: &str) -> Option<String> {
+    let date = value.split_whitespace().next()?.split('T').next()?;
+    let parts: Vec<&str> = date.split(['/', '-']).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let nums: Vec<u32> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+    if nums.len() != 3 {
+        return None;
+    }
+    let (y, m, d) = if parts[0].len() == 4 {
+        (nums[0], nums[1], nums[2])
+    } else {
+        let y = if nums[2] < 100 {
+            2000 + nums[2]
+        } else {
+            nums[2]
+        };
+        (y, nums[0], nums[1])
+    };
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    Some(format!("{:04}-{:02}-{:02}", y, m, d))
+}
+
+pub struct SalesImportResult {
+    pub import_id: i64,
+    pub rows: usize,
+    pub skipped: usize,
+    pub new_items: usize,
+    pub unmapped_items: usize,
+    pub total_qty: f64,
+    pub total_sales: f64,
+}
+
+/// Imports an item-level sales CSV using a mapping profile. The header row is
+/// located automatically since POS exports often lead with report titles.
+/// Rows without a date column fall back to `period_start`.
+pub fn import_sales_csv(
+    conn: &Connection,
+    path: &Path,
+    profile: &PosProfile,
+    period_start: &str,
+    period_end: &str,
+) -> Result<SalesImportResult, String> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+    let records: Vec<StringRecord> = rdr
+        .records()
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let header_idx = records
+        .iter()
+        .take(25)
+        .position(|r| {
+            find_column(r, &profile.item_col).is_some()
+                && find_column(r, &profile.qty_col).is_some()
+        })
+        .ok_or_else(|| {
+            format!(
+                "No header row with item ({}) and quantity ({}) columns found for profile '{}'",
+                profile.item_col, profile.qty_col, profile.name
+            )
+        })?;
+    let header = &records[header_idx];
+    let item_idx = find_column(header, &profile.item_col).unwrap_or(0);
+    let qty_idx = find_column(header, &profile.qty_col).unwrap_or(0);
+    let sales_idx = find_column(header, &profile.net_sales_col);
+    let date_idx = find_column(header, &profile.date_col);
+    let category_idx = find_column(header, &profile.category_col);
+
+    let source = path.to_string_lossy().to_string();
+    // Importing the same export or period twice would double-count sales, so
+    // the earlier import has to be removed first.
+    let overlap: Option<(i64, String, String, String)> = conn
+        .query_row(
+            "SELECT import_id, COALESCE(source_file, ''), COALESCE(period_start, ''), COALESCE(period_end, '')
+             FROM sales_imports
+             WHERE profile_id = ?1
+               AND (source_file = ?2 OR (period_start <= ?4 AND period_end >= ?3))
+             ORDER BY import_id LIMIT 1",
+            rusqlite::params![profile.profile_id, source, period_start, period_end],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some((id, file, start, end)) = overlap {
+        return Err(format!(
+            "Import {} ({}, {} to {}) already covers this file or period for profile '{}'; delete it before importing again",
+            id, file, start, end, profile.name
+        ));
+    }
+    conn.execute(
+        "INSERT INTO sales_imports (profile_id, source_file, period_start, period_end, imported_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now', 'localtime'))",
+        rusqlite::params![profile.profile_id, source, period_start, period_end],
+    )
+    .map_err(|e| e.to_string())?;
+    let import_id = conn.last_insert_rowid();
+
+    let mut result = SalesImportResult {
+        import_id,
+        rows: 0,
+        skipped: 0,
+        new_items: 0,
+        unmapped_items: 0,
+        total_qty: 0.0,
+        total_sales: 0.0,
+    };
+    let mut pos_ids: HashMap<String, i64> = HashMap::new();
+
+    for record in records.iter().skip(header_idx + 1) {
+        let name = record
+            .get(item_idx)
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default();
+        let qty = record.get(qty_idx).and_then(parse_amount);
+        let (name, qty) = match (name, qty) {
+            (n, Some(q)) if !n.is_empty() && !n.eq_ignore_ascii_case("total") && q != 0.0 => (n, q),
+            _ => {
+                result.skipped += 1;
+                continue;
+            }
+        };
+        let net_sales = sales_idx.and_then(|i| record.get(i)).and_then(parse_amount);
+        let sale_date = date_idx
+            .and_then(|i| record.get(i))
+            .and_then(normalize_date)
+            .unwrap_or_else(|| period_start.to_string());
+        let category = category_idx
+            .and_then(|i| record.get(i))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let key = name.to_lowercase();
+        let pos_item_id = match pos_ids.get(&key) {
+            Some(id) => *id,
+            None => {
+                let existing: Option<i64> = conn
+                    .query_row(
+                        "SELECT pos_item_id FROM pos_items WHERE name = ?1 COLLATE NOCASE",
+                        [&name],
+                        |row| row.get(0),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?;
+                let id = match existing {
+                    Some(id) => {
+                        if category.is_some() {
+                            conn.execute(
+                                "UPDATE pos_items SET category = COALESCE(category, ?1) WHERE pos_item_id = ?2",
+                                rusqlite::params![category, id],
+                            )
+                            .map_err(|e| e.to_string())?;
+                        }
+                        id
+                    }
+                    None => {
+                        conn.execute(
+                            "INSERT INTO pos_items (name, category, portions, ignored) VALUES (?1, ?2, 1, 0)",
+                            rusqlite::params![name, category],
+                        )
+                        .map_err(|e| e.to_string())?;
+                        result.new_items += 1;
+                        conn.last_insert_rowid()
+                    }
+                };
+                pos_ids.insert(key, id);
+                id
+            }
+        };
+
+        conn.execute(
+            "INSERT INTO sales (import_id, pos_item_id, sale_date, qty, net_sales) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![import_id, pos_item_id, sale_date, qty, net_sales],
+        )
+        .map_err(|e| e.to_string())?;
+        result.rows += 1;
+        result.total_qty += qty;
+        result.total_sales += net_sales.unwrap_or(0.0);
+    }
+
+    if result.rows == 0 {
+        return Err("The file contained no sales rows".to_string());
+    }
+
+    conn.execute(
+        "UPDATE sales_imports SET row_count = ?1, total_qty = ?2, total_sales = ?3 WHERE import_id = ?4",
+        rusqlite::params![result.rows as i64, result.total_qty, result.total_sales, import_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    result.unmapped_items = conn
+        .query_row(
+            "SELECT COUNT(DISTINCT s.pos_item_id) FROM sales s
+             JOIN pos_items p ON p.pos_item_id = s.pos_item_id
+             WHERE s.import_id = ?1 AND p.recipe_id IS NULL AND COALESCE(p.ignored, 0) = 0",
+            [import_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())? as usize;
+
+    Ok(result)
+}
+
+pub struct MenuEngineeringRow {
+    pub pos_item_id: i64,
+    pub name: String,
+    pub category: String,
+    pub recipe_id: i64,
+    pub recipe_name: String,
+    pub units_sold: f64,
+    pub menu_price: Option<f64>,
+    pub plate_cost: Option<f64>,
+    pub contribution_margin: Option<f64>,
+    pub revenue: f64,
+    pub total_cost: Option<f64>,
+    pub total_margin: Option<f64>,
+    pub mix_pct: f64,
+    pub popularity_index: f64,
+    pub classification: String,
+}
+
+pub struct MenuEngineeringReport {
+    pub date_from: String,
+    pub date_to: String,
+    pub category: String,
+    pub rows: Vec<MenuEngineeringRow>,
+    pub total_units: f64,
+    pub total_revenue: f64,
+    pub total_cost: f64,
+    pub total_margin: f64,
+    pub avg_contribution_margin: Option<f64>,
+    pub popularity_threshold_pct: f64,
+    pub food_cost_pct: Option<f64>,
+    pub uncosted_items: usize,
+    pub unmapped_items: usize,
+    pub unmapped_units: f64,
+}
+
+/// Classifies an item from its popularity and margin against the menu averages.
+pub fn classify(popular: bool, profitable: bool) -> &'static str {
+    match (popular, profitable) {
+        (true, true) => "Star",
+        (true, false) => "Plowhorse",
+        (false, true) => "Puzzle",
+        (false, false) => "Dog",
+    }
+}
+
+/// pos_item_id, name, category, recipe_id, recipe_name, portions, units, net sales
+type SalesMixRow = (
+    i64,
+    String,
+    String,
+    Option<i64>,
+    String,
+    f64,
+    f64,
+    Option<f64>,
+);
+
+/// Builds a Kasavana–Smith menu engineering report from mapped POS sales in
+/// the period. Items are popular when their mix share reaches 70% of an even
+/// split, and profitable when their contribution margin reaches the
+/// sales-weighted average. Items without a full plate cost are "Uncosted".
+pub fn menu_engineering(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+    category: &str,
+) -> Result<MenuEngineeringReport, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.pos_item_id, p.name, COALESCE(p.category, ''), p.recipe_id, COALESCE(r.name, ''),
+                    COALESCE(p.portions, 1), SUM(s.qty), SUM(s.net_sales)
+             FROM sales s
+             JOIN pos_items p ON p.pos_item_id = s.pos_item_id
+             LEFT JOIN recipes r ON r.recipe_id = p.recipe_id
+             WHERE COALESCE(p.ignored, 0) = 0
+               AND (?1 = '' OR s.sale_date >= ?1)
+               AND (?2 = '' OR s.sale_date <= ?2)
+               AND (?3 = '' OR p.category = ?3 COLLATE NOCASE)
+             GROUP BY p.pos_item_id
+             ORDER BY p.name COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let mix: Vec<SalesMixRow> = stmt
+        .query_map(rusqlite::params![date_from, date_to, category], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get::<_, Option<f64>>(6)?.unwrap_or(0.0),
+                row.get(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut ctx = CostContext::default();
+    let mut portion_costs: HashMap<i64, (Option<f64>, Option<f64>)> = HashMap::new();
+    let mut rows = Vec::new();
+    let mut unmapped_items = 0usize;
+    let mut unmapped_units = 0.0;
+
+    for (pos_item_id, name, item_category, recipe_id, recipe_name, portions, units, net_sales) in
+        mix
+    {
+        let recipe_id = match recipe_id {
+            Some(id) if units > 0.0 => id,
+            _ => {
+                unmapped_items += 1;
+                unmapped_units += units;
+                continue;
+            }
+        };
+        let (cost_per_portion, recipe_price) = match portion_costs.get(&recipe_id) {
+            Some(v) => *v,
+            None => {
+                let priced = price_recipe(conn, recipe_id, &mut ctx).ok();
+                let v = (
+                    priced.as_ref().and_then(|p| p.cost_per_portion),
+                    priced.as_ref().and_then(|p| p.menu_price),
+                );
+                portion_costs.insert(recipe_id, v);
+                v
+            }
+        };
+        let portions = if portions > 0.0 { portions } else { 1.0 };
+        // POS net sales give the real average price; the recipe's menu price
+        // only stands in when the export has no sales column.
+        let menu_price = match net_sales {
+            Some(total) if total > 0.0 => Some(total / units),
+            _ => recipe_price.map(|p| p * portions),
+        };
+        let plate_cost = cost_per_portion.map(|c| c * portions);
+        let contribution_margin = match (menu_price, plate_cost) {
+            (Some(p), Some(c)) => Some(p - c),
+            _ => None,
+        };
+        let revenue = menu_price.map(|p| p * units).unwrap_or(0.0);
+        rows.push(MenuEngineeringRow {
+            pos_item_id,
+            name,
+            category: item_category,
+            recipe_id,
+            recipe_name,
+            units_sold: units,
+            menu_price,
+            plate_cost,
+            contribution_margin,
+            revenue,
+            total_cost: plate_cost.map(|c| c * units),
+            total_margin: contribution_margin.map(|m| m * units),
+            mix_pct: 0.0,
+            popularity_index: 0.0,
+            classification: String::new(),
+        });
+    }
+
+    let total_units: f64 = rows.iter().map(|r| r.units_sold).sum();
+    let item_count = rows.len() as f64;
+    let even_share = if item_count > 0.0 {
+        100.0 / item_count
+    } else {
+        0.0
+    };
+    let popularity_threshold_pct = even_share * POPULARITY_FACTOR;
+
+    let costed: Vec<&MenuEngineeringRow> =
+        rows.iter().filter(|r| r.total_margin.is_some()).collect();
+    let costed_units: f64 = costed.iter().map(|r| r.units_sold).sum();
+    let total_cost: f64 = costed.iter().filter_map(|r| r.total_cost).sum();
+    let total_margin: f64 = costed.iter().filter_map(|r| r.total_margin).sum();
+    let costed_revenue: f64 = costed.iter().map(|r| r.revenue).sum();
+    let avg_contribution_margin = if costed_units > 0.0 {
+        Some(total_margin / costed_units)
+    } else {
+        None
+    };
+    let uncosted_items = rows.len() - costed.len();
+
+    for row in rows.iter_mut() {
+        row.mix_pct = if total_units > 0.0 {
+            row.units_sold / total_units * 100.0
+        } else {
+            0.0
+        };
+        row.popularity_index = if even_share > 0.0 {
+            row.mix_pct / even_share
+        } else {
+            0.0
+        };
+        row.classification = match (row.contribution_margin, avg_contribution_margin) {
+            (Some(cm), Some(avg)) => {
+                classify(row.mix_pct >= popularity_threshold_pct, cm >= avg).to_string()
+            }
+            _ => "Uncosted".to_string(),
+        };
+    }
+    rows.sort_by(|a, b| {
+        b.units_sold
+            .partial_cmp(&a.units_sold)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(MenuEngineeringReport {
+        date_from: date_from.to_string(),
+        date_to: date_to.to_string(),
+        category: category.to_string(),
+        total_units,
+        total_revenue: rows.iter().map(|r| r.revenue).sum(),
+        total_cost,
+        total_margin,
+        avg_contribution_margin,
+        popularity_threshold_pct,
+        food_cost_pct: if costed_revenue > 0.0 {
+            Some(total_cost / costed_revenue * 100.0)
+        } else {
+            None
+        },
+        uncosted_items,
+        unmapped_items,
+        unmapped_units,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, import_sales_csv, load_profile, normalize_date, parse_amount};
+    use rusqlite::Connection;
+    use std::path::PathBuf;
+
+    fn write_csv(name: &str, body: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("4chef-sales-{}-{}.csv", std::process::id(), name));
+        std::fs::write(&path, body).unwrap();
+        path
+    }
+
+    #[test]
+    fn amounts_and_dates_parse_pos_formats() {
+        assert_eq!(parse_amount("$1,234.50"), Some(1234.5));
+        assert_eq!(parse_amount("(12.00)"), Some(-12.0));
+        assert_eq!(parse_amount("-3"), Some(-3.0));
+        assert_eq!(parse_amount(""), None);
+        assert_eq!(normalize_date("3/7/2026"), Some("2026-03-07".to_string()));
+        assert_eq!(
+            normalize_date("3/7/26 14:05"),
+            Some("2026-03-07".to_string())
+        );
+        assert_eq!(
+            normalize_date("2026-03-07T09:00:00"),
+            Some("2026-03-07".to_string())
+        );
+        assert_eq!(normalize_date("13/7/2026"), None);
+        assert_eq!(normalize_date("March 7"), None);
+    }
+
+    #[test]
+    fn classify_follows_the_menu_engineering_quadrants() {
+        assert_eq!(classify(true, true), "Star");
+        assert_eq!(classify(true, false), "Plowhorse");
+        assert_eq!(classify(false, true), "Puzzle");
+        assert_eq!(classify(false, false), "Dog");
+    }
+
+    #[test]
+    fn import_finds_the_header_and_rejects_overlapping_periods() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let profile_id: i64 = conn
+            .query_row(
+                "SELECT profile_id FROM pos_profiles WHERE name = 'Square'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let profile = load_profile(&conn, profile_id).unwrap();
+        let path = write_csv(
+            "header",
+            "Item Sales Report\nMarch 2026\n\nItem,Qty,Net Sales,Category\nBurger,10,\"$120.00\",Mains\nFries,4,$16.00,Sides\nTotal,14,$136.00,\n",
+        );
+
+        let result = import_sales_csv(&conn, &path, &profile, "2026-03-01", "2026-03-31").unwrap();
+        assert_eq!((result.rows, result.skipped, result.new_items), (2, 1, 2));
+        assert_eq!(result.total_sales, 136.0);
+
+        let again = write_csv("again", "Item,Qty\nBurger,1\n");
+        let err = import_sales_csv(&conn, &again, &profile, "2026-03-15", "2026-04-15")
+            .err()
+            .unwrap();
+        assert!(
+            err.contains(&format!("Import {}", result.import_id)),
+            "{}",
+            err
+        );
+        assert!(import_sales_csv(&conn, &path, &profile, "2026-05-01", "2026-05-31").is_err());
+        assert!(import_sales_csv(&conn, &again, &profile, "2026-04-01", "2026-04-30").is_ok());
+
+        std::fs::remove_file(path).ok();
+        std::fs::remove_file(again).ok();
+    }
+}
@@ -0,0 +1,348 @@
+use crate::costing::{
+    conversion_factor, cost_recipe, recipe_yield, standard_conversion_edges, ConvEdge, CostContext,
+};
+use crate::pricing::recipe_servings;
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+/// The standard measures (see `units`) a scaled quantity may be moved into,
+/// grouped by family. Quantities only move between units of the same family.
+const KITCHEN_STANDARDS: &[(&str, &str)] = &[
+    ("tsp", "us_volume"),
+    ("tbsp", "us_volume"),
+    ("fl oz", "us_volume"),
+    ("cup", "us_volume"),
+    ("pt", "us_volume"),
+    ("qt", "us_volume"),
+    ("gal", "us_volume"),
+    ("oz", "us_weight"),
+    ("lb", "us_weight"),
+    ("g", "metric_weight"),
+    ("kg", "metric_weight"),
+    ("ml", "metric_volume"),
+    ("l", "metric_volume"),
+];
+
+/// A unit whose standard measure is a kitchen measure.
+struct KitchenUnit {
+    unit_id: i64,
+    name: String,
+    standard: String,
+    family: &'static str,
+    base_qty: f64,
+}
+
+/// The kitchen family of a standard measure, if it is one.
+fn kitchen_family(standard: &str) -> Option<&'static str> {
+    KITCHEN_STANDARDS
+        .iter()
+        .find(|(name, _)| *name == standard)
+        .map(|(_, family)| *family)
+}
+
+/// Every unit with a kitchen standard measure, smallest unit id first.
+fn load_kitchen_units(conn: &Connection) -> Result<Vec<KitchenUnit>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT u.unit_id, COALESCE(u.sing, ''), s.standard, s.base_qty
+             FROM units u
+             JOIN unit_standards s ON s.unit_id = u.unit_id
+             WHERE s.standard IS NOT NULL AND s.base_qty > 0
+             ORDER BY u.unit_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut units = Vec::new();
+    for row in rows {
+        let (unit_id, name, standard, base_qty) = row.map_err(|e| e.to_string())?;
+        if let Some(family) = kitchen_family(&standard) {
+            units.push(KitchenUnit {
+                unit_id,
+                name,
+                standard,
+                family,
+                base_qty,
+            });
+        }
+    }
+    Ok(units)
+}
+
+/// Practical rounding: eighths for small US measures, halves for mid-size
+/// quantities, whole numbers beyond that. Metric rounds to 0.1 / whole.
+/// Quantities too small to round, and units that aren't kitchen measures
+/// (each, case), stay as scaled.
+fn round_practical(qty: f64, family: Option<&str>) -> f64 {
+    let Some(family) = family else {
+        return qty;
+    };
+    if family.starts_with("metric") {
+        let tenths = (qty * 10.0).round() / 10.0;
+        if qty >= 10.0 {
+            qty.round()
+        } else if tenths > 0.0 {
+            tenths
+        } else {
+            qty
+        }
+    } else if qty < 10.0 {
+        let eighths = (qty * 8.0).round() / 8.0;
+        if eighths > 0.0 {
+            eighths
+        } else {
+            qty
+        }
+    } else if qty < 50.0 {
+        (qty * 2.0).round() / 2.0
+    } else {
+        qty.round()
+    }
+}
+
+/// Formats a quantity as a kitchen fraction, e.g. 1.5 → "1 1/2".
+pub fn format_fraction(qty: f64) -> String {
+    let whole = qty.trunc();
+    let frac = qty - whole;
+    let eighths = (frac * 8.0).round() as i64;
+    if eighths == 0 || eighths == 8 {
+        let w = if eighths == 8 { whole + 1.0 } else { whole };
+        if (qty - w).abs() < 1e-6 || w != whole {
+            return format!("{}", w as i64);
+        }
+        return format!("{:.2}", qty);
+    }
+    let (num, den) = match eighths {
+        2 | 6 => (eighths / 2, 4),
+        4 => (1, 2),
+        n => (n, 8),
+    };
+    if (frac - num as f64 / den as f64).abs() > 1e-6 {
+        return format!("{:.2}", qty);
+    }
+    if whole >= 1.0 {
+        format!("{} {}/{}", whole as i64, num, den)
+    } else {
+        format!("{}/{}", num, den)
+    }
+}
+
+pub struct ScaledLine {
+    pub recp_item_id: i64,
+    pub item_name: String,
+    pub orig_qty: Option<f64>,
+    pub orig_unit_name: String,
+    pub qty: Option<f64>,
+    pub unit_id: Option<i64>,
+    pub unit_name: String,
+    pub display_qty: String,
+    pub extended_cost: Option<f64>,
+    pub cost_status: String,
+}
+
+pub struct ScaledRecipe {
+    pub name: String,
+    pub factor: f64,
+    pub servings: Option<f64>,
+    pub yield_qty: Option<f64>,
+    pub yield_unit_name: String,
+    pub total_cost: f64,
+    pub missing_costs: i64,
+    pub lines: Vec<ScaledLine>,
+}
+
+/// Turns a scale request into a multiplier. `mode` is "factor", "servings"
+/// (target servings over the recipe's servings) or "yield" (target quantity
+/// in the recipe's yield unit).
+pub fn scale_factor(
+    conn: &Connection,
+    recipe_id: i64,
+    mode: &str,
+    target: f64,
+) -> Result<f64, String> {
+    if target <= 0.0 {
+        return Err("Scale target must be greater than 0".to_string());
+    }
+    match mode {
+        "factor" => Ok(target),
+        "servings" => match recipe_servings(conn, recipe_id)? {
+            Some(s) => Ok(target / s),
+            None => Err("Recipe has no servings set".to_string()),
+        },
+        "yield" => match recipe_yield(conn, recipe_id)?.qty {
+            Some(q) => Ok(target / q),
+            None => Err("Recipe has no yield set".to_string()),
+        },
+        other => Err(format!("Unknown scale mode '{}'", other)),
+    }
+}
+
+/// Picks the kitchen unit that reads best for `qty` of `unit_id`: the largest
+/// unit of the same family that still gives at least 1 (or a clean quarter
+/// cup), converting through the standard conversions. Units that aren't
+/// kitchen measures (each, case, bunch) are left alone.
+fn best_kitchen_unit(
+    kitchen: &[KitchenUnit],
+    edges: &[ConvEdge],
+    unit_id: i64,
+    qty: f64,
+) -> Option<(i64, String, f64, &'static str)> {
+    let family = kitchen.iter().find(|k| k.unit_id == unit_id)?.family;
+    // One unit per measure, so duplicates ("Cup", "cups") don't compete.
+    let mut seen = HashSet::new();
+    let mut members: Vec<&KitchenUnit> = kitchen
+        .iter()
+        .filter(|k| k.family == family && seen.insert(k.standard.as_str()))
+        .collect();
+    members.sort_by(|a, b| b.base_qty.total_cmp(&a.base_qty));
+    let in_unit =
+        |k: &KitchenUnit| conversion_factor(edges, unit_id, k.unit_id, 1).map(|(f, _)| qty * f);
+    let mut smallest = None;
+    for k in &members {
+        let Some(q) = in_unit(k) else {
+            continue;
+        };
+        smallest = Some((k.unit_id, k.name.clone(), q, family));
+        // Fluid ounces are a purchasing measure; cooks read tbsp and cups.
+        if k.standard == "fl oz" {
+            continue;
+        }
+        let quarter_cup =
+            k.standard == "cup" && q >= 0.25 && (q * 4.0 - (q * 4.0).round()).abs() < 0.05;
+        if q >= 1.0 || quarter_cup {
+            return Some((k.unit_id, k.name.clone(), q, family));
+        }
+    }
+    smallest
+}
+
+/// Scales every line of a recipe by `factor`, moves quantities into readable
+/// kitchen units and recosts the batch.
+pub fn scale_recipe(
+    conn: &Connection,
+    recipe_id: i64,
+    factor: f64,
+) -> Result<ScaledRecipe, String> {
+    let name: String = conn
+        .query_row(
+            "SELECT name FROM recipes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let servings = recipe_servings(conn, recipe_id)?;
+    let costed = cost_recipe(conn, recipe_id, &mut CostContext::default())?;
+    let yld = recipe_yield(conn, recipe_id)?;
+    let kitchen = load_kitchen_units(conn)?;
+    let edges = standard_conversion_edges(conn)?;
+
+    let mut lines = Vec::new();
+    for line in costed.lines {
+        let scaled = line.qty.map(|q| q * factor);
+        let mut qty = scaled;
+        let mut unit_id = line.unit_id;
+        let mut unit_name = line.unit_name.clone();
+        let mut family = None;
+        if let (Some(q), Some(uid)) = (scaled, line.unit_id) {
+            if q > 0.0 {
+                if let Some((best_id, best_name, best_qty, fam)) =
+                    best_kitchen_unit(&kitchen, &edges, uid, q)
+                {
+                    unit_id = Some(best_id);
+                    unit_name = best_name;
+                    qty = Some(best_qty);
+                    family = Some(fam);
+                }
+            }
+        }
+        let qty = qty.map(|q| round_practical(q, family));
+        let display_qty = match (qty, family) {
+            (Some(q), Some(f)) if !f.starts_with("metric") => format_fraction(q),
+            (Some(q), _) => format!("{:.2}", q),
+            (None, _) => "-".to_string(),
+        };
+        lines.push(ScaledLine {
+            recp_item_id: line.recp_item_id,
+            item_name: line.item_name,
+            orig_qty: line.qty,
+            orig_unit_name: line.unit_name,
+            qty,
+            unit_id,
+            unit_name,
+            display_qty,
+            extended_cost: line.cost.extended_cost.map(|c| c * factor),
+            cost_status: line.cost.cost_status,
+        });
+    }
+
+    Ok(ScaledRecipe {
+        name,
+        factor,
+        servings: servings.map(|s| s * factor),
+        yield_qty: yld.qty.map(|q| q * factor),
+        yield_unit_name: yld.unit_name,
+        total_cost: costed.total_cost * factor,
+        missing_costs: costed.missing_costs,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_kitchen_unit, format_fraction, load_kitchen_units, round_practical};
+    use crate::costing::standard_conversion_edges;
+    use rusqlite::Connection;
+
+    #[test]
+    fn formats_kitchen_fractions() {
+        assert_eq!(format_fraction(1.5), "1 1/2");
+        assert_eq!(format_fraction(0.25), "1/4");
+        assert_eq!(format_fraction(2.375), "2 3/8");
+        assert_eq!(format_fraction(3.0), "3");
+        assert_eq!(format_fraction(0.3), "0.30");
+    }
+
+    #[test]
+    fn small_and_count_quantities_stay_as_scaled() {
+        assert_eq!(round_practical(0.3, Some("us_volume")), 0.25);
+        assert_eq!(round_practical(1.0 / 32.0, Some("us_volume")), 1.0 / 32.0);
+        assert_eq!(round_practical(0.04, Some("metric_weight")), 0.04);
+        assert_eq!(round_practical(0.04, None), 0.04);
+        assert_eq!(round_practical(12.3, None), 12.3);
+        assert_eq!(round_practical(12.3, Some("us_weight")), 12.5);
+    }
+
+    #[test]
+    fn moves_quantities_within_a_family() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES
+               (1, 'tsp', 'tsps'), (2, 'Tbsp', 'Tbsps'), (3, 'Cup', 'Cups'), (4, 'cups', 'cups'),
+               (5, 'CS', 'CS'), (6, 'oz', 'oz'), (7, 'lb', 'lbs'), (8, 'Case', 'Cases');",
+        )
+        .unwrap();
+        crate::units::refresh_standard_units(&conn).unwrap();
+        let kitchen = load_kitchen_units(&conn).unwrap();
+        let edges = standard_conversion_edges(&conn).unwrap();
+        let best = |unit_id, qty| {
+            best_kitchen_unit(&kitchen, &edges, unit_id, qty)
+                .map(|(id, _, q, _)| (id, (q * 1000.0).round() / 1000.0))
+        };
+
+        assert_eq!(best(1, 48.0), Some((3, 1.0)));
+        assert_eq!(best(4, 0.5), Some((3, 0.5)));
+        assert_eq!(best(1, 2.0), Some((1, 2.0)));
+        assert_eq!(best(6, 32.0), Some((7, 2.0)));
+        assert_eq!(best(5, 3.0), None);
+        assert_eq!(best(8, 3.0), None);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::sales::normalize_date;
+use rusqlite::Connection;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub const GROUPS: [&str; 3] = ["vendor", "category", "item"];
+pub const PERIODS: [&str; 3] = ["week", "month", "quarter"];
+
+pub struct SpendQuery {
+    /// vendor, category or item.
+    pub group_by: String,
+    /// week (starting Monday), month or quarter.
+    pub period: String,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub vendor_id: Option<i64>,
+    pub category: Option<String>,
+}
+
+/// Spend for one vendor, category or item. `current` and `previous` are the
+/// last two periods in the range.
+#[derive(Clone)]
+pub struct SpendRow {
+    pub key: String,
+    pub label: String,
+    pub by_period: Vec<f64>,
+    pub total: f64,
+    pub share_pct: f64,
+    pub current: f64,
+    pub previous: f64,
+    pub change: f64,
+    pub change_pct: Option<f64>,
+}
+
+/// A vendor's share of total spend over the range and in the last two
+/// periods.
+pub struct VendorShare {
+    pub vendor_name: String,
+    pub total: f64,
+    pub share_pct: f64,
+    pub current_share_pct: Option<f64>,
+    pub previous_share_pct: Option<f64>,
+    pub top_category: String,
+}
+
+pub struct SpendReport {
+    pub group_by: String,
+    pub period: String,
+    pub date_from: String,
+    pub date_to: String,
+    pub periods: Vec<String>,
+    pub period_totals: Vec<f64>,
+    pub total: f64,
+    pub invoice_count: usize,
+    pub line_count: usize,
+    pub rows: Vec<SpendRow>,
+    /// Rows with the largest change between the last two periods.
+    pub movers: Vec<SpendRow>,
+    pub vendor_share: Vec<VendorShare>,
+}
+
+struct VendorSpend {
+    name: String,
+    by_period: Vec<f64>,
+    categories: HashMap<String, f64>,
+}
+
+struct SpendLine {
+    invoice_id: i64,
+    date: String,
+    vendor_id: i64,
+    vendor_name: String,
+    item_id: i64,
+    item_name: String,
+    category: String,
+    amount: f64,
+}
+
+/// The period a normalized date falls in: the Monday of its week, its
+/// month ("2026-03") or its quarter ("2026-Q1").
+fn period_key(
+    conn: &Connection,
+    period: &str,
+    date: &str,
+    weeks: &mut HashMap<String, String>,
+) -> String {
+    match period {
+        "week" => {
+            if let Some(monday) = weeks.get(date) {
+                return monday.clone();
+            }
+            let monday: String = conn
+                .query_row("SELECT date(?1, '-6 days', 'weekday 1')", [date], |row| {
+                    row.get(0)
+                })
+                .unwrap_or_else(|_| date.to_string());
+            weeks.insert(date.to_string(), monday.clone());
+            monday
+        }
+        "quarter" => {
+            let month: u32 = date.get(5..7).and_then(|m| m.parse().ok()).unwrap_or(1);
+            format!("{}-Q{}", &date[..4], month.div_ceil(3))
+        }
+        _ => date[..7].to_string(),
+    }
+}
+
+/// Every period from `first` to `last`, so gaps with no spend still show.
+fn period_range(conn: &Connection, period: &str, first: &str, last: &str) -> Vec<String> {
+    let mut periods = vec![first.to_string()];
+    let mut key = first.to_string();
+    while key.as_str() < last {
+        key = match period {
+            "week" => conn
+                .query_row("SELECT date(?1, '+7 days')", [&key], |row| row.get(0))
+                .unwrap_or_else(|_| last.to_string()),
+            _ => {
+                let quarters = period == "quarter";
+                let year: u32 = key[..4].parse().unwrap_or(9999);
+                let n: u32 = key[if quarters { 6 } else { 5 }..].parse().unwrap_or(1);
+                let (year, n) = if n == if quarters { 4 } else { 12 } {
+                    (year + 1, 1)
+                } else {
+                    (year, n + 1)
+                };
+                if quarters {
+                    format!("{}-Q{}", year, n)
+                } else {
+                    format!("{}-{:02}", year, n)
+                }
+            }
+        };
+        periods.push(key.clone());
+    }
+    periods
+}
+
+fn load_lines(conn: &Connection, query: &SpendQuery) -> Result<Vec<SpendLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.invoice_id, COALESCE(NULLIF(i.invoice_date, ''), t.trans_date, ''),
+                    COALESCE(t.vendor_id, i.vendor_id, 0), COALESCE(v.name, 'No vendor'),
+                    COALESCE(t.item_id, 0), COALESCE(it.name, 'Unknown item'),
+                    COALESCE(NULLIF(it.food_category, ''), 'Uncategorized'),
+                    COALESCE(t.ext_cost, t.qty * t.price, 0)
+             FROM trans t
+             JOIN invoices i ON i.invoice_id = t.invoice_id
+             LEFT JOIN vendors v ON v.vendor_id = COALESCE(t.vendor_id, i.vendor_id)
+             LEFT JOIN items it ON it.item_id = t.item_id
+             WHERE (?1 IS NULL OR COALESCE(t.vendor_id, i.vendor_id) = ?1)
+               AND (?2 IS NULL OR COALESCE(NULLIF(it.food_category, ''), 'Uncategorized') = ?2 COLLATE NOCASE)",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![query.vendor_id, query.category], |row| {
+            Ok(SpendLine {
+                invoice_id: row.get(0)?,
+                date: row.get(1)?,
+                vendor_id: row.get(2)?,
+                vendor_name: row.get(3)?,
+                item_id: row.get(4)?,
+                item_name: row.get(5)?,
+                category: row.get(6)?,
+                amount: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let from = query.date_from.as_deref().unwrap_or("");
+    let to = query.date_to.as_deref().unwrap_or("9999-12-31");
+    let mut lines = Vec::new();
+    for row in rows {
+        let mut line = row.map_err(|e| e.to_string())?;
+        // Legacy invoices carry dates in several formats.
+        let Some(date) = normalize_date(&line.date) else {
+            continue;
+        };
+        if date.as_str() < from || date.as_str() > to || line.amount == 0.0 {
+            continue;
+        }
+        line.date = date;
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+fn pct_of(part: f64, whole: f64) -> Option<f64> {
+    (whole != 0.0).then(|| part / whole * 100.0)
+}
+
+/// Totals by vendor, category or item for each period in the range, with
+/// period-over-period change, top movers and each vendor's share of spend.
+/// Spend is the extended cost of invoice lines, before freight and fees.
+pub fn spend_report(conn: &Connection, query: &SpendQuery) -> Result<SpendReport, String> {
+    if !GROUPS.contains(&query.group_by.as_str()) {
+        return Err(format!("Unknown grouping '{}'", query.group_by));
+    }
+    if !PERIODS.contains(&query.period.as_str()) {
+        return Err(format!("Unknown period '{}'", query.period));
+    }
+    let lines = load_lines(conn, query)?;
+
+    let mut weeks = HashMap::new();
+    let keys: Vec<String> = lines
+        .iter()
+        .map(|l| period_key(conn, &query.period, &l.date, &mut weeks))
+        .collect();
+    let periods = match (keys.iter().min(), keys.iter().max()) {
+        (Some(first), Some(last)) => period_range(conn, &query.period, first, last),
+        _ => Vec::new(),
+    };
+    let index: HashMap<&str, usize> = periods
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.as_str(), i))
+        .collect();
+    let n = periods.len();
+    let (cur, prev) = (n.checked_sub(1), n.checked_sub(2));
+
+    let mut period_totals = vec![0.0; n];
+    let mut groups: BTreeMap<String, (String, Vec<f64>)> = BTreeMap::new();
+    let mut vendors: BTreeMap<i64, VendorSpend> = BTreeMap::new();
+    let mut invoices = BTreeSet::new();
+    for (line, key) in lines.iter().zip(&keys) {
+        let p = index[key.as_str()];
+        period_totals[p] += line.amount;
+        invoices.insert(line.invoice_id);
+        let (group_key, label) = match query.group_by.as_str() {
+            "vendor" => (line.vendor_id.to_string(), line.vendor_name.clone()),
+            "category" => (line.category.clone(), line.category.clone()),
+            _ => (line.item_id.to_string(), line.item_name.clone()),
+        };
+        groups
+            .entry(group_key)
+            .or_insert_with(|| (label, vec![0.0; n]))
+            .1[p] += line.amount;
+        let vendor = vendors
+            .entry(line.vendor_id)
+            .or_insert_with(|| VendorSpend {
+                name: line.vendor_name.clone(),
+                by_period: vec![0.0; n],
+                categories: HashMap::new(),
+            });
+        vendor.by_period[p] += line.amount;
+        *vendor
+            .categories
+            .entry(line.category.clone())
+            .or_insert(0.0) += line.amount;
+    }
+    let total: f64 = period_totals.iter().sum();
+    let at = |values: &[f64], i: Option<usize>| i.map(|i| values[i]).unwrap_or(0.0);
+
+    let mut rows: Vec<SpendRow> = groups
+        .into_iter()
+        .map(|(key, (label, by_period))| {
+            let row_total: f64 = by_period.iter().sum();
+            let current = at(&by_period, cur);
+            let previous = at(&by_period, prev);
+            SpendRow {
+                key,
+                label,
+                total: row_total,
+                share_pct: pct_of(row_total, total).unwrap_or(0.0),
+                current,
+                previous,
+                change: current - previous,
+                change_pct: pct_of(current - previous, previous),
+                by_period,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.total
+            .total_cmp(&a.total)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+
+    let mut movers: Vec<SpendRow> = if prev.is_some() {
+        rows.iter()
+            .filter(|r| r.change.abs() >= 0.01)
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    movers.sort_by(|a, b| b.change.abs().total_cmp(&a.change.abs()));
+    movers.truncate(10);
+
+    let mut vendor_share: Vec<VendorShare> = vendors
+        .into_values()
+        .map(|v| {
+            let by_period = v.by_period;
+            let vendor_total: f64 = by_period.iter().sum();
+            let top_category = v
+                .categories
+                .into_iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(c, _)| c)
+                .unwrap_or_default();
+            VendorShare {
+                vendor_name: v.name,
+                total: vendor_total,
+                share_pct: pct_of(vendor_total, total).unwrap_or(0.0),
+                current_share_pct: cur.and_then(|i| pct_of(by_period[i], period_totals[i])),
+                previous_share_pct: prev.and_then(|i| pct_of(by_period[i], period_totals[i])),
+                top_category,
+            }
+        })
+        .collect();
+    vendor_share.sort_by(|a, b| b.total.total_cmp(&a.total));
+
+    Ok(SpendReport {
+        group_by: query.group_by.clone(),
+        period: query.period.clone(),
+        date_from: lines
+            .iter()
+            .map(|l| l.date.as_str())
+            .min()
+            .unwrap_or("")
+            .to_string(),
+        date_to: lines
+            .iter()
+            .map(|l| l.date.as_str())
+            .max()
+            .unwrap_or("")
+            .to_string(),
+        periods,
+        period_totals,
+        total,
+        invoice_count: invoices.len(),
+        line_count: lines.len(),
+        rows,
+        movers,
+        vendor_share,
+    })
+}
+
+/// Line spend per food category between two normalized dates, inclusive.
+pub fn category_spend(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+) -> Result<HashMap<String, f64>, String> {
+    let query = SpendQuery {
+        group_by: "category".to_string(),
+        period: "month".to_string(),
+        date_from: Some(date_from.to_string()),
+        date_to: Some(date_to.to_string()),
+        vendor_id: None,
+        category: None,
+    };
+    let mut totals = HashMap::new();
+    for line in load_lines(conn, &query)? {
+        *totals.entry(line.category).or_insert(0.0) += line.amount;
+    }
+    Ok(totals)
+}
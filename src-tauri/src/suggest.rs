@@ -0,0 +1,546 @@
+use crate::costing::{
+    best_conversion_path, build_conversion_edges, conversion_factor, cost_recipe,
+    default_purch_unit, item_price, path_summary, ConvEdge, ConvPath, CostContext, EdgeSource,
+};
+use crate::db::{cache_versions, mark_cache_built};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+
+/// Suggestions at or above this confidence also go to
+/// `conv_suggestions_safe`.
+pub const SAFE_CONFIDENCE: f64 = 0.7;
+
+/// Factors within this fraction of each other count as agreeing.
+const AGREEMENT: f64 = 0.02;
+
+/// Siblings looked at per item.
+const MAX_SIBLINGS: usize = 25;
+
+/// A recipe unit that doesn't convert to an item's purchase unit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct MissingPair {
+    item_id: i64,
+    vendor_id: i64,
+    recipe_unit_id: i64,
+    purch_unit_id: i64,
+}
+
+/// A proposed conversion: 1 recipe unit = `factor` purchase units.
+struct Candidate {
+    factor: f64,
+    confidence: f64,
+    derived_from: String,
+    hops: usize,
+    path: String,
+}
+
+#[derive(Default)]
+pub struct SuggestSummary {
+    pub missing_edges: usize,
+    pub suggestions: usize,
+    pub safe: usize,
+    pub todo: usize,
+    pub missing_purch: usize,
+    pub incomplete_recipes: usize,
+}
+
+#[derive(Default)]
+struct RecipeGaps {
+    name: String,
+    missing_costs: i64,
+    missing_conversions: i64,
+    missing_units: i64,
+}
+
+/// Regenerates the suggestion tables when the data they are built from has
+/// changed since the last run. Returns whether it ran.
+pub fn ensure_fresh(conn: &Connection) -> Result<bool, String> {
+    let (version, built) = cache_versions(conn, "suggestions")?;
+    if built.as_deref() == Some(version.to_string().as_str()) {
+        return Ok(false);
+    }
+    regenerate(conn)?;
+    Ok(true)
+}
+
+fn unit_label(conn: &Connection, unit_id: i64) -> String {
+    conn.query_row(
+        "SELECT sing FROM units WHERE unit_id = ?1",
+        [unit_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "-".to_string())
+}
+
+fn item_label(conn: &Connection, item_id: i64) -> String {
+    conn.query_row(
+        "SELECT name FROM items WHERE item_id = ?1",
+        [item_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "-".to_string())
+}
+
+fn agrees(a: f64, b: f64) -> bool {
+    (a / b - 1.0).abs() <= AGREEMENT
+}
+
+/// Factors the user has rejected for a pair. Candidates agreeing with one
+/// of them aren't suggested again, whichever source they come from.
+fn rejected_factors(conn: &Connection, pair: &MissingPair) -> Result<Vec<f64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT qty2 / qty1 FROM conv_suggestion_rejects
+             WHERE item_id = ?1 AND vendor_id = ?2 AND unit_id1 = ?3 AND unit_id2 = ?4 AND qty1 > 0 AND qty2 > 0",
+        )
+        .map_err(|e| e.to_string())?;
+    let factors = stmt
+        .query_map(
+            [
+                pair.item_id,
+                pair.vendor_id,
+                pair.recipe_unit_id,
+                pair.purch_unit_id,
+            ],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(factors)
+}
+
+/// Items whose names start with the same word and that share the item's
+/// food category when both have one.
+fn siblings(conn: &Connection, item_id: i64) -> Result<Vec<(i64, String)>, String> {
+    let Some((name, category)): Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT COALESCE(name, ''), NULLIF(food_category, '') FROM items WHERE item_id = ?1",
+            [item_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(Vec::new());
+    };
+    let Some(first) = name.split_whitespace().next().filter(|w| w.len() >= 3) else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn
+        .prepare(
+            "SELECT item_id, name FROM items
+             WHERE item_id <> ?1 AND (name = ?2 COLLATE NOCASE OR name LIKE ?2 || ' %')
+               AND (?3 IS NULL OR NULLIF(food_category, '') IS NULL OR food_category = ?3 COLLATE NOCASE)
+             ORDER BY item_id LIMIT ?4",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![item_id, first, category, MAX_SIBLINGS as i64],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Candidate conversions for a missing pair from each source: other
+/// vendors' conversions and packs, sibling items and the ratio of invoice
+/// prices paid in each unit.
+fn candidates(
+    conn: &Connection,
+    pair: &MissingPair,
+    sibling_edges: &mut HashMap<i64, Vec<ConvEdge>>,
+) -> Result<Vec<Candidate>, String> {
+    let (from, to) = (pair.recipe_unit_id, pair.purch_unit_id);
+    let mut found = Vec::new();
+    let all_vendors = build_conversion_edges(conn, pair.item_id, None)?;
+
+    // The pair's own vendor's conversions and packs already failed, so a
+    // path here relies on another vendor's.
+    if pair.vendor_id != 0 {
+        if let Some(path) = best_conversion_path(&all_vendors, from, to, 6) {
+            let from_pack = path.hops.iter().any(|h| h.source == EdgeSource::Pack);
+            let notes = path.notes();
+            found.push(Candidate {
+                factor: path.factor,
+                confidence: if from_pack { 0.6 } else { 0.8 },
+                derived_from: if from_pack {
+                    "other vendors' packs"
+                } else {
+                    "other vendors"
+                }
+                .to_string(),
+                hops: path.hops.len(),
+                path: if notes.is_empty() {
+                    path_summary(conn, &path)
+                } else {
+                    format!("{}; {}", path_summary(conn, &path), notes.join(", "))
+                },
+            });
+        }
+    }
+
+    // Siblings only count when their path relies on something specific to
+    // them rather than the standard conversions every item shares.
+    let mut sibling_paths: Vec<(String, ConvPath)> = Vec::new();
+    for (sibling_id, sibling_name) in siblings(conn, pair.item_id)? {
+        if let std::collections::hash_map::Entry::Vacant(slot) = sibling_edges.entry(sibling_id) {
+            slot.insert(build_conversion_edges(conn, sibling_id, None)?);
+        }
+        if let Some(path) = best_conversion_path(&sibling_edges[&sibling_id], from, to, 6) {
+            if path.hops.iter().any(|h| h.source != EdgeSource::Standard) {
+                sibling_paths.push((sibling_name, path));
+            }
+        }
+    }
+    let best_group = sibling_paths
+        .iter()
+        .map(|(_, p)| {
+            let agreeing: Vec<&(String, ConvPath)> = sibling_paths
+                .iter()
+                .filter(|(_, q)| agrees(p.factor, q.factor))
+                .collect();
+            agreeing
+        })
+        .max_by_key(|group| group.len());
+    if let Some(group) = best_group {
+        let disagreeing = sibling_paths.len() - group.len();
+        let (name, path) = group[0];
+        let names: Vec<&str> = group.iter().map(|(n, _)| n.as_str()).collect();
+        found.push(Candidate {
+            factor: path.factor,
+            confidence: (0.3 + 0.1 * group.len() as f64 - 0.1 * disagreeing as f64).clamp(0.1, 0.6),
+            derived_from: format!("sibling items: {}", names.join(", ")),
+            hops: path.hops.len(),
+            path: format!("{}: {}", name, path_summary(conn, path)),
+        });
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT unit_id, price FROM trans WHERE item_id = ?1 AND price > 0 AND unit_id IN (?2, ?3)")
+        .map_err(|e| e.to_string())?;
+    let mut prices: (Vec<f64>, Vec<f64>) = (Vec::new(), Vec::new());
+    let rows = stmt
+        .query_map((pair.item_id, from, to), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (unit_id, price) = row.map_err(|e| e.to_string())?;
+        if unit_id == from {
+            prices.0.push(price);
+        } else {
+            prices.1.push(price);
+        }
+    }
+    let (from_count, to_count) = (prices.0.len(), prices.1.len());
+    if let (Some(from_price), Some(to_price)) = (median(&mut prices.0), median(&mut prices.1)) {
+        found.push(Candidate {
+            factor: from_price / to_price,
+            confidence: if from_count >= 3 && to_count >= 3 {
+                0.5
+            } else {
+                0.4
+            },
+            derived_from: "invoice prices".to_string(),
+            hops: 1,
+            path: format!(
+                "{} at {:.2} ({} lines), {} at {:.2} ({} lines)",
+                unit_label(conn, from),
+                from_price,
+                from_count,
+                unit_label(conn, to),
+                to_price,
+                to_count
+            ),
+        });
+    }
+
+    // Independent sources that agree back each other up.
+    let factors: Vec<f64> = found.iter().map(|c| c.factor).collect();
+    for candidate in &mut found {
+        let support = factors
+            .iter()
+            .filter(|f| agrees(**f, candidate.factor))
+            .count()
+            - 1;
+        candidate.confidence = (candidate.confidence + 0.1 * support as f64).min(0.95);
+    }
+    found.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(found)
+}
+
+/// Rebuilds `missing_edges`, `conv_suggestions`, `conv_suggestions_safe`,
+/// `conv_todo`, `missing_purch_unit` and `missing_data_report` from the
+/// current recipes, conversions, packs and invoices.
+pub fn regenerate(conn: &Connection) -> Result<SuggestSummary, String> {
+    let mut stmt = conn
+        .prepare("SELECT recipe_id, COALESCE(name, '') FROM recipes ORDER BY recipe_id")
+        .map_err(|e| e.to_string())?;
+    let recipes: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut ctx = CostContext::default();
+    let mut gaps: BTreeMap<i64, RecipeGaps> = BTreeMap::new();
+    let mut missing: BTreeMap<MissingPair, i64> = BTreeMap::new();
+    let mut missing_purch: BTreeMap<i64, i64> = BTreeMap::new();
+    let mut edges_by_item: HashMap<(i64, i64), Vec<ConvEdge>> = HashMap::new();
+
+    for (recipe_id, name) in recipes {
+        let costed = cost_recipe(conn, recipe_id, &mut ctx)?;
+        let recipe = gaps.entry(recipe_id).or_insert_with(|| RecipeGaps {
+            name,
+            ..Default::default()
+        });
+        for line in costed.lines {
+            if line.cost.extended_cost.is_none() {
+                recipe.missing_costs += 1;
+            }
+            let Some(unit_id) = line.unit_id else {
+                recipe.missing_units += 1;
+                continue;
+            };
+            if line.item_id <= 0 {
+                continue;
+            }
+            let pair = if line.sub_recipe_id.is_some() {
+                if line.cost.cost_status != "Needs conversion" {
+                    continue;
+                }
+                let Some(yield_unit) = line.cost.purch_unit_id else {
+                    continue;
+                };
+                MissingPair {
+                    item_id: line.item_id,
+                    vendor_id: 0,
+                    recipe_unit_id: unit_id,
+                    purch_unit_id: yield_unit,
+                }
+            } else {
+                let Some(purch_unit_id) = default_purch_unit(conn, line.item_id) else {
+                    *missing_purch.entry(line.item_id).or_insert(0) += 1;
+                    continue;
+                };
+                if purch_unit_id == unit_id {
+                    continue;
+                }
+                let vendor_id = item_price(conn, line.item_id).map(|p| p.1).unwrap_or(0);
+                if let std::collections::hash_map::Entry::Vacant(slot) =
+                    edges_by_item.entry((line.item_id, vendor_id))
+                {
+                    let vendor = (vendor_id != 0).then_some(vendor_id);
+                    slot.insert(build_conversion_edges(conn, line.item_id, vendor)?);
+                }
+                if conversion_factor(
+                    &edges_by_item[&(line.item_id, vendor_id)],
+                    unit_id,
+                    purch_unit_id,
+                    6,
+                )
+                .is_some()
+                {
+                    continue;
+                }
+                MissingPair {
+                    item_id: line.item_id,
+                    vendor_id,
+                    recipe_unit_id: unit_id,
+                    purch_unit_id,
+                }
+            };
+            recipe.missing_conversions += 1;
+            *missing.entry(pair).or_insert(0) += 1;
+        }
+    }
+
+    for table in [
+        "missing_edges",
+        "conv_suggestions",
+        "conv_suggestions_safe",
+        "conv_todo",
+        "missing_purch_unit",
+        "missing_data_report",
+    ] {
+        conn.execute(&format!("DELETE FROM {}", table), [])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut summary = SuggestSummary::default();
+    let mut sibling_edges = HashMap::new();
+    for (pair, hits) in &missing {
+        let recipe_unit = unit_label(conn, pair.recipe_unit_id);
+        let purch_unit = unit_label(conn, pair.purch_unit_id);
+        conn.execute(
+            "INSERT INTO missing_edges (item_id, item_name, vendor_id, recipe_unit_id, recipe_unit, purch_unit_id, purch_unit, hits)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                pair.item_id,
+                item_label(conn, pair.item_id),
+                pair.vendor_id,
+                pair.recipe_unit_id,
+                recipe_unit,
+                pair.purch_unit_id,
+                purch_unit,
+                hits
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        summary.missing_edges += 1;
+
+        let mut found = candidates(conn, pair, &mut sibling_edges)?;
+        let rejected = rejected_factors(conn, pair)?;
+        found.retain(|c| !rejected.iter().any(|r| agrees(c.factor, *r)));
+        if found.is_empty() {
+            conn.execute(
+                "INSERT INTO conv_todo (item_id, vendor_id, recipe_unit_id, purch_unit_id, recipe_unit, purch_unit, hits, needed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                rusqlite::params![
+                    pair.item_id,
+                    pair.vendor_id,
+                    pair.recipe_unit_id,
+                    pair.purch_unit_id,
+                    recipe_unit,
+                    purch_unit,
+                    hits,
+                    format!("1 {} = ? {}", recipe_unit, purch_unit)
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            summary.todo += 1;
+            continue;
+        }
+        for (i, candidate) in found.iter().enumerate() {
+            let tables: &[&str] = if i == 0 && candidate.confidence >= SAFE_CONFIDENCE {
+                &["conv_suggestions", "conv_suggestions_safe"]
+            } else {
+                &["conv_suggestions"]
+            };
+            for table in tables {
+                conn.execute(
+                    &format!(
+                        "INSERT INTO {} (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, recipe_unit, purch_unit,
+                                         hits, derived_from, hops, path, confidence)
+                         VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                        table
+                    ),
+                    rusqlite::params![
+                        pair.item_id,
+                        pair.vendor_id,
+                        pair.recipe_unit_id,
+                        pair.purch_unit_id,
+                        candidate.factor,
+                        recipe_unit,
+                        purch_unit,
+                        hits,
+                        candidate.derived_from,
+                        candidate.hops as i64,
+                        candidate.path,
+                        (candidate.confidence * 100.0).round() / 100.0
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            summary.suggestions += 1;
+            if tables.len() == 2 {
+                summary.safe += 1;
+            }
+        }
+    }
+
+    for (item_id, usage) in &missing_purch {
+        conn.execute(
+            "INSERT INTO missing_purch_unit (item_id, item_name, usage_count) VALUES (?1, ?2, ?3)",
+            rusqlite::params![item_id, item_label(conn, *item_id), usage],
+        )
+        .map_err(|e| e.to_string())?;
+        summary.missing_purch += 1;
+    }
+    for (recipe_id, recipe) in &gaps {
+        if recipe.missing_costs + recipe.missing_conversions + recipe.missing_units == 0 {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO missing_data_report (recipe_id, recipe_name, missing_a, missing_b, missing_c)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                recipe_id,
+                recipe.name,
+                recipe.missing_costs,
+                recipe.missing_conversions,
+                recipe.missing_units
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        summary.incomplete_recipes += 1;
+    }
+
+    let (version, _) = cache_versions(conn, "suggestions")?;
+    mark_cache_built(conn, "suggestions", &version.to_string())?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ensure_fresh;
+    use rusqlite::Connection;
+
+    #[test]
+    fn suggestions_rebuild_only_after_their_inputs_change() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (1, 'Each', 'Each'), (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Lemon');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1);
+             INSERT INTO recipes (recipe_id, name) VALUES (1, 'Lemonade');
+             INSERT INTO recp_items (recipe_id, recp_item_id, item_id, unit_id, qty) VALUES (1, 1, 1, 1, 6);",
+        )
+        .unwrap();
+        assert!(ensure_fresh(&conn).unwrap());
+        assert!(!ensure_fresh(&conn).unwrap());
+        let missing: i64 = conn
+            .query_row("SELECT COUNT(*) FROM missing_edges", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(missing, 1);
+
+        conn.execute(
+            "INSERT INTO convunit (item_id, vendor_id, unit_id1, unit_id2, qty1, qty2, status) VALUES (1, 0, 3, 1, 1, 115, 1)",
+            [],
+        )
+        .unwrap();
+        assert!(ensure_fresh(&conn).unwrap());
+        let missing: i64 = conn
+            .query_row("SELECT COUNT(*) FROM missing_edges", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(missing, 0);
+        assert!(!ensure_fresh(&conn).unwrap());
+    }
+}
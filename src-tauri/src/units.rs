@@ -0,0 +1,657 @@
+use crate::costing::{build_conversion_edges, conversion_factor, ConvEdge};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+
+/// Standard weight and volume measures, sized in grams or millilitres, with
+/// the exact spellings they are recognised by, plurals included. Case
+/// packs ("cs", "C") must never match, so nothing is guessed from a name.
+const STANDARD_UNITS: &[(&str, &str, f64, &[&str])] = &[
+    (
+        "mg",
+        "weight",
+        0.001,
+        &[
+            "mg",
+            "mgs",
+            "milligram",
+            "milligrams",
+            "milligramme",
+            "milligrammes",
+        ],
+    ),
+    (
+        "g",
+        "weight",
+        1.0,
+        &[
+            "g", "gm", "gms", "gr", "grm", "gram", "grams", "gramme", "grammes",
+        ],
+    ),
+    (
+        "kg",
+        "weight",
+        1000.0,
+        &[
+            "kg",
+            "kgs",
+            "kilo",
+            "kilos",
+            "kilogram",
+            "kilograms",
+            "kilogramme",
+            "kilogrammes",
+        ],
+    ),
+    (
+        "oz",
+        "weight",
+        28.349523125,
+        &[
+            "oz",
+            "ozs",
+            "ounce",
+            "ounces",
+            "wt oz",
+            "oz wt",
+            "ozwt",
+            "net wt oz",
+        ],
+    ),
+    (
+        "lb",
+        "weight",
+        453.59237,
+        &["lb", "lbs", "#", "pound", "pounds"],
+    ),
+    (
+        "ml",
+        "volume",
+        1.0,
+        &[
+            "ml",
+            "mls",
+            "cc",
+            "milliliter",
+            "milliliters",
+            "millilitre",
+            "millilitres",
+        ],
+    ),
+    (
+        "cl",
+        "volume",
+        10.0,
+        &[
+            "cl",
+            "centiliter",
+            "centiliters",
+            "centilitre",
+            "centilitres",
+        ],
+    ),
+    (
+        "dl",
+        "volume",
+        100.0,
+        &["dl", "deciliter", "deciliters", "decilitre", "decilitres"],
+    ),
+    (
+        "l",
+        "volume",
+        1000.0,
+        &[
+            "l", "lt", "ltr", "ltrs", "liter", "liters", "litre", "litres",
+        ],
+    ),
+    (
+        "tsp",
+        "volume",
+        4.92892159375,
+        &["tsp", "tsps", "teaspoon", "teaspoons", "t"],
+    ),
+    (
+        "tbsp",
+        "volume",
+        14.78676478125,
+        &[
+            "tbsp",
+            "tbsps",
+            "tablespoon",
+            "tablespoons",
+            "tbs",
+            "tbl",
+            "tb",
+            "T",
+        ],
+    ),
+    (
+        "fl oz",
+        "volume",
+        29.5735295625,
+        &[
+            "fl oz",
+            "floz",
+            "fluid ounce",
+            "fluid ounces",
+            "fl ounce",
+            "fl ounces",
+        ],
+    ),
+    ("cup", "volume", 236.5882365, &["cup", "cups", "c"]),
+    ("pt", "volume", 473.176473, &["pt", "pts", "pint", "pints"]),
+    (
+        "qt",
+        "volume",
+        946.352946,
+        &["qt", "qts", "quart", "quarts"],
+    ),
+    (
+        "gal",
+        "volume",
+        3785.411784,
+        &["gal", "gals", "gallon", "gallons"],
+    ),
+];
+
+/// Spellings matched with their case: t = tsp and T = tbsp, as in scaling,
+/// and only a lowercase c is a cup ("C" is as often a case).
+const CASE_SENSITIVE_SPELLINGS: &[&str] = &["t", "T", "c"];
+
+/// Spellings of "oz" that could mean weight or fluid ounces. Other units
+/// sharing the unit's type or kind settle which.
+const OUNCE_SPELLINGS: &[&str] = &["oz", "ozs", "ounce", "ounces"];
+
+/// A unit with the standard measure it converts as, if any.
+pub struct UnitStandard {
+    pub unit_id: i64,
+    pub name: String,
+    pub plural: String,
+    pub unit_type: Option<i64>,
+    pub unit_kind: Option<i64>,
+    pub standard: Option<String>,
+    pub dimension: Option<String>,
+    pub base_qty: Option<f64>,
+    /// "auto" when recognised from its names, "manual" when set by hand,
+    /// empty when it has no standard.
+    pub source: String,
+    pub aliases: Vec<String>,
+}
+
+struct UnitRow {
+    unit_id: i64,
+    sing: String,
+    plur: String,
+    unit_type: Option<i64>,
+    unit_kind: Option<i64>,
+}
+
+/// The standard measures a unit can be set to: name, dimension and size in
+/// grams or millilitres.
+pub fn standard_measures() -> impl Iterator<Item = (&'static str, &'static str, f64)> {
+    STANDARD_UNITS
+        .iter()
+        .map(|(name, dimension, size, _)| (*name, *dimension, *size))
+}
+
+fn measure(standard: &str) -> Option<(&'static str, &'static str, f64)> {
+    standard_measures().find(|(name, _, _)| *name == standard)
+}
+
+/// The standard measure a unit name spells, and whether it is an ambiguous
+/// ounce.
+fn match_spelling(name: &str) -> Option<(&'static str, bool)> {
+    let trimmed = name.trim().trim_end_matches('.');
+    let lower = trimmed
+        .to_lowercase()
+        .replace('.', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if lower.is_empty() {
+        return None;
+    }
+    for (standard, _, _, spellings) in STANDARD_UNITS {
+        for spelling in spellings.iter() {
+            let hit = if CASE_SENSITIVE_SPELLINGS.contains(spelling) {
+                trimmed == *spelling
+            } else {
+                lower == *spelling
+            };
+            if hit {
+                let ambiguous = OUNCE_SPELLINGS.contains(spelling);
+                return Some((standard, ambiguous));
+            }
+        }
+    }
+    None
+}
+
+fn load_units(conn: &Connection) -> Result<Vec<UnitRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT unit_id, COALESCE(sing, ''), COALESCE(plur, ''), unit_type, unit_kind
+             FROM units ORDER BY unit_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(UnitRow {
+                unit_id: row.get(0)?,
+                sing: row.get(1)?,
+                plur: row.get(2)?,
+                unit_type: row.get(3)?,
+                unit_kind: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn aliases_by_unit(conn: &Connection) -> Result<HashMap<i64, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT unit_id, alias FROM unit_aliases ORDER BY alias COLLATE NOCASE")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (unit_id, alias) = row.map_err(|e| e.to_string())?;
+        aliases.entry(unit_id).or_default().push(alias);
+    }
+    Ok(aliases)
+}
+
+/// Recognises the standard measure of every unit from its names and
+/// aliases. Ounces are read as fluid ounces when the other units sharing
+/// their `unit_type` or `unit_kind` are mostly volumes. Hand-set standards
+/// are kept. Returns how many units convert as a standard measure.
+pub fn refresh_standard_units(conn: &Connection) -> Result<usize, String> {
+    let units = load_units(conn)?;
+    let aliases = aliases_by_unit(conn)?;
+    let manual: HashSet<i64> = {
+        let mut stmt = conn
+            .prepare("SELECT unit_id FROM unit_standards WHERE source = 'manual'")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    let mut detected = Vec::new();
+    // (weight, volume) votes per unit_type and per unit_kind.
+    let mut type_votes: HashMap<i64, (u32, u32)> = HashMap::new();
+    let mut kind_votes: HashMap<i64, (u32, u32)> = HashMap::new();
+    for unit in &units {
+        let extra = aliases
+            .get(&unit.unit_id)
+            .map(|a| a.as_slice())
+            .unwrap_or_default();
+        let found = [&unit.sing, &unit.plur]
+            .into_iter()
+            .chain(extra)
+            .find_map(|name| match_spelling(name));
+        let Some((standard, ambiguous)) = found else {
+            continue;
+        };
+        if !ambiguous {
+            let volume = measure(standard).map(|m| m.1 == "volume").unwrap_or(false);
+            for (votes, key) in [
+                (&mut type_votes, unit.unit_type),
+                (&mut kind_votes, unit.unit_kind),
+            ] {
+                if let Some(key) = key {
+                    let entry = votes.entry(key).or_default();
+                    if volume {
+                        entry.1 += 1;
+                    } else {
+                        entry.0 += 1;
+                    }
+                }
+            }
+        }
+        detected.push((unit, standard, ambiguous));
+    }
+
+    conn.execute(
+        "DELETE FROM unit_standards WHERE COALESCE(source, 'auto') = 'auto'",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+    for (unit, standard, ambiguous) in detected {
+        if manual.contains(&unit.unit_id) {
+            continue;
+        }
+        let mut standard = standard;
+        if ambiguous {
+            let (mut weight, mut volume) = (0, 0);
+            for (votes, key) in [(&type_votes, unit.unit_type), (&kind_votes, unit.unit_kind)] {
+                if let Some((w, v)) = key.and_then(|k| votes.get(&k)) {
+                    weight += w;
+                    volume += v;
+                }
+            }
+            if volume > weight {
+                standard = "fl oz";
+            }
+        }
+        let Some((_, dimension, base_qty)) = measure(standard) else {
+            continue;
+        };
+        conn.execute(
+            "INSERT INTO unit_standards (unit_id, standard, dimension, base_qty, source) VALUES (?1, ?2, ?3, ?4, 'auto')",
+            rusqlite::params![unit.unit_id, standard, dimension, base_qty],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    conn.query_row(
+        "SELECT COUNT(*) FROM unit_standards WHERE dimension IS NOT NULL",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|n| n as usize)
+    .map_err(|e| e.to_string())
+}
+
+pub fn list_unit_standards(conn: &Connection) -> Result<Vec<UnitStandard>, String> {
+    let mut aliases = aliases_by_unit(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT u.unit_id, COALESCE(u.sing, ''), COALESCE(u.plur, ''), u.unit_type, u.unit_kind,
+                    s.standard, s.dimension, s.base_qty, COALESCE(s.source, '')
+             FROM units u LEFT JOIN unit_standards s ON s.unit_id = u.unit_id
+             ORDER BY s.dimension IS NULL, s.dimension, s.base_qty, u.sing COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(UnitStandard {
+                unit_id: row.get(0)?,
+                name: row.get(1)?,
+                plural: row.get(2)?,
+                unit_type: row.get(3)?,
+                unit_kind: row.get(4)?,
+                standard: row.get(5)?,
+                dimension: row.get(6)?,
+                base_qty: row.get(7)?,
+                source: row.get(8)?,
+                aliases: Vec::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut units = Vec::new();
+    for row in rows {
+        let mut unit = row.map_err(|e| e.to_string())?;
+        unit.aliases = aliases.remove(&unit.unit_id).unwrap_or_default();
+        units.push(unit);
+    }
+    Ok(units)
+}
+
+/// Sets the standard measure a unit converts as. `Some("")` marks it as not
+/// a standard measure; `None` goes back to recognising it from its names.
+pub fn set_unit_standard(
+    conn: &Connection,
+    unit_id: i64,
+    standard: Option<&str>,
+) -> Result<(), String> {
+    require_unit(conn, unit_id)?;
+    match standard {
+        None => {
+            conn.execute("DELETE FROM unit_standards WHERE unit_id = ?1", [unit_id])
+                .map_err(|e| e.to_string())?;
+        }
+        Some("") => {
+            conn.execute(
+                "INSERT OR REPLACE INTO unit_standards (unit_id, standard, dimension, base_qty, source)
+                 VALUES (?1, NULL, NULL, NULL, 'manual')",
+                [unit_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Some(standard) => {
+            let (name, dimension, base_qty) = measure(standard)
+                .ok_or_else(|| format!("Unknown standard measure '{}'", standard))?;
+            conn.execute(
+                "INSERT OR REPLACE INTO unit_standards (unit_id, standard, dimension, base_qty, source)
+                 VALUES (?1, ?2, ?3, ?4, 'manual')",
+                rusqlite::params![unit_id, name, dimension, base_qty],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    refresh_standard_units(conn)?;
+    Ok(())
+}
+
+fn require_unit(conn: &Connection, unit_id: i64) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM units WHERE unit_id = ?1)",
+            [unit_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if exists {
+        Ok(())
+    } else {
+        Err(format!("Unit {} not found", unit_id))
+    }
+}
+
+/// Adds another spelling for a unit, e.g. "#" for a "Pound" unit.
+pub fn save_unit_alias(conn: &Connection, alias: &str, unit_id: i64) -> Result<(), String> {
+    let alias = alias.trim();
+    if alias.is_empty() {
+        return Err("Alias is required".to_string());
+    }
+    require_unit(conn, unit_id)?;
+    let taken: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT unit_id, sing FROM units
+             WHERE unit_id <> ?2 AND (TRIM(sing) = ?1 COLLATE NOCASE OR TRIM(plur) = ?1 COLLATE NOCASE)
+             LIMIT 1",
+            rusqlite::params![alias, unit_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some((other, name)) = taken {
+        return Err(format!(
+            "'{}' is already the name of unit {} ({})",
+            alias, other, name
+        ));
+    }
+    conn.execute(
+        "INSERT INTO unit_aliases (alias, unit_id) VALUES (?1, ?2)
+         ON CONFLICT(alias) DO UPDATE SET unit_id = excluded.unit_id",
+        rusqlite::params![alias, unit_id],
+    )
+    .map_err(|e| e.to_string())?;
+    refresh_standard_units(conn)?;
+    Ok(())
+}
+
+pub fn delete_unit_alias(conn: &Connection, alias: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM unit_aliases WHERE alias = ?1", [alias.trim()])
+        .map_err(|e| e.to_string())?;
+    refresh_standard_units(conn)?;
+    Ok(())
+}
+
+/// The unit a piece of text names: a unit's own name, one of its aliases,
+/// or any spelling of a standard measure the unit converts as.
+pub fn resolve_unit(conn: &Connection, text: &str) -> Result<Option<i64>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let named: Option<i64> = conn
+        .query_row(
+            "SELECT unit_id FROM units
+             WHERE TRIM(sing) = ?1 COLLATE NOCASE OR TRIM(plur) = ?1 COLLATE NOCASE
+             ORDER BY TRIM(sing) = ?1 COLLATE NOCASE DESC, unit_id LIMIT 1",
+            [text],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if named.is_some() {
+        return Ok(named);
+    }
+    let aliased: Option<i64> = conn
+        .query_row(
+            "SELECT unit_id FROM unit_aliases WHERE alias = ?1",
+            [text],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if aliased.is_some() {
+        return Ok(aliased);
+    }
+    let Some((standard, _)) = match_spelling(text) else {
+        return Ok(None);
+    };
+    conn.query_row(
+        "SELECT unit_id FROM unit_standards WHERE standard = ?1 ORDER BY unit_id LIMIT 1",
+        [standard],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// "weight" or "volume" when the unit is a standard measure.
+pub fn unit_dimension(conn: &Connection, unit_id: i64) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT dimension FROM unit_standards WHERE unit_id = ?1 AND standard IS NOT NULL",
+        [unit_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| e.to_string())
+}
+
+/// Conversion edges between every pair of units of the same dimension, in
+/// `(unit_id1, unit_id2, qty1, qty2)` form. They hold for every item.
+pub fn standard_edges(conn: &Connection) -> Result<Vec<(i64, i64, f64, f64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT unit_id, dimension, base_qty FROM unit_standards
+             WHERE dimension IS NOT NULL AND base_qty > 0
+             ORDER BY unit_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let units: Vec<(i64, String, f64)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut edges = Vec::new();
+    for (i, (a, dim_a, base_a)) in units.iter().enumerate() {
+        for (b, dim_b, base_b) in &units[i + 1..] {
+            if dim_a == dim_b {
+                // base_b of unit a weighs or holds the same as base_a of unit b.
+                edges.push((*a, *b, *base_b, *base_a));
+            }
+        }
+    }
+    Ok(edges)
+}
+
+/// Imported lists of conversions that were missing when the data was
+/// exported.
+pub const BACKLOG_TABLES: [&str; 2] = ["conv_todo", "missing_edges"];
+
+/// Rows (by rowid) of a backlog table whose units now convert, typically
+/// through standard conversions or a density.
+pub fn covered_rows(conn: &Connection, table: &str) -> Result<Vec<i64>, String> {
+    if !BACKLOG_TABLES.contains(&table) {
+        return Err(format!("Unknown conversion backlog '{}'", table));
+    }
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT rowid, item_id, vendor_id, recipe_unit_id, purch_unit_id FROM {}
+             WHERE item_id IS NOT NULL AND recipe_unit_id IS NOT NULL AND purch_unit_id IS NOT NULL",
+            table
+        ))
+        .map_err(|e| e.to_string())?;
+    type Row = (i64, i64, Option<i64>, i64, i64);
+    let rows: Vec<Row> = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut edges_by_item: HashMap<(i64, Option<i64>), Vec<ConvEdge>> = HashMap::new();
+    let mut covered = Vec::new();
+    for (row_id, item_id, vendor_id, from, to) in rows {
+        let vendor_id = vendor_id.filter(|v| *v > 0);
+        if let std::collections::hash_map::Entry::Vacant(slot) =
+            edges_by_item.entry((item_id, vendor_id))
+        {
+            slot.insert(build_conversion_edges(conn, item_id, vendor_id)?);
+        }
+        let edges = &edges_by_item[&(item_id, vendor_id)];
+        if conversion_factor(edges, from, to, 6).is_some() {
+            covered.push(row_id);
+        }
+    }
+    Ok(covered)
+}
+
+/// Deletes the rows `covered_rows` finds. Returns how many.
+pub fn clear_covered(conn: &Connection, table: &str) -> Result<usize, String> {
+    let covered = covered_rows(conn, table)?;
+    for row_id in &covered {
+        conn.execute(&format!("DELETE FROM {} WHERE rowid = ?1", table), [row_id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(covered.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::match_spelling;
+
+    #[test]
+    fn matches_spellings_and_plurals() {
+        assert_eq!(match_spelling("Cups"), Some(("cup", false)));
+        assert_eq!(match_spelling("c"), Some(("cup", false)));
+        assert_eq!(match_spelling("LBS"), Some(("lb", false)));
+        assert_eq!(match_spelling("lb."), Some(("lb", false)));
+        assert_eq!(match_spelling("#"), Some(("lb", false)));
+        assert_eq!(match_spelling("Fl. Oz."), Some(("fl oz", false)));
+        assert_eq!(match_spelling("Gallons"), Some(("gal", false)));
+        assert_eq!(match_spelling("t"), Some(("tsp", false)));
+        assert_eq!(match_spelling("T"), Some(("tbsp", false)));
+    }
+
+    #[test]
+    fn ounces_are_ambiguous() {
+        assert_eq!(match_spelling("oz"), Some(("oz", true)));
+        assert_eq!(match_spelling("Ounces"), Some(("oz", true)));
+        assert_eq!(match_spelling("wt oz"), Some(("oz", false)));
+    }
+
+    #[test]
+    fn case_packs_are_not_measures() {
+        for name in [
+            "CS", "cs", "C", "Case", "Cases", "Ls", "Each", "EA", "ct", "bag", "",
+        ] {
+            assert_eq!(match_spelling(name), None, "{name}");
+        }
+    }
+}
@@ -0,0 +1,477 @@
+use crate::costing::{accumulate_recipe_usage, convert_item_qty, default_purch_unit, item_price};
+use crate::pricing::recipe_servings;
+use rusqlite::Connection;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Theoretical vs actual usage of one inventory item, in its purchase unit.
+pub struct VarianceLine {
+    pub item_id: i64,
+    pub item_name: String,
+    pub category: String,
+    pub unit_name: String,
+    pub theoretical_qty: f64,
+    pub purchased_qty: f64,
+    pub begin_qty: Option<f64>,
+    pub end_qty: Option<f64>,
+    pub actual_qty: f64,
+    pub variance_qty: f64,
+    pub price: Option<f64>,
+    pub theoretical_cost: Option<f64>,
+    pub actual_cost: Option<f64>,
+    pub variance_cost: Option<f64>,
+    pub variance_pct: Option<f64>,
+    /// "Counted" when opening and closing counts exist, else "Purchases only".
+    pub basis: String,
+    /// The days the purchases and theoretical usage cover: from the day
+    /// after the opening count through the closing count when counted,
+    /// otherwise the requested period.
+    pub usage_from: String,
+    pub usage_to: String,
+}
+
+pub struct CategoryVariance {
+    pub category: String,
+    pub theoretical_cost: f64,
+    pub actual_cost: f64,
+    pub variance_cost: f64,
+}
+
+pub struct VarianceReport {
+    pub date_from: String,
+    pub date_to: String,
+    pub net_sales: f64,
+    pub theoretical_cost: f64,
+    pub actual_cost: f64,
+    pub theoretical_pct: Option<f64>,
+    pub actual_pct: Option<f64>,
+    pub lines: Vec<VarianceLine>,
+    pub categories: Vec<CategoryVariance>,
+    /// Recipe lines, purchases and counts that couldn't be put in a purchase unit.
+    pub unresolved: Vec<String>,
+}
+
+/// Counts entered in a still-open count session aren't final yet.
+const FINAL_COUNT: &str =
+    "(session_id IS NULL OR session_id IN (SELECT session_id FROM count_sessions WHERE status = 'closed'))";
+
+/// Sums the counts of an item on its latest count date within the bounds,
+/// converted to `purch_unit`. Returns the date and quantity (`None` when a
+/// counted unit has no conversion).
+pub fn item_count(
+    conn: &Connection,
+    item_id: i64,
+    purch_unit: i64,
+    after: Option<&str>,
+    on_or_before: &str,
+) -> Result<Option<(String, Option<f64>)>, String> {
+    let date: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT MAX(count_date) FROM inv_counts
+                 WHERE item_id = ?1 AND count_date <= ?2 AND (?3 IS NULL OR count_date > ?3) AND {}",
+                FINAL_COUNT
+            ),
+            rusqlite::params![item_id, on_or_before, after],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let date = match date {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT COALESCE(qty, 0), unit_id FROM inv_counts WHERE item_id = ?1 AND count_date = ?2 AND {}",
+            FINAL_COUNT
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(f64, Option<i64>)> = stmt
+        .query_map(rusqlite::params![item_id, date], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut total = Some(0.0);
+    for (qty, unit_id) in rows {
+        let converted = convert_item_qty(
+            conn,
+            item_id,
+            qty,
+            unit_id.unwrap_or(purch_unit),
+            purch_unit,
+        )?;
+        total = match (total, converted) {
+            (Some(t), Some(q)) => Some(t + q),
+            _ => None,
+        };
+    }
+    Ok(Some((date, total)))
+}
+
+/// Purchase-unit quantity of every inventory item the recipes sold between
+/// the two dates (inclusive) should have used.
+pub fn theoretical_usage(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+    unresolved: &mut Vec<String>,
+) -> Result<HashMap<i64, f64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.recipe_id, r.name, SUM(s.qty * COALESCE(p.portions, 1))
+             FROM sales s
+             JOIN pos_items p ON p.pos_item_id = s.pos_item_id
+             LEFT JOIN recipes r ON r.recipe_id = p.recipe_id
+             WHERE p.recipe_id IS NOT NULL AND COALESCE(p.ignored, 0) = 0
+               AND s.sale_date >= ?1 AND s.sale_date <= ?2
+             GROUP BY p.recipe_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let sold: Vec<(i64, Option<String>, f64)> = stmt
+        .query_map(rusqlite::params![date_from, date_to], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get::<_, Option<f64>>(2)?.unwrap_or(0.0),
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut theoretical: HashMap<i64, f64> = HashMap::new();
+    for (recipe_id, name, portions) in sold {
+        let name = name.unwrap_or_else(|| format!("Recipe {}", recipe_id));
+        match recipe_servings(conn, recipe_id)? {
+            Some(servings) => accumulate_recipe_usage(
+                conn,
+                recipe_id,
+                portions / servings,
+                0,
+                &mut theoretical,
+                unresolved,
+            )?,
+            None => unresolved.push(format!("{} (no servings set)", name)),
+        }
+    }
+    Ok(theoretical)
+}
+
+/// Invoice lines (item, qty, unit) received between the two dates.
+pub fn received_lines(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+) -> Result<Vec<(i64, f64, Option<i64>)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.item_id, COALESCE(t.qty, 0), t.unit_id
+             FROM trans t
+             LEFT JOIN invoices i ON i.invoice_id = t.invoice_id
+             WHERE t.item_id IS NOT NULL
+               AND substr(COALESCE(i.invoice_date, t.trans_date), 1, 10) >= ?1
+               AND substr(COALESCE(i.invoice_date, t.trans_date), 1, 10) <= ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let received: Vec<(i64, f64, Option<i64>)> = stmt
+        .query_map(rusqlite::params![date_from, date_to], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(received)
+}
+
+/// Totals received lines per item in each item's purchase unit.
+pub fn purchased_qty(
+    conn: &Connection,
+    received: &[(i64, f64, Option<i64>)],
+    unresolved: &mut Vec<String>,
+) -> Result<HashMap<i64, f64>, String> {
+    let mut purchased: HashMap<i64, f64> = HashMap::new();
+    for (item_id, qty, unit_id) in received {
+        let purch_unit = match default_purch_unit(conn, *item_id) {
+            Some(u) => u,
+            None => continue,
+        };
+        match convert_item_qty(
+            conn,
+            *item_id,
+            *qty,
+            unit_id.unwrap_or(purch_unit),
+            purch_unit,
+        )? {
+            Some(q) => *purchased.entry(*item_id).or_insert(0.0) += q,
+            None => unresolved.push(format!("Item {} purchase unit has no conversion", item_id)),
+        }
+    }
+    Ok(purchased)
+}
+
+/// Theoretical usage and purchases per item over one usage window.
+struct WindowUsage {
+    theoretical: HashMap<i64, f64>,
+    purchased: HashMap<i64, f64>,
+}
+
+fn window_usage(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+    unresolved: &mut Vec<String>,
+) -> Result<WindowUsage, String> {
+    let theoretical = theoretical_usage(conn, date_from, date_to, unresolved)?;
+    let received = received_lines(conn, date_from, date_to)?;
+    let purchased = purchased_qty(conn, &received, unresolved)?;
+    Ok(WindowUsage {
+        theoretical,
+        purchased,
+    })
+}
+
+/// The day after `date` (YYYY-MM-DD).
+pub fn day_after(conn: &Connection, date: &str) -> Result<String, String> {
+    conn.query_row("SELECT date(?1, '+1 day')", [date], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Compares what sales say should have been used (recipes × POS units sold)
+/// with what was actually used (opening count + purchases − closing count)
+/// for each inventory item over a period. Counts are taken as of close of
+/// business, so a counted item's purchases and sales run from the day after
+/// its opening count through its closing count rather than over the period
+/// as asked. Both sides are valued at the same current purchase price so the
+/// variance is purely a quantity difference.
+pub fn variance_report(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+) -> Result<VarianceReport, String> {
+    if date_from.is_empty() || date_to.is_empty() {
+        return Err("Choose a start and end date for the period".to_string());
+    }
+    if date_to < date_from {
+        return Err("Period end is before period start".to_string());
+    }
+    let mut unresolved = Vec::new();
+
+    let theoretical = theoretical_usage(conn, date_from, date_to, &mut unresolved)?;
+    let received = received_lines(conn, date_from, date_to)?;
+    let purchased = purchased_qty(conn, &received, &mut unresolved)?;
+    let mut counted_windows: HashMap<(String, String), WindowUsage> = HashMap::new();
+
+    let mut counted_items: BTreeSet<i64> = BTreeSet::new();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT DISTINCT item_id FROM inv_counts WHERE count_date <= ?1 AND item_id IS NOT NULL AND {}",
+            FINAL_COUNT
+        ))
+        .map_err(|e| e.to_string())?;
+    for row in stmt
+        .query_map([date_to], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?
+    {
+        counted_items.insert(row.map_err(|e| e.to_string())?);
+    }
+
+    let mut item_ids: BTreeSet<i64> = theoretical.keys().copied().collect();
+    item_ids.extend(received.iter().map(|r| r.0));
+    item_ids.extend(counted_items.iter().copied());
+
+    let mut lines = Vec::new();
+    for item_id in item_ids {
+        let purch_unit = default_purch_unit(conn, item_id);
+        let (item_name, category, unit_name): (String, String, String) = conn
+            .query_row(
+                "SELECT COALESCE(i.name, ''), COALESCE(i.food_category, ''), COALESCE(u.sing, '-')
+                 FROM items i
+                 LEFT JOIN units u ON u.unit_id = ?2
+                 WHERE i.item_id = ?1",
+                rusqlite::params![item_id, purch_unit],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap_or_else(|_| (format!("Item {}", item_id), String::new(), "-".to_string()));
+        let purch_unit = match purch_unit {
+            Some(u) => u,
+            None => {
+                unresolved.push(format!("{} (no purchase unit)", item_name));
+                continue;
+            }
+        };
+
+        let begin = item_count(conn, item_id, purch_unit, None, date_from)?;
+        let end = match &begin {
+            Some((begin_date, _)) => {
+                item_count(conn, item_id, purch_unit, Some(begin_date), date_to)?
+            }
+            None => None,
+        };
+        let counted = match (&begin, &end) {
+            (Some((begin_date, Some(b))), Some((end_date, Some(e)))) => {
+                Some((day_after(conn, begin_date)?, end_date.clone(), *b, *e))
+            }
+            _ => None,
+        };
+        let begin_qty = begin.and_then(|b| b.1);
+        let end_qty = end.and_then(|e| e.1);
+        let (theoretical_qty, purchased_qty, actual_qty, basis, usage_from, usage_to) =
+            match counted {
+                Some((from, to, b, e)) => {
+                    let key = (from.clone(), to.clone());
+                    if !counted_windows.contains_key(&key) {
+                        let usage = window_usage(conn, &from, &to, &mut unresolved)?;
+                        counted_windows.insert(key.clone(), usage);
+                    }
+                    let usage = &counted_windows[&key];
+                    let theoretical_qty = usage.theoretical.get(&item_id).copied().unwrap_or(0.0);
+                    let purchased_qty = usage.purchased.get(&item_id).copied().unwrap_or(0.0);
+                    (
+                        theoretical_qty,
+                        purchased_qty,
+                        b + purchased_qty - e,
+                        "Counted",
+                        from,
+                        to,
+                    )
+                }
+                None => {
+                    let purchased_qty = purchased.get(&item_id).copied().unwrap_or(0.0);
+                    (
+                        theoretical.get(&item_id).copied().unwrap_or(0.0),
+                        purchased_qty,
+                        purchased_qty,
+                        "Purchases only",
+                        date_from.to_string(),
+                        date_to.to_string(),
+                    )
+                }
+            };
+        if theoretical_qty == 0.0 && actual_qty == 0.0 {
+            continue;
+        }
+
+        let price = item_price(conn, item_id).map(|p| p.0);
+        let variance_qty = actual_qty - theoretical_qty;
+        lines.push(VarianceLine {
+            item_id,
+            item_name,
+            category,
+            unit_name,
+            theoretical_qty,
+            purchased_qty,
+            begin_qty,
+            end_qty,
+            actual_qty,
+            variance_qty,
+            price,
+            theoretical_cost: price.map(|p| p * theoretical_qty),
+            actual_cost: price.map(|p| p * actual_qty),
+            variance_cost: price.map(|p| p * variance_qty),
+            variance_pct: if theoretical_qty > 0.0 {
+                Some(variance_qty / theoretical_qty * 100.0)
+            } else {
+                None
+            },
+            basis: basis.to_string(),
+            usage_from,
+            usage_to,
+        });
+    }
+    lines.sort_by(|a, b| {
+        b.variance_cost
+            .unwrap_or(f64::MIN)
+            .partial_cmp(&a.variance_cost.unwrap_or(f64::MIN))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut by_category: BTreeMap<String, CategoryVariance> = BTreeMap::new();
+    for line in &lines {
+        let key = if line.category.is_empty() {
+            "Uncategorized".to_string()
+        } else {
+            line.category.clone()
+        };
+        let entry = by_category.entry(key.clone()).or_insert(CategoryVariance {
+            category: key,
+            theoretical_cost: 0.0,
+            actual_cost: 0.0,
+            variance_cost: 0.0,
+        });
+        entry.theoretical_cost += line.theoretical_cost.unwrap_or(0.0);
+        entry.actual_cost += line.actual_cost.unwrap_or(0.0);
+        entry.variance_cost += line.variance_cost.unwrap_or(0.0);
+    }
+    let mut categories: Vec<CategoryVariance> = by_category.into_values().collect();
+    categories.sort_by(|a, b| {
+        b.variance_cost
+            .partial_cmp(&a.variance_cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let net_sales: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(s.net_sales), 0)
+             FROM sales s
+             JOIN pos_items p ON p.pos_item_id = s.pos_item_id
+             WHERE COALESCE(p.ignored, 0) = 0 AND s.sale_date >= ?1 AND s.sale_date <= ?2",
+            rusqlite::params![date_from, date_to],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let theoretical_cost: f64 = lines.iter().filter_map(|l| l.theoretical_cost).sum();
+    let actual_cost: f64 = lines.iter().filter_map(|l| l.actual_cost).sum();
+    let pct = |cost: f64| {
+        if net_sales > 0.0 {
+            Some(cost / net_sales * 100.0)
+        } else {
+            None
+        }
+    };
+    unresolved.sort();
+    unresolved.dedup();
+
+    Ok(VarianceReport {
+        date_from: date_from.to_string(),
+        date_to: date_to.to_string(),
+        net_sales,
+        theoretical_cost,
+        actual_cost,
+        theoretical_pct: pct(theoretical_cost),
+        actual_pct: pct(actual_cost),
+        lines,
+        categories,
+        unresolved,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::variance_report;
+    use rusqlite::Connection;
+
+    #[test]
+    fn counted_items_use_the_count_dates() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO units (unit_id, sing, plur) VALUES (3, 'Case', 'Cases');
+             INSERT INTO items (item_id, name) VALUES (1, 'Fryer Oil');
+             INSERT INTO inv_units (item_id, purch_unit_id, is_default) VALUES (1, 3, 1);
+             INSERT INTO inv_counts (item_id, count_date, qty, unit_id) VALUES (1, '2026-02-28', 10, 3), (1, '2026-03-31', 4, 3);
+             INSERT INTO invoices (invoice_id, invoice_date, vendor_id) VALUES (1, '2026-02-28', 7), (2, '2026-03-02', 7);
+             INSERT INTO trans (invoice_id, trans_id, item_id, vendor_id, price, qty, unit_id)
+             VALUES (1, 10, 1, 7, 30.0, 5, 3), (2, 20, 1, 7, 30.0, 6, 3);",
+        )
+        .unwrap();
+        let report = variance_report(&conn, "2026-03-05", "2026-03-31").unwrap();
+        let line = &report.lines[0];
+        assert_eq!(line.basis, "Counted");
+        assert_eq!(
+            (line.usage_from.as_str(), line.usage_to.as_str()),
+            ("2026-03-01", "2026-03-31")
+        );
+        assert_eq!(line.purchased_qty, 6.0);
+        assert_eq!(line.actual_qty, 12.0);
+    }
+}
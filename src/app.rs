@@ -41,6 +41,8 @@ struct InventoryQueryArgs {
 #[derive(Serialize)]
 struct RecipeQueryArgs {
     query: String,
+    #[serde(rename = "orderBy")]
+    order_by: Option<String>,
     limit: u32,
     offset: u32,
 }
@@ -179,6 +181,19 @@ struct AddRecpItemArgs {
     #[serde(rename = "unitId")]
     unit_id: Option<i64>,
     qty: Option<f64>,
+    #[serde(rename = "subRecipeId")]
+    sub_recipe_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct UpdateRecipeYieldArgs {
+    #[serde(rename = "recipeId")]
+    recipe_id: i64,
+    #[serde(rename = "yieldQty")]
+    yield_qty: Option<f64>,
+    #[serde(rename = "yieldUnitId")]
+    yield_unit_id: Option<i64>,
+    servings: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -205,6 +220,93 @@ struct UpdateItemCategoryArgs {
     storage_type: String,
 }
 
+#[derive(Serialize)]
+struct UpdateItemYieldArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "yieldPct")]
+    yield_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct UpdateItemDensityArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    density: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct UpsertItemPrepArgs {
+    #[serde(rename = "prepId")]
+    prep_id: Option<i64>,
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    name: String,
+    #[serde(rename = "yieldPct")]
+    yield_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ItemPrepIdArgs {
+    #[serde(rename = "prepId")]
+    prep_id: i64,
+}
+
+#[derive(Serialize)]
+struct YieldTestInput {
+    item_id: i64,
+    prep_id: Option<i64>,
+    test_date: Option<String>,
+    unit_id: Option<i64>,
+    gross_qty: f64,
+    trim_qty: Option<f64>,
+    waste_qty: Option<f64>,
+    net_qty: Option<f64>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RecordYieldTestArgs {
+    test: YieldTestInput,
+}
+
+#[derive(Serialize)]
+struct RecordInventoryCountArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "countDate")]
+    count_date: Option<String>,
+    qty: f64,
+    #[serde(rename = "unitId")]
+    unit_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct InventoryCountIdArgs {
+    #[serde(rename = "countId")]
+    count_id: i64,
+}
+
+#[derive(Serialize)]
+struct SaveParLevelArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "storageType")]
+    storage_type: Option<String>,
+    #[serde(rename = "dayOfWeek")]
+    day_of_week: Option<i64>,
+    #[serde(rename = "parQty")]
+    par_qty: f64,
+    #[serde(rename = "unitId")]
+    unit_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct ParLevelIdArgs {
+    #[serde(rename = "parId")]
+    par_id: i64,
+}
+
 #[derive(Serialize)]
 struct GlobalSearchArgs {
     query: String,
@@ -224,6 +326,45 @@ struct ExportRecipeArgs {
     recipe_id: i64,
     #[serde(rename = "outputPath")]
     output_path: String,
+    #[serde(rename = "scaleMode")]
+    scale_mode: Option<String>,
+    #[serde(rename = "scaleTarget")]
+    scale_target: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ScaleRecipeArgs {
+    #[serde(rename = "recipeId")]
+    recipe_id: i64,
+    mode: String,
+    target: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ScaledRecipeLine {
+    recp_item_id: i64,
+    item_name: String,
+    orig_qty: Option<f64>,
+    orig_unit_name: String,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    display_qty: String,
+    extended_cost: Option<f64>,
+    cost_status: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ScaledRecipeResponse {
+    recipe_id: i64,
+    name: String,
+    factor: f64,
+    servings: Option<f64>,
+    yield_qty: Option<f64>,
+    yield_unit_name: String,
+    total_cost: f64,
+    missing_costs: i64,
+    lines: Vec<ScaledRecipeLine>,
 }
 
 #[derive(Serialize)]
@@ -327,6 +468,8 @@ struct InventoryDetailPrice {
     prev_price: Option<f64>,
     diff_pct: Option<f64>,
     pack: String,
+    #[serde(default)]
+    pack_reading: String,
     status: Option<i64>,
 }
 
@@ -369,6 +512,66 @@ struct InventoryDetailResponse {
     conversions: Vec<InventoryDetailConversion>,
     usage: Vec<InventoryDetailUsage>,
     missing_edges: Vec<InventoryDetailMissingEdge>,
+    #[serde(default)]
+    yield_pct: Option<f64>,
+    #[serde(default)]
+    density: Option<f64>,
+    #[serde(default)]
+    effective_density: Option<f64>,
+    #[serde(default)]
+    density_source: String,
+    #[serde(default)]
+    preps: Vec<ItemPrep>,
+    #[serde(default)]
+    yield_tests: Vec<YieldTest>,
+    #[serde(default)]
+    counts: Vec<InventoryCount>,
+    #[serde(default)]
+    par_levels: Vec<ParLevel>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ParLevel {
+    par_id: i64,
+    storage_type: String,
+    day_of_week: Option<i64>,
+    day_name: String,
+    par_qty: f64,
+    unit_id: Option<i64>,
+    unit_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InventoryCount {
+    count_id: i64,
+    count_date: String,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    notes: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ItemPrep {
+    prep_id: i64,
+    item_id: i64,
+    name: String,
+    yield_pct: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct YieldTest {
+    test_id: i64,
+    prep_id: Option<i64>,
+    prep_name: String,
+    test_date: String,
+    unit_name: String,
+    gross_qty: Option<f64>,
+    trim_qty: Option<f64>,
+    waste_qty: Option<f64>,
+    net_qty: Option<f64>,
+    yield_pct: Option<f64>,
+    notes: String,
 }
 
 #[derive(Serialize)]
@@ -413,6 +616,10 @@ struct VendorDetailResponse {
     price_items: Vec<VendorPriceItem>,
     invoice_count: i64,
     trans_count: i64,
+    #[serde(default)]
+    prices_include_tax: bool,
+    #[serde(default)]
+    tax_rate: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -442,6 +649,16 @@ struct RecipeListItem {
     recipe_id: i64,
     name: String,
     item_count: i64,
+    #[serde(default)]
+    servings: Option<f64>,
+    #[serde(default)]
+    cost_per_portion: Option<f64>,
+    #[serde(default)]
+    menu_price: Option<f64>,
+    #[serde(default)]
+    food_cost_pct: Option<f64>,
+    #[serde(default)]
+    margin: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -449,6 +666,7 @@ struct RecipeListResponse {
     recipes: Vec<RecipeListItem>,
     total: i64,
     filtered: i64,
+    costs_stale: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -464,6 +682,46 @@ struct RecipeIngredient {
     price: Option<f64>,
     extended_cost: Option<f64>,
     cost_status: String,
+    #[serde(default)]
+    sub_recipe_id: Option<i64>,
+    #[serde(default)]
+    prep_name: String,
+    #[serde(default)]
+    yield_pct: Option<f64>,
+    #[serde(default)]
+    ap_cost: Option<f64>,
+    #[serde(default)]
+    conversion_summary: String,
+    #[serde(default)]
+    conversion_path: Vec<ConversionStep>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ConversionStep {
+    from_unit_name: String,
+    to_unit_name: String,
+    factor: f64,
+    source: String,
+    #[serde(default)]
+    vendor_name: String,
+    #[serde(default)]
+    note: String,
+}
+
+/// One line per hop, e.g. "1 Case = 6 Each (vendor: Sysco)".
+fn conversion_path_text(steps: &[ConversionStep]) -> String {
+    steps
+        .iter()
+        .map(|s| {
+            let source = match (s.vendor_name.is_empty(), s.note.is_empty()) {
+                (false, _) => format!("{}: {}", s.source, s.vendor_name),
+                (true, false) => s.note.clone(),
+                (true, true) => s.source.clone(),
+            };
+            format!("1 {} = {:.4} {} ({})", s.from_unit_name, s.factor, s.to_unit_name, source)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -477,6 +735,50 @@ struct RecipeDetailResponse {
     ingredients: Vec<RecipeIngredient>,
     #[serde(default)]
     allergens: Vec<String>,
+    #[serde(default)]
+    yield_qty: Option<f64>,
+    #[serde(default)]
+    yield_unit_id: Option<i64>,
+    #[serde(default)]
+    yield_unit_name: String,
+    #[serde(default)]
+    cost_per_yield_unit: Option<f64>,
+    #[serde(default)]
+    servings: Option<f64>,
+    #[serde(default)]
+    portion_size: Option<f64>,
+    #[serde(default)]
+    portion_unit_id: Option<i64>,
+    #[serde(default)]
+    portion_unit_name: String,
+    #[serde(default)]
+    cost_per_portion: Option<f64>,
+    #[serde(default)]
+    target_food_cost_pct: Option<f64>,
+    #[serde(default)]
+    effective_target_pct: f64,
+    #[serde(default)]
+    suggested_price: Option<f64>,
+    #[serde(default)]
+    menu_price: Option<f64>,
+    #[serde(default)]
+    food_cost_pct: Option<f64>,
+    #[serde(default)]
+    margin: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct UpdateRecipePricingArgs {
+    #[serde(rename = "recipeId")]
+    recipe_id: i64,
+    #[serde(rename = "portionSize")]
+    portion_size: Option<f64>,
+    #[serde(rename = "portionUnitId")]
+    portion_unit_id: Option<i64>,
+    #[serde(rename = "targetFoodCostPct")]
+    target_food_cost_pct: Option<f64>,
+    #[serde(rename = "menuPrice")]
+    menu_price: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -516,6 +818,20 @@ struct ConversionSuggestionRow {
     derived_from: String,
     hops: Option<i64>,
     path: String,
+    #[serde(default)]
+    confidence: Option<f64>,
+}
+
+/// "pack · 75% · 2 hops" for a suggestion row.
+fn suggestion_source(row: &ConversionSuggestionRow) -> String {
+    let mut parts = vec![row.derived_from.clone()];
+    if let Some(c) = row.confidence {
+        parts.push(format!("{:.0}%", c * 100.0));
+    }
+    if let Some(h) = row.hops {
+        parts.push(format!("{} hops", h));
+    }
+    parts.join(" · ")
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -589,2709 +905,8160 @@ struct MissingDataResponse {
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct InvoiceListItem {
-    invoice_id: i64,
-    invoice_no: String,
-    vendor_id: Option<i64>,
+struct ConversionIssue {
+    kind: String,
+    row_id: i64,
+    item_id: i64,
+    item_name: String,
     vendor_name: String,
-    invoice_date: String,
-    total: Option<f64>,
+    unit1_name: String,
+    unit2_name: String,
+    qty1: Option<f64>,
+    qty2: Option<f64>,
+    detail: String,
+    suggested_qty2: Option<f64>,
+    fixes: Vec<String>,
+    recipe_count: i64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct InvoiceListResponse {
-    invoices: Vec<InvoiceListItem>,
-    total: i64,
-    filtered: i64,
+struct ConversionCheckResponse {
+    tolerance_pct: f64,
+    items_checked: usize,
+    rows_checked: usize,
+    issues: Vec<ConversionIssue>,
+}
+
+#[derive(Serialize)]
+struct CheckConversionsArgs {
+    #[serde(rename = "tolerancePct")]
+    tolerance_pct: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct FixConversionIssueArgs {
+    #[serde(rename = "rowId")]
+    row_id: i64,
+    fix: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct InvoiceLineItem {
-    trans_id: Option<i64>,
-    item_id: Option<i64>,
+struct PackIssue {
+    row_id: i64,
+    item_id: i64,
     item_name: String,
-    qty: Option<f64>,
-    unit_name: String,
-    price: Option<f64>,
-    ext_cost: Option<f64>,
+    vendor_id: i64,
+    vendor_name: String,
+    pack: String,
+    error: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct InvoiceDetailResponse {
-    invoice: InvoiceListItem,
-    freight: Option<f64>,
-    lines: Vec<InvoiceLineItem>,
+struct PackReport {
+    read: i64,
+    unreadable: i64,
+    issues: Vec<PackIssue>,
+}
+
+#[derive(Serialize)]
+struct UpdatePackArgs {
+    #[serde(rename = "rowId")]
+    row_id: i64,
+    pack: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct ExportCsvResponse {
-    path: String,
-    rows: usize,
+struct NutrientInfo {
+    key: String,
+    label: String,
+    unit: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct PatchResponse {
-    ok: bool,
-    message: String,
+struct FoodMatch {
+    fdc_id: i64,
+    description: String,
+    data_type: String,
+    score: f64,
 }
 
-#[derive(Serialize)]
-struct InvoiceQueryArgs {
-    query: String,
-    #[serde(rename = "vendorId")]
-    vendor_id: Option<i64>,
-    #[serde(rename = "dateFrom")]
-    date_from: String,
-    #[serde(rename = "dateTo")]
-    date_to: String,
-    limit: u32,
-    offset: u32,
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ItemNutritionProfile {
+    basis_qty: f64,
+    basis_unit_id: i64,
+    basis_unit_name: String,
+    values: Vec<Option<f64>>,
+    source: String,
+    source_label: String,
+    fdc_id: Option<i64>,
+    updated_at: String,
 }
 
-#[derive(Serialize)]
-struct InvoiceDetailArgs {
-    #[serde(rename = "invoiceId")]
-    invoice_id: i64,
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ItemNutritionResponse {
+    item_id: i64,
+    nutrients: Vec<NutrientInfo>,
+    profile: Option<ItemNutritionProfile>,
+    foods_imported: i64,
+    matches: Vec<FoodMatch>,
+    label_name: Option<String>,
 }
+
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct PingResponse {
-    ok: bool,
-    message: String,
+struct NutritionMatch {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    best: Option<FoodMatch>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct ItemSimple {
+struct NutritionAutoLink {
     item_id: i64,
-    name: String,
+    item_name: String,
+    category: String,
+    fdc_id: i64,
+    description: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct ItemSimpleResponse {
-    items: Vec<ItemSimple>,
+struct NutritionCoverage {
+    foods: i64,
+    recipe_items: i64,
+    with_nutrition: i64,
+    unmatched: Vec<NutritionMatch>,
+    auto_linked: Vec<NutritionAutoLink>,
 }
 
-#[derive(Serialize, Clone)]
-struct FoodCostLineInput {
-    #[serde(rename = "itemId")]
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeNutritionLine {
+    recp_item_id: i64,
     item_id: i64,
-    #[serde(rename = "unitId")]
-    unit_id: Option<i64>,
-    qty: f64,
+    item_name: String,
+    qty: Option<f64>,
+    unit_name: String,
+    sub_recipe_id: Option<i64>,
+    grams: Option<f64>,
+    values: Vec<Option<f64>>,
+    status: String,
+    source: String,
+    complete: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeNutrition {
+    recipe_id: i64,
+    nutrients: Vec<NutrientInfo>,
+    servings: Option<f64>,
+    lines: Vec<RecipeNutritionLine>,
+    totals: Vec<f64>,
+    per_serving: Option<Vec<f64>>,
+    total_grams: Option<f64>,
+    missing_lines: i64,
+    gaps: Vec<i64>,
 }
 
 #[derive(Serialize)]
-struct SaveSettingsArgs {
-    #[serde(rename = "companyName")]
-    company_name: String,
-    #[serde(rename = "serviceCategory")]
-    service_category: String,
-    #[serde(rename = "operationSize")]
-    operation_size: String,
+struct ItemNutritionArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    query: Option<String>,
 }
 
 #[derive(Serialize)]
-struct UploadLogoArgs {
-    #[serde(rename = "sourcePath")]
-    source_path: String,
+struct SaveItemNutritionArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "basisQty")]
+    basis_qty: f64,
+    #[serde(rename = "basisUnitId")]
+    basis_unit_id: i64,
+    values: Vec<Option<f64>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
-struct SettingsResponse {
-    company_name: String,
-    logo_path: String,
-    service_category: String,
-    operation_size: String,
+#[derive(Serialize)]
+struct LinkItemFoodArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "fdcId")]
+    fdc_id: i64,
 }
 
 #[derive(Serialize)]
-struct CalculateFoodCostArgs {
-    lines: Vec<FoodCostLineInput>,
+struct ImportFdcArgs {
+    path: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
-struct FoodCostLineOutput {
+#[derive(Serialize)]
+struct LinkNutritionMatchesArgs {
+    #[serde(rename = "minScore")]
+    min_score: f64,
+}
+
+#[derive(Serialize)]
+struct SaveItemLabelNameArgs {
+    #[serde(rename = "itemId")]
     item_id: i64,
-    item_name: String,
-    unit_id: Option<i64>,
-    unit_name: String,
-    qty: f64,
-    price: Option<f64>,
-    extended_cost: Option<f64>,
-    cost_status: String,
+    #[serde(rename = "labelName")]
+    label_name: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-struct FoodCostResponse {
-    lines: Vec<FoodCostLineOutput>,
-    total_cost: f64,
-    missing_costs: i64,
+struct NutritionLabelRow {
+    name: String,
+    amount: String,
+    daily_value: Option<String>,
+    indent: u8,
 }
 
-async fn invoke_cmd<T: for<'de> Deserialize<'de>>(cmd: &str, args: JsValue) -> Result<T, String> {
-    let promise = invoke(cmd, args);
-    match JsFuture::from(promise).await {
-        Ok(value) => serde_wasm_bindgen::from_value(value).map_err(|e| e.to_string()),
-        Err(err) => {
-            if let Some(s) = err.as_string() {
-                return Err(s);
-            }
-
-            if let Some(js_err) = err.dyn_ref::<js_sys::Error>() {
-                return Err(js_err.message().into());
-            }
-
-            let msg = Reflect::get(&err, &JsValue::from_str("message"))
-                .ok()
-                .and_then(|v| v.as_string());
-            if let Some(msg) = msg {
-                return Err(msg);
-            }
-
-            let msg = Reflect::get(&err, &JsValue::from_str("error"))
-                .ok()
-                .and_then(|v| v.as_string());
-            if let Some(msg) = msg {
-                return Err(msg);
-            }
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct NutritionLabel {
+    recipe_id: i64,
+    servings_per_container: String,
+    serving_size: String,
+    calories: String,
+    nutrients: Vec<NutritionLabelRow>,
+    vitamins: Vec<NutritionLabelRow>,
+    ingredients: String,
+    allergens: Vec<String>,
+    warnings: Vec<String>,
+}
 
-            let msg = Reflect::get(&err, &JsValue::from_str("cause"))
-                .ok()
-                .and_then(|v| v.as_string());
-            if let Some(msg) = msg {
-                return Err(msg);
-            }
+#[derive(Serialize)]
+struct NutritionLabelArgs {
+    #[serde(rename = "recipeId")]
+    recipe_id: i64,
+    #[serde(rename = "servingsPerContainer")]
+    servings_per_container: f64,
+}
 
-            if err.is_object() {
-                let obj = js_sys::Object::from(err.clone());
-                let keys = js_sys::Object::keys(&obj);
-                let mut parts = Vec::new();
-                for idx in 0..keys.length() {
-                    if let Some(key) = keys.get(idx).as_string() {
-                        if let Ok(value) = Reflect::get(&obj, &JsValue::from_str(&key)) {
-                            if let Some(v) = value.as_string() {
-                                parts.push(format!("{key}: {v}"));
-                            }
-                        }
-                    }
-                }
-                if !parts.is_empty() {
-                    return Err(parts.join(" | "));
-                }
-            }
+#[derive(Serialize)]
+struct ExportLabelArgs {
+    #[serde(rename = "recipeId")]
+    recipe_id: i64,
+    #[serde(rename = "servingsPerContainer")]
+    servings_per_container: f64,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
 
-            let msg = js_sys::JSON::stringify(&err)
-                .ok()
-                .and_then(|v| v.as_string())
-                .unwrap_or_else(|| "Command failed".to_string());
-            Err(msg)
-        }
+/// "12.3" for grams and most nutrients, whole numbers for calories and
+/// milligrams.
+fn format_nutrient(value: f64, unit: &str) -> String {
+    match unit {
+        "kcal" | "mg" => format!("{:.0}", value),
+        _ => format!("{:.1}", value),
     }
 }
 
-fn format_money(value: f64) -> String {
-    format!("${:.2}", value)
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ReviewSuggestionRow {
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: i64,
+    unit_id2: i64,
+    item_name: String,
+    category: String,
+    recipe_unit: String,
+    purch_unit: String,
+    qty1: f64,
+    qty2: f64,
+    hits: i64,
+    derived_from: String,
+    hops: i64,
+    path: String,
+    confidence: Option<f64>,
 }
 
-fn trigger_inventory_fetch(
-    query: String,
-    food_category: String,
-    order_by: String,
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_items: WriteSignal<Vec<InventoryItem>>,
-    set_total: WriteSignal<i64>,
-    set_filtered: WriteSignal<i64>,
-    set_loaded: WriteSignal<bool>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading inventory...".to_string());
-
-    spawn_local(async move {
-        let args = to_value(&InventoryQueryArgs {
-            query,
-            food_category,
-            order_by,
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
+impl ReviewSuggestionRow {
+    fn key(&self) -> String {
+        format!(
+            "{}|{}|{}|{}|{}",
+            self.item_id, self.vendor_id, self.unit_id1, self.unit_id2, self.derived_from
+        )
+    }
+}
 
-        match invoke_cmd::<InventoryQueryResponse>("search_inventory", args).await {
-            Ok(result) => {
-                set_items.set(result.items);
-                set_total.set(result.total);
-                set_filtered.set(result.filtered);
-                set_status.set("Inventory loaded".to_string());
-                set_loaded.set(true);
-            }
-            Err(err) => {
-                set_status.set(format!("Inventory load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ReviewSuggestionsResponse {
+    rows: Vec<ReviewSuggestionRow>,
+    total: i64,
 }
 
-fn trigger_recipe_fetch(
-    query: String,
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_list: WriteSignal<Vec<RecipeListItem>>,
-    set_total: WriteSignal<i64>,
-    set_filtered: WriteSignal<i64>,
-    set_loaded: WriteSignal<bool>,
-    auto_select: bool,
-    set_detail_loading: WriteSignal<bool>,
-    set_detail_status: WriteSignal<String>,
-    set_selected: WriteSignal<Option<RecipeDetailResponse>>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading recipes...".to_string());
+#[derive(Serialize)]
+struct ReviewConvSuggestionsArgs {
+    #[serde(rename = "minConfidence")]
+    min_confidence: Option<f64>,
+    #[serde(rename = "maxHops")]
+    max_hops: Option<i64>,
+    category: Option<String>,
+}
 
-    spawn_local(async move {
-        let args = to_value(&RecipeQueryArgs {
-            query,
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
+#[derive(Serialize, Clone)]
+struct SuggestionKeyArg {
+    item_id: i64,
+    vendor_id: i64,
+    unit_id1: i64,
+    unit_id2: i64,
+    derived_from: String,
+}
 
-        match invoke_cmd::<RecipeListResponse>("search_recipes", args).await {
-            Ok(result) => {
-                let first_id = result.recipes.first().map(|r| r.recipe_id);
-                set_list.set(result.recipes);
-                set_total.set(result.total);
-                set_filtered.set(result.filtered);
-                set_status.set("Recipes loaded".to_string());
-                set_loaded.set(true);
-                if auto_select {
-                    if let Some(recipe_id) = first_id {
-                        trigger_recipe_detail_fetch(
-                            recipe_id,
-                            set_detail_loading,
-                            set_detail_status,
-                            set_selected,
-                        );
-                    }
-                }
-            }
-            Err(err) => {
-                set_status.set(format!("Recipe load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize)]
+struct SuggestionKeysArgs {
+    suggestions: Vec<SuggestionKeyArg>,
 }
 
-fn trigger_recipe_detail_fetch(
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeCostChange {
     recipe_id: i64,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_selected: WriteSignal<Option<RecipeDetailResponse>>,
-) {
-    set_loading.set(true);
-    set_status.set("Loading recipe...".to_string());
+    recipe_name: String,
+    old_cost: f64,
+    new_cost: f64,
+    old_missing: i64,
+    new_missing: i64,
+    now_complete: bool,
+}
 
-    spawn_local(async move {
-        let args = to_value(&RecipeDetailArgs { recipe_id }).unwrap();
-        match invoke_cmd::<RecipeDetailResponse>("get_recipe_detail", args).await {
-            Ok(result) => {
-                set_selected.set(Some(result));
-                set_status.set("Recipe loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Recipe load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct AcceptanceImpact {
+    accepted: usize,
+    skipped: Vec<String>,
+    recipes: Vec<RecipeCostChange>,
+    complete_before: usize,
+    complete_after: usize,
+    newly_complete: usize,
 }
 
-fn trigger_inventory_detail_fetch(
-    item_id: i64,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_selected: WriteSignal<Option<InventoryDetailResponse>>,
-) {
-    set_loading.set(true);
-    set_status.set("Loading item detail...".to_string());
-    spawn_local(async move {
-        let args = to_value(&InventoryDetailArgs { item_id }).unwrap();
-        match invoke_cmd::<InventoryDetailResponse>("get_inventory_detail", args).await {
-            Ok(result) => {
-                set_selected.set(Some(result));
-                set_status.set("Item detail loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Item detail failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceListItem {
+    invoice_id: i64,
+    invoice_no: String,
+    vendor_id: Option<i64>,
+    vendor_name: String,
+    invoice_date: String,
+    total: Option<f64>,
 }
 
-fn trigger_vendor_fetch(
-    query: String,
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_list: WriteSignal<Vec<VendorListItem>>,
-    set_total: WriteSignal<i64>,
-    set_filtered: WriteSignal<i64>,
-    set_loaded: WriteSignal<bool>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading vendors...".to_string());
-    spawn_local(async move {
-        let args = to_value(&VendorQueryArgs {
-            query,
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<VendorListResponse>("search_vendors", args).await {
-            Ok(result) => {
-                set_list.set(result.vendors);
-                set_total.set(result.total);
-                set_filtered.set(result.filtered);
-                set_status.set("Vendors loaded".to_string());
-                set_loaded.set(true);
-            }
-            Err(err) => {
-                set_status.set(format!("Vendor load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceListResponse {
+    invoices: Vec<InvoiceListItem>,
+    total: i64,
+    filtered: i64,
 }
 
-fn trigger_vendor_detail_fetch(
-    vendor_id: i64,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_selected: WriteSignal<Option<VendorDetailResponse>>,
-) {
-    set_loading.set(true);
-    set_status.set("Loading vendor...".to_string());
-    spawn_local(async move {
-        let args = to_value(&VendorDetailArgs { vendor_id }).unwrap();
-        match invoke_cmd::<VendorDetailResponse>("get_vendor_detail", args).await {
-            Ok(result) => {
-                set_selected.set(Some(result));
-                set_status.set("Vendor loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Vendor load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceLineItem {
+    trans_id: Option<i64>,
+    item_id: Option<i64>,
+    item_name: String,
+    qty: Option<f64>,
+    unit_name: String,
+    price: Option<f64>,
+    ext_cost: Option<f64>,
+    #[serde(default)]
+    alloc_amount: Option<f64>,
+    #[serde(default)]
+    landed_price: Option<f64>,
 }
 
-fn trigger_vendor_options_fetch(set_options: WriteSignal<Vec<VendorSimple>>) {
-    spawn_local(async move {
-        let args = to_value(&PingArgs {}).unwrap();
-        if let Ok(result) = invoke_cmd::<VendorSimpleResponse>("list_vendors_simple", args).await {
-            set_options.set(result.vendors);
-        }
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceCharge {
+    charge_id: i64,
+    kind: String,
+    description: String,
+    amount: f64,
+    allocation: String,
 }
 
-fn trigger_vendor_all_options_fetch(set_options: WriteSignal<Vec<VendorSimple>>) {
-    spawn_local(async move {
-        let args = to_value(&PingArgs {}).unwrap();
-        if let Ok(result) = invoke_cmd::<VendorSimpleResponse>("list_vendors_all", args).await {
-            set_options.set(result.vendors);
-        }
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceDetailResponse {
+    invoice: InvoiceListItem,
+    freight: Option<f64>,
+    lines: Vec<InvoiceLineItem>,
+    #[serde(default)]
+    charges: Vec<InvoiceCharge>,
+    #[serde(default)]
+    credits: Vec<CreditMemo>,
+    #[serde(default)]
+    recon_status: Option<String>,
 }
 
-fn trigger_unit_options_fetch(set_options: WriteSignal<Vec<UnitSimple>>) {
-    spawn_local(async move {
-        let args = to_value(&PingArgs {}).unwrap();
-        if let Ok(result) = invoke_cmd::<UnitSimpleResponse>("list_units_simple", args).await {
-            set_options.set(result.units);
-        }
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CreditMemo {
+    credit_id: i64,
+    memo_no: String,
+    memo_date: String,
+    reason: String,
+    amount: f64,
+    trans_id: Option<i64>,
+    item_name: String,
+    qty: Option<f64>,
+    unit_name: String,
+    note: String,
 }
 
-fn trigger_conversion_overview_fetch(set_overview: WriteSignal<ConversionOverview>) {
-    spawn_local(async move {
-        let args = to_value(&PingArgs {}).unwrap();
-        if let Ok(result) = invoke_cmd::<ConversionOverview>("get_conversion_overview", args).await
-        {
-            set_overview.set(result);
-        }
-    });
+#[derive(Serialize)]
+struct CreditMemoInput {
+    invoice_id: i64,
+    memo_no: Option<String>,
+    memo_date: Option<String>,
+    reason: String,
+    amount: Option<f64>,
+    trans_id: Option<i64>,
+    qty: Option<f64>,
+    note: Option<String>,
 }
 
-fn trigger_conversion_suggestions_fetch(
-    table: String,
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_rows: WriteSignal<Vec<ConversionSuggestionRow>>,
-    set_total: WriteSignal<i64>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading conversion suggestions...".to_string());
-    spawn_local(async move {
-        let args = to_value(&ConversionListArgs {
-            table,
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<ConversionSuggestionResponse>("list_conv_suggestions", args).await {
-            Ok(result) => {
-                set_rows.set(result.rows);
-                set_total.set(result.total);
-                set_status.set("Suggestions loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Suggestions load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize)]
+struct SaveCreditMemoArgs {
+    credit: CreditMemoInput,
 }
 
-fn trigger_conversion_todo_fetch(
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_rows: WriteSignal<Vec<ConversionTodoRow>>,
-    set_total: WriteSignal<i64>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading conversion todo...".to_string());
-    spawn_local(async move {
-        let args = to_value(&ConversionPageArgs {
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<ConversionTodoResponse>("list_conv_todo", args).await {
-            Ok(result) => {
-                set_rows.set(result.rows);
-                set_total.set(result.total);
-                set_status.set("Todo loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Todo load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize)]
+struct CreditIdArgs {
+    #[serde(rename = "creditId")]
+    credit_id: i64,
 }
 
-fn trigger_missing_edges_fetch(
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_rows: WriteSignal<Vec<MissingEdgeRow>>,
-    set_total: WriteSignal<i64>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading missing edges...".to_string());
-    spawn_local(async move {
-        let args = to_value(&ConversionPageArgs {
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<MissingEdgeResponse>("list_missing_edges", args).await {
-            Ok(result) => {
-                set_rows.set(result.rows);
-                set_total.set(result.total);
-                set_status.set("Missing edges loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Missing edges load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceDiscrepancy {
+    trans_id: Option<i64>,
+    kind: String,
+    expected: Option<f64>,
+    actual: Option<f64>,
+    message: String,
 }
 
-fn trigger_missing_purch_fetch(
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_rows: WriteSignal<Vec<MissingPurchRow>>,
-    set_total: WriteSignal<i64>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading missing purchase units...".to_string());
-    spawn_local(async move {
-        let args = to_value(&ConversionPageArgs {
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<MissingPurchResponse>("list_missing_purch_unit", args).await {
-            Ok(result) => {
-                set_rows.set(result.rows);
-                set_total.set(result.total);
-                set_status.set("Missing purchase units loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Missing purchase units load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InvoiceRecon {
+    invoice_id: i64,
+    invoice_no: String,
+    invoice_date: String,
+    vendor_name: String,
+    status: String,
+    lines_total: f64,
+    charges_total: f64,
+    credits_total: f64,
+    header_total: Option<f64>,
+    difference: Option<f64>,
+    checked_at: String,
+    note: String,
+    discrepancies: Vec<InvoiceDiscrepancy>,
 }
 
-fn trigger_missing_data_fetch(
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_rows: WriteSignal<Vec<MissingDataRow>>,
-    set_total: WriteSignal<i64>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading missing data report...".to_string());
-    spawn_local(async move {
-        let args = to_value(&ConversionPageArgs {
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<MissingDataResponse>("list_missing_data_report", args).await {
-            Ok(result) => {
-                set_rows.set(result.rows);
-                set_total.set(result.total);
-                set_status.set("Missing data loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Missing data load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ReconciliationResponse {
+    balanced: i64,
+    unbalanced: i64,
+    reviewed: i64,
+    matching: i64,
+    invoices: Vec<InvoiceRecon>,
 }
 
-fn trigger_invoice_fetch(
-    query: String,
-    vendor_id: Option<i64>,
-    date_from: String,
-    date_to: String,
-    page: usize,
-    limit: usize,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_list: WriteSignal<Vec<InvoiceListItem>>,
-    set_total: WriteSignal<i64>,
-    set_filtered: WriteSignal<i64>,
-    set_loaded: WriteSignal<bool>,
-) {
-    let offset = page * limit;
-    set_loading.set(true);
-    set_status.set("Loading invoices...".to_string());
-    spawn_local(async move {
-        let args = to_value(&InvoiceQueryArgs {
-            query,
-            vendor_id,
-            date_from,
-            date_to,
-            limit: limit as u32,
-            offset: offset as u32,
-        })
-        .unwrap();
-        match invoke_cmd::<InvoiceListResponse>("list_invoices", args).await {
-            Ok(result) => {
-                set_list.set(result.invoices);
-                set_total.set(result.total);
-                set_filtered.set(result.filtered);
-                set_status.set("Invoices loaded".to_string());
-                set_loaded.set(true);
-            }
-            Err(err) => {
-                set_status.set(format!("Invoice load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+#[derive(Serialize)]
+struct ListReconciliationArgs {
+    status: Option<String>,
 }
 
-fn trigger_invoice_detail_fetch(
+#[derive(Serialize)]
+struct SetInvoiceReviewedArgs {
+    #[serde(rename = "invoiceId")]
     invoice_id: i64,
-    set_loading: WriteSignal<bool>,
-    set_status: WriteSignal<String>,
-    set_selected: WriteSignal<Option<InvoiceDetailResponse>>,
-) {
-    set_loading.set(true);
-    set_status.set("Loading invoice...".to_string());
-    spawn_local(async move {
-        let args = to_value(&InvoiceDetailArgs { invoice_id }).unwrap();
-        match invoke_cmd::<InvoiceDetailResponse>("get_invoice_detail", args).await {
-            Ok(result) => {
-                set_selected.set(Some(result));
-                set_status.set("Invoice loaded".to_string());
-            }
-            Err(err) => {
-                set_status.set(format!("Invoice load failed: {err}"));
-            }
-        }
-        set_loading.set(false);
-    });
+    reviewed: bool,
+    note: Option<String>,
 }
-/// Turns raw SQLite column names into human-friendly display names.
-fn humanize_column(raw: &str) -> String {
-    match raw {
-        // ── common ids / names ──
-        "item_id" => "Item ID".into(),
-        "unit_id" => "Unit ID".into(),
-        "vendor_id" => "Vendor ID".into(),
-        "recipe_id" => "Recipe ID".into(),
-        "recipe_group_id" => "Recipe Group".into(),
-        "invoice_id" => "Invoice ID".into(),
-        "trans_id" => "Transaction ID".into(),
-        "recp_item_id" => "Recipe Item ID".into(),
-        "recp_inv_id" => "Recipe Inv. ID".into(),
-        "purch_unit_id" => "Purchase Unit ID".into(),
-        "recipe_unit_id" => "Recipe Unit ID".into(),
-
-        // ── units table ──
-        "sing" => "Singular".into(),
-        "plur" => "Plural".into(),
-        "unit_type" => "Unit Type".into(),
-        "is_whole_unit" => "Whole Unit".into(),
-        "unit_kind" => "Kind".into(),
 
-        // ── items ──
-        "name" => "Name".into(),
-        "item_name" => "Item Name".into(),
-        "recipe_name" => "Recipe Name".into(),
-        "status" => "Status".into(),
-        "raw_len" => "Raw Length".into(),
+#[derive(Serialize)]
+struct InvoiceChargeInput {
+    charge_id: Option<i64>,
+    invoice_id: i64,
+    kind: String,
+    description: Option<String>,
+    amount: f64,
+    allocation: Option<String>,
+}
 
-        // ── convunit ──
-        "unit_id1" => "From Unit".into(),
-        "unit_id2" => "To Unit".into(),
-        "qty1" => "Qty From".into(),
-        "qty2" => "Qty To".into(),
-        "qty" => "Quantity".into(),
-        "is_calculated" => "Calculated".into(),
+#[derive(Serialize)]
+struct SaveInvoiceChargeArgs {
+    charge: InvoiceChargeInput,
+}
 
-        // ── inv_units / inv_prices ──
-        "is_default" => "Default".into(),
-        "price" => "Price".into(),
-        "pack" => "Pack".into(),
+#[derive(Serialize)]
+struct ChargeIdArgs {
+    #[serde(rename = "chargeId")]
+    charge_id: i64,
+}
 
-        // ── conv_suggestions / conv_todo ──
-        "recipe_unit" => "Recipe Unit".into(),
-        "purch_unit" => "Purchase Unit".into(),
-        "hits" => "Hits".into(),
-        "derived_from" => "Derived From".into(),
-        "hops" => "Hops".into(),
-        "path" => "Path".into(),
-        "needed" => "Needed".into(),
+#[derive(Serialize)]
+struct SetVendorTaxArgs {
+    #[serde(rename = "vendorId")]
+    vendor_id: i64,
+    #[serde(rename = "pricesIncludeTax")]
+    prices_include_tax: bool,
+    #[serde(rename = "taxRate")]
+    tax_rate: Option<f64>,
+}
 
-        // ── missing_edges ──
-        "usage_count" => "Usage Count".into(),
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ExportCsvResponse {
+    path: String,
+    rows: usize,
+}
 
-        // ── missing_data_report ──
-        "missing_a" => "Missing Costs".into(),
-        "missing_b" => "Missing Conversions".into(),
-        "missing_c" => "Missing Units".into(),
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PatchResponse {
+    ok: bool,
+    message: String,
+}
 
-        // ── invoices ──
-        "invoice_date" | "trans_date" => "Date".into(),
-        "invoice_no" => "Invoice #".into(),
-        "freight" => "Freight".into(),
-        "total" => "Total".into(),
-        "ext_cost" => "Ext. Cost".into(),
+#[derive(Serialize)]
+struct InvoiceQueryArgs {
+    query: String,
+    #[serde(rename = "vendorId")]
+    vendor_id: Option<i64>,
+    #[serde(rename = "dateFrom")]
+    date_from: String,
+    #[serde(rename = "dateTo")]
+    date_to: String,
+    limit: u32,
+    offset: u32,
+}
 
-        // ── generic colN placeholders ──
-        other => {
-            if let Some(rest) = other.strip_prefix("col") {
-                if rest.chars().all(|c| c.is_ascii_digit()) {
-                    return format!("Column {rest}");
-                }
-            }
-            // Fallback: replace underscores and title-case
-            other
-                .split('_')
-                .map(|word| {
-                    let mut chars = word.chars();
-                    match chars.next() {
-                        None => String::new(),
-                        Some(c) => {
-                            let mut s = c.to_uppercase().to_string();
-                            s.extend(chars);
-                            s
-                        }
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(" ")
-        }
-    }
+#[derive(Serialize)]
+struct InvoiceDetailArgs {
+    #[serde(rename = "invoiceId")]
+    invoice_id: i64,
+}
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PingResponse {
+    ok: bool,
+    message: String,
 }
 
-#[component]
-pub fn App() -> impl IntoView {
-    let (base_path, set_base_path) = signal("~/exports".to_string());
-    let (import_mode, set_import_mode) = signal("exports".to_string());
-    let (summary, set_summary) = signal(ImportSummary::default());
-    let (has_summary, set_has_summary) = signal(false);
-    let (status, set_status) = signal(String::new());
-    let (busy, set_busy) = signal(false);
-    let (active_panel, set_active_panel) = signal("imports".to_string());
-    let (show_warnings, set_show_warnings) = signal(false);
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ItemSimple {
+    item_id: i64,
+    name: String,
+}
 
-    let browse_limit: u32 = 100;
-    let (browse_table_name, set_browse_table_name) = signal(String::new());
-    let (browse_data, set_browse_data) = signal(BrowseTableResponse::default());
-    let (browse_loading, set_browse_loading) = signal(false);
-    let (browse_page, set_browse_page) = signal(0u32);
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ItemSimpleResponse {
+    items: Vec<ItemSimple>,
+}
 
-    let inventory_limit: usize = 50;
-    let (inventory_query, set_inventory_query) = signal(String::new());
-    let (inventory_food_category, set_inventory_food_category) = signal(String::new());
-    let (inventory_order_by, set_inventory_order_by) = signal(String::new());
-    let (inventory_items, set_inventory_items) = signal(Vec::<InventoryItem>::new());
-    let (inventory_total, set_inventory_total) = signal(0i64);
-    let (inventory_filtered, set_inventory_filtered) = signal(0i64);
-    let (inventory_page, set_inventory_page) = signal(0usize);
-    let (inventory_status, set_inventory_status) = signal(String::new());
-    let (inventory_loading, set_inventory_loading) = signal(false);
-    let (_inventory_loaded, set_inventory_loaded) = signal(false);
-    let (inventory_selected, set_inventory_selected) =
-        signal(Option::<InventoryDetailResponse>::None);
-    let (inventory_detail_status, set_inventory_detail_status) = signal(String::new());
-    let (inventory_detail_loading, set_inventory_detail_loading) = signal(false);
+#[derive(Serialize, Clone)]
+struct FoodCostLineInput {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "unitId")]
+    unit_id: Option<i64>,
+    qty: f64,
+}
 
-    let recipe_limit: usize = 25;
-    let (recipe_query, set_recipe_query) = signal(String::new());
-    let (recipe_list, set_recipe_list) = signal(Vec::<RecipeListItem>::new());
-    let (recipe_total, set_recipe_total) = signal(0i64);
-    let (recipe_filtered, set_recipe_filtered) = signal(0i64);
-    let (recipe_page, set_recipe_page) = signal(0usize);
-    let (recipe_status, set_recipe_status) = signal(String::new());
-    let (recipe_loading, set_recipe_loading) = signal(false);
-    let (recipe_loaded, set_recipe_loaded) = signal(false);
-    let (selected_recipe, set_selected_recipe) = signal(Option::<RecipeDetailResponse>::None);
-    let (recipe_detail_status, set_recipe_detail_status) = signal(String::new());
-    let (recipe_detail_loading, set_recipe_detail_loading) = signal(false);
-    let (tauri_status, set_tauri_status) = signal(String::new());
-    let (ping_status, set_ping_status) = signal(String::new());
+#[derive(Serialize)]
+struct SaveSettingsArgs {
+    #[serde(rename = "companyName")]
+    company_name: String,
+    #[serde(rename = "serviceCategory")]
+    service_category: String,
+    #[serde(rename = "operationSize")]
+    operation_size: String,
+    #[serde(rename = "targetFoodCostPct")]
+    target_food_cost_pct: Option<String>,
+    #[serde(rename = "priceRounding")]
+    price_rounding: Option<String>,
+    purchasing: Option<PurchasingSettings>,
+}
 
-    let vendor_limit: usize = 50;
-    let (vendor_query, set_vendor_query) = signal(String::new());
-    let (vendor_list, set_vendor_list) = signal(Vec::<VendorListItem>::new());
-    let (vendor_total, set_vendor_total) = signal(0i64);
-    let (vendor_filtered, set_vendor_filtered) = signal(0i64);
-    let (vendor_page, set_vendor_page) = signal(0usize);
-    let (vendor_status, set_vendor_status) = signal(String::new());
-    let (vendor_loading, set_vendor_loading) = signal(false);
-    let (vendor_loaded, set_vendor_loaded) = signal(false);
-    let (selected_vendor, set_selected_vendor) = signal(Option::<VendorDetailResponse>::None);
-    let (vendor_detail_status, set_vendor_detail_status) = signal(String::new());
-    let (vendor_detail_loading, set_vendor_detail_loading) = signal(false);
-    let (vendor_merge_options, set_vendor_merge_options) = signal(Vec::<VendorSimple>::new());
-    let (vendor_merge_source_id, set_vendor_merge_source_id) = signal(String::new());
-    let (vendor_merge_target_id, set_vendor_merge_target_id) = signal(String::new());
-    let (vendor_merge_status, set_vendor_merge_status) = signal(String::new());
+#[derive(Serialize)]
+struct PurchasingSettings {
+    price_alert_pct: String,
+    landed_alloc_method: String,
+    landed_include_tax: bool,
+    costing_use_landed: bool,
+}
 
-    let conversion_limit: usize = 50;
-    let (conversion_tab, set_conversion_tab) = signal("suggestions".to_string());
-    let (conversion_page, set_conversion_page) = signal(0usize);
-    let (conversion_status, set_conversion_status) = signal(String::new());
-    let (conversion_loading, set_conversion_loading) = signal(false);
-    let (conversion_overview, set_conversion_overview) = signal(ConversionOverview::default());
-    let (conversion_suggestions, set_conversion_suggestions) =
-        signal(Vec::<ConversionSuggestionRow>::new());
-    let (conversion_suggestions_total, set_conversion_suggestions_total) = signal(0i64);
-    let (conversion_suggestions_safe, set_conversion_suggestions_safe) =
-        signal(Vec::<ConversionSuggestionRow>::new());
-    let (conversion_suggestions_safe_total, set_conversion_suggestions_safe_total) = signal(0i64);
-    let (conversion_todo, set_conversion_todo) = signal(Vec::<ConversionTodoRow>::new());
-    let (conversion_todo_total, set_conversion_todo_total) = signal(0i64);
-    let (conversion_missing_edges, set_conversion_missing_edges) =
-        signal(Vec::<MissingEdgeRow>::new());
-    let (conversion_missing_edges_total, set_conversion_missing_edges_total) = signal(0i64);
+#[derive(Serialize)]
+struct UploadLogoArgs {
+    #[serde(rename = "sourcePath")]
+    source_path: String,
+}
 
-    let report_limit: usize = 50;
-    let (report_page, _set_report_page) = signal(0usize);
-    let (report_status, set_report_status) = signal(String::new());
-    let (_report_loading, set_report_loading) = signal(false);
-    let (missing_data_rows, set_missing_data_rows) = signal(Vec::<MissingDataRow>::new());
-    let (missing_data_total, set_missing_data_total) = signal(0i64);
-    let (missing_purch_rows, set_missing_purch_rows) = signal(Vec::<MissingPurchRow>::new());
-    let (missing_purch_total, set_missing_purch_total) = signal(0i64);
-    let (report_purch_item_id, set_report_purch_item_id) = signal(String::new());
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SettingsResponse {
+    company_name: String,
+    logo_path: String,
+    service_category: String,
+    operation_size: String,
+    #[serde(default)]
+    target_food_cost_pct: String,
+    #[serde(default)]
+    price_rounding: String,
+    #[serde(default)]
+    price_alert_pct: String,
+    #[serde(default)]
+    landed_alloc_method: String,
+    #[serde(default)]
+    landed_include_tax: bool,
+    #[serde(default)]
+    costing_use_landed: bool,
+}
 
-    let invoice_limit: usize = 50;
-    let (invoice_query, set_invoice_query) = signal(String::new());
-    let (invoice_list, set_invoice_list) = signal(Vec::<InvoiceListItem>::new());
-    let (invoice_total, set_invoice_total) = signal(0i64);
-    let (invoice_filtered, set_invoice_filtered) = signal(0i64);
-    let (invoice_page, set_invoice_page) = signal(0usize);
-    let (invoice_status, set_invoice_status) = signal(String::new());
-    let (invoice_loading, set_invoice_loading) = signal(false);
-    let (invoice_loaded, set_invoice_loaded) = signal(false);
-    let (selected_invoice, set_selected_invoice) = signal(Option::<InvoiceDetailResponse>::None);
-    let (invoice_detail_status, set_invoice_detail_status) = signal(String::new());
-    let (invoice_detail_loading, set_invoice_detail_loading) = signal(false);
-    let (invoice_vendor_filter, set_invoice_vendor_filter) = signal(String::new());
-    let (invoice_vendor_options, set_invoice_vendor_options) = signal(Vec::<VendorSimple>::new());
-    let (editor_vendor_options, set_editor_vendor_options) = signal(Vec::<VendorSimple>::new());
-    let (unit_options, set_unit_options) = signal(Vec::<UnitSimple>::new());
-    let (invoice_date_from, set_invoice_date_from) = signal(String::new());
-    let (invoice_date_to, set_invoice_date_to) = signal(String::new());
-    let (invoice_export_status, set_invoice_export_status) = signal(String::new());
-    let (invoice_export_path, set_invoice_export_path) = signal(String::new());
-    let (purch_unit_selected_id, set_purch_unit_selected_id) = signal(String::new());
-    let (purch_unit_set_default, set_purch_unit_set_default) = signal(true);
-    let (purch_unit_status, set_purch_unit_status) = signal(String::new());
-    let (manual_price_vendor_id, set_manual_price_vendor_id) = signal(String::new());
-    let (manual_price_value, set_manual_price_value) = signal(String::new());
-    let (manual_price_pack, set_manual_price_pack) = signal(String::new());
-    let (manual_price_status, set_manual_price_status) = signal(String::new());
+#[derive(Serialize)]
+struct CalculateFoodCostArgs {
+    lines: Vec<FoodCostLineInput>,
+}
 
-    let start_import = move || {
-        let path = base_path.get();
-        let mode = import_mode.get();
-        set_busy.set(true);
-        set_status.set(if mode == "mdf" {
-            "Importing from MDF/LDF...".to_string()
-        } else {
-            "Importing exports...".to_string()
-        });
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct FoodCostLineOutput {
+    item_id: i64,
+    item_name: String,
+    unit_id: Option<i64>,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    #[serde(default)]
+    yield_pct: Option<f64>,
+    #[serde(default)]
+    ap_cost: Option<f64>,
+    extended_cost: Option<f64>,
+    cost_status: String,
+}
 
-        spawn_local(async move {
-            let result = if mode == "mdf" {
-                let args = to_value(&ImportMdfArgs { mdf_path: path }).unwrap();
-                invoke_cmd::<ImportSummary>("import_from_mdf", args).await
-            } else {
-                let args = to_value(&ImportArgs { base_path: path }).unwrap();
-                invoke_cmd::<ImportSummary>("import_exports", args).await
-            };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct FoodCostResponse {
+    lines: Vec<FoodCostLineOutput>,
+    total_cost: f64,
+    missing_costs: i64,
+}
 
-            match result {
-                Ok(result) => {
-                    set_summary.set(result);
-                    set_has_summary.set(true);
-                    set_status.set("Import complete".to_string());
-                    set_inventory_loaded.set(false);
-                    set_recipe_loaded.set(false);
-                    set_selected_recipe.set(None);
-                    set_vendor_loaded.set(false);
-                    set_invoice_loaded.set(false);
-                }
-                Err(err) => {
-                    set_status.set(format!("Import failed: {err}"));
-                }
+async fn invoke_cmd<T: for<'de> Deserialize<'de>>(cmd: &str, args: JsValue) -> Result<T, String> {
+    let promise = invoke(cmd, args);
+    match JsFuture::from(promise).await {
+        Ok(value) => serde_wasm_bindgen::from_value(value).map_err(|e| e.to_string()),
+        Err(err) => {
+            if let Some(s) = err.as_string() {
+                return Err(s);
             }
-            set_busy.set(false);
-        });
-    };
 
-    Effect::new(move |_| {
-        let status = match window() {
-            Some(win) => {
-                if let Ok(tauri_obj) = Reflect::get(&win, &JsValue::from_str("__TAURI__")) {
-                    if tauri_obj.is_undefined() || tauri_obj.is_null() {
-                        "Tauri API missing".to_string()
-                    } else if let Ok(core) = Reflect::get(&tauri_obj, &JsValue::from_str("core")) {
-                        if core.is_undefined() || core.is_null() {
-                            "Tauri core missing".to_string()
-                        } else if let Ok(invoke_fn) =
-                            Reflect::get(&core, &JsValue::from_str("invoke"))
-                        {
-                            if invoke_fn.is_function() {
-                                "Tauri API ready".to_string()
-                            } else {
-                                "Tauri invoke missing".to_string()
-                            }
-                        } else {
-                            "Tauri core missing".to_string()
-                        }
-                    } else {
-                        "Tauri API missing".to_string()
-                    }
-                } else {
-                    "Tauri API missing".to_string()
-                }
+            if let Some(js_err) = err.dyn_ref::<js_sys::Error>() {
+                return Err(js_err.message().into());
             }
-            None => "Window missing".to_string(),
-        };
-        set_tauri_status.set(status);
-    });
 
-    let ping_backend = move || {
-        set_ping_status.set("Pinging backend...".to_string());
-        spawn_local(async move {
-            let args = to_value(&PingArgs {}).unwrap();
-            match invoke_cmd::<PingResponse>("ping_backend", args).await {
-                Ok(resp) => {
-                    set_ping_status.set(format!("Ping: {} ({})", resp.message, resp.ok));
-                }
-                Err(err) => {
-                    set_ping_status.set(format!("Ping failed: {err}"));
-                }
+            let msg = Reflect::get(&err, &JsValue::from_str("message"))
+                .ok()
+                .and_then(|v| v.as_string());
+            if let Some(msg) = msg {
+                return Err(msg);
             }
-        });
-    };
-
-    let open_path = move |path: String| {
-        spawn_local(async move {
-            let args = to_value(&OpenPathArgs { path }).unwrap();
-            let _ = invoke_cmd::<PatchResponse>("open_path", args).await;
-        });
-    };
 
-    let fetch_browse_table = move |table: String, page: u32| {
-        set_browse_loading.set(true);
-        spawn_local(async move {
-            let args = to_value(&BrowseTableArgs {
-                table_name: table,
-                limit: browse_limit,
-                offset: page * browse_limit,
-            })
-            .unwrap();
-            match invoke_cmd::<BrowseTableResponse>("browse_table", args).await {
-                Ok(resp) => {
-                    set_browse_data.set(resp);
+            let msg = Reflect::get(&err, &JsValue::from_str("error"))
+                .ok()
+                .and_then(|v| v.as_string());
+            if let Some(msg) = msg {
+                return Err(msg);
+            }
+
+            let msg = Reflect::get(&err, &JsValue::from_str("cause"))
+                .ok()
+                .and_then(|v| v.as_string());
+            if let Some(msg) = msg {
+                return Err(msg);
+            }
+
+            if err.is_object() {
+                let obj = js_sys::Object::from(err.clone());
+                let keys = js_sys::Object::keys(&obj);
+                let mut parts = Vec::new();
+                for idx in 0..keys.length() {
+                    if let Some(key) = keys.get(idx).as_string() {
+                        if let Ok(value) = Reflect::get(&obj, &JsValue::from_str(&key)) {
+                            if let Some(v) = value.as_string() {
+                                parts.push(format!("{key}: {v}"));
+                            }
+                        }
+                    }
                 }
-                Err(_) => {
-                    set_browse_data.set(BrowseTableResponse::default());
+                if !parts.is_empty() {
+                    return Err(parts.join(" | "));
                 }
             }
-            set_browse_loading.set(false);
-        });
-    };
 
-    let toggle_card = move |table: &str| {
-        let table = table.to_string();
-        if browse_table_name.get() == table {
-            set_browse_table_name.set(String::new());
-            set_browse_data.set(BrowseTableResponse::default());
-            set_browse_page.set(0);
-        } else {
-            set_browse_table_name.set(table.clone());
-            set_browse_page.set(0);
-            fetch_browse_table(table, 0);
+            let msg = js_sys::JSON::stringify(&err)
+                .ok()
+                .and_then(|v| v.as_string())
+                .unwrap_or_else(|| "Command failed".to_string());
+            Err(msg)
         }
-    };
+    }
+}
 
-    let show_imports = move || {
-        set_active_panel.set("imports".to_string());
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeSimple {
+    recipe_id: i64,
+    name: String,
+}
 
-    let show_inventory = move || {
-        set_active_panel.set("inventory".to_string());
-        set_inventory_food_category.set(String::new());
-        if editor_vendor_options.get().is_empty() {
-            trigger_vendor_options_fetch(set_editor_vendor_options);
-        }
-        if unit_options.get().is_empty() {
-            trigger_unit_options_fetch(set_unit_options);
-        }
-        // Always re-fetch when switching modes
-        set_inventory_loaded.set(false);
-        set_inventory_page.set(0);
-        trigger_inventory_fetch(
-            inventory_query.get(),
-            String::new(),
-            inventory_order_by.get(),
-            0,
-            inventory_limit,
-            set_inventory_loading,
-            set_inventory_status,
-            set_inventory_items,
-            set_inventory_total,
-            set_inventory_filtered,
-            set_inventory_loaded,
-        );
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeSimpleResponse {
+    recipes: Vec<RecipeSimple>,
+}
 
-    let show_recipes = move || {
-        set_active_panel.set("recipes".to_string());
-        if !recipe_loaded.get() {
-            trigger_recipe_fetch(
-                recipe_query.get(),
-                recipe_page.get(),
-                recipe_limit,
-                set_recipe_loading,
-                set_recipe_status,
-                set_recipe_list,
-                set_recipe_total,
-                set_recipe_filtered,
-                set_recipe_loaded,
-                selected_recipe.get().is_none(),
-                set_recipe_detail_loading,
-                set_recipe_detail_status,
-                set_selected_recipe,
-            );
-        }
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PosProfile {
+    profile_id: i64,
+    name: String,
+    item_col: String,
+    qty_col: String,
+    net_sales_col: String,
+    date_col: String,
+    category_col: String,
+    builtin: bool,
+}
 
-    let show_vendors = move || {
-        set_active_panel.set("vendors".to_string());
-        if vendor_merge_options.get().is_empty() {
-            trigger_vendor_all_options_fetch(set_vendor_merge_options);
-        }
-        if !vendor_loaded.get() {
-            trigger_vendor_fetch(
-                vendor_query.get(),
-                vendor_page.get(),
-                vendor_limit,
-                set_vendor_loading,
-                set_vendor_status,
-                set_vendor_list,
-                set_vendor_total,
-                set_vendor_filtered,
-                set_vendor_loaded,
-            );
-        }
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PosProfileListResponse {
+    profiles: Vec<PosProfile>,
+}
 
-    let show_conversions = move || {
-        set_active_panel.set("conversions".to_string());
-        trigger_conversion_overview_fetch(set_conversion_overview);
-        let tab = conversion_tab.get();
-        let page = conversion_page.get();
-        match tab.as_str() {
-            "suggestions" => trigger_conversion_suggestions_fetch(
-                "conv_suggestions".to_string(),
-                page,
-                conversion_limit,
-                set_conversion_loading,
-                set_conversion_status,
-                set_conversion_suggestions,
-                set_conversion_suggestions_total,
-            ),
-            "safe" => trigger_conversion_suggestions_fetch(
-                "conv_suggestions_safe".to_string(),
-                page,
-                conversion_limit,
-                set_conversion_loading,
-                set_conversion_status,
-                set_conversion_suggestions_safe,
-                set_conversion_suggestions_safe_total,
-            ),
-            "todo" => trigger_conversion_todo_fetch(
-                page,
-                conversion_limit,
-                set_conversion_loading,
-                set_conversion_status,
-                set_conversion_todo,
-                set_conversion_todo_total,
-            ),
-            "missing" => trigger_missing_edges_fetch(
-                page,
-                conversion_limit,
-                set_conversion_loading,
-                set_conversion_status,
-                set_conversion_missing_edges,
-                set_conversion_missing_edges_total,
-            ),
-            _ => {}
-        }
-    };
+#[derive(Serialize)]
+struct PosProfileInput {
+    profile_id: Option<i64>,
+    name: String,
+    item_col: String,
+    qty_col: String,
+    net_sales_col: String,
+    date_col: String,
+    category_col: String,
+}
 
-    let show_reports = move || {
-        set_active_panel.set("reports".to_string());
-        if unit_options.get().is_empty() {
-            trigger_unit_options_fetch(set_unit_options);
-        }
-        trigger_missing_data_fetch(
-            report_page.get(),
-            report_limit,
-            set_report_loading,
-            set_report_status,
-            set_missing_data_rows,
-            set_missing_data_total,
-        );
-        trigger_missing_purch_fetch(
-            report_page.get(),
-            report_limit,
-            set_report_loading,
-            set_report_status,
-            set_missing_purch_rows,
-            set_missing_purch_total,
-        );
-    };
+#[derive(Serialize)]
+struct SavePosProfileArgs {
+    profile: PosProfileInput,
+}
 
-    let show_purchasing = move || {
-        set_active_panel.set("purchasing".to_string());
-        if invoice_vendor_options.get().is_empty() {
-            trigger_vendor_options_fetch(set_invoice_vendor_options);
-        }
-        if !invoice_loaded.get() {
-            trigger_invoice_fetch(
-                invoice_query.get(),
-                invoice_vendor_filter.get().trim().parse::<i64>().ok(),
-                invoice_date_from.get(),
-                invoice_date_to.get(),
-                invoice_page.get(),
-                invoice_limit,
-                set_invoice_loading,
-                set_invoice_status,
-                set_invoice_list,
-                set_invoice_total,
-                set_invoice_filtered,
-                set_invoice_loaded,
-            );
-        }
-    };
+#[derive(Serialize)]
+struct PosProfileIdArgs {
+    #[serde(rename = "profileId")]
+    profile_id: i64,
+}
 
-    // ── Food Cost state ──
-    let (fc_item_options, set_fc_item_options) = signal(Vec::<ItemSimple>::new());
-    let (fc_unit_options, set_fc_unit_options) = signal(Vec::<UnitSimple>::new());
-    let (fc_lines, set_fc_lines) = signal(Vec::<FoodCostLineInput>::new());
-    let (fc_result, set_fc_result) = signal(Option::<FoodCostResponse>::None);
-    let (fc_target_pct, set_fc_target_pct) = signal(30.0f64);
-    let (fc_status, set_fc_status) = signal(String::new());
-    let (fc_loading, set_fc_loading) = signal(false);
-    let (fc_add_item_id, set_fc_add_item_id) = signal(String::new());
-    let (fc_add_unit_id, set_fc_add_unit_id) = signal(String::new());
-    let (fc_add_qty, set_fc_add_qty) = signal(String::new());
-    let (fc_dish_name, set_fc_dish_name) = signal(String::new());
+#[derive(Serialize)]
+struct ImportSalesArgs {
+    #[serde(rename = "csvPath")]
+    csv_path: String,
+    #[serde(rename = "profileId")]
+    profile_id: i64,
+    #[serde(rename = "periodStart")]
+    period_start: String,
+    #[serde(rename = "periodEnd")]
+    period_end: String,
+}
 
-    // ── Settings state ──
-    let (settings_company, set_settings_company) = signal(String::new());
-    let (settings_logo_path, set_settings_logo_path) = signal(String::new());
-    let (settings_service_cat, set_settings_service_cat) = signal(String::new());
-    let (settings_op_size, set_settings_op_size) = signal(String::new());
-    let (settings_status, set_settings_status) = signal(String::new());
-    let (settings_loaded, set_settings_loaded) = signal(false);
-    let (settings_logo_upload_path, set_settings_logo_upload_path) = signal(String::new());
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SalesImportResponse {
+    import_id: i64,
+    rows: usize,
+    skipped: usize,
+    new_items: usize,
+    unmapped_items: usize,
+    total_qty: f64,
+    total_sales: f64,
+}
 
-    let load_settings = move || {
-        spawn_local(async move {
-            let args = to_value(&PingArgs {}).unwrap();
-            match invoke_cmd::<SettingsResponse>("get_settings", args).await {
-                Ok(s) => {
-                    set_settings_company.set(s.company_name);
-                    set_settings_logo_path.set(s.logo_path);
-                    set_settings_service_cat.set(s.service_category);
-                    set_settings_op_size.set(s.operation_size);
-                    set_settings_loaded.set(true);
-                }
-                Err(err) => {
-                    set_settings_status.set(format!("Failed to load settings: {err}"));
-                }
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SalesImportRow {
+    import_id: i64,
+    profile_name: String,
+    source_file: String,
+    period_start: String,
+    period_end: String,
+    imported_at: String,
+    row_count: i64,
+    total_qty: f64,
+    total_sales: f64,
+}
 
-    let show_settings = move || {
-        set_active_panel.set("settings".to_string());
-        if !settings_loaded.get() {
-            load_settings();
-        }
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SalesImportListResponse {
+    imports: Vec<SalesImportRow>,
+}
 
-    let show_fda = move || {
-        set_active_panel.set("fda".to_string());
-    };
+#[derive(Serialize)]
+struct SalesImportIdArgs {
+    #[serde(rename = "importId")]
+    import_id: i64,
+}
 
-    let save_settings_action = move || {
-        let company_name = settings_company.get();
-        let service_category = settings_service_cat.get();
-        let operation_size = settings_op_size.get();
-        set_settings_status.set("Saving...".to_string());
-        spawn_local(async move {
-            let args = to_value(&SaveSettingsArgs {
-                company_name,
-                service_category,
-                operation_size,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("save_settings", args).await {
-                Ok(resp) => set_settings_status.set(resp.message),
-                Err(err) => set_settings_status.set(format!("Save failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PosItemRow {
+    pos_item_id: i64,
+    name: String,
+    category: String,
+    recipe_id: Option<i64>,
+    recipe_name: String,
+    portions: f64,
+    ignored: bool,
+    units_sold: f64,
+    net_sales: f64,
+}
 
-    let upload_logo_action = move || {
-        let source = settings_logo_upload_path.get();
-        if source.trim().is_empty() {
-            set_settings_status.set("Enter a logo file path".to_string());
-            return;
-        }
-        set_settings_status.set("Uploading logo...".to_string());
-        spawn_local(async move {
-            let args = to_value(&UploadLogoArgs { source_path: source }).unwrap();
-            match invoke_cmd::<PatchResponse>("upload_logo", args).await {
-                Ok(resp) => {
-                    set_settings_status.set(resp.message);
-                    // reload to get the new path
-                    let args2 = to_value(&PingArgs {}).unwrap();
-                    if let Ok(s) = invoke_cmd::<SettingsResponse>("get_settings", args2).await {
-                        set_settings_logo_path.set(s.logo_path);
-                    }
-                    set_settings_logo_upload_path.set(String::new());
-                }
-                Err(err) => set_settings_status.set(format!("Upload failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PosItemListResponse {
+    items: Vec<PosItemRow>,
+    unmapped: usize,
+}
 
-    let remove_logo_action = move || {
-        set_settings_status.set("Removing logo...".to_string());
-        spawn_local(async move {
-            let args = to_value(&PingArgs {}).unwrap();
-            match invoke_cmd::<PatchResponse>("remove_logo", args).await {
-                Ok(resp) => {
-                    set_settings_status.set(resp.message);
-                    set_settings_logo_path.set(String::new());
-                }
-                Err(err) => set_settings_status.set(format!("Remove failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize)]
+struct PosItemQueryArgs {
+    #[serde(rename = "unmappedOnly")]
+    unmapped_only: bool,
+}
 
-    let show_foodcost = move || {
-        set_active_panel.set("foodcost".to_string());
-        if fc_item_options.get().is_empty() {
-            spawn_local(async move {
-                let args = to_value(&PingArgs {}).unwrap();
-                if let Ok(result) = invoke_cmd::<ItemSimpleResponse>("list_items_simple", args).await {
-                    set_fc_item_options.set(result.items);
-                }
-            });
-        }
-        if fc_unit_options.get().is_empty() {
-            trigger_unit_options_fetch(set_fc_unit_options);
-        }
-    };
+#[derive(Serialize)]
+struct MapPosItemArgs {
+    #[serde(rename = "posItemId")]
+    pos_item_id: i64,
+    #[serde(rename = "recipeId")]
+    recipe_id: Option<i64>,
+    portions: Option<f64>,
+    ignored: bool,
+}
 
-    let fc_add_line = move || {
-        let item_id = match fc_add_item_id.get().trim().parse::<i64>() {
-            Ok(v) => v,
-            Err(_) => {
-                set_fc_status.set("Select an ingredient".to_string());
-                return;
-            }
-        };
-        let unit_id: Option<i64> = fc_add_unit_id.get().trim().parse().ok();
-        let qty: f64 = fc_add_qty.get().trim().parse().unwrap_or(0.0);
-        if qty <= 0.0 {
-            set_fc_status.set("Enter a quantity > 0".to_string());
-            return;
-        }
-        let mut current = fc_lines.get();
-        current.push(FoodCostLineInput { item_id, unit_id, qty });
-        set_fc_lines.set(current);
-        set_fc_add_item_id.set(String::new());
-        set_fc_add_unit_id.set(String::new());
-        set_fc_add_qty.set(String::new());
-        set_fc_status.set(String::new());
-    };
+#[derive(Serialize, Clone)]
+struct MenuEngineeringArgs {
+    #[serde(rename = "dateFrom")]
+    date_from: String,
+    #[serde(rename = "dateTo")]
+    date_to: String,
+    category: String,
+}
 
-    let fc_remove_line = move |idx: usize| {
-        let mut current = fc_lines.get();
-        if idx < current.len() {
-            current.remove(idx);
-            set_fc_lines.set(current);
-            set_fc_result.set(None);
-        }
-    };
+#[derive(Serialize)]
+struct ExportMenuEngineeringArgs {
+    #[serde(rename = "dateFrom")]
+    date_from: String,
+    #[serde(rename = "dateTo")]
+    date_to: String,
+    category: String,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
 
-    let fc_calculate = move || {
-        let lines = fc_lines.get();
-        if lines.is_empty() {
-            set_fc_status.set("Add at least one ingredient".to_string());
-            return;
-        }
-        set_fc_loading.set(true);
-        set_fc_status.set("Calculating...".to_string());
-        spawn_local(async move {
-            let args = to_value(&CalculateFoodCostArgs { lines }).unwrap();
-            match invoke_cmd::<FoodCostResponse>("calculate_food_cost", args).await {
-                Ok(result) => {
-                    set_fc_result.set(Some(result));
-                    set_fc_status.set("Done".to_string());
-                }
-                Err(err) => {
-                    set_fc_status.set(format!("Calculation failed: {err}"));
-                }
-            }
-            set_fc_loading.set(false);
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct MenuEngineeringRow {
+    pos_item_id: i64,
+    name: String,
+    category: String,
+    recipe_id: i64,
+    recipe_name: String,
+    units_sold: f64,
+    menu_price: Option<f64>,
+    plate_cost: Option<f64>,
+    contribution_margin: Option<f64>,
+    revenue: f64,
+    total_cost: Option<f64>,
+    total_margin: Option<f64>,
+    mix_pct: f64,
+    popularity_index: f64,
+    classification: String,
+}
 
-    let fc_clear = move || {
-        set_fc_lines.set(Vec::new());
-        set_fc_result.set(None);
-        set_fc_status.set(String::new());
-        set_fc_dish_name.set(String::new());
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct MenuEngineeringResponse {
+    date_from: String,
+    date_to: String,
+    category: String,
+    categories: Vec<String>,
+    rows: Vec<MenuEngineeringRow>,
+    total_units: f64,
+    total_revenue: f64,
+    total_cost: f64,
+    total_margin: f64,
+    avg_contribution_margin: Option<f64>,
+    popularity_threshold_pct: f64,
+    food_cost_pct: Option<f64>,
+    uncosted_items: usize,
+    unmapped_items: usize,
+    unmapped_units: f64,
+}
 
-    let export_invoices = move || {
-        let query = invoice_query.get();
-        let vendor_id = invoice_vendor_filter.get().trim().parse::<i64>().ok();
-        let date_from = invoice_date_from.get();
-        let date_to = invoice_date_to.get();
-        set_invoice_export_status.set("Exporting...".to_string());
-        spawn_local(async move {
-            let args = to_value(&ExportInvoiceArgs {
-                query,
-                vendor_id,
-                date_from,
-                date_to,
-                output_path: None,
-            })
-            .unwrap();
-            match invoke_cmd::<ExportCsvResponse>("export_invoice_lines_csv", args).await {
-                Ok(result) => {
-                    set_invoice_export_path.set(result.path.clone());
-                    set_invoice_export_status.set(format!("Exported {} rows", result.rows));
-                }
-                Err(err) => {
-                    set_invoice_export_status.set(format!("Export failed: {err}"));
-                }
-            }
-        });
-    };
+#[derive(Serialize)]
+struct VarianceArgs {
+    #[serde(rename = "dateFrom")]
+    date_from: String,
+    #[serde(rename = "dateTo")]
+    date_to: String,
+}
 
-    let approve_conversion = move |row: ConversionSuggestionRow| {
-        let base_path = base_path.get();
-        set_conversion_status.set("Saving patch...".to_string());
-        spawn_local(async move {
-            let args = to_value(&PatchConvunitArgs {
-                base_path,
-                item_id: row.item_id,
-                vendor_id: row.vendor_id,
-                unit_id1: row.unit_id1,
-                unit_id2: row.unit_id2,
-                qty1: row.qty1,
-                qty2: row.qty2,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("patch_convunit", args).await {
-                Ok(resp) => {
-                    set_conversion_status.set(resp.message);
-                    trigger_conversion_overview_fetch(set_conversion_overview);
-                    let table = conversion_tab.get();
-                    let page = conversion_page.get();
-                    match table.as_str() {
-                        "suggestions" => trigger_conversion_suggestions_fetch(
-                            "conv_suggestions".to_string(),
-                            page,
-                            conversion_limit,
-                            set_conversion_loading,
-                            set_conversion_status,
-                            set_conversion_suggestions,
-                            set_conversion_suggestions_total,
-                        ),
-                        "safe" => trigger_conversion_suggestions_fetch(
-                            "conv_suggestions_safe".to_string(),
-                            page,
-                            conversion_limit,
-                            set_conversion_loading,
-                            set_conversion_status,
-                            set_conversion_suggestions_safe,
-                            set_conversion_suggestions_safe_total,
-                        ),
-                        "todo" => trigger_conversion_todo_fetch(
-                            page,
-                            conversion_limit,
-                            set_conversion_loading,
-                            set_conversion_status,
-                            set_conversion_todo,
-                            set_conversion_todo_total,
-                        ),
-                        "missing" => trigger_missing_edges_fetch(
-                            page,
-                            conversion_limit,
-                            set_conversion_loading,
-                            set_conversion_status,
-                            set_conversion_missing_edges,
-                            set_conversion_missing_edges_total,
-                        ),
-                        _ => {}
-                    }
-                }
-                Err(err) => set_conversion_status.set(format!("Patch failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct VarianceLine {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    unit_name: String,
+    theoretical_qty: f64,
+    purchased_qty: f64,
+    begin_qty: Option<f64>,
+    end_qty: Option<f64>,
+    actual_qty: f64,
+    variance_qty: f64,
+    price: Option<f64>,
+    theoretical_cost: Option<f64>,
+    actual_cost: Option<f64>,
+    variance_cost: Option<f64>,
+    variance_pct: Option<f64>,
+    basis: String,
+    usage_from: String,
+    usage_to: String,
+}
 
-    let assign_purch_unit = move |item_id: i64| {
-        let purch_unit_id = match purch_unit_selected_id.get().trim().parse::<i64>() {
-            Ok(value) => value,
-            Err(_) => {
-                set_purch_unit_status.set("Enter a valid purchase unit".to_string());
-                return;
-            }
-        };
-        let is_default = purch_unit_set_default.get();
-        set_purch_unit_status.set("Saving purchase unit...".to_string());
-        spawn_local(async move {
-            let args = to_value(&SetPurchUnitArgs {
-                item_id,
-                purch_unit_id,
-                is_default,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("set_item_purch_unit", args).await {
-                Ok(resp) => {
-                    set_purch_unit_status.set(resp.message);
-                    if active_panel.get() == "reports" {
-                        trigger_missing_purch_fetch(
-                            report_page.get(),
-                            report_limit,
-                            set_report_loading,
-                            set_report_status,
-                            set_missing_purch_rows,
-                            set_missing_purch_total,
-                        );
-                    }
-                    if let Some(detail) = inventory_selected.get() {
-                        if detail.item_id == item_id {
-                            trigger_inventory_detail_fetch(
-                                item_id,
-                                set_inventory_detail_loading,
-                                set_inventory_detail_status,
-                                set_inventory_selected,
-                            );
-                        }
-                    }
-                }
-                Err(err) => set_purch_unit_status.set(format!("Save failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CategoryVariance {
+    category: String,
+    theoretical_cost: f64,
+    actual_cost: f64,
+    variance_cost: f64,
+}
 
-    let save_manual_price = move |item_id: i64| {
-        let vendor_id = match manual_price_vendor_id.get().trim().parse::<i64>() {
-            Ok(value) => value,
-            Err(_) => {
-                set_manual_price_status.set("Select a vendor".to_string());
-                return;
-            }
-        };
-        let price = match manual_price_value.get().trim().parse::<f64>() {
-            Ok(value) if value > 0.0 => value,
-            _ => {
-                set_manual_price_status.set("Enter a valid price > 0".to_string());
-                return;
-            }
-        };
-        let pack = manual_price_pack.get();
-        set_manual_price_status.set("Saving manual price...".to_string());
-        spawn_local(async move {
-            let args = to_value(&UpsertManualPriceArgs {
-                item_id,
-                vendor_id,
-                price,
-                pack,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("upsert_manual_price", args).await {
-                Ok(resp) => {
-                    set_manual_price_status.set(resp.message);
-                    trigger_inventory_detail_fetch(
-                        item_id,
-                        set_inventory_detail_loading,
-                        set_inventory_detail_status,
-                        set_inventory_selected,
-                    );
-                }
-                Err(err) => set_manual_price_status.set(format!("Save failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct VarianceReport {
+    date_from: String,
+    date_to: String,
+    net_sales: f64,
+    theoretical_cost: f64,
+    actual_cost: f64,
+    theoretical_pct: Option<f64>,
+    actual_pct: Option<f64>,
+    lines: Vec<VarianceLine>,
+    categories: Vec<CategoryVariance>,
+    unresolved: Vec<String>,
+}
 
-    let merge_vendor_ids = move || {
-        let source_vendor_id = match vendor_merge_source_id.get().trim().parse::<i64>() {
-            Ok(value) => value,
-            Err(_) => {
-                set_vendor_merge_status.set("Enter a valid source vendor ID".to_string());
-                return;
-            }
-        };
-        let target_vendor_id = match vendor_merge_target_id.get().trim().parse::<i64>() {
-            Ok(value) => value,
-            Err(_) => {
-                set_vendor_merge_status.set("Select a valid target vendor".to_string());
-                return;
-            }
-        };
-        if source_vendor_id == target_vendor_id {
-            set_vendor_merge_status.set("Source and target vendors must be different".to_string());
-            return;
-        }
-        set_vendor_merge_status.set("Merging vendors...".to_string());
-        spawn_local(async move {
-            let args = to_value(&MergeVendorArgs {
-                source_vendor_id,
-                target_vendor_id,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("merge_vendor", args).await {
-                Ok(resp) => {
-                    set_vendor_merge_status.set(resp.message);
-                    set_vendor_merge_source_id.set(target_vendor_id.to_string());
-                    trigger_vendor_fetch(
-                        vendor_query.get(),
-                        vendor_page.get(),
-                        vendor_limit,
-                        set_vendor_loading,
-                        set_vendor_status,
-                        set_vendor_list,
-                        set_vendor_total,
-                        set_vendor_filtered,
-                        set_vendor_loaded,
-                    );
-                    trigger_vendor_detail_fetch(
-                        target_vendor_id,
-                        set_vendor_detail_loading,
-                        set_vendor_detail_status,
-                        set_selected_vendor,
-                    );
-                    trigger_vendor_options_fetch(set_invoice_vendor_options);
-                    trigger_vendor_options_fetch(set_editor_vendor_options);
-                    trigger_vendor_all_options_fetch(set_vendor_merge_options);
-                }
-                Err(err) => set_vendor_merge_status.set(format!("Merge failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize)]
+struct CreateCountSessionArgs {
+    name: String,
+    #[serde(rename = "countDate")]
+    count_date: Option<String>,
+    notes: Option<String>,
+}
 
-    // ── Inventory edit signals ──
-    let (edit_item_name, set_edit_item_name) = signal(String::new());
-    let (edit_item_status, set_edit_item_status) = signal(String::new());
-    let (edit_item_msg, set_edit_item_msg) = signal(String::new());
-    let (edit_item_food_category, set_edit_item_food_category) = signal(String::new());
-    let (edit_item_storage_type, set_edit_item_storage_type) = signal(String::new());
-    let (edit_item_category_msg, set_edit_item_category_msg) = signal(String::new());
+#[derive(Serialize)]
+struct CountSessionIdArgs {
+    #[serde(rename = "sessionId")]
+    session_id: i64,
+}
 
-    let save_item_edit = move |item_id: i64| {
-        let name = edit_item_name.get();
-        let status_val: Option<i64> = edit_item_status.get().parse().ok();
-        set_edit_item_msg.set("Saving...".to_string());
-        spawn_local(async move {
-            let args = to_value(&UpdateItemArgs {
-                item_id,
-                name,
-                status: status_val,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("update_item", args).await {
-                Ok(resp) => {
-                    set_edit_item_msg.set(resp.message);
-                    // Refresh detail
-                    trigger_inventory_detail_fetch(
-                        item_id,
-                        set_inventory_detail_loading,
-                        set_inventory_detail_status,
-                        set_inventory_selected,
-                    );
-                    // Refresh list
-                    trigger_inventory_fetch(
-                        inventory_query.get(),
-                        inventory_food_category.get(),
-                        inventory_order_by.get(),
-                        inventory_page.get(),
-                        inventory_limit,
-                        set_inventory_loading,
-                        set_inventory_status,
-                        set_inventory_items,
-                        set_inventory_total,
-                        set_inventory_filtered,
-                        set_inventory_loaded,
-                    );
-                }
-                Err(err) => set_edit_item_msg.set(format!("Save failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize)]
+struct SaveCountLineArgs {
+    #[serde(rename = "sessionId")]
+    session_id: i64,
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    qty: Option<f64>,
+    #[serde(rename = "unitId")]
+    unit_id: Option<i64>,
+}
 
-    // ── Recipe edit signals ──
-    let (edit_recipe_name, set_edit_recipe_name) = signal(String::new());
-    let (edit_recipe_instructions, set_edit_recipe_instructions) = signal(String::new());
-    let (edit_recipe_msg, set_edit_recipe_msg) = signal(String::new());
-    let (edit_recipe_editing, set_edit_recipe_editing) = signal(false);
+#[derive(Serialize)]
+struct SetShelfOrderArgs {
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "shelfOrder")]
+    shelf_order: Option<i64>,
+}
 
-    let save_recipe_edit = move |recipe_id: i64| {
-        let name = edit_recipe_name.get();
-        let instructions = edit_recipe_instructions.get();
-        set_edit_recipe_msg.set("Saving...".to_string());
-        spawn_local(async move {
-            let args = to_value(&UpdateRecipeArgs {
-                recipe_id,
-                name,
-                instructions,
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("update_recipe", args).await {
-                Ok(resp) => {
-                    set_edit_recipe_msg.set(resp.message);
-                    set_edit_recipe_editing.set(false);
-                    trigger_recipe_detail_fetch(
-                        recipe_id,
-                        set_recipe_detail_loading,
-                        set_recipe_detail_status,
-                        set_selected_recipe,
-                    );
-                    trigger_recipe_fetch(
-                        recipe_query.get(),
-                        recipe_page.get(),
-                        recipe_limit,
-                        set_recipe_loading,
-                        set_recipe_status,
-                        set_recipe_list,
-                        set_recipe_total,
-                        set_recipe_filtered,
-                        set_recipe_loaded,
-                        false,
-                        set_recipe_detail_loading,
-                        set_recipe_detail_status,
-                        set_selected_recipe,
-                    );
-                }
-                Err(err) => set_edit_recipe_msg.set(format!("Save failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize)]
+struct InventoryValuationArgs {
+    #[serde(rename = "sessionId")]
+    session_id: i64,
+    #[serde(rename = "compareSessionId")]
+    compare_session_id: Option<i64>,
+}
 
-    let delete_recipe_ingredient = move |recipe_id: i64, recp_item_id: i64| {
-        set_edit_recipe_msg.set("Removing ingredient...".to_string());
-        spawn_local(async move {
-            let args = to_value(&AddRecpItemArgs {
-                recipe_id,
-                recp_item_id: Some(recp_item_id),
-                item_id: 0,
-                unit_id: None,
-                qty: Some(0.0),
-            })
-            .unwrap();
-            match invoke_cmd::<PatchResponse>("add_recp_item", args).await {
-                Ok(resp) => {
-                    set_edit_recipe_msg.set(resp.message);
-                    trigger_recipe_detail_fetch(
-                        recipe_id,
-                        set_recipe_detail_loading,
-                        set_recipe_detail_status,
-                        set_selected_recipe,
-                    );
-                }
-                Err(err) => set_edit_recipe_msg.set(format!("Remove failed: {err}")),
-            }
-        });
-    };
+#[derive(Serialize)]
+struct ExportCountSheetArgs {
+    #[serde(rename = "sessionId")]
+    session_id: i64,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
 
-    // ── Export signals ──
-    let (export_status, set_export_status) = signal(String::new());
+#[derive(Serialize)]
+struct ExportValuationArgs {
+    #[serde(rename = "sessionId")]
+    session_id: i64,
+    #[serde(rename = "compareSessionId")]
+    compare_session_id: Option<i64>,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
 
-    /// Open a native Save dialog, then invoke an export command.
-    fn trigger_save_dialog_and_export(
-        title: &str,
-        default_name: &str,
-        filter_label: &str,
-        filter_ext: &str,
-        set_export_status: WriteSignal<String>,
-        build_cmd: impl FnOnce(String) -> (String, JsValue) + 'static,
-    ) {
-        let title = title.to_string();
-        let default_name = default_name.to_string();
-        let filter_label = filter_label.to_string();
-        let filter_ext = filter_ext.to_string();
-        spawn_local(async move {
-            let path: Option<String> = {
-                let w = match window() {
-                    Some(w) => w,
-                    None => { set_export_status.set("Window not available".into()); return; }
-                };
-                let tauri = match Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
-                    Ok(t) if !t.is_undefined() && !t.is_null() => t,
-                    _ => { set_export_status.set("Tauri API missing".into()); return; }
-                };
-                let dialog = match Reflect::get(&tauri, &JsValue::from_str("dialog")) {
-                    Ok(d) if !d.is_undefined() && !d.is_null() => d,
-                    _ => { set_export_status.set("Dialog API missing".into()); return; }
-                };
-                let save_fn = match Reflect::get(&dialog, &JsValue::from_str("save")) {
-                    Ok(f) if f.is_function() => f,
-                    _ => { set_export_status.set("Save dialog missing".into()); return; }
-                };
-                let opts = js_sys::Object::new();
-                let _ = Reflect::set(&opts, &JsValue::from_str("title"), &JsValue::from_str(&title));
-                let _ = Reflect::set(&opts, &JsValue::from_str("defaultPath"), &JsValue::from_str(&default_name));
-                // Filter
-                let filter_obj = js_sys::Object::new();
-                let _ = Reflect::set(&filter_obj, &JsValue::from_str("name"), &JsValue::from_str(&filter_label));
-                let exts = js_sys::Array::new();
-                exts.push(&JsValue::from_str(&filter_ext));
-                let _ = Reflect::set(&filter_obj, &JsValue::from_str("extensions"), &exts);
-                let filters = js_sys::Array::new();
-                filters.push(&filter_obj);
-                let _ = Reflect::set(&opts, &JsValue::from_str("filters"), &filters);
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CountSession {
+    session_id: i64,
+    name: String,
+    count_date: String,
+    status: String,
+    notes: String,
+    created_at: String,
+    closed_at: String,
+    item_count: i64,
+    total_value: Option<f64>,
+}
 
-                let func = save_fn.dyn_ref::<js_sys::Function>().unwrap();
-                match func.call1(&JsValue::NULL, &opts) {
-                    Ok(promise) => {
-                        if let Ok(p) = promise.dyn_into::<Promise>() {
-                            match JsFuture::from(p).await {
-                                Ok(result) => result.as_string(),
-                                Err(_) => None,
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                    Err(_) => None,
-                }
-            };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CountUnitOption {
+    unit_id: i64,
+    unit_name: String,
+}
 
-            if let Some(output_path) = path {
-                set_export_status.set("Exporting...".to_string());
-                let (cmd, args) = build_cmd(output_path);
-                match invoke_cmd::<PatchResponse>(&cmd, args).await {
-                    Ok(resp) => set_export_status.set(resp.message),
-                    Err(err) => set_export_status.set(format!("Export failed: {err}")),
-                }
-            }
-        });
-    }
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CountSheetLine {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    storage_type: String,
+    shelf_order: Option<i64>,
+    purch_unit_id: Option<i64>,
+    purch_unit_name: String,
+    units: Vec<CountUnitOption>,
+    qty: Option<f64>,
+    unit_id: Option<i64>,
+    unit_name: String,
+    purch_qty: Option<f64>,
+    unit_price: Option<f64>,
+    value: Option<f64>,
+}
 
-    let export_inventory_pdf = move || {
-        let gb = inventory_order_by.get();
-        trigger_save_dialog_and_export(
-            "Save Inventory PDF",
-            "inventory.pdf",
-            "PDF",
-            "pdf",
-            set_export_status,
-            move |path| ("export_inventory_pdf".to_string(), to_value(&ExportInventoryArgs { output_path: path, group_by: if gb.is_empty() { None } else { Some(gb) } }).unwrap()),
-        );
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CountSheet {
+    session: CountSession,
+    lines: Vec<CountSheetLine>,
+}
 
-    let export_inventory_docx = move || {
-        let gb = inventory_order_by.get();
-        trigger_save_dialog_and_export(
-            "Save Inventory Word Document",
-            "inventory.docx",
-            "Word Document",
-            "docx",
-            set_export_status,
-            move |path| ("export_inventory_docx".to_string(), to_value(&ExportInventoryArgs { output_path: path, group_by: if gb.is_empty() { None } else { Some(gb) } }).unwrap()),
-        );
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ValuationLine {
+    item_id: i64,
+    item_name: String,
+    category: String,
+    storage_type: String,
+    unit_name: String,
+    qty: Option<f64>,
+    unit_price: Option<f64>,
+    value: Option<f64>,
+    prior_qty: Option<f64>,
+    prior_value: Option<f64>,
+    change_value: f64,
+}
 
-    // ── FDA PDF export ──
-    let export_fda_pdf = move || {
-        trigger_save_dialog_and_export(
-            "Save FDA Guidelines PDF",
-            "fda-guidelines.pdf",
-            "PDF",
-            "pdf",
-            set_export_status,
-            |path| ("export_fda_pdf".to_string(), to_value(&ExportPathArgs { output_path: path }).unwrap()),
-        );
-    };
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ValuationGroup {
+    name: String,
+    value: f64,
+    prior_value: f64,
+}
 
-    // ── Global search signals ──
-    let (search_query, set_search_query) = signal(String::new());
-    let (search_results, set_search_results) = signal(Vec::<GlobalSearchHit>::new());
-    let (_search_loading, set_search_loading) = signal(false);
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InventoryValuation {
+    session: CountSession,
+    prior: Option<CountSession>,
+    lines: Vec<ValuationLine>,
+    storage_groups: Vec<ValuationGroup>,
+    categories: Vec<ValuationGroup>,
+    total_value: f64,
+    prior_total: f64,
+    unpriced: Vec<String>,
+}
 
-    let do_global_search = move || {
-        let q = search_query.get();
-        if q.trim().is_empty() {
-            set_search_results.set(Vec::new());
-            return;
-        }
-        set_search_loading.set(true);
-        spawn_local(async move {
-            let args = to_value(&GlobalSearchArgs { query: q }).unwrap();
-            match JsFuture::from(invoke("global_search", args)).await {
-                Ok(val) => {
-                    if let Ok(hits) = serde_wasm_bindgen::from_value::<Vec<GlobalSearchHit>>(val) {
-                        set_search_results.set(hits);
-                    }
-                }
-                Err(_) => {
-                    set_search_results.set(Vec::new());
-                }
-            }
-            set_search_loading.set(false);
-        });
-    };
+#[derive(Serialize)]
+struct OrderGuideArgs {
+    #[serde(rename = "orderDate")]
+    order_date: Option<String>,
+    theoretical: bool,
+    #[serde(rename = "vendorId")]
+    vendor_id: Option<i64>,
+}
 
-    // ── PDF Invoice import signals ──
-    let (pdf_preview, set_pdf_preview) = signal(Option::<PdfInvoicePreview>::None);
-    let (pdf_import_status, set_pdf_import_status) = signal(String::new());
-    let (pdf_vendor_id, set_pdf_vendor_id) = signal(String::new());
-    let (pdf_invoice_no, set_pdf_invoice_no) = signal(String::new());
-    let (pdf_invoice_date, set_pdf_invoice_date) = signal(String::new());
+#[derive(Serialize)]
+struct ExportOrderGuideArgs {
+    #[serde(rename = "orderDate")]
+    order_date: Option<String>,
+    theoretical: bool,
+    #[serde(rename = "vendorId")]
+    vendor_id: Option<i64>,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
 
-    view! {
-        <div class="app-shell">
-            <aside class="sidebar">
-                <div class="brand">
-                    <img src="/public/4chef-logo.png" alt="4chef" class="brand-logo" />
-                </div>
-                <div class="sidebar-search">
-                    <input
-                        type="text"
-                        class="sidebar-search-input"
-                        placeholder="Search everything..."
-                        prop:value=move || search_query.get()
-                        on:input=move |ev| {
-                            let val = event_target_value(&ev);
-                            set_search_query.set(val);
-                            do_global_search();
-                        }
-                        on:keydown=move |ev| {
-                            if ev.key() == "Escape" {
-                                set_search_query.set(String::new());
-                                set_search_results.set(Vec::new());
-                            }
-                        }
-                    />
-                    <Show when=move || !search_results.get().is_empty()>
-                        <div class="sidebar-search-results">
-                            <For
-                                each=move || search_results.get()
-                                key=|hit| format!("{}-{}", hit.category, hit.id)
-                                children=move |hit: GlobalSearchHit| {
-                                    let cat = hit.category.clone();
-                                    let label = hit.label.clone();
-                                    let detail = hit.detail.clone();
-                                    view! {
-                                        <button
-                                            class="sidebar-search-hit"
-                                            type="button"
-                                            on:click=move |_| {
-                                                match cat.as_str() {
-                                                    "item" => set_active_panel.set("inventory".to_string()),
-                                                    "recipe" => set_active_panel.set("recipes".to_string()),
-                                                    "vendor" => set_active_panel.set("vendors".to_string()),
-                                                    "invoice" => set_active_panel.set("purchasing".to_string()),
-                                                    _ => {}
-                                                }
-                                                set_search_query.set(String::new());
-                                                set_search_results.set(Vec::new());
-                                            }
-                                        >
-                                            <span class="search-hit-label">{label.clone()}</span>
-                                            <span class="search-hit-badge">{detail.clone()}</span>
-                                        </button>
-                                    }
-                                }
-                            />
-                        </div>
-                    </Show>
-                </div>
-                <div class="nav-section">
-                    <div class="nav-label">"Modules"</div>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "imports"
-                        on:click=move |_| show_imports()
-                        type="button"
-                    >
-                        "Imports"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "inventory"
-                        on:click=move |_| show_inventory()
-                        type="button"
-                    >
-                        "Inventory"
-                    </button>
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct OrderGuideLine {
+    item_id: i64,
+    item_name: String,
+    storage_type: String,
+    purch_unit_name: String,
+    par_qty: f64,
+    on_hand: Option<f64>,
+    on_hand_basis: String,
+    need_qty: f64,
+    order_unit_id: i64,
+    order_unit_name: String,
+    order_qty: f64,
+    price: Option<f64>,
+    est_cost: Option<f64>,
+}
 
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "recipes"
-                        on:click=move |_| show_recipes()
-                        type="button"
-                    >
-                        "Recipes"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "vendors"
-                        on:click=move |_| show_vendors()
-                        type="button"
-                    >
-                        "Vendors"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "conversions"
-                        on:click=move |_| show_conversions()
-                        type="button"
-                    >
-                        "Conversions"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "purchasing"
-                        on:click=move |_| show_purchasing()
-                        type="button"
-                    >
-                        "Purchasing"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "reports"
-                        on:click=move |_| show_reports()
-                        type="button"
-                    >
-                        "Reports"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "foodcost"
-                        on:click=move |_| show_foodcost()
-                        type="button"
-                    >
-                        "Food Cost"
-                    </button>
-                </div>
-                <div class="nav-section">
-                    <div class="nav-label">"System"</div>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "settings"
-                        on:click=move |_| show_settings()
-                        type="button"
-                    >
-                        "Settings"
-                    </button>
-                    <button
-                        class="nav-item"
-                        class:active=move || active_panel.get() == "fda"
-                        on:click=move |_| show_fda()
-                        type="button"
-                    >
-                        "FDA Guidelines"
-                    </button>
-                </div>
-            </aside>
-            <main class="main">
-                <div class="topbar">
-                    <div>
-                        <div class={move || {
-                            if active_panel.get().as_str() == "imports" || active_panel.get().as_str() == "" || active_panel.get().as_str() == "home" {
-                                "title title-home"
-                            } else {
-                                "title"
-                            }
-                        }}>
-                            {move || {
-                                match active_panel.get().as_str() {
-                                    "inventory" => "Inventory Browser".to_string(),
-                                    "recipes" => "Recipe Costing".to_string(),
-                                    "vendors" => "Vendor Hub".to_string(),
-                                    "conversions" => "Conversion Lab".to_string(),
-                                    "reports" => "Data Health".to_string(),
-                                    "purchasing" => "Purchasing".to_string(),
-                                    "foodcost" => "Food Cost Calculator".to_string(),
-                                    "settings" => "General Settings".to_string(),
-                                    "fda" => "FDA Guidelines".to_string(),
-                                    _ => "4chef".to_string(),
-                                }
-                            }}
-                        </div>
-                        <div class="subtitle">
-                            {move || {
-                                match active_panel.get().as_str() {
-                                    "inventory" => "Search and inspect inventory items in your 4chef library.".to_string(),
-                                    "recipes" => "Cost recipes using purchase units and conversions."
-                                        .to_string(),
-                                    "vendors" => "Search vendors and review their priced items.".to_string(),
-                                    "conversions" => "Review conversion gaps and suggested fixes.".to_string(),
-                                    "reports" => "Track missing data that blocks accurate costing.".to_string(),
-                                    "purchasing" => "Browse invoices and transaction lines.".to_string(),
-                                    "foodcost" => "Build a dish, set your target %, and find the right menu price.".to_string(),
-                                    "settings" => "Company info and preferences.".to_string(),
-                                    "fda" => "Quick-reference FDA food safety guidelines for your kitchen.".to_string(),
-                                    _ => "Organize. Optimize. Itemize. Done."
-                                        .to_string(),
-                                }
-                            }}
-                        </div>
-                    </div>
-                </div>
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct VendorOrder {
+    vendor_id: Option<i64>,
+    vendor_name: String,
+    lines: Vec<OrderGuideLine>,
+    total_cost: f64,
+}
 
-                <Show when=move || active_panel.get() == "imports">
-                    <div class="panel">
-                        <div class="row">
-                            <div class="input" style="flex: 1;">
-                                <label>
-                                    {move || {
-                                        if import_mode.get() == "mdf" {
-                                            "MDF/LDF source"
-                                        } else {
-                                            "Exports folder"
-                                        }
-                                    }}
-                                </label>
-                                <div style="display: flex; gap: 6px;">
-                                    <input
-                                        type="text"
-                                        prop:value=base_path
-                                        on:input=move |ev| {
-                                            let value = event_target_value(&ev);
-                                            set_base_path.set(value);
-                                        }
-                                        style="flex: 1;"
-                                    />
-                                    <button
-                                        class="button secondary"
-                                        style="white-space: nowrap;"
-                                        on:click=move |_| {
-                                            let mode_now = import_mode.get();
-                                            spawn_local(async move {
-                                                if let Some(w) = window() {
-                                                    if let Ok(tauri) = Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
-                                                        if let Ok(dialog) = Reflect::get(&tauri, &JsValue::from_str("dialog")) {
-                                                            if let Ok(open_fn) = Reflect::get(&dialog, &JsValue::from_str("open")) {
-                                                                let opts = js_sys::Object::new();
-                                                                let _ = Reflect::set(
-                                                                    &opts,
-                                                                    &JsValue::from_str("directory"),
-                                                                    &JsValue::from_bool(mode_now != "mdf"),
-                                                                );
-                                                                let _ = Reflect::set(&opts, &JsValue::from_str("multiple"), &JsValue::from_bool(false));
-                                                                let title = if mode_now == "mdf" {
-                                                                    "Select MDF or LDF file"
-                                                                } else {
-                                                                    "Select exports folder"
-                                                                };
-                                                                let _ = Reflect::set(
-                                                                    &opts,
-                                                                    &JsValue::from_str("title"),
-                                                                    &JsValue::from_str(title),
-                                                                );
-                                                                if let Ok(f) = open_fn.dyn_ref::<js_sys::Function>()
-                                                                    .ok_or(JsValue::NULL)
-                                                                {
-                                                                    if let Ok(promise) = f.call1(&JsValue::NULL, &opts) {
-                                                                        if let Ok(p) = promise.dyn_into::<Promise>() {
-                                                                            if let Ok(result) = JsFuture::from(p).await {
-                                                                                if let Some(path) = result.as_string() {
-                                                                                    set_base_path.set(path);
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            });
-                                        }
-                                    >
-                                        "Browse\u{2026}"
-                                    </button>
-                                </div>
-                            </div>
-                            <div class="input" style="max-width: 220px;">
-                                <label>"Import mode"</label>
-                                <select
-                                    prop:value=move || import_mode.get()
-                                    on:change=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_import_mode.set(value);
-                                    }
-                                >
-                                    <option value="exports">"Exports (CSV)"</option>
-                                    <option value="mdf">"MDF/LDF database"</option>
-                                </select>
-                            </div>
-                            <div class="input">
-                                <label style="visibility: hidden;">{"\u{00A0}"}</label>
-                                <button class="button import-btn" on:click=move |_| start_import() disabled=move || busy.get()>
-                                    {move || if busy.get() { "Importing\u{2026}" } else { "\u{2B07} Import" }}
-                                </button>
-                            </div>
-                        </div>
-                    <div class="status">{move || status.get()}</div>
-                    <div class="status">{move || tauri_status.get()}</div>
-                    <div class="row" style="margin-top: 10px;">
-                        <button class="button secondary" on:click=move |_| ping_backend()>
-                            "Ping backend"
-                        </button>
-                        <button class="button secondary" on:click=move |_| open_path(base_path.get())>
-                            {move || {
-                                if import_mode.get() == "mdf" {
-                                    "Open source path"
-                                } else {
-                                    "Open exports folder"
-                                }
-                            }}
-                        </button>
-                        <div class="status">{move || ping_status.get()}</div>
-                    </div>
-                </div>
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct OrderGuide {
+    order_date: String,
+    day_name: String,
+    theoretical: bool,
+    vendors: Vec<VendorOrder>,
+    unresolved: Vec<String>,
+}
 
-                    <div class="panel">
-                        <div class="row">
-                            <div>
-                                <strong>"Database"</strong>
-                                <div class="status">
-                                    {move || if has_summary.get() { summary.get().db_path } else { "No database yet".to_string() }}
-                                </div>
-                            </div>
-                            <div style="margin-left: auto;">
-                                <button class="button secondary" on:click=move |_| {
-                                    let path = summary.get().db_path;
-                                    open_path(path);
-                                } disabled=move || !has_summary.get()>
-                                    "Open DB folder"
-                                </button>
-                            </div>
-                        </div>
-                        <div class="cards">
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "units"
-                                on:click=move |_| toggle_card("units")
-                            >
-                                <h4>"Units"</h4>
-                                <p>{move || summary.get().units.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "items"
-                                on:click=move |_| toggle_card("items")
-                            >
-                                <h4>"Items"</h4>
-                                <p>{move || summary.get().items.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "convunit"
-                                on:click=move |_| toggle_card("convunit")
-                            >
-                                <h4>"Conversions"</h4>
-                                <p>{move || summary.get().convunit.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "recp_items"
-                                on:click=move |_| toggle_card("recp_items")
-                            >
-                                <h4>"Recipe Items"</h4>
-                                <p>{move || summary.get().recp_items.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "inv_units"
-                                on:click=move |_| toggle_card("inv_units")
-                            >
-                                <h4>"Inv Units"</h4>
-                                <p>{move || summary.get().inv_units.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "vendors"
-                                on:click=move |_| toggle_card("vendors")
-                            >
-                                <h4>"Vendors"</h4>
-                                <p>{move || summary.get().vendors.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "recipes"
-                                on:click=move |_| toggle_card("recipes")
-                            >
-                                <h4>"Recipes"</h4>
-                                <p>{move || summary.get().recipes.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "inv_prices"
-                                on:click=move |_| toggle_card("inv_prices")
-                            >
-                                <h4>"Inv Prices"</h4>
-                                <p>{move || summary.get().inv_prices.to_string()}</p>
-                            </div>
-                        </div>
-                        <div class="cards">
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "invoices"
-                                on:click=move |_| toggle_card("invoices")
-                            >
-                                <h4>"Invoices"</h4>
-                                <p>{move || summary.get().invoices.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "trans"
-                                on:click=move |_| toggle_card("trans")
-                            >
-                                <h4>"Trans Lines"</h4>
-                                <p>{move || summary.get().trans.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "recp_inv"
-                                on:click=move |_| toggle_card("recp_inv")
-                            >
-                                <h4>"RecpInv"</h4>
-                                <p>{move || summary.get().recp_inv.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "conv_suggestions"
-                                on:click=move |_| toggle_card("conv_suggestions")
-                            >
-                                <h4>"Conv Suggestions"</h4>
-                                <p>{move || summary.get().conv_suggestions.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "conv_suggestions_safe"
-                                on:click=move |_| toggle_card("conv_suggestions_safe")
-                            >
-                                <h4>"Safe Suggestions"</h4>
-                                <p>{move || summary.get().conv_suggestions_safe.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "missing_edges"
-                                on:click=move |_| toggle_card("missing_edges")
-                            >
-                                <h4>"Local Conversions"</h4>
-                                <p>{move || summary.get().missing_edges.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "missing_data_report"
-                                on:click=move |_| toggle_card("missing_data_report")
-                            >
-                                <h4>"Missing Data"</h4>
-                                <p>{move || summary.get().missing_data_report.to_string()}</p>
-                            </div>
-                            <div
-                                class="card card-clickable"
-                                class:card-active=move || browse_table_name.get() == "missing_purch_unit"
-                                on:click=move |_| toggle_card("missing_purch_unit")
-                            >
-                                <h4>"Missing Purch"</h4>
-                                <p>{move || summary.get().missing_purch_unit.to_string()}</p>
-                            </div>
-                        </div>
+#[derive(Serialize)]
+struct ListPurchaseOrdersArgs {
+    status: Option<String>,
+    #[serde(rename = "vendorId")]
+    vendor_id: Option<i64>,
+}
 
-                        <Show when=move || !browse_table_name.get().is_empty()>
-                            <div class="browse-section">
-                                <div class="row" style="align-items: center; margin-bottom: 8px;">
-                                    <strong>{move || format!("Browsing: {}", browse_table_name.get())}</strong>
-                                    <span class="status" style="margin-left: 10px;">
-                                        {move || {
-                                            let data = browse_data.get();
-                                            let page = browse_page.get();
-                                            let start = page * browse_limit + 1;
-                                            let end = std::cmp::min((page + 1) * browse_limit, data.total as u32);
-                                            if data.total > 0 {
-                                                format!("Rows {}\u{2013}{} of {}", start, end, data.total)
-                                            } else {
-                                                "No rows".to_string()
-                                            }
-                                        }}
-                                    </span>
-                                    <div style="margin-left: auto; display: flex; gap: 6px;">
-                                        <button
-                                            class="button secondary"
-                                            disabled=move || browse_page.get() == 0 || browse_loading.get()
-                                            on:click=move |_| {
-                                                let p = browse_page.get().saturating_sub(1);
-                                                set_browse_page.set(p);
-                                                fetch_browse_table(browse_table_name.get(), p);
-                                            }
-                                        >
-                                            "\u{25C0} Prev"
-                                        </button>
-                                        <button
-                                            class="button secondary"
-                                            disabled=move || {
-                                                let data = browse_data.get();
-                                                ((browse_page.get() + 1) * browse_limit) as i64 >= data.total || browse_loading.get()
-                                            }
-                                            on:click=move |_| {
-                                                let p = browse_page.get() + 1;
-                                                set_browse_page.set(p);
+#[derive(Serialize)]
+struct CreatePurchaseOrderArgs {
+    #[serde(rename = "vendorId")]
+    vendor_id: i64,
+    #[serde(rename = "expectedDate")]
+    expected_date: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CreatePosFromGuideArgs {
+    #[serde(rename = "orderDate")]
+    order_date: Option<String>,
+    theoretical: bool,
+}
+
+#[derive(Serialize)]
+struct PoIdArgs {
+    #[serde(rename = "poId")]
+    po_id: i64,
+}
+
+#[derive(Serialize)]
+struct SavePoLineArgs {
+    #[serde(rename = "poId")]
+    po_id: i64,
+    #[serde(rename = "poLineId")]
+    po_line_id: Option<i64>,
+    #[serde(rename = "itemId")]
+    item_id: i64,
+    #[serde(rename = "unitId")]
+    unit_id: Option<i64>,
+    qty: f64,
+    price: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct PoLineIdArgs {
+    #[serde(rename = "poLineId")]
+    po_line_id: i64,
+}
+
+#[derive(Serialize)]
+struct SetPoStatusArgs {
+    #[serde(rename = "poId")]
+    po_id: i64,
+    status: String,
+}
+
+#[derive(Serialize)]
+struct ReceivePoLine {
+    po_line_id: Option<i64>,
+    item_id: i64,
+    unit_id: Option<i64>,
+    qty: f64,
+    price: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ReceivePurchaseOrderArgs {
+    #[serde(rename = "poId")]
+    po_id: i64,
+    #[serde(rename = "invoiceNo")]
+    invoice_no: String,
+    #[serde(rename = "invoiceDate")]
+    invoice_date: Option<String>,
+    lines: Vec<ReceivePoLine>,
+}
+
+#[derive(Serialize)]
+struct ExportPurchaseOrderArgs {
+    #[serde(rename = "poId")]
+    po_id: i64,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PurchaseOrder {
+    po_id: i64,
+    po_number: String,
+    vendor_id: i64,
+    vendor_name: String,
+    status: String,
+    order_date: String,
+    expected_date: String,
+    notes: String,
+    line_count: i64,
+    total: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PoLine {
+    po_line_id: i64,
+    item_id: i64,
+    item_name: String,
+    unit_id: Option<i64>,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    ext_cost: Option<f64>,
+    received_qty: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PoReceiptLine {
+    po_line_id: Option<i64>,
+    item_name: String,
+    unit_name: String,
+    qty: f64,
+    price: Option<f64>,
+    flag: String,
+    note: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PoReceipt {
+    receipt_id: i64,
+    invoice_id: i64,
+    invoice_no: String,
+    received_date: String,
+    lines: Vec<PoReceiptLine>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PurchaseOrderDetail {
+    order: PurchaseOrder,
+    lines: Vec<PoLine>,
+    receipts: Vec<PoReceipt>,
+}
+
+#[derive(Serialize)]
+struct ListVendorBidsArgs {
+    #[serde(rename = "itemId")]
+    item_id: Option<i64>,
+    #[serde(rename = "vendorId")]
+    vendor_id: Option<i64>,
+    status: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VendorBidInput {
+    bid_id: Option<i64>,
+    vendor_id: i64,
+    item_id: i64,
+    price: f64,
+    unit_id: Option<i64>,
+    pack: Option<String>,
+    valid_from: Option<String>,
+    valid_to: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SaveVendorBidArgs {
+    bid: VendorBidInput,
+}
+
+#[derive(Serialize)]
+struct BidIdArgs {
+    #[serde(rename = "bidId")]
+    bid_id: i64,
+}
+
+#[derive(Serialize)]
+struct ImportBidSheetArgs {
+    #[serde(rename = "csvPath")]
+    csv_path: String,
+    #[serde(rename = "validFrom")]
+    valid_from: Option<String>,
+    #[serde(rename = "validTo")]
+    valid_to: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BidComparisonArgs {
+    #[serde(rename = "onDate")]
+    on_date: Option<String>,
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ExportBidRequestArgs {
+    #[serde(rename = "vendorIds")]
+    vendor_ids: Vec<i64>,
+    category: Option<String>,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct VendorBid {
+    bid_id: i64,
+    vendor_id: i64,
+    vendor_name: String,
+    item_id: i64,
+    item_name: String,
+    price: f64,
+    unit_id: Option<i64>,
+    unit_name: String,
+    pack: String,
+    valid_from: String,
+    valid_to: String,
+    status: String,
+    source: String,
+    norm_price: Option<f64>,
+    purch_unit_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct BidComparison {
+    item_id: i64,
+    item_name: String,
+    purch_unit_name: String,
+    current_vendor: String,
+    current_price: Option<f64>,
+    best_bid_id: Option<i64>,
+    bids: Vec<VendorBid>,
+}
+
+#[derive(Serialize)]
+struct ListPriceAlertsArgs {
+    status: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SetPriceAlertStatusArgs {
+    #[serde(rename = "alertId")]
+    alert_id: i64,
+    status: String,
+    note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PriceAlertLine {
+    trans_id: i64,
+    qty: f64,
+    price: f64,
+    unit_name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PriceAlert {
+    alert_id: i64,
+    item_id: i64,
+    item_name: String,
+    vendor_id: i64,
+    vendor_name: String,
+    old_price: f64,
+    new_price: f64,
+    pct_change: f64,
+    unit_name: String,
+    source: String,
+    invoice_id: Option<i64>,
+    invoice_no: String,
+    invoice_date: String,
+    status: String,
+    note: String,
+    created_at: String,
+    lines: Vec<PriceAlertLine>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PriceAlertsResponse {
+    threshold_pct: f64,
+    new_count: i64,
+    alerts: Vec<PriceAlert>,
+}
+
+#[derive(Serialize, Clone, Default)]
+struct CostImpactInput {
+    mode: String,
+    item_id: Option<i64>,
+    new_price: Option<f64>,
+    invoice_id: Option<i64>,
+    pct: Option<f64>,
+    category: Option<String>,
+    vendor_id: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct CostImpactArgs {
+    change: CostImpactInput,
+}
+
+#[derive(Serialize)]
+struct ExportCostImpactArgs {
+    change: CostImpactInput,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
+
+#[derive(Serialize, Clone, Default)]
+struct SpendQueryInput {
+    group_by: String,
+    period: String,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    vendor_id: Option<i64>,
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SpendReportArgs {
+    query: SpendQueryInput,
+}
+
+#[derive(Serialize)]
+struct ExportSpendArgs {
+    query: SpendQueryInput,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SpendRow {
+    key: String,
+    label: String,
+    by_period: Vec<f64>,
+    total: f64,
+    share_pct: f64,
+    current: f64,
+    previous: f64,
+    change: f64,
+    change_pct: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct VendorShare {
+    vendor_name: String,
+    total: f64,
+    share_pct: f64,
+    current_share_pct: Option<f64>,
+    previous_share_pct: Option<f64>,
+    top_category: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SpendReport {
+    group_by: String,
+    period: String,
+    date_from: String,
+    date_to: String,
+    periods: Vec<String>,
+    period_totals: Vec<f64>,
+    total: f64,
+    invoice_count: usize,
+    line_count: usize,
+    rows: Vec<SpendRow>,
+    movers: Vec<SpendRow>,
+    vendor_share: Vec<VendorShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct UnitStandardRow {
+    unit_id: i64,
+    name: String,
+    plural: String,
+    unit_type: Option<i64>,
+    unit_kind: Option<i64>,
+    standard: Option<String>,
+    dimension: Option<String>,
+    base_qty: Option<f64>,
+    source: String,
+    aliases: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct StandardMeasure {
+    name: String,
+    dimension: String,
+    base_qty: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct UnitStandardsResponse {
+    units: Vec<UnitStandardRow>,
+    measures: Vec<StandardMeasure>,
+    covered_todo: usize,
+    #[serde(default)]
+    covered_missing: usize,
+    #[serde(default)]
+    reference_densities: bool,
+}
+
+#[derive(Serialize)]
+struct SetReferenceDensitiesArgs {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+struct SetUnitStandardArgs {
+    #[serde(rename = "unitId")]
+    unit_id: i64,
+    standard: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SaveUnitAliasArgs {
+    alias: String,
+    #[serde(rename = "unitId")]
+    unit_id: i64,
+}
+
+#[derive(Serialize)]
+struct DeleteUnitAliasArgs {
+    alias: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct AccountingPeriod {
+    period_id: i64,
+    name: String,
+    start_date: String,
+    end_date: String,
+    opening_session_id: Option<i64>,
+    closing_session_id: Option<i64>,
+    status: String,
+    notes: String,
+}
+
+#[derive(Serialize)]
+struct AccountingPeriodInput {
+    period_id: Option<i64>,
+    name: String,
+    start_date: String,
+    end_date: String,
+    opening_session_id: Option<i64>,
+    closing_session_id: Option<i64>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SaveAccountingPeriodArgs {
+    period: AccountingPeriodInput,
+}
+
+#[derive(Serialize)]
+struct AccountingPeriodIdArgs {
+    #[serde(rename = "periodId")]
+    period_id: i64,
+}
+
+#[derive(Serialize)]
+struct SetPeriodRevenueArgs {
+    #[serde(rename = "periodId")]
+    period_id: i64,
+    category: String,
+    amount: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct SetCogsCategoryArgs {
+    #[serde(rename = "foodCategory")]
+    food_category: String,
+    #[serde(rename = "cogsCategory")]
+    cogs_category: String,
+}
+
+#[derive(Serialize)]
+struct ExportCogsArgs {
+    #[serde(rename = "periodId")]
+    period_id: i64,
+    #[serde(rename = "outputPath")]
+    output_path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CogsLine {
+    category: String,
+    opening: f64,
+    purchases: f64,
+    closing: f64,
+    cogs: f64,
+    revenue: Option<f64>,
+    cost_pct: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CogsMapping {
+    food_category: String,
+    cogs_category: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CogsReport {
+    period: AccountingPeriod,
+    opening_session: Option<String>,
+    closing_session: Option<String>,
+    lines: Vec<CogsLine>,
+    total: CogsLine,
+    mapping: Vec<CogsMapping>,
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ImpactItem {
+    item_id: i64,
+    item_name: String,
+    unit_name: String,
+    old_price: f64,
+    new_price: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct RecipeImpact {
+    recipe_id: i64,
+    recipe_name: String,
+    direct: bool,
+    old_cost: f64,
+    new_cost: f64,
+    cost_change: f64,
+    cost_change_pct: Option<f64>,
+    old_portion_cost: Option<f64>,
+    new_portion_cost: Option<f64>,
+    menu_price: Option<f64>,
+    old_food_cost_pct: Option<f64>,
+    new_food_cost_pct: Option<f64>,
+    food_cost_pct_change: Option<f64>,
+    missing_costs: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct CostImpact {
+    description: String,
+    items: Vec<ImpactItem>,
+    recipes: Vec<RecipeImpact>,
+    skipped: Vec<String>,
+}
+
+fn format_money(value: f64) -> String {
+    format!("${:.2}", value)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trigger_inventory_fetch(
+    query: String,
+    food_category: String,
+    order_by: String,
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_items: WriteSignal<Vec<InventoryItem>>,
+    set_total: WriteSignal<i64>,
+    set_filtered: WriteSignal<i64>,
+    set_loaded: WriteSignal<bool>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading inventory...".to_string());
+
+    spawn_local(async move {
+        let args = to_value(&InventoryQueryArgs {
+            query,
+            food_category,
+            order_by,
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+
+        match invoke_cmd::<InventoryQueryResponse>("search_inventory", args).await {
+            Ok(result) => {
+                set_items.set(result.items);
+                set_total.set(result.total);
+                set_filtered.set(result.filtered);
+                set_status.set("Inventory loaded".to_string());
+                set_loaded.set(true);
+            }
+            Err(err) => {
+                set_status.set(format!("Inventory load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trigger_recipe_fetch(
+    query: String,
+    order_by: String,
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_list: WriteSignal<Vec<RecipeListItem>>,
+    set_total: WriteSignal<i64>,
+    set_filtered: WriteSignal<i64>,
+    set_loaded: WriteSignal<bool>,
+    auto_select: bool,
+    set_detail_loading: WriteSignal<bool>,
+    set_detail_status: WriteSignal<String>,
+    set_selected: WriteSignal<Option<RecipeDetailResponse>>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading recipes...".to_string());
+
+    spawn_local(async move {
+        let args = to_value(&RecipeQueryArgs {
+            query,
+            order_by: if order_by.is_empty() { None } else { Some(order_by) },
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+
+        match invoke_cmd::<RecipeListResponse>("search_recipes", args.clone()).await {
+            Ok(result) => {
+                let first_id = result.recipes.first().map(|r| r.recipe_id);
+                let costs_stale = result.costs_stale;
+                set_list.set(result.recipes);
+                set_total.set(result.total);
+                set_filtered.set(result.filtered);
+                set_status.set("Recipes loaded".to_string());
+                // Costs are recomputed once after a change, then read until the next one.
+                if costs_stale {
+                    set_status.set("Updating recipe costs...".to_string());
+                    let refreshed = match invoke_cmd::<PatchResponse>("refresh_recipe_costs", to_value(&PingArgs {}).unwrap()).await {
+                        Ok(_) => invoke_cmd::<RecipeListResponse>("search_recipes", args).await,
+                        Err(err) => Err(err),
+                    };
+                    match refreshed {
+                        Ok(fresh) => {
+                            set_list.set(fresh.recipes);
+                            set_status.set("Recipes loaded".to_string());
+                        }
+                        Err(err) => set_status.set(format!("Recipe costs not updated: {err}")),
+                    }
+                }
+                set_loaded.set(true);
+                if auto_select {
+                    if let Some(recipe_id) = first_id {
+                        trigger_recipe_detail_fetch(
+                            recipe_id,
+                            set_detail_loading,
+                            set_detail_status,
+                            set_selected,
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                set_status.set(format!("Recipe load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_recipe_detail_fetch(
+    recipe_id: i64,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_selected: WriteSignal<Option<RecipeDetailResponse>>,
+) {
+    set_loading.set(true);
+    set_status.set("Loading recipe...".to_string());
+
+    spawn_local(async move {
+        let args = to_value(&RecipeDetailArgs { recipe_id }).unwrap();
+        match invoke_cmd::<RecipeDetailResponse>("get_recipe_detail", args).await {
+            Ok(result) => {
+                set_selected.set(Some(result));
+                set_status.set("Recipe loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Recipe load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_inventory_detail_fetch(
+    item_id: i64,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_selected: WriteSignal<Option<InventoryDetailResponse>>,
+) {
+    set_loading.set(true);
+    set_status.set("Loading item detail...".to_string());
+    spawn_local(async move {
+        let args = to_value(&InventoryDetailArgs { item_id }).unwrap();
+        match invoke_cmd::<InventoryDetailResponse>("get_inventory_detail", args).await {
+            Ok(result) => {
+                set_selected.set(Some(result));
+                set_status.set("Item detail loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Item detail failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trigger_vendor_fetch(
+    query: String,
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_list: WriteSignal<Vec<VendorListItem>>,
+    set_total: WriteSignal<i64>,
+    set_filtered: WriteSignal<i64>,
+    set_loaded: WriteSignal<bool>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading vendors...".to_string());
+    spawn_local(async move {
+        let args = to_value(&VendorQueryArgs {
+            query,
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<VendorListResponse>("search_vendors", args).await {
+            Ok(result) => {
+                set_list.set(result.vendors);
+                set_total.set(result.total);
+                set_filtered.set(result.filtered);
+                set_status.set("Vendors loaded".to_string());
+                set_loaded.set(true);
+            }
+            Err(err) => {
+                set_status.set(format!("Vendor load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_vendor_detail_fetch(
+    vendor_id: i64,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_selected: WriteSignal<Option<VendorDetailResponse>>,
+) {
+    set_loading.set(true);
+    set_status.set("Loading vendor...".to_string());
+    spawn_local(async move {
+        let args = to_value(&VendorDetailArgs { vendor_id }).unwrap();
+        match invoke_cmd::<VendorDetailResponse>("get_vendor_detail", args).await {
+            Ok(result) => {
+                set_selected.set(Some(result));
+                set_status.set("Vendor loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Vendor load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_vendor_options_fetch(set_options: WriteSignal<Vec<VendorSimple>>) {
+    spawn_local(async move {
+        let args = to_value(&PingArgs {}).unwrap();
+        if let Ok(result) = invoke_cmd::<VendorSimpleResponse>("list_vendors_simple", args).await {
+            set_options.set(result.vendors);
+        }
+    });
+}
+
+fn trigger_vendor_all_options_fetch(set_options: WriteSignal<Vec<VendorSimple>>) {
+    spawn_local(async move {
+        let args = to_value(&PingArgs {}).unwrap();
+        if let Ok(result) = invoke_cmd::<VendorSimpleResponse>("list_vendors_all", args).await {
+            set_options.set(result.vendors);
+        }
+    });
+}
+
+fn trigger_unit_options_fetch(set_options: WriteSignal<Vec<UnitSimple>>) {
+    spawn_local(async move {
+        let args = to_value(&PingArgs {}).unwrap();
+        if let Ok(result) = invoke_cmd::<UnitSimpleResponse>("list_units_simple", args).await {
+            set_options.set(result.units);
+        }
+    });
+}
+
+fn trigger_conversion_overview_fetch(set_overview: WriteSignal<ConversionOverview>) {
+    spawn_local(async move {
+        let args = to_value(&PingArgs {}).unwrap();
+        if let Ok(result) = invoke_cmd::<ConversionOverview>("get_conversion_overview", args).await
+        {
+            set_overview.set(result);
+        }
+    });
+}
+
+fn trigger_conversion_suggestions_fetch(
+    table: String,
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_rows: WriteSignal<Vec<ConversionSuggestionRow>>,
+    set_total: WriteSignal<i64>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading conversion suggestions...".to_string());
+    spawn_local(async move {
+        let args = to_value(&ConversionListArgs {
+            table,
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<ConversionSuggestionResponse>("list_conv_suggestions", args).await {
+            Ok(result) => {
+                set_rows.set(result.rows);
+                set_total.set(result.total);
+                set_status.set("Suggestions loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Suggestions load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_conversion_todo_fetch(
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_rows: WriteSignal<Vec<ConversionTodoRow>>,
+    set_total: WriteSignal<i64>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading conversion todo...".to_string());
+    spawn_local(async move {
+        let args = to_value(&ConversionPageArgs {
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<ConversionTodoResponse>("list_conv_todo", args).await {
+            Ok(result) => {
+                set_rows.set(result.rows);
+                set_total.set(result.total);
+                set_status.set("Todo loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Todo load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_missing_edges_fetch(
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_rows: WriteSignal<Vec<MissingEdgeRow>>,
+    set_total: WriteSignal<i64>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading missing edges...".to_string());
+    spawn_local(async move {
+        let args = to_value(&ConversionPageArgs {
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<MissingEdgeResponse>("list_missing_edges", args).await {
+            Ok(result) => {
+                set_rows.set(result.rows);
+                set_total.set(result.total);
+                set_status.set("Missing edges loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Missing edges load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_missing_purch_fetch(
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_rows: WriteSignal<Vec<MissingPurchRow>>,
+    set_total: WriteSignal<i64>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading missing purchase units...".to_string());
+    spawn_local(async move {
+        let args = to_value(&ConversionPageArgs {
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<MissingPurchResponse>("list_missing_purch_unit", args).await {
+            Ok(result) => {
+                set_rows.set(result.rows);
+                set_total.set(result.total);
+                set_status.set("Missing purchase units loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Missing purchase units load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_missing_data_fetch(
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_rows: WriteSignal<Vec<MissingDataRow>>,
+    set_total: WriteSignal<i64>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading missing data report...".to_string());
+    spawn_local(async move {
+        let args = to_value(&ConversionPageArgs {
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<MissingDataResponse>("list_missing_data_report", args).await {
+            Ok(result) => {
+                set_rows.set(result.rows);
+                set_total.set(result.total);
+                set_status.set("Missing data loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Missing data load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn trigger_invoice_fetch(
+    query: String,
+    vendor_id: Option<i64>,
+    date_from: String,
+    date_to: String,
+    page: usize,
+    limit: usize,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_list: WriteSignal<Vec<InvoiceListItem>>,
+    set_total: WriteSignal<i64>,
+    set_filtered: WriteSignal<i64>,
+    set_loaded: WriteSignal<bool>,
+) {
+    let offset = page * limit;
+    set_loading.set(true);
+    set_status.set("Loading invoices...".to_string());
+    spawn_local(async move {
+        let args = to_value(&InvoiceQueryArgs {
+            query,
+            vendor_id,
+            date_from,
+            date_to,
+            limit: limit as u32,
+            offset: offset as u32,
+        })
+        .unwrap();
+        match invoke_cmd::<InvoiceListResponse>("list_invoices", args).await {
+            Ok(result) => {
+                set_list.set(result.invoices);
+                set_total.set(result.total);
+                set_filtered.set(result.filtered);
+                set_status.set("Invoices loaded".to_string());
+                set_loaded.set(true);
+            }
+            Err(err) => {
+                set_status.set(format!("Invoice load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+
+fn trigger_invoice_detail_fetch(
+    invoice_id: i64,
+    set_loading: WriteSignal<bool>,
+    set_status: WriteSignal<String>,
+    set_selected: WriteSignal<Option<InvoiceDetailResponse>>,
+) {
+    set_loading.set(true);
+    set_status.set("Loading invoice...".to_string());
+    spawn_local(async move {
+        let args = to_value(&InvoiceDetailArgs { invoice_id }).unwrap();
+        match invoke_cmd::<InvoiceDetailResponse>("get_invoice_detail", args).await {
+            Ok(result) => {
+                set_selected.set(Some(result));
+                set_status.set("Invoice loaded".to_string());
+            }
+            Err(err) => {
+                set_status.set(format!("Invoice load failed: {err}"));
+            }
+        }
+        set_loading.set(false);
+    });
+}
+/// Turns raw SQLite column names into human-friendly display names.
+fn humanize_column(raw: &str) -> String {
+    match raw {
+        // ── common ids / names ──
+        "item_id" => "Item ID".into(),
+        "unit_id" => "Unit ID".into(),
+        "vendor_id" => "Vendor ID".into(),
+        "recipe_id" => "Recipe ID".into(),
+        "recipe_group_id" => "Recipe Group".into(),
+        "invoice_id" => "Invoice ID".into(),
+        "trans_id" => "Transaction ID".into(),
+        "recp_item_id" => "Recipe Item ID".into(),
+        "recp_inv_id" => "Recipe Inv. ID".into(),
+        "purch_unit_id" => "Purchase Unit ID".into(),
+        "recipe_unit_id" => "Recipe Unit ID".into(),
+
+        // ── units table ──
+        "sing" => "Singular".into(),
+        "plur" => "Plural".into(),
+        "unit_type" => "Unit Type".into(),
+        "is_whole_unit" => "Whole Unit".into(),
+        "unit_kind" => "Kind".into(),
+
+        // ── items ──
+        "name" => "Name".into(),
+        "item_name" => "Item Name".into(),
+        "recipe_name" => "Recipe Name".into(),
+        "status" => "Status".into(),
+        "raw_len" => "Raw Length".into(),
+
+        // ── convunit ──
+        "unit_id1" => "From Unit".into(),
+        "unit_id2" => "To Unit".into(),
+        "qty1" => "Qty From".into(),
+        "qty2" => "Qty To".into(),
+        "qty" => "Quantity".into(),
+        "is_calculated" => "Calculated".into(),
+
+        // ── inv_units / inv_prices ──
+        "is_default" => "Default".into(),
+        "price" => "Price".into(),
+        "pack" => "Pack".into(),
+
+        // ── conv_suggestions / conv_todo ──
+        "recipe_unit" => "Recipe Unit".into(),
+        "purch_unit" => "Purchase Unit".into(),
+        "hits" => "Hits".into(),
+        "derived_from" => "Derived From".into(),
+        "hops" => "Hops".into(),
+        "path" => "Path".into(),
+        "needed" => "Needed".into(),
+
+        // ── missing_edges ──
+        "usage_count" => "Usage Count".into(),
+
+        // ── missing_data_report ──
+        "missing_a" => "Missing Costs".into(),
+        "missing_b" => "Missing Conversions".into(),
+        "missing_c" => "Missing Units".into(),
+
+        // ── invoices ──
+        "invoice_date" | "trans_date" => "Date".into(),
+        "invoice_no" => "Invoice #".into(),
+        "freight" => "Freight".into(),
+        "total" => "Total".into(),
+        "ext_cost" => "Ext. Cost".into(),
+
+        // ── generic colN placeholders ──
+        other => {
+            if let Some(rest) = other.strip_prefix("col") {
+                if rest.chars().all(|c| c.is_ascii_digit()) {
+                    return format!("Column {rest}");
+                }
+            }
+            // Fallback: replace underscores and title-case
+            other
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        None => String::new(),
+                        Some(c) => {
+                            let mut s = c.to_uppercase().to_string();
+                            s.extend(chars);
+                            s
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+}
+
+#[component]
+pub fn App() -> impl IntoView {
+    let (base_path, set_base_path) = signal("~/exports".to_string());
+    let (import_mode, set_import_mode) = signal("exports".to_string());
+    let (summary, set_summary) = signal(ImportSummary::default());
+    let (has_summary, set_has_summary) = signal(false);
+    let (status, set_status) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (active_panel, set_active_panel) = signal("imports".to_string());
+    let (show_warnings, set_show_warnings) = signal(false);
+
+    let browse_limit: u32 = 100;
+    let (browse_table_name, set_browse_table_name) = signal(String::new());
+    let (browse_data, set_browse_data) = signal(BrowseTableResponse::default());
+    let (browse_loading, set_browse_loading) = signal(false);
+    let (browse_page, set_browse_page) = signal(0u32);
+
+    let inventory_limit: usize = 50;
+    let (inventory_query, set_inventory_query) = signal(String::new());
+    let (inventory_food_category, set_inventory_food_category) = signal(String::new());
+    let (inventory_order_by, set_inventory_order_by) = signal(String::new());
+    let (inventory_items, set_inventory_items) = signal(Vec::<InventoryItem>::new());
+    let (inventory_total, set_inventory_total) = signal(0i64);
+    let (inventory_filtered, set_inventory_filtered) = signal(0i64);
+    let (inventory_page, set_inventory_page) = signal(0usize);
+    let (inventory_status, set_inventory_status) = signal(String::new());
+    let (inventory_loading, set_inventory_loading) = signal(false);
+    let (_inventory_loaded, set_inventory_loaded) = signal(false);
+    let (inventory_selected, set_inventory_selected) =
+        signal(Option::<InventoryDetailResponse>::None);
+    let (inventory_detail_status, set_inventory_detail_status) = signal(String::new());
+    let (inventory_detail_loading, set_inventory_detail_loading) = signal(false);
+
+    let recipe_limit: usize = 25;
+    let (recipe_query, set_recipe_query) = signal(String::new());
+    let (recipe_order_by, set_recipe_order_by) = signal(String::new());
+    let (recipe_list, set_recipe_list) = signal(Vec::<RecipeListItem>::new());
+    let (recipe_total, set_recipe_total) = signal(0i64);
+    let (recipe_filtered, set_recipe_filtered) = signal(0i64);
+    let (recipe_page, set_recipe_page) = signal(0usize);
+    let (recipe_status, set_recipe_status) = signal(String::new());
+    let (recipe_loading, set_recipe_loading) = signal(false);
+    let (recipe_loaded, set_recipe_loaded) = signal(false);
+    let (selected_recipe, set_selected_recipe) = signal(Option::<RecipeDetailResponse>::None);
+    let (recipe_detail_status, set_recipe_detail_status) = signal(String::new());
+    let (recipe_detail_loading, set_recipe_detail_loading) = signal(false);
+    let (tauri_status, set_tauri_status) = signal(String::new());
+    let (ping_status, set_ping_status) = signal(String::new());
+
+    let vendor_limit: usize = 50;
+    let (vendor_query, set_vendor_query) = signal(String::new());
+    let (vendor_list, set_vendor_list) = signal(Vec::<VendorListItem>::new());
+    let (vendor_total, set_vendor_total) = signal(0i64);
+    let (vendor_filtered, set_vendor_filtered) = signal(0i64);
+    let (vendor_page, set_vendor_page) = signal(0usize);
+    let (vendor_status, set_vendor_status) = signal(String::new());
+    let (vendor_loading, set_vendor_loading) = signal(false);
+    let (vendor_loaded, set_vendor_loaded) = signal(false);
+    let (selected_vendor, set_selected_vendor) = signal(Option::<VendorDetailResponse>::None);
+    let (vendor_detail_status, set_vendor_detail_status) = signal(String::new());
+    let (vendor_detail_loading, set_vendor_detail_loading) = signal(false);
+    let (vendor_merge_options, set_vendor_merge_options) = signal(Vec::<VendorSimple>::new());
+    let (vendor_merge_source_id, set_vendor_merge_source_id) = signal(String::new());
+    let (vendor_merge_target_id, set_vendor_merge_target_id) = signal(String::new());
+    let (vendor_merge_status, set_vendor_merge_status) = signal(String::new());
+
+    let conversion_limit: usize = 50;
+    let (conversion_tab, set_conversion_tab) = signal("suggestions".to_string());
+    let (conversion_page, set_conversion_page) = signal(0usize);
+    let (conversion_status, set_conversion_status) = signal(String::new());
+    let (conversion_loading, set_conversion_loading) = signal(false);
+    let (conversion_overview, set_conversion_overview) = signal(ConversionOverview::default());
+    let (conversion_suggestions, set_conversion_suggestions) =
+        signal(Vec::<ConversionSuggestionRow>::new());
+    let (conversion_suggestions_total, set_conversion_suggestions_total) = signal(0i64);
+    let (conversion_suggestions_safe, set_conversion_suggestions_safe) =
+        signal(Vec::<ConversionSuggestionRow>::new());
+    let (conversion_suggestions_safe_total, set_conversion_suggestions_safe_total) = signal(0i64);
+    let (conversion_todo, set_conversion_todo) = signal(Vec::<ConversionTodoRow>::new());
+    let (conversion_todo_total, set_conversion_todo_total) = signal(0i64);
+    let (conversion_missing_edges, set_conversion_missing_edges) =
+        signal(Vec::<MissingEdgeRow>::new());
+    let (conversion_missing_edges_total, set_conversion_missing_edges_total) = signal(0i64);
+    let (review_rows, set_review_rows) = signal(Vec::<ReviewSuggestionRow>::new());
+    let (review_total, set_review_total) = signal(0i64);
+    let (review_selected, set_review_selected) = signal(std::collections::HashSet::<String>::new());
+    let (review_min_confidence, set_review_min_confidence) = signal("70".to_string());
+    let (review_max_hops, set_review_max_hops) = signal(String::new());
+    let (review_category, set_review_category) = signal(String::new());
+    // The last preview or acceptance, and whether it was saved.
+    let (review_impact, set_review_impact) = signal(None::<(AcceptanceImpact, bool)>);
+
+    let report_limit: usize = 50;
+    let (report_page, _set_report_page) = signal(0usize);
+    let (report_status, set_report_status) = signal(String::new());
+    let (_report_loading, set_report_loading) = signal(false);
+    let (missing_data_rows, set_missing_data_rows) = signal(Vec::<MissingDataRow>::new());
+    let (missing_data_total, set_missing_data_total) = signal(0i64);
+    let (missing_purch_rows, set_missing_purch_rows) = signal(Vec::<MissingPurchRow>::new());
+    let (missing_purch_total, set_missing_purch_total) = signal(0i64);
+    let (report_purch_item_id, set_report_purch_item_id) = signal(String::new());
+    let (conv_check, set_conv_check) = signal(None::<ConversionCheckResponse>);
+    let (conv_check_tolerance, set_conv_check_tolerance) = signal("2".to_string());
+    let (conv_check_status, set_conv_check_status) = signal(String::new());
+    let (pack_report, set_pack_report) = signal(None::<PackReport>);
+    let (pack_status, set_pack_status) = signal(String::new());
+    let (nutrition_coverage, set_nutrition_coverage) = signal(None::<NutritionCoverage>);
+    let (nutrition_status, set_nutrition_status) = signal(String::new());
+    let (nutrition_min_match, set_nutrition_min_match) = signal("70".to_string());
+
+    let invoice_limit: usize = 50;
+    let (invoice_query, set_invoice_query) = signal(String::new());
+    let (invoice_list, set_invoice_list) = signal(Vec::<InvoiceListItem>::new());
+    let (invoice_total, set_invoice_total) = signal(0i64);
+    let (invoice_filtered, set_invoice_filtered) = signal(0i64);
+    let (invoice_page, set_invoice_page) = signal(0usize);
+    let (invoice_status, set_invoice_status) = signal(String::new());
+    let (invoice_loading, set_invoice_loading) = signal(false);
+    let (invoice_loaded, set_invoice_loaded) = signal(false);
+    let (selected_invoice, set_selected_invoice) = signal(Option::<InvoiceDetailResponse>::None);
+    let (invoice_detail_status, set_invoice_detail_status) = signal(String::new());
+    let (invoice_detail_loading, set_invoice_detail_loading) = signal(false);
+    let (invoice_vendor_filter, set_invoice_vendor_filter) = signal(String::new());
+    let (invoice_vendor_options, set_invoice_vendor_options) = signal(Vec::<VendorSimple>::new());
+    let (editor_vendor_options, set_editor_vendor_options) = signal(Vec::<VendorSimple>::new());
+    let (unit_options, set_unit_options) = signal(Vec::<UnitSimple>::new());
+    let (invoice_date_from, set_invoice_date_from) = signal(String::new());
+    let (invoice_date_to, set_invoice_date_to) = signal(String::new());
+    let (invoice_export_status, set_invoice_export_status) = signal(String::new());
+    let (invoice_export_path, set_invoice_export_path) = signal(String::new());
+    let (purch_unit_selected_id, set_purch_unit_selected_id) = signal(String::new());
+    let (purch_unit_set_default, set_purch_unit_set_default) = signal(true);
+    let (purch_unit_status, set_purch_unit_status) = signal(String::new());
+    let (manual_price_vendor_id, set_manual_price_vendor_id) = signal(String::new());
+    let (manual_price_value, set_manual_price_value) = signal(String::new());
+    let (manual_price_pack, set_manual_price_pack) = signal(String::new());
+    let (manual_price_status, set_manual_price_status) = signal(String::new());
+
+    let start_import = move || {
+        let path = base_path.get();
+        let mode = import_mode.get();
+        set_busy.set(true);
+        set_status.set(if mode == "mdf" {
+            "Importing from MDF/LDF...".to_string()
+        } else {
+            "Importing exports...".to_string()
+        });
+
+        spawn_local(async move {
+            let result = if mode == "mdf" {
+                let args = to_value(&ImportMdfArgs { mdf_path: path }).unwrap();
+                invoke_cmd::<ImportSummary>("import_from_mdf", args).await
+            } else {
+                let args = to_value(&ImportArgs { base_path: path }).unwrap();
+                invoke_cmd::<ImportSummary>("import_exports", args).await
+            };
+
+            match result {
+                Ok(result) => {
+                    set_summary.set(result);
+                    set_has_summary.set(true);
+                    set_status.set("Import complete".to_string());
+                    set_inventory_loaded.set(false);
+                    set_recipe_loaded.set(false);
+                    set_selected_recipe.set(None);
+                    set_vendor_loaded.set(false);
+                    set_invoice_loaded.set(false);
+                }
+                Err(err) => {
+                    set_status.set(format!("Import failed: {err}"));
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    Effect::new(move |_| {
+        let status = match window() {
+            Some(win) => {
+                if let Ok(tauri_obj) = Reflect::get(&win, &JsValue::from_str("__TAURI__")) {
+                    if tauri_obj.is_undefined() || tauri_obj.is_null() {
+                        "Tauri API missing".to_string()
+                    } else if let Ok(core) = Reflect::get(&tauri_obj, &JsValue::from_str("core")) {
+                        if core.is_undefined() || core.is_null() {
+                            "Tauri core missing".to_string()
+                        } else if let Ok(invoke_fn) =
+                            Reflect::get(&core, &JsValue::from_str("invoke"))
+                        {
+                            if invoke_fn.is_function() {
+                                "Tauri API ready".to_string()
+                            } else {
+                                "Tauri invoke missing".to_string()
+                            }
+                        } else {
+                            "Tauri core missing".to_string()
+                        }
+                    } else {
+                        "Tauri API missing".to_string()
+                    }
+                } else {
+                    "Tauri API missing".to_string()
+                }
+            }
+            None => "Window missing".to_string(),
+        };
+        set_tauri_status.set(status);
+    });
+
+    let ping_backend = move || {
+        set_ping_status.set("Pinging backend...".to_string());
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<PingResponse>("ping_backend", args).await {
+                Ok(resp) => {
+                    set_ping_status.set(format!("Ping: {} ({})", resp.message, resp.ok));
+                }
+                Err(err) => {
+                    set_ping_status.set(format!("Ping failed: {err}"));
+                }
+            }
+        });
+    };
+
+    let open_path = move |path: String| {
+        spawn_local(async move {
+            let args = to_value(&OpenPathArgs { path }).unwrap();
+            let _ = invoke_cmd::<PatchResponse>("open_path", args).await;
+        });
+    };
+
+    let fetch_browse_table = move |table: String, page: u32| {
+        set_browse_loading.set(true);
+        spawn_local(async move {
+            let args = to_value(&BrowseTableArgs {
+                table_name: table,
+                limit: browse_limit,
+                offset: page * browse_limit,
+            })
+            .unwrap();
+            match invoke_cmd::<BrowseTableResponse>("browse_table", args).await {
+                Ok(resp) => {
+                    set_browse_data.set(resp);
+                }
+                Err(_) => {
+                    set_browse_data.set(BrowseTableResponse::default());
+                }
+            }
+            set_browse_loading.set(false);
+        });
+    };
+
+    let toggle_card = move |table: &str| {
+        let table = table.to_string();
+        if browse_table_name.get() == table {
+            set_browse_table_name.set(String::new());
+            set_browse_data.set(BrowseTableResponse::default());
+            set_browse_page.set(0);
+        } else {
+            set_browse_table_name.set(table.clone());
+            set_browse_page.set(0);
+            fetch_browse_table(table, 0);
+        }
+    };
+
+    let show_imports = move || {
+        set_active_panel.set("imports".to_string());
+    };
+
+    let show_inventory = move || {
+        set_active_panel.set("inventory".to_string());
+        set_inventory_food_category.set(String::new());
+        if editor_vendor_options.get().is_empty() {
+            trigger_vendor_options_fetch(set_editor_vendor_options);
+        }
+        if unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_unit_options);
+        }
+        // Always re-fetch when switching modes
+        set_inventory_loaded.set(false);
+        set_inventory_page.set(0);
+        trigger_inventory_fetch(
+            inventory_query.get(),
+            String::new(),
+            inventory_order_by.get(),
+            0,
+            inventory_limit,
+            set_inventory_loading,
+            set_inventory_status,
+            set_inventory_items,
+            set_inventory_total,
+            set_inventory_filtered,
+            set_inventory_loaded,
+        );
+    };
+
+    let show_recipes = move || {
+        set_active_panel.set("recipes".to_string());
+        if unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_unit_options);
+        }
+        if !recipe_loaded.get() {
+            trigger_recipe_fetch(
+                recipe_query.get(),
+                recipe_order_by.get(),
+                recipe_page.get(),
+                recipe_limit,
+                set_recipe_loading,
+                set_recipe_status,
+                set_recipe_list,
+                set_recipe_total,
+                set_recipe_filtered,
+                set_recipe_loaded,
+                selected_recipe.get().is_none(),
+                set_recipe_detail_loading,
+                set_recipe_detail_status,
+                set_selected_recipe,
+            );
+        }
+    };
+
+    let show_vendors = move || {
+        set_active_panel.set("vendors".to_string());
+        if vendor_merge_options.get().is_empty() {
+            trigger_vendor_all_options_fetch(set_vendor_merge_options);
+        }
+        if !vendor_loaded.get() {
+            trigger_vendor_fetch(
+                vendor_query.get(),
+                vendor_page.get(),
+                vendor_limit,
+                set_vendor_loading,
+                set_vendor_status,
+                set_vendor_list,
+                set_vendor_total,
+                set_vendor_filtered,
+                set_vendor_loaded,
+            );
+        }
+    };
+
+    // ── Standard units ──
+    let (unit_standards, set_unit_standards) = signal(Option::<UnitStandardsResponse>::None);
+
+    let load_unit_standards = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<UnitStandardsResponse>("list_unit_standards", args).await {
+                Ok(result) => set_unit_standards.set(Some(result)),
+                Err(err) => set_conversion_status.set(format!("Standard units load failed: {err}")),
+            }
+        });
+    };
+
+    let unit_standard_action = move |cmd: &'static str, args: JsValue| {
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_conversion_status.set(resp.message);
+                    load_unit_standards();
+                    trigger_conversion_overview_fetch(set_conversion_overview);
+                }
+                Err(err) => set_conversion_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let load_review = move || {
+        let min_confidence = review_min_confidence
+            .get()
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|pct| pct / 100.0);
+        let max_hops = review_max_hops.get().trim().parse::<i64>().ok();
+        let category = Some(review_category.get().trim().to_string()).filter(|c| !c.is_empty());
+        spawn_local(async move {
+            let args = to_value(&ReviewConvSuggestionsArgs {
+                min_confidence,
+                max_hops,
+                category,
+            })
+            .unwrap();
+            match invoke_cmd::<ReviewSuggestionsResponse>("review_conv_suggestions", args).await {
+                Ok(result) => {
+                    set_review_selected.update(|selected| {
+                        let keys: std::collections::HashSet<String> =
+                            result.rows.iter().map(|r| r.key()).collect();
+                        selected.retain(|k| keys.contains(k));
+                    });
+                    set_review_total.set(result.total);
+                    set_review_rows.set(result.rows);
+                }
+                Err(err) => set_conversion_status.set(format!("Review load failed: {err}")),
+            }
+        });
+    };
+
+    let show_conversions = move || {
+        set_active_panel.set("conversions".to_string());
+        trigger_conversion_overview_fetch(set_conversion_overview);
+        let tab = conversion_tab.get();
+        let page = conversion_page.get();
+        match tab.as_str() {
+            "suggestions" => trigger_conversion_suggestions_fetch(
+                "conv_suggestions".to_string(),
+                page,
+                conversion_limit,
+                set_conversion_loading,
+                set_conversion_status,
+                set_conversion_suggestions,
+                set_conversion_suggestions_total,
+            ),
+            "safe" => trigger_conversion_suggestions_fetch(
+                "conv_suggestions_safe".to_string(),
+                page,
+                conversion_limit,
+                set_conversion_loading,
+                set_conversion_status,
+                set_conversion_suggestions_safe,
+                set_conversion_suggestions_safe_total,
+            ),
+            "todo" => trigger_conversion_todo_fetch(
+                page,
+                conversion_limit,
+                set_conversion_loading,
+                set_conversion_status,
+                set_conversion_todo,
+                set_conversion_todo_total,
+            ),
+            "missing" => trigger_missing_edges_fetch(
+                page,
+                conversion_limit,
+                set_conversion_loading,
+                set_conversion_status,
+                set_conversion_missing_edges,
+                set_conversion_missing_edges_total,
+            ),
+            "standard" => load_unit_standards(),
+            "review" => load_review(),
+            _ => {}
+        }
+    };
+
+    let run_conversion_check = move || {
+        let tolerance_pct = conv_check_tolerance.get().trim().parse::<f64>().ok();
+        set_conv_check_status.set("Checking conversions...".to_string());
+        spawn_local(async move {
+            let args = to_value(&CheckConversionsArgs { tolerance_pct }).unwrap();
+            match invoke_cmd::<ConversionCheckResponse>("check_conversions", args).await {
+                Ok(check) => {
+                    set_conv_check.set(Some(check));
+                    set_conv_check_status.set(String::new());
+                }
+                Err(err) => set_conv_check_status.set(format!("Check failed: {err}")),
+            }
+        });
+    };
+
+    let fix_conversion_issue = move |row_id: i64, fix: String| {
+        spawn_local(async move {
+            let args = to_value(&FixConversionIssueArgs { row_id, fix }).unwrap();
+            match invoke_cmd::<PatchResponse>("fix_conversion_issue", args).await {
+                Ok(resp) => {
+                    run_conversion_check();
+                    set_conv_check_status.set(resp.message);
+                }
+                Err(err) => set_conv_check_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let load_pack_report = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<PackReport>("get_pack_report", args).await {
+                Ok(report) => set_pack_report.set(Some(report)),
+                Err(err) => set_pack_status.set(format!("Pack report failed: {err}")),
+            }
+        });
+    };
+
+    // "update_pack" with the corrected text, or "reread_packs" for all.
+    let pack_action = move |command: &'static str, args: JsValue| {
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(command, args).await {
+                Ok(resp) => {
+                    set_pack_status.set(resp.message);
+                    load_pack_report();
+                }
+                Err(err) => set_pack_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let load_nutrition_coverage = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<NutritionCoverage>("get_nutrition_coverage", args).await {
+                Ok(coverage) => set_nutrition_coverage.set(Some(coverage)),
+                Err(err) => set_nutrition_status.set(format!("Nutrition coverage failed: {err}")),
+            }
+        });
+    };
+
+    // "import_fdc_foods", "link_item_food", "link_nutrition_matches",
+    // "confirm_nutrition_link" or "clear_item_nutrition".
+    let nutrition_action = move |command: &'static str, args: JsValue| {
+        set_nutrition_status.set("Working...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(command, args).await {
+                Ok(resp) => {
+                    set_nutrition_status.set(resp.message);
+                    load_nutrition_coverage();
+                }
+                Err(err) => set_nutrition_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let import_fdc_foods = move || {
+        spawn_local(async move {
+            let path = match open_file_dialog("Select FoodData Central JSON or CSV File", "FoodData Central", "json,csv").await {
+                Some(path) => path,
+                None => return,
+            };
+            nutrition_action("import_fdc_foods", to_value(&ImportFdcArgs { path }).unwrap());
+        });
+    };
+
+    let link_nutrition_matches = move || {
+        let Some(min_score) = nutrition_min_match.get_untracked().trim().parse::<f64>().ok().filter(|p| *p > 0.0 && *p <= 100.0) else {
+            set_nutrition_status.set("Enter a minimum match between 1 and 100%".to_string());
+            return;
+        };
+        let args = to_value(&LinkNutritionMatchesArgs { min_score: min_score / 100.0 }).unwrap();
+        nutrition_action("link_nutrition_matches", args);
+    };
+
+    let show_reports = move || {
+        set_active_panel.set("reports".to_string());
+        run_conversion_check();
+        load_pack_report();
+        load_nutrition_coverage();
+        if unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_unit_options);
+        }
+        trigger_missing_data_fetch(
+            report_page.get(),
+            report_limit,
+            set_report_loading,
+            set_report_status,
+            set_missing_data_rows,
+            set_missing_data_total,
+        );
+        trigger_missing_purch_fetch(
+            report_page.get(),
+            report_limit,
+            set_report_loading,
+            set_report_status,
+            set_missing_purch_rows,
+            set_missing_purch_total,
+        );
+    };
+
+    // ── Order guide ──
+    let (og_date, set_og_date) = signal(String::new());
+    let (og_theoretical, set_og_theoretical) = signal(false);
+    let (og_status, set_og_status) = signal(String::new());
+    let (og_guide, set_og_guide) = signal(Option::<OrderGuide>::None);
+
+    let run_order_guide = move || {
+        let date = og_date.get_untracked();
+        let args = OrderGuideArgs {
+            order_date: if date.is_empty() { None } else { Some(date) },
+            theoretical: og_theoretical.get_untracked(),
+            vendor_id: None,
+        };
+        set_og_status.set("Building order guide...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<OrderGuide>("get_order_guide", to_value(&args).unwrap()).await {
+                Ok(guide) => {
+                    set_og_status.set(String::new());
+                    set_og_guide.set(Some(guide));
+                }
+                Err(err) => set_og_status.set(format!("Order guide failed: {err}")),
+            }
+        });
+    };
+
+    let export_order_guide = move |vendor_id: Option<i64>, pdf: bool| {
+        let date = og_date.get_untracked();
+        let order_date = if date.is_empty() { None } else { Some(date) };
+        let theoretical = og_theoretical.get_untracked();
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Order Guide PDF", "order-guide.pdf", "PDF", "pdf", "export_order_guide_pdf")
+        } else {
+            ("Save Order Guide CSV", "order-guide.csv", "CSV", "csv", "export_order_guide_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_og_status, move |path| {
+            (
+                cmd.to_string(),
+                to_value(&ExportOrderGuideArgs {
+                    order_date,
+                    theoretical,
+                    vendor_id,
+                    output_path: path,
+                })
+                .unwrap(),
+            )
+        });
+    };
+
+    // ── Purchase orders ──
+    let (po_list, set_po_list) = signal(Vec::<PurchaseOrder>::new());
+    let (po_status_filter, set_po_status_filter) = signal(String::new());
+    let (po_status, set_po_status) = signal(String::new());
+    let (po_detail, set_po_detail) = signal(Option::<PurchaseOrderDetail>::None);
+    let (po_new_vendor, set_po_new_vendor) = signal(String::new());
+    let (po_new_expected, set_po_new_expected) = signal(String::new());
+    let (po_new_notes, set_po_new_notes) = signal(String::new());
+    let (po_item_options, set_po_item_options) = signal(Vec::<ItemSimple>::new());
+    let (po_unit_options, set_po_unit_options) = signal(Vec::<UnitSimple>::new());
+    let (po_line_item, set_po_line_item) = signal(String::new());
+    let (po_line_unit, set_po_line_unit) = signal(String::new());
+    let (po_line_qty, set_po_line_qty) = signal(String::new());
+    let (po_line_price, set_po_line_price) = signal(String::new());
+    let (po_recv_invoice_no, set_po_recv_invoice_no) = signal(String::new());
+    let (po_recv_date, set_po_recv_date) = signal(String::new());
+
+    let load_po_list = move || {
+        let status = po_status_filter.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&ListPurchaseOrdersArgs {
+                status: if status.is_empty() { None } else { Some(status) },
+                vendor_id: None,
+            })
+            .unwrap();
+            match invoke_cmd::<Vec<PurchaseOrder>>("list_purchase_orders", args).await {
+                Ok(list) => set_po_list.set(list),
+                Err(err) => set_po_status.set(format!("Failed to load purchase orders: {err}")),
+            }
+        });
+    };
+
+    let load_po = move |po_id: i64| {
+        spawn_local(async move {
+            let args = to_value(&PoIdArgs { po_id }).unwrap();
+            match invoke_cmd::<PurchaseOrderDetail>("get_purchase_order", args).await {
+                Ok(detail) => set_po_detail.set(Some(detail)),
+                Err(err) => set_po_status.set(format!("Failed to load purchase order: {err}")),
+            }
+        });
+    };
+
+    let open_po = move |po_id: i64| {
+        set_po_recv_invoice_no.set(String::new());
+        set_po_recv_date.set(String::new());
+        load_po(po_id);
+    };
+
+    // Runs a PO mutation, then refreshes the list and the open PO (if it still exists).
+    let run_po_action = move |cmd: &'static str, args: JsValue, po_id: Option<i64>| {
+        set_po_status.set("Saving...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_po_status.set(resp.message);
+                    match po_id {
+                        Some(po_id) => load_po(po_id),
+                        None => set_po_detail.set(None),
+                    }
+                    load_po_list();
+                }
+                Err(err) => set_po_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let create_po = move || {
+        let Ok(vendor_id) = po_new_vendor.get_untracked().parse::<i64>() else {
+            set_po_status.set("Choose a vendor".to_string());
+            return;
+        };
+        let expected = po_new_expected.get_untracked();
+        let notes = po_new_notes.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&CreatePurchaseOrderArgs {
+                vendor_id,
+                expected_date: if expected.is_empty() { None } else { Some(expected) },
+                notes: if notes.trim().is_empty() { None } else { Some(notes) },
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("create_purchase_order", args).await {
+                Ok(resp) => {
+                    set_po_status.set(resp.message);
+                    set_po_new_notes.set(String::new());
+                    let args = to_value(&ListPurchaseOrdersArgs { status: None, vendor_id: None }).unwrap();
+                    if let Ok(list) = invoke_cmd::<Vec<PurchaseOrder>>("list_purchase_orders", args).await {
+                        if let Some(newest) = list.iter().map(|o| o.po_id).max() {
+                            open_po(newest);
+                        }
+                    }
+                    load_po_list();
+                }
+                Err(err) => set_po_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let create_pos_from_guide = move || {
+        let date = og_date.get_untracked();
+        let args = to_value(&CreatePosFromGuideArgs {
+            order_date: if date.is_empty() { None } else { Some(date) },
+            theoretical: og_theoretical.get_untracked(),
+        })
+        .unwrap();
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>("create_pos_from_order_guide", args).await {
+                Ok(resp) => {
+                    set_og_status.set(resp.message);
+                    load_po_list();
+                }
+                Err(err) => set_og_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let add_po_line = move |po_id: i64| {
+        let Ok(item_id) = po_line_item.get_untracked().parse::<i64>() else {
+            set_po_status.set("Choose an item".to_string());
+            return;
+        };
+        let Ok(qty) = po_line_qty.get_untracked().trim().parse::<f64>() else {
+            set_po_status.set("Enter a quantity".to_string());
+            return;
+        };
+        let args = to_value(&SavePoLineArgs {
+            po_id,
+            po_line_id: None,
+            item_id,
+            unit_id: po_line_unit.get_untracked().parse::<i64>().ok(),
+            qty,
+            price: po_line_price.get_untracked().trim().parse::<f64>().ok(),
+        })
+        .unwrap();
+        set_po_line_qty.set(String::new());
+        set_po_line_price.set(String::new());
+        run_po_action("save_po_line", args, Some(po_id));
+    };
+
+    let export_po = move |po_id: i64, pdf: bool| {
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Purchase Order PDF", "purchase-order.pdf", "PDF", "pdf", "export_purchase_order_pdf")
+        } else {
+            ("Save Purchase Order CSV", "purchase-order.csv", "CSV", "csv", "export_purchase_order_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_po_status, move |path| {
+            (
+                cmd.to_string(),
+                to_value(&ExportPurchaseOrderArgs { po_id, output_path: path }).unwrap(),
+            )
+        });
+    };
+
+    // ── Vendor bids ──
+    let (bid_list, set_bid_list) = signal(Vec::<VendorBid>::new());
+    let (bid_status_filter, set_bid_status_filter) = signal(String::new());
+    let (bid_status, set_bid_status) = signal(String::new());
+    let (bid_compare, set_bid_compare) = signal(Vec::<BidComparison>::new());
+    let (bid_compare_date, set_bid_compare_date) = signal(String::new());
+    let (bid_category, set_bid_category) = signal(String::new());
+    let (bid_req_vendors, set_bid_req_vendors) = signal(Vec::<i64>::new());
+    let (bid_valid_from, set_bid_valid_from) = signal(String::new());
+    let (bid_valid_to, set_bid_valid_to) = signal(String::new());
+    let (bid_new_vendor, set_bid_new_vendor) = signal(String::new());
+    let (bid_new_item, set_bid_new_item) = signal(String::new());
+    let (bid_new_price, set_bid_new_price) = signal(String::new());
+    let (bid_new_unit, set_bid_new_unit) = signal(String::new());
+    let (bid_new_pack, set_bid_new_pack) = signal(String::new());
+
+    let load_bids = move || {
+        let status = bid_status_filter.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&ListVendorBidsArgs {
+                item_id: None,
+                vendor_id: None,
+                status: if status.is_empty() { None } else { Some(status) },
+            })
+            .unwrap();
+            match invoke_cmd::<Vec<VendorBid>>("list_vendor_bids", args).await {
+                Ok(list) => set_bid_list.set(list),
+                Err(err) => set_bid_status.set(format!("Failed to load bids: {err}")),
+            }
+        });
+    };
+
+    let run_bid_comparison = move || {
+        let date = bid_compare_date.get_untracked();
+        let category = bid_category.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&BidComparisonArgs {
+                on_date: if date.is_empty() { None } else { Some(date) },
+                category: if category.trim().is_empty() { None } else { Some(category) },
+            })
+            .unwrap();
+            match invoke_cmd::<Vec<BidComparison>>("get_bid_comparison", args).await {
+                Ok(rows) => set_bid_compare.set(rows),
+                Err(err) => set_bid_status.set(format!("Comparison failed: {err}")),
+            }
+        });
+    };
+
+    // Runs a bid mutation, then refreshes the list and the comparison.
+    let run_bid_action = move |cmd: &'static str, args: JsValue| {
+        set_bid_status.set("Saving...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_bid_status.set(resp.message);
+                    load_bids();
+                    run_bid_comparison();
+                }
+                Err(err) => set_bid_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let import_bid_sheet = move || {
+        let from = bid_valid_from.get_untracked();
+        let to = bid_valid_to.get_untracked();
+        spawn_local(async move {
+            let path = match open_file_dialog("Select Returned Bid Sheet", "CSV", "csv").await {
+                Some(path) => path,
+                None => return,
+            };
+            let args = to_value(&ImportBidSheetArgs {
+                csv_path: path,
+                valid_from: if from.is_empty() { None } else { Some(from) },
+                valid_to: if to.is_empty() { None } else { Some(to) },
+            })
+            .unwrap();
+            run_bid_action("import_bid_sheet", args);
+        });
+    };
+
+    let add_bid = move || {
+        let (Ok(vendor_id), Ok(item_id)) = (
+            bid_new_vendor.get_untracked().parse::<i64>(),
+            bid_new_item.get_untracked().parse::<i64>(),
+        ) else {
+            set_bid_status.set("Choose a vendor and an item".to_string());
+            return;
+        };
+        let Ok(price) = bid_new_price.get_untracked().trim().trim_start_matches('$').parse::<f64>() else {
+            set_bid_status.set("Enter the quoted price".to_string());
+            return;
+        };
+        let from = bid_valid_from.get_untracked();
+        let to = bid_valid_to.get_untracked();
+        let args = to_value(&SaveVendorBidArgs {
+            bid: VendorBidInput {
+                bid_id: None,
+                vendor_id,
+                item_id,
+                price,
+                unit_id: bid_new_unit.get_untracked().parse::<i64>().ok(),
+                pack: Some(bid_new_pack.get_untracked()),
+                valid_from: if from.is_empty() { None } else { Some(from) },
+                valid_to: if to.is_empty() { None } else { Some(to) },
+            },
+        })
+        .unwrap();
+        set_bid_new_price.set(String::new());
+        set_bid_new_pack.set(String::new());
+        run_bid_action("save_vendor_bid", args);
+    };
+
+    let export_bid_request = move |pdf: bool| {
+        let vendor_ids = bid_req_vendors.get_untracked();
+        if vendor_ids.is_empty() {
+            set_bid_status.set("Tick the vendors to ask for quotes".to_string());
+            return;
+        }
+        let category = bid_category.get_untracked();
+        let category = if category.trim().is_empty() { None } else { Some(category) };
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Bid Request PDF", "bid-request.pdf", "PDF", "pdf", "export_bid_request_pdf")
+        } else {
+            ("Save Bid Request CSV", "bid-request.csv", "CSV", "csv", "export_bid_request_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_bid_status, move |path| {
+            (
+                cmd.to_string(),
+                to_value(&ExportBidRequestArgs { vendor_ids, category, output_path: path }).unwrap(),
+            )
+        });
+    };
+
+    let show_bids = move || {
+        set_active_panel.set("bids".to_string());
+        if invoice_vendor_options.get().is_empty() {
+            trigger_vendor_options_fetch(set_invoice_vendor_options);
+        }
+        if po_item_options.get().is_empty() {
+            spawn_local(async move {
+                let args = to_value(&PingArgs {}).unwrap();
+                if let Ok(result) = invoke_cmd::<ItemSimpleResponse>("list_items_simple", args).await {
+                    set_po_item_options.set(result.items);
+                }
+            });
+        }
+        if po_unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_po_unit_options);
+        }
+        load_bids();
+        run_bid_comparison();
+    };
+
+    // ── Invoice charge state ──
+    let (ic_kind, set_ic_kind) = signal("freight".to_string());
+    let (ic_desc, set_ic_desc) = signal(String::new());
+    let (ic_amount, set_ic_amount) = signal(String::new());
+    let (ic_alloc, set_ic_alloc) = signal("default".to_string());
+    let (ic_status, set_ic_status) = signal(String::new());
+
+    let run_charge_action = move |cmd: &'static str, args: JsValue, invoice_id: i64| {
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_ic_status.set(resp.message);
+                    trigger_invoice_detail_fetch(
+                        invoice_id,
+                        set_invoice_detail_loading,
+                        set_invoice_detail_status,
+                        set_selected_invoice,
+                    );
+                }
+                Err(err) => set_ic_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let add_charge = move |invoice_id: i64| {
+        let Ok(amount) = ic_amount.get_untracked().trim().parse::<f64>() else {
+            set_ic_status.set("Enter the charge amount".to_string());
+            return;
+        };
+        let description = ic_desc.get_untracked();
+        let args = to_value(&SaveInvoiceChargeArgs {
+            charge: InvoiceChargeInput {
+                charge_id: None,
+                invoice_id,
+                kind: ic_kind.get_untracked(),
+                description: if description.trim().is_empty() { None } else { Some(description) },
+                amount,
+                allocation: Some(ic_alloc.get_untracked()),
+            },
+        })
+        .unwrap();
+        set_ic_desc.set(String::new());
+        set_ic_amount.set(String::new());
+        run_charge_action("save_invoice_charge", args, invoice_id);
+    };
+
+    // ── Credit memo state ──
+    let (cm_reason, set_cm_reason) = signal("short".to_string());
+    let (cm_line, set_cm_line) = signal(String::new());
+    let (cm_qty, set_cm_qty) = signal(String::new());
+    let (cm_amount, set_cm_amount) = signal(String::new());
+    let (cm_no, set_cm_no) = signal(String::new());
+    let (cm_note, set_cm_note) = signal(String::new());
+    let (cm_status, set_cm_status) = signal(String::new());
+
+    let run_credit_action = move |cmd: &'static str, args: JsValue, invoice_id: i64| {
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_cm_status.set(resp.message);
+                    trigger_invoice_detail_fetch(
+                        invoice_id,
+                        set_invoice_detail_loading,
+                        set_invoice_detail_status,
+                        set_selected_invoice,
+                    );
+                }
+                Err(err) => set_cm_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let add_credit = move |invoice_id: i64| {
+        let text = |s: ReadSignal<String>| {
+            let v = s.get_untracked().trim().to_string();
+            if v.is_empty() { None } else { Some(v) }
+        };
+        let args = to_value(&SaveCreditMemoArgs {
+            credit: CreditMemoInput {
+                invoice_id,
+                memo_no: text(cm_no),
+                memo_date: None,
+                reason: cm_reason.get_untracked(),
+                amount: cm_amount.get_untracked().trim().parse::<f64>().ok(),
+                trans_id: cm_line.get_untracked().parse::<i64>().ok(),
+                qty: cm_qty.get_untracked().trim().parse::<f64>().ok(),
+                note: text(cm_note),
+            },
+        })
+        .unwrap();
+        set_cm_qty.set(String::new());
+        set_cm_amount.set(String::new());
+        set_cm_no.set(String::new());
+        set_cm_note.set(String::new());
+        run_credit_action("save_credit_memo", args, invoice_id);
+    };
+
+    // ── Invoice reconciliation state ──
+    let (recon_filter, set_recon_filter) = signal("unbalanced".to_string());
+    let (recon_list, set_recon_list) = signal(Option::<ReconciliationResponse>::None);
+    let (recon_status, set_recon_status) = signal(String::new());
+
+    let load_recon = move || {
+        let filter = recon_filter.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&ListReconciliationArgs {
+                status: if filter.is_empty() { None } else { Some(filter) },
+            })
+            .unwrap();
+            match invoke_cmd::<ReconciliationResponse>("list_invoice_reconciliation", args).await {
+                Ok(list) => set_recon_list.set(Some(list)),
+                Err(err) => set_recon_status.set(format!("Failed to load reconciliation: {err}")),
+            }
+        });
+    };
+
+    let run_recon = move || {
+        set_recon_status.set("Reconciling invoices...".to_string());
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<PatchResponse>("run_reconciliation", args).await {
+                Ok(resp) => {
+                    set_recon_status.set(resp.message);
+                    load_recon();
+                }
+                Err(err) => set_recon_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let mark_reviewed = move |invoice_id: i64, reviewed: bool, note: String| {
+        spawn_local(async move {
+            let args = to_value(&SetInvoiceReviewedArgs {
+                invoice_id,
+                reviewed,
+                note: Some(note),
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("set_invoice_reviewed", args).await {
+                Ok(resp) => {
+                    set_recon_status.set(resp.message);
+                    load_recon();
+                }
+                Err(err) => set_recon_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    // ── Price alert state ──
+    let (alert_filter, set_alert_filter) = signal("new".to_string());
+    let (alert_list, set_alert_list) = signal(Option::<PriceAlertsResponse>::None);
+    let (alert_status, set_alert_status) = signal(String::new());
+
+    let load_alerts = move || {
+        let filter = alert_filter.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&ListPriceAlertsArgs {
+                status: if filter.is_empty() { None } else { Some(filter) },
+            })
+            .unwrap();
+            match invoke_cmd::<PriceAlertsResponse>("list_price_alerts", args).await {
+                Ok(list) => set_alert_list.set(Some(list)),
+                Err(err) => set_alert_status.set(format!("Failed to load price alerts: {err}")),
+            }
+        });
+    };
+
+    let resolve_alert = move |alert_id: i64, status: &'static str, note: String| {
+        spawn_local(async move {
+            let args = to_value(&SetPriceAlertStatusArgs {
+                alert_id,
+                status: status.to_string(),
+                note: Some(note),
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("set_price_alert_status", args).await {
+                Ok(resp) => {
+                    set_alert_status.set(resp.message);
+                    load_alerts();
+                }
+                Err(err) => set_alert_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    // ── Recipe cost impact state ──
+    let (ci_mode, set_ci_mode) = signal("item".to_string());
+    let (ci_item, set_ci_item) = signal(String::new());
+    let (ci_price, set_ci_price) = signal(String::new());
+    let (ci_invoice, set_ci_invoice) = signal(Option::<(i64, String)>::None);
+    let (ci_pct, set_ci_pct) = signal(String::new());
+    let (ci_category, set_ci_category) = signal(String::new());
+    let (ci_vendor, set_ci_vendor) = signal(String::new());
+    let (ci_result, set_ci_result) = signal(Option::<CostImpact>::None);
+    let (ci_last, set_ci_last) = signal(Option::<CostImpactInput>::None);
+    let (ci_status, set_ci_status) = signal(String::new());
+
+    let run_impact = move |change: CostImpactInput| {
+        set_ci_status.set("Costing recipes...".to_string());
+        set_ci_last.set(Some(change.clone()));
+        spawn_local(async move {
+            let args = to_value(&CostImpactArgs { change }).unwrap();
+            match invoke_cmd::<CostImpact>("get_cost_impact", args).await {
+                Ok(result) => {
+                    set_ci_status.set(String::new());
+                    set_ci_result.set(Some(result));
+                }
+                Err(err) => {
+                    set_ci_status.set(format!("Error: {err}"));
+                    set_ci_result.set(None);
+                }
+            }
+        });
+    };
+
+    let run_impact_form = move || {
+        let mode = ci_mode.get_untracked();
+        let mut change = CostImpactInput { mode: mode.clone(), ..Default::default() };
+        match mode.as_str() {
+            "item" => {
+                change.item_id = ci_item.get_untracked().parse::<i64>().ok();
+                change.new_price = ci_price.get_untracked().trim().parse::<f64>().ok();
+            }
+            "invoice" => change.invoice_id = ci_invoice.get_untracked().map(|(id, _)| id),
+            _ => {
+                change.pct = ci_pct.get_untracked().trim().parse::<f64>().ok();
+                let category = ci_category.get_untracked();
+                change.category = if category.trim().is_empty() { None } else { Some(category) };
+                change.vendor_id = ci_vendor.get_untracked().parse::<i64>().ok();
+            }
+        }
+        run_impact(change);
+    };
+
+    let impact_for_invoice = move |invoice_id: i64, label: String| {
+        set_ci_mode.set("invoice".to_string());
+        set_ci_invoice.set(Some((invoice_id, label)));
+        run_impact(CostImpactInput {
+            mode: "invoice".to_string(),
+            invoice_id: Some(invoice_id),
+            ..Default::default()
+        });
+    };
+
+    let export_impact = move |pdf: bool| {
+        let Some(change) = ci_last.get_untracked() else {
+            set_ci_status.set("Run an impact analysis first".to_string());
+            return;
+        };
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Cost Impact PDF", "cost-impact.pdf", "PDF", "pdf", "export_cost_impact_pdf")
+        } else {
+            ("Save Cost Impact CSV", "cost-impact.csv", "CSV", "csv", "export_cost_impact_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_ci_status, move |path| {
+            (cmd.to_string(), to_value(&ExportCostImpactArgs { change, output_path: path }).unwrap())
+        });
+    };
+
+    // ── Spend analytics state ──
+    let (sp_group, set_sp_group) = signal("vendor".to_string());
+    let (sp_period, set_sp_period) = signal("month".to_string());
+    let (sp_from, set_sp_from) = signal(String::new());
+    let (sp_to, set_sp_to) = signal(String::new());
+    let (sp_vendor, set_sp_vendor) = signal(String::new());
+    let (sp_category, set_sp_category) = signal(String::new());
+    let (sp_result, set_sp_result) = signal(Option::<SpendReport>::None);
+    let (sp_status, set_sp_status) = signal(String::new());
+
+    let spend_query = move || {
+        let text = |s: ReadSignal<String>| {
+            let v = s.get_untracked().trim().to_string();
+            if v.is_empty() { None } else { Some(v) }
+        };
+        SpendQueryInput {
+            group_by: sp_group.get_untracked(),
+            period: sp_period.get_untracked(),
+            date_from: text(sp_from),
+            date_to: text(sp_to),
+            vendor_id: sp_vendor.get_untracked().parse::<i64>().ok(),
+            category: text(sp_category),
+        }
+    };
+
+    let run_spend = move || {
+        set_sp_status.set("Totalling spend...".to_string());
+        let query = spend_query();
+        spawn_local(async move {
+            let args = to_value(&SpendReportArgs { query }).unwrap();
+            match invoke_cmd::<SpendReport>("get_spend_report", args).await {
+                Ok(report) => {
+                    set_sp_status.set(if report.line_count == 0 {
+                        "No invoice lines in this range.".to_string()
+                    } else {
+                        String::new()
+                    });
+                    set_sp_result.set(Some(report));
+                }
+                Err(err) => {
+                    set_sp_status.set(format!("Error: {err}"));
+                    set_sp_result.set(None);
+                }
+            }
+        });
+    };
+
+    let export_spend = move |pdf: bool| {
+        let query = spend_query();
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Spend PDF", "spend-analytics.pdf", "PDF", "pdf", "export_spend_pdf")
+        } else {
+            ("Save Spend CSV", "spend-analytics.csv", "CSV", "csv", "export_spend_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_sp_status, move |path| {
+            (cmd.to_string(), to_value(&ExportSpendArgs { query, output_path: path }).unwrap())
+        });
+    };
+
+    let show_purchasing = move || {
+        set_active_panel.set("purchasing".to_string());
+        load_alerts();
+        load_recon();
+        if invoice_vendor_options.get().is_empty() {
+            trigger_vendor_options_fetch(set_invoice_vendor_options);
+        }
+        if po_item_options.get().is_empty() {
+            spawn_local(async move {
+                let args = to_value(&PingArgs {}).unwrap();
+                if let Ok(result) = invoke_cmd::<ItemSimpleResponse>("list_items_simple", args).await {
+                    set_po_item_options.set(result.items);
+                }
+            });
+        }
+        if po_unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_po_unit_options);
+        }
+        load_po_list();
+        if !invoice_loaded.get() {
+            trigger_invoice_fetch(
+                invoice_query.get(),
+                invoice_vendor_filter.get().trim().parse::<i64>().ok(),
+                invoice_date_from.get(),
+                invoice_date_to.get(),
+                invoice_page.get(),
+                invoice_limit,
+                set_invoice_loading,
+                set_invoice_status,
+                set_invoice_list,
+                set_invoice_total,
+                set_invoice_filtered,
+                set_invoice_loaded,
+            );
+        }
+    };
+
+    // ── Food Cost state ──
+    let (fc_item_options, set_fc_item_options) = signal(Vec::<ItemSimple>::new());
+    let (fc_unit_options, set_fc_unit_options) = signal(Vec::<UnitSimple>::new());
+    let (fc_lines, set_fc_lines) = signal(Vec::<FoodCostLineInput>::new());
+    let (fc_result, set_fc_result) = signal(Option::<FoodCostResponse>::None);
+    let (fc_target_pct, set_fc_target_pct) = signal(30.0f64);
+    let (fc_status, set_fc_status) = signal(String::new());
+    let (fc_loading, set_fc_loading) = signal(false);
+    let (fc_add_item_id, set_fc_add_item_id) = signal(String::new());
+    let (fc_add_unit_id, set_fc_add_unit_id) = signal(String::new());
+    let (fc_add_qty, set_fc_add_qty) = signal(String::new());
+    let (fc_dish_name, set_fc_dish_name) = signal(String::new());
+
+    // ── Settings state ──
+    let (settings_company, set_settings_company) = signal(String::new());
+    let (settings_logo_path, set_settings_logo_path) = signal(String::new());
+    let (settings_service_cat, set_settings_service_cat) = signal(String::new());
+    let (settings_op_size, set_settings_op_size) = signal(String::new());
+    let (settings_target_pct, set_settings_target_pct) = signal(String::new());
+    let (settings_price_rounding, set_settings_price_rounding) = signal(String::new());
+    let (settings_alert_pct, set_settings_alert_pct) = signal(String::new());
+    let (settings_alloc_method, set_settings_alloc_method) = signal("value".to_string());
+    let (settings_tax_cost, set_settings_tax_cost) = signal(false);
+    let (settings_use_landed, set_settings_use_landed) = signal(false);
+    let (settings_status, set_settings_status) = signal(String::new());
+    let (settings_loaded, set_settings_loaded) = signal(false);
+    let (settings_logo_upload_path, set_settings_logo_upload_path) = signal(String::new());
+
+    let load_settings = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<SettingsResponse>("get_settings", args).await {
+                Ok(s) => {
+                    set_settings_company.set(s.company_name);
+                    set_settings_logo_path.set(s.logo_path);
+                    set_settings_service_cat.set(s.service_category);
+                    set_settings_op_size.set(s.operation_size);
+                    if let Ok(pct) = s.target_food_cost_pct.trim().parse::<f64>() {
+                        set_fc_target_pct.set(pct);
+                    }
+                    set_settings_target_pct.set(s.target_food_cost_pct);
+                    set_settings_price_rounding.set(s.price_rounding);
+                    set_settings_alert_pct.set(s.price_alert_pct);
+                    set_settings_alloc_method.set(s.landed_alloc_method);
+                    set_settings_tax_cost.set(s.landed_include_tax);
+                    set_settings_use_landed.set(s.costing_use_landed);
+                    set_settings_loaded.set(true);
+                }
+                Err(err) => {
+                    set_settings_status.set(format!("Failed to load settings: {err}"));
+                }
+            }
+        });
+    };
+
+    let show_settings = move || {
+        set_active_panel.set("settings".to_string());
+        if !settings_loaded.get() {
+            load_settings();
+        }
+    };
+
+    let show_fda = move || {
+        set_active_panel.set("fda".to_string());
+    };
+
+    let save_settings_action = move || {
+        let company_name = settings_company.get();
+        let service_category = settings_service_cat.get();
+        let operation_size = settings_op_size.get();
+        let target_food_cost_pct = Some(settings_target_pct.get());
+        let price_rounding = Some(settings_price_rounding.get());
+        let purchasing = Some(PurchasingSettings {
+            price_alert_pct: settings_alert_pct.get(),
+            landed_alloc_method: settings_alloc_method.get(),
+            landed_include_tax: settings_tax_cost.get(),
+            costing_use_landed: settings_use_landed.get(),
+        });
+        set_settings_status.set("Saving...".to_string());
+        spawn_local(async move {
+            let args = to_value(&SaveSettingsArgs {
+                company_name,
+                service_category,
+                operation_size,
+                target_food_cost_pct,
+                price_rounding,
+                purchasing,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("save_settings", args).await {
+                Ok(resp) => set_settings_status.set(resp.message),
+                Err(err) => set_settings_status.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let upload_logo_action = move || {
+        let source = settings_logo_upload_path.get();
+        if source.trim().is_empty() {
+            set_settings_status.set("Enter a logo file path".to_string());
+            return;
+        }
+        set_settings_status.set("Uploading logo...".to_string());
+        spawn_local(async move {
+            let args = to_value(&UploadLogoArgs { source_path: source }).unwrap();
+            match invoke_cmd::<PatchResponse>("upload_logo", args).await {
+                Ok(resp) => {
+                    set_settings_status.set(resp.message);
+                    // reload to get the new path
+                    let args2 = to_value(&PingArgs {}).unwrap();
+                    if let Ok(s) = invoke_cmd::<SettingsResponse>("get_settings", args2).await {
+                        set_settings_logo_path.set(s.logo_path);
+                    }
+                    set_settings_logo_upload_path.set(String::new());
+                }
+                Err(err) => set_settings_status.set(format!("Upload failed: {err}")),
+            }
+        });
+    };
+
+    let remove_logo_action = move || {
+        set_settings_status.set("Removing logo...".to_string());
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<PatchResponse>("remove_logo", args).await {
+                Ok(resp) => {
+                    set_settings_status.set(resp.message);
+                    set_settings_logo_path.set(String::new());
+                }
+                Err(err) => set_settings_status.set(format!("Remove failed: {err}")),
+            }
+        });
+    };
+
+    let show_foodcost = move || {
+        set_active_panel.set("foodcost".to_string());
+        if fc_item_options.get().is_empty() {
+            spawn_local(async move {
+                let args = to_value(&PingArgs {}).unwrap();
+                if let Ok(result) = invoke_cmd::<ItemSimpleResponse>("list_items_simple", args).await {
+                    set_fc_item_options.set(result.items);
+                }
+            });
+        }
+        if fc_unit_options.get().is_empty() {
+            trigger_unit_options_fetch(set_fc_unit_options);
+        }
+    };
+
+    let fc_add_line = move || {
+        let item_id = match fc_add_item_id.get().trim().parse::<i64>() {
+            Ok(v) => v,
+            Err(_) => {
+                set_fc_status.set("Select an ingredient".to_string());
+                return;
+            }
+        };
+        let unit_id: Option<i64> = fc_add_unit_id.get().trim().parse().ok();
+        let qty: f64 = fc_add_qty.get().trim().parse().unwrap_or(0.0);
+        if qty <= 0.0 {
+            set_fc_status.set("Enter a quantity > 0".to_string());
+            return;
+        }
+        let mut current = fc_lines.get();
+        current.push(FoodCostLineInput { item_id, unit_id, qty });
+        set_fc_lines.set(current);
+        set_fc_add_item_id.set(String::new());
+        set_fc_add_unit_id.set(String::new());
+        set_fc_add_qty.set(String::new());
+        set_fc_status.set(String::new());
+    };
+
+    let fc_remove_line = move |idx: usize| {
+        let mut current = fc_lines.get();
+        if idx < current.len() {
+            current.remove(idx);
+            set_fc_lines.set(current);
+            set_fc_result.set(None);
+        }
+    };
+
+    let fc_calculate = move || {
+        let lines = fc_lines.get();
+        if lines.is_empty() {
+            set_fc_status.set("Add at least one ingredient".to_string());
+            return;
+        }
+        set_fc_loading.set(true);
+        set_fc_status.set("Calculating...".to_string());
+        spawn_local(async move {
+            let args = to_value(&CalculateFoodCostArgs { lines }).unwrap();
+            match invoke_cmd::<FoodCostResponse>("calculate_food_cost", args).await {
+                Ok(result) => {
+                    set_fc_result.set(Some(result));
+                    set_fc_status.set("Done".to_string());
+                }
+                Err(err) => {
+                    set_fc_status.set(format!("Calculation failed: {err}"));
+                }
+            }
+            set_fc_loading.set(false);
+        });
+    };
+
+    let fc_clear = move || {
+        set_fc_lines.set(Vec::new());
+        set_fc_result.set(None);
+        set_fc_status.set(String::new());
+        set_fc_dish_name.set(String::new());
+    };
+
+    let export_invoices = move || {
+        let query = invoice_query.get();
+        let vendor_id = invoice_vendor_filter.get().trim().parse::<i64>().ok();
+        let date_from = invoice_date_from.get();
+        let date_to = invoice_date_to.get();
+        set_invoice_export_status.set("Exporting...".to_string());
+        spawn_local(async move {
+            let args = to_value(&ExportInvoiceArgs {
+                query,
+                vendor_id,
+                date_from,
+                date_to,
+                output_path: None,
+            })
+            .unwrap();
+            match invoke_cmd::<ExportCsvResponse>("export_invoice_lines_csv", args).await {
+                Ok(result) => {
+                    set_invoice_export_path.set(result.path.clone());
+                    set_invoice_export_status.set(format!("Exported {} rows", result.rows));
+                }
+                Err(err) => {
+                    set_invoice_export_status.set(format!("Export failed: {err}"));
+                }
+            }
+        });
+    };
+
+    let approve_conversion = move |row: ConversionSuggestionRow| {
+        let base_path = base_path.get();
+        set_conversion_status.set("Saving patch...".to_string());
+        spawn_local(async move {
+            let args = to_value(&PatchConvunitArgs {
+                base_path,
+                item_id: row.item_id,
+                vendor_id: row.vendor_id,
+                unit_id1: row.unit_id1,
+                unit_id2: row.unit_id2,
+                qty1: row.qty1,
+                qty2: row.qty2,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("patch_convunit", args).await {
+                Ok(resp) => {
+                    set_conversion_status.set(resp.message);
+                    trigger_conversion_overview_fetch(set_conversion_overview);
+                    let table = conversion_tab.get();
+                    let page = conversion_page.get();
+                    match table.as_str() {
+                        "suggestions" => trigger_conversion_suggestions_fetch(
+                            "conv_suggestions".to_string(),
+                            page,
+                            conversion_limit,
+                            set_conversion_loading,
+                            set_conversion_status,
+                            set_conversion_suggestions,
+                            set_conversion_suggestions_total,
+                        ),
+                        "safe" => trigger_conversion_suggestions_fetch(
+                            "conv_suggestions_safe".to_string(),
+                            page,
+                            conversion_limit,
+                            set_conversion_loading,
+                            set_conversion_status,
+                            set_conversion_suggestions_safe,
+                            set_conversion_suggestions_safe_total,
+                        ),
+                        "todo" => trigger_conversion_todo_fetch(
+                            page,
+                            conversion_limit,
+                            set_conversion_loading,
+                            set_conversion_status,
+                            set_conversion_todo,
+                            set_conversion_todo_total,
+                        ),
+                        "missing" => trigger_missing_edges_fetch(
+                            page,
+                            conversion_limit,
+                            set_conversion_loading,
+                            set_conversion_status,
+                            set_conversion_missing_edges,
+                            set_conversion_missing_edges_total,
+                        ),
+                        _ => {}
+                    }
+                }
+                Err(err) => set_conversion_status.set(format!("Patch failed: {err}")),
+            }
+        });
+    };
+
+    let selected_review_keys = move || {
+        let selected = review_selected.get();
+        review_rows
+            .get()
+            .into_iter()
+            .filter(|row| selected.contains(&row.key()))
+            .map(|row| SuggestionKeyArg {
+                item_id: row.item_id,
+                vendor_id: row.vendor_id,
+                unit_id1: row.unit_id1,
+                unit_id2: row.unit_id2,
+                derived_from: row.derived_from,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // "preview" leaves the data as it is; "accept" saves the conversions.
+    let run_review_acceptance = move |save: bool| {
+        let suggestions = selected_review_keys();
+        if suggestions.is_empty() {
+            set_conversion_status.set("Select suggestions first".to_string());
+            return;
+        }
+        set_conversion_status.set(if save {
+            "Accepting suggestions...".to_string()
+        } else {
+            "Costing recipes with the selected suggestions...".to_string()
+        });
+        spawn_local(async move {
+            let args = to_value(&SuggestionKeysArgs { suggestions }).unwrap();
+            let command = if save { "accept_conv_suggestions" } else { "preview_conv_suggestions" };
+            match invoke_cmd::<AcceptanceImpact>(command, args).await {
+                Ok(impact) => {
+                    set_conversion_status.set(if save {
+                        format!(
+                            "Accepted {} conversions; {} recipes are now fully costed",
+                            impact.accepted, impact.newly_complete
+                        )
+                    } else {
+                        String::new()
+                    });
+                    set_review_impact.set(Some((impact, save)));
+                    if save {
+                        set_review_selected.set(Default::default());
+                        trigger_conversion_overview_fetch(set_conversion_overview);
+                        load_review();
+                    }
+                }
+                Err(err) => set_conversion_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let reject_review = move || {
+        let suggestions = selected_review_keys();
+        if suggestions.is_empty() {
+            set_conversion_status.set("Select suggestions first".to_string());
+            return;
+        }
+        spawn_local(async move {
+            let args = to_value(&SuggestionKeysArgs { suggestions }).unwrap();
+            match invoke_cmd::<PatchResponse>("reject_conv_suggestions", args).await {
+                Ok(resp) => {
+                    set_conversion_status.set(resp.message);
+                    set_review_selected.set(Default::default());
+                    set_review_impact.set(None);
+                    trigger_conversion_overview_fetch(set_conversion_overview);
+                    load_review();
+                }
+                Err(err) => set_conversion_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let assign_purch_unit = move |item_id: i64| {
+        let purch_unit_id = match purch_unit_selected_id.get().trim().parse::<i64>() {
+            Ok(value) => value,
+            Err(_) => {
+                set_purch_unit_status.set("Enter a valid purchase unit".to_string());
+                return;
+            }
+        };
+        let is_default = purch_unit_set_default.get();
+        set_purch_unit_status.set("Saving purchase unit...".to_string());
+        spawn_local(async move {
+            let args = to_value(&SetPurchUnitArgs {
+                item_id,
+                purch_unit_id,
+                is_default,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("set_item_purch_unit", args).await {
+                Ok(resp) => {
+                    set_purch_unit_status.set(resp.message);
+                    if active_panel.get() == "reports" {
+                        trigger_missing_purch_fetch(
+                            report_page.get(),
+                            report_limit,
+                            set_report_loading,
+                            set_report_status,
+                            set_missing_purch_rows,
+                            set_missing_purch_total,
+                        );
+                    }
+                    if let Some(detail) = inventory_selected.get() {
+                        if detail.item_id == item_id {
+                            trigger_inventory_detail_fetch(
+                                item_id,
+                                set_inventory_detail_loading,
+                                set_inventory_detail_status,
+                                set_inventory_selected,
+                            );
+                        }
+                    }
+                }
+                Err(err) => set_purch_unit_status.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let save_manual_price = move |item_id: i64| {
+        let vendor_id = match manual_price_vendor_id.get().trim().parse::<i64>() {
+            Ok(value) => value,
+            Err(_) => {
+                set_manual_price_status.set("Select a vendor".to_string());
+                return;
+            }
+        };
+        let price = match manual_price_value.get().trim().parse::<f64>() {
+            Ok(value) if value > 0.0 => value,
+            _ => {
+                set_manual_price_status.set("Enter a valid price > 0".to_string());
+                return;
+            }
+        };
+        let pack = manual_price_pack.get();
+        set_manual_price_status.set("Saving manual price...".to_string());
+        spawn_local(async move {
+            let args = to_value(&UpsertManualPriceArgs {
+                item_id,
+                vendor_id,
+                price,
+                pack,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("upsert_manual_price", args).await {
+                Ok(resp) => {
+                    set_manual_price_status.set(resp.message);
+                    trigger_inventory_detail_fetch(
+                        item_id,
+                        set_inventory_detail_loading,
+                        set_inventory_detail_status,
+                        set_inventory_selected,
+                    );
+                }
+                Err(err) => set_manual_price_status.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let merge_vendor_ids = move || {
+        let source_vendor_id = match vendor_merge_source_id.get().trim().parse::<i64>() {
+            Ok(value) => value,
+            Err(_) => {
+                set_vendor_merge_status.set("Enter a valid source vendor ID".to_string());
+                return;
+            }
+        };
+        let target_vendor_id = match vendor_merge_target_id.get().trim().parse::<i64>() {
+            Ok(value) => value,
+            Err(_) => {
+                set_vendor_merge_status.set("Select a valid target vendor".to_string());
+                return;
+            }
+        };
+        if source_vendor_id == target_vendor_id {
+            set_vendor_merge_status.set("Source and target vendors must be different".to_string());
+            return;
+        }
+        set_vendor_merge_status.set("Merging vendors...".to_string());
+        spawn_local(async move {
+            let args = to_value(&MergeVendorArgs {
+                source_vendor_id,
+                target_vendor_id,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("merge_vendor", args).await {
+                Ok(resp) => {
+                    set_vendor_merge_status.set(resp.message);
+                    set_vendor_merge_source_id.set(target_vendor_id.to_string());
+                    trigger_vendor_fetch(
+                        vendor_query.get(),
+                        vendor_page.get(),
+                        vendor_limit,
+                        set_vendor_loading,
+                        set_vendor_status,
+                        set_vendor_list,
+                        set_vendor_total,
+                        set_vendor_filtered,
+                        set_vendor_loaded,
+                    );
+                    trigger_vendor_detail_fetch(
+                        target_vendor_id,
+                        set_vendor_detail_loading,
+                        set_vendor_detail_status,
+                        set_selected_vendor,
+                    );
+                    trigger_vendor_options_fetch(set_invoice_vendor_options);
+                    trigger_vendor_options_fetch(set_editor_vendor_options);
+                    trigger_vendor_all_options_fetch(set_vendor_merge_options);
+                }
+                Err(err) => set_vendor_merge_status.set(format!("Merge failed: {err}")),
+            }
+        });
+    };
+
+    // ── Inventory edit signals ──
+    let (edit_item_name, set_edit_item_name) = signal(String::new());
+    let (edit_item_status, set_edit_item_status) = signal(String::new());
+    let (edit_item_msg, set_edit_item_msg) = signal(String::new());
+    let (edit_item_food_category, set_edit_item_food_category) = signal(String::new());
+    let (edit_item_storage_type, set_edit_item_storage_type) = signal(String::new());
+    let (edit_item_category_msg, set_edit_item_category_msg) = signal(String::new());
+    let (edit_item_yield, set_edit_item_yield) = signal(String::new());
+    let (edit_item_density, set_edit_item_density) = signal(String::new());
+    let (new_prep_name, set_new_prep_name) = signal(String::new());
+    let (new_prep_yield, set_new_prep_yield) = signal(String::new());
+    let (yield_test_prep, set_yield_test_prep) = signal(String::new());
+    let (yield_test_gross, set_yield_test_gross) = signal(String::new());
+    let (yield_test_trim, set_yield_test_trim) = signal(String::new());
+    let (yield_test_waste, set_yield_test_waste) = signal(String::new());
+    let (item_yield_msg, set_item_yield_msg) = signal(String::new());
+    let (count_date, set_count_date) = signal(String::new());
+    let (count_qty, set_count_qty) = signal(String::new());
+    let (count_unit, set_count_unit) = signal(String::new());
+    let (par_area, set_par_area) = signal(String::new());
+    let (par_day, set_par_day) = signal(String::new());
+    let (par_qty, set_par_qty) = signal(String::new());
+    let (par_unit, set_par_unit) = signal(String::new());
+    let (item_nutrition, set_item_nutrition) = signal(None::<ItemNutritionResponse>);
+    let (item_nutrition_query, set_item_nutrition_query) = signal(String::new());
+    let (item_nutrition_basis, set_item_nutrition_basis) = signal(String::new());
+    let (item_nutrition_unit, set_item_nutrition_unit) = signal(String::new());
+    let (item_nutrition_values, set_item_nutrition_values) = signal(Vec::<String>::new());
+    let (item_nutrition_msg, set_item_nutrition_msg) = signal(String::new());
+    let (item_label_name, set_item_label_name) = signal(String::new());
+
+    let load_item_nutrition = move |item_id: i64, query: Option<String>| {
+        spawn_local(async move {
+            let args = to_value(&ItemNutritionArgs { item_id, query }).unwrap();
+            match invoke_cmd::<ItemNutritionResponse>("get_item_nutrition", args).await {
+                Ok(resp) => {
+                    let profile = resp.profile.clone();
+                    set_item_nutrition_basis.set(profile.as_ref().map(|p| format!("{}", p.basis_qty)).unwrap_or_default());
+                    set_item_nutrition_unit.set(profile.as_ref().map(|p| p.basis_unit_id.to_string()).unwrap_or_default());
+                    set_item_nutrition_values.set(match &profile {
+                        Some(p) => p.values.iter().map(|v| v.map(|v| format!("{}", v)).unwrap_or_default()).collect(),
+                        None => vec![String::new(); resp.nutrients.len()],
+                    });
+                    set_item_label_name.set(resp.label_name.clone().unwrap_or_default());
+                    set_item_nutrition.set(Some(resp));
+                }
+                Err(err) => set_item_nutrition_msg.set(format!("Nutrition failed: {err}")),
+            }
+        });
+    };
+
+    // "save_item_nutrition", "clear_item_nutrition", "link_item_food" or
+    // "save_item_label_name".
+    let item_nutrition_action = move |item_id: i64, command: &'static str, args: JsValue| {
+        set_item_nutrition_msg.set("Saving...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(command, args).await {
+                Ok(resp) => {
+                    set_item_nutrition_msg.set(resp.message);
+                    load_item_nutrition(item_id, None);
+                }
+                Err(err) => set_item_nutrition_msg.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_item_nutrition = move |item_id: i64| {
+        let Some(basis_qty) = item_nutrition_basis.get_untracked().trim().parse::<f64>().ok().filter(|q| *q > 0.0) else {
+            set_item_nutrition_msg.set("Enter the amount the nutrients are for".to_string());
+            return;
+        };
+        let Ok(basis_unit_id) = item_nutrition_unit.get_untracked().parse::<i64>() else {
+            set_item_nutrition_msg.set("Choose the unit the nutrients are for".to_string());
+            return;
+        };
+        let mut values = Vec::new();
+        for text in item_nutrition_values.get_untracked() {
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                values.push(None);
+            } else if let Ok(v) = text.parse::<f64>() {
+                values.push(Some(v));
+            } else {
+                set_item_nutrition_msg.set(format!("\"{}\" isn't a number", text));
+                return;
+            }
+        }
+        let args = to_value(&SaveItemNutritionArgs { item_id, basis_qty, basis_unit_id, values }).unwrap();
+        item_nutrition_action(item_id, "save_item_nutrition", args);
+    };
+
+    // Nutrition follows the selected item.
+    Effect::new(move |_| {
+        let Some(item_id) = inventory_selected.with(|d| d.as_ref().map(|d| d.item_id)) else {
+            return;
+        };
+        if item_nutrition.with_untracked(|n| n.as_ref().map(|n| n.item_id)) != Some(item_id) {
+            set_item_nutrition_query.set(String::new());
+            set_item_nutrition_msg.set(String::new());
+            load_item_nutrition(item_id, None);
+        }
+    });
+
+    let run_yield_action = move |item_id: i64, cmd: &'static str, args: JsValue| {
+        set_item_yield_msg.set("Saving...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_item_yield_msg.set(resp.message);
+                    trigger_inventory_detail_fetch(
+                        item_id,
+                        set_inventory_detail_loading,
+                        set_inventory_detail_status,
+                        set_inventory_selected,
+                    );
+                }
+                Err(err) => set_item_yield_msg.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_item_edit = move |item_id: i64| {
+        let name = edit_item_name.get();
+        let status_val: Option<i64> = edit_item_status.get().parse().ok();
+        set_edit_item_msg.set("Saving...".to_string());
+        spawn_local(async move {
+            let args = to_value(&UpdateItemArgs {
+                item_id,
+                name,
+                status: status_val,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("update_item", args).await {
+                Ok(resp) => {
+                    set_edit_item_msg.set(resp.message);
+                    // Refresh detail
+                    trigger_inventory_detail_fetch(
+                        item_id,
+                        set_inventory_detail_loading,
+                        set_inventory_detail_status,
+                        set_inventory_selected,
+                    );
+                    // Refresh list
+                    trigger_inventory_fetch(
+                        inventory_query.get(),
+                        inventory_food_category.get(),
+                        inventory_order_by.get(),
+                        inventory_page.get(),
+                        inventory_limit,
+                        set_inventory_loading,
+                        set_inventory_status,
+                        set_inventory_items,
+                        set_inventory_total,
+                        set_inventory_filtered,
+                        set_inventory_loaded,
+                    );
+                }
+                Err(err) => set_edit_item_msg.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    // ── Recipe edit signals ──
+    let (edit_recipe_name, set_edit_recipe_name) = signal(String::new());
+    let (edit_recipe_instructions, set_edit_recipe_instructions) = signal(String::new());
+    let (edit_recipe_msg, set_edit_recipe_msg) = signal(String::new());
+    let (edit_recipe_editing, set_edit_recipe_editing) = signal(false);
+    let (edit_recipe_yield_qty, set_edit_recipe_yield_qty) = signal(String::new());
+    let (edit_recipe_yield_unit, set_edit_recipe_yield_unit) = signal(String::new());
+    let (edit_recipe_servings, set_edit_recipe_servings) = signal(String::new());
+    let (edit_recipe_portion_size, set_edit_recipe_portion_size) = signal(String::new());
+    let (edit_recipe_portion_unit, set_edit_recipe_portion_unit) = signal(String::new());
+    let (edit_recipe_target_pct, set_edit_recipe_target_pct) = signal(String::new());
+    let (edit_recipe_menu_price, set_edit_recipe_menu_price) = signal(String::new());
+    let (scale_mode, set_scale_mode) = signal("factor".to_string());
+    let (scale_target, set_scale_target) = signal(String::new());
+    let (scale_result, set_scale_result) = signal(Option::<ScaledRecipeResponse>::None);
+    let (scale_status, set_scale_status) = signal(String::new());
+
+    let run_scale_recipe = move |recipe_id: i64| {
+        let target = match scale_target.get().trim().parse::<f64>() {
+            Ok(v) if v > 0.0 => v,
+            _ => {
+                set_scale_status.set("Enter a target greater than 0".to_string());
+                return;
+            }
+        };
+        let mode = scale_mode.get();
+        set_scale_status.set("Scaling...".to_string());
+        spawn_local(async move {
+            let args = to_value(&ScaleRecipeArgs {
+                recipe_id,
+                mode,
+                target,
+            })
+            .unwrap();
+            match invoke_cmd::<ScaledRecipeResponse>("scale_recipe", args).await {
+                Ok(result) => {
+                    set_scale_status.set(format!("Scaled x{:.2}", result.factor));
+                    set_scale_result.set(Some(result));
+                }
+                Err(err) => {
+                    set_scale_result.set(None);
+                    set_scale_status.set(format!("Scale failed: {err}"));
+                }
+            }
+        });
+    };
+    let (recipe_nutrition, set_recipe_nutrition) = signal(None::<RecipeNutrition>);
+    let (recipe_nutrition_status, set_recipe_nutrition_status) = signal(String::new());
+    let run_recipe_nutrition = move |recipe_id: i64| {
+        set_recipe_nutrition_status.set("Calculating...".to_string());
+        spawn_local(async move {
+            let args = to_value(&RecipeDetailArgs { recipe_id }).unwrap();
+            match invoke_cmd::<RecipeNutrition>("get_recipe_nutrition", args).await {
+                Ok(result) => {
+                    set_recipe_nutrition_status.set(String::new());
+                    set_recipe_nutrition.set(Some(result));
+                }
+                Err(err) => {
+                    set_recipe_nutrition.set(None);
+                    set_recipe_nutrition_status.set(format!("Nutrition failed: {err}"));
+                }
+            }
+        });
+    };
+    let (label_servings, set_label_servings) = signal("1".to_string());
+    let (nutrition_label, set_nutrition_label) = signal(None::<NutritionLabel>);
+    let (label_status, set_label_status) = signal(String::new());
+    let label_servings_value = move || {
+        label_servings
+            .get_untracked()
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|s| *s > 0.0)
+    };
+    let run_nutrition_label = move |recipe_id: i64| {
+        let Some(servings_per_container) = label_servings_value() else {
+            set_label_status.set("Enter how many servings are in the package".to_string());
+            return;
+        };
+        set_label_status.set("Building label...".to_string());
+        spawn_local(async move {
+            let args = to_value(&NutritionLabelArgs { recipe_id, servings_per_container }).unwrap();
+            match invoke_cmd::<NutritionLabel>("get_nutrition_label", args).await {
+                Ok(result) => {
+                    set_label_status.set(String::new());
+                    set_nutrition_label.set(Some(result));
+                }
+                Err(err) => {
+                    set_nutrition_label.set(None);
+                    set_label_status.set(format!("Label failed: {err}"));
+                }
+            }
+        });
+    };
+    // Saves the label as "pdf" or "png" for the servings currently entered.
+    let export_nutrition_label = move |recipe_id: i64, format: &'static str, default_name: String| {
+        let Some(servings_per_container) = label_servings_value() else {
+            set_label_status.set("Enter how many servings are in the package".to_string());
+            return;
+        };
+        let (title, filter_label, command) = if format == "png" {
+            ("Save Nutrition Facts PNG", "PNG Image", "export_label_png")
+        } else {
+            ("Save Nutrition Facts PDF", "PDF", "export_label_pdf")
+        };
+        trigger_save_dialog_and_export(title, &default_name, filter_label, format, set_label_status, move |path| {
+            (
+                command.to_string(),
+                to_value(&ExportLabelArgs { recipe_id, servings_per_container, output_path: path }).unwrap(),
+            )
+        });
+    };
+    // Export args carry the active scale only while it belongs to this recipe.
+    let active_scale = move |recipe_id: i64| -> (Option<String>, Option<f64>) {
+        match scale_result.get() {
+            Some(r) if r.recipe_id == recipe_id => (
+                Some(scale_mode.get()),
+                scale_target.get().trim().parse::<f64>().ok(),
+            ),
+            _ => (None, None),
+        }
+    };
+
+    let save_recipe_edit = move |recipe_id: i64| {
+        let name = edit_recipe_name.get();
+        let instructions = edit_recipe_instructions.get();
+        let yield_qty = edit_recipe_yield_qty.get().trim().parse::<f64>().ok();
+        let yield_unit_id = edit_recipe_yield_unit.get().trim().parse::<i64>().ok();
+        let servings = edit_recipe_servings.get().trim().parse::<f64>().ok();
+        let pricing_args = to_value(&UpdateRecipePricingArgs {
+            recipe_id,
+            portion_size: edit_recipe_portion_size.get().trim().parse::<f64>().ok(),
+            portion_unit_id: edit_recipe_portion_unit.get().trim().parse::<i64>().ok(),
+            target_food_cost_pct: edit_recipe_target_pct.get().trim().parse::<f64>().ok(),
+            menu_price: edit_recipe_menu_price.get().trim().parse::<f64>().ok(),
+        })
+        .unwrap();
+        set_edit_recipe_msg.set("Saving...".to_string());
+        spawn_local(async move {
+            let args = to_value(&UpdateRecipeArgs {
+                recipe_id,
+                name,
+                instructions,
+            })
+            .unwrap();
+            let yield_args = to_value(&UpdateRecipeYieldArgs {
+                recipe_id,
+                yield_qty,
+                yield_unit_id,
+                servings,
+            })
+            .unwrap();
+            let result = match invoke_cmd::<PatchResponse>("update_recipe", args).await {
+                Ok(_) => match invoke_cmd::<PatchResponse>("update_recipe_yield", yield_args).await {
+                    Ok(_) => invoke_cmd::<PatchResponse>("update_recipe_pricing", pricing_args).await,
+                    Err(err) => Err(err),
+                },
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(resp) => {
+                    set_edit_recipe_msg.set(resp.message);
+                    set_edit_recipe_editing.set(false);
+                    trigger_recipe_detail_fetch(
+                        recipe_id,
+                        set_recipe_detail_loading,
+                        set_recipe_detail_status,
+                        set_selected_recipe,
+                    );
+                    trigger_recipe_fetch(
+                        recipe_query.get(),
+                        recipe_order_by.get(),
+                        recipe_page.get(),
+                        recipe_limit,
+                        set_recipe_loading,
+                        set_recipe_status,
+                        set_recipe_list,
+                        set_recipe_total,
+                        set_recipe_filtered,
+                        set_recipe_loaded,
+                        false,
+                        set_recipe_detail_loading,
+                        set_recipe_detail_status,
+                        set_selected_recipe,
+                    );
+                }
+                Err(err) => set_edit_recipe_msg.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let delete_recipe_ingredient = move |recipe_id: i64, recp_item_id: i64| {
+        set_edit_recipe_msg.set("Removing ingredient...".to_string());
+        spawn_local(async move {
+            let args = to_value(&AddRecpItemArgs {
+                recipe_id,
+                recp_item_id: Some(recp_item_id),
+                item_id: 0,
+                unit_id: None,
+                qty: Some(0.0),
+                sub_recipe_id: None,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("add_recp_item", args).await {
+                Ok(resp) => {
+                    set_edit_recipe_msg.set(resp.message);
+                    trigger_recipe_detail_fetch(
+                        recipe_id,
+                        set_recipe_detail_loading,
+                        set_recipe_detail_status,
+                        set_selected_recipe,
+                    );
+                }
+                Err(err) => set_edit_recipe_msg.set(format!("Remove failed: {err}")),
+            }
+        });
+    };
+
+    // ── Export signals ──
+    let (export_status, set_export_status) = signal(String::new());
+
+    /// Open a native Save dialog, then invoke an export command.
+    fn trigger_save_dialog_and_export(
+        title: &str,
+        default_name: &str,
+        filter_label: &str,
+        filter_ext: &str,
+        set_export_status: WriteSignal<String>,
+        build_cmd: impl FnOnce(String) -> (String, JsValue) + 'static,
+    ) {
+        let title = title.to_string();
+        let default_name = default_name.to_string();
+        let filter_label = filter_label.to_string();
+        let filter_ext = filter_ext.to_string();
+        spawn_local(async move {
+            let path: Option<String> = {
+                let w = match window() {
+                    Some(w) => w,
+                    None => { set_export_status.set("Window not available".into()); return; }
+                };
+                let tauri = match Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
+                    Ok(t) if !t.is_undefined() && !t.is_null() => t,
+                    _ => { set_export_status.set("Tauri API missing".into()); return; }
+                };
+                let dialog = match Reflect::get(&tauri, &JsValue::from_str("dialog")) {
+                    Ok(d) if !d.is_undefined() && !d.is_null() => d,
+                    _ => { set_export_status.set("Dialog API missing".into()); return; }
+                };
+                let save_fn = match Reflect::get(&dialog, &JsValue::from_str("save")) {
+                    Ok(f) if f.is_function() => f,
+                    _ => { set_export_status.set("Save dialog missing".into()); return; }
+                };
+                let opts = js_sys::Object::new();
+                let _ = Reflect::set(&opts, &JsValue::from_str("title"), &JsValue::from_str(&title));
+                let _ = Reflect::set(&opts, &JsValue::from_str("defaultPath"), &JsValue::from_str(&default_name));
+                // Filter
+                let filter_obj = js_sys::Object::new();
+                let _ = Reflect::set(&filter_obj, &JsValue::from_str("name"), &JsValue::from_str(&filter_label));
+                let exts = js_sys::Array::new();
+                exts.push(&JsValue::from_str(&filter_ext));
+                let _ = Reflect::set(&filter_obj, &JsValue::from_str("extensions"), &exts);
+                let filters = js_sys::Array::new();
+                filters.push(&filter_obj);
+                let _ = Reflect::set(&opts, &JsValue::from_str("filters"), &filters);
+
+                let func = save_fn.dyn_ref::<js_sys::Function>().unwrap();
+                match func.call1(&JsValue::NULL, &opts) {
+                    Ok(promise) => {
+                        if let Ok(p) = promise.dyn_into::<Promise>() {
+                            match JsFuture::from(p).await {
+                                Ok(result) => result.as_string(),
+                                Err(_) => None,
+                            }
+                        } else {
+                            None
+                        }
+                    }
+                    Err(_) => None,
+                }
+            };
+
+            if let Some(output_path) = path {
+                set_export_status.set("Exporting...".to_string());
+                let (cmd, args) = build_cmd(output_path);
+                match invoke_cmd::<PatchResponse>(&cmd, args).await {
+                    Ok(resp) => set_export_status.set(resp.message),
+                    Err(err) => set_export_status.set(format!("Export failed: {err}")),
+                }
+            }
+        });
+    }
+
+    /// Open a native Open dialog for a single file and return its path.
+    async fn open_file_dialog(title: &str, filter_label: &str, filter_ext: &str) -> Option<String> {
+        let w = window()?;
+        let tauri = Reflect::get(&w, &JsValue::from_str("__TAURI__")).ok().filter(|t| !t.is_undefined())?;
+        let dialog = Reflect::get(&tauri, &JsValue::from_str("dialog")).ok().filter(|d| !d.is_undefined())?;
+        let open_fn = Reflect::get(&dialog, &JsValue::from_str("open")).ok().filter(|f| f.is_function())?;
+        let opts = js_sys::Object::new();
+        let _ = Reflect::set(&opts, &JsValue::from_str("title"), &JsValue::from_str(title));
+        let _ = Reflect::set(&opts, &JsValue::from_str("directory"), &JsValue::from_bool(false));
+        let _ = Reflect::set(&opts, &JsValue::from_str("multiple"), &JsValue::from_bool(false));
+        let filter = js_sys::Object::new();
+        let _ = Reflect::set(&filter, &JsValue::from_str("name"), &JsValue::from_str(filter_label));
+        let exts = js_sys::Array::new();
+        for ext in filter_ext.split(',') {
+            exts.push(&JsValue::from_str(ext));
+        }
+        let _ = Reflect::set(&filter, &JsValue::from_str("extensions"), &exts);
+        let filters = js_sys::Array::new();
+        filters.push(&filter);
+        let _ = Reflect::set(&opts, &JsValue::from_str("filters"), &filters);
+
+        let func = open_fn.dyn_ref::<js_sys::Function>()?;
+        let promise = func.call1(&JsValue::NULL, &opts).ok()?.dyn_into::<Promise>().ok()?;
+        JsFuture::from(promise).await.ok()?.as_string()
+    }
+
+    let export_inventory_pdf = move || {
+        let gb = inventory_order_by.get();
+        trigger_save_dialog_and_export(
+            "Save Inventory PDF",
+            "inventory.pdf",
+            "PDF",
+            "pdf",
+            set_export_status,
+            move |path| ("export_inventory_pdf".to_string(), to_value(&ExportInventoryArgs { output_path: path, group_by: if gb.is_empty() { None } else { Some(gb) } }).unwrap()),
+        );
+    };
+
+    let export_inventory_docx = move || {
+        let gb = inventory_order_by.get();
+        trigger_save_dialog_and_export(
+            "Save Inventory Word Document",
+            "inventory.docx",
+            "Word Document",
+            "docx",
+            set_export_status,
+            move |path| ("export_inventory_docx".to_string(), to_value(&ExportInventoryArgs { output_path: path, group_by: if gb.is_empty() { None } else { Some(gb) } }).unwrap()),
+        );
+    };
+
+    // ── FDA PDF export ──
+    let export_fda_pdf = move || {
+        trigger_save_dialog_and_export(
+            "Save FDA Guidelines PDF",
+            "fda-guidelines.pdf",
+            "PDF",
+            "pdf",
+            set_export_status,
+            |path| ("export_fda_pdf".to_string(), to_value(&ExportPathArgs { output_path: path }).unwrap()),
+        );
+    };
+
+    // ── Menu engineering signals ──
+    let (pos_profiles, set_pos_profiles) = signal(Vec::<PosProfile>::new());
+    let (sales_profile_id, set_sales_profile_id) = signal(String::new());
+    let (sales_period_start, set_sales_period_start) = signal(String::new());
+    let (sales_period_end, set_sales_period_end) = signal(String::new());
+    let (sales_status, set_sales_status) = signal(String::new());
+    let (sales_imports, set_sales_imports) = signal(Vec::<SalesImportRow>::new());
+    let (pos_items, set_pos_items) = signal(Vec::<PosItemRow>::new());
+    let (pos_unmapped_count, set_pos_unmapped_count) = signal(0usize);
+    let (pos_unmapped_only, set_pos_unmapped_only) = signal(true);
+    let (sales_recipe_options, set_sales_recipe_options) = signal(Vec::<RecipeSimple>::new());
+    let (new_profile_name, set_new_profile_name) = signal(String::new());
+    let (new_profile_item, set_new_profile_item) = signal(String::new());
+    let (new_profile_qty, set_new_profile_qty) = signal(String::new());
+    let (new_profile_sales, set_new_profile_sales) = signal(String::new());
+    let (new_profile_date, set_new_profile_date) = signal(String::new());
+    let (new_profile_category, set_new_profile_category) = signal(String::new());
+    let (me_date_from, set_me_date_from) = signal(String::new());
+    let (me_date_to, set_me_date_to) = signal(String::new());
+    let (me_category, set_me_category) = signal(String::new());
+    let (me_report, set_me_report) = signal(Option::<MenuEngineeringResponse>::None);
+
+    let load_pos_profiles = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<PosProfileListResponse>("list_pos_profiles", args).await {
+                Ok(result) => {
+                    if sales_profile_id.get_untracked().is_empty() {
+                        if let Some(first) = result.profiles.first() {
+                            set_sales_profile_id.set(first.profile_id.to_string());
+                        }
+                    }
+                    set_pos_profiles.set(result.profiles);
+                }
+                Err(err) => set_sales_status.set(format!("Failed to load profiles: {err}")),
+            }
+        });
+    };
+
+    let custom_pos_profiles = move || {
+        pos_profiles
+            .get()
+            .into_iter()
+            .filter(|p| !p.builtin)
+            .collect::<Vec<_>>()
+    };
+
+    let load_sales_imports = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            if let Ok(result) = invoke_cmd::<SalesImportListResponse>("list_sales_imports", args).await {
+                set_sales_imports.set(result.imports);
+            }
+        });
+    };
+
+    let load_pos_items = move || {
+        let unmapped_only = pos_unmapped_only.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&PosItemQueryArgs { unmapped_only }).unwrap();
+            match invoke_cmd::<PosItemListResponse>("list_pos_items", args).await {
+                Ok(result) => {
+                    set_pos_unmapped_count.set(result.unmapped);
+                    set_pos_items.set(result.items);
+                }
+                Err(err) => set_sales_status.set(format!("Failed to load POS items: {err}")),
+            }
+        });
+    };
+
+    let run_menu_engineering = move || {
+        let args = MenuEngineeringArgs {
+            date_from: me_date_from.get_untracked(),
+            date_to: me_date_to.get_untracked(),
+            category: me_category.get_untracked(),
+        };
+        spawn_local(async move {
+            match invoke_cmd::<MenuEngineeringResponse>("get_menu_engineering", to_value(&args).unwrap()).await {
+                Ok(report) => set_me_report.set(Some(report)),
+                Err(err) => set_sales_status.set(format!("Menu engineering failed: {err}")),
+            }
+        });
+    };
+
+    let import_sales_file = move || {
+        let profile_id = match sales_profile_id.get_untracked().parse::<i64>() {
+            Ok(id) => id,
+            Err(_) => {
+                set_sales_status.set("Select a mapping profile".to_string());
+                return;
+            }
+        };
+        let period_start = sales_period_start.get_untracked();
+        if period_start.trim().is_empty() {
+            set_sales_status.set("Enter the sales period start date".to_string());
+            return;
+        }
+        let period_end = sales_period_end.get_untracked();
+        spawn_local(async move {
+            let path = match open_file_dialog("Select POS Sales CSV", "CSV", "csv").await {
+                Some(path) => path,
+                None => return,
+            };
+            set_sales_status.set("Importing sales...".to_string());
+            let args = to_value(&ImportSalesArgs {
+                csv_path: path,
+                profile_id,
+                period_start,
+                period_end,
+            })
+            .unwrap();
+            match invoke_cmd::<SalesImportResponse>("import_sales_csv", args).await {
+                Ok(result) => {
+                    set_sales_status.set(format!(
+                        "Imported {} rows ({} units, {}); {} new POS items, {} unmapped, {} rows skipped",
+                        result.rows,
+                        result.total_qty,
+                        format_money(result.total_sales),
+                        result.new_items,
+                        result.unmapped_items,
+                        result.skipped
+                    ));
+                    load_sales_imports();
+                    load_pos_items();
+                }
+                Err(err) => set_sales_status.set(format!("Import failed: {err}")),
+            }
+        });
+    };
+
+    let save_new_profile = move || {
+        let profile = PosProfileInput {
+            profile_id: None,
+            name: new_profile_name.get_untracked(),
+            item_col: new_profile_item.get_untracked(),
+            qty_col: new_profile_qty.get_untracked(),
+            net_sales_col: new_profile_sales.get_untracked(),
+            date_col: new_profile_date.get_untracked(),
+            category_col: new_profile_category.get_untracked(),
+        };
+        spawn_local(async move {
+            let args = to_value(&SavePosProfileArgs { profile }).unwrap();
+            match invoke_cmd::<PatchResponse>("save_pos_profile", args).await {
+                Ok(resp) => {
+                    set_sales_status.set(resp.message);
+                    set_new_profile_name.set(String::new());
+                    load_pos_profiles();
+                }
+                Err(err) => set_sales_status.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let map_pos_item = move |args: MapPosItemArgs| {
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>("map_pos_item", to_value(&args).unwrap()).await {
+                Ok(resp) => {
+                    set_sales_status.set(resp.message);
+                    load_pos_items();
+                }
+                Err(err) => set_sales_status.set(format!("Mapping failed: {err}")),
+            }
+        });
+    };
+
+    let export_menu_engineering = move |pdf: bool| {
+        let date_from = me_date_from.get_untracked();
+        let date_to = me_date_to.get_untracked();
+        let category = me_category.get_untracked();
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save Menu Engineering PDF", "menu-engineering.pdf", "PDF", "pdf", "export_menu_engineering_pdf")
+        } else {
+            ("Save Menu Engineering CSV", "menu-engineering.csv", "CSV", "csv", "export_menu_engineering_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_sales_status, move |path| {
+            (
+                cmd.to_string(),
+                to_value(&ExportMenuEngineeringArgs {
+                    date_from,
+                    date_to,
+                    category,
+                    output_path: path,
+                })
+                .unwrap(),
+            )
+        });
+    };
+
+    let (var_date_from, set_var_date_from) = signal(String::new());
+    let (var_date_to, set_var_date_to) = signal(String::new());
+    let (var_status, set_var_status) = signal(String::new());
+    let (var_report, set_var_report) = signal(Option::<VarianceReport>::None);
+
+    let run_variance = move || {
+        let args = VarianceArgs {
+            date_from: var_date_from.get_untracked(),
+            date_to: var_date_to.get_untracked(),
+        };
+        set_var_status.set("Calculating...".to_string());
+        spawn_local(async move {
+            match invoke_cmd::<VarianceReport>("get_variance_report", to_value(&args).unwrap()).await {
+                Ok(report) => {
+                    set_var_status.set(String::new());
+                    set_var_report.set(Some(report));
+                }
+                Err(err) => set_var_status.set(format!("Variance report failed: {err}")),
+            }
+        });
+    };
+
+    // ── Count sessions ──
+    let (count_sessions, set_count_sessions) = signal(Vec::<CountSession>::new());
+    let (count_sheet, set_count_sheet) = signal(Option::<CountSheet>::None);
+    let (count_status, set_count_status) = signal(String::new());
+    let (new_count_name, set_new_count_name) = signal(String::new());
+    let (new_count_date, set_new_count_date) = signal(String::new());
+    let (count_compare_id, set_count_compare_id) = signal(String::new());
+    let (count_valuation, set_count_valuation) = signal(Option::<InventoryValuation>::None);
+
+    let load_count_sessions = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<Vec<CountSession>>("list_count_sessions", args).await {
+                Ok(list) => set_count_sessions.set(list),
+                Err(err) => set_count_status.set(format!("Failed to load count sessions: {err}")),
+            }
+        });
+    };
+
+    let load_count_sheet = move |session_id: i64| {
+        spawn_local(async move {
+            let args = to_value(&CountSessionIdArgs { session_id }).unwrap();
+            match invoke_cmd::<CountSheet>("get_count_sheet", args).await {
+                Ok(sheet) => set_count_sheet.set(Some(sheet)),
+                Err(err) => set_count_status.set(format!("Failed to load count sheet: {err}")),
+            }
+        });
+    };
+
+    let run_valuation = move |session_id: i64| {
+        let args = InventoryValuationArgs {
+            session_id,
+            compare_session_id: count_compare_id.get_untracked().parse::<i64>().ok(),
+        };
+        spawn_local(async move {
+            match invoke_cmd::<InventoryValuation>("get_inventory_valuation", to_value(&args).unwrap()).await {
+                Ok(report) => set_count_valuation.set(Some(report)),
+                Err(err) => set_count_status.set(format!("Valuation failed: {err}")),
+            }
+        });
+    };
+
+    let open_count_session = move |session_id: i64| {
+        set_count_valuation.set(None);
+        set_count_compare_id.set(String::new());
+        load_count_sheet(session_id);
+        run_valuation(session_id);
+    };
+
+    let create_count_session = move || {
+        let name = new_count_name.get_untracked();
+        let date = new_count_date.get_untracked();
+        spawn_local(async move {
+            let args = to_value(&CreateCountSessionArgs {
+                name,
+                count_date: if date.is_empty() { None } else { Some(date) },
+                notes: None,
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("create_count_session", args).await {
+                Ok(resp) => {
+                    set_count_status.set(resp.message);
+                    set_new_count_name.set(String::new());
+                    let args = to_value(&PingArgs {}).unwrap();
+                    if let Ok(list) = invoke_cmd::<Vec<CountSession>>("list_count_sessions", args).await {
+                        if let Some(newest) = list.iter().map(|s| s.session_id).max() {
+                            open_count_session(newest);
+                        }
+                        set_count_sessions.set(list);
+                    }
+                }
+                Err(err) => set_count_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let count_session_action = move |cmd: &'static str, session_id: i64| {
+        spawn_local(async move {
+            let args = to_value(&CountSessionIdArgs { session_id }).unwrap();
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_count_status.set(resp.message);
+                    if cmd == "delete_count_session" {
+                        set_count_sheet.set(None);
+                        set_count_valuation.set(None);
+                    } else {
+                        open_count_session(session_id);
+                    }
+                    load_count_sessions();
+                }
+                Err(err) => set_count_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_count_line = move |args: SaveCountLineArgs| {
+        let session_id = args.session_id;
+        spawn_local(async move {
+            match invoke_cmd::<PatchResponse>("save_count_line", to_value(&args).unwrap()).await {
+                Ok(resp) => {
+                    set_count_status.set(resp.message);
+                    load_count_sheet(session_id);
+                }
+                Err(err) => set_count_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_shelf_order = move |session_id: i64, item_id: i64, value: String| {
+        spawn_local(async move {
+            let args = to_value(&SetShelfOrderArgs {
+                item_id,
+                shelf_order: value.trim().parse::<i64>().ok(),
+            })
+            .unwrap();
+            match invoke_cmd::<PatchResponse>("set_item_shelf_order", args).await {
+                Ok(_) => load_count_sheet(session_id),
+                Err(err) => set_count_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let export_count_pdf = move |valuation: bool| {
+        let session_id = match count_sheet.get_untracked() {
+            Some(sheet) => sheet.session.session_id,
+            None => return,
+        };
+        if valuation {
+            let compare_session_id = count_valuation.get_untracked().and_then(|v| v.prior.map(|p| p.session_id));
+            trigger_save_dialog_and_export(
+                "Save Inventory Valuation PDF",
+                "inventory-valuation.pdf",
+                "PDF",
+                "pdf",
+                set_count_status,
+                move |path| {
+                    (
+                        "export_inventory_valuation_pdf".to_string(),
+                        to_value(&ExportValuationArgs { session_id, compare_session_id, output_path: path }).unwrap(),
+                    )
+                },
+            );
+        } else {
+            trigger_save_dialog_and_export(
+                "Save Count Sheet PDF",
+                "count-sheet.pdf",
+                "PDF",
+                "pdf",
+                set_count_status,
+                move |path| {
+                    (
+                        "export_count_sheet_pdf".to_string(),
+                        to_value(&ExportCountSheetArgs { session_id, output_path: path }).unwrap(),
+                    )
+                },
+            );
+        }
+    };
+
+    // ── Accounting periods and COGS ──
+    let (ap_periods, set_ap_periods) = signal(Vec::<AccountingPeriod>::new());
+    let (ap_report, set_ap_report) = signal(Option::<CogsReport>::None);
+    let (ap_status, set_ap_status) = signal(String::new());
+    let (ap_edit_id, set_ap_edit_id) = signal(Option::<i64>::None);
+    let (ap_name, set_ap_name) = signal(String::new());
+    let (ap_start, set_ap_start) = signal(String::new());
+    let (ap_end, set_ap_end) = signal(String::new());
+    let (ap_opening, set_ap_opening) = signal(String::new());
+    let (ap_closing, set_ap_closing) = signal(String::new());
+    let (ap_notes, set_ap_notes) = signal(String::new());
+
+    let load_periods = move || {
+        spawn_local(async move {
+            let args = to_value(&PingArgs {}).unwrap();
+            match invoke_cmd::<Vec<AccountingPeriod>>("list_accounting_periods", args).await {
+                Ok(list) => set_ap_periods.set(list),
+                Err(err) => set_ap_status.set(format!("Failed to load periods: {err}")),
+            }
+        });
+    };
+
+    let load_cogs = move |period_id: i64| {
+        spawn_local(async move {
+            let args = to_value(&AccountingPeriodIdArgs { period_id }).unwrap();
+            match invoke_cmd::<CogsReport>("get_cogs_report", args).await {
+                Ok(report) => set_ap_report.set(Some(report)),
+                Err(err) => {
+                    set_ap_status.set(format!("COGS report failed: {err}"));
+                    set_ap_report.set(None);
+                }
+            }
+        });
+    };
+
+    let reset_period_form = move || {
+        set_ap_edit_id.set(None);
+        set_ap_name.set(String::new());
+        set_ap_start.set(String::new());
+        set_ap_end.set(String::new());
+        set_ap_opening.set(String::new());
+        set_ap_closing.set(String::new());
+        set_ap_notes.set(String::new());
+    };
+
+    let edit_period = move |p: AccountingPeriod| {
+        set_ap_edit_id.set(Some(p.period_id));
+        set_ap_name.set(p.name);
+        set_ap_start.set(p.start_date);
+        set_ap_end.set(p.end_date);
+        set_ap_opening.set(p.opening_session_id.map(|id| id.to_string()).unwrap_or_default());
+        set_ap_closing.set(p.closing_session_id.map(|id| id.to_string()).unwrap_or_default());
+        set_ap_notes.set(p.notes);
+    };
+
+    let save_period = move || {
+        let notes = ap_notes.get_untracked().trim().to_string();
+        let period = AccountingPeriodInput {
+            period_id: ap_edit_id.get_untracked(),
+            name: ap_name.get_untracked().trim().to_string(),
+            start_date: ap_start.get_untracked(),
+            end_date: ap_end.get_untracked(),
+            opening_session_id: ap_opening.get_untracked().parse::<i64>().ok(),
+            closing_session_id: ap_closing.get_untracked().parse::<i64>().ok(),
+            notes: if notes.is_empty() { None } else { Some(notes) },
+        };
+        if period.name.is_empty() || period.start_date.is_empty() || period.end_date.is_empty() {
+            set_ap_status.set("Name, start and end dates are required.".to_string());
+            return;
+        }
+        spawn_local(async move {
+            let args = to_value(&SaveAccountingPeriodArgs { period }).unwrap();
+            match invoke_cmd::<PatchResponse>("save_accounting_period", args).await {
+                Ok(resp) => {
+                    set_ap_status.set(resp.message);
+                    reset_period_form();
+                    load_periods();
+                    if let Some(report) = ap_report.get_untracked() {
+                        load_cogs(report.period.period_id);
+                    }
+                }
+                Err(err) => set_ap_status.set(format!("Save failed: {err}")),
+            }
+        });
+    };
+
+    let period_action = move |cmd: &'static str, period_id: i64| {
+        spawn_local(async move {
+            let args = to_value(&AccountingPeriodIdArgs { period_id }).unwrap();
+            match invoke_cmd::<PatchResponse>(cmd, args).await {
+                Ok(resp) => {
+                    set_ap_status.set(resp.message);
+                    load_periods();
+                    if cmd == "delete_accounting_period" {
+                        if ap_report.get_untracked().is_some_and(|r| r.period.period_id == period_id) {
+                            set_ap_report.set(None);
+                        }
+                    } else {
+                        load_cogs(period_id);
+                    }
+                }
+                Err(err) => set_ap_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_period_revenue = move |period_id: i64, category: String, text: String| {
+        let text = text.trim().to_string();
+        let amount = if text.is_empty() {
+            None
+        } else {
+            match text.replace(['$', ','], "").parse::<f64>() {
+                Ok(v) => Some(v),
+                Err(_) => {
+                    set_ap_status.set(format!("'{}' isn't a number", text));
+                    return;
+                }
+            }
+        };
+        spawn_local(async move {
+            let args = to_value(&SetPeriodRevenueArgs { period_id, category, amount }).unwrap();
+            match invoke_cmd::<PatchResponse>("set_period_revenue", args).await {
+                Ok(resp) => {
+                    set_ap_status.set(resp.message);
+                    load_cogs(period_id);
+                }
+                Err(err) => set_ap_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let save_cogs_category = move |period_id: i64, food_category: String, cogs_category: String| {
+        spawn_local(async move {
+            let args = to_value(&SetCogsCategoryArgs { food_category, cogs_category }).unwrap();
+            match invoke_cmd::<PatchResponse>("set_cogs_category", args).await {
+                Ok(resp) => {
+                    set_ap_status.set(resp.message);
+                    load_cogs(period_id);
+                }
+                Err(err) => set_ap_status.set(format!("Error: {err}")),
+            }
+        });
+    };
+
+    let export_cogs = move |period_id: i64, pdf: bool| {
+        let (title, name, label, ext, cmd) = if pdf {
+            ("Save COGS PDF", "cogs.pdf", "PDF", "pdf", "export_cogs_pdf")
+        } else {
+            ("Save COGS CSV", "cogs.csv", "CSV", "csv", "export_cogs_csv")
+        };
+        trigger_save_dialog_and_export(title, name, label, ext, set_ap_status, move |path| {
+            (cmd.to_string(), to_value(&ExportCogsArgs { period_id, output_path: path }).unwrap())
+        });
+    };
+
+    let show_counts = move || {
+        set_active_panel.set("counts".to_string());
+        load_count_sessions();
+        load_periods();
+    };
+
+    let show_sales = move || {
+        set_active_panel.set("sales".to_string());
+        load_pos_profiles();
+        load_sales_imports();
+        load_pos_items();
+        if sales_recipe_options.get().is_empty() {
+            spawn_local(async move {
+                let args = to_value(&PingArgs {}).unwrap();
+                if let Ok(result) = invoke_cmd::<RecipeSimpleResponse>("list_recipes_simple", args).await {
+                    set_sales_recipe_options.set(result.recipes);
+                }
+            });
+        }
+        run_menu_engineering();
+    };
+
+    // ── Global search signals ──
+    let (search_query, set_search_query) = signal(String::new());
+    let (search_results, set_search_results) = signal(Vec::<GlobalSearchHit>::new());
+    let (_search_loading, set_search_loading) = signal(false);
+
+    let do_global_search = move || {
+        let q = search_query.get();
+        if q.trim().is_empty() {
+            set_search_results.set(Vec::new());
+            return;
+        }
+        set_search_loading.set(true);
+        spawn_local(async move {
+            let args = to_value(&GlobalSearchArgs { query: q }).unwrap();
+            match JsFuture::from(invoke("global_search", args)).await {
+                Ok(val) => {
+                    if let Ok(hits) = serde_wasm_bindgen::from_value::<Vec<GlobalSearchHit>>(val) {
+                        set_search_results.set(hits);
+                    }
+                }
+                Err(_) => {
+                    set_search_results.set(Vec::new());
+                }
+            }
+            set_search_loading.set(false);
+        });
+    };
+
+    // ── PDF Invoice import signals ──
+    let (pdf_preview, set_pdf_preview) = signal(Option::<PdfInvoicePreview>::None);
+    let (pdf_import_status, set_pdf_import_status) = signal(String::new());
+    let (pdf_vendor_id, set_pdf_vendor_id) = signal(String::new());
+    let (pdf_invoice_no, set_pdf_invoice_no) = signal(String::new());
+    let (pdf_invoice_date, set_pdf_invoice_date) = signal(String::new());
+
+    view! {
+        <div class="app-shell">
+            <aside class="sidebar">
+                <div class="brand">
+                    <img src="/public/4chef-logo.png" alt="4chef" class="brand-logo" />
+                </div>
+                <div class="sidebar-search">
+                    <input
+                        type="text"
+                        class="sidebar-search-input"
+                        placeholder="Search everything..."
+                        prop:value=move || search_query.get()
+                        on:input=move |ev| {
+                            let val = event_target_value(&ev);
+                            set_search_query.set(val);
+                            do_global_search();
+                        }
+                        on:keydown=move |ev| {
+                            if ev.key() == "Escape" {
+                                set_search_query.set(String::new());
+                                set_search_results.set(Vec::new());
+                            }
+                        }
+                    />
+                    <Show when=move || !search_results.get().is_empty()>
+                        <div class="sidebar-search-results">
+                            <For
+                                each=move || search_results.get()
+                                key=|hit| format!("{}-{}", hit.category, hit.id)
+                                children=move |hit: GlobalSearchHit| {
+                                    let cat = hit.category.clone();
+                                    let label = hit.label.clone();
+                                    let detail = hit.detail.clone();
+                                    view! {
+                                        <button
+                                            class="sidebar-search-hit"
+                                            type="button"
+                                            on:click=move |_| {
+                                                match cat.as_str() {
+                                                    "item" => set_active_panel.set("inventory".to_string()),
+                                                    "recipe" => set_active_panel.set("recipes".to_string()),
+                                                    "vendor" => set_active_panel.set("vendors".to_string()),
+                                                    "invoice" => set_active_panel.set("purchasing".to_string()),
+                                                    _ => {}
+                                                }
+                                                set_search_query.set(String::new());
+                                                set_search_results.set(Vec::new());
+                                            }
+                                        >
+                                            <span class="search-hit-label">{label.clone()}</span>
+                                            <span class="search-hit-badge">{detail.clone()}</span>
+                                        </button>
+                                    }
+                                }
+                            />
+                        </div>
+                    </Show>
+                </div>
+                <div class="nav-section">
+                    <div class="nav-label">"Modules"</div>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "imports"
+                        on:click=move |_| show_imports()
+                        type="button"
+                    >
+                        "Imports"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "inventory"
+                        on:click=move |_| show_inventory()
+                        type="button"
+                    >
+                        "Inventory"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "counts"
+                        on:click=move |_| show_counts()
+                        type="button"
+                    >
+                        "Inventory Counts"
+                    </button>
+
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "recipes"
+                        on:click=move |_| show_recipes()
+                        type="button"
+                    >
+                        "Recipes"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "vendors"
+                        on:click=move |_| show_vendors()
+                        type="button"
+                    >
+                        "Vendors"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "bids"
+                        on:click=move |_| show_bids()
+                        type="button"
+                    >
+                        "Vendor Bids"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "conversions"
+                        on:click=move |_| show_conversions()
+                        type="button"
+                    >
+                        "Conversions"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "purchasing"
+                        on:click=move |_| show_purchasing()
+                        type="button"
+                    >
+                        "Purchasing"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "reports"
+                        on:click=move |_| show_reports()
+                        type="button"
+                    >
+                        "Reports"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "foodcost"
+                        on:click=move |_| show_foodcost()
+                        type="button"
+                    >
+                        "Food Cost"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "sales"
+                        on:click=move |_| show_sales()
+                        type="button"
+                    >
+                        "Sales & Variance"
+                    </button>
+                </div>
+                <div class="nav-section">
+                    <div class="nav-label">"System"</div>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "settings"
+                        on:click=move |_| show_settings()
+                        type="button"
+                    >
+                        "Settings"
+                    </button>
+                    <button
+                        class="nav-item"
+                        class:active=move || active_panel.get() == "fda"
+                        on:click=move |_| show_fda()
+                        type="button"
+                    >
+                        "FDA Guidelines"
+                    </button>
+                </div>
+            </aside>
+            <main class="main">
+                <div class="topbar">
+                    <div>
+                        <div class={move || {
+                            if active_panel.get().as_str() == "imports" || active_panel.get().as_str() == "" || active_panel.get().as_str() == "home" {
+                                "title title-home"
+                            } else {
+                                "title"
+                            }
+                        }}>
+                            {move || {
+                                match active_panel.get().as_str() {
+                                    "inventory" => "Inventory Browser".to_string(),
+                                    "recipes" => "Recipe Costing".to_string(),
+                                    "vendors" => "Vendor Hub".to_string(),
+                                    "bids" => "Vendor Bids".to_string(),
+                                    "conversions" => "Conversion Lab".to_string(),
+                                    "reports" => "Data Health".to_string(),
+                                    "purchasing" => "Purchasing".to_string(),
+                                    "foodcost" => "Food Cost Calculator".to_string(),
+                                    "sales" => "Sales & Variance".to_string(),
+                                    "settings" => "General Settings".to_string(),
+                                    "fda" => "FDA Guidelines".to_string(),
+                                    _ => "4chef".to_string(),
+                                }
+                            }}
+                        </div>
+                        <div class="subtitle">
+                            {move || {
+                                match active_panel.get().as_str() {
+                                    "inventory" => "Search and inspect inventory items in your 4chef library.".to_string(),
+                                    "recipes" => "Cost recipes using purchase units and conversions."
+                                        .to_string(),
+                                    "vendors" => "Search vendors and review their priced items.".to_string(),
+                                    "bids" => "Request quotes, compare them per purchase unit, and award the best price.".to_string(),
+                                    "conversions" => "Review conversion gaps and suggested fixes.".to_string(),
+                                    "reports" => "Track missing data that blocks accurate costing.".to_string(),
+                                    "purchasing" => "Review price alerts, recipe cost impact, invoices and transaction lines.".to_string(),
+                                    "foodcost" => "Build a dish, set your target %, and find the right menu price.".to_string(),
+                                    "sales" => "Import POS sales, rank the menu, and compare theoretical to actual usage.".to_string(),
+                                    "settings" => "Company info and preferences.".to_string(),
+                                    "fda" => "Quick-reference FDA food safety guidelines for your kitchen.".to_string(),
+                                    _ => "Organize. Optimize. Itemize. Done."
+                                        .to_string(),
+                                }
+                            }}
+                        </div>
+                    </div>
+                </div>
+
+                <Show when=move || active_panel.get() == "imports">
+                    <div class="panel">
+                        <div class="row">
+                            <div class="input" style="flex: 1;">
+                                <label>
+                                    {move || {
+                                        if import_mode.get() == "mdf" {
+                                            "MDF/LDF source"
+                                        } else {
+                                            "Exports folder"
+                                        }
+                                    }}
+                                </label>
+                                <div style="display: flex; gap: 6px;">
+                                    <input
+                                        type="text"
+                                        prop:value=base_path
+                                        on:input=move |ev| {
+                                            let value = event_target_value(&ev);
+                                            set_base_path.set(value);
+                                        }
+                                        style="flex: 1;"
+                                    />
+                                    <button
+                                        class="button secondary"
+                                        style="white-space: nowrap;"
+                                        on:click=move |_| {
+                                            let mode_now = import_mode.get();
+                                            spawn_local(async move {
+                                                if let Some(w) = window() {
+                                                    if let Ok(tauri) = Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
+                                                        if let Ok(dialog) = Reflect::get(&tauri, &JsValue::from_str("dialog")) {
+                                                            if let Ok(open_fn) = Reflect::get(&dialog, &JsValue::from_str("open")) {
+                                                                let opts = js_sys::Object::new();
+                                                                let _ = Reflect::set(
+                                                                    &opts,
+                                                                    &JsValue::from_str("directory"),
+                                                                    &JsValue::from_bool(mode_now != "mdf"),
+                                                                );
+                                                                let _ = Reflect::set(&opts, &JsValue::from_str("multiple"), &JsValue::from_bool(false));
+                                                                let title = if mode_now == "mdf" {
+                                                                    "Select MDF or LDF file"
+                                                                } else {
+                                                                    "Select exports folder"
+                                                                };
+                                                                let _ = Reflect::set(
+                                                                    &opts,
+                                                                    &JsValue::from_str("title"),
+                                                                    &JsValue::from_str(title),
+                                                                );
+                                                                if let Ok(f) = open_fn.dyn_ref::<js_sys::Function>()
+                                                                    .ok_or(JsValue::NULL)
+                                                                {
+                                                                    if let Ok(promise) = f.call1(&JsValue::NULL, &opts) {
+                                                                        if let Ok(p) = promise.dyn_into::<Promise>() {
+                                                                            if let Ok(result) = JsFuture::from(p).await {
+                                                                                if let Some(path) = result.as_string() {
+                                                                                    set_base_path.set(path);
+                                                                                }
+                                                                            }
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    >
+                                        "Browse\u{2026}"
+                                    </button>
+                                </div>
+                            </div>
+                            <div class="input" style="max-width: 220px;">
+                                <label>"Import mode"</label>
+                                <select
+                                    prop:value=move || import_mode.get()
+                                    on:change=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_import_mode.set(value);
+                                    }
+                                >
+                                    <option value="exports">"Exports (CSV)"</option>
+                                    <option value="mdf">"MDF/LDF database"</option>
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label style="visibility: hidden;">{"\u{00A0}"}</label>
+                                <button class="button import-btn" on:click=move |_| start_import() disabled=move || busy.get()>
+                                    {move || if busy.get() { "Importing\u{2026}" } else { "\u{2B07} Import" }}
+                                </button>
+                            </div>
+                        </div>
+                    <div class="status">{move || status.get()}</div>
+                    <div class="status">{move || tauri_status.get()}</div>
+                    <div class="row" style="margin-top: 10px;">
+                        <button class="button secondary" on:click=move |_| ping_backend()>
+                            "Ping backend"
+                        </button>
+                        <button class="button secondary" on:click=move |_| open_path(base_path.get())>
+                            {move || {
+                                if import_mode.get() == "mdf" {
+                                    "Open source path"
+                                } else {
+                                    "Open exports folder"
+                                }
+                            }}
+                        </button>
+                        <div class="status">{move || ping_status.get()}</div>
+                    </div>
+                </div>
+
+                    <div class="panel">
+                        <div class="row">
+                            <div>
+                                <strong>"Database"</strong>
+                                <div class="status">
+                                    {move || if has_summary.get() { summary.get().db_path } else { "No database yet".to_string() }}
+                                </div>
+                            </div>
+                            <div style="margin-left: auto;">
+                                <button class="button secondary" on:click=move |_| {
+                                    let path = summary.get().db_path;
+                                    open_path(path);
+                                } disabled=move || !has_summary.get()>
+                                    "Open DB folder"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="cards">
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "units"
+                                on:click=move |_| toggle_card("units")
+                            >
+                                <h4>"Units"</h4>
+                                <p>{move || summary.get().units.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "items"
+                                on:click=move |_| toggle_card("items")
+                            >
+                                <h4>"Items"</h4>
+                                <p>{move || summary.get().items.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "convunit"
+                                on:click=move |_| toggle_card("convunit")
+                            >
+                                <h4>"Conversions"</h4>
+                                <p>{move || summary.get().convunit.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "recp_items"
+                                on:click=move |_| toggle_card("recp_items")
+                            >
+                                <h4>"Recipe Items"</h4>
+                                <p>{move || summary.get().recp_items.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "inv_units"
+                                on:click=move |_| toggle_card("inv_units")
+                            >
+                                <h4>"Inv Units"</h4>
+                                <p>{move || summary.get().inv_units.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "vendors"
+                                on:click=move |_| toggle_card("vendors")
+                            >
+                                <h4>"Vendors"</h4>
+                                <p>{move || summary.get().vendors.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "recipes"
+                                on:click=move |_| toggle_card("recipes")
+                            >
+                                <h4>"Recipes"</h4>
+                                <p>{move || summary.get().recipes.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "inv_prices"
+                                on:click=move |_| toggle_card("inv_prices")
+                            >
+                                <h4>"Inv Prices"</h4>
+                                <p>{move || summary.get().inv_prices.to_string()}</p>
+                            </div>
+                        </div>
+                        <div class="cards">
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "invoices"
+                                on:click=move |_| toggle_card("invoices")
+                            >
+                                <h4>"Invoices"</h4>
+                                <p>{move || summary.get().invoices.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "trans"
+                                on:click=move |_| toggle_card("trans")
+                            >
+                                <h4>"Trans Lines"</h4>
+                                <p>{move || summary.get().trans.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "recp_inv"
+                                on:click=move |_| toggle_card("recp_inv")
+                            >
+                                <h4>"RecpInv"</h4>
+                                <p>{move || summary.get().recp_inv.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "conv_suggestions"
+                                on:click=move |_| toggle_card("conv_suggestions")
+                            >
+                                <h4>"Conv Suggestions"</h4>
+                                <p>{move || summary.get().conv_suggestions.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "conv_suggestions_safe"
+                                on:click=move |_| toggle_card("conv_suggestions_safe")
+                            >
+                                <h4>"Safe Suggestions"</h4>
+                                <p>{move || summary.get().conv_suggestions_safe.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "missing_edges"
+                                on:click=move |_| toggle_card("missing_edges")
+                            >
+                                <h4>"Local Conversions"</h4>
+                                <p>{move || summary.get().missing_edges.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "missing_data_report"
+                                on:click=move |_| toggle_card("missing_data_report")
+                            >
+                                <h4>"Missing Data"</h4>
+                                <p>{move || summary.get().missing_data_report.to_string()}</p>
+                            </div>
+                            <div
+                                class="card card-clickable"
+                                class:card-active=move || browse_table_name.get() == "missing_purch_unit"
+                                on:click=move |_| toggle_card("missing_purch_unit")
+                            >
+                                <h4>"Missing Purch"</h4>
+                                <p>{move || summary.get().missing_purch_unit.to_string()}</p>
+                            </div>
+                        </div>
+
+                        <Show when=move || !browse_table_name.get().is_empty()>
+                            <div class="browse-section">
+                                <div class="row" style="align-items: center; margin-bottom: 8px;">
+                                    <strong>{move || format!("Browsing: {}", browse_table_name.get())}</strong>
+                                    <span class="status" style="margin-left: 10px;">
+                                        {move || {
+                                            let data = browse_data.get();
+                                            let page = browse_page.get();
+                                            let start = page * browse_limit + 1;
+                                            let end = std::cmp::min((page + 1) * browse_limit, data.total as u32);
+                                            if data.total > 0 {
+                                                format!("Rows {}\u{2013}{} of {}", start, end, data.total)
+                                            } else {
+                                                "No rows".to_string()
+                                            }
+                                        }}
+                                    </span>
+                                    <div style="margin-left: auto; display: flex; gap: 6px;">
+                                        <button
+                                            class="button secondary"
+                                            disabled=move || browse_page.get() == 0 || browse_loading.get()
+                                            on:click=move |_| {
+                                                let p = browse_page.get().saturating_sub(1);
+                                                set_browse_page.set(p);
+                                                fetch_browse_table(browse_table_name.get(), p);
+                                            }
+                                        >
+                                            "\u{25C0} Prev"
+                                        </button>
+                                        <button
+                                            class="button secondary"
+                                            disabled=move || {
+                                                let data = browse_data.get();
+                                                ((browse_page.get() + 1) * browse_limit) as i64 >= data.total || browse_loading.get()
+                                            }
+                                            on:click=move |_| {
+                                                let p = browse_page.get() + 1;
+                                                set_browse_page.set(p);
                                                 fetch_browse_table(browse_table_name.get(), p);
                                             }
                                         >
                                             "Next \u{25B6}"
                                         </button>
                                     </div>
-                                </div>
-                                <Show when=move || browse_loading.get()>
-                                    <div class="status">"Loading\u{2026}"</div>
-                                </Show>
-                                <Show when=move || !browse_loading.get() && !browse_data.get().columns.is_empty()>
-                                    <div class="browse-table-wrap">
-                                        <table class="browse-table">
-                                            <thead>
-                                                <tr>
-                                                    <For
-                                                        each=move || browse_data.get().columns.clone()
-                                                        key=|col| col.clone()
-                                                        children=move |col| {
-                                                            let label = humanize_column(&col);
-                                                            view! { <th>{label}</th> }
-                                                        }
-                                                    />
-                                                </tr>
-                                            </thead>
-                                            <tbody>
-                                                <For
-                                                    each=move || {
-                                                        browse_data.get().rows.iter().enumerate()
-                                                            .map(|(i, r)| (i, r.clone()))
-                                                            .collect::<Vec<_>>()
-                                                    }
-                                                    key=|(i, _)| *i
-                                                    children=move |(_, row)| view! {
-                                                        <tr>
-                                                            <For
-                                                                each=move || {
-                                                        row.iter().enumerate()
-                                                            .map(|(i, c)| (i, c.clone()))
-                                                            .collect::<Vec<_>>()
-                                                    }
-                                                    key=|(i, _)| *i
-                                                    children=move |(_, cell)| view! { <td>{cell}</td> }
+                                </div>
+                                <Show when=move || browse_loading.get()>
+                                    <div class="status">"Loading\u{2026}"</div>
+                                </Show>
+                                <Show when=move || !browse_loading.get() && !browse_data.get().columns.is_empty()>
+                                    <div class="browse-table-wrap">
+                                        <table class="browse-table">
+                                            <thead>
+                                                <tr>
+                                                    <For
+                                                        each=move || browse_data.get().columns.clone()
+                                                        key=|col| col.clone()
+                                                        children=move |col| {
+                                                            let label = humanize_column(&col);
+                                                            view! { <th>{label}</th> }
+                                                        }
+                                                    />
+                                                </tr>
+                                            </thead>
+                                            <tbody>
+                                                <For
+                                                    each=move || {
+                                                        browse_data.get().rows.iter().enumerate()
+                                                            .map(|(i, r)| (i, r.clone()))
+                                                            .collect::<Vec<_>>()
+                                                    }
+                                                    key=|(i, _)| *i
+                                                    children=move |(_, row)| view! {
+                                                        <tr>
+                                                            <For
+                                                                each=move || {
+                                                        row.iter().enumerate()
+                                                            .map(|(i, c)| (i, c.clone()))
+                                                            .collect::<Vec<_>>()
+                                                    }
+                                                    key=|(i, _)| *i
+                                                    children=move |(_, cell)| view! { <td>{cell}</td> }
+                                                            />
+                                                        </tr>
+                                                    }
+                                                />
+                                            </tbody>
+                                        </table>
+                                    </div>
+                                </Show>
+                            </div>
+                        </Show>
+                    </div>
+
+                    <Show
+                        when=move || has_summary.get() && !summary.get().warnings.is_empty()
+                    >
+                        <div class="panel">
+                            <div class="row">
+                                <div>
+                                    <strong>"Import Notes"</strong>
+                                    <div class="status">
+                                        {move || format!("Warnings: {}", summary.get().warnings.len())}
+                                    </div>
+                                </div>
+                                <div style="margin-left: auto;">
+                                    <button class="button secondary" on:click=move |_| {
+                                        set_show_warnings.set(!show_warnings.get());
+                                    }>
+                                        {move || if show_warnings.get() { "Hide details" } else { "View details" }}
+                                    </button>
+                                </div>
+                            </div>
+                            <Show when=move || show_warnings.get()>
+                                <div class="log">
+                                    <For
+                                        each=move || summary.get().warnings
+                                        key=|msg| msg.clone()
+                                        children=move |msg| view! { <div>{msg}</div> }
+                                    />
+                                </div>
+                            </Show>
+                        </div>
+                    </Show>
+
+                    // ── PDF Invoice Import ──
+                    <div class="panel">
+                        <div class="row">
+                            <div>
+                                <strong>"Import PDF Invoice"</strong>
+                                <div class="status">"Upload a digital vendor invoice (PDF) to preview and import line items."</div>
+                            </div>
+                        </div>
+                        <div class="row" style="margin-top: 12px;">
+                            <button
+                                class="button secondary"
+                                on:click=move |_| {
+                                    spawn_local(async move {
+                                        let w = match window() {
+                                            Some(w) => w,
+                                            None => return,
+                                        };
+                                        let tauri = match Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
+                                            Ok(t) if !t.is_undefined() => t,
+                                            _ => return,
+                                        };
+                                        let dialog = match Reflect::get(&tauri, &JsValue::from_str("dialog")) {
+                                            Ok(d) if !d.is_undefined() => d,
+                                            _ => return,
+                                        };
+                                        let open_fn = match Reflect::get(&dialog, &JsValue::from_str("open")) {
+                                            Ok(f) if f.is_function() => f,
+                                            _ => return,
+                                        };
+                                        let opts = js_sys::Object::new();
+                                        let _ = Reflect::set(&opts, &JsValue::from_str("title"), &JsValue::from_str("Select PDF Invoice"));
+                                        let _ = Reflect::set(&opts, &JsValue::from_str("directory"), &JsValue::from_bool(false));
+                                        let _ = Reflect::set(&opts, &JsValue::from_str("multiple"), &JsValue::from_bool(false));
+                                        let filter = js_sys::Object::new();
+                                        let _ = Reflect::set(&filter, &JsValue::from_str("name"), &JsValue::from_str("PDF"));
+                                        let exts = js_sys::Array::new();
+                                        exts.push(&JsValue::from_str("pdf"));
+                                        let _ = Reflect::set(&filter, &JsValue::from_str("extensions"), &exts);
+                                        let filters = js_sys::Array::new();
+                                        filters.push(&filter);
+                                        let _ = Reflect::set(&opts, &JsValue::from_str("filters"), &filters);
+
+                                        let func = open_fn.dyn_ref::<js_sys::Function>().unwrap();
+                                        if let Ok(promise) = func.call1(&JsValue::NULL, &opts) {
+                                            if let Ok(p) = promise.dyn_into::<Promise>() {
+                                                if let Ok(result) = JsFuture::from(p).await {
+                                                    if let Some(path) = result.as_string() {
+                                                        set_pdf_import_status.set("Extracting PDF...".to_string());
+                                                        let args = to_value(&PdfPathArgs { pdf_path: path }).unwrap();
+                                                        match invoke_cmd::<PdfInvoicePreview>("preview_pdf_invoice", args).await {
+                                                            Ok(preview) => {
+                                                                set_pdf_invoice_no.set(preview.invoice_no.clone());
+                                                                set_pdf_invoice_date.set(preview.invoice_date.clone());
+                                                                set_pdf_import_status.set(format!(
+                                                                    "Extracted {} line items from PDF",
+                                                                    preview.lines.len()
+                                                                ));
+                                                                set_pdf_preview.set(Some(preview));
+                                                            }
+                                                            Err(err) => {
+                                                                set_pdf_import_status.set(format!("PDF extraction failed: {err}"));
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    });
+                                }
+                            >
+                                "Select PDF File"
+                            </button>
+                        </div>
+                        <div class="status">{move || pdf_import_status.get()}</div>
+
+                        <Show when=move || pdf_preview.get().is_some()>
+                            {move || pdf_preview.with(|opt| {
+                                opt.as_ref().map(|preview| {
+                                    let preview = preview.clone();
+                                    let line_count = preview.lines.len();
+                                    view! {
+                                        <div style="margin-top: 12px;">
+                                            <div class="row">
+                                                <div class="input">
+                                                    <label>"Vendor"</label>
+                                                    <select
+                                                        prop:value=pdf_vendor_id
+                                                        on:change=move |ev| {
+                                                            set_pdf_vendor_id.set(event_target_value(&ev));
+                                                        }
+                                                    >
+                                                        <option value="">{format!("Detected: {}", preview.vendor_guess)}</option>
+                                                        <For
+                                                            each=move || editor_vendor_options.get()
+                                                            key=|v| v.vendor_id
+                                                            children=move |v| view! {
+                                                                <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
+                                                            }
+                                                        />
+                                                    </select>
+                                                </div>
+                                                <div class="input">
+                                                    <label>"Invoice #"</label>
+                                                    <input
+                                                        type="text"
+                                                        prop:value=pdf_invoice_no
+                                                        on:input=move |ev| {
+                                                            set_pdf_invoice_no.set(event_target_value(&ev));
+                                                        }
+                                                    />
+                                                </div>
+                                                <div class="input">
+                                                    <label>"Date"</label>
+                                                    <input
+                                                        type="text"
+                                                        prop:value=pdf_invoice_date
+                                                        on:input=move |ev| {
+                                                            set_pdf_invoice_date.set(event_target_value(&ev));
+                                                        }
+                                                    />
+                                                </div>
+                                            </div>
+                                            <div class="data-table" style="margin-top: 12px;">
+                                                <div class="data-header data-cols-5">
+                                                    <span>"Item"</span>
+                                                    <span>"Qty"</span>
+                                                    <span>"Unit"</span>
+                                                    <span>"Unit Price"</span>
+                                                    <span>"Ext. Cost"</span>
+                                                </div>
+                                                <For
+                                                    each=move || {
+                                                        let v: Vec<(usize, PdfInvoiceLine)> = preview.lines.clone().into_iter().enumerate().collect();
+                                                        v
+                                                    }
+                                                    key=|(i, _)| *i
+                                                    children=move |(_, line)| view! {
+                                                        <div class="data-row data-cols-5">
+                                                            <span>{line.item_name}</span>
+                                                            <span>{line.qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                            <span>{line.unit}</span>
+                                                            <span>{line.unit_price.map(|p| format!("${:.2}", p)).unwrap_or_else(|| "-".to_string())}</span>
+                                                            <span>{line.ext_cost.map(|c| format!("${:.2}", c)).unwrap_or_else(|| "-".to_string())}</span>
+                                                        </div>
+                                                    }
+                                                />
+                                            </div>
+                                            <div class="row" style="margin-top: 12px;">
+                                                <button
+                                                    class="button import-btn"
+                                                    on:click=move |_| {
+                                                        let vid = match pdf_vendor_id.get().parse::<i64>() {
+                                                            Ok(v) => v,
+                                                            Err(_) => {
+                                                                set_pdf_import_status.set("Please select a vendor".to_string());
+                                                                return;
+                                                            }
+                                                        };
+                                                        let inv_no = pdf_invoice_no.get();
+                                                        let inv_date = pdf_invoice_date.get();
+                                                        let pdf_lines = pdf_preview.get().map(|p| p.lines.clone()).unwrap_or_default();
+                                                        set_pdf_import_status.set("Importing...".to_string());
+                                                        spawn_local(async move {
+                                                            let line_vals: Vec<serde_json::Value> = pdf_lines.iter().map(|l| {
+                                                                serde_json::json!({
+                                                                    "item_name": l.item_name,
+                                                                    "qty": l.qty,
+                                                                    "unit_price": l.unit_price,
+                                                                    "ext_cost": l.ext_cost,
+                                                                })
+                                                            }).collect();
+                                                            let args = to_value(&ImportPdfInvoiceArgs {
+                                                                vendor_id: vid,
+                                                                invoice_no: inv_no,
+                                                                invoice_date: inv_date,
+                                                                lines: line_vals,
+                                                            }).unwrap();
+                                                            match invoke_cmd::<PatchResponse>("import_pdf_invoice", args).await {
+                                                                Ok(resp) => {
+                                                                    set_pdf_import_status.set(resp.message);
+                                                                    set_pdf_preview.set(None);
+                                                                }
+                                                                Err(err) => set_pdf_import_status.set(format!("Import failed: {err}")),
+                                                            }
+                                                        });
+                                                    }
+                                                >
+                                                    {move || format!("Import {} Lines", line_count)}
+                                                </button>
+                                                <button
+                                                    class="button secondary"
+                                                    on:click=move |_| {
+                                                        set_pdf_preview.set(None);
+                                                        set_pdf_import_status.set(String::new());
+                                                    }
+                                                >
+                                                    "Cancel"
+                                                </button>
+                                            </div>
+                                        </div>
+                                    }
+                                })
+                            })}
+                        </Show>
+                    </div>
+                </Show>
+
+                <Show when=move || active_panel.get() == "inventory">
+                    <div class="panel">
+                        <div class="row">
+                            <div class="input">
+                                <label>"Search"</label>
+                                <input
+                                    type="text"
+                                    prop:value=inventory_query
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_inventory_query.set(value);
+                                    }
+                                />
+                            </div>
+                            <div class="input">
+                                <label>"Organize by"</label>
+                                <select
+                                    prop:value=inventory_order_by
+                                    on:change=move |ev| {
+                                        set_inventory_order_by.set(event_target_value(&ev));
+                                    }
+                                >
+                                    <option value="">"Alphabetically"</option>
+                                    <option value="vendor">"By Vendor"</option>
+                                </select>
+                            </div>
+                            <div class="input" style="align-self: end;">
+                                <div class="row">
+                                    <button class="button" on:click=move |_| {
+                                        set_inventory_page.set(0);
+                                        trigger_inventory_fetch(
+                                            inventory_query.get(),
+                                            inventory_food_category.get(),
+                                            inventory_order_by.get(),
+                                            0,
+                                            inventory_limit,
+                                            set_inventory_loading,
+                                            set_inventory_status,
+                                            set_inventory_items,
+                                            set_inventory_total,
+                                            set_inventory_filtered,
+                                            set_inventory_loaded,
+                                        );
+                                    } disabled=move || inventory_loading.get()>
+                                        {move || if inventory_loading.get() { "Searching..." } else { "Search" }}
+                                    </button>
+                                    <button class="button secondary" on:click=move |_| {
+                                        set_inventory_query.set(String::new());
+                                        set_inventory_order_by.set(String::new());
+                                        set_inventory_page.set(0);
+                                        set_inventory_food_category.set(String::new());
+                                        trigger_inventory_fetch(
+                                            String::new(),
+                                            String::new(),
+                                            String::new(),
+                                            0,
+                                            inventory_limit,
+                                            set_inventory_loading,
+                                            set_inventory_status,
+                                            set_inventory_items,
+                                            set_inventory_total,
+                                            set_inventory_filtered,
+                                            set_inventory_loaded,
+                                        );
+                                    }>
+                                        "Reset"
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                        <div class="status">{move || inventory_status.get()}</div>
+                    </div>
+
+                    <div class="panel inventory-grid">
+                        <div class="inventory-list">
+                            <div class="row">
+                                <div>
+                                    <strong>"Inventory Overview"</strong>
+                                    <div class="status">
+                                        {move || format!(
+                                            "Total items: {} | Filtered: {} | Page {}",
+                                            inventory_total.get(),
+                                            inventory_filtered.get(),
+                                            inventory_page.get() + 1
+                                        )}
+                                    </div>
+                                </div>
+                                <div style="margin-left: auto; display: flex; gap: 8px; align-items: end;">
+                                    <button class="button tiny" on:click=move |_| export_inventory_pdf()>"Export PDF"</button>
+                                    <button class="button tiny secondary" on:click=move |_| export_inventory_docx()>"Export Word"</button>
+                                </div>
+                            </div>
+                            <Show when=move || !export_status.get().is_empty()>
+                                <div class="status">{move || export_status.get()}</div>
+                            </Show>
+                            <div class="inventory-table">
+                                <div class="inventory-header">
+                                    <span>"ID"</span>
+                                    <span>"Name"</span>
+                                    <span>"Status"</span>
+                                </div>
+                                {move || {
+                                    let items = inventory_items.get();
+                                    let order = inventory_order_by.get();
+                                    let mut last_group = String::new();
+                                    items.iter().map(|item| {
+                                        let group_label = match order.as_str() {
+                                            "vendor" => {
+                                                let vn = &item.vendor_name;
+                                                if vn.is_empty() || vn == "<No Vendor>" { "No Vendor".to_string() } else { vn.clone() }
+                                            },
+                                            _ => String::new(),
+                                        };
+                                        let show_header = !group_label.is_empty() && group_label != last_group;
+                                        if show_header { last_group = group_label.clone(); }
+                                        let item_id = item.item_id;
+                                        let item_name = item.name.clone();
+                                        let item_status = item.status;
+                                        let header_view = if show_header {
+                                            Some(view! { <div class="inventory-group-header">{group_label}</div> })
+                                        } else {
+                                            None
+                                        };
+                                        view! {
+                                            <>
+                                                {header_view}
+                                                <div
+                                                    class="inventory-row"
+                                                    class:selected=move || inventory_selected.get().map(|d| d.item_id == item_id).unwrap_or(false)
+                                                    on:click=move |_| {
+                                                        trigger_inventory_detail_fetch(
+                                                            item_id,
+                                                            set_inventory_detail_loading,
+                                                            set_inventory_detail_status,
+                                                            set_inventory_selected,
+                                                        );
+                                                    }
+                                                >
+                                                    <span>{item_id}</span>
+                                                    <span>{item_name}</span>
+                                                    <span>{item_status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                </div>
+                                            </>
+                                        }
+                                    }).collect::<Vec<_>>()
+                                }}
+                            </div>
+                            <div class="row" style="margin-top: 16px;">
+                                <button class="button secondary" on:click=move |_| {
+                                    let current = inventory_page.get();
+                                    if current > 0 {
+                                        set_inventory_page.set(current - 1);
+                                        trigger_inventory_fetch(
+                                            inventory_query.get(),
+                                            inventory_food_category.get(),
+                                            inventory_order_by.get(),
+                                            current - 1,
+                                            inventory_limit,
+                                            set_inventory_loading,
+                                            set_inventory_status,
+                                            set_inventory_items,
+                                            set_inventory_total,
+                                            set_inventory_filtered,
+                                            set_inventory_loaded,
+                                        );
+                                    }
+                                } disabled=move || inventory_page.get() == 0 || inventory_loading.get()>
+                                    "Previous"
+                                </button>
+                                <button class="button" on:click=move |_| {
+                                    let current = inventory_page.get();
+                                    let max = (inventory_filtered.get() as f64 / inventory_limit as f64).ceil() as usize;
+                                    if current + 1 < max {
+                                        set_inventory_page.set(current + 1);
+                                        trigger_inventory_fetch(
+                                            inventory_query.get(),
+                                            inventory_food_category.get(),
+                                            inventory_order_by.get(),
+                                            current + 1,
+                                            inventory_limit,
+                                            set_inventory_loading,
+                                            set_inventory_status,
+                                            set_inventory_items,
+                                            set_inventory_total,
+                                            set_inventory_filtered,
+                                            set_inventory_loaded,
+                                        );
+                                    }
+                                } disabled=move || {
+                                    let max = (inventory_filtered.get() as f64 / inventory_limit as f64).ceil() as usize;
+                                    inventory_loading.get() || max == 0 || inventory_page.get() + 1 >= max
+                                }>
+                                    "Next"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="inventory-detail">
+                            <div class="status">{move || inventory_detail_status.get()}</div>
+                            <Show
+                                when=move || inventory_selected.get().is_some()
+                                fallback=move || view! {
+                                    <div class="recipe-empty">
+                                        <strong>"Select an item"</strong>
+                                        <p>"Choose an inventory item to review purchase units, pricing, and usage."</p>
+                                    </div>
+                                }
+                            >
+                                {move || inventory_selected.with(|opt| {
+                                    opt.as_ref().map(|detail| {
+                                        let detail = detail.clone();
+                                        let prep_options = detail.preps.clone();
+                                        view! {
+                                            <div>
+                                                <div class="recipe-title">{detail.name.clone()}</div>
+                                                <div class="recipe-summary">
+                                                    <span>{format!("Item ID {}", detail.item_id)}</span>
+                                                    <span>{format!("Status {}", detail.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()))}</span>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Edit Item"</strong>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Name"</label>
+                                                            <input
+                                                                type="text"
+                                                                prop:value=move || {
+                                                                    let v = edit_item_name.get();
+                                                                    if v.is_empty() {
+                                                                        inventory_selected.get()
+                                                                            .map(|d| d.name.clone())
+                                                                            .unwrap_or_default()
+                                                                    } else { v }
+                                                                }
+                                                                on:input=move |ev| {
+                                                                    set_edit_item_name.set(event_target_value(&ev));
+                                                                }
+                                                                on:focus=move |_| {
+                                                                    if edit_item_name.get().is_empty() {
+                                                                        if let Some(d) = inventory_selected.get() {
+                                                                            set_edit_item_name.set(d.name.clone());
+                                                                        }
+                                                                    }
+                                                                }
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="flex: 0 0 100px;">
+                                                            <label>"Status"</label>
+                                                            <input
+                                                                type="number"
+                                                                prop:value=move || {
+                                                                    let v = edit_item_status.get();
+                                                                    if v.is_empty() {
+                                                                        inventory_selected.get()
+                                                                            .and_then(|d| d.status)
+                                                                            .map(|s| s.to_string())
+                                                                            .unwrap_or_default()
+                                                                    } else { v }
+                                                                }
+                                                                on:input=move |ev| {
+                                                                    set_edit_item_status.set(event_target_value(&ev));
+                                                                }
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    save_item_edit(detail.item_id);
+                                                                    set_edit_item_name.set(String::new());
+                                                                    set_edit_item_status.set(String::new());
+                                                                }
+                                                            >
+                                                                "Save changes"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || edit_item_msg.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Categories"</strong>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Food Category"</label>
+                                                            <select
+                                                                prop:value=move || {
+                                                                    let v = edit_item_food_category.get();
+                                                                    if v.is_empty() {
+                                                                        inventory_selected.get()
+                                                                            .map(|d| d.food_category.clone())
+                                                                            .unwrap_or_default()
+                                                                    } else { v }
+                                                                }
+                                                                on:change=move |ev| {
+                                                                    set_edit_item_food_category.set(event_target_value(&ev));
+                                                                }
+                                                                on:focus=move |_| {
+                                                                    if edit_item_food_category.get().is_empty() {
+                                                                        if let Some(d) = inventory_selected.get() {
+                                                                            set_edit_item_food_category.set(d.food_category.clone());
+                                                                        }
+                                                                    }
+                                                                }
+                                                            >
+                                                                <option value="">"None"</option>
+                                                                <option value="Produce">"Produce"</option>
+                                                                <option value="Meat">"Meat"</option>
+                                                                <option value="Poultry">"Poultry"</option>
+                                                                <option value="Seafood">"Seafood"</option>
+                                                                <option value="Vegetables">"Vegetables"</option>
+                                                                <option value="Dairy">"Dairy"</option>
+                                                                <option value="Bakery">"Bakery"</option>
+                                                                <option value="Beverages">"Beverages"</option>
+                                                                <option value="Condiments">"Condiments"</option>
+                                                                <option value="Spices/Seasonings">"Spices/Seasonings"</option>
+                                                                <option value="Grains/Pasta">"Grains/Pasta"</option>
+                                                                <option value="Canned Goods">"Canned Goods"</option>
+                                                                <option value="Other">"Other"</option>
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Storage Type"</label>
+                                                            <select
+                                                                prop:value=move || {
+                                                                    let v = edit_item_storage_type.get();
+                                                                    if v.is_empty() {
+                                                                        inventory_selected.get()
+                                                                            .map(|d| d.storage_type.clone())
+                                                                            .unwrap_or_default()
+                                                                    } else { v }
+                                                                }
+                                                                on:change=move |ev| {
+                                                                    set_edit_item_storage_type.set(event_target_value(&ev));
+                                                                }
+                                                                on:focus=move |_| {
+                                                                    if edit_item_storage_type.get().is_empty() {
+                                                                        if let Some(d) = inventory_selected.get() {
+                                                                            set_edit_item_storage_type.set(d.storage_type.clone());
+                                                                        }
+                                                                    }
+                                                                }
+                                                            >
+                                                                <option value="">"None"</option>
+                                                                <option value="Walk-in/Cooler">"Walk-in/Cooler"</option>
+                                                                <option value="Frozen">"Frozen"</option>
+                                                                <option value="Dry Goods">"Dry Goods"</option>
+                                                            </select>
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let iid = detail.item_id;
+                                                                    let fc = edit_item_food_category.get();
+                                                                    let st = edit_item_storage_type.get();
+                                                                    let fc_val = if fc.is_empty() {
+                                                                        inventory_selected.get().map(|d| d.food_category.clone()).unwrap_or_default()
+                                                                    } else { fc };
+                                                                    let st_val = if st.is_empty() {
+                                                                        inventory_selected.get().map(|d| d.storage_type.clone()).unwrap_or_default()
+                                                                    } else { st };
+                                                                    spawn_local(async move {
+                                                                        let args = to_value(&UpdateItemCategoryArgs {
+                                                                            item_id: iid,
+                                                                            food_category: fc_val,
+                                                                            storage_type: st_val,
+                                                                        }).unwrap();
+                                                                        match invoke_cmd::<PatchResponse>("update_item_category", args).await {
+                                                                            Ok(resp) => {
+                                                                                set_edit_item_category_msg.set(resp.message);
+                                                                                // refresh detail
+                                                                                trigger_inventory_detail_fetch(
+                                                                                    iid,
+                                                                                    set_inventory_detail_loading,
+                                                                                    set_inventory_detail_status,
+                                                                                    set_inventory_selected,
+                                                                                );
+                                                                                // refresh list
+                                                                                trigger_inventory_fetch(
+                                                                                    inventory_query.get(),
+                                                                                    inventory_food_category.get(),
+                                                                                    inventory_order_by.get(),
+                                                                                    inventory_page.get(),
+                                                                                    inventory_limit,
+                                                                                    set_inventory_loading,
+                                                                                    set_inventory_status,
+                                                                                    set_inventory_items,
+                                                                                    set_inventory_total,
+                                                                                    set_inventory_filtered,
+                                                                                    set_inventory_loaded,
+                                                                                );
+                                                                                set_edit_item_food_category.set(String::new());
+                                                                                set_edit_item_storage_type.set(String::new());
+                                                                            }
+                                                                            Err(err) => set_edit_item_category_msg.set(format!("Error: {err}")),
+                                                                        }
+                                                                    });
+                                                                }
+                                                            >
+                                                                "Save categories"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || edit_item_category_msg.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Density"</strong>
+                                                    <div class="recipe-meta">
+                                                        {match (detail.effective_density, detail.density_source.as_str()) {
+                                                            (Some(d), "item") => format!("{:.3} g/ml ({:.0} g per cup), bridges volume and weight units", d, d * 236.588),
+                                                            (Some(d), source) => format!("{:.3} g/ml ({:.0} g per cup) from the {} table", d, d * 236.588, source),
+                                                            (None, _) => "No density; volume and weight units only convert through item conversions".to_string(),
+                                                        }}
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Grams per ml"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                placeholder={detail.density.map(|d| format!("{:.3}", d)).unwrap_or_default()}
+                                                                prop:value=move || edit_item_density.get()
+                                                                on:input=move |ev| set_edit_item_density.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let iid = detail.item_id;
+                                                                    let density = edit_item_density.get().trim().parse::<f64>().ok();
+                                                                    let args = to_value(&UpdateItemDensityArgs { item_id: iid, density }).unwrap();
+                                                                    run_yield_action(iid, "update_item_density", args);
+                                                                    set_edit_item_density.set(String::new());
+                                                                }
+                                                            >
+                                                                {if detail.density.is_some() { "Save / clear density" } else { "Save density" }}
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Nutrition"</strong>
+                                                    {move || {
+                                                        let item_id = detail.item_id;
+                                                        item_nutrition.get().filter(|n| n.item_id == item_id).map(|n| {
+                                                            let summary = match &n.profile {
+                                                                Some(p) => {
+                                                                    let amounts = n
+                                                                        .nutrients
+                                                                        .iter()
+                                                                        .zip(&p.values)
+                                                                        .filter_map(|(info, v)| {
+                                                                            v.map(|v| format!("{} {} {}", info.label, format_nutrient(v, &info.unit), info.unit))
+                                                                        })
+                                                                        .collect::<Vec<_>>()
+                                                                        .join(", ");
+                                                                    format!("Per {} {}: {} \u{2014} {}", p.basis_qty, p.basis_unit_name, amounts, p.source_label)
+                                                                }
+                                                                None => "No nutrition; recipes count this item as missing".to_string(),
+                                                            };
+                                                            let has_profile = n.profile.is_some();
+                                                            let has_foods = n.foods_imported > 0;
+                                                            let matches = n.matches.clone();
+                                                            let nutrients = n.nutrients.clone();
+                                                            view! {
+                                                                <div class="recipe-meta">{summary}</div>
+                                                                <Show when=move || !has_foods>
+                                                                    <div class="recipe-meta">"Import USDA FoodData Central data under Reports to match this item"</div>
+                                                                </Show>
+                                                                <Show when=move || has_foods>
+                                                                    <div class="row" style="margin-top: 10px;">
+                                                                        <div class="input">
+                                                                            <label>"Search USDA foods"</label>
+                                                                            <input
+                                                                                type="text"
+                                                                                placeholder="Item name"
+                                                                                prop:value=move || item_nutrition_query.get()
+                                                                                on:input=move |ev| set_item_nutrition_query.set(event_target_value(&ev))
+                                                                            />
+                                                                        </div>
+                                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                                            <button
+                                                                                class="button tiny secondary"
+                                                                                on:click=move |_| load_item_nutrition(item_id, Some(item_nutrition_query.get()))
+                                                                            >"Search"</button>
+                                                                        </div>
+                                                                    </div>
+                                                                    <div class="data-table">
+                                                                        <div class="data-header data-cols-4">
+                                                                            <span>"Food"</span>
+                                                                            <span>"Type"</span>
+                                                                            <span>"Match"</span>
+                                                                            <span>"Action"</span>
+                                                                        </div>
+                                                                        <For
+                                                                            each={
+                                                                                let matches = matches.clone();
+                                                                                move || matches.clone()
+                                                                            }
+                                                                            key=|food| food.fdc_id
+                                                                            children=move |food| {
+                                                                                let fdc_id = food.fdc_id;
+                                                                                view! {
+                                                                                    <div class="data-row data-cols-4">
+                                                                                        <span>{format!("{} ({})", food.description, food.fdc_id)}</span>
+                                                                                        <span>{food.data_type.clone()}</span>
+                                                                                        <span>{format!("{:.0}%", food.score * 100.0)}</span>
+                                                                                        <span class="action-cell">
+                                                                                            <button
+                                                                                                class="button tiny"
+                                                                                                on:click=move |_| item_nutrition_action(
+                                                                                                    item_id,
+                                                                                                    "link_item_food",
+                                                                                                    to_value(&LinkItemFoodArgs { item_id, fdc_id }).unwrap(),
+                                                                                                )
+                                                                                            >"Use"</button>
+                                                                                        </span>
+                                                                                    </div>
+                                                                                }
+                                                                            }
+                                                                        />
+                                                                    </div>
+                                                                </Show>
+                                                                <div class="row" style="margin-top: 10px; flex-wrap: wrap;">
+                                                                    <div class="input">
+                                                                        <label>"Amounts per"</label>
+                                                                        <input
+                                                                            type="number"
+                                                                            step="any"
+                                                                            placeholder="100"
+                                                                            prop:value=move || item_nutrition_basis.get()
+                                                                            on:input=move |ev| set_item_nutrition_basis.set(event_target_value(&ev))
+                                                                        />
+                                                                    </div>
+                                                                    <div class="input">
+                                                                        <label>"Unit"</label>
+                                                                        <select
+                                                                            prop:value=move || item_nutrition_unit.get()
+                                                                            on:change=move |ev| set_item_nutrition_unit.set(event_target_value(&ev))
+                                                                        >
+                                                                            <option value="">"Choose unit"</option>
+                                                                            <For
+                                                                                each=move || unit_options.get()
+                                                                                key=|u| u.unit_id
+                                                                                children=move |u| view! {
+                                                                                    <option value={u.unit_id.to_string()}>{u.sing.clone()}</option>
+                                                                                }
+                                                                            />
+                                                                        </select>
+                                                                    </div>
+                                                                    {nutrients
+                                                                        .iter()
+                                                                        .enumerate()
+                                                                        .map(|(idx, info)| {
+                                                                            view! {
+                                                                                <div class="input">
+                                                                                    <label>{format!("{} ({})", info.label, info.unit)}</label>
+                                                                                    <input
+                                                                                        type="number"
+                                                                                        step="any"
+                                                                                        prop:value=move || item_nutrition_values.with(|v| v.get(idx).cloned().unwrap_or_default())
+                                                                                        on:input=move |ev| {
+                                                                                            let text = event_target_value(&ev);
+                                                                                            set_item_nutrition_values.update(|v| {
+                                                                                                if let Some(slot) = v.get_mut(idx) {
+                                                                                                    *slot = text;
+                                                                                                }
+                                                                                            });
+                                                                                        }
+                                                                                    />
+                                                                                </div>
+                                                                            }
+                                                                        })
+                                                                        .collect_view()}
+                                                                </div>
+                                                                <div class="row" style="margin-top: 10px; gap: 8px;">
+                                                                    <button class="button tiny" on:click=move |_| save_item_nutrition(item_id)>
+                                                                        "Save nutrition"
+                                                                    </button>
+                                                                    <Show when=move || has_profile>
+                                                                        <button
+                                                                            class="button tiny secondary"
+                                                                            on:click=move |_| item_nutrition_action(
+                                                                                item_id,
+                                                                                "clear_item_nutrition",
+                                                                                to_value(&InventoryDetailArgs { item_id }).unwrap(),
+                                                                            )
+                                                                        >"Clear"</button>
+                                                                    </Show>
+                                                                </div>
+                                                                <div class="row" style="margin-top: 10px;">
+                                                                    <div class="input">
+                                                                        <label>"Name in ingredient statements"</label>
+                                                                        <input
+                                                                            type="text"
+                                                                            placeholder="Item name"
+                                                                            prop:value=move || item_label_name.get()
+                                                                            on:input=move |ev| set_item_label_name.set(event_target_value(&ev))
+                                                                        />
+                                                                    </div>
+                                                                    <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                                        <button
+                                                                            class="button tiny secondary"
+                                                                            on:click=move |_| item_nutrition_action(
+                                                                                item_id,
+                                                                                "save_item_label_name",
+                                                                                to_value(&SaveItemLabelNameArgs {
+                                                                                    item_id,
+                                                                                    label_name: item_label_name.get_untracked(),
+                                                                                })
+                                                                                .unwrap(),
+                                                                            )
+                                                                        >"Save name"</button>
+                                                                    </div>
+                                                                </div>
+                                                            }
+                                                        })
+                                                    }}
+                                                    <div class="status">{move || item_nutrition_msg.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Yield & Trim Loss"</strong>
+                                                    <div class="recipe-meta">
+                                                        {match detail.yield_pct {
+                                                            Some(p) => format!("Item yield {:.1}% (trim loss {:.1}%)", p, 100.0 - p),
+                                                            None => "No yield set (costed at 100%)".to_string(),
+                                                        }}
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Item Yield %"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                placeholder={detail.yield_pct.map(|p| format!("{:.1}", p)).unwrap_or_default()}
+                                                                prop:value=move || edit_item_yield.get()
+                                                                on:input=move |ev| set_edit_item_yield.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let iid = detail.item_id;
+                                                                    let yield_pct = edit_item_yield.get().trim().parse::<f64>().ok();
+                                                                    let args = to_value(&UpdateItemYieldArgs { item_id: iid, yield_pct }).unwrap();
+                                                                    run_yield_action(iid, "update_item_yield", args);
+                                                                    set_edit_item_yield.set(String::new());
+                                                                }
+                                                            >
+                                                                "Save yield"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="data-table" style="margin-top: 10px;">
+                                                        <div class="data-header data-cols-3">
+                                                            <span>"Preparation"</span>
+                                                            <span>"Yield %"</span>
+                                                            <span></span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.preps.clone()
+                                                            key=|prep| prep.prep_id
+                                                            children=move |prep| {
+                                                                let prep_id = prep.prep_id;
+                                                                let iid = prep.item_id;
+                                                                view! {
+                                                                    <div class="data-row data-cols-3">
+                                                                        <span>{prep.name}</span>
+                                                                        <span>{prep.yield_pct.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                        <span>
+                                                                            <button
+                                                                                class="button tiny danger"
+                                                                                on:click=move |_| {
+                                                                                    let args = to_value(&ItemPrepIdArgs { prep_id }).unwrap();
+                                                                                    run_yield_action(iid, "delete_item_prep", args);
+                                                                                }
+                                                                            >
+                                                                                "Delete"
+                                                                            </button>
+                                                                        </span>
+                                                                    </div>
+                                                                }
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"New preparation"</label>
+                                                            <input
+                                                                type="text"
+                                                                placeholder="e.g. Fillet, skin off"
+                                                                prop:value=move || new_prep_name.get()
+                                                                on:input=move |ev| set_new_prep_name.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Yield %"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || new_prep_yield.get()
+                                                                on:input=move |ev| set_new_prep_yield.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let iid = detail.item_id;
+                                                                    let args = to_value(&UpsertItemPrepArgs {
+                                                                        prep_id: None,
+                                                                        item_id: iid,
+                                                                        name: new_prep_name.get(),
+                                                                        yield_pct: new_prep_yield.get().trim().parse::<f64>().ok(),
+                                                                    })
+                                                                    .unwrap();
+                                                                    run_yield_action(iid, "upsert_item_prep", args);
+                                                                    set_new_prep_name.set(String::new());
+                                                                    set_new_prep_yield.set(String::new());
+                                                                }
+                                                            >
+                                                                "Add preparation"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <strong style="display: block; margin-top: 12px;">"Yield Tests"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-6">
+                                                            <span>"Date"</span>
+                                                            <span>"Preparation"</span>
+                                                            <span>"Gross"</span>
+                                                            <span>"Trim / Waste"</span>
+                                                            <span>"Net"</span>
+                                                            <span>"Yield"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.yield_tests.clone()
+                                                            key=|test| test.test_id
+                                                            children=move |test| view! {
+                                                                <div class="data-row data-cols-6">
+                                                                    <span>{test.test_date}</span>
+                                                                    <span>{test.prep_name}</span>
+                                                                    <span>{test.gross_qty.map(|q| format!("{:.3} {}", q, test.unit_name)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>
+                                                                        {format!(
+                                                                            "{:.3} / {:.3}",
+                                                                            test.trim_qty.unwrap_or(0.0),
+                                                                            test.waste_qty.unwrap_or(0.0)
+                                                                        )}
+                                                                    </span>
+                                                                    <span>{test.net_qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{test.yield_pct.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Preparation"</label>
+                                                            <select
+                                                                prop:value=move || yield_test_prep.get()
+                                                                on:change=move |ev| set_yield_test_prep.set(event_target_value(&ev))
+                                                            >
+                                                                <option value="">"Whole item"</option>
+                                                                {prep_options
+                                                                    .iter()
+                                                                    .map(|prep| view! { <option value={prep.prep_id.to_string()}>{prep.name.clone()}</option> })
+                                                                    .collect_view()}
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Gross"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || yield_test_gross.get()
+                                                                on:input=move |ev| set_yield_test_gross.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Trim"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || yield_test_trim.get()
+                                                                on:input=move |ev| set_yield_test_trim.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Waste"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || yield_test_waste.get()
+                                                                on:input=move |ev| set_yield_test_waste.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let iid = detail.item_id;
+                                                                    let gross_qty = yield_test_gross.get().trim().parse::<f64>().unwrap_or(0.0);
+                                                                    let args = to_value(&RecordYieldTestArgs {
+                                                                        test: YieldTestInput {
+                                                                            item_id: iid,
+                                                                            prep_id: yield_test_prep.get().trim().parse::<i64>().ok(),
+                                                                            test_date: None,
+                                                                            unit_id: None,
+                                                                            gross_qty,
+                                                                            trim_qty: yield_test_trim.get().trim().parse::<f64>().ok(),
+                                                                            waste_qty: yield_test_waste.get().trim().parse::<f64>().ok(),
+                                                                            net_qty: None,
+                                                                            notes: None,
+                                                                        },
+                                                                    })
+                                                                    .unwrap();
+                                                                    run_yield_action(iid, "record_yield_test", args);
+                                                                    set_yield_test_gross.set(String::new());
+                                                                    set_yield_test_trim.set(String::new());
+                                                                    set_yield_test_waste.set(String::new());
+                                                                }
+                                                            >
+                                                                "Record test"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || item_yield_msg.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Inventory Counts"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-3">
+                                                            <span>"Date"</span>
+                                                            <span>"On Hand"</span>
+                                                            <span></span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.counts.clone()
+                                                            key=|c| c.count_id
+                                                            children=move |c| {
+                                                                let count_id = c.count_id;
+                                                                view! {
+                                                                    <div class="data-row data-cols-3">
+                                                                        <span>{c.count_date}</span>
+                                                                        <span>{c.qty.map(|q| format!("{} {}", q, c.unit_name)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                        <span>
+                                                                            <button
+                                                                                class="button tiny secondary"
+                                                                                on:click=move |_| {
+                                                                                    let args = to_value(&InventoryCountIdArgs { count_id }).unwrap();
+                                                                                    run_yield_action(detail.item_id, "delete_inventory_count", args);
+                                                                                }
+                                                                            >
+                                                                                "Delete"
+                                                                            </button>
+                                                                        </span>
+                                                                    </div>
+                                                                }
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Date"</label>
+                                                            <input
+                                                                type="date"
+                                                                prop:value=move || count_date.get()
+                                                                on:input=move |ev| set_count_date.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"On Hand"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || count_qty.get()
+                                                                on:input=move |ev| set_count_qty.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Unit"</label>
+                                                            <select
+                                                                prop:value=move || count_unit.get()
+                                                                on:change=move |ev| set_count_unit.set(event_target_value(&ev))
+                                                            >
+                                                                <option value="">"Purchase unit"</option>
+                                                                <For
+                                                                    each=move || unit_options.get()
+                                                                    key=|unit| unit.unit_id
+                                                                    children=move |unit| view! {
+                                                                        <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                                                    }
+                                                                />
+                                                            </select>
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let qty = match count_qty.get().trim().parse::<f64>() {
+                                                                        Ok(q) => q,
+                                                                        Err(_) => {
+                                                                            set_item_yield_msg.set("Enter the quantity on hand".to_string());
+                                                                            return;
+                                                                        }
+                                                                    };
+                                                                    let date = count_date.get();
+                                                                    let args = to_value(&RecordInventoryCountArgs {
+                                                                        item_id: detail.item_id,
+                                                                        count_date: if date.trim().is_empty() { None } else { Some(date) },
+                                                                        qty,
+                                                                        unit_id: count_unit.get().trim().parse::<i64>().ok(),
+                                                                    })
+                                                                    .unwrap();
+                                                                    run_yield_action(detail.item_id, "record_inventory_count", args);
+                                                                    set_count_qty.set(String::new());
+                                                                }
+                                                            >
+                                                                "Record count"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Par Levels"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-4">
+                                                            <span>"Storage Area"</span>
+                                                            <span>"Day"</span>
+                                                            <span>"Par"</span>
+                                                            <span></span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.par_levels.clone()
+                                                            key=|p| p.par_id
+                                                            children=move |p| {
+                                                                let par_id = p.par_id;
+                                                                view! {
+                                                                    <div class="data-row data-cols-4">
+                                                                        <span>{if p.storage_type.is_empty() { "Any".to_string() } else { p.storage_type.clone() }}</span>
+                                                                        <span>{p.day_name.clone()}</span>
+                                                                        <span>{format!("{} {}", p.par_qty, p.unit_name)}</span>
+                                                                        <span>
+                                                                            <button
+                                                                                class="button tiny secondary"
+                                                                                on:click=move |_| {
+                                                                                    let args = to_value(&ParLevelIdArgs { par_id }).unwrap();
+                                                                                    run_yield_action(detail.item_id, "delete_par_level", args);
+                                                                                }
+                                                                            >
+                                                                                "Delete"
+                                                                            </button>
+                                                                        </span>
+                                                                    </div>
+                                                                }
+                                                            }
+                                                        />
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Storage Area"</label>
+                                                            <input
+                                                                type="text"
+                                                                placeholder="Walk-in"
+                                                                prop:value=move || par_area.get()
+                                                                on:input=move |ev| set_par_area.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Day"</label>
+                                                            <select
+                                                                prop:value=move || par_day.get()
+                                                                on:change=move |ev| set_par_day.set(event_target_value(&ev))
+                                                            >
+                                                                <option value="">"Every day"</option>
+                                                                <option value="0">"Sunday"</option>
+                                                                <option value="1">"Monday"</option>
+                                                                <option value="2">"Tuesday"</option>
+                                                                <option value="3">"Wednesday"</option>
+                                                                <option value="4">"Thursday"</option>
+                                                                <option value="5">"Friday"</option>
+                                                                <option value="6">"Saturday"</option>
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Par"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || par_qty.get()
+                                                                on:input=move |ev| set_par_qty.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Unit"</label>
+                                                            <select
+                                                                prop:value=move || par_unit.get()
+                                                                on:change=move |ev| set_par_unit.set(event_target_value(&ev))
+                                                            >
+                                                                <option value="">"Purchase unit"</option>
+                                                                <For
+                                                                    each=move || unit_options.get()
+                                                                    key=|unit| unit.unit_id
+                                                                    children=move |unit| view! {
+                                                                        <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                                                    }
+                                                                />
+                                                            </select>
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| {
+                                                                    let qty = match par_qty.get().trim().parse::<f64>() {
+                                                                        Ok(q) => q,
+                                                                        Err(_) => {
+                                                                            set_item_yield_msg.set("Enter the par quantity".to_string());
+                                                                            return;
+                                                                        }
+                                                                    };
+                                                                    let area = par_area.get();
+                                                                    let args = to_value(&SaveParLevelArgs {
+                                                                        item_id: detail.item_id,
+                                                                        storage_type: if area.trim().is_empty() { None } else { Some(area) },
+                                                                        day_of_week: par_day.get().trim().parse::<i64>().ok(),
+                                                                        par_qty: qty,
+                                                                        unit_id: par_unit.get().trim().parse::<i64>().ok(),
+                                                                    })
+                                                                    .unwrap();
+                                                                    run_yield_action(detail.item_id, "save_par_level", args);
+                                                                    set_par_qty.set(String::new());
+                                                                }
+                                                            >
+                                                                "Set par"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Assign Purchase Unit"</strong>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Unit"</label>
+                                                            <select
+                                                                prop:value=purch_unit_selected_id
+                                                                on:change=move |ev| {
+                                                                    set_purch_unit_selected_id.set(event_target_value(&ev));
+                                                                }
+                                                            >
+                                                                <option value="">"Select unit"</option>
+                                                                <For
+                                                                    each=move || unit_options.get()
+                                                                    key=|unit| unit.unit_id
+                                                                    children=move |unit| view! {
+                                                                        <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                                                    }
+                                                                />
+                                                            </select>
+                                                        </div>
+                                                        <div class="input" style="flex: 0 0 150px;">
+                                                            <label>"Default"</label>
+                                                            <label style="display: inline-flex; gap: 8px; align-items: center;">
+                                                                <input
+                                                                    type="checkbox"
+                                                                    prop:checked=purch_unit_set_default
+                                                                    on:change=move |ev| {
+                                                                        set_purch_unit_set_default.set(event_target_checked(&ev));
+                                                                    }
+                                                                />
+                                                                <span>"Set default"</span>
+                                                            </label>
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| assign_purch_unit(detail.item_id)
+                                                            >
+                                                                "Save unit"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || purch_unit_status.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Purchase Units"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-3">
+                                                            <span>"Unit"</span>
+                                                            <span>"Default"</span>
+                                                            <span>"Status"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.purch_units.clone()
+                                                            key=|unit| unit.unit_id.unwrap_or(0)
+                                                            children=move |unit| view! {
+                                                                <div class="data-row data-cols-3">
+                                                                    <span>{unit.unit_name}</span>
+                                                                    <span>{unit.is_default.map(|v| if v == 1 { "Yes" } else { "No" }.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{unit.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Vendor Prices"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-6">
+                                                            <span>"Vendor"</span>
+                                                            <span>"Current Price"</span>
+                                                            <span>"Previous Price"</span>
+                                                            <span>"Diff %"</span>
+                                                            <span>"Pack"</span>
+                                                            <span>"Status"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.prices.clone()
+                                                            key=|price| price.vendor_id.unwrap_or(0)
+                                                            children=move |price| {
+                                                                let diff_class = match price.diff_pct {
+                                                                    Some(d) if d > 0.0 => "diff-up",
+                                                                    Some(d) if d < 0.0 => "diff-down",
+                                                                    Some(_) => "diff-zero",
+                                                                    None => "",
+                                                                };
+                                                                let diff_text = match price.diff_pct {
+                                                                    Some(d) => format!("{:+.1}%", d),
+                                                                    None => "-".to_string(),
+                                                                };
+                                                                view! {
+                                                                    <div class="data-row data-cols-6">
+                                                                        <span>{price.vendor_name}</span>
+                                                                        <span>{price.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                        <span>{price.prev_price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                        <span class={diff_class}>{diff_text}</span>
+                                                                        <span>
+                                                                            {price.pack}
+                                                                            <div class="recipe-meta">{price.pack_reading}</div>
+                                                                        </span>
+                                                                        <span>{price.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    </div>
+                                                                }
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Manual Price Override"</strong>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input">
+                                                            <label>"Vendor"</label>
+                                                            <select
+                                                                prop:value=manual_price_vendor_id
+                                                                on:change=move |ev| {
+                                                                    set_manual_price_vendor_id.set(event_target_value(&ev));
+                                                                }
+                                                            >
+                                                                <option value="">"Select vendor"</option>
+                                                                <For
+                                                                    each=move || editor_vendor_options.get()
+                                                                    key=|vendor| vendor.vendor_id
+                                                                    children=move |vendor| view! {
+                                                                        <option value={vendor.vendor_id.to_string()}>{format!("{} - {}", vendor.vendor_id, vendor.name)}</option>
+                                                                    }
+                                                                />
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Price"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="0.0001"
+                                                                min="0"
+                                                                prop:value=manual_price_value
+                                                                on:input=move |ev| {
+                                                                    set_manual_price_value.set(event_target_value(&ev));
+                                                                }
                                                             />
-                                                        </tr>
-                                                    }
-                                                />
-                                            </tbody>
-                                        </table>
-                                    </div>
-                                </Show>
-                            </div>
-                        </Show>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Pack"</label>
+                                                            <input
+                                                                type="text"
+                                                                prop:value=manual_price_pack
+                                                                on:input=move |ev| {
+                                                                    set_manual_price_pack.set(event_target_value(&ev));
+                                                                }
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny"
+                                                                on:click=move |_| save_manual_price(detail.item_id)
+                                                            >
+                                                                "Save price"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || manual_price_status.get()}</div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Conversions"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-5">
+                                                            <span>"Vendor"</span>
+                                                            <span>"Unit 1"</span>
+                                                            <span>"Qty 1"</span>
+                                                            <span>"Unit 2"</span>
+                                                            <span>"Qty 2"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.conversions.clone()
+                                                            key=|conv| (conv.unit_id1, conv.unit_id2, conv.vendor_id)
+                                                            children=move |conv| view! {
+                                                                <div class="data-row data-cols-5">
+                                                                    <span>{conv.vendor_id}</span>
+                                                                    <span>{conv.unit_id1}</span>
+                                                                    <span>{format!("{:.3}", conv.qty1)}</span>
+                                                                    <span>{conv.unit_id2}</span>
+                                                                    <span>{format!("{:.3}", conv.qty2)}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Recipe Usage"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-3">
+                                                            <span>"Recipe"</span>
+                                                            <span>"Qty"</span>
+                                                            <span>"Unit"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.usage.clone()
+                                                            key=|usage| usage.recipe_id
+                                                            children=move |usage| view! {
+                                                                <div class="data-row data-cols-3">
+                                                                    <span>{usage.recipe_name}</span>
+                                                                    <span>{usage.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{usage.unit_name}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Local Conversions"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-4">
+                                                            <span>"Vendor"</span>
+                                                            <span>"Recipe Unit"</span>
+                                                            <span>"Purch Unit"</span>
+                                                            <span>"Hits"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.missing_edges.clone()
+                                                            key=|edge| (edge.vendor_id, edge.recipe_unit.clone(), edge.purch_unit.clone())
+                                                            children=move |edge| view! {
+                                                                <div class="data-row data-cols-4">
+                                                                    <span>{edge.vendor_id}</span>
+                                                                    <span>{edge.recipe_unit}</span>
+                                                                    <span>{edge.purch_unit}</span>
+                                                                    <span>{edge.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                            </div>
+                                        }
+                                    })
+                                })}
+                            </Show>
+                            <Show when=move || inventory_detail_loading.get()>
+                                <div class="status">"Loading item detail..."</div>
+                            </Show>
+                        </div>
                     </div>
+                </Show>
 
-                    <Show
-                        when=move || has_summary.get() && !summary.get().warnings.is_empty()
-                    >
-                        <div class="panel">
-                            <div class="row">
-                                <div>
-                                    <strong>"Import Notes"</strong>
-                                    <div class="status">
-                                        {move || format!("Warnings: {}", summary.get().warnings.len())}
-                                    </div>
-                                </div>
-                                <div style="margin-left: auto;">
+                <Show when=move || active_panel.get() == "recipes">
+                    <div class="panel">
+                        <div class="row">
+                            <div class="input">
+                                <label>"Search recipes"</label>
+                                <input
+                                    type="text"
+                                    prop:value=recipe_query
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_recipe_query.set(value);
+                                    }
+                                />
+                            </div>
+                            <div class="input" style="flex: 0 0 180px;">
+                                <label>"Sort by"</label>
+                                <select
+                                    prop:value=move || recipe_order_by.get()
+                                    on:change=move |ev| set_recipe_order_by.set(event_target_value(&ev))
+                                >
+                                    <option value="">"Name"</option>
+                                    <option value="margin">"Margin (high first)"</option>
+                                    <option value="food_cost_pct">"Food cost % (low first)"</option>
+                                    <option value="cost_per_portion">"Cost per portion"</option>
+                                </select>
+                            </div>
+                            <div class="input" style="align-self: end;">
+                                <div class="row">
+                                    <button class="button" on:click=move |_| {
+                                        set_recipe_page.set(0);
+                                        trigger_recipe_fetch(
+                                            recipe_query.get(),
+                                            recipe_order_by.get(),
+                                            0,
+                                            recipe_limit,
+                                            set_recipe_loading,
+                                            set_recipe_status,
+                                            set_recipe_list,
+                                            set_recipe_total,
+                                            set_recipe_filtered,
+                                            set_recipe_loaded,
+                                            true,
+                                            set_recipe_detail_loading,
+                                            set_recipe_detail_status,
+                                            set_selected_recipe,
+                                        );
+                                    } disabled=move || recipe_loading.get()>
+                                        {move || if recipe_loading.get() { "Searching..." } else { "Search" }}
+                                    </button>
                                     <button class="button secondary" on:click=move |_| {
-                                        set_show_warnings.set(!show_warnings.get());
+                                        set_recipe_query.set(String::new());
+                                        set_recipe_page.set(0);
+                                        trigger_recipe_fetch(
+                                            String::new(),
+                                            recipe_order_by.get(),
+                                            0,
+                                            recipe_limit,
+                                            set_recipe_loading,
+                                            set_recipe_status,
+                                            set_recipe_list,
+                                            set_recipe_total,
+                                            set_recipe_filtered,
+                                            set_recipe_loaded,
+                                            true,
+                                            set_recipe_detail_loading,
+                                            set_recipe_detail_status,
+                                            set_selected_recipe,
+                                        );
                                     }>
-                                        {move || if show_warnings.get() { "Hide details" } else { "View details" }}
+                                        "Reset"
                                     </button>
                                 </div>
                             </div>
-                            <Show when=move || show_warnings.get()>
-                                <div class="log">
-                                    <For
-                                        each=move || summary.get().warnings
-                                        key=|msg| msg.clone()
-                                        children=move |msg| view! { <div>{msg}</div> }
-                                    />
-                                </div>
-                            </Show>
                         </div>
-                    </Show>
+                        <div class="status">{move || recipe_status.get()}</div>
+                    </div>
 
-                    // ── PDF Invoice Import ──
-                    <div class="panel">
-                        <div class="row">
-                            <div>
-                                <strong>"Import PDF Invoice"</strong>
-                                <div class="status">"Upload a digital vendor invoice (PDF) to preview and import line items."</div>
+                    <div class="panel recipe-grid">
+                        <div class="recipe-list">
+                            <div class="recipe-list-header">
+                                <span>"Recipes"</span>
+                                <span>{move || format!(
+                                    "{} / {}",
+                                    recipe_filtered.get(),
+                                    recipe_total.get()
+                                )}</span>
                             </div>
-                        </div>
-                        <div class="row" style="margin-top: 12px;">
-                            <button
-                                class="button secondary"
-                                on:click=move |_| {
-                                    spawn_local(async move {
-                                        let w = match window() {
-                                            Some(w) => w,
-                                            None => return,
-                                        };
-                                        let tauri = match Reflect::get(&w, &JsValue::from_str("__TAURI__")) {
-                                            Ok(t) if !t.is_undefined() => t,
-                                            _ => return,
-                                        };
-                                        let dialog = match Reflect::get(&tauri, &JsValue::from_str("dialog")) {
-                                            Ok(d) if !d.is_undefined() => d,
-                                            _ => return,
-                                        };
-                                        let open_fn = match Reflect::get(&dialog, &JsValue::from_str("open")) {
-                                            Ok(f) if f.is_function() => f,
-                                            _ => return,
-                                        };
-                                        let opts = js_sys::Object::new();
-                                        let _ = Reflect::set(&opts, &JsValue::from_str("title"), &JsValue::from_str("Select PDF Invoice"));
-                                        let _ = Reflect::set(&opts, &JsValue::from_str("directory"), &JsValue::from_bool(false));
-                                        let _ = Reflect::set(&opts, &JsValue::from_str("multiple"), &JsValue::from_bool(false));
-                                        let filter = js_sys::Object::new();
-                                        let _ = Reflect::set(&filter, &JsValue::from_str("name"), &JsValue::from_str("PDF"));
-                                        let exts = js_sys::Array::new();
-                                        exts.push(&JsValue::from_str("pdf"));
-                                        let _ = Reflect::set(&filter, &JsValue::from_str("extensions"), &exts);
-                                        let filters = js_sys::Array::new();
-                                        filters.push(&filter);
-                                        let _ = Reflect::set(&opts, &JsValue::from_str("filters"), &filters);
-
-                                        let func = open_fn.dyn_ref::<js_sys::Function>().unwrap();
-                                        if let Ok(promise) = func.call1(&JsValue::NULL, &opts) {
-                                            if let Ok(p) = promise.dyn_into::<Promise>() {
-                                                if let Ok(result) = JsFuture::from(p).await {
-                                                    if let Some(path) = result.as_string() {
-                                                        set_pdf_import_status.set("Extracting PDF...".to_string());
-                                                        let args = to_value(&PdfPathArgs { pdf_path: path }).unwrap();
-                                                        match invoke_cmd::<PdfInvoicePreview>("preview_pdf_invoice", args).await {
-                                                            Ok(preview) => {
-                                                                set_pdf_invoice_no.set(preview.invoice_no.clone());
-                                                                set_pdf_invoice_date.set(preview.invoice_date.clone());
-                                                                set_pdf_import_status.set(format!(
-                                                                    "Extracted {} line items from PDF",
-                                                                    preview.lines.len()
-                                                                ));
-                                                                set_pdf_preview.set(Some(preview));
-                                                            }
-                                                            Err(err) => {
-                                                                set_pdf_import_status.set(format!("PDF extraction failed: {err}"));
-                                                            }
-                                                        }
-                                                    }
+                            <div class="recipe-list-body">
+                                <For
+                                    each=move || recipe_list.get()
+                                    key=|item| item.recipe_id
+                                    children=move |item| {
+                                        let recipe_id = item.recipe_id;
+                                        view! {
+                                            <div
+                                                class="recipe-row"
+                                                class:selected=move || selected_recipe.get().map(|r| r.recipe_id == recipe_id).unwrap_or(false)
+                                                on:click=move |_| {
+                                                    trigger_recipe_detail_fetch(
+                                                        recipe_id,
+                                                        set_recipe_detail_loading,
+                                                        set_recipe_detail_status,
+                                                        set_selected_recipe,
+                                                    );
                                                 }
-                                            }
+                                            >
+                                                <div class="recipe-name">{item.name}</div>
+                                                <div class="recipe-meta">
+                                                    {match (item.margin, item.food_cost_pct, item.cost_per_portion) {
+                                                        (Some(m), Some(pct), _) => format!("{} items \u{2014} margin {} ({:.1}% FC)", item.item_count, format_money(m), pct),
+                                                        (_, _, Some(c)) => format!("{} items \u{2014} {} / portion", item.item_count, format_money(c)),
+                                                        _ => format!("{} items", item.item_count),
+                                                    }}
+                                                </div>
+                                            </div>
                                         }
-                                    });
+                                    }
+                                />
+                            </div>
+                            <div class="row" style="margin-top: 12px;">
+                                <button class="button secondary" on:click=move |_| {
+                                    let current = recipe_page.get();
+                                    if current > 0 {
+                                        set_recipe_page.set(current - 1);
+                                        trigger_recipe_fetch(
+                                            recipe_query.get(),
+                                            recipe_order_by.get(),
+                                            current - 1,
+                                            recipe_limit,
+                                            set_recipe_loading,
+                                            set_recipe_status,
+                                            set_recipe_list,
+                                            set_recipe_total,
+                                            set_recipe_filtered,
+                                            set_recipe_loaded,
+                                            true,
+                                            set_recipe_detail_loading,
+                                            set_recipe_detail_status,
+                                            set_selected_recipe,
+                                        );
+                                    }
+                                } disabled=move || recipe_page.get() == 0 || recipe_loading.get()>
+                                    "Previous"
+                                </button>
+                                <button class="button" on:click=move |_| {
+                                    let current = recipe_page.get();
+                                    let max = (recipe_filtered.get() as f64 / recipe_limit as f64).ceil() as usize;
+                                    if current + 1 < max {
+                                        set_recipe_page.set(current + 1);
+                                        trigger_recipe_fetch(
+                                            recipe_query.get(),
+                                            recipe_order_by.get(),
+                                            current + 1,
+                                            recipe_limit,
+                                            set_recipe_loading,
+                                            set_recipe_status,
+                                            set_recipe_list,
+                                            set_recipe_total,
+                                            set_recipe_filtered,
+                                            set_recipe_loaded,
+                                            true,
+                                            set_recipe_detail_loading,
+                                            set_recipe_detail_status,
+                                            set_selected_recipe,
+                                        );
+                                    }
+                                } disabled=move || {
+                                    let max = (recipe_filtered.get() as f64 / recipe_limit as f64).ceil() as usize;
+                                    recipe_loading.get() || max == 0 || recipe_page.get() + 1 >= max
+                                }>
+                                    "Next"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="recipe-detail">
+                            <div class="status">{move || recipe_detail_status.get()}</div>
+                            <Show
+                                when=move || selected_recipe.get().is_some()
+                                fallback=move || view! {
+                                    <div class="recipe-empty">
+                                        <strong>"Select a recipe"</strong>
+                                        <p>"Choose a recipe from the list to view ingredients and costing."</p>
+                                    </div>
                                 }
                             >
-                                "Select PDF File"
-                            </button>
-                        </div>
-                        <div class="status">{move || pdf_import_status.get()}</div>
-
-                        <Show when=move || pdf_preview.get().is_some()>
-                            {move || pdf_preview.with(|opt| {
-                                opt.as_ref().map(|preview| {
-                                    let preview = preview.clone();
-                                    let line_count = preview.lines.len();
-                                    view! {
-                                        <div style="margin-top: 12px;">
-                                            <div class="row">
-                                                <div class="input">
-                                                    <label>"Vendor"</label>
-                                                    <select
-                                                        prop:value=pdf_vendor_id
-                                                        on:change=move |ev| {
-                                                            set_pdf_vendor_id.set(event_target_value(&ev));
-                                                        }
-                                                    >
-                                                        <option value="">{format!("Detected: {}", preview.vendor_guess)}</option>
-                                                        <For
-                                                            each=move || editor_vendor_options.get()
-                                                            key=|v| v.vendor_id
-                                                            children=move |v| view! {
-                                                                <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
-                                                            }
-                                                        />
-                                                    </select>
-                                                </div>
-                                                <div class="input">
-                                                    <label>"Invoice #"</label>
-                                                    <input
-                                                        type="text"
-                                                        prop:value=pdf_invoice_no
-                                                        on:input=move |ev| {
-                                                            set_pdf_invoice_no.set(event_target_value(&ev));
-                                                        }
-                                                    />
+                                {move || selected_recipe.with(|opt| {
+                                    opt.as_ref().map(|detail| {
+                                        let detail = detail.clone();
+                                        let instruction_lines = detail
+                                            .instructions
+                                            .lines()
+                                            .enumerate()
+                                            .map(|(idx, line)| (idx, line.trim_end().to_string()))
+                                            .filter(|(_, line)| !line.is_empty())
+                                            .collect::<Vec<(usize, String)>>();
+                                        let has_instructions = !instruction_lines.is_empty();
+                                        let instruction_lines = Arc::new(instruction_lines);
+                                        let recipe_id_for_export = detail.recipe_id;
+                                        let recipe_id_for_edit = detail.recipe_id;
+                                        let edit_init_name = detail.name.clone();
+                                        let edit_init_instructions = detail.instructions.clone();
+                                        let edit_init_yield_qty = detail.yield_qty.map(|q| q.to_string()).unwrap_or_default();
+                                        let edit_init_yield_unit = detail.yield_unit_id.map(|u| u.to_string()).unwrap_or_default();
+                                        let edit_init_servings = detail.servings.map(|s| s.to_string()).unwrap_or_default();
+                                        let edit_init_pricing = (
+                                            detail.portion_size.map(|v| v.to_string()).unwrap_or_default(),
+                                            detail.portion_unit_id.map(|v| v.to_string()).unwrap_or_default(),
+                                            detail.target_food_cost_pct.map(|v| v.to_string()).unwrap_or_default(),
+                                            detail.menu_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+                                        );
+                                        let pricing_label = {
+                                            let portion = match detail.portion_size {
+                                                Some(ps) => format!("{} {} portion", ps, detail.portion_unit_name),
+                                                None => "Portion".to_string(),
+                                            };
+                                            let cost = detail
+                                                .cost_per_portion
+                                                .map(|c| format!("{} {}", portion, format_money(c)))
+                                                .unwrap_or_else(|| format!("{} cost unavailable", portion));
+                                            let suggested = detail
+                                                .suggested_price
+                                                .map(|p| format!(" \u{2014} suggested {} at {:.1}%", format_money(p), detail.effective_target_pct))
+                                                .unwrap_or_default();
+                                            let actual = match (detail.menu_price, detail.food_cost_pct, detail.margin) {
+                                                (Some(p), Some(pct), Some(m)) => format!(
+                                                    " \u{2014} menu {} ({:.1}% FC, margin {})",
+                                                    format_money(p),
+                                                    pct,
+                                                    format_money(m)
+                                                ),
+                                                (Some(p), _, _) => format!(" \u{2014} menu {}", format_money(p)),
+                                                _ => String::new(),
+                                            };
+                                            format!("{}{}{}", cost, suggested, actual)
+                                        };
+                                        let recipe_id_for_scale = detail.recipe_id;
+                                        let recipe_id_for_nutrition = detail.recipe_id;
+                                        let yield_label = match detail.yield_qty {
+                                            Some(q) => {
+                                                let per_unit = detail
+                                                    .cost_per_yield_unit
+                                                    .map(|c| format!(" ({} / {})", format_money(c), detail.yield_unit_name))
+                                                    .unwrap_or_default();
+                                                format!("Yield {:.2} {}{}", q, detail.yield_unit_name, per_unit)
+                                            }
+                                            None => "No yield set".to_string(),
+                                        };
+                                        let recipe_name_for_file = detail.name.chars()
+                                            .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-')
+                                            .collect::<String>()
+                                            .replace(' ', "_");
+                                        let pdf_name = format!("{}.pdf", recipe_name_for_file);
+                                        let docx_name = format!("{}.docx", recipe_name_for_file);
+                                        let label_pdf_name = format!("{} Nutrition Facts.pdf", recipe_name_for_file);
+                                        let label_png_name = format!("{} Nutrition Facts.png", recipe_name_for_file);
+                                        view! {
+                                            <div>
+                                                <div class="recipe-title">{detail.name}</div>
+                                                <div class="recipe-summary">
+                                                    <span>{format!("{} items", detail.item_count)}</span>
+                                                    <span>
+                                                        {if detail.missing_costs > 0 {
+                                                            format!("Cost incomplete ({} missing)", detail.missing_costs)
+                                                        } else {
+                                                            format!("Total cost {}", format_money(detail.total_cost))
+                                                        }}
+                                                    </span>
+                                                    <span>{yield_label}</span>
+                                                    <span>
+                                                        {detail.servings.map(|s| format!("{:.1} servings", s)).unwrap_or_else(|| "No servings set".to_string())}
+                                                    </span>
                                                 </div>
-                                                <div class="input">
-                                                    <label>"Date"</label>
-                                                    <input
-                                                        type="text"
-                                                        prop:value=pdf_invoice_date
-                                                        on:input=move |ev| {
-                                                            set_pdf_invoice_date.set(event_target_value(&ev));
-                                                        }
-                                                    />
+                                                <div class="recipe-meta">{pricing_label}</div>
+                                                <Show when={
+                                                    let has = !detail.allergens.is_empty();
+                                                    move || has
+                                                }>
+                                                    <div class="allergen-notice">
+                                                        <span class="allergen-icon">{"\u{26A0}"}</span>
+                                                        <strong>"Allergen Notice: "</strong>
+                                                        <span class="allergen-list">
+                                                            {detail.allergens.join(", ")}
+                                                        </span>
+                                                    </div>
+                                                </Show>
+                                                <div class="row" style="margin: 10px 0; gap: 8px;">
+                                                    <button class="button tiny" on:click=move |_| {
+                                                        let rid = recipe_id_for_export;
+                                                        let name = pdf_name.clone();
+                                                        trigger_save_dialog_and_export(
+                                                            "Save Recipe PDF",
+                                                            &name,
+                                                            "PDF",
+                                                            "pdf",
+                                                            set_export_status,
+                                                            move |path| {
+                                                                let (scale_mode, scale_target) = active_scale(rid);
+                                                                ("export_recipe_pdf".to_string(),
+                                                                    to_value(&ExportRecipeArgs { recipe_id: rid, output_path: path, scale_mode, scale_target }).unwrap())
+                                                            },
+                                                        );
+                                                    }>"Export PDF"</button>
+                                                    <button class="button tiny secondary" on:click=move |_| {
+                                                        let rid = recipe_id_for_export;
+                                                        let name = docx_name.clone();
+                                                        trigger_save_dialog_and_export(
+                                                            "Save Recipe Word Document",
+                                                            &name,
+                                                            "Word Document",
+                                                            "docx",
+                                                            set_export_status,
+                                                            move |path| {
+                                                                let (scale_mode, scale_target) = active_scale(rid);
+                                                                ("export_recipe_docx".to_string(),
+                                                                    to_value(&ExportRecipeArgs { recipe_id: rid, output_path: path, scale_mode, scale_target }).unwrap())
+                                                            },
+                                                        );
+                                                    }>"Export Word"</button>
                                                 </div>
-                                            </div>
-                                            <div class="data-table" style="margin-top: 12px;">
-                                                <div class="data-header data-cols-5">
-                                                    <span>"Item"</span>
-                                                    <span>"Qty"</span>
-                                                    <span>"Unit"</span>
-                                                    <span>"Unit Price"</span>
-                                                    <span>"Ext. Cost"</span>
+                                                <Show when=move || !export_status.get().is_empty()>
+                                                    <div class="status">{move || export_status.get()}</div>
+                                                </Show>
+                                                <div class="detail-block" style="margin-top: 14px;">
+                                                    <strong>"Scale Recipe"</strong>
+                                                    <div class="row" style="margin-top: 8px;">
+                                                        <div class="input">
+                                                            <label>"Scale by"</label>
+                                                            <select
+                                                                prop:value=move || scale_mode.get()
+                                                                on:change=move |ev| set_scale_mode.set(event_target_value(&ev))
+                                                            >
+                                                                <option value="factor">"Factor"</option>
+                                                                <option value="servings">"Servings"</option>
+                                                                <option value="yield">"Yield"</option>
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Target"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                prop:value=move || scale_target.get()
+                                                                on:input=move |ev| set_scale_target.set(event_target_value(&ev))
+                                                            />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button class="button tiny" on:click=move |_| run_scale_recipe(recipe_id_for_scale)>
+                                                                "Scale"
+                                                            </button>
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button tiny secondary"
+                                                                on:click=move |_| {
+                                                                    set_scale_result.set(None);
+                                                                    set_scale_status.set(String::new());
+                                                                }
+                                                            >
+                                                                "Reset"
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || scale_status.get()}</div>
+                                                    {move || {
+                                                        scale_result
+                                                            .get()
+                                                            .filter(|r| r.recipe_id == recipe_id_for_scale)
+                                                            .map(|scaled| {
+                                                                let summary = format!(
+                                                                    "x{:.2}{}{} \u{2014} {}",
+                                                                    scaled.factor,
+                                                                    scaled.servings.map(|s| format!(", {:.0} servings", s)).unwrap_or_default(),
+                                                                    scaled.yield_qty.map(|q| format!(", yield {:.2} {}", q, scaled.yield_unit_name)).unwrap_or_default(),
+                                                                    if scaled.missing_costs > 0 {
+                                                                        format!("batch cost incomplete ({} missing)", scaled.missing_costs)
+                                                                    } else {
+                                                                        format!("batch cost {}", format_money(scaled.total_cost))
+                                                                    },
+                                                                );
+                                                                view! {
+                                                                    <div class="recipe-meta">{summary}</div>
+                                                                    <div class="data-table">
+                                                                        <div class="data-header data-cols-4">
+                                                                            <span>"Item"</span>
+                                                                            <span>"Original"</span>
+                                                                            <span>"Scaled"</span>
+                                                                            <span>"Cost"</span>
+                                                                        </div>
+                                                                        <For
+                                                                            each=move || scaled.lines.clone()
+                                                                            key=|line| line.recp_item_id
+                                                                            children=move |line| view! {
+                                                                                <div class="data-row data-cols-4">
+                                                                                    <span>{line.item_name}</span>
+                                                                                    <span>
+                                                                                        {format!(
+                                                                                            "{} {}",
+                                                                                            line.orig_qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string()),
+                                                                                            line.orig_unit_name
+                                                                                        )}
+                                                                                    </span>
+                                                                                    <span>{format!("{} {}", line.display_qty, line.unit_name)}</span>
+                                                                                    <span>{line.extended_cost.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                                </div>
+                                                                            }
+                                                                        />
+                                                                    </div>
+                                                                }
+                                                            })
+                                                    }}
                                                 </div>
-                                                <For
-                                                    each=move || {
-                                                        let v: Vec<(usize, PdfInvoiceLine)> = preview.lines.clone().into_iter().enumerate().collect();
-                                                        v
-                                                    }
-                                                    key=|(i, _)| *i
-                                                    children=move |(_, line)| view! {
-                                                        <div class="data-row data-cols-5">
-                                                            <span>{line.item_name}</span>
-                                                            <span>{line.qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string())}</span>
-                                                            <span>{line.unit}</span>
-                                                            <span>{line.unit_price.map(|p| format!("${:.2}", p)).unwrap_or_else(|| "-".to_string())}</span>
-                                                            <span>{line.ext_cost.map(|c| format!("${:.2}", c)).unwrap_or_else(|| "-".to_string())}</span>
+                                                <div class="detail-block" style="margin-top: 14px;">
+                                                    <div class="row" style="align-items: center; gap: 8px;">
+                                                        <strong>"Nutrition"</strong>
+                                                        <button class="button tiny secondary" on:click=move |_| run_recipe_nutrition(recipe_id_for_nutrition)>
+                                                            "Calculate"
+                                                        </button>
+                                                    </div>
+                                                    <div class="status">{move || recipe_nutrition_status.get()}</div>
+                                                    {move || {
+                                                        recipe_nutrition
+                                                            .get()
+                                                            .filter(|n| n.recipe_id == recipe_id_for_nutrition)
+                                                            .map(|n| {
+                                                                let counted = n.lines.iter().filter(|l| l.complete).count();
+                                                                let completeness = if n.lines.is_empty() {
+                                                                    "No ingredients".to_string()
+                                                                } else if n.missing_lines == 0 {
+                                                                    format!("Complete: all {} lines counted", n.lines.len())
+                                                                } else {
+                                                                    format!(
+                                                                        "{:.0}% complete: {} of {} lines counted, {} ingredients missing; totals are a lower bound and per-serving amounts wait for the rest",
+                                                                        counted as f64 * 100.0 / n.lines.len() as f64,
+                                                                        counted,
+                                                                        n.lines.len(),
+                                                                        n.missing_lines
+                                                                    )
+                                                                };
+                                                                let weight = match (n.total_grams, n.servings) {
+                                                                    (Some(g), Some(s)) => format!(" \u{2014} batch {:.0} g, {:.0} g per serving", g, g / s),
+                                                                    (Some(g), None) => format!(" \u{2014} batch {:.0} g; set servings for per-serving amounts", g),
+                                                                    (None, Some(s)) => format!(" \u{2014} {:.1} servings", s),
+                                                                    (None, None) => " \u{2014} set servings for per-serving amounts".to_string(),
+                                                                };
+                                                                let nutrient_rows = n
+                                                                    .nutrients
+                                                                    .iter()
+                                                                    .enumerate()
+                                                                    .map(|(idx, info)| {
+                                                                        let total = n.totals.get(idx).copied().unwrap_or(0.0);
+                                                                        let per_serving = n.per_serving.as_ref().and_then(|p| p.get(idx).copied());
+                                                                        let gaps = n.gaps.get(idx).copied().unwrap_or(0);
+                                                                        view! {
+                                                                            <div class="data-row data-cols-4">
+                                                                                <span>{format!("{} ({})", info.label, info.unit)}</span>
+                                                                                <span>{format_nutrient(total, &info.unit)}</span>
+                                                                                <span>{per_serving.map(|v| format_nutrient(v, &info.unit)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                                <span>
+                                                                                    {if gaps > 0 {
+                                                                                        format!("Not reported for {} ingredients", gaps)
+                                                                                    } else {
+                                                                                        String::new()
+                                                                                    }}
+                                                                                </span>
+                                                                            </div>
+                                                                        }
+                                                                    })
+                                                                    .collect_view();
+                                                                let slot = |key: &str| n.nutrients.iter().position(|info| info.key == key);
+                                                                let shown = [slot("calories"), slot("fat_g"), slot("carbs_g"), slot("protein_g")];
+                                                                let line_rows = n
+                                                                    .lines
+                                                                    .iter()
+                                                                    .map(|line| {
+                                                                        let amount = |idx: Option<usize>| {
+                                                                            idx.and_then(|i| line.values.get(i).copied().flatten())
+                                                                                .map(|v| format!("{:.1}", v))
+                                                                                .unwrap_or_else(|| "-".to_string())
+                                                                        };
+                                                                        view! {
+                                                                            <div class="data-row data-cols-nutrition">
+                                                                                <span>{line.item_name.clone()}</span>
+                                                                                <span>
+                                                                                    {format!(
+                                                                                        "{} {}",
+                                                                                        line.qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string()),
+                                                                                        line.unit_name
+                                                                                    )}
+                                                                                </span>
+                                                                                <span>{line.grams.map(|g| format!("{:.0} g", g)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                                <span>{amount(shown[0])}</span>
+                                                                                <span>{amount(shown[1])}</span>
+                                                                                <span>{amount(shown[2])}</span>
+                                                                                <span>{amount(shown[3])}</span>
+                                                                                <span title=line.source.clone()>{line.status.clone()}</span>
+                                                                            </div>
+                                                                        }
+                                                                    })
+                                                                    .collect_view();
+                                                                view! {
+                                                                    <div class="recipe-meta">{format!("{}{}", completeness, weight)}</div>
+                                                                    <div class="data-table">
+                                                                        <div class="data-header data-cols-4">
+                                                                            <span>"Nutrient"</span>
+                                                                            <span>"Batch"</span>
+                                                                            <span>"Per serving"</span>
+                                                                            <span>"Gaps"</span>
+                                                                        </div>
+                                                                        {nutrient_rows}
+                                                                    </div>
+                                                                    <div class="data-table" style="margin-top: 10px;">
+                                                                        <div class="data-header data-cols-nutrition">
+                                                                            <span>"Ingredient"</span>
+                                                                            <span>"Qty"</span>
+                                                                            <span>"Weight"</span>
+                                                                            <span>"Calories"</span>
+                                                                            <span>"Fat (g)"</span>
+                                                                            <span>"Carbs (g)"</span>
+                                                                            <span>"Protein (g)"</span>
+                                                                            <span>"Status"</span>
+                                                                        </div>
+                                                                        {line_rows}
+                                                                    </div>
+                                                                }
+                                                            })
+                                                    }}
+                                                    <div class="row" style="margin-top: 12px; align-items: end; gap: 8px;">
+                                                        <div class="input" style="flex: 0 0 160px;">
+                                                            <label>"Servings per package"</label>
+                                                            <input
+                                                                type="number"
+                                                                min="0"
+                                                                step="0.5"
+                                                                prop:value=move || label_servings.get()
+                                                                on:input=move |ev| set_label_servings.set(event_target_value(&ev))
+                                                            />
                                                         </div>
-                                                    }
-                                                />
-                                            </div>
-                                            <div class="row" style="margin-top: 12px;">
-                                                <button
-                                                    class="button import-btn"
-                                                    on:click=move |_| {
-                                                        let vid = match pdf_vendor_id.get().parse::<i64>() {
-                                                            Ok(v) => v,
-                                                            Err(_) => {
-                                                                set_pdf_import_status.set("Please select a vendor".to_string());
-                                                                return;
+                                                        <button class="button tiny secondary" on:click=move |_| run_nutrition_label(recipe_id_for_nutrition)>
+                                                            "Nutrition Facts label"
+                                                        </button>
+                                                    </div>
+                                                    <div class="status">{move || label_status.get()}</div>
+                                                    {move || {
+                                                        let pdf_name = label_pdf_name.clone();
+                                                        let png_name = label_png_name.clone();
+                                                        nutrition_label
+                                                            .get()
+                                                            .filter(|l| l.recipe_id == recipe_id_for_nutrition)
+                                                            .map(|l| {
+                                                                let label_row = |row: &NutritionLabelRow, bold_dv: bool| {
+                                                                    let name_class = if row.indent == 0 && bold_dv { "nf-bold" } else { "" };
+                                                                    let dv_class = if bold_dv { "nf-bold" } else { "" };
+                                                                    view! {
+                                                                        <div class={format!("nf-row nf-indent-{}", row.indent)}>
+                                                                            <span>
+                                                                                <span class={name_class}>{row.name.clone()}</span>
+                                                                                {format!(" {}", row.amount)}
+                                                                            </span>
+                                                                            <span class={dv_class}>{row.daily_value.clone().unwrap_or_default()}</span>
+                                                                        </div>
+                                                                    }
+                                                                };
+                                                                let nutrient_rows = l.nutrients.iter().map(|row| label_row(row, true)).collect_view();
+                                                                let vitamin_rows = l.vitamins.iter().map(|row| label_row(row, false)).collect_view();
+                                                                let has_allergens = !l.allergens.is_empty();
+                                                                let allergens = l.allergens.join(", ");
+                                                                let warnings = l.warnings.clone();
+                                                                view! {
+                                                                    <div class="nutrition-label">
+                                                                        <div class="nf-title">"Nutrition Facts"</div>
+                                                                        <div class="nf-servings">{l.servings_per_container.clone()}</div>
+                                                                        <div class="nf-serving-size">
+                                                                            <span>"Serving size"</span>
+                                                                            <span>{l.serving_size.clone()}</span>
+                                                                        </div>
+                                                                        <div class="nf-bar-thick"></div>
+                                                                        <div class="nf-small nf-bold">"Amount per serving"</div>
+                                                                        <div class="nf-calories">
+                                                                            <span>"Calories"</span>
+                                                                            <span>{l.calories.clone()}</span>
+                                                                        </div>
+                                                                        <div class="nf-bar-medium"></div>
+                                                                        <div class="nf-small nf-bold nf-dv-heading">"% Daily Value*"</div>
+                                                                        {nutrient_rows}
+                                                                        <div class="nf-bar-thick"></div>
+                                                                        {vitamin_rows}
+                                                                        <div class="nf-bar-medium"></div>
+                                                                        <div class="nf-footnote">
+                                                                            "* The % Daily Value (DV) tells you how much a nutrient in a serving of food contributes to a daily diet. 2,000 calories a day is used for general nutrition advice."
+                                                                        </div>
+                                                                    </div>
+                                                                    <div class="nf-statement">
+                                                                        <strong>"Ingredients: "</strong>
+                                                                        {l.ingredients.clone()}
+                                                                    </div>
+                                                                    <Show when=move || has_allergens>
+                                                                        <div class="nf-statement">
+                                                                            <strong>"Contains: "</strong>
+                                                                            {allergens.clone()}
+                                                                        </div>
+                                                                    </Show>
+                                                                    {warnings
+                                                                        .into_iter()
+                                                                        .map(|w| view! { <div class="recipe-meta">{format!("\u{26A0} {}", w)}</div> })
+                                                                        .collect_view()}
+                                                                    <div class="row" style="margin-top: 10px; gap: 8px;">
+                                                                        <button
+                                                                            class="button tiny"
+                                                                            on:click=move |_| export_nutrition_label(recipe_id_for_nutrition, "pdf", pdf_name.clone())
+                                                                        >"Export label PDF"</button>
+                                                                        <button
+                                                                            class="button tiny secondary"
+                                                                            on:click=move |_| export_nutrition_label(recipe_id_for_nutrition, "png", png_name.clone())
+                                                                        >"Export label PNG"</button>
+                                                                    </div>
+                                                                }
+                                                            })
+                                                    }}
+                                                </div>
+                                                <div class="detail-block" style="margin-top: 14px;">
+                                                    <div class="row" style="align-items: center; gap: 8px;">
+                                                        <strong>"Edit Recipe"</strong>
+                                                        <button class="button tiny secondary" on:click={
+                                                            let init_n = edit_init_name.clone();
+                                                            let init_i = edit_init_instructions.clone();
+                                                            let init_yq = edit_init_yield_qty.clone();
+                                                            let init_yu = edit_init_yield_unit.clone();
+                                                            let init_sv = edit_init_servings.clone();
+                                                            let init_pr = edit_init_pricing.clone();
+                                                            move |_| {
+                                                                if !edit_recipe_editing.get() {
+                                                                    set_edit_recipe_name.set(init_n.clone());
+                                                                    set_edit_recipe_instructions.set(init_i.clone());
+                                                                    set_edit_recipe_yield_qty.set(init_yq.clone());
+                                                                    set_edit_recipe_yield_unit.set(init_yu.clone());
+                                                                    set_edit_recipe_servings.set(init_sv.clone());
+                                                                    set_edit_recipe_portion_size.set(init_pr.0.clone());
+                                                                    set_edit_recipe_portion_unit.set(init_pr.1.clone());
+                                                                    set_edit_recipe_target_pct.set(init_pr.2.clone());
+                                                                    set_edit_recipe_menu_price.set(init_pr.3.clone());
+                                                                }
+                                                                set_edit_recipe_editing.set(!edit_recipe_editing.get());
+                                                                set_edit_recipe_msg.set(String::new());
                                                             }
-                                                        };
-                                                        let inv_no = pdf_invoice_no.get();
-                                                        let inv_date = pdf_invoice_date.get();
-                                                        let pdf_lines = pdf_preview.get().map(|p| p.lines.clone()).unwrap_or_default();
-                                                        set_pdf_import_status.set("Importing...".to_string());
-                                                        spawn_local(async move {
-                                                            let line_vals: Vec<serde_json::Value> = pdf_lines.iter().map(|l| {
-                                                                serde_json::json!({
-                                                                    "item_name": l.item_name,
-                                                                    "qty": l.qty,
-                                                                    "unit_price": l.unit_price,
-                                                                    "ext_cost": l.ext_cost,
-                                                                })
-                                                            }).collect();
-                                                            let args = to_value(&ImportPdfInvoiceArgs {
-                                                                vendor_id: vid,
-                                                                invoice_no: inv_no,
-                                                                invoice_date: inv_date,
-                                                                lines: line_vals,
-                                                            }).unwrap();
-                                                            match invoke_cmd::<PatchResponse>("import_pdf_invoice", args).await {
-                                                                Ok(resp) => {
-                                                                    set_pdf_import_status.set(resp.message);
-                                                                    set_pdf_preview.set(None);
+                                                        }>{move || if edit_recipe_editing.get() { "Cancel" } else { "Edit" }}</button>
+                                                    </div>
+                                                    <Show when=move || edit_recipe_editing.get()>
+                                                        <div style="margin-top: 10px;">
+                                                            <div class="input">
+                                                                <label>"Name"</label>
+                                                                <input
+                                                                    type="text"
+                                                                    prop:value=move || edit_recipe_name.get()
+                                                                    on:input=move |ev| {
+                                                                        set_edit_recipe_name.set(event_target_value(&ev));
+                                                                    }
+                                                                />
+                                                            </div>
+                                                            <div class="input" style="margin-top: 8px;">
+                                                                <label>"Instructions"</label>
+                                                                <textarea
+                                                                    rows="8"
+                                                                    style="width: 100%; font-family: inherit; font-size: 14px; padding: 8px; border-radius: 8px; border: 1px solid var(--border);"
+                                                                    prop:value=move || edit_recipe_instructions.get()
+                                                                    on:input=move |ev| {
+                                                                        set_edit_recipe_instructions.set(event_target_value(&ev));
+                                                                    }
+                                                                />
+                                                            </div>
+                                                            <div class="row" style="margin-top: 8px;">
+                                                                <div class="input">
+                                                                    <label>"Yield Qty"</label>
+                                                                    <input
+                                                                        type="number"
+                                                                        step="any"
+                                                                        prop:value=move || edit_recipe_yield_qty.get()
+                                                                        on:input=move |ev| {
+                                                                            set_edit_recipe_yield_qty.set(event_target_value(&ev));
+                                                                        }
+                                                                    />
+                                                                </div>
+                                                                <div class="input">
+                                                                    <label>"Yield Unit"</label>
+                                                                    <select
+                                                                        prop:value=move || edit_recipe_yield_unit.get()
+                                                                        on:change=move |ev| {
+                                                                            set_edit_recipe_yield_unit.set(event_target_value(&ev));
+                                                                        }
+                                                                    >
+                                                                        <option value="">"Select unit"</option>
+                                                                        <For
+                                                                            each=move || unit_options.get()
+                                                                            key=|unit| unit.unit_id
+                                                                            children=move |unit| view! {
+                                                                                <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                                                            }
+                                                                        />
+                                                                    </select>
+                                                                </div>
+                                                                <div class="input">
+                                                                    <label>"Servings"</label>
+                                                                    <input
+                                                                        type="number"
+                                                                        step="any"
+                                                                        prop:value=move || edit_recipe_servings.get()
+                                                                        on:input=move |ev| {
+                                                                            set_edit_recipe_servings.set(event_target_value(&ev));
+                                                                        }
+                                                                    />
+                                                                </div>
+                                                            </div>
+                                                            <div class="row" style="margin-top: 8px;">
+                                                                <div class="input">
+                                                                    <label>"Portion Size"</label>
+                                                                    <input
+                                                                        type="number"
+                                                                        step="any"
+                                                                        prop:value=move || edit_recipe_portion_size.get()
+                                                                        on:input=move |ev| set_edit_recipe_portion_size.set(event_target_value(&ev))
+                                                                    />
+                                                                </div>
+                                                                <div class="input">
+                                                                    <label>"Portion Unit"</label>
+                                                                    <select
+                                                                        prop:value=move || edit_recipe_portion_unit.get()
+                                                                        on:change=move |ev| set_edit_recipe_portion_unit.set(event_target_value(&ev))
+                                                                    >
+                                                                        <option value="">"Select unit"</option>
+                                                                        <For
+                                                                            each=move || unit_options.get()
+                                                                            key=|unit| unit.unit_id
+                                                                            children=move |unit| view! {
+                                                                                <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                                                            }
+                                                                        />
+                                                                    </select>
+                                                                </div>
+                                                                <div class="input">
+                                                                    <label>"Target Food Cost %"</label>
+                                                                    <input
+                                                                        type="number"
+                                                                        step="any"
+                                                                        placeholder="Use default"
+                                                                        prop:value=move || edit_recipe_target_pct.get()
+                                                                        on:input=move |ev| set_edit_recipe_target_pct.set(event_target_value(&ev))
+                                                                    />
+                                                                </div>
+                                                                <div class="input">
+                                                                    <label>"Menu Price"</label>
+                                                                    <input
+                                                                        type="number"
+                                                                        step="0.01"
+                                                                        prop:value=move || edit_recipe_menu_price.get()
+                                                                        on:input=move |ev| set_edit_recipe_menu_price.set(event_target_value(&ev))
+                                                                    />
+                                                                </div>
+                                                            </div>
+                                                            <div class="row" style="margin-top: 10px; gap: 8px;">
+                                                                <button class="button tiny" on:click=move |_| {
+                                                                    save_recipe_edit(recipe_id_for_edit);
+                                                                }>"Save Changes"</button>
+                                                            </div>
+                                                            <Show when=move || !edit_recipe_msg.get().is_empty()>
+                                                                <div class="status" style="margin-top: 6px;">{move || edit_recipe_msg.get()}</div>
+                                                            </Show>
+                                                        </div>
+                                                    </Show>
+                                                </div>
+                                                <div class="recipe-instructions">
+                                                    <strong>"Instructions"</strong>
+                                                    <Show
+                                                        when=move || has_instructions
+                                                        fallback=move || view! { <p>"No instructions imported."</p> }
+                                                    >
+                                                        <div class="instruction-lines">
+                                                            <For
+                                                                each={
+                                                                    let instruction_lines = instruction_lines.clone();
+                                                                    move || instruction_lines.as_ref().clone()
                                                                 }
-                                                                Err(err) => set_pdf_import_status.set(format!("Import failed: {err}")),
+                                                                key=|(idx, _)| *idx
+                                                                children=move |(_, line)| view! {
+                                                                    <p class="instruction-line">{line}</p>
+                                                                }
+                                                            />
+                                                        </div>
+                                                    </Show>
+                                                </div>
+                                                <div class="recipe-ingredients">
+                                                    <div
+                                                        class="recipe-ingredients-header"
+                                                        class:recipe-ingredients-8=move || edit_recipe_editing.get()
+                                                    >
+                                                        <span>"Item"</span>
+                                                        <span>"Qty"</span>
+                                                        <span>"Unit"</span>
+                                                        <span>"Purch Unit"</span>
+                                                        <span>"Price"</span>
+                                                        <span>"EP Cost"</span>
+                                                        <span>"Status"</span>
+                                                        <Show when=move || edit_recipe_editing.get()>
+                                                            <span></span>
+                                                        </Show>
+                                                    </div>
+                                                    <For
+                                                        each=move || detail.ingredients.clone()
+                                                        key=|item| item.recp_item_id
+                                                        children=move |item| {
+                                                            let recp_item_id = item.recp_item_id;
+                                                            let rid = recipe_id_for_edit;
+                                                            let sub_recipe_id = item.sub_recipe_id;
+                                                            view! {
+                                                            <div
+                                                                class="recipe-ingredients-row"
+                                                                class:recipe-ingredients-8=move || edit_recipe_editing.get()
+                                                            >
+                                                                {match sub_recipe_id {
+                                                                    Some(sub_id) => view! {
+                                                                        <span>
+                                                                            <a
+                                                                                href="#"
+                                                                                title="Open component recipe costing"
+                                                                                on:click=move |ev| {
+                                                                                    ev.prevent_default();
+                                                                                    trigger_recipe_detail_fetch(
+                                                                                        sub_id,
+                                                                                        set_recipe_detail_loading,
+                                                                                        set_recipe_detail_status,
+                                                                                        set_selected_recipe,
+                                                                                    );
+                                                                                }
+                                                                            >{format!("\u{21B3} {}", item.item_name)}</a>
+                                                                        </span>
+                                                                    }.into_any(),
+                                                                    None => {
+                                                                        let label = if item.prep_name.is_empty() {
+                                                                            item.item_name
+                                                                        } else {
+                                                                            format!("{}, {}", item.item_name, item.prep_name)
+                                                                        };
+                                                                        view! { <span>{label}</span> }.into_any()
+                                                                    }
+                                                                }}
+                                                                <span>{item.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                <span>{item.unit_name}</span>
+                                                                <span>{item.purch_unit_name}</span>
+                                                                <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                <span title={item.ap_cost.map(|c| format!("AP cost {}", format_money(c))).unwrap_or_default()}>
+                                                                    {item.extended_cost.map(format_money).unwrap_or_else(|| "-".to_string())}
+                                                                    {item.yield_pct.map(|p| format!(" @ {:.0}%", p)).unwrap_or_default()}
+                                                                </span>
+                                                                <span title=conversion_path_text(&item.conversion_path)>
+                                                                    {item.cost_status}
+                                                                    <Show when={
+                                                                        let shown = !item.conversion_summary.is_empty();
+                                                                        move || shown
+                                                                    }>
+                                                                        <div class="recipe-meta">{item.conversion_summary.clone()}</div>
+                                                                    </Show>
+                                                                </span>
+                                                                <Show when=move || edit_recipe_editing.get()>
+                                                                    <button
+                                                                        class="button tiny danger"
+                                                                        on:click=move |_| {
+                                                                            delete_recipe_ingredient(rid, recp_item_id);
+                                                                        }
+                                                                    >"Remove"</button>
+                                                                </Show>
+                                                            </div>
                                                             }
-                                                        });
-                                                    }
-                                                >
-                                                    {move || format!("Import {} Lines", line_count)}
-                                                </button>
-                                                <button
-                                                    class="button secondary"
-                                                    on:click=move |_| {
-                                                        set_pdf_preview.set(None);
-                                                        set_pdf_import_status.set(String::new());
-                                                    }
-                                                >
-                                                    "Cancel"
-                                                </button>
+                                                        }
+                                                    />
+                                                </div>
                                             </div>
-                                        </div>
-                                    }
-                                })
-                            })}
-                        </Show>
+                                        }
+                                    })
+                                })}
+                            </Show>
+                            <Show when=move || recipe_detail_loading.get()>
+                                <div class="status">"Loading recipe details..."</div>
+                            </Show>
+                        </div>
                     </div>
                 </Show>
 
-                <Show when=move || active_panel.get() == "inventory">
+                <Show when=move || active_panel.get() == "vendors">
                     <div class="panel">
                         <div class="row">
                             <div class="input">
-                                <label>"Search"</label>
+                                <label>"Search vendors"</label>
                                 <input
                                     type="text"
-                                    prop:value=inventory_query
+                                    prop:value=vendor_query
                                     on:input=move |ev| {
                                         let value = event_target_value(&ev);
-                                        set_inventory_query.set(value);
+                                        set_vendor_query.set(value);
                                     }
                                 />
                             </div>
-                            <div class="input">
-                                <label>"Organize by"</label>
-                                <select
-                                    prop:value=inventory_order_by
-                                    on:change=move |ev| {
-                                        set_inventory_order_by.set(event_target_value(&ev));
-                                    }
-                                >
-                                    <option value="">"Alphabetically"</option>
-                                    <option value="vendor">"By Vendor"</option>
-                                </select>
-                            </div>
                             <div class="input" style="align-self: end;">
                                 <div class="row">
                                     <button class="button" on:click=move |_| {
-                                        set_inventory_page.set(0);
-                                        trigger_inventory_fetch(
-                                            inventory_query.get(),
-                                            inventory_food_category.get(),
-                                            inventory_order_by.get(),
+                                        set_vendor_page.set(0);
+                                        trigger_vendor_fetch(
+                                            vendor_query.get(),
                                             0,
-                                            inventory_limit,
-                                            set_inventory_loading,
-                                            set_inventory_status,
-                                            set_inventory_items,
-                                            set_inventory_total,
-                                            set_inventory_filtered,
-                                            set_inventory_loaded,
+                                            vendor_limit,
+                                            set_vendor_loading,
+                                            set_vendor_status,
+                                            set_vendor_list,
+                                            set_vendor_total,
+                                            set_vendor_filtered,
+                                            set_vendor_loaded,
                                         );
-                                    } disabled=move || inventory_loading.get()>
-                                        {move || if inventory_loading.get() { "Searching..." } else { "Search" }}
+                                    } disabled=move || vendor_loading.get()>
+                                        {move || if vendor_loading.get() { "Searching..." } else { "Search" }}
                                     </button>
                                     <button class="button secondary" on:click=move |_| {
-                                        set_inventory_query.set(String::new());
-                                        set_inventory_order_by.set(String::new());
-                                        set_inventory_page.set(0);
-                                        set_inventory_food_category.set(String::new());
-                                        trigger_inventory_fetch(
-                                            String::new(),
-                                            String::new(),
+                                        set_vendor_query.set(String::new());
+                                        set_vendor_page.set(0);
+                                        trigger_vendor_fetch(
                                             String::new(),
                                             0,
-                                            inventory_limit,
-                                            set_inventory_loading,
-                                            set_inventory_status,
-                                            set_inventory_items,
-                                            set_inventory_total,
-                                            set_inventory_filtered,
-                                            set_inventory_loaded,
+                                            vendor_limit,
+                                            set_vendor_loading,
+                                            set_vendor_status,
+                                            set_vendor_list,
+                                            set_vendor_total,
+                                            set_vendor_filtered,
+                                            set_vendor_loaded,
                                         );
                                     }>
                                         "Reset"
@@ -3299,562 +9066,211 @@ pub fn App() -> impl IntoView {
                                 </div>
                             </div>
                         </div>
-                        <div class="status">{move || inventory_status.get()}</div>
+                        <div class="status">{move || vendor_status.get()}</div>
                     </div>
 
-                    <div class="panel inventory-grid">
-                        <div class="inventory-list">
-                            <div class="row">
-                                <div>
-                                    <strong>"Inventory Overview"</strong>
-                                    <div class="status">
-                                        {move || format!(
-                                            "Total items: {} | Filtered: {} | Page {}",
-                                            inventory_total.get(),
-                                            inventory_filtered.get(),
-                                            inventory_page.get() + 1
-                                        )}
-                                    </div>
-                                </div>
-                                <div style="margin-left: auto; display: flex; gap: 8px; align-items: end;">
-                                    <button class="button tiny" on:click=move |_| export_inventory_pdf()>"Export PDF"</button>
-                                    <button class="button tiny secondary" on:click=move |_| export_inventory_docx()>"Export Word"</button>
-                                </div>
+                    <div class="panel recipe-grid">
+                        <div class="recipe-list">
+                            <div class="recipe-list-header">
+                                <span>"Vendors"</span>
+                                <span>{move || format!(
+                                    "{} / {}",
+                                    vendor_filtered.get(),
+                                    vendor_total.get()
+                                )}</span>
                             </div>
-                            <Show when=move || !export_status.get().is_empty()>
-                                <div class="status">{move || export_status.get()}</div>
-                            </Show>
-                            <div class="inventory-table">
-                                <div class="inventory-header">
-                                    <span>"ID"</span>
-                                    <span>"Name"</span>
-                                    <span>"Status"</span>
-                                </div>
-                                {move || {
-                                    let items = inventory_items.get();
-                                    let order = inventory_order_by.get();
-                                    let mut last_group = String::new();
-                                    items.iter().map(|item| {
-                                        let group_label = match order.as_str() {
-                                            "vendor" => {
-                                                let vn = &item.vendor_name;
-                                                if vn.is_empty() || vn == "<No Vendor>" { "No Vendor".to_string() } else { vn.clone() }
-                                            },
-                                            _ => String::new(),
-                                        };
-                                        let show_header = !group_label.is_empty() && group_label != last_group;
-                                        if show_header { last_group = group_label.clone(); }
-                                        let item_id = item.item_id;
-                                        let item_name = item.name.clone();
-                                        let item_status = item.status;
-                                        let header_view = if show_header {
-                                            Some(view! { <div class="inventory-group-header">{group_label}</div> })
-                                        } else {
-                                            None
-                                        };
+                            <div class="recipe-list-body">
+                                <For
+                                    each=move || vendor_list.get()
+                                    key=|item| item.vendor_id
+                                    children=move |item| {
+                                        let vendor_id = item.vendor_id;
                                         view! {
-                                            <>
-                                                {header_view}
-                                                <div
-                                                    class="inventory-row"
-                                                    class:selected=move || inventory_selected.get().map(|d| d.item_id == item_id).unwrap_or(false)
-                                                    on:click=move |_| {
-                                                        trigger_inventory_detail_fetch(
-                                                            item_id,
-                                                            set_inventory_detail_loading,
-                                                            set_inventory_detail_status,
-                                                            set_inventory_selected,
-                                                        );
-                                                    }
-                                                >
-                                                    <span>{item_id}</span>
-                                                    <span>{item_name}</span>
-                                                    <span>{item_status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                </div>
-                                            </>
+                                            <div
+                                                class="recipe-row"
+                                                class:selected=move || selected_vendor.get().map(|v| v.vendor_id == vendor_id).unwrap_or(false)
+                                                on:click=move |_| {
+                                                    set_vendor_merge_source_id.set(vendor_id.to_string());
+                                                    trigger_vendor_detail_fetch(
+                                                        vendor_id,
+                                                        set_vendor_detail_loading,
+                                                        set_vendor_detail_status,
+                                                        set_selected_vendor,
+                                                    );
+                                                }
+                                            >
+                                                <div class="recipe-name">{item.name}</div>
+                                                <div class="recipe-meta">{format!("{} priced items", item.price_items)}</div>
+                                            </div>
                                         }
-                                    }).collect::<Vec<_>>()
-                                }}
+                                    }
+                                />
                             </div>
-                            <div class="row" style="margin-top: 16px;">
+                            <div class="row" style="margin-top: 12px;">
                                 <button class="button secondary" on:click=move |_| {
-                                    let current = inventory_page.get();
-                                    if current > 0 {
-                                        set_inventory_page.set(current - 1);
-                                        trigger_inventory_fetch(
-                                            inventory_query.get(),
-                                            inventory_food_category.get(),
-                                            inventory_order_by.get(),
+                                    let current = vendor_page.get();
+                                    if current > 0 {
+                                        set_vendor_page.set(current - 1);
+                                        trigger_vendor_fetch(
+                                            vendor_query.get(),
                                             current - 1,
-                                            inventory_limit,
-                                            set_inventory_loading,
-                                            set_inventory_status,
-                                            set_inventory_items,
-                                            set_inventory_total,
-                                            set_inventory_filtered,
-                                            set_inventory_loaded,
+                                            vendor_limit,
+                                            set_vendor_loading,
+                                            set_vendor_status,
+                                            set_vendor_list,
+                                            set_vendor_total,
+                                            set_vendor_filtered,
+                                            set_vendor_loaded,
                                         );
                                     }
-                                } disabled=move || inventory_page.get() == 0 || inventory_loading.get()>
+                                } disabled=move || vendor_page.get() == 0 || vendor_loading.get()>
                                     "Previous"
                                 </button>
                                 <button class="button" on:click=move |_| {
-                                    let current = inventory_page.get();
-                                    let max = (inventory_filtered.get() as f64 / inventory_limit as f64).ceil() as usize;
+                                    let current = vendor_page.get();
+                                    let max = (vendor_filtered.get() as f64 / vendor_limit as f64).ceil() as usize;
                                     if current + 1 < max {
-                                        set_inventory_page.set(current + 1);
-                                        trigger_inventory_fetch(
-                                            inventory_query.get(),
-                                            inventory_food_category.get(),
-                                            inventory_order_by.get(),
-                                            current + 1,
-                                            inventory_limit,
-                                            set_inventory_loading,
-                                            set_inventory_status,
-                                            set_inventory_items,
-                                            set_inventory_total,
-                                            set_inventory_filtered,
-                                            set_inventory_loaded,
-                                        );
-                                    }
-                                } disabled=move || {
-                                    let max = (inventory_filtered.get() as f64 / inventory_limit as f64).ceil() as usize;
-                                    inventory_loading.get() || max == 0 || inventory_page.get() + 1 >= max
-                                }>
-                                    "Next"
-                                </button>
-                            </div>
-                        </div>
-                        <div class="inventory-detail">
-                            <div class="status">{move || inventory_detail_status.get()}</div>
-                            <Show
-                                when=move || inventory_selected.get().is_some()
-                                fallback=move || view! {
-                                    <div class="recipe-empty">
-                                        <strong>"Select an item"</strong>
-                                        <p>"Choose an inventory item to review purchase units, pricing, and usage."</p>
-                                    </div>
-                                }
-                            >
-                                {move || inventory_selected.with(|opt| {
-                                    opt.as_ref().map(|detail| {
-                                        let detail = detail.clone();
-                                        view! {
-                                            <div>
-                                                <div class="recipe-title">{detail.name.clone()}</div>
-                                                <div class="recipe-summary">
-                                                    <span>{format!("Item ID {}", detail.item_id)}</span>
-                                                    <span>{format!("Status {}", detail.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()))}</span>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Edit Item"</strong>
-                                                    <div class="row" style="margin-top: 10px;">
-                                                        <div class="input">
-                                                            <label>"Name"</label>
-                                                            <input
-                                                                type="text"
-                                                                prop:value=move || {
-                                                                    let v = edit_item_name.get();
-                                                                    if v.is_empty() {
-                                                                        inventory_selected.get()
-                                                                            .map(|d| d.name.clone())
-                                                                            .unwrap_or_default()
-                                                                    } else { v }
-                                                                }
-                                                                on:input=move |ev| {
-                                                                    set_edit_item_name.set(event_target_value(&ev));
-                                                                }
-                                                                on:focus=move |_| {
-                                                                    if edit_item_name.get().is_empty() {
-                                                                        if let Some(d) = inventory_selected.get() {
-                                                                            set_edit_item_name.set(d.name.clone());
-                                                                        }
-                                                                    }
-                                                                }
-                                                            />
-                                                        </div>
-                                                        <div class="input" style="flex: 0 0 100px;">
-                                                            <label>"Status"</label>
-                                                            <input
-                                                                type="number"
-                                                                prop:value=move || {
-                                                                    let v = edit_item_status.get();
-                                                                    if v.is_empty() {
-                                                                        inventory_selected.get()
-                                                                            .and_then(|d| d.status)
-                                                                            .map(|s| s.to_string())
-                                                                            .unwrap_or_default()
-                                                                    } else { v }
-                                                                }
-                                                                on:input=move |ev| {
-                                                                    set_edit_item_status.set(event_target_value(&ev));
-                                                                }
-                                                            />
-                                                        </div>
-                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                                            <button
-                                                                class="button tiny"
-                                                                on:click=move |_| {
-                                                                    save_item_edit(detail.item_id);
-                                                                    set_edit_item_name.set(String::new());
-                                                                    set_edit_item_status.set(String::new());
-                                                                }
-                                                            >
-                                                                "Save changes"
-                                                            </button>
-                                                        </div>
-                                                    </div>
-                                                    <div class="status">{move || edit_item_msg.get()}</div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Categories"</strong>
-                                                    <div class="row" style="margin-top: 10px;">
-                                                        <div class="input">
-                                                            <label>"Food Category"</label>
-                                                            <select
-                                                                prop:value=move || {
-                                                                    let v = edit_item_food_category.get();
-                                                                    if v.is_empty() {
-                                                                        inventory_selected.get()
-                                                                            .map(|d| d.food_category.clone())
-                                                                            .unwrap_or_default()
-                                                                    } else { v }
-                                                                }
-                                                                on:change=move |ev| {
-                                                                    set_edit_item_food_category.set(event_target_value(&ev));
-                                                                }
-                                                                on:focus=move |_| {
-                                                                    if edit_item_food_category.get().is_empty() {
-                                                                        if let Some(d) = inventory_selected.get() {
-                                                                            set_edit_item_food_category.set(d.food_category.clone());
-                                                                        }
-                                                                    }
-                                                                }
-                                                            >
-                                                                <option value="">"None"</option>
-                                                                <option value="Produce">"Produce"</option>
-                                                                <option value="Meat">"Meat"</option>
-                                                                <option value="Poultry">"Poultry"</option>
-                                                                <option value="Seafood">"Seafood"</option>
-                                                                <option value="Vegetables">"Vegetables"</option>
-                                                                <option value="Dairy">"Dairy"</option>
-                                                                <option value="Bakery">"Bakery"</option>
-                                                                <option value="Beverages">"Beverages"</option>
-                                                                <option value="Condiments">"Condiments"</option>
-                                                                <option value="Spices/Seasonings">"Spices/Seasonings"</option>
-                                                                <option value="Grains/Pasta">"Grains/Pasta"</option>
-                                                                <option value="Canned Goods">"Canned Goods"</option>
-                                                                <option value="Other">"Other"</option>
-                                                            </select>
-                                                        </div>
-                                                        <div class="input">
-                                                            <label>"Storage Type"</label>
-                                                            <select
-                                                                prop:value=move || {
-                                                                    let v = edit_item_storage_type.get();
-                                                                    if v.is_empty() {
-                                                                        inventory_selected.get()
-                                                                            .map(|d| d.storage_type.clone())
-                                                                            .unwrap_or_default()
-                                                                    } else { v }
-                                                                }
-                                                                on:change=move |ev| {
-                                                                    set_edit_item_storage_type.set(event_target_value(&ev));
-                                                                }
-                                                                on:focus=move |_| {
-                                                                    if edit_item_storage_type.get().is_empty() {
-                                                                        if let Some(d) = inventory_selected.get() {
-                                                                            set_edit_item_storage_type.set(d.storage_type.clone());
-                                                                        }
-                                                                    }
-                                                                }
-                                                            >
-                                                                <option value="">"None"</option>
-                                                                <option value="Walk-in/Cooler">"Walk-in/Cooler"</option>
-                                                                <option value="Frozen">"Frozen"</option>
-                                                                <option value="Dry Goods">"Dry Goods"</option>
-                                                            </select>
-                                                        </div>
-                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                                            <button
-                                                                class="button tiny"
-                                                                on:click=move |_| {
-                                                                    let iid = detail.item_id;
-                                                                    let fc = edit_item_food_category.get();
-                                                                    let st = edit_item_storage_type.get();
-                                                                    let fc_val = if fc.is_empty() {
-                                                                        inventory_selected.get().map(|d| d.food_category.clone()).unwrap_or_default()
-                                                                    } else { fc };
-                                                                    let st_val = if st.is_empty() {
-                                                                        inventory_selected.get().map(|d| d.storage_type.clone()).unwrap_or_default()
-                                                                    } else { st };
-                                                                    spawn_local(async move {
-                                                                        let args = to_value(&UpdateItemCategoryArgs {
-                                                                            item_id: iid,
-                                                                            food_category: fc_val,
-                                                                            storage_type: st_val,
-                                                                        }).unwrap();
-                                                                        match invoke_cmd::<PatchResponse>("update_item_category", args).await {
-                                                                            Ok(resp) => {
-                                                                                set_edit_item_category_msg.set(resp.message);
-                                                                                // refresh detail
-                                                                                trigger_inventory_detail_fetch(
-                                                                                    iid,
-                                                                                    set_inventory_detail_loading,
-                                                                                    set_inventory_detail_status,
-                                                                                    set_inventory_selected,
-                                                                                );
-                                                                                // refresh list
-                                                                                trigger_inventory_fetch(
-                                                                                    inventory_query.get(),
-                                                                                    inventory_food_category.get(),
-                                                                                    inventory_order_by.get(),
-                                                                                    inventory_page.get(),
-                                                                                    inventory_limit,
-                                                                                    set_inventory_loading,
-                                                                                    set_inventory_status,
-                                                                                    set_inventory_items,
-                                                                                    set_inventory_total,
-                                                                                    set_inventory_filtered,
-                                                                                    set_inventory_loaded,
-                                                                                );
-                                                                                set_edit_item_food_category.set(String::new());
-                                                                                set_edit_item_storage_type.set(String::new());
-                                                                            }
-                                                                            Err(err) => set_edit_item_category_msg.set(format!("Error: {err}")),
-                                                                        }
-                                                                    });
-                                                                }
-                                                            >
-                                                                "Save categories"
-                                                            </button>
-                                                        </div>
-                                                    </div>
-                                                    <div class="status">{move || edit_item_category_msg.get()}</div>
+                                        set_vendor_page.set(current + 1);
+                                        trigger_vendor_fetch(
+                                            vendor_query.get(),
+                                            current + 1,
+                                            vendor_limit,
+                                            set_vendor_loading,
+                                            set_vendor_status,
+                                            set_vendor_list,
+                                            set_vendor_total,
+                                            set_vendor_filtered,
+                                            set_vendor_loaded,
+                                        );
+                                    }
+                                } disabled=move || {
+                                    let max = (vendor_filtered.get() as f64 / vendor_limit as f64).ceil() as usize;
+                                    vendor_loading.get() || max == 0 || vendor_page.get() + 1 >= max
+                                }>
+                                    "Next"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="recipe-detail">
+                            <div class="status">{move || vendor_detail_status.get()}</div>
+                            <Show
+                                when=move || selected_vendor.get().is_some()
+                                fallback=move || view! {
+                                    <div class="recipe-empty">
+                                        <strong>"Select a vendor"</strong>
+                                        <p>"Choose a vendor to see price lists and purchasing history."</p>
+                                    </div>
+                                }
+                            >
+                                {move || selected_vendor.with(|opt| {
+                                    opt.as_ref().map(|detail| {
+                                        let detail = detail.clone();
+                                        view! {
+                                            <div>
+                                                <div class="recipe-title">{detail.name}</div>
+                                                <div class="recipe-summary">
+                                                    <span>{format!("Vendor ID {}", detail.vendor_id)}</span>
+                                                    <span>{format!("{} invoices", detail.invoice_count)}</span>
+                                                    <span>{format!("{} trans lines", detail.trans_count)}</span>
                                                 </div>
+                                                {
+                                                    let vendor_id = detail.vendor_id;
+                                                    let (inclusive, set_inclusive) = signal(detail.prices_include_tax);
+                                                    let (rate, set_rate) = signal(detail.tax_rate.map(|r| r.to_string()).unwrap_or_default());
+                                                    let save_tax = move |_| {
+                                                        let tax_rate = rate.get_untracked().trim().parse::<f64>().ok();
+                                                        let prices_include_tax = inclusive.get_untracked();
+                                                        spawn_local(async move {
+                                                            let args = to_value(&SetVendorTaxArgs { vendor_id, prices_include_tax, tax_rate }).unwrap();
+                                                            match invoke_cmd::<PatchResponse>("set_vendor_tax", args).await {
+                                                                Ok(resp) => set_vendor_detail_status.set(resp.message),
+                                                                Err(err) => set_vendor_detail_status.set(format!("Error: {err}")),
+                                                            }
+                                                        });
+                                                    };
+                                                    view! {
+                                                        <div class="detail-block">
+                                                            <strong>"Tax Terms"</strong>
+                                                            <div class="row" style="margin-top: 10px; align-items: center;">
+                                                                <label style="display: inline-flex; gap: 8px; align-items: center;">
+                                                                    <input
+                                                                        type="checkbox"
+                                                                        prop:checked=move || inclusive.get()
+                                                                        on:change=move |ev| set_inclusive.set(event_target_checked(&ev))
+                                                                    />
+                                                                    <span>"Invoice prices include tax"</span>
+                                                                </label>
+                                                                <div class="input">
+                                                                    <label>"Tax Rate %"</label>
+                                                                    <input type="number" step="any" min="0" prop:value=move || rate.get() on:input=move |ev| set_rate.set(event_target_value(&ev)) />
+                                                                </div>
+                                                                <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                                    <button class="button secondary" on:click=save_tax>"Save"</button>
+                                                                </div>
+                                                            </div>
+                                                        </div>
+                                                    }
+                                                }
                                                 <div class="detail-block">
-                                                    <strong>"Assign Purchase Unit"</strong>
+                                                    <strong>"Merge Vendor IDs"</strong>
+                                                    <div class="status">
+                                                        "Move pricing, purchasing, and conversion history from one vendor ID into another."
+                                                    </div>
                                                     <div class="row" style="margin-top: 10px;">
                                                         <div class="input">
-                                                            <label>"Unit"</label>
-                                                            <select
-                                                                prop:value=purch_unit_selected_id
-                                                                on:change=move |ev| {
-                                                                    set_purch_unit_selected_id.set(event_target_value(&ev));
+                                                            <label>"Source vendor ID"</label>
+                                                            <input
+                                                                type="text"
+                                                                prop:value=vendor_merge_source_id
+                                                                on:input=move |ev| {
+                                                                    set_vendor_merge_source_id.set(event_target_value(&ev));
                                                                 }
-                                                            >
-                                                                <option value="">"Select unit"</option>
-                                                                <For
-                                                                    each=move || unit_options.get()
-                                                                    key=|unit| unit.unit_id
-                                                                    children=move |unit| view! {
-                                                                        <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
-                                                                    }
-                                                                />
-                                                            </select>
-                                                        </div>
-                                                        <div class="input" style="flex: 0 0 150px;">
-                                                            <label>"Default"</label>
-                                                            <label style="display: inline-flex; gap: 8px; align-items: center;">
-                                                                <input
-                                                                    type="checkbox"
-                                                                    prop:checked=purch_unit_set_default
-                                                                    on:change=move |ev| {
-                                                                        set_purch_unit_set_default.set(event_target_checked(&ev));
-                                                                    }
-                                                                />
-                                                                <span>"Set default"</span>
-                                                            </label>
-                                                        </div>
-                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                                            <button
-                                                                class="button tiny"
-                                                                on:click=move |_| assign_purch_unit(detail.item_id)
-                                                            >
-                                                                "Save unit"
-                                                            </button>
-                                                        </div>
-                                                    </div>
-                                                    <div class="status">{move || purch_unit_status.get()}</div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Purchase Units"</strong>
-                                                    <div class="data-table">
-                                                        <div class="data-header data-cols-3">
-                                                            <span>"Unit"</span>
-                                                            <span>"Default"</span>
-                                                            <span>"Status"</span>
-                                                        </div>
-                                                        <For
-                                                            each=move || detail.purch_units.clone()
-                                                            key=|unit| unit.unit_id.unwrap_or(0)
-                                                            children=move |unit| view! {
-                                                                <div class="data-row data-cols-3">
-                                                                    <span>{unit.unit_name}</span>
-                                                                    <span>{unit.is_default.map(|v| if v == 1 { "Yes" } else { "No" }.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{unit.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                                </div>
-                                                            }
-                                                        />
-                                                    </div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Vendor Prices"</strong>
-                                                    <div class="data-table">
-                                                        <div class="data-header data-cols-6">
-                                                            <span>"Vendor"</span>
-                                                            <span>"Current Price"</span>
-                                                            <span>"Previous Price"</span>
-                                                            <span>"Diff %"</span>
-                                                            <span>"Pack"</span>
-                                                            <span>"Status"</span>
+                                                            />
                                                         </div>
-                                                        <For
-                                                            each=move || detail.prices.clone()
-                                                            key=|price| price.vendor_id.unwrap_or(0)
-                                                            children=move |price| {
-                                                                let diff_class = match price.diff_pct {
-                                                                    Some(d) if d > 0.0 => "diff-up",
-                                                                    Some(d) if d < 0.0 => "diff-down",
-                                                                    Some(_) => "diff-zero",
-                                                                    None => "",
-                                                                };
-                                                                let diff_text = match price.diff_pct {
-                                                                    Some(d) => format!("{:+.1}%", d),
-                                                                    None => "-".to_string(),
-                                                                };
-                                                                view! {
-                                                                    <div class="data-row data-cols-6">
-                                                                        <span>{price.vendor_name}</span>
-                                                                        <span>{price.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                        <span>{price.prev_price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                        <span class={diff_class}>{diff_text}</span>
-                                                                        <span>{price.pack}</span>
-                                                                        <span>{price.status.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    </div>
-                                                                }
-                                                            }
-                                                        />
-                                                    </div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Manual Price Override"</strong>
-                                                    <div class="row" style="margin-top: 10px;">
                                                         <div class="input">
-                                                            <label>"Vendor"</label>
+                                                            <label>"Target vendor"</label>
                                                             <select
-                                                                prop:value=manual_price_vendor_id
+                                                                prop:value=vendor_merge_target_id
                                                                 on:change=move |ev| {
-                                                                    set_manual_price_vendor_id.set(event_target_value(&ev));
+                                                                    set_vendor_merge_target_id.set(event_target_value(&ev));
                                                                 }
                                                             >
-                                                                <option value="">"Select vendor"</option>
+                                                                <option value="">"Select target vendor"</option>
                                                                 <For
-                                                                    each=move || editor_vendor_options.get()
-                                                                    key=|vendor| vendor.vendor_id
-                                                                    children=move |vendor| view! {
-                                                                        <option value={vendor.vendor_id.to_string()}>{format!("{} - {}", vendor.vendor_id, vendor.name)}</option>
+                                                                    each=move || vendor_merge_options.get()
+                                                                    key=|v| v.vendor_id
+                                                                    children=move |v| view! {
+                                                                        <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
                                                                     }
                                                                 />
                                                             </select>
                                                         </div>
-                                                        <div class="input">
-                                                            <label>"Price"</label>
-                                                            <input
-                                                                type="number"
-                                                                step="0.0001"
-                                                                min="0"
-                                                                prop:value=manual_price_value
-                                                                on:input=move |ev| {
-                                                                    set_manual_price_value.set(event_target_value(&ev));
-                                                                }
-                                                            />
-                                                        </div>
-                                                        <div class="input">
-                                                            <label>"Pack"</label>
-                                                            <input
-                                                                type="text"
-                                                                prop:value=manual_price_pack
-                                                                on:input=move |ev| {
-                                                                    set_manual_price_pack.set(event_target_value(&ev));
-                                                                }
-                                                            />
-                                                        </div>
                                                         <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                                            <button
-                                                                class="button tiny"
-                                                                on:click=move |_| save_manual_price(detail.item_id)
-                                                            >
-                                                                "Save price"
-                                                            </button>
-                                                        </div>
-                                                    </div>
-                                                    <div class="status">{move || manual_price_status.get()}</div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Conversions"</strong>
-                                                    <div class="data-table">
-                                                        <div class="data-header data-cols-5">
-                                                            <span>"Vendor"</span>
-                                                            <span>"Unit 1"</span>
-                                                            <span>"Qty 1"</span>
-                                                            <span>"Unit 2"</span>
-                                                            <span>"Qty 2"</span>
-                                                        </div>
-                                                        <For
-                                                            each=move || detail.conversions.clone()
-                                                            key=|conv| (conv.unit_id1, conv.unit_id2, conv.vendor_id)
-                                                            children=move |conv| view! {
-                                                                <div class="data-row data-cols-5">
-                                                                    <span>{conv.vendor_id}</span>
-                                                                    <span>{conv.unit_id1}</span>
-                                                                    <span>{format!("{:.3}", conv.qty1)}</span>
-                                                                    <span>{conv.unit_id2}</span>
-                                                                    <span>{format!("{:.3}", conv.qty2)}</span>
-                                                                </div>
-                                                            }
-                                                        />
-                                                    </div>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Recipe Usage"</strong>
-                                                    <div class="data-table">
-                                                        <div class="data-header data-cols-3">
-                                                            <span>"Recipe"</span>
-                                                            <span>"Qty"</span>
-                                                            <span>"Unit"</span>
-                                                        </div>
-                                                        <For
-                                                            each=move || detail.usage.clone()
-                                                            key=|usage| usage.recipe_id
-                                                            children=move |usage| view! {
-                                                                <div class="data-row data-cols-3">
-                                                                    <span>{usage.recipe_name}</span>
-                                                                    <span>{usage.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{usage.unit_name}</span>
-                                                                </div>
-                                                            }
-                                                        />
+                                                            <button class="button secondary" on:click=move |_| merge_vendor_ids()>
+                                                                "Merge vendors"
+                                                            </button>
+                                                        </div>
                                                     </div>
+                                                    <div class="status">{move || vendor_merge_status.get()}</div>
                                                 </div>
                                                 <div class="detail-block">
-                                                    <strong>"Local Conversions"</strong>
+                                                    <strong>"Priced Items"</strong>
                                                     <div class="data-table">
                                                         <div class="data-header data-cols-4">
-                                                            <span>"Vendor"</span>
-                                                            <span>"Recipe Unit"</span>
-                                                            <span>"Purch Unit"</span>
-                                                            <span>"Hits"</span>
+                                                            <span>"Item"</span>
+                                                            <span>"Item ID"</span>
+                                                            <span>"Price"</span>
+                                                            <span>"Pack"</span>
                                                         </div>
                                                         <For
-                                                            each=move || detail.missing_edges.clone()
-                                                            key=|edge| (edge.vendor_id, edge.recipe_unit.clone(), edge.purch_unit.clone())
-                                                            children=move |edge| view! {
+                                                            each=move || detail.price_items.clone()
+                                                            key=|item| item.item_id
+                                                            children=move |item| view! {
                                                                 <div class="data-row data-cols-4">
-                                                                    <span>{edge.vendor_id}</span>
-                                                                    <span>{edge.recipe_unit}</span>
-                                                                    <span>{edge.purch_unit}</span>
-                                                                    <span>{edge.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.item_name}</span>
+                                                                    <span>{item.item_id}</span>
+                                                                    <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.pack}</span>
                                                                 </div>
                                                             }
                                                         />
@@ -3865,428 +9281,1191 @@ pub fn App() -> impl IntoView {
                                     })
                                 })}
                             </Show>
-                            <Show when=move || inventory_detail_loading.get()>
-                                <div class="status">"Loading item detail..."</div>
+                            <Show when=move || vendor_detail_loading.get()>
+                                <div class="status">"Loading vendor details..."</div>
                             </Show>
                         </div>
                     </div>
                 </Show>
 
-                <Show when=move || active_panel.get() == "recipes">
+                <Show when=move || active_panel.get() == "conversions">
                     <div class="panel">
                         <div class="row">
-                            <div class="input">
-                                <label>"Search recipes"</label>
-                                <input
-                                    type="text"
-                                    prop:value=recipe_query
-                                    on:input=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_recipe_query.set(value);
+                            <div>
+                                <strong>"Conversion Overview"</strong>
+                                <div class="status">
+                                    {move || format!(
+                                        "Suggestions: {} | Safe: {} | Todo: {} | Local conversions: {}",
+                                        conversion_overview.get().suggestions,
+                                        conversion_overview.get().suggestions_safe,
+                                        conversion_overview.get().todo,
+                                        conversion_overview.get().missing_edges
+                                    )}
+                                </div>
+                            </div>
+                        </div>
+                        <div class="row" style="margin-top: 12px;">
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "suggestions"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("suggestions".to_string());
+                                    set_conversion_page.set(0);
+                                    trigger_conversion_suggestions_fetch(
+                                        "conv_suggestions".to_string(),
+                                        0,
+                                        conversion_limit,
+                                        set_conversion_loading,
+                                        set_conversion_status,
+                                        set_conversion_suggestions,
+                                        set_conversion_suggestions_total,
+                                    );
+                                }
+                            >
+                                "Suggestions"
+                            </button>
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "safe"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("safe".to_string());
+                                    set_conversion_page.set(0);
+                                    trigger_conversion_suggestions_fetch(
+                                        "conv_suggestions_safe".to_string(),
+                                        0,
+                                        conversion_limit,
+                                        set_conversion_loading,
+                                        set_conversion_status,
+                                        set_conversion_suggestions_safe,
+                                        set_conversion_suggestions_safe_total,
+                                    );
+                                }
+                            >
+                                "Safe"
+                            </button>
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "review"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("review".to_string());
+                                    set_conversion_page.set(0);
+                                    load_review();
+                                }
+                            >
+                                "Review"
+                            </button>
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "todo"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("todo".to_string());
+                                    set_conversion_page.set(0);
+                                    trigger_conversion_todo_fetch(
+                                        0,
+                                        conversion_limit,
+                                        set_conversion_loading,
+                                        set_conversion_status,
+                                        set_conversion_todo,
+                                        set_conversion_todo_total,
+                                    );
+                                }
+                            >
+                                "Todo"
+                            </button>
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "missing"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("missing".to_string());
+                                    set_conversion_page.set(0);
+                                    trigger_missing_edges_fetch(
+                                        0,
+                                        conversion_limit,
+                                        set_conversion_loading,
+                                        set_conversion_status,
+                                        set_conversion_missing_edges,
+                                        set_conversion_missing_edges_total,
+                                    );
+                                }
+                            >
+                                "Local Conversions"
+                            </button>
+                            <button
+                                class="button secondary"
+                                class:active=move || conversion_tab.get() == "standard"
+                                on:click=move |_| {
+                                    set_conversion_tab.set("standard".to_string());
+                                    set_conversion_page.set(0);
+                                    load_unit_standards();
+                                }
+                            >
+                                "Standard Units"
+                            </button>
+                        </div>
+                        <div class="status">{move || conversion_status.get()}</div>
+                    </div>
+
+                    <div class="panel">
+                        <Show
+                            when=move || conversion_tab.get() == "suggestions"
+                            fallback=move || ()
+                        >
+                            <div class="data-table">
+                                <div class="data-header data-cols-7">
+                                    <span>"Item"</span>
+                                    <span>"Units"</span>
+                                    <span>"Qty"</span>
+                                    <span>"Hits"</span>
+                                    <span>"Source"</span>
+                                    <span>"Path"</span>
+                                    <span>"Action"</span>
+                                </div>
+                                <For
+                                    each=move || conversion_suggestions.get()
+                                    key=|row| (row.item_id, row.vendor_id, row.unit_id1, row.unit_id2, row.derived_from.clone())
+                                    children=move |row| view! {
+                                        <div class="data-row data-cols-7">
+                                            <span>{format!("{} (v{})", row.item_id, row.vendor_id)}</span>
+                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
+                                            <span>{format!("{:.3} / {:.3}", row.qty1, row.qty2)}</span>
+                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                            <span>{suggestion_source(&row)}</span>
+                                            <span>{row.path.clone()}</span>
+                                            <button class="button tiny" on:click=move |_| approve_conversion(row.clone())>
+                                                "Approve"
+                                            </button>
+                                        </div>
+                                    }
+                                />
+                            </div>
+                        </Show>
+                        <Show when=move || conversion_tab.get() == "safe">
+                            <div class="data-table">
+                                <div class="data-header data-cols-7">
+                                    <span>"Item"</span>
+                                    <span>"Units"</span>
+                                    <span>"Qty"</span>
+                                    <span>"Hits"</span>
+                                    <span>"Source"</span>
+                                    <span>"Path"</span>
+                                    <span>"Action"</span>
+                                </div>
+                                <For
+                                    each=move || conversion_suggestions_safe.get()
+                                    key=|row| (row.item_id, row.vendor_id, row.unit_id1, row.unit_id2, row.derived_from.clone())
+                                    children=move |row| view! {
+                                        <div class="data-row data-cols-7">
+                                            <span>{format!("{} (v{})", row.item_id, row.vendor_id)}</span>
+                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
+                                            <span>{format!("{:.3} / {:.3}", row.qty1, row.qty2)}</span>
+                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                            <span>{suggestion_source(&row)}</span>
+                                            <span>{row.path.clone()}</span>
+                                            <button class="button tiny" on:click=move |_| approve_conversion(row.clone())>
+                                                "Approve"
+                                            </button>
+                                        </div>
+                                    }
+                                />
+                            </div>
+                        </Show>
+                        <Show when=move || conversion_tab.get() == "review">
+                            <div class="row" style="align-items: end;">
+                                <div class="input" style="max-width: 140px;">
+                                    <label>"Min confidence %"</label>
+                                    <input
+                                        type="number"
+                                        min="0"
+                                        max="100"
+                                        prop:value=move || review_min_confidence.get()
+                                        on:input=move |ev| set_review_min_confidence.set(event_target_value(&ev))
+                                    />
+                                </div>
+                                <div class="input" style="max-width: 120px;">
+                                    <label>"Max hops"</label>
+                                    <input
+                                        type="number"
+                                        min="1"
+                                        prop:value=move || review_max_hops.get()
+                                        on:input=move |ev| set_review_max_hops.set(event_target_value(&ev))
+                                    />
+                                </div>
+                                <div class="input" style="max-width: 200px;">
+                                    <label>"Item category"</label>
+                                    <input
+                                        type="text"
+                                        prop:value=move || review_category.get()
+                                        on:input=move |ev| set_review_category.set(event_target_value(&ev))
+                                    />
+                                </div>
+                                <div class="input" style="flex: 0 0 auto;">
+                                    <button class="button secondary" on:click=move |_| load_review()>"Filter"</button>
+                                </div>
+                            </div>
+                            <div class="row" style="align-items: center;">
+                                <div class="status">
+                                    {move || format!(
+                                        "{} suggestions match, showing {}; {} selected",
+                                        review_total.get(),
+                                        review_rows.get().len(),
+                                        review_selected.get().len()
+                                    )}
+                                </div>
+                                <div style="margin-left: auto; display: flex; gap: 6px;">
+                                    <button
+                                        class="button tiny secondary"
+                                        on:click=move |_| set_review_selected.set(review_rows.get().iter().map(|r| r.key()).collect())
+                                    >"Select all"</button>
+                                    <button
+                                        class="button tiny secondary"
+                                        on:click=move |_| set_review_selected.set(Default::default())
+                                    >"Clear"</button>
+                                    <button class="button tiny secondary" on:click=move |_| run_review_acceptance(false)>"Preview impact"</button>
+                                    <button class="button tiny" on:click=move |_| run_review_acceptance(true)>"Accept selected"</button>
+                                    <button class="button tiny secondary" on:click=move |_| reject_review()>"Reject selected"</button>
+                                </div>
+                            </div>
+                            {move || review_impact.get().map(|(impact, saved)| view! {
+                                <div class="detail-block">
+                                    <strong>
+                                        {format!(
+                                            "{} {} conversions: {} recipes become fully costed ({} → {} fully costed)",
+                                            if saved { "Accepted" } else { "Accepting" },
+                                            impact.accepted,
+                                            impact.newly_complete,
+                                            impact.complete_before,
+                                            impact.complete_after
+                                        )}
+                                    </strong>
+                                    {impact.skipped.iter().map(|s| view! { <div class="status">{format!("Skipped {}", s)}</div> }).collect_view()}
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-4">
+                                            <span>"Recipe"</span>
+                                            <span>"Cost"</span>
+                                            <span>"Uncosted lines"</span>
+                                            <span>"Result"</span>
+                                        </div>
+                                        {impact.recipes.into_iter().map(|r| view! {
+                                            <div class="data-row data-cols-4">
+                                                <span>{r.recipe_name}</span>
+                                                <span>{format!("${:.2} → ${:.2}", r.old_cost, r.new_cost)}</span>
+                                                <span>{format!("{} → {}", r.old_missing, r.new_missing)}</span>
+                                                <span>{if r.now_complete { "Fully costed" } else { "" }}</span>
+                                            </div>
+                                        }).collect_view()}
+                                    </div>
+                                </div>
+                            })}
+                            <div class="data-table">
+                                <div class="data-header data-cols-8">
+                                    <span></span>
+                                    <span>"Item"</span>
+                                    <span>"Category"</span>
+                                    <span>"Units"</span>
+                                    <span>"Qty"</span>
+                                    <span>"Hits"</span>
+                                    <span>"Source"</span>
+                                    <span>"Path"</span>
+                                </div>
+                                <For
+                                    each=move || review_rows.get()
+                                    key=|row| row.key()
+                                    children=move |row| {
+                                        let key = row.key();
+                                        let checked_key = key.clone();
+                                        let source = format!(
+                                            "{} · {} · {} hops",
+                                            row.derived_from,
+                                            row.confidence.map(|c| format!("{:.0}%", c * 100.0)).unwrap_or_else(|| "-".to_string()),
+                                            row.hops
+                                        );
+                                        view! {
+                                            <div class="data-row data-cols-8">
+                                                <span>
+                                                    <input
+                                                        type="checkbox"
+                                                        prop:checked=move || review_selected.get().contains(&checked_key)
+                                                        on:change=move |ev| {
+                                                            let on = event_target_checked(&ev);
+                                                            let key = key.clone();
+                                                            set_review_selected.update(|selected| {
+                                                                if on {
+                                                                    selected.insert(key);
+                                                                } else {
+                                                                    selected.remove(&key);
+                                                                }
+                                                            });
+                                                        }
+                                                    />
+                                                </span>
+                                                <span>{format!("{} ({})", row.item_name, row.item_id)}</span>
+                                                <span>{row.category.clone()}</span>
+                                                <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
+                                                <span>{format!("{:.3} / {:.3}", row.qty1, row.qty2)}</span>
+                                                <span>{row.hits}</span>
+                                                <span>{source}</span>
+                                                <span>{row.path.clone()}</span>
+                                            </div>
+                                        }
                                     }
                                 />
                             </div>
-                            <div class="input" style="align-self: end;">
-                                <div class="row">
-                                    <button class="button" on:click=move |_| {
-                                        set_recipe_page.set(0);
-                                        trigger_recipe_fetch(
-                                            recipe_query.get(),
-                                            0,
-                                            recipe_limit,
-                                            set_recipe_loading,
-                                            set_recipe_status,
-                                            set_recipe_list,
-                                            set_recipe_total,
-                                            set_recipe_filtered,
-                                            set_recipe_loaded,
-                                            true,
-                                            set_recipe_detail_loading,
-                                            set_recipe_detail_status,
-                                            set_selected_recipe,
-                                        );
-                                    } disabled=move || recipe_loading.get()>
-                                        {move || if recipe_loading.get() { "Searching..." } else { "Search" }}
-                                    </button>
-                                    <button class="button secondary" on:click=move |_| {
-                                        set_recipe_query.set(String::new());
-                                        set_recipe_page.set(0);
-                                        trigger_recipe_fetch(
-                                            String::new(),
-                                            0,
-                                            recipe_limit,
-                                            set_recipe_loading,
-                                            set_recipe_status,
-                                            set_recipe_list,
-                                            set_recipe_total,
-                                            set_recipe_filtered,
-                                            set_recipe_loaded,
-                                            true,
-                                            set_recipe_detail_loading,
-                                            set_recipe_detail_status,
-                                            set_selected_recipe,
-                                        );
-                                    }>
-                                        "Reset"
-                                    </button>
+                        </Show>
+                        <Show when=move || conversion_tab.get() == "todo">
+                            <div class="data-table">
+                                <div class="data-header data-cols-5">
+                                    <span>"Item"</span>
+                                    <span>"Units"</span>
+                                    <span>"Hits"</span>
+                                    <span>"Needed"</span>
+                                    <span>"Vendor"</span>
                                 </div>
+                                <For
+                                    each=move || conversion_todo.get()
+                                    key=|row| (row.item_id, row.recipe_unit_id, row.purch_unit_id)
+                                    children=move |row| view! {
+                                        <div class="data-row data-cols-5">
+                                            <span>{row.item_id}</span>
+                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
+                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                            <span>{row.needed}</span>
+                                            <span>{row.vendor_id}</span>
+                                        </div>
+                                    }
+                                />
                             </div>
-                        </div>
-                        <div class="status">{move || recipe_status.get()}</div>
-                    </div>
-
-                    <div class="panel recipe-grid">
-                        <div class="recipe-list">
-                            <div class="recipe-list-header">
-                                <span>"Recipes"</span>
-                                <span>{move || format!(
-                                    "{} / {}",
-                                    recipe_filtered.get(),
-                                    recipe_total.get()
-                                )}</span>
-                            </div>
-                            <div class="recipe-list-body">
+                        </Show>
+                        <Show when=move || conversion_tab.get() == "missing">
+                            <div class="data-table">
+                                <div class="data-header data-cols-8">
+                                    <span>"Item"</span>
+                                    <span>"Item Name"</span>
+                                    <span>"Units"</span>
+                                    <span>"Hits"</span>
+                                    <span>"Qty (recipe)"</span>
+                                    <span>"Qty (purch)"</span>
+                                    <span>"Vendor"</span>
+                                    <span>"Action"</span>
+                                </div>
                                 <For
-                                    each=move || recipe_list.get()
-                                    key=|item| item.recipe_id
-                                    children=move |item| {
-                                        let recipe_id = item.recipe_id;
-                                        view! {
-                                            <div
-                                                class="recipe-row"
-                                                class:selected=move || selected_recipe.get().map(|r| r.recipe_id == recipe_id).unwrap_or(false)
-                                                on:click=move |_| {
-                                                    trigger_recipe_detail_fetch(
-                                                        recipe_id,
-                                                        set_recipe_detail_loading,
-                                                        set_recipe_detail_status,
-                                                        set_selected_recipe,
-                                                    );
+                                    each=move || conversion_missing_edges.get()
+                                    key=|row| (row.item_id, row.recipe_unit_id, row.purch_unit_id)
+                                    children=move |row| {
+                                        let (qty1_val, set_qty1_val) = signal(String::new());
+                                        let (qty2_val, set_qty2_val) = signal(String::new());
+                                        let (save_status, set_save_status) = signal(String::new());
+                                        let row_c = row.clone();
+                                        let save_local_conv = move |_| {
+                                            let q1: f64 = match qty1_val.get().parse() {
+                                                Ok(v) if v > 0.0 => v,
+                                                _ => { set_save_status.set("Invalid qty".into()); return; }
+                                            };
+                                            let q2: f64 = match qty2_val.get().parse() {
+                                                Ok(v) if v > 0.0 => v,
+                                                _ => { set_save_status.set("Invalid qty".into()); return; }
+                                            };
+                                            let r = row_c.clone();
+                                            set_save_status.set("Saving...".into());
+                                            spawn_local(async move {
+                                                let args = to_value(&UpsertConvunitArgs {
+                                                    item_id: r.item_id,
+                                                    vendor_id: r.vendor_id,
+                                                    unit_id1: r.recipe_unit_id,
+                                                    unit_id2: r.purch_unit_id,
+                                                    qty1: q1,
+                                                    qty2: q2,
+                                                    status: Some(1),
+                                                }).unwrap();
+                                                match invoke_cmd::<PatchResponse>("upsert_convunit", args).await {
+                                                    Ok(resp) => set_save_status.set(resp.message),
+                                                    Err(e) => set_save_status.set(format!("Error: {e}")),
                                                 }
-                                            >
-                                                <div class="recipe-name">{item.name}</div>
-                                                <div class="recipe-meta">{format!("{} items", item.item_count)}</div>
+                                            });
+                                        };
+                                        view! {
+                                            <div class="data-row data-cols-8">
+                                                <span>{row.item_id}</span>
+                                                <span>{row.item_name}</span>
+                                                <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
+                                                <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                <input
+                                                    class="inline-input"
+                                                    type="number"
+                                                    step="any"
+                                                    min="0"
+                                                    placeholder="qty"
+                                                    prop:value=move || qty1_val.get()
+                                                    on:input=move |ev| set_qty1_val.set(event_target_value(&ev))
+                                                />
+                                                <input
+                                                    class="inline-input"
+                                                    type="number"
+                                                    step="any"
+                                                    min="0"
+                                                    placeholder="qty"
+                                                    prop:value=move || qty2_val.get()
+                                                    on:input=move |ev| set_qty2_val.set(event_target_value(&ev))
+                                                />
+                                                <span>{row.vendor_id}</span>
+                                                <span class="action-cell">
+                                                    <button class="button tiny" on:click=save_local_conv>"Save"</button>
+                                                    <Show when=move || !save_status.get().is_empty()>
+                                                        <span class="inline-status">{move || save_status.get()}</span>
+                                                    </Show>
+                                                </span>
                                             </div>
                                         }
                                     }
                                 />
                             </div>
-                            <div class="row" style="margin-top: 12px;">
+                        </Show>
+                        <Show when=move || conversion_tab.get() == "standard">
+                            {move || unit_standards.get().map(|data| {
+                                let covered = data.covered_todo;
+                                let covered_missing = data.covered_missing;
+                                let has_covered = covered + covered_missing > 0;
+                                let use_reference = data.reference_densities;
+                                let measures = data.measures.clone();
+                                view! {
+                                    <div class="row" style="align-items: center;">
+                                        <div class="status">
+                                            {format!(
+                                                "Weight and volume units convert for every item, and item densities bridge weights and volumes. {} todo and {} local conversions now convert.",
+                                                covered, covered_missing
+                                            )}
+                                        </div>
+                                        <label class="inline-status">
+                                            <input
+                                                type="checkbox"
+                                                prop:checked=use_reference
+                                                on:change=move |ev| {
+                                                    let enabled = event_target_checked(&ev);
+                                                    unit_standard_action("set_reference_densities", to_value(&SetReferenceDensitiesArgs { enabled }).unwrap());
+                                                }
+                                            />
+                                            " Use reference densities for items without one"
+                                        </label>
+                                        <div style="margin-left: auto; display: flex; gap: 6px;">
+                                            <button
+                                                class="button tiny secondary"
+                                                on:click=move |_| unit_standard_action("refresh_standard_units", to_value(&PingArgs {}).unwrap())
+                                            >"Re-detect"</button>
+                                            <Show when=move || has_covered>
+                                                <button
+                                                    class="button tiny"
+                                                    on:click=move |_| unit_standard_action("clear_covered_conv_todo", to_value(&PingArgs {}).unwrap())
+                                                >"Clear Covered Todo"</button>
+                                            </Show>
+                                        </div>
+                                    </div>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-5">
+                                            <span>"Unit"</span>
+                                            <span>"Type / Kind"</span>
+                                            <span>"Converts As"</span>
+                                            <span>"Size"</span>
+                                            <span>"Aliases"</span>
+                                        </div>
+                                        {data.units.into_iter().map(|u| {
+                                            let unit_id = u.unit_id;
+                                            let selected = match (u.source.as_str(), u.standard.clone()) {
+                                                ("manual", Some(s)) => s,
+                                                ("manual", None) => "none".to_string(),
+                                                _ => "auto".to_string(),
+                                            };
+                                            let auto_label = match (u.source.as_str(), u.standard.as_deref()) {
+                                                ("auto", Some(s)) => format!("Auto ({})", s),
+                                                _ => "Auto".to_string(),
+                                            };
+                                            let size = match (u.base_qty, u.dimension.as_deref()) {
+                                                (Some(qty), Some(dimension)) => format!(
+                                                    "{} {}",
+                                                    (qty * 10000.0).round() / 10000.0,
+                                                    if dimension == "weight" { "g" } else { "ml" }
+                                                ),
+                                                _ => "-".to_string(),
+                                            };
+                                            let name = if u.plural.is_empty() || u.plural == u.name {
+                                                u.name.clone()
+                                            } else {
+                                                format!("{} / {}", u.name, u.plural)
+                                            };
+                                            let code = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+                                            view! {
+                                                <div class="data-row data-cols-5">
+                                                    <span>{name}</span>
+                                                    <span>{format!("{} / {}", code(u.unit_type), code(u.unit_kind))}</span>
+                                                    <span>
+                                                        <select
+                                                            prop:value=selected
+                                                            on:change=move |ev| {
+                                                                let value = event_target_value(&ev);
+                                                                let standard = match value.as_str() {
+                                                                    "auto" => None,
+                                                                    "none" => Some(String::new()),
+                                                                    _ => Some(value),
+                                                                };
+                                                                unit_standard_action(
+                                                                    "set_unit_standard",
+                                                                    to_value(&SetUnitStandardArgs { unit_id, standard }).unwrap(),
+                                                                );
+                                                            }
+                                                        >
+                                                            <option value="auto">{auto_label}</option>
+                                                            <option value="none">"Not a standard measure"</option>
+                                                            {measures.iter().map(|m| view! {
+                                                                <option value={m.name.clone()}>{format!("{} ({})", m.name, m.dimension)}</option>
+                                                            }).collect::<Vec<_>>()}
+                                                        </select>
+                                                    </span>
+                                                    <span>{size}</span>
+                                                    <span class="action-cell">
+                                                        {u.aliases.into_iter().map(|alias| {
+                                                            let label = format!("{} \u{00d7}", alias);
+                                                            view! {
+                                                                <button
+                                                                    class="button tiny secondary"
+                                                                    title="Remove alias"
+                                                                    on:click=move |_| unit_standard_action(
+                                                                        "delete_unit_alias",
+                                                                        to_value(&DeleteUnitAliasArgs { alias: alias.clone() }).unwrap(),
+                                                                    )
+                                                                >{label}</button>
+                                                            }
+                                                        }).collect::<Vec<_>>()}
+                                                        <input
+                                                            class="inline-input"
+                                                            type="text"
+                                                            placeholder="Add alias"
+                                                            on:change=move |ev| {
+                                                                let alias = event_target_value(&ev).trim().to_string();
+                                                                if !alias.is_empty() {
+                                                                    unit_standard_action(
+                                                                        "save_unit_alias",
+                                                                        to_value(&SaveUnitAliasArgs { alias, unit_id }).unwrap(),
+                                                                    );
+                                                                }
+                                                            }
+                                                        />
+                                                    </span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                }
+                            })}
+                        </Show>
+                        <Show when=move || conversion_tab.get() != "standard" && conversion_tab.get() != "review">
+                            <div class="row" style="margin-top: 16px;">
                                 <button class="button secondary" on:click=move |_| {
-                                    let current = recipe_page.get();
+                                    let current = conversion_page.get();
                                     if current > 0 {
-                                        set_recipe_page.set(current - 1);
-                                        trigger_recipe_fetch(
-                                            recipe_query.get(),
-                                            current - 1,
-                                            recipe_limit,
-                                            set_recipe_loading,
-                                            set_recipe_status,
-                                            set_recipe_list,
-                                            set_recipe_total,
-                                            set_recipe_filtered,
-                                            set_recipe_loaded,
-                                            true,
-                                            set_recipe_detail_loading,
-                                            set_recipe_detail_status,
-                                            set_selected_recipe,
-                                        );
+                                        set_conversion_page.set(current - 1);
+                                        show_conversions();
                                     }
-                                } disabled=move || recipe_page.get() == 0 || recipe_loading.get()>
+                                } disabled=move || conversion_page.get() == 0 || conversion_loading.get()>
                                     "Previous"
                                 </button>
                                 <button class="button" on:click=move |_| {
-                                    let current = recipe_page.get();
-                                    let max = (recipe_filtered.get() as f64 / recipe_limit as f64).ceil() as usize;
+                                    let current = conversion_page.get();
+                                    let total = match conversion_tab.get().as_str() {
+                                        "suggestions" => conversion_suggestions_total.get(),
+                                        "safe" => conversion_suggestions_safe_total.get(),
+                                        "todo" => conversion_todo_total.get(),
+                                        _ => conversion_missing_edges_total.get(),
+                                    };
+                                    let max = (total as f64 / conversion_limit as f64).ceil() as usize;
                                     if current + 1 < max {
-                                        set_recipe_page.set(current + 1);
-                                        trigger_recipe_fetch(
-                                            recipe_query.get(),
-                                            current + 1,
-                                            recipe_limit,
-                                            set_recipe_loading,
-                                            set_recipe_status,
-                                            set_recipe_list,
-                                            set_recipe_total,
-                                            set_recipe_filtered,
-                                            set_recipe_loaded,
-                                            true,
-                                            set_recipe_detail_loading,
-                                            set_recipe_detail_status,
-                                            set_selected_recipe,
-                                        );
+                                        set_conversion_page.set(current + 1);
+                                        show_conversions();
                                     }
                                 } disabled=move || {
-                                    let max = (recipe_filtered.get() as f64 / recipe_limit as f64).ceil() as usize;
-                                    recipe_loading.get() || max == 0 || recipe_page.get() + 1 >= max
+                                    let total = match conversion_tab.get().as_str() {
+                                        "suggestions" => conversion_suggestions_total.get(),
+                                        "safe" => conversion_suggestions_safe_total.get(),
+                                        "todo" => conversion_todo_total.get(),
+                                        _ => conversion_missing_edges_total.get(),
+                                    };
+                                    let max = (total as f64 / conversion_limit as f64).ceil() as usize;
+                                    conversion_loading.get() || max == 0 || conversion_page.get() + 1 >= max
                                 }>
                                     "Next"
                                 </button>
                             </div>
+                        </Show>
+                    </div>
+                </Show>
+
+                <Show when=move || active_panel.get() == "purchasing">
+                    <div class="panel">
+                        <div class="row" style="align-items: center;">
+                            <strong>
+                                {move || match alert_list.get() {
+                                    Some(list) if list.new_count > 0 => format!("Price Alerts ({} new)", list.new_count),
+                                    _ => "Price Alerts".to_string(),
+                                }}
+                            </strong>
+                            <select
+                                style="margin-left: auto;"
+                                prop:value=move || alert_filter.get()
+                                on:change=move |ev| {
+                                    set_alert_filter.set(event_target_value(&ev));
+                                    load_alerts();
+                                }
+                            >
+                                <option value="new">"New"</option>
+                                <option value="acknowledged">"Acknowledged"</option>
+                                <option value="disputed">"Disputed"</option>
+                                <option value="">"All"</option>
+                            </select>
                         </div>
-                        <div class="recipe-detail">
-                            <div class="status">{move || recipe_detail_status.get()}</div>
-                            <Show
-                                when=move || selected_recipe.get().is_some()
-                                fallback=move || view! {
-                                    <div class="recipe-empty">
-                                        <strong>"Select a recipe"</strong>
-                                        <p>"Choose a recipe from the list to view ingredients and costing."</p>
+                        <div class="status">
+                            {move || alert_list.get().map(|list| format!(
+                                "Invoice and list prices more than {}% above the last known price for the same item and vendor, per purchase unit.",
+                                list.threshold_pct
+                            ))}
+                        </div>
+                        <Show when=move || !alert_status.get().is_empty()>
+                            <div class="status">{move || alert_status.get()}</div>
+                        </Show>
+                        {move || alert_list.get().map(|list| {
+                            if list.alerts.is_empty() {
+                                return view! { <div class="status">"No price alerts."</div> }.into_any();
+                            }
+                            list.alerts.into_iter().map(|a| {
+                                let alert_id = a.alert_id;
+                                let is_new = a.status == "new";
+                                let item_id = a.item_id;
+                                let new_price = a.new_price;
+                                let invoice = a.invoice_id.map(|id| (id, format!("#{} ({})", a.invoice_no, a.invoice_date)));
+                                let (note, set_note) = signal(a.note.clone());
+                                let source = match a.invoice_id {
+                                    Some(_) => format!("Invoice #{} ({})", a.invoice_no, a.invoice_date),
+                                    None => format!("Manual price ({})", a.created_at),
+                                };
+                                let lines = a.lines.iter().map(|l| {
+                                    format!("{} {} @ {}", l.qty, l.unit_name, format_money(l.price))
+                                }).collect::<Vec<_>>().join(", ");
+                                view! {
+                                    <div class="detail-block">
+                                        <div class="row" style="align-items: center;">
+                                            <strong>{format!("{} \u{2014} {}", a.item_name, a.vendor_name)}</strong>
+                                            <span>
+                                                {format!("{} \u{2192} {} / {} ", format_money(a.old_price), format_money(a.new_price), a.unit_name)}
+                                                <span class="diff-up">{format!("+{:.1}%", a.pct_change)}</span>
+                                            </span>
+                                            <span style="margin-left: auto;">{a.status.clone()}</span>
+                                        </div>
+                                        <div class="status">{source}</div>
+                                        <Show when={
+                                            let empty = lines.is_empty();
+                                            move || !empty
+                                        }>
+                                            <div class="status">{format!("Lines: {}", lines)}</div>
+                                        </Show>
+                                        <div class="row" style="align-items: center;">
+                                            <input
+                                                class="inline-input"
+                                                placeholder="Note (e.g. credit requested)"
+                                                prop:value=move || note.get()
+                                                on:input=move |ev| set_note.set(event_target_value(&ev))
+                                            />
+                                            {if is_new {
+                                                view! {
+                                                    <button class="button tiny" on:click=move |_| resolve_alert(alert_id, "acknowledged", note.get_untracked())>"Acknowledge"</button>
+                                                    <button class="button tiny secondary" on:click=move |_| resolve_alert(alert_id, "disputed", note.get_untracked())>"Dispute"</button>
+                                                }.into_any()
+                                            } else {
+                                                view! {
+                                                    <button class="button tiny secondary" on:click=move |_| resolve_alert(alert_id, "new", note.get_untracked())>"Reopen"</button>
+                                                }.into_any()
+                                            }}
+                                            <button
+                                                class="button tiny secondary"
+                                                title="Recipe cost impact of this price"
+                                                on:click={
+                                                    let invoice = invoice.clone();
+                                                    move |_| match invoice.clone() {
+                                                        Some((invoice_id, label)) => impact_for_invoice(invoice_id, label),
+                                                        None => {
+                                                            set_ci_mode.set("item".to_string());
+                                                            set_ci_item.set(item_id.to_string());
+                                                            set_ci_price.set(format!("{:.2}", new_price));
+                                                            run_impact(CostImpactInput {
+                                                                mode: "item".to_string(),
+                                                                item_id: Some(item_id),
+                                                                new_price: Some(new_price),
+                                                                ..Default::default()
+                                                            });
+                                                        }
+                                                    }
+                                                }
+                                            >"Impact"</button>
+                                        </div>
                                     </div>
                                 }
+                            }).collect::<Vec<_>>().into_any()
+                        })}
+                    </div>
+                    <div class="panel">
+                        <div class="row" style="align-items: center;">
+                            <strong>"Invoice Reconciliation"</strong>
+                            <span class="status">
+                                {move || recon_list.get().map(|r| format!(
+                                    "{} balanced, {} unbalanced, {} reviewed",
+                                    r.balanced, r.unbalanced, r.reviewed
+                                ))}
+                            </span>
+                            <select
+                                style="margin-left: auto;"
+                                prop:value=move || recon_filter.get()
+                                on:change=move |ev| {
+                                    set_recon_filter.set(event_target_value(&ev));
+                                    load_recon();
+                                }
                             >
-                                {move || selected_recipe.with(|opt| {
-                                    opt.as_ref().map(|detail| {
-                                        let detail = detail.clone();
-                                        let instruction_lines = detail
-                                            .instructions
-                                            .lines()
-                                            .enumerate()
-                                            .map(|(idx, line)| (idx, line.trim_end().to_string()))
-                                            .filter(|(_, line)| !line.is_empty())
-                                            .collect::<Vec<(usize, String)>>();
-                                        let has_instructions = !instruction_lines.is_empty();
-                                        let instruction_lines = Arc::new(instruction_lines);
-                                        let recipe_id_for_export = detail.recipe_id;
-                                        let recipe_id_for_edit = detail.recipe_id;
-                                        let edit_init_name = detail.name.clone();
-                                        let edit_init_instructions = detail.instructions.clone();
-                                        let recipe_name_for_file = detail.name.chars()
-                                            .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-')
-                                            .collect::<String>()
-                                            .replace(' ', "_");
-                                        let pdf_name = format!("{}.pdf", recipe_name_for_file);
-                                        let docx_name = format!("{}.docx", recipe_name_for_file);
+                                <option value="unbalanced">"Unbalanced"</option>
+                                <option value="reviewed">"Reviewed"</option>
+                                <option value="balanced">"Balanced"</option>
+                                <option value="">"All"</option>
+                            </select>
+                            <button class="button tiny" on:click=move |_| run_recon()>"Reconcile All"</button>
+                        </div>
+                        <div class="status">
+                            "Checks each invoice total against its lines, freight and charges less credit memos, plus line math, duplicate, zero-price and unknown-item lines."
+                        </div>
+                        <Show when=move || !recon_status.get().is_empty()>
+                            <div class="status">{move || recon_status.get()}</div>
+                        </Show>
+                        {move || recon_list.get().map(|list| {
+                            if list.invoices.is_empty() {
+                                return view! { <div class="status">"No invoices to show. Run Reconcile All to check every invoice."</div> }.into_any();
+                            }
+                            let more = (list.matching > list.invoices.len() as i64)
+                                .then(|| format!("Showing {} of {} invoices.", list.invoices.len(), list.matching));
+                            view! {
+                                <div>
+                                    {more.map(|m| view! { <div class="status">{m}</div> })}
+                                    {list.invoices.into_iter().map(|r| {
+                                        let invoice_id = r.invoice_id;
+                                        let is_reviewed = r.status == "reviewed";
+                                        let is_unbalanced = r.status == "unbalanced";
+                                        let (note, set_note) = signal(r.note.clone());
+                                        let totals = format!(
+                                            "Header {} \u{00b7} lines {} \u{00b7} charges {} \u{00b7} credits {}",
+                                            r.header_total.map(format_money).unwrap_or_else(|| "-".to_string()),
+                                            format_money(r.lines_total),
+                                            format_money(r.charges_total),
+                                            format_money(r.credits_total),
+                                        );
+                                        let diff_class = match r.difference {
+                                            Some(d) if d > 0.005 => "diff-up",
+                                            Some(d) if d < -0.005 => "diff-down",
+                                            _ => "",
+                                        };
                                         view! {
-                                            <div>
-                                                <div class="recipe-title">{detail.name}</div>
-                                                <div class="recipe-summary">
-                                                    <span>{format!("{} items", detail.item_count)}</span>
-                                                    <span>
-                                                        {if detail.missing_costs > 0 {
-                                                            format!("Cost incomplete ({} missing)", detail.missing_costs)
-                                                        } else {
-                                                            format!("Total cost {}", format_money(detail.total_cost))
-                                                        }}
+                                            <div class="detail-block">
+                                                <div class="row" style="align-items: center;">
+                                                    <strong>{format!("#{} \u{2014} {}", r.invoice_no, r.vendor_name)}</strong>
+                                                    <span>{r.invoice_date.clone()}</span>
+                                                    <span class=diff_class>
+                                                        {r.difference.map(|d| format!("{:+.2}", d)).unwrap_or_default()}
                                                     </span>
+                                                    <span style="margin-left: auto;">{r.status.clone()}</span>
                                                 </div>
-                                                <Show when={
-                                                    let has = !detail.allergens.is_empty();
-                                                    move || has
-                                                }>
-                                                    <div class="allergen-notice">
-                                                        <span class="allergen-icon">{"\u{26A0}"}</span>
-                                                        <strong>"Allergen Notice: "</strong>
-                                                        <span class="allergen-list">
-                                                            {detail.allergens.join(", ")}
-                                                        </span>
-                                                    </div>
-                                                </Show>
-                                                <div class="row" style="margin: 10px 0; gap: 8px;">
-                                                    <button class="button tiny" on:click=move |_| {
-                                                        let rid = recipe_id_for_export;
-                                                        let name = pdf_name.clone();
-                                                        trigger_save_dialog_and_export(
-                                                            "Save Recipe PDF",
-                                                            &name,
-                                                            "PDF",
-                                                            "pdf",
-                                                            set_export_status,
-                                                            move |path| ("export_recipe_pdf".to_string(),
-                                                                to_value(&ExportRecipeArgs { recipe_id: rid, output_path: path }).unwrap()),
-                                                        );
-                                                    }>"Export PDF"</button>
-                                                    <button class="button tiny secondary" on:click=move |_| {
-                                                        let rid = recipe_id_for_export;
-                                                        let name = docx_name.clone();
-                                                        trigger_save_dialog_and_export(
-                                                            "Save Recipe Word Document",
-                                                            &name,
-                                                            "Word Document",
-                                                            "docx",
-                                                            set_export_status,
-                                                            move |path| ("export_recipe_docx".to_string(),
-                                                                to_value(&ExportRecipeArgs { recipe_id: rid, output_path: path }).unwrap()),
-                                                        );
-                                                    }>"Export Word"</button>
-                                                </div>
-                                                <Show when=move || !export_status.get().is_empty()>
-                                                    <div class="status">{move || export_status.get()}</div>
-                                                </Show>
-                                                <div class="detail-block" style="margin-top: 14px;">
-                                                    <div class="row" style="align-items: center; gap: 8px;">
-                                                        <strong>"Edit Recipe"</strong>
-                                                        <button class="button tiny secondary" on:click={
-                                                            let init_n = edit_init_name.clone();
-                                                            let init_i = edit_init_instructions.clone();
-                                                            move |_| {
-                                                                if !edit_recipe_editing.get() {
-                                                                    set_edit_recipe_name.set(init_n.clone());
-                                                                    set_edit_recipe_instructions.set(init_i.clone());
-                                                                }
-                                                                set_edit_recipe_editing.set(!edit_recipe_editing.get());
-                                                                set_edit_recipe_msg.set(String::new());
-                                                            }
-                                                        }>{move || if edit_recipe_editing.get() { "Cancel" } else { "Edit" }}</button>
-                                                    </div>
-                                                    <Show when=move || edit_recipe_editing.get()>
-                                                        <div style="margin-top: 10px;">
-                                                            <div class="input">
-                                                                <label>"Name"</label>
-                                                                <input
-                                                                    type="text"
-                                                                    prop:value=move || edit_recipe_name.get()
-                                                                    on:input=move |ev| {
-                                                                        set_edit_recipe_name.set(event_target_value(&ev));
-                                                                    }
-                                                                />
-                                                            </div>
-                                                            <div class="input" style="margin-top: 8px;">
-                                                                <label>"Instructions"</label>
-                                                                <textarea
-                                                                    rows="8"
-                                                                    style="width: 100%; font-family: inherit; font-size: 14px; padding: 8px; border-radius: 8px; border: 1px solid var(--border);"
-                                                                    prop:value=move || edit_recipe_instructions.get()
-                                                                    on:input=move |ev| {
-                                                                        set_edit_recipe_instructions.set(event_target_value(&ev));
-                                                                    }
-                                                                />
-                                                            </div>
-                                                            <div class="row" style="margin-top: 10px; gap: 8px;">
-                                                                <button class="button tiny" on:click=move |_| {
-                                                                    save_recipe_edit(recipe_id_for_edit);
-                                                                }>"Save Changes"</button>
-                                                            </div>
-                                                            <Show when=move || !edit_recipe_msg.get().is_empty()>
-                                                                <div class="status" style="margin-top: 6px;">{move || edit_recipe_msg.get()}</div>
-                                                            </Show>
-                                                        </div>
+                                                <div class="status">{totals}</div>
+                                                {r.discrepancies.iter().map(|d| {
+                                                    let line = d.trans_id.map(|id| format!("Line {}: ", id)).unwrap_or_default();
+                                                    view! { <div class="status">{format!("{}{}", line, d.message)}</div> }
+                                                }).collect::<Vec<_>>()}
+                                                <div class="row" style="align-items: center;">
+                                                    <input
+                                                        class="inline-input"
+                                                        placeholder="Note (e.g. vendor confirmed)"
+                                                        prop:value=move || note.get()
+                                                        on:input=move |ev| set_note.set(event_target_value(&ev))
+                                                    />
+                                                    <Show when=move || is_unbalanced>
+                                                        <button class="button tiny" on:click=move |_| mark_reviewed(invoice_id, true, note.get_untracked())>"Mark Reviewed"</button>
                                                     </Show>
-                                                </div>
-                                                <div class="recipe-instructions">
-                                                    <strong>"Instructions"</strong>
-                                                    <Show
-                                                        when=move || has_instructions
-                                                        fallback=move || view! { <p>"No instructions imported."</p> }
-                                                    >
-                                                        <div class="instruction-lines">
-                                                            <For
-                                                                each={
-                                                                    let instruction_lines = instruction_lines.clone();
-                                                                    move || instruction_lines.as_ref().clone()
-                                                                }
-                                                                key=|(idx, _)| *idx
-                                                                children=move |(_, line)| view! {
-                                                                    <p class="instruction-line">{line}</p>
-                                                                }
-                                                            />
-                                                        </div>
+                                                    <Show when=move || is_reviewed>
+                                                        <button class="button tiny secondary" on:click=move |_| mark_reviewed(invoice_id, false, note.get_untracked())>"Reopen"</button>
                                                     </Show>
+                                                    <button
+                                                        class="button tiny secondary"
+                                                        on:click=move |_| trigger_invoice_detail_fetch(
+                                                            invoice_id,
+                                                            set_invoice_detail_loading,
+                                                            set_invoice_detail_status,
+                                                            set_selected_invoice,
+                                                        )
+                                                    >"Open Invoice"</button>
                                                 </div>
-                                                <div class="recipe-ingredients">
-                                                    <div
-                                                        class="recipe-ingredients-header"
-                                                        class:recipe-ingredients-8=move || edit_recipe_editing.get()
-                                                    >
-                                                        <span>"Item"</span>
-                                                        <span>"Qty"</span>
-                                                        <span>"Unit"</span>
-                                                        <span>"Purch Unit"</span>
-                                                        <span>"Price"</span>
-                                                        <span>"Cost"</span>
-                                                        <span>"Status"</span>
-                                                        <Show when=move || edit_recipe_editing.get()>
-                                                            <span></span>
-                                                        </Show>
-                                                    </div>
-                                                    <For
-                                                        each=move || detail.ingredients.clone()
-                                                        key=|item| item.recp_item_id
-                                                        children=move |item| {
-                                                            let recp_item_id = item.recp_item_id;
-                                                            let rid = recipe_id_for_edit;
-                                                            view! {
-                                                            <div
-                                                                class="recipe-ingredients-row"
-                                                                class:recipe-ingredients-8=move || edit_recipe_editing.get()
-                                                            >
-                                                                <span>{item.item_name}</span>
-                                                                <span>{item.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
-                                                                <span>{item.unit_name}</span>
-                                                                <span>{item.purch_unit_name}</span>
-                                                                <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                <span>{item.extended_cost.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                <span>{item.cost_status}</span>
-                                                                <Show when=move || edit_recipe_editing.get()>
-                                                                    <button
-                                                                        class="button tiny danger"
-                                                                        on:click=move |_| {
-                                                                            delete_recipe_ingredient(rid, recp_item_id);
-                                                                        }
-                                                                    >"Remove"</button>
-                                                                </Show>
-                                                            </div>
-                                                            }
-                                                        }
-                                                    />
-                                                </div>
                                             </div>
                                         }
-                                    })
-                                })}
-                            </Show>
-                            <Show when=move || recipe_detail_loading.get()>
-                                <div class="status">"Loading recipe details..."</div>
-                            </Show>
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                            }.into_any()
+                        })}
+                    </div>
+                    <div class="panel">
+                        <strong>"Recipe Cost Impact"</strong>
+                        <div class="status">
+                            "Old vs new cost of every recipe using the changed items, directly or through sub-recipes. Prices are per purchase unit."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input" style="flex: 0 0 auto;">
+                                <label>"Change"</label>
+                                <select prop:value=move || ci_mode.get() on:change=move |ev| set_ci_mode.set(event_target_value(&ev))>
+                                    <option value="item">"One item"</option>
+                                    <option value="invoice">"Invoice prices"</option>
+                                    <option value="percent">"Percent change"</option>
+                                </select>
+                            </div>
+                            <Show when=move || ci_mode.get() == "item">
+                                <div class="input">
+                                    <label>"Item"</label>
+                                    <select prop:value=move || ci_item.get() on:change=move |ev| set_ci_item.set(event_target_value(&ev))>
+                                        <option value="">"Choose an item"</option>
+                                        {move || po_item_options.get().into_iter().map(|i| view! {
+                                            <option value={i.item_id.to_string()}>{i.name}</option>
+                                        }).collect::<Vec<_>>()}
+                                    </select>
+                                </div>
+                                <div class="input">
+                                    <label>"New Price"</label>
+                                    <input type="number" step="any" min="0" prop:value=move || ci_price.get() on:input=move |ev| set_ci_price.set(event_target_value(&ev)) />
+                                </div>
+                            </Show>
+                            <Show when=move || ci_mode.get() == "invoice">
+                                <div class="input" style="align-self: end;">
+                                    <div class="status">
+                                        {move || match ci_invoice.get() {
+                                            Some((_, label)) => format!("Invoice {}", label),
+                                            None => "Open an invoice below and choose Recipe Impact".to_string(),
+                                        }}
+                                    </div>
+                                </div>
+                            </Show>
+                            <Show when=move || ci_mode.get() == "percent">
+                                <div class="input">
+                                    <label>"Change %"</label>
+                                    <input type="number" step="any" placeholder="5" prop:value=move || ci_pct.get() on:input=move |ev| set_ci_pct.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Food Category"</label>
+                                    <input placeholder="All categories" prop:value=move || ci_category.get() on:input=move |ev| set_ci_category.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Vendor"</label>
+                                    <select prop:value=move || ci_vendor.get() on:change=move |ev| set_ci_vendor.set(event_target_value(&ev))>
+                                        <option value="">"All vendors"</option>
+                                        {move || invoice_vendor_options.get().into_iter().map(|v| view! {
+                                            <option value={v.vendor_id.to_string()}>{v.name}</option>
+                                        }).collect::<Vec<_>>()}
+                                    </select>
+                                </div>
+                            </Show>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_impact_form()>"Analyze"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_impact(true)>"PDF"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_impact(false)>"CSV"</button>
+                            </div>
+                        </div>
+                        <Show when=move || !ci_status.get().is_empty()>
+                            <div class="status">{move || ci_status.get()}</div>
+                        </Show>
+                        {move || ci_result.get().map(|result| {
+                            let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
+                            let pct = |v: Option<f64>| v.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string());
+                            let items = result.items.iter().map(|i| {
+                                format!("{} {} \u{2192} {} / {}", i.item_name, format_money(i.old_price), format_money(i.new_price), i.unit_name)
+                            }).collect::<Vec<_>>().join("; ");
+                            let skipped = result.skipped.join("; ");
+                            let has_skipped = !skipped.is_empty();
+                            let empty = result.recipes.is_empty();
+                            view! {
+                                <div class="status">
+                                    {format!("{} \u{2014} {} items, {} recipes", result.description, result.items.len(), result.recipes.len())}
+                                </div>
+                                <div class="status" title={items.clone()}>{items.chars().take(240).collect::<String>()}</div>
+                                <Show when=move || has_skipped>
+                                    <div class="status">{format!("Skipped: {}", skipped)}</div>
+                                </Show>
+                                <Show when=move || empty>
+                                    <div class="status">"No recipes use these items."</div>
+                                </Show>
+                                <div class="data-table">
+                                    <div class="data-header data-cols-7">
+                                        <span>"Recipe"</span>
+                                        <span>"Old Cost"</span>
+                                        <span>"New Cost"</span>
+                                        <span>"Change"</span>
+                                        <span>"Portion"</span>
+                                        <span>"Food Cost %"</span>
+                                        <span>"\u{0394} FC pts"</span>
+                                    </div>
+                                    {result.recipes.into_iter().map(|r| {
+                                        let change_class = if r.cost_change > 0.005 {
+                                            "diff-up"
+                                        } else if r.cost_change < -0.005 {
+                                            "diff-down"
+                                        } else {
+                                            "diff-zero"
+                                        };
+                                        let name = if r.direct { r.recipe_name } else { format!("{} (via sub-recipe)", r.recipe_name) };
+                                        let title = if r.missing_costs > 0 { format!("{} lines without a cost", r.missing_costs) } else { String::new() };
+                                        view! {
+                                            <div class="data-row data-cols-7" title={title}>
+                                                <span>{name}</span>
+                                                <span>{format_money(r.old_cost)}</span>
+                                                <span>{format_money(r.new_cost)}</span>
+                                                <span class={change_class}>
+                                                    {format!("{:+.2} ({})", r.cost_change, r.cost_change_pct.map(|p| format!("{:+.1}%", p)).unwrap_or_else(|| "-".to_string()))}
+                                                </span>
+                                                <span>{format!("{} \u{2192} {}", money(r.old_portion_cost), money(r.new_portion_cost))}</span>
+                                                <span>{format!("{} \u{2192} {}", pct(r.old_food_cost_pct), pct(r.new_food_cost_pct))}</span>
+                                                <span class={change_class}>{r.food_cost_pct_change.map(|p| format!("{:+.1}", p)).unwrap_or_else(|| "-".to_string())}</span>
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                            }
+                        })}
+                    </div>
+                    <div class="panel">
+                        <strong>"Spend Analytics"</strong>
+                        <div class="status">
+                            "Invoice line spend (before freight and fees) by vendor, food category or item, comparing the last two periods in the range."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input" style="flex: 0 0 auto;">
+                                <label>"Group By"</label>
+                                <select prop:value=move || sp_group.get() on:change=move |ev| set_sp_group.set(event_target_value(&ev))>
+                                    <option value="vendor">"Vendor"</option>
+                                    <option value="category">"Food category"</option>
+                                    <option value="item">"Item"</option>
+                                </select>
+                            </div>
+                            <div class="input" style="flex: 0 0 auto;">
+                                <label>"Period"</label>
+                                <select prop:value=move || sp_period.get() on:change=move |ev| set_sp_period.set(event_target_value(&ev))>
+                                    <option value="week">"Week"</option>
+                                    <option value="month">"Month"</option>
+                                    <option value="quarter">"Quarter"</option>
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"From"</label>
+                                <input type="date" prop:value=move || sp_from.get() on:input=move |ev| set_sp_from.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"To"</label>
+                                <input type="date" prop:value=move || sp_to.get() on:input=move |ev| set_sp_to.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"Vendor"</label>
+                                <select prop:value=move || sp_vendor.get() on:change=move |ev| set_sp_vendor.set(event_target_value(&ev))>
+                                    <option value="">"All vendors"</option>
+                                    {move || invoice_vendor_options.get().into_iter().map(|v| view! {
+                                        <option value={v.vendor_id.to_string()}>{v.name}</option>
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Food Category"</label>
+                                <input placeholder="All categories" prop:value=move || sp_category.get() on:input=move |ev| set_sp_category.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_spend()>"Run"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_spend(true)>"PDF"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_spend(false)>"CSV"</button>
+                            </div>
                         </div>
+                        <Show when=move || !sp_status.get().is_empty()>
+                            <div class="status">{move || sp_status.get()}</div>
+                        </Show>
+                        {move || sp_result.get().map(|report| {
+                            let change_class = |change: f64| if change > 0.005 {
+                                "diff-up"
+                            } else if change < -0.005 {
+                                "diff-down"
+                            } else {
+                                "diff-zero"
+                            };
+                            let change_text = |change: f64, pct: Option<f64>| match pct {
+                                Some(p) => format!("{:+.2} ({:+.1}%)", change, p),
+                                None => format!("{:+.2}", change),
+                            };
+                            let n = report.periods.len();
+                            let (previous_label, current_label) = match n {
+                                0 => ("Previous".to_string(), "Current".to_string()),
+                                1 => ("Previous".to_string(), report.periods[0].clone()),
+                                _ => (report.periods[n - 2].clone(), report.periods[n - 1].clone()),
+                            };
+                            let trend = report
+                                .periods
+                                .iter()
+                                .zip(&report.period_totals)
+                                .skip(n.saturating_sub(12))
+                                .map(|(p, t)| (p.clone(), *t))
+                                .collect::<Vec<_>>();
+                            let has_movers = !report.movers.is_empty();
+                            view! {
+                                <div class="status">
+                                    {format!(
+                                        "{} to {} \u{2014} {} across {} invoices, {} lines",
+                                        report.date_from,
+                                        report.date_to,
+                                        format_money(report.total),
+                                        report.invoice_count,
+                                        report.line_count
+                                    )}
+                                </div>
+                                <div class="detail-block">
+                                    <strong>{format!("Spend by {}", report.period)}</strong>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-3">
+                                            <span>"Period"</span>
+                                            <span>"Spend"</span>
+                                            <span>"Change"</span>
+                                        </div>
+                                        {trend.iter().enumerate().map(|(i, (p, t))| {
+                                            let change = if i == 0 { None } else { Some(t - trend[i - 1].1) };
+                                            view! {
+                                                <div class="data-row data-cols-3">
+                                                    <span>{p.clone()}</span>
+                                                    <span>{format_money(*t)}</span>
+                                                    <span class={change.map(change_class).unwrap_or("diff-zero")}>
+                                                        {change.map(|c| format!("{:+.2}", c)).unwrap_or_else(|| "-".to_string())}
+                                                    </span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </div>
+                                <div class="detail-block">
+                                    <strong>{format!("By {}", report.group_by)}</strong>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-6">
+                                            <span>"Name"</span>
+                                            <span>"Total"</span>
+                                            <span>"Share"</span>
+                                            <span>{previous_label.clone()}</span>
+                                            <span>{current_label.clone()}</span>
+                                            <span>"Change"</span>
+                                        </div>
+                                        {report.rows.iter().take(100).map(|r| view! {
+                                            <div class="data-row data-cols-6">
+                                                <span>{r.label.clone()}</span>
+                                                <span>{format_money(r.total)}</span>
+                                                <span>{format!("{:.1}%", r.share_pct)}</span>
+                                                <span>{format_money(r.previous)}</span>
+                                                <span>{format_money(r.current)}</span>
+                                                <span class={change_class(r.change)}>{change_text(r.change, r.change_pct)}</span>
+                                            </div>
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </div>
+                                <Show when=move || has_movers>
+                                    <div class="detail-block">
+                                        <strong>{format!("Top movers, {} vs {}", current_label, previous_label)}</strong>
+                                        {report.movers.iter().map(|r| view! {
+                                            <div class="status">
+                                                {format!("{}: {} \u{2192} {} ", r.label, format_money(r.previous), format_money(r.current))}
+                                                <span class={change_class(r.change)}>{change_text(r.change, r.change_pct)}</span>
+                                            </div>
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </Show>
+                                <div class="detail-block">
+                                    <strong>"Share of Wallet"</strong>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-5">
+                                            <span>"Vendor"</span>
+                                            <span>"Spend"</span>
+                                            <span>"Share"</span>
+                                            <span>"Last Two Periods"</span>
+                                            <span>"Main Category"</span>
+                                        </div>
+                                        {report.vendor_share.iter().map(|v| {
+                                            let pct = |p: Option<f64>| p.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string());
+                                            view! {
+                                                <div class="data-row data-cols-5">
+                                                    <span>{v.vendor_name.clone()}</span>
+                                                    <span>{format_money(v.total)}</span>
+                                                    <span>{format!("{:.1}%", v.share_pct)}</span>
+                                                    <span>{format!("{} \u{2192} {}", pct(v.previous_share_pct), pct(v.current_share_pct))}</span>
+                                                    <span>{v.top_category.clone()}</span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </div>
+                            }
+                        })}
                     </div>
-                </Show>
-
-                <Show when=move || active_panel.get() == "vendors">
                     <div class="panel">
                         <div class="row">
                             <div class="input">
-                                <label>"Search vendors"</label>
+                                <label>"Search invoices"</label>
                                 <input
                                     type="text"
-                                    prop:value=vendor_query
+                                    prop:value=invoice_query
                                     on:input=move |ev| {
                                         let value = event_target_value(&ev);
-                                        set_vendor_query.set(value);
+                                        set_invoice_query.set(value);
                                     }
                                 />
                             </div>
                             <div class="input" style="align-self: end;">
                                 <div class="row">
                                     <button class="button" on:click=move |_| {
-                                        set_vendor_page.set(0);
-                                        trigger_vendor_fetch(
-                                            vendor_query.get(),
+                                        set_invoice_page.set(0);
+                                        trigger_invoice_fetch(
+                                            invoice_query.get(),
+                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
+                                            invoice_date_from.get(),
+                                            invoice_date_to.get(),
                                             0,
-                                            vendor_limit,
-                                            set_vendor_loading,
-                                            set_vendor_status,
-                                            set_vendor_list,
-                                            set_vendor_total,
-                                            set_vendor_filtered,
-                                            set_vendor_loaded,
+                                            invoice_limit,
+                                            set_invoice_loading,
+                                            set_invoice_status,
+                                            set_invoice_list,
+                                            set_invoice_total,
+                                            set_invoice_filtered,
+                                            set_invoice_loaded,
                                         );
-                                    } disabled=move || vendor_loading.get()>
-                                        {move || if vendor_loading.get() { "Searching..." } else { "Search" }}
+                                    } disabled=move || invoice_loading.get()>
+                                        {move || if invoice_loading.get() { "Searching..." } else { "Search" }}
                                     </button>
                                     <button class="button secondary" on:click=move |_| {
-                                        set_vendor_query.set(String::new());
-                                        set_vendor_page.set(0);
-                                        trigger_vendor_fetch(
+                                        set_invoice_query.set(String::new());
+                                        set_invoice_vendor_filter.set(String::new());
+                                        set_invoice_date_from.set(String::new());
+                                        set_invoice_date_to.set(String::new());
+                                        set_invoice_page.set(0);
+                                        trigger_invoice_fetch(
+                                            String::new(),
+                                            None,
+                                            String::new(),
                                             String::new(),
                                             0,
-                                            vendor_limit,
-                                            set_vendor_loading,
-                                            set_vendor_status,
-                                            set_vendor_list,
-                                            set_vendor_total,
-                                            set_vendor_filtered,
-                                            set_vendor_loaded,
+                                            invoice_limit,
+                                            set_invoice_loading,
+                                            set_invoice_status,
+                                            set_invoice_list,
+                                            set_invoice_total,
+                                            set_invoice_filtered,
+                                            set_invoice_loaded,
                                         );
                                     }>
                                         "Reset"
@@ -4294,41 +10473,102 @@ pub fn App() -> impl IntoView {
                                 </div>
                             </div>
                         </div>
-                        <div class="status">{move || vendor_status.get()}</div>
+                        <div class="row">
+                            <div class="input">
+                                <label>"Vendor ID"</label>
+                                <select
+                                    prop:value=invoice_vendor_filter
+                                    on:change=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_invoice_vendor_filter.set(value);
+                                    }
+                                >
+                                    <option value="">"All vendors"</option>
+                                    <For
+                                        each=move || invoice_vendor_options.get()
+                                        key=|v| v.vendor_id
+                                        children=move |v| view! {
+                                            <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
+                                        }
+                                    />
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Date from"</label>
+                                <input
+                                    type="date"
+                                    prop:value=invoice_date_from
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_invoice_date_from.set(value);
+                                    }
+                                />
+                            </div>
+                            <div class="input">
+                                <label>"Date to"</label>
+                                <input
+                                    type="date"
+                                    prop:value=invoice_date_to
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        set_invoice_date_to.set(value);
+                                    }
+                                />
+                            </div>
+                            <div class="input" style="align-self: end;">
+                                <button class="button secondary" on:click=move |_| export_invoices()>
+                                    "Export CSV"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="status">{move || invoice_status.get()}</div>
+                        <div class="status">{move || invoice_export_status.get()}</div>
+                        <div class="status">
+                            "Source: SQLite (imported from Invoice.csv + Trans.csv)"
+                        </div>
+                        <Show when=move || !invoice_export_path.get().is_empty()>
+                            <div class="row">
+                                <button class="button secondary" on:click=move |_| {
+                                    open_path(invoice_export_path.get());
+                                }>
+                                    "Reveal export file"
+                                </button>
+                                <div class="status">{move || invoice_export_path.get()}</div>
+                            </div>
+                        </Show>
                     </div>
 
                     <div class="panel recipe-grid">
                         <div class="recipe-list">
                             <div class="recipe-list-header">
-                                <span>"Vendors"</span>
+                                <span>"Invoices"</span>
                                 <span>{move || format!(
                                     "{} / {}",
-                                    vendor_filtered.get(),
-                                    vendor_total.get()
+                                    invoice_filtered.get(),
+                                    invoice_total.get()
                                 )}</span>
                             </div>
                             <div class="recipe-list-body">
                                 <For
-                                    each=move || vendor_list.get()
-                                    key=|item| item.vendor_id
+                                    each=move || invoice_list.get()
+                                    key=|item| item.invoice_id
                                     children=move |item| {
-                                        let vendor_id = item.vendor_id;
+                                        let invoice_id = item.invoice_id;
                                         view! {
                                             <div
                                                 class="recipe-row"
-                                                class:selected=move || selected_vendor.get().map(|v| v.vendor_id == vendor_id).unwrap_or(false)
+                                                class:selected=move || selected_invoice.get().map(|r| r.invoice.invoice_id == invoice_id).unwrap_or(false)
                                                 on:click=move |_| {
-                                                    set_vendor_merge_source_id.set(vendor_id.to_string());
-                                                    trigger_vendor_detail_fetch(
-                                                        vendor_id,
-                                                        set_vendor_detail_loading,
-                                                        set_vendor_detail_status,
-                                                        set_selected_vendor,
+                                                    trigger_invoice_detail_fetch(
+                                                        invoice_id,
+                                                        set_invoice_detail_loading,
+                                                        set_invoice_detail_status,
+                                                        set_selected_invoice,
                                                     );
                                                 }
                                             >
-                                                <div class="recipe-name">{item.name}</div>
-                                                <div class="recipe-meta">{format!("{} priced items", item.price_items)}</div>
+                                                <div class="recipe-name">{item.invoice_no.clone()}</div>
+                                                <div class="recipe-meta">{format!("{} | {}", item.vendor_name, item.invoice_date)}</div>
                                             </div>
                                         }
                                     }
@@ -4336,1061 +10576,2622 @@ pub fn App() -> impl IntoView {
                             </div>
                             <div class="row" style="margin-top: 12px;">
                                 <button class="button secondary" on:click=move |_| {
-                                    let current = vendor_page.get();
+                                    let current = invoice_page.get();
                                     if current > 0 {
-                                        set_vendor_page.set(current - 1);
-                                        trigger_vendor_fetch(
-                                            vendor_query.get(),
+                                        set_invoice_page.set(current - 1);
+                                        trigger_invoice_fetch(
+                                            invoice_query.get(),
+                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
+                                            invoice_date_from.get(),
+                                            invoice_date_to.get(),
                                             current - 1,
-                                            vendor_limit,
-                                            set_vendor_loading,
-                                            set_vendor_status,
-                                            set_vendor_list,
-                                            set_vendor_total,
-                                            set_vendor_filtered,
-                                            set_vendor_loaded,
+                                            invoice_limit,
+                                            set_invoice_loading,
+                                            set_invoice_status,
+                                            set_invoice_list,
+                                            set_invoice_total,
+                                            set_invoice_filtered,
+                                            set_invoice_loaded,
                                         );
                                     }
-                                } disabled=move || vendor_page.get() == 0 || vendor_loading.get()>
+                                } disabled=move || invoice_page.get() == 0 || invoice_loading.get()>
                                     "Previous"
                                 </button>
                                 <button class="button" on:click=move |_| {
-                                    let current = vendor_page.get();
-                                    let max = (vendor_filtered.get() as f64 / vendor_limit as f64).ceil() as usize;
+                                    let current = invoice_page.get();
+                                    let max = (invoice_filtered.get() as f64 / invoice_limit as f64).ceil() as usize;
                                     if current + 1 < max {
-                                        set_vendor_page.set(current + 1);
-                                        trigger_vendor_fetch(
-                                            vendor_query.get(),
+                                        set_invoice_page.set(current + 1);
+                                        trigger_invoice_fetch(
+                                            invoice_query.get(),
+                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
+                                            invoice_date_from.get(),
+                                            invoice_date_to.get(),
                                             current + 1,
-                                            vendor_limit,
-                                            set_vendor_loading,
-                                            set_vendor_status,
-                                            set_vendor_list,
-                                            set_vendor_total,
-                                            set_vendor_filtered,
-                                            set_vendor_loaded,
+                                            invoice_limit,
+                                            set_invoice_loading,
+                                            set_invoice_status,
+                                            set_invoice_list,
+                                            set_invoice_total,
+                                            set_invoice_filtered,
+                                            set_invoice_loaded,
                                         );
                                     }
                                 } disabled=move || {
-                                    let max = (vendor_filtered.get() as f64 / vendor_limit as f64).ceil() as usize;
-                                    vendor_loading.get() || max == 0 || vendor_page.get() + 1 >= max
+                                    let max = (invoice_filtered.get() as f64 / invoice_limit as f64).ceil() as usize;
+                                    invoice_loading.get() || max == 0 || invoice_page.get() + 1 >= max
                                 }>
                                     "Next"
                                 </button>
                             </div>
                         </div>
                         <div class="recipe-detail">
-                            <div class="status">{move || vendor_detail_status.get()}</div>
+                            <div class="status">{move || invoice_detail_status.get()}</div>
                             <Show
-                                when=move || selected_vendor.get().is_some()
+                                when=move || selected_invoice.get().is_some()
                                 fallback=move || view! {
                                     <div class="recipe-empty">
-                                        <strong>"Select a vendor"</strong>
-                                        <p>"Choose a vendor to see price lists and purchasing history."</p>
+                                        <strong>"Select an invoice"</strong>
+                                        <p>"Choose an invoice to review transaction lines."</p>
                                     </div>
                                 }
                             >
-                                {move || selected_vendor.with(|opt| {
+                                {move || selected_invoice.with(|opt| {
                                     opt.as_ref().map(|detail| {
                                         let detail = detail.clone();
+                                        let credit_lines: Vec<(i64, String)> = detail
+                                            .lines
+                                            .iter()
+                                            .filter_map(|l| {
+                                                let label = format!("{} ({} {})", l.item_name, l.qty.unwrap_or(0.0), l.unit_name);
+                                                l.trans_id.map(|id| (id, label))
+                                            })
+                                            .collect();
                                         view! {
                                             <div>
-                                                <div class="recipe-title">{detail.name}</div>
+                                                <div class="recipe-title">{detail.invoice.invoice_no.clone()}</div>
                                                 <div class="recipe-summary">
-                                                    <span>{format!("Vendor ID {}", detail.vendor_id)}</span>
-                                                    <span>{format!("{} invoices", detail.invoice_count)}</span>
-                                                    <span>{format!("{} trans lines", detail.trans_count)}</span>
+                                                    <span>{detail.invoice.vendor_name.clone()}</span>
+                                                    <span>{detail.invoice.invoice_date.clone()}</span>
+                                                    <span>{detail.invoice.total.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                    <span>{detail.freight.map(|v| format!("Freight {}", format_money(v))).unwrap_or_else(|| "Freight -".to_string())}</span>
+                                                    <span>
+                                                        {format!(
+                                                            "Line total {}",
+                                                            format_money(detail.lines.iter().filter_map(|l| l.ext_cost).sum::<f64>())
+                                                        )}
+                                                    </span>
+                                                    <span>{detail.recon_status.clone().unwrap_or_else(|| "not reconciled".to_string())}</span>
+                                                    <button
+                                                        class="button tiny secondary"
+                                                        on:click={
+                                                            let invoice_id = detail.invoice.invoice_id;
+                                                            let label = format!("#{} ({})", detail.invoice.invoice_no, detail.invoice.invoice_date);
+                                                            move |_| impact_for_invoice(invoice_id, label.clone())
+                                                        }
+                                                    >"Recipe Impact"</button>
                                                 </div>
                                                 <div class="detail-block">
-                                                    <strong>"Merge Vendor IDs"</strong>
+                                                    <strong>"Lines"</strong>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-8">
+                                                            <span>"Item"</span>
+                                                            <span>"Qty"</span>
+                                                            <span>"Unit"</span>
+                                                            <span>"Price"</span>
+                                                            <span>"Ext"</span>
+                                                            <span>"Charges"</span>
+                                                            <span>"Landed"</span>
+                                                            <span>"Trans ID"</span>
+                                                        </div>
+                                                        <For
+                                                            each=move || detail.lines.clone()
+                                                            key=|item| (item.trans_id.unwrap_or(0), item.item_id.unwrap_or(0))
+                                                            children=move |item| view! {
+                                                                <div class="data-row data-cols-8">
+                                                                    <span>{item.item_name}</span>
+                                                                    <span>{item.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.unit_name}</span>
+                                                                    <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.ext_cost.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.alloc_amount.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.landed_price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                                                    <span>{item.trans_id.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                                                </div>
+                                                            }
+                                                        />
+                                                    </div>
+                                                </div>
+                                                <div class="detail-block">
+                                                    <strong>"Freight, Fees & Tax"</strong>
                                                     <div class="status">
-                                                        "Move pricing, purchasing, and conversion history from one vendor ID into another."
+                                                        "Header charges are spread over the lines to give each a landed unit cost."
+                                                    </div>
+                                                    <div class="data-table">
+                                                        <div class="data-header data-cols-5">
+                                                            <span>"Charge"</span>
+                                                            <span>"Type"</span>
+                                                            <span>"Amount"</span>
+                                                            <span>"Allocate By"</span>
+                                                            <span></span>
+                                                        </div>
+                                                        {detail.charges.iter().map(|c| {
+                                                            let charge_id = c.charge_id;
+                                                            let removable = charge_id > 0;
+                                                            let invoice_id = detail.invoice.invoice_id;
+                                                            view! {
+                                                                <div class="data-row data-cols-5">
+                                                                    <span>{c.description.clone()}</span>
+                                                                    <span>{c.kind.clone()}</span>
+                                                                    <span>{format_money(c.amount)}</span>
+                                                                    <span>{c.allocation.clone()}</span>
+                                                                    <span class="action-cell">
+                                                                        <Show when=move || removable>
+                                                                            <button
+                                                                                class="button tiny secondary"
+                                                                                on:click=move |_| run_charge_action(
+                                                                                    "delete_invoice_charge",
+                                                                                    to_value(&ChargeIdArgs { charge_id }).unwrap(),
+                                                                                    invoice_id,
+                                                                                )
+                                                                            >"Delete"</button>
+                                                                        </Show>
+                                                                    </span>
+                                                                </div>
+                                                            }
+                                                        }).collect::<Vec<_>>()}
                                                     </div>
                                                     <div class="row" style="margin-top: 10px;">
+                                                        <div class="input" style="flex: 0 0 auto;">
+                                                            <label>"Type"</label>
+                                                            <select prop:value=move || ic_kind.get() on:change=move |ev| set_ic_kind.set(event_target_value(&ev))>
+                                                                <option value="freight">"Freight"</option>
+                                                                <option value="fuel">"Fuel surcharge"</option>
+                                                                <option value="fee">"Fee"</option>
+                                                                <option value="tax">"Tax"</option>
+                                                            </select>
+                                                        </div>
                                                         <div class="input">
-                                                            <label>"Source vendor ID"</label>
-                                                            <input
-                                                                type="text"
-                                                                prop:value=vendor_merge_source_id
-                                                                on:input=move |ev| {
-                                                                    set_vendor_merge_source_id.set(event_target_value(&ev));
-                                                                }
-                                                            />
+                                                            <label>"Description"</label>
+                                                            <input prop:value=move || ic_desc.get() on:input=move |ev| set_ic_desc.set(event_target_value(&ev)) />
                                                         </div>
                                                         <div class="input">
-                                                            <label>"Target vendor"</label>
-                                                            <select
-                                                                prop:value=vendor_merge_target_id
-                                                                on:change=move |ev| {
-                                                                    set_vendor_merge_target_id.set(event_target_value(&ev));
-                                                                }
-                                                            >
-                                                                <option value="">"Select target vendor"</option>
-                                                                <For
-                                                                    each=move || vendor_merge_options.get()
-                                                                    key=|v| v.vendor_id
-                                                                    children=move |v| view! {
-                                                                        <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
-                                                                    }
-                                                                />
+                                                            <label>"Amount"</label>
+                                                            <input type="number" step="any" prop:value=move || ic_amount.get() on:input=move |ev| set_ic_amount.set(event_target_value(&ev)) />
+                                                        </div>
+                                                        <div class="input" style="flex: 0 0 auto;">
+                                                            <label>"Allocate By"</label>
+                                                            <select prop:value=move || ic_alloc.get() on:change=move |ev| set_ic_alloc.set(event_target_value(&ev))>
+                                                                <option value="default">"Default"</option>
+                                                                <option value="value">"Line value"</option>
+                                                                <option value="weight">"Weight"</option>
+                                                                <option value="qty">"Quantity"</option>
+                                                                <option value="none">"Don't allocate"</option>
                                                             </select>
                                                         </div>
                                                         <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                                            <button class="button secondary" on:click=move |_| merge_vendor_ids()>
-                                                                "Merge vendors"
-                                                            </button>
+                                                            <button
+                                                                class="button"
+                                                                on:click={
+                                                                    let invoice_id = detail.invoice.invoice_id;
+                                                                    move |_| add_charge(invoice_id)
+                                                                }
+                                                            >"Add Charge"</button>
                                                         </div>
                                                     </div>
-                                                    <div class="status">{move || vendor_merge_status.get()}</div>
+                                                    <div class="status">{move || ic_status.get()}</div>
                                                 </div>
                                                 <div class="detail-block">
-                                                    <strong>"Priced Items"</strong>
+                                                    <strong>"Credit Memos"</strong>
+                                                    <div class="status">
+                                                        "Vendor credits for short shipments, returns and price corrections. The invoice total less credits should equal the lines and charges received."
+                                                    </div>
                                                     <div class="data-table">
-                                                        <div class="data-header data-cols-4">
-                                                            <span>"Item"</span>
-                                                            <span>"Item ID"</span>
-                                                            <span>"Price"</span>
-                                                            <span>"Pack"</span>
+                                                        <div class="data-header data-cols-6">
+                                                            <span>"Memo"</span>
+                                                            <span>"Date"</span>
+                                                            <span>"Reason"</span>
+                                                            <span>"Line"</span>
+                                                            <span>"Amount"</span>
+                                                            <span></span>
+                                                        </div>
+                                                        {detail.credits.iter().map(|c| {
+                                                            let credit_id = c.credit_id;
+                                                            let invoice_id = detail.invoice.invoice_id;
+                                                            let line = match (c.trans_id, c.qty) {
+                                                                (Some(_), Some(q)) => format!("{} {} {}", c.item_name, q, c.unit_name),
+                                                                (Some(_), None) => c.item_name.clone(),
+                                                                _ => "-".to_string(),
+                                                            };
+                                                            view! {
+                                                                <div class="data-row data-cols-6" title=c.note.clone()>
+                                                                    <span>{if c.memo_no.is_empty() { "-".to_string() } else { c.memo_no.clone() }}</span>
+                                                                    <span>{c.memo_date.clone()}</span>
+                                                                    <span>{c.reason.clone()}</span>
+                                                                    <span>{line}</span>
+                                                                    <span>{format_money(c.amount)}</span>
+                                                                    <span class="action-cell">
+                                                                        <button
+                                                                            class="button tiny secondary"
+                                                                            on:click=move |_| run_credit_action(
+                                                                                "delete_credit_memo",
+                                                                                to_value(&CreditIdArgs { credit_id }).unwrap(),
+                                                                                invoice_id,
+                                                                            )
+                                                                        >"Delete"</button>
+                                                                    </span>
+                                                                </div>
+                                                            }
+                                                        }).collect::<Vec<_>>()}
+                                                    </div>
+                                                    <div class="row" style="margin-top: 10px;">
+                                                        <div class="input" style="flex: 0 0 auto;">
+                                                            <label>"Reason"</label>
+                                                            <select prop:value=move || cm_reason.get() on:change=move |ev| set_cm_reason.set(event_target_value(&ev))>
+                                                                <option value="short">"Short shipment"</option>
+                                                                <option value="return">"Return"</option>
+                                                                <option value="damaged">"Damaged"</option>
+                                                                <option value="price">"Price correction"</option>
+                                                                <option value="other">"Other"</option>
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Line"</label>
+                                                            <select prop:value=move || cm_line.get() on:change=move |ev| set_cm_line.set(event_target_value(&ev))>
+                                                                <option value="">"Whole invoice"</option>
+                                                                {credit_lines.into_iter().map(|(id, label)| view! {
+                                                                    <option value=id.to_string()>{label}</option>
+                                                                }).collect::<Vec<_>>()}
+                                                            </select>
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Qty"</label>
+                                                            <input type="number" step="any" prop:value=move || cm_qty.get() on:input=move |ev| set_cm_qty.set(event_target_value(&ev)) />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Amount"</label>
+                                                            <input
+                                                                type="number"
+                                                                step="any"
+                                                                placeholder="Qty \u{00d7} price"
+                                                                prop:value=move || cm_amount.get()
+                                                                on:input=move |ev| set_cm_amount.set(event_target_value(&ev))
+                                                            />
                                                         </div>
+                                                        <div class="input">
+                                                            <label>"Memo #"</label>
+                                                            <input prop:value=move || cm_no.get() on:input=move |ev| set_cm_no.set(event_target_value(&ev)) />
+                                                        </div>
+                                                        <div class="input">
+                                                            <label>"Note"</label>
+                                                            <input prop:value=move || cm_note.get() on:input=move |ev| set_cm_note.set(event_target_value(&ev)) />
+                                                        </div>
+                                                        <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                            <button
+                                                                class="button"
+                                                                on:click={
+                                                                    let invoice_id = detail.invoice.invoice_id;
+                                                                    move |_| add_credit(invoice_id)
+                                                                }
+                                                            >"Add Credit"</button>
+                                                        </div>
+                                                    </div>
+                                                    <div class="status">{move || cm_status.get()}</div>
+                                                </div>
+                                            </div>
+                                        }
+                                    })
+                                })}
+                            </Show>
+                            <Show when=move || invoice_detail_loading.get()>
+                                <div class="status">"Loading invoice..."</div>
+                            </Show>
+                        </div>
+                    </div>
+                    <div class="panel">
+                        <strong>"Order Guide"</strong>
+                        <div class="status">
+                            "Par for the order day minus on hand, converted to each vendor's pack and rounded up to whole packs. Set pars on each item's Inventory page."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Order Date"</label>
+                                <input type="date" prop:value=move || og_date.get() on:input=move |ev| set_og_date.set(event_target_value(&ev)) />
+                            </div>
+                            <label style="display: inline-flex; gap: 8px; align-items: center; align-self: end;">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || og_theoretical.get()
+                                    on:change=move |ev| set_og_theoretical.set(event_target_checked(&ev))
+                                />
+                                <span>"Theoretical on hand (count + purchases \u{2212} sales usage)"</span>
+                            </label>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_order_guide()>"Build"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_order_guide(None, true)>"All PDF"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_order_guide(None, false)>"All CSV"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| create_pos_from_guide()>"Create POs"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || og_status.get()}</div>
+                        {move || og_guide.get().map(|guide| {
+                            let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
+                            let unresolved = guide.unresolved.clone();
+                            let empty = guide.vendors.is_empty();
+                            view! {
+                                <div class="status">{format!("Ordering for {} ({})", guide.order_date, guide.day_name)}</div>
+                                <Show when=move || empty>
+                                    <div class="status">"Everything is at or above par."</div>
+                                </Show>
+                                {guide.vendors.into_iter().map(|vendor| {
+                                    let vendor_id = vendor.vendor_id;
+                                    view! {
+                                        <div class="detail-block">
+                                            <div class="row" style="align-items: center;">
+                                                <strong>{format!("{} \u{2014} {}", vendor.vendor_name, format_money(vendor.total_cost))}</strong>
+                                                <button class="button tiny secondary" style="margin-left: auto;" on:click=move |_| export_order_guide(vendor_id, true)>"PDF"</button>
+                                                <button class="button tiny secondary" on:click=move |_| export_order_guide(vendor_id, false)>"CSV"</button>
+                                            </div>
+                                            <div class="data-table">
+                                                <div class="data-header data-cols-7">
+                                                    <span>"Item"</span>
+                                                    <span>"Par"</span>
+                                                    <span>"On Hand"</span>
+                                                    <span>"Need"</span>
+                                                    <span>"Order"</span>
+                                                    <span>"Price"</span>
+                                                    <span>"Est."</span>
+                                                </div>
+                                                {vendor.lines.into_iter().map(|l| view! {
+                                                    <div class="data-row data-cols-7" title={l.on_hand_basis.clone()}>
+                                                        <span>{format!("{} ({})", l.item_name, l.purch_unit_name)}</span>
+                                                        <span>{format!("{:.2}", l.par_qty)}</span>
+                                                        <span>{l.on_hand.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                        <span>{format!("{:.2}", l.need_qty)}</span>
+                                                        <span>{format!("{} {}", l.order_qty, l.order_unit_name)}</span>
+                                                        <span>{money(l.price)}</span>
+                                                        <span>{money(l.est_cost)}</span>
+                                                    </div>
+                                                }).collect::<Vec<_>>()}
+                                            </div>
+                                        </div>
+                                    }
+                                }).collect::<Vec<_>>()}
+                                <Show when=move || !unresolved.is_empty()>
+                                    <div class="status">
+                                        {format!("Not included: {}", guide.unresolved.join(", "))}
+                                    </div>
+                                </Show>
+                            }
+                        })}
+                    </div>
+                    <div class="panel">
+                        <strong>"Purchase Orders"</strong>
+                        <div class="status">
+                            "Draft, send and receive orders. Receiving creates the invoice and flags anything that differs from the PO."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Show"</label>
+                                <select
+                                    prop:value=move || po_status_filter.get()
+                                    on:change=move |ev| {
+                                        set_po_status_filter.set(event_target_value(&ev));
+                                        load_po_list();
+                                    }
+                                >
+                                    <option value="">"All"</option>
+                                    <option value="draft">"Draft"</option>
+                                    <option value="sent">"Sent"</option>
+                                    <option value="partial">"Partially received"</option>
+                                    <option value="closed">"Closed"</option>
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Vendor"</label>
+                                <select
+                                    prop:value=move || po_new_vendor.get()
+                                    on:change=move |ev| set_po_new_vendor.set(event_target_value(&ev))
+                                >
+                                    <option value="">"Choose vendor"</option>
+                                    <For
+                                        each=move || invoice_vendor_options.get()
+                                        key=|v| v.vendor_id
+                                        children=move |v| view! {
+                                            <option value={v.vendor_id.to_string()}>{v.name}</option>
+                                        }
+                                    />
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Expected"</label>
+                                <input type="date" prop:value=move || po_new_expected.get() on:input=move |ev| set_po_new_expected.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"Notes"</label>
+                                <input prop:value=move || po_new_notes.get() on:input=move |ev| set_po_new_notes.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| create_po()>"New PO"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || po_status.get()}</div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-6">
+                                <span>"PO"</span>
+                                <span>"Vendor"</span>
+                                <span>"Ordered / Expected"</span>
+                                <span>"Status"</span>
+                                <span>"Lines"</span>
+                                <span>"Total"</span>
+                            </div>
+                            <For
+                                each=move || po_list.get()
+                                key=|o| (o.po_id, o.status.clone(), o.line_count, o.total.to_bits())
+                                children=move |o| {
+                                    let po_id = o.po_id;
+                                    view! {
+                                        <div class="data-row data-cols-6" on:click=move |_| open_po(po_id)>
+                                            <span>{o.po_number}</span>
+                                            <span>{o.vendor_name}</span>
+                                            <span>{if o.expected_date.is_empty() { o.order_date } else { format!("{} / {}", o.order_date, o.expected_date) }}</span>
+                                            <span>{o.status}</span>
+                                            <span>{o.line_count}</span>
+                                            <span>{format_money(o.total)}</span>
+                                        </div>
+                                    }
+                                }
+                            />
+                        </div>
+                        {move || po_detail.get().map(|detail| {
+                            let order = detail.order;
+                            let po_id = order.po_id;
+                            let status = order.status.clone();
+                            let draft = status == "draft";
+                            let sent = status == "sent";
+                            let receivable = sent || status == "partial";
+                            let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
+                            let mut subtitle = format!("{} \u{2014} ordered {}", order.vendor_name, order.order_date);
+                            if !order.expected_date.is_empty() {
+                                subtitle.push_str(&format!(", expected {}", order.expected_date));
+                            }
+                            if !order.notes.is_empty() {
+                                subtitle.push_str(&format!(" \u{2014} {}", order.notes));
+                            }
+                            let set_status_to = move |status: &str| {
+                                run_po_action(
+                                    "set_purchase_order_status",
+                                    to_value(&SetPoStatusArgs { po_id, status: status.to_string() }).unwrap(),
+                                    Some(po_id),
+                                );
+                            };
+
+                            let line_rows = detail.lines.iter().map(|l| {
+                                let po_line_id = l.po_line_id;
+                                let item_id = l.item_id;
+                                let unit_id = l.unit_id;
+                                let (qty, set_qty) = signal(l.qty.to_string());
+                                let (price, set_price) = signal(l.price.map(|p| p.to_string()).unwrap_or_default());
+                                view! {
+                                    <div class="data-row data-cols-po-lines">
+                                        <span>{l.item_name.clone()}</span>
+                                        <span>
+                                            <input
+                                                class="inline-input"
+                                                type="number"
+                                                step="any"
+                                                min="0"
+                                                disabled=!draft
+                                                prop:value=move || qty.get()
+                                                on:input=move |ev| set_qty.set(event_target_value(&ev))
+                                            />
+                                        </span>
+                                        <span>{l.unit_name.clone()}</span>
+                                        <span>
+                                            <input
+                                                class="inline-input"
+                                                type="number"
+                                                step="any"
+                                                min="0"
+                                                disabled=!draft
+                                                prop:value=move || price.get()
+                                                on:input=move |ev| set_price.set(event_target_value(&ev))
+                                            />
+                                        </span>
+                                        <span>{money(l.ext_cost)}</span>
+                                        <span>{format!("{:.2}", l.received_qty)}</span>
+                                        <span class="action-cell">
+                                            <Show when=move || draft>
+                                                <button class="button tiny" on:click=move |_| {
+                                                    let Ok(qty) = qty.get_untracked().trim().parse::<f64>() else {
+                                                        set_po_status.set("Enter a quantity".to_string());
+                                                        return;
+                                                    };
+                                                    let args = to_value(&SavePoLineArgs {
+                                                        po_id,
+                                                        po_line_id: Some(po_line_id),
+                                                        item_id,
+                                                        unit_id,
+                                                        qty,
+                                                        price: price.get_untracked().trim().parse::<f64>().ok(),
+                                                    })
+                                                    .unwrap();
+                                                    run_po_action("save_po_line", args, Some(po_id));
+                                                }>"Save"</button>
+                                                <button class="button tiny secondary" on:click=move |_| {
+                                                    run_po_action("delete_po_line", to_value(&PoLineIdArgs { po_line_id }).unwrap(), Some(po_id));
+                                                }>"Delete"</button>
+                                            </Show>
+                                        </span>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>();
+
+                            // One editable receiving row per PO line, defaulting to what is still outstanding.
+                            let mut recv_inputs = Vec::new();
+                            let recv_rows = detail.lines.iter().map(|l| {
+                                let remaining = (l.qty - l.received_qty).max(0.0);
+                                let (item, set_item) = signal(l.item_id.to_string());
+                                let (qty, set_qty) = signal(if remaining > 0.0 { format!("{}", (remaining * 1000.0).round() / 1000.0) } else { String::new() });
+                                let (unit, set_unit) = signal(l.unit_id.map(|u| u.to_string()).unwrap_or_default());
+                                let (price, set_price) = signal(String::new());
+                                recv_inputs.push((Some(l.po_line_id), item, qty, unit, price));
+                                view! {
+                                    <div class="data-row data-cols-po-receive">
+                                        <span>{format!("{} \u{2014} {:.2} of {} {} due", l.item_name, remaining, l.qty, l.unit_name)}</span>
+                                        <span>
+                                            <select prop:value=move || item.get() on:change=move |ev| set_item.set(event_target_value(&ev))>
+                                                <For
+                                                    each=move || po_item_options.get()
+                                                    key=|i| i.item_id
+                                                    children=move |i| view! {
+                                                        <option value={i.item_id.to_string()}>{i.name}</option>
+                                                    }
+                                                />
+                                            </select>
+                                        </span>
+                                        <span>
+                                            <input
+                                                class="inline-input"
+                                                type="number"
+                                                step="any"
+                                                min="0"
+                                                prop:value=move || qty.get()
+                                                on:input=move |ev| set_qty.set(event_target_value(&ev))
+                                            />
+                                        </span>
+                                        <span>
+                                            <select prop:value=move || unit.get() on:change=move |ev| set_unit.set(event_target_value(&ev))>
+                                                <For
+                                                    each=move || po_unit_options.get()
+                                                    key=|u| u.unit_id
+                                                    children=move |u| view! {
+                                                        <option value={u.unit_id.to_string()}>{u.sing}</option>
+                                                    }
+                                                />
+                                            </select>
+                                        </span>
+                                        <span>
+                                            <input
+                                                class="inline-input"
+                                                type="number"
+                                                step="any"
+                                                min="0"
+                                                placeholder={l.price.map(|p| format!("{:.2}", p)).unwrap_or_default()}
+                                                prop:value=move || price.get()
+                                                on:input=move |ev| set_price.set(event_target_value(&ev))
+                                            />
+                                        </span>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>();
+                            // An extra row for an item that arrived without being ordered.
+                            let (extra_item, set_extra_item) = signal(String::new());
+                            let (extra_qty, set_extra_qty) = signal(String::new());
+                            let (extra_unit, set_extra_unit) = signal(String::new());
+                            let (extra_price, set_extra_price) = signal(String::new());
+                            recv_inputs.push((None, extra_item, extra_qty, extra_unit, extra_price));
+                            let receive = move |_| {
+                                let lines: Vec<ReceivePoLine> = recv_inputs
+                                    .iter()
+                                    .filter_map(|(po_line_id, item, qty, unit, price)| {
+                                        Some(ReceivePoLine {
+                                            po_line_id: *po_line_id,
+                                            item_id: item.get_untracked().parse::<i64>().ok()?,
+                                            unit_id: unit.get_untracked().parse::<i64>().ok(),
+                                            qty: qty.get_untracked().trim().parse::<f64>().ok()?,
+                                            price: price.get_untracked().trim().parse::<f64>().ok(),
+                                        })
+                                    })
+                                    .collect();
+                                let date = po_recv_date.get_untracked();
+                                let args = to_value(&ReceivePurchaseOrderArgs {
+                                    po_id,
+                                    invoice_no: po_recv_invoice_no.get_untracked(),
+                                    invoice_date: if date.is_empty() { None } else { Some(date) },
+                                    lines,
+                                })
+                                .unwrap();
+                                run_po_action("receive_purchase_order", args, Some(po_id));
+                            };
+
+                            let receipts = detail.receipts.into_iter().map(|r| view! {
+                                <div class="detail-block">
+                                    <strong>{format!("Invoice {} \u{2014} received {}", if r.invoice_no.is_empty() { r.invoice_id.to_string() } else { r.invoice_no.clone() }, r.received_date)}</strong>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-5">
+                                            <span>"Item"</span>
+                                            <span>"Received"</span>
+                                            <span>"Price"</span>
+                                            <span>"Note"</span>
+                                            <span>"Flag"</span>
+                                        </div>
+                                        {r.lines.into_iter().map(|l| {
+                                            let flag_class = if l.flag == "OK" { "diff-zero" } else { "diff-up" };
+                                            view! {
+                                                <div class="data-row data-cols-5">
+                                                    <span>{l.item_name}</span>
+                                                    <span>{format!("{} {}", l.qty, l.unit_name)}</span>
+                                                    <span>{money(l.price)}</span>
+                                                    <span>{l.note}</span>
+                                                    <span class=flag_class>{l.flag}</span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </div>
+                            }).collect::<Vec<_>>();
+
+                            view! {
+                                <div class="detail-block">
+                                    <div class="row" style="align-items: center;">
+                                        <strong>{format!("{} \u{2014} {} \u{2014} {}", order.po_number, status, format_money(order.total))}</strong>
+                                        <button class="button tiny secondary" style="margin-left: auto;" on:click=move |_| export_po(po_id, true)>"PDF"</button>
+                                        <button class="button tiny secondary" on:click=move |_| export_po(po_id, false)>"CSV"</button>
+                                        <Show when=move || draft>
+                                            <button class="button tiny" on:click=move |_| set_status_to("sent")>"Mark Sent"</button>
+                                            <button class="button tiny secondary" on:click=move |_| {
+                                                run_po_action("delete_purchase_order", to_value(&PoIdArgs { po_id }).unwrap(), None);
+                                            }>"Delete"</button>
+                                        </Show>
+                                        <Show when=move || sent>
+                                            <button class="button tiny secondary" on:click=move |_| set_status_to("draft")>"Back to Draft"</button>
+                                        </Show>
+                                        <Show when=move || receivable>
+                                            <button class="button tiny secondary" on:click=move |_| set_status_to("closed")>"Close"</button>
+                                        </Show>
+                                    </div>
+                                    <div class="status">{subtitle}</div>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-po-lines">
+                                            <span>"Item"</span>
+                                            <span>"Qty"</span>
+                                            <span>"Unit"</span>
+                                            <span>"Price"</span>
+                                            <span>"Ext."</span>
+                                            <span>"Received"</span>
+                                            <span></span>
+                                        </div>
+                                        {line_rows}
+                                    </div>
+                                    <Show when=move || draft>
+                                        <div class="row" style="margin-top: 10px;">
+                                            <div class="input">
+                                                <label>"Item"</label>
+                                                <select prop:value=move || po_line_item.get() on:change=move |ev| set_po_line_item.set(event_target_value(&ev))>
+                                                    <option value="">"Choose item"</option>
+                                                    <For
+                                                        each=move || po_item_options.get()
+                                                        key=|i| i.item_id
+                                                        children=move |i| view! {
+                                                            <option value={i.item_id.to_string()}>{i.name}</option>
+                                                        }
+                                                    />
+                                                </select>
+                                            </div>
+                                            <div class="input">
+                                                <label>"Unit"</label>
+                                                <select prop:value=move || po_line_unit.get() on:change=move |ev| set_po_line_unit.set(event_target_value(&ev))>
+                                                    <option value="">"Purchase unit"</option>
+                                                    <For
+                                                        each=move || po_unit_options.get()
+                                                        key=|u| u.unit_id
+                                                        children=move |u| view! {
+                                                            <option value={u.unit_id.to_string()}>{u.sing}</option>
+                                                        }
+                                                    />
+                                                </select>
+                                            </div>
+                                            <div class="input">
+                                                <label>"Qty"</label>
+                                                <input type="number" step="any" min="0" prop:value=move || po_line_qty.get() on:input=move |ev| set_po_line_qty.set(event_target_value(&ev)) />
+                                            </div>
+                                            <div class="input">
+                                                <label>"Price"</label>
+                                                <input type="number" step="any" min="0" placeholder="Last price" prop:value=move || po_line_price.get() on:input=move |ev| set_po_line_price.set(event_target_value(&ev)) />
+                                            </div>
+                                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                <button class="button" on:click=move |_| add_po_line(po_id)>"Add Line"</button>
+                                            </div>
+                                        </div>
+                                    </Show>
+                                </div>
+                                {receivable.then(|| view! {
+                                    <div class="detail-block">
+                                        <strong>"Receive Shipment"</strong>
+                                        <div class="status">"Change the item for a substitution; leave price blank to use the PO price."</div>
+                                        <div class="row" style="margin-top: 10px;">
+                                            <div class="input">
+                                                <label>"Invoice #"</label>
+                                                <input prop:value=move || po_recv_invoice_no.get() on:input=move |ev| set_po_recv_invoice_no.set(event_target_value(&ev)) />
+                                            </div>
+                                            <div class="input">
+                                                <label>"Invoice Date"</label>
+                                                <input type="date" prop:value=move || po_recv_date.get() on:input=move |ev| set_po_recv_date.set(event_target_value(&ev)) />
+                                            </div>
+                                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                                <button class="button" on:click=receive>"Receive"</button>
+                                            </div>
+                                        </div>
+                                        <div class="data-table">
+                                            <div class="data-header data-cols-po-receive">
+                                                <span>"Ordered"</span>
+                                                <span>"Received Item"</span>
+                                                <span>"Qty"</span>
+                                                <span>"Unit"</span>
+                                                <span>"Price"</span>
+                                            </div>
+                                            {recv_rows}
+                                            <div class="data-row data-cols-po-receive">
+                                                <span>"Not on PO"</span>
+                                                <span>
+                                                    <select prop:value=move || extra_item.get() on:change=move |ev| set_extra_item.set(event_target_value(&ev))>
+                                                        <option value="">"None"</option>
                                                         <For
-                                                            each=move || detail.price_items.clone()
-                                                            key=|item| item.item_id
-                                                            children=move |item| view! {
-                                                                <div class="data-row data-cols-4">
-                                                                    <span>{item.item_name}</span>
-                                                                    <span>{item.item_id}</span>
-                                                                    <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{item.pack}</span>
-                                                                </div>
+                                                            each=move || po_item_options.get()
+                                                            key=|i| i.item_id
+                                                            children=move |i| view! {
+                                                                <option value={i.item_id.to_string()}>{i.name}</option>
                                                             }
                                                         />
-                                                    </div>
-                                                </div>
+                                                    </select>
+                                                </span>
+                                                <span>
+                                                    <input class="inline-input" type="number" step="any" min="0" prop:value=move || extra_qty.get() on:input=move |ev| set_extra_qty.set(event_target_value(&ev)) />
+                                                </span>
+                                                <span>
+                                                    <select prop:value=move || extra_unit.get() on:change=move |ev| set_extra_unit.set(event_target_value(&ev))>
+                                                        <option value="">"Purchase unit"</option>
+                                                        <For
+                                                            each=move || po_unit_options.get()
+                                                            key=|u| u.unit_id
+                                                            children=move |u| view! {
+                                                                <option value={u.unit_id.to_string()}>{u.sing}</option>
+                                                            }
+                                                        />
+                                                    </select>
+                                                </span>
+                                                <span>
+                                                    <input class="inline-input" type="number" step="any" min="0" prop:value=move || extra_price.get() on:input=move |ev| set_extra_price.set(event_target_value(&ev)) />
+                                                </span>
                                             </div>
-                                        }
-                                    })
+                                        </div>
+                                    </div>
                                 })}
-                            </Show>
-                            <Show when=move || vendor_detail_loading.get()>
-                                <div class="status">"Loading vendor details..."</div>
-                            </Show>
-                        </div>
+                                {receipts}
+                            }
+                        })}
                     </div>
                 </Show>
 
-                <Show when=move || active_panel.get() == "conversions">
+                <Show when=move || active_panel.get() == "reports">
                     <div class="panel">
-                        <div class="row">
+                        <div class="row" style="align-items: end;">
                             <div>
-                                <strong>"Conversion Overview"</strong>
+                                <strong>"Conversion Consistency"</strong>
                                 <div class="status">
-                                    {move || format!(
-                                        "Suggestions: {} | Safe: {} | Todo: {} | Local conversions: {}",
-                                        conversion_overview.get().suggestions,
-                                        conversion_overview.get().suggestions_safe,
-                                        conversion_overview.get().todo,
-                                        conversion_overview.get().missing_edges
-                                    )}
+                                    {move || match conv_check.get() {
+                                        Some(check) => format!(
+                                            "{} issues in {} conversions for {} items (tolerance {}%)",
+                                            check.issues.len(), check.rows_checked, check.items_checked, check.tolerance_pct
+                                        ),
+                                        None => "Finds contradictory, broken and duplicate conversions".to_string(),
+                                    }}
                                 </div>
                             </div>
+                            <div class="input" style="max-width: 140px; margin-left: auto;">
+                                <label>"Tolerance %"</label>
+                                <input
+                                    type="number"
+                                    step="any"
+                                    min="0"
+                                    prop:value=move || conv_check_tolerance.get()
+                                    on:input=move |ev| set_conv_check_tolerance.set(event_target_value(&ev))
+                                />
+                            </div>
+                            <div class="input" style="flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| run_conversion_check()>"Check"</button>
+                            </div>
                         </div>
-                        <div class="row" style="margin-top: 12px;">
-                            <button
-                                class="button secondary"
-                                class:active=move || conversion_tab.get() == "suggestions"
-                                on:click=move |_| {
-                                    set_conversion_tab.set("suggestions".to_string());
-                                    set_conversion_page.set(0);
-                                    trigger_conversion_suggestions_fetch(
-                                        "conv_suggestions".to_string(),
-                                        0,
-                                        conversion_limit,
-                                        set_conversion_loading,
-                                        set_conversion_status,
-                                        set_conversion_suggestions,
-                                        set_conversion_suggestions_total,
-                                    );
+                        <div class="status">{move || conv_check_status.get()}</div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-6">
+                                <span>"Item"</span>
+                                <span>"Vendor"</span>
+                                <span>"Conversion"</span>
+                                <span>"Problem"</span>
+                                <span>"Recipes"</span>
+                                <span>"Fix"</span>
+                            </div>
+                            <For
+                                each=move || conv_check.get().map(|c| c.issues).unwrap_or_default()
+                                key=|issue| (issue.row_id, issue.kind.clone())
+                                children=move |issue| {
+                                    let row_id = issue.row_id;
+                                    let qty = |q: Option<f64>| q.map(|q| format!("{}", q)).unwrap_or_else(|| "?".to_string());
+                                    let align_label = issue
+                                        .suggested_qty2
+                                        .map(|q| format!("Align ({:.4})", q))
+                                        .unwrap_or_else(|| "Align".to_string());
+                                    view! {
+                                        <div class="data-row data-cols-6">
+                                            <span>{format!("{} ({})", issue.item_name, issue.item_id)}</span>
+                                            <span>{issue.vendor_name.clone()}</span>
+                                            <span>{format!("{} {} = {} {}", qty(issue.qty1), issue.unit1_name, qty(issue.qty2), issue.unit2_name)}</span>
+                                            <span>{format!("{}: {}", issue.kind.replace('_', " "), issue.detail)}</span>
+                                            <span>{issue.recipe_count}</span>
+                                            <span class="action-cell">
+                                                {issue.fixes.iter().map(|fix| {
+                                                    let fix = fix.clone();
+                                                    let label = match fix.as_str() {
+                                                        "align" => align_label.clone(),
+                                                        "swap" => "Swap".to_string(),
+                                                        _ => "Delete".to_string(),
+                                                    };
+                                                    view! {
+                                                        <button
+                                                            class="button tiny secondary"
+                                                            on:click=move |_| fix_conversion_issue(row_id, fix.clone())
+                                                        >{label}</button>
+                                                    }
+                                                }).collect_view()}
+                                            </span>
+                                        </div>
+                                    }
                                 }
-                            >
-                                "Suggestions"
-                            </button>
-                            <button
-                                class="button secondary"
-                                class:active=move || conversion_tab.get() == "safe"
-                                on:click=move |_| {
-                                    set_conversion_tab.set("safe".to_string());
-                                    set_conversion_page.set(0);
-                                    trigger_conversion_suggestions_fetch(
-                                        "conv_suggestions_safe".to_string(),
-                                        0,
-                                        conversion_limit,
-                                        set_conversion_loading,
-                                        set_conversion_status,
-                                        set_conversion_suggestions_safe,
-                                        set_conversion_suggestions_safe_total,
-                                    );
+                            />
+                        </div>
+                    </div>
+
+                    <div class="panel">
+                        <div class="row" style="align-items: end;">
+                            <div>
+                                <strong>"Pack Sizes"</strong>
+                                <div class="status">
+                                    {move || match pack_report.get() {
+                                        Some(report) => format!(
+                                            "{} vendor packs read into case, each and weight or volume conversions; {} couldn't be read",
+                                            report.read, report.unreadable
+                                        ),
+                                        None => "Vendor pack descriptions such as 4/5 LB or 6/#10 CN".to_string(),
+                                    }}
+                                </div>
+                            </div>
+                            <div class="input" style="flex: 0 0 auto; margin-left: auto;">
+                                <button
+                                    class="button secondary"
+                                    on:click=move |_| pack_action("reread_packs", to_value(&PingArgs {}).unwrap())
+                                >"Re-read all"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || pack_status.get()}</div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-5">
+                                <span>"Item"</span>
+                                <span>"Vendor"</span>
+                                <span>"Pack"</span>
+                                <span>"Problem"</span>
+                                <span>"Action"</span>
+                            </div>
+                            <For
+                                each=move || pack_report.get().map(|r| r.issues).unwrap_or_default()
+                                key=|issue| (issue.row_id, issue.pack.clone())
+                                children=move |issue| {
+                                    let row_id = issue.row_id;
+                                    let (edit_pack, set_edit_pack) = signal(issue.pack.clone());
+                                    view! {
+                                        <div class="data-row data-cols-5">
+                                            <span>{format!("{} ({})", issue.item_name, issue.item_id)}</span>
+                                            <span>{issue.vendor_name.clone()}</span>
+                                            <span>
+                                                <input
+                                                    class="inline-input"
+                                                    type="text"
+                                                    prop:value=move || edit_pack.get()
+                                                    on:input=move |ev| set_edit_pack.set(event_target_value(&ev))
+                                                />
+                                            </span>
+                                            <span>{issue.error.clone()}</span>
+                                            <span class="action-cell">
+                                                <button
+                                                    class="button tiny"
+                                                    on:click=move |_| pack_action(
+                                                        "update_pack",
+                                                        to_value(&UpdatePackArgs { row_id, pack: edit_pack.get() }).unwrap(),
+                                                    )
+                                                >"Save"</button>
+                                            </span>
+                                        </div>
+                                    }
                                 }
-                            >
-                                "Safe"
-                            </button>
-                            <button
-                                class="button secondary"
-                                class:active=move || conversion_tab.get() == "todo"
-                                on:click=move |_| {
-                                    set_conversion_tab.set("todo".to_string());
-                                    set_conversion_page.set(0);
-                                    trigger_conversion_todo_fetch(
-                                        0,
-                                        conversion_limit,
-                                        set_conversion_loading,
-                                        set_conversion_status,
-                                        set_conversion_todo,
-                                        set_conversion_todo_total,
-                                    );
+                            />
+                        </div>
+                    </div>
+
+                    <div class="panel">
+                        <div class="row">
+                            <div>
+                                <strong>"Nutrition Data"</strong>
+                                <div class="status">
+                                    {move || match nutrition_coverage.get() {
+                                        Some(c) => format!(
+                                            "{} USDA foods imported; {} of {} recipe ingredients have nutrition",
+                                            c.foods, c.with_nutrition, c.recipe_items
+                                        ),
+                                        None => "Nutrient profiles from USDA FoodData Central downloads".to_string(),
+                                    }}
+                                </div>
+                            </div>
+                            <div class="input" style="flex: 0 0 auto; margin-left: auto;">
+                                <label>"Min match %"</label>
+                                <input
+                                    type="number"
+                                    step="any"
+                                    prop:value=move || nutrition_min_match.get()
+                                    on:input=move |ev| set_nutrition_min_match.set(event_target_value(&ev))
+                                />
+                            </div>
+                            <div class="input" style="flex: 0 0 auto; align-self: end;">
+                                <button class="button secondary" on:click=move |_| link_nutrition_matches()>"Link matches"</button>
+                            </div>
+                            <div class="input" style="flex: 0 0 auto; align-self: end;">
+                                <button class="button" on:click=move |_| import_fdc_foods()>"Import USDA data"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || nutrition_status.get()}</div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-5">
+                                <span>"Ingredient"</span>
+                                <span>"Closest USDA food"</span>
+                                <span>"Match"</span>
+                                <span>"Category"</span>
+                                <span>"Action"</span>
+                            </div>
+                            <For
+                                each=move || nutrition_coverage.get().map(|c| c.unmatched).unwrap_or_default()
+                                key=|m| (m.item_id, m.best.as_ref().map(|b| b.fdc_id))
+                                children=move |m| {
+                                    let item_id = m.item_id;
+                                    let fdc_id = m.best.as_ref().map(|b| b.fdc_id);
+                                    view! {
+                                        <div class="data-row data-cols-5">
+                                            <span>{format!("{} ({})", m.item_name, m.item_id)}</span>
+                                            <span>
+                                                {m.best
+                                                    .as_ref()
+                                                    .map(|b| format!("{} ({})", b.description, b.data_type))
+                                                    .unwrap_or_else(|| "No match".to_string())}
+                                            </span>
+                                            <span>{m.best.as_ref().map(|b| format!("{:.0}%", b.score * 100.0)).unwrap_or_else(|| "-".to_string())}</span>
+                                            <span>{m.category.clone()}</span>
+                                            <span class="action-cell">
+                                                <Show when=move || fdc_id.is_some()>
+                                                    <button
+                                                        class="button tiny"
+                                                        on:click=move |_| nutrition_action(
+                                                            "link_item_food",
+                                                            to_value(&LinkItemFoodArgs { item_id, fdc_id: fdc_id.unwrap_or_default() }).unwrap(),
+                                                        )
+                                                    >"Link"</button>
+                                                </Show>
+                                            </span>
+                                        </div>
+                                    }
                                 }
-                            >
-                                "Todo"
-                            </button>
-                            <button
-                                class="button secondary"
-                                class:active=move || conversion_tab.get() == "missing"
-                                on:click=move |_| {
-                                    set_conversion_tab.set("missing".to_string());
-                                    set_conversion_page.set(0);
-                                    trigger_missing_edges_fetch(
-                                        0,
-                                        conversion_limit,
-                                        set_conversion_loading,
-                                        set_conversion_status,
-                                        set_conversion_missing_edges,
-                                        set_conversion_missing_edges_total,
-                                    );
+                            />
+                        </div>
+                        <Show when=move || nutrition_coverage.get().is_some_and(|c| !c.auto_linked.is_empty())>
+                            <div class="status" style="margin-top: 10px;">
+                                "Linked automatically, not reviewed yet. Keep the links that are right; unlink the rest."
+                            </div>
+                            <div class="data-table">
+                                <div class="data-header data-cols-4">
+                                    <span>"Ingredient"</span>
+                                    <span>"Linked USDA food"</span>
+                                    <span>"Category"</span>
+                                    <span>"Action"</span>
+                                </div>
+                                <For
+                                    each=move || nutrition_coverage.get().map(|c| c.auto_linked).unwrap_or_default()
+                                    key=|a| (a.item_id, a.fdc_id)
+                                    children=move |a| {
+                                        let item_id = a.item_id;
+                                        view! {
+                                            <div class="data-row data-cols-4">
+                                                <span>{format!("{} ({})", a.item_name, a.item_id)}</span>
+                                                <span>{format!("{} ({})", a.description, a.fdc_id)}</span>
+                                                <span>{a.category.clone()}</span>
+                                                <span class="action-cell">
+                                                    <button
+                                                        class="button tiny"
+                                                        on:click=move |_| nutrition_action(
+                                                            "confirm_nutrition_link",
+                                                            to_value(&InventoryDetailArgs { item_id }).unwrap(),
+                                                        )
+                                                    >"Keep"</button>
+                                                    <button
+                                                        class="button tiny secondary"
+                                                        on:click=move |_| nutrition_action(
+                                                            "clear_item_nutrition",
+                                                            to_value(&InventoryDetailArgs { item_id }).unwrap(),
+                                                        )
+                                                    >"Unlink"</button>
+                                                </span>
+                                            </div>
+                                        }
+                                    }
+                                />
+                            </div>
+                        </Show>
+                    </div>
+
+                    <div class="panel">
+                        <div class="row">
+                            <div>
+                                <strong>"Missing Data Report"</strong>
+                                <div class="status">
+                                    {move || format!("Rows: {}", missing_data_total.get())}
+                                </div>
+                            </div>
+                        </div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-5">
+                                <span>"Recipe"</span>
+                                <span>"Recipe ID"</span>
+                                <span>"Missing Costs"</span>
+                                <span>"Missing Conversions"</span>
+                                <span>"Missing Units"</span>
+                            </div>
+                            <For
+                                each=move || missing_data_rows.get()
+                                key=|row| row.recipe_id
+                                children=move |row| view! {
+                                    <div class="data-row data-cols-5">
+                                        <span>{row.recipe_name}</span>
+                                        <span>{row.recipe_id}</span>
+                                        <span>{row.missing_a.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                        <span>{row.missing_b.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                        <span>{row.missing_c.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
+                                    </div>
                                 }
-                            >
-                                "Local Conversions"
-                            </button>
+                            />
                         </div>
-                        <div class="status">{move || conversion_status.get()}</div>
                     </div>
 
                     <div class="panel">
-                        <Show
-                            when=move || conversion_tab.get() == "suggestions"
-                            fallback=move || ()
-                        >
-                            <div class="data-table">
-                                <div class="data-header data-cols-7">
-                                    <span>"Item"</span>
-                                    <span>"Units"</span>
-                                    <span>"Qty"</span>
-                                    <span>"Hits"</span>
-                                    <span>"Hops"</span>
-                                    <span>"Path"</span>
-                                    <span>"Action"</span>
+                        <div class="row">
+                            <div>
+                                <strong>"Missing Purchase Units"</strong>
+                                <div class="status">
+                                    {move || format!("Rows: {}", missing_purch_total.get())}
                                 </div>
-                                <For
-                                    each=move || conversion_suggestions.get()
-                                    key=|row| (row.item_id, row.unit_id1, row.unit_id2)
-                                    children=move |row| view! {
-                                        <div class="data-row data-cols-7">
-                                            <span>{format!("{} (v{})", row.item_id, row.vendor_id)}</span>
-                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
-                                            <span>{format!("{:.3} / {:.3}", row.qty1, row.qty2)}</span>
-                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                            <span>{row.hops.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                            <span>{row.path.clone()}</span>
-                                            <button class="button tiny" on:click=move |_| approve_conversion(row.clone())>
-                                                "Approve"
-                                            </button>
-                                        </div>
+                            </div>
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Item ID"</label>
+                                <input
+                                    type="text"
+                                    prop:value=report_purch_item_id
+                                    on:input=move |ev| {
+                                        set_report_purch_item_id.set(event_target_value(&ev));
                                     }
                                 />
                             </div>
-                        </Show>
-                        <Show when=move || conversion_tab.get() == "safe">
-                            <div class="data-table">
-                                <div class="data-header data-cols-7">
-                                    <span>"Item"</span>
-                                    <span>"Units"</span>
-                                    <span>"Qty"</span>
-                                    <span>"Hits"</span>
-                                    <span>"Hops"</span>
-                                    <span>"Path"</span>
-                                    <span>"Action"</span>
-                                </div>
-                                <For
-                                    each=move || conversion_suggestions_safe.get()
-                                    key=|row| (row.item_id, row.unit_id1, row.unit_id2)
-                                    children=move |row| view! {
-                                        <div class="data-row data-cols-7">
-                                            <span>{format!("{} (v{})", row.item_id, row.vendor_id)}</span>
-                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
-                                            <span>{format!("{:.3} / {:.3}", row.qty1, row.qty2)}</span>
-                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                            <span>{row.hops.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                            <span>{row.path.clone()}</span>
-                                            <button class="button tiny" on:click=move |_| approve_conversion(row.clone())>
-                                                "Approve"
-                                            </button>
-                                        </div>
+                            <div class="input">
+                                <label>"Purchase Unit"</label>
+                                <select
+                                    prop:value=purch_unit_selected_id
+                                    on:change=move |ev| {
+                                        set_purch_unit_selected_id.set(event_target_value(&ev));
                                     }
-                                />
+                                >
+                                    <option value="">"Select unit"</option>
+                                    <For
+                                        each=move || unit_options.get()
+                                        key=|unit| unit.unit_id
+                                        children=move |unit| view! {
+                                            <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                        }
+                                    />
+                                </select>
                             </div>
-                        </Show>
-                        <Show when=move || conversion_tab.get() == "todo">
-                            <div class="data-table">
-                                <div class="data-header data-cols-5">
-                                    <span>"Item"</span>
-                                    <span>"Units"</span>
-                                    <span>"Hits"</span>
-                                    <span>"Needed"</span>
-                                    <span>"Vendor"</span>
-                                </div>
-                                <For
-                                    each=move || conversion_todo.get()
-                                    key=|row| (row.item_id, row.recipe_unit_id, row.purch_unit_id)
-                                    children=move |row| view! {
-                                        <div class="data-row data-cols-5">
+                            <div class="input" style="flex: 0 0 150px;">
+                                <label>"Default"</label>
+                                <label style="display: inline-flex; gap: 8px; align-items: center;">
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=purch_unit_set_default
+                                        on:change=move |ev| {
+                                            set_purch_unit_set_default.set(event_target_checked(&ev));
+                                        }
+                                    />
+                                    <span>"Set default"</span>
+                                </label>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| {
+                                    match report_purch_item_id.get().trim().parse::<i64>() {
+                                        Ok(item_id) => assign_purch_unit(item_id),
+                                        Err(_) => set_purch_unit_status.set("Enter a valid item ID".to_string()),
+                                    }
+                                }>
+                                    "Assign unit"
+                                </button>
+                            </div>
+                        </div>
+                        <div class="status">{move || purch_unit_status.get()}</div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-3">
+                                <span>"Item"</span>
+                                <span>"Item ID"</span>
+                                <span>"Usage Count"</span>
+                            </div>
+                            <For
+                                each=move || missing_purch_rows.get()
+                                key=|row| row.item_id
+                                children=move |row| {
+                                    let row_item_id = row.item_id;
+                                    view! {
+                                        <div class="data-row data-cols-3" on:click=move |_| {
+                                            set_report_purch_item_id.set(row_item_id.to_string());
+                                        }>
+                                            <span>{row.item_name}</span>
                                             <span>{row.item_id}</span>
-                                            <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
-                                            <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                            <span>{row.needed}</span>
-                                            <span>{row.vendor_id}</span>
+                                            <span>{row.usage_count.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
                                         </div>
                                     }
+                                }
+                            />
+                        </div>
+                        <div class="status">{move || report_status.get()}</div>
+                    </div>
+                </Show>
+
+                // ── Food Cost Panel ──
+                <Show when=move || active_panel.get() == "foodcost">
+                    <div class="panel">
+                        <div class="row" style="align-items: center; gap: 12px; margin-bottom: 12px;">
+                            <div class="input" style="flex: 1;">
+                                <label>"Dish name (optional)"</label>
+                                <input
+                                    type="text"
+                                    placeholder="e.g. Grilled Salmon Plate"
+                                    prop:value=fc_dish_name
+                                    on:input=move |ev| set_fc_dish_name.set(event_target_value(&ev))
                                 />
                             </div>
-                        </Show>
-                        <Show when=move || conversion_tab.get() == "missing">
-                            <div class="data-table">
-                                <div class="data-header data-cols-8">
-                                    <span>"Item"</span>
-                                    <span>"Item Name"</span>
-                                    <span>"Units"</span>
-                                    <span>"Hits"</span>
-                                    <span>"Qty (recipe)"</span>
-                                    <span>"Qty (purch)"</span>
-                                    <span>"Vendor"</span>
-                                    <span>"Action"</span>
+                            <div class="input" style="max-width: 160px;">
+                                <label>"Target food cost %"</label>
+                                <input
+                                    type="number"
+                                    min="1"
+                                    max="100"
+                                    step="0.5"
+                                    prop:value=move || format!("{}", fc_target_pct.get())
+                                    on:input=move |ev| {
+                                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
+                                            set_fc_target_pct.set(v);
+                                        }
+                                    }
+                                />
+                            </div>
+                        </div>
+
+                        <div class="fc-add-row">
+                            <div class="input" style="flex: 2;">
+                                <label>"Ingredient"</label>
+                                <select
+                                    prop:value=fc_add_item_id
+                                    on:change=move |ev| set_fc_add_item_id.set(event_target_value(&ev))
+                                >
+                                    <option value="">"-- Select item --"</option>
+                                    <For
+                                        each=move || fc_item_options.get()
+                                        key=|item| item.item_id
+                                        children=move |item| {
+                                            let val = item.item_id.to_string();
+                                            view! { <option value={val.clone()}>{format!("{} ({})", item.name, item.item_id)}</option> }
+                                        }
+                                    />
+                                </select>
+                            </div>
+                            <div class="input" style="flex: 1;">
+                                <label>"Unit"</label>
+                                <select
+                                    prop:value=fc_add_unit_id
+                                    on:change=move |ev| set_fc_add_unit_id.set(event_target_value(&ev))
+                                >
+                                    <option value="">"-- Unit --"</option>
+                                    <For
+                                        each=move || fc_unit_options.get()
+                                        key=|u| u.unit_id
+                                        children=move |u| {
+                                            let val = u.unit_id.to_string();
+                                            view! { <option value={val.clone()}>{u.sing.clone()}</option> }
+                                        }
+                                    />
+                                </select>
+                            </div>
+                            <div class="input" style="max-width: 100px;">
+                                <label>"Qty"</label>
+                                <input
+                                    type="number"
+                                    step="0.01"
+                                    min="0"
+                                    placeholder="0.00"
+                                    prop:value=fc_add_qty
+                                    on:input=move |ev| set_fc_add_qty.set(event_target_value(&ev))
+                                />
+                            </div>
+                            <div class="input">
+                                <label style="visibility: hidden;">{"\u{00A0}"}</label>
+                                <button class="button secondary" on:click=move |_| fc_add_line()>
+                                    "+ Add"
+                                </button>
+                            </div>
+                        </div>
+
+                        // ingredient lines table
+                        <Show when=move || !fc_lines.get().is_empty()>
+                            <div class="data-table" style="margin-top: 12px;">
+                                <div class="data-header data-cols-4">
+                                    <span>"Ingredient"</span>
+                                    <span>"Unit"</span>
+                                    <span>"Qty"</span>
+                                    <span></span>
                                 </div>
                                 <For
-                                    each=move || conversion_missing_edges.get()
-                                    key=|row| (row.item_id, row.recipe_unit_id, row.purch_unit_id)
-                                    children=move |row| {
-                                        let (qty1_val, set_qty1_val) = signal(String::new());
-                                        let (qty2_val, set_qty2_val) = signal(String::new());
-                                        let (save_status, set_save_status) = signal(String::new());
-                                        let row_c = row.clone();
-                                        let save_local_conv = move |_| {
-                                            let q1: f64 = match qty1_val.get().parse() {
-                                                Ok(v) if v > 0.0 => v,
-                                                _ => { set_save_status.set("Invalid qty".into()); return; }
-                                            };
-                                            let q2: f64 = match qty2_val.get().parse() {
-                                                Ok(v) if v > 0.0 => v,
-                                                _ => { set_save_status.set("Invalid qty".into()); return; }
-                                            };
-                                            let r = row_c.clone();
-                                            set_save_status.set("Saving...".into());
-                                            spawn_local(async move {
-                                                let args = to_value(&UpsertConvunitArgs {
-                                                    item_id: r.item_id,
-                                                    vendor_id: r.vendor_id,
-                                                    unit_id1: r.recipe_unit_id,
-                                                    unit_id2: r.purch_unit_id,
-                                                    qty1: q1,
-                                                    qty2: q2,
-                                                    status: Some(1),
-                                                }).unwrap();
-                                                match invoke_cmd::<PatchResponse>("upsert_convunit", args).await {
-                                                    Ok(resp) => set_save_status.set(resp.message),
-                                                    Err(e) => set_save_status.set(format!("Error: {e}")),
-                                                }
-                                            });
-                                        };
+                                    each=move || {
+                                        fc_lines.get().iter().enumerate().map(|(i, l)| (i, l.clone())).collect::<Vec<_>>()
+                                    }
+                                    key=|(i, _)| *i
+                                    children=move |(idx, line)| {
+                                        let item_name = fc_item_options.get().iter()
+                                            .find(|it| it.item_id == line.item_id)
+                                            .map(|it| it.name.clone())
+                                            .unwrap_or_else(|| format!("Item {}", line.item_id));
+                                        let unit_name = line.unit_id
+                                            .and_then(|uid| fc_unit_options.get().iter().find(|u| u.unit_id == uid).map(|u| u.sing.clone()))
+                                            .unwrap_or_else(|| "-".to_string());
                                         view! {
-                                            <div class="data-row data-cols-8">
-                                                <span>{row.item_id}</span>
-                                                <span>{row.item_name}</span>
-                                                <span>{format!("{} → {}", row.recipe_unit, row.purch_unit)}</span>
-                                                <span>{row.hits.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                <input
-                                                    class="inline-input"
-                                                    type="number"
-                                                    step="any"
-                                                    min="0"
-                                                    placeholder="qty"
-                                                    prop:value=move || qty1_val.get()
-                                                    on:input=move |ev| set_qty1_val.set(event_target_value(&ev))
-                                                />
-                                                <input
-                                                    class="inline-input"
-                                                    type="number"
-                                                    step="any"
-                                                    min="0"
-                                                    placeholder="qty"
-                                                    prop:value=move || qty2_val.get()
-                                                    on:input=move |ev| set_qty2_val.set(event_target_value(&ev))
-                                                />
-                                                <span>{row.vendor_id}</span>
-                                                <span class="action-cell">
-                                                    <button class="button tiny" on:click=save_local_conv>"Save"</button>
-                                                    <Show when=move || !save_status.get().is_empty()>
-                                                        <span class="inline-status">{move || save_status.get()}</span>
-                                                    </Show>
+                                            <div class="data-row data-cols-4">
+                                                <span>{item_name}</span>
+                                                <span>{unit_name}</span>
+                                                <span>{format!("{:.2}", line.qty)}</span>
+                                                <span>
+                                                    <button class="button-link danger" on:click=move |_| fc_remove_line(idx)>
+                                                        "\u{2715}"
+                                                    </button>
                                                 </span>
                                             </div>
                                         }
                                     }
                                 />
                             </div>
+
+                            <div class="row" style="margin-top: 12px; gap: 8px;">
+                                <button class="button" on:click=move |_| fc_calculate() disabled=move || fc_loading.get()>
+                                    {move || if fc_loading.get() { "Calculating\u{2026}" } else { "Calculate Cost" }}
+                                </button>
+                                <button class="button secondary" on:click=move |_| fc_clear()>
+                                    "Clear All"
+                                </button>
+                            </div>
                         </Show>
-                        <div class="row" style="margin-top: 16px;">
-                            <button class="button secondary" on:click=move |_| {
-                                let current = conversion_page.get();
-                                if current > 0 {
-                                    set_conversion_page.set(current - 1);
-                                    show_conversions();
-                                }
-                            } disabled=move || conversion_page.get() == 0 || conversion_loading.get()>
-                                "Previous"
-                            </button>
-                            <button class="button" on:click=move |_| {
-                                let current = conversion_page.get();
-                                let total = match conversion_tab.get().as_str() {
-                                    "suggestions" => conversion_suggestions_total.get(),
-                                    "safe" => conversion_suggestions_safe_total.get(),
-                                    "todo" => conversion_todo_total.get(),
-                                    _ => conversion_missing_edges_total.get(),
-                                };
-                                let max = (total as f64 / conversion_limit as f64).ceil() as usize;
-                                if current + 1 < max {
-                                    set_conversion_page.set(current + 1);
-                                    show_conversions();
+
+                        <div class="status">{move || fc_status.get()}</div>
+
+                        // results
+                        <Show when=move || fc_result.get().is_some()>
+                            {move || {
+                                let result = fc_result.get().unwrap_or_default();
+                                let total = result.total_cost;
+                                let missing = result.missing_costs;
+                                let pct = fc_target_pct.get();
+                                let menu_price = if pct > 0.0 { total / (pct / 100.0) } else { 0.0 };
+                                let dish = fc_dish_name.get();
+                                let dish_label = if dish.trim().is_empty() { "This dish".to_string() } else { dish };
+
+                                view! {
+                                    <div class="fc-results">
+                                        <div class="fc-summary-cards">
+                                            <div class="card fc-card">
+                                                <h4>"Total Ingredient Cost"</h4>
+                                                <p class="fc-big-number">{format_money(total)}</p>
+                                            </div>
+                                            <div class="card fc-card fc-card-highlight">
+                                                <h4>"Suggested Menu Price"</h4>
+                                                <p class="fc-big-number">{format_money(menu_price)}</p>
+                                                <p class="fc-sub">{format!("at {}% food cost", pct)}</p>
+                                            </div>
+                                            <div class="card fc-card">
+                                                <h4>"Profit per Plate"</h4>
+                                                <p class="fc-big-number">{format_money(menu_price - total)}</p>
+                                            </div>
+                                        </div>
+
+                                        <Show when=move || { missing > 0 }>
+                                            <div class="fc-warning">
+                                                {format!("\u{26A0} {} ingredient(s) could not be costed. The total may be understated.", missing)}
+                                            </div>
+                                        </Show>
+
+                                        <div class="fc-detail-label">
+                                            {format!("\u{1F4CB} {} \u{2014} ingredient breakdown", dish_label)}
+                                        </div>
+                                        <div class="data-table">
+                                            <div class="data-header data-cols-6">
+                                                <span>"Ingredient"</span>
+                                                <span>"Qty"</span>
+                                                <span>"Unit Price"</span>
+                                                <span>"Yield"</span>
+                                                <span>"EP Cost"</span>
+                                                <span>"Status"</span>
+                                            </div>
+                                            <For
+                                                each=move || result.lines.clone()
+                                                key=|line| line.item_id
+                                                children=move |line| {
+                                                    let status_class = if line.cost_status == "OK" { "fc-status-ok" } else { "fc-status-warn" };
+                                                    view! {
+                                                        <div class="data-row data-cols-6">
+                                                            <span>{format!("{} ({})", line.item_name, line.unit_name)}</span>
+                                                            <span>{format!("{:.2}", line.qty)}</span>
+                                                            <span>{line.price.map(format_money).unwrap_or_else(|| "\u{2014}".to_string())}</span>
+                                                            <span>{line.yield_pct.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "100%".to_string())}</span>
+                                                            <span>{line.extended_cost.map(format_money).unwrap_or_else(|| "\u{2014}".to_string())}</span>
+                                                            <span class={status_class}>{line.cost_status.clone()}</span>
+                                                        </div>
+                                                    }
+                                                }
+                                            />
+                                        </div>
+                                    </div>
                                 }
-                            } disabled=move || {
-                                let total = match conversion_tab.get().as_str() {
-                                    "suggestions" => conversion_suggestions_total.get(),
-                                    "safe" => conversion_suggestions_safe_total.get(),
-                                    "todo" => conversion_todo_total.get(),
-                                    _ => conversion_missing_edges_total.get(),
-                                };
-                                let max = (total as f64 / conversion_limit as f64).ceil() as usize;
-                                conversion_loading.get() || max == 0 || conversion_page.get() + 1 >= max
-                            }>
-                                "Next"
-                            </button>
-                        </div>
+                            }}
+                        </Show>
                     </div>
                 </Show>
-
-                <Show when=move || active_panel.get() == "purchasing">
+                // ── Menu Engineering Panel ──
+                <Show when=move || active_panel.get() == "sales">
                     <div class="panel">
-                        <div class="row">
-                            <div class="input">
-                                <label>"Search invoices"</label>
-                                <input
-                                    type="text"
-                                    prop:value=invoice_query
-                                    on:input=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_invoice_query.set(value);
-                                    }
-                                />
-                            </div>
-                            <div class="input" style="align-self: end;">
-                                <div class="row">
-                                    <button class="button" on:click=move |_| {
-                                        set_invoice_page.set(0);
-                                        trigger_invoice_fetch(
-                                            invoice_query.get(),
-                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
-                                            invoice_date_from.get(),
-                                            invoice_date_to.get(),
-                                            0,
-                                            invoice_limit,
-                                            set_invoice_loading,
-                                            set_invoice_status,
-                                            set_invoice_list,
-                                            set_invoice_total,
-                                            set_invoice_filtered,
-                                            set_invoice_loaded,
-                                        );
-                                    } disabled=move || invoice_loading.get()>
-                                        {move || if invoice_loading.get() { "Searching..." } else { "Search" }}
-                                    </button>
-                                    <button class="button secondary" on:click=move |_| {
-                                        set_invoice_query.set(String::new());
-                                        set_invoice_vendor_filter.set(String::new());
-                                        set_invoice_date_from.set(String::new());
-                                        set_invoice_date_to.set(String::new());
-                                        set_invoice_page.set(0);
-                                        trigger_invoice_fetch(
-                                            String::new(),
-                                            None,
-                                            String::new(),
-                                            String::new(),
-                                            0,
-                                            invoice_limit,
-                                            set_invoice_loading,
-                                            set_invoice_status,
-                                            set_invoice_list,
-                                            set_invoice_total,
-                                            set_invoice_filtered,
-                                            set_invoice_loaded,
-                                        );
-                                    }>
-                                        "Reset"
-                                    </button>
-                                </div>
-                            </div>
-                        </div>
-                        <div class="row">
+                        <strong>"Import POS Sales"</strong>
+                        <div class="row" style="margin-top: 10px;">
                             <div class="input">
-                                <label>"Vendor ID"</label>
+                                <label>"Mapping Profile"</label>
                                 <select
-                                    prop:value=invoice_vendor_filter
-                                    on:change=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_invoice_vendor_filter.set(value);
-                                    }
+                                    prop:value=move || sales_profile_id.get()
+                                    on:change=move |ev| set_sales_profile_id.set(event_target_value(&ev))
                                 >
-                                    <option value="">"All vendors"</option>
                                     <For
-                                        each=move || invoice_vendor_options.get()
-                                        key=|v| v.vendor_id
-                                        children=move |v| view! {
-                                            <option value={v.vendor_id.to_string()}>{format!("{} - {}", v.vendor_id, v.name)}</option>
+                                        each=move || pos_profiles.get()
+                                        key=|p| p.profile_id
+                                        children=move |p| view! {
+                                            <option value={p.profile_id.to_string()}>{p.name}</option>
                                         }
                                     />
                                 </select>
                             </div>
                             <div class="input">
-                                <label>"Date from"</label>
+                                <label>"Period Start"</label>
                                 <input
                                     type="date"
-                                    prop:value=invoice_date_from
-                                    on:input=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_invoice_date_from.set(value);
-                                    }
+                                    prop:value=move || sales_period_start.get()
+                                    on:input=move |ev| set_sales_period_start.set(event_target_value(&ev))
                                 />
-                            </div>
-                            <div class="input">
-                                <label>"Date to"</label>
-                                <input
-                                    type="date"
-                                    prop:value=invoice_date_to
-                                    on:input=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_invoice_date_to.set(value);
-                                    }
+                            </div>
+                            <div class="input">
+                                <label>"Period End"</label>
+                                <input
+                                    type="date"
+                                    prop:value=move || sales_period_end.get()
+                                    on:input=move |ev| set_sales_period_end.set(event_target_value(&ev))
                                 />
                             </div>
-                            <div class="input" style="align-self: end;">
-                                <button class="button secondary" on:click=move |_| export_invoices()>
-                                    "Export CSV"
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| import_sales_file()>
+                                    "Select CSV & Import"
                                 </button>
                             </div>
                         </div>
-                        <div class="status">{move || invoice_status.get()}</div>
-                        <div class="status">{move || invoice_export_status.get()}</div>
                         <div class="status">
-                            "Source: SQLite (imported from Invoice.csv + Trans.csv)"
+                            {move || {
+                                let selected = sales_profile_id.get();
+                                pos_profiles
+                                    .get()
+                                    .into_iter()
+                                    .find(|p| p.profile_id.to_string() == selected)
+                                    .map(|p| format!(
+                                        "Columns \u{2014} item: {} | qty: {} | net sales: {} | date: {} | category: {}",
+                                        p.item_col, p.qty_col, p.net_sales_col, p.date_col, p.category_col
+                                    ))
+                                    .unwrap_or_default()
+                            }}
                         </div>
-                        <Show when=move || !invoice_export_path.get().is_empty()>
-                            <div class="row">
-                                <button class="button secondary" on:click=move |_| {
-                                    open_path(invoice_export_path.get());
-                                }>
-                                    "Reveal export file"
-                                </button>
-                                <div class="status">{move || invoice_export_path.get()}</div>
+                        <div class="status">{move || sales_status.get()}</div>
+                        <details style="margin-top: 10px;">
+                            <summary>"Custom mapping profile"</summary>
+                            <div class="row" style="margin-top: 8px;">
+                                <div class="input">
+                                    <label>"Name"</label>
+                                    <input type="text" prop:value=move || new_profile_name.get() on:input=move |ev| set_new_profile_name.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Item column"</label>
+                                    <input type="text" placeholder="Menu Item" prop:value=move || new_profile_item.get() on:input=move |ev| set_new_profile_item.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Qty column"</label>
+                                    <input type="text" placeholder="Qty" prop:value=move || new_profile_qty.get() on:input=move |ev| set_new_profile_qty.set(event_target_value(&ev)) />
+                                </div>
                             </div>
-                        </Show>
+                            <div class="row" style="margin-top: 8px;">
+                                <div class="input">
+                                    <label>"Net sales column"</label>
+                                    <input type="text" placeholder="Net Sales" prop:value=move || new_profile_sales.get() on:input=move |ev| set_new_profile_sales.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Date column"</label>
+                                    <input type="text" placeholder="Date" prop:value=move || new_profile_date.get() on:input=move |ev| set_new_profile_date.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input">
+                                    <label>"Category column"</label>
+                                    <input type="text" placeholder="Category" prop:value=move || new_profile_category.get() on:input=move |ev| set_new_profile_category.set(event_target_value(&ev)) />
+                                </div>
+                                <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                    <button class="button secondary" on:click=move |_| save_new_profile()>"Save Profile"</button>
+                                </div>
+                            </div>
+                            <div class="status">"Separate alternative header names with |, e.g. \"Qty|Quantity\"."</div>
+                            <For
+                                each=custom_pos_profiles
+                                key=|p| p.profile_id
+                                children=move |p| {
+                                    let profile_id = p.profile_id;
+                                    view! {
+                                        <div class="row" style="align-items: center; gap: 8px;">
+                                            <span>{p.name}</span>
+                                            <button class="button secondary" on:click=move |_| {
+                                                spawn_local(async move {
+                                                    let args = to_value(&PosProfileIdArgs { profile_id }).unwrap();
+                                                    match invoke_cmd::<PatchResponse>("delete_pos_profile", args).await {
+                                                        Ok(resp) => {
+                                                            set_sales_status.set(resp.message);
+                                                            set_sales_profile_id.set(String::new());
+                                                            load_pos_profiles();
+                                                        }
+                                                        Err(err) => set_sales_status.set(format!("Delete failed: {err}")),
+                                                    }
+                                                });
+                                            }>"Delete"</button>
+                                        </div>
+                                    }
+                                }
+                            />
+                        </details>
+                        <div class="data-table" style="margin-top: 10px;">
+                            <div class="data-header data-cols-6">
+                                <span>"Period"</span>
+                                <span>"Profile"</span>
+                                <span>"Imported"</span>
+                                <span>"Units"</span>
+                                <span>"Net Sales"</span>
+                                <span></span>
+                            </div>
+                            <For
+                                each=move || sales_imports.get()
+                                key=|row| row.import_id
+                                children=move |row| {
+                                    let import_id = row.import_id;
+                                    view! {
+                                        <div class="data-row data-cols-6" title={row.source_file.clone()}>
+                                            <span>{format!("{} \u{2192} {}", row.period_start, row.period_end)}</span>
+                                            <span>{row.profile_name}</span>
+                                            <span>{row.imported_at}</span>
+                                            <span>{format!("{}", row.total_qty)}</span>
+                                            <span>{format_money(row.total_sales)}</span>
+                                            <span>
+                                                <button class="button secondary" on:click=move |_| {
+                                                    spawn_local(async move {
+                                                        let args = to_value(&SalesImportIdArgs { import_id }).unwrap();
+                                                        match invoke_cmd::<PatchResponse>("delete_sales_import", args).await {
+                                                            Ok(resp) => {
+                                                                set_sales_status.set(resp.message);
+                                                                load_sales_imports();
+                                                                load_pos_items();
+                                                            }
+                                                            Err(err) => set_sales_status.set(format!("Delete failed: {err}")),
+                                                        }
+                                                    });
+                                                }>"Remove"</button>
+                                            </span>
+                                        </div>
+                                    }
+                                }
+                            />
+                        </div>
                     </div>
 
-                    <div class="panel recipe-grid">
-                        <div class="recipe-list">
-                            <div class="recipe-list-header">
-                                <span>"Invoices"</span>
-                                <span>{move || format!(
-                                    "{} / {}",
-                                    invoice_filtered.get(),
-                                    invoice_total.get()
-                                )}</span>
+                    <div class="panel">
+                        <div class="row" style="align-items: center;">
+                            <div>
+                                <strong>"POS Item Mapping"</strong>
+                                <div class="status">
+                                    {move || format!("{} POS items not mapped to a recipe", pos_unmapped_count.get())}
+                                </div>
                             </div>
-                            <div class="recipe-list-body">
-                                <For
-                                    each=move || invoice_list.get()
-                                    key=|item| item.invoice_id
-                                    children=move |item| {
-                                        let invoice_id = item.invoice_id;
-                                        view! {
-                                            <div
-                                                class="recipe-row"
-                                                class:selected=move || selected_invoice.get().map(|r| r.invoice.invoice_id == invoice_id).unwrap_or(false)
-                                                on:click=move |_| {
-                                                    trigger_invoice_detail_fetch(
-                                                        invoice_id,
-                                                        set_invoice_detail_loading,
-                                                        set_invoice_detail_status,
-                                                        set_selected_invoice,
-                                                    );
-                                                }
-                                            >
-                                                <div class="recipe-name">{item.invoice_no.clone()}</div>
-                                                <div class="recipe-meta">{format!("{} | {}", item.vendor_name, item.invoice_date)}</div>
-                                            </div>
-                                        }
+                            <label style="display: inline-flex; gap: 8px; align-items: center; margin-left: auto;">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || pos_unmapped_only.get()
+                                    on:change=move |ev| {
+                                        set_pos_unmapped_only.set(event_target_checked(&ev));
+                                        load_pos_items();
                                     }
                                 />
+                                <span>"Unmapped only"</span>
+                            </label>
+                        </div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-6">
+                                <span>"POS Item"</span>
+                                <span>"Category"</span>
+                                <span>"Recipe"</span>
+                                <span>"Portions"</span>
+                                <span>"Sold"</span>
+                                <span></span>
                             </div>
-                            <div class="row" style="margin-top: 12px;">
-                                <button class="button secondary" on:click=move |_| {
-                                    let current = invoice_page.get();
-                                    if current > 0 {
-                                        set_invoice_page.set(current - 1);
-                                        trigger_invoice_fetch(
-                                            invoice_query.get(),
-                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
-                                            invoice_date_from.get(),
-                                            invoice_date_to.get(),
-                                            current - 1,
-                                            invoice_limit,
-                                            set_invoice_loading,
-                                            set_invoice_status,
-                                            set_invoice_list,
-                                            set_invoice_total,
-                                            set_invoice_filtered,
-                                            set_invoice_loaded,
-                                        );
-                                    }
-                                } disabled=move || invoice_page.get() == 0 || invoice_loading.get()>
-                                    "Previous"
-                                </button>
-                                <button class="button" on:click=move |_| {
-                                    let current = invoice_page.get();
-                                    let max = (invoice_filtered.get() as f64 / invoice_limit as f64).ceil() as usize;
-                                    if current + 1 < max {
-                                        set_invoice_page.set(current + 1);
-                                        trigger_invoice_fetch(
-                                            invoice_query.get(),
-                                            invoice_vendor_filter.get().trim().parse::<i64>().ok(),
-                                            invoice_date_from.get(),
-                                            invoice_date_to.get(),
-                                            current + 1,
-                                            invoice_limit,
-                                            set_invoice_loading,
-                                            set_invoice_status,
-                                            set_invoice_list,
-                                            set_invoice_total,
-                                            set_invoice_filtered,
-                                            set_invoice_loaded,
-                                        );
+                            <For
+                                each=move || pos_items.get()
+                                key=|row| (row.pos_item_id, row.recipe_id, row.ignored)
+                                children=move |row| {
+                                    let pos_item_id = row.pos_item_id;
+                                    let (recipe_sel, set_recipe_sel) = signal(row.recipe_id.map(|id| id.to_string()).unwrap_or_default());
+                                    let (portions, set_portions) = signal(row.portions.to_string());
+                                    let ignored = row.ignored;
+                                    view! {
+                                        <div class="data-row data-cols-6">
+                                            <span>{if ignored { format!("{} (ignored)", row.name) } else { row.name.clone() }}</span>
+                                            <span>{if row.category.is_empty() { "-".to_string() } else { row.category.clone() }}</span>
+                                            <span>
+                                                <select
+                                                    prop:value=move || recipe_sel.get()
+                                                    on:change=move |ev| set_recipe_sel.set(event_target_value(&ev))
+                                                >
+                                                    <option value="">"Not mapped"</option>
+                                                    <For
+                                                        each=move || sales_recipe_options.get()
+                                                        key=|r| r.recipe_id
+                                                        children=move |r| view! {
+                                                            <option value={r.recipe_id.to_string()}>{r.name}</option>
+                                                        }
+                                                    />
+                                                </select>
+                                            </span>
+                                            <span>
+                                                <input
+                                                    type="number"
+                                                    step="any"
+                                                    style="width: 70px;"
+                                                    prop:value=move || portions.get()
+                                                    on:input=move |ev| set_portions.set(event_target_value(&ev))
+                                                />
+                                            </span>
+                                            <span>{format!("{}", row.units_sold)}</span>
+                                            <span style="display: flex; gap: 4px;">
+                                                <button class="button" on:click=move |_| {
+                                                    map_pos_item(MapPosItemArgs {
+                                                        pos_item_id,
+                                                        recipe_id: recipe_sel.get_untracked().parse::<i64>().ok(),
+                                                        portions: portions.get_untracked().trim().parse::<f64>().ok(),
+                                                        ignored: false,
+                                                    });
+                                                }>"Save"</button>
+                                                <button class="button secondary" on:click=move |_| {
+                                                    map_pos_item(MapPosItemArgs {
+                                                        pos_item_id,
+                                                        recipe_id: None,
+                                                        portions: None,
+                                                        ignored: !ignored,
+                                                    });
+                                                }>{if ignored { "Include" } else { "Ignore" }}</button>
+                                            </span>
+                                        </div>
                                     }
-                                } disabled=move || {
-                                    let max = (invoice_filtered.get() as f64 / invoice_limit as f64).ceil() as usize;
-                                    invoice_loading.get() || max == 0 || invoice_page.get() + 1 >= max
-                                }>
-                                    "Next"
-                                </button>
+                                }
+                            />
+                        </div>
+                    </div>
+
+                    <div class="panel">
+                        <strong>"Menu Engineering Report"</strong>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"From"</label>
+                                <input type="date" prop:value=move || me_date_from.get() on:input=move |ev| set_me_date_from.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"To"</label>
+                                <input type="date" prop:value=move || me_date_to.get() on:input=move |ev| set_me_date_to.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"Category"</label>
+                                <select
+                                    prop:value=move || me_category.get()
+                                    on:change=move |ev| set_me_category.set(event_target_value(&ev))
+                                >
+                                    <option value="">"All categories"</option>
+                                    {move || me_report.get().map(|r| r.categories).unwrap_or_default().into_iter().map(|c| {
+                                        let value = c.clone();
+                                        view! { <option value={value}>{c}</option> }
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_menu_engineering()>"Run"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_menu_engineering(true)>"PDF"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_menu_engineering(false)>"CSV"</button>
                             </div>
                         </div>
-                        <div class="recipe-detail">
-                            <div class="status">{move || invoice_detail_status.get()}</div>
-                            <Show
-                                when=move || selected_invoice.get().is_some()
-                                fallback=move || view! {
-                                    <div class="recipe-empty">
-                                        <strong>"Select an invoice"</strong>
-                                        <p>"Choose an invoice to review transaction lines."</p>
+                        {move || me_report.get().map(|report| {
+                            let food_cost = report.food_cost_pct.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string());
+                            let avg_cm = report.avg_contribution_margin.map(format_money).unwrap_or_else(|| "-".to_string());
+                            view! {
+                                <div class="status">
+                                    {format!(
+                                        "{} items | {} units | revenue {} | margin {} | food cost {} | avg CM {} | popular at \u{2265} {:.1}% mix",
+                                        report.rows.len(),
+                                        report.total_units,
+                                        format_money(report.total_revenue),
+                                        format_money(report.total_margin),
+                                        food_cost,
+                                        avg_cm,
+                                        report.popularity_threshold_pct
+                                    )}
+                                </div>
+                                <Show when=move || { report.unmapped_items > 0 || report.uncosted_items > 0 }>
+                                    <div class="status">
+                                        {format!(
+                                            "{} unmapped POS items ({} units) excluded; {} items missing plate cost",
+                                            report.unmapped_items, report.unmapped_units, report.uncosted_items
+                                        )}
                                     </div>
-                                }
-                            >
-                                {move || selected_invoice.with(|opt| {
-                                    opt.as_ref().map(|detail| {
-                                        let detail = detail.clone();
+                                </Show>
+                                <div class="data-table">
+                                    <div class="data-header data-cols-menu-eng">
+                                        <span>"Menu Item"</span>
+                                        <span>"Sold"</span>
+                                        <span>"Mix %"</span>
+                                        <span>"Price"</span>
+                                        <span>"Plate Cost"</span>
+                                        <span>"CM"</span>
+                                        <span>"Total CM"</span>
+                                        <span>"Class"</span>
+                                    </div>
+                                    {report.rows.into_iter().map(|row| {
+                                        let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
                                         view! {
-                                            <div>
-                                                <div class="recipe-title">{detail.invoice.invoice_no.clone()}</div>
-                                                <div class="recipe-summary">
-                                                    <span>{detail.invoice.vendor_name.clone()}</span>
-                                                    <span>{detail.invoice.invoice_date.clone()}</span>
-                                                    <span>{detail.invoice.total.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                    <span>{detail.freight.map(|v| format!("Freight {}", format_money(v))).unwrap_or_else(|| "Freight -".to_string())}</span>
-                                                    <span>
-                                                        {format!(
-                                                            "Line total {}",
-                                                            format_money(detail.lines.iter().filter_map(|l| l.ext_cost).sum::<f64>())
-                                                        )}
-                                                    </span>
-                                                </div>
-                                                <div class="detail-block">
-                                                    <strong>"Lines"</strong>
-                                                    <div class="data-table">
-                                                        <div class="data-header data-cols-6">
-                                                            <span>"Item"</span>
-                                                            <span>"Qty"</span>
-                                                            <span>"Unit"</span>
-                                                            <span>"Price"</span>
-                                                            <span>"Ext"</span>
-                                                            <span>"Trans ID"</span>
-                                                        </div>
-                                                        <For
-                                                            each=move || detail.lines.clone()
-                                                            key=|item| (item.trans_id.unwrap_or(0), item.item_id.unwrap_or(0))
-                                                            children=move |item| view! {
-                                                                <div class="data-row data-cols-6">
-                                                                    <span>{item.item_name}</span>
-                                                                    <span>{item.qty.map(|q| format!("{:.3}", q)).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{item.unit_name}</span>
-                                                                    <span>{item.price.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{item.ext_cost.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
-                                                                    <span>{item.trans_id.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                                                </div>
-                                                            }
-                                                        />
-                                                    </div>
-                                                </div>
+                                            <div class="data-row data-cols-menu-eng" title={row.recipe_name.clone()}>
+                                                <span>{row.name.clone()}</span>
+                                                <span>{format!("{}", row.units_sold)}</span>
+                                                <span>{format!("{:.1} ({:.2})", row.mix_pct, row.popularity_index)}</span>
+                                                <span>{money(row.menu_price)}</span>
+                                                <span>{money(row.plate_cost)}</span>
+                                                <span>{money(row.contribution_margin)}</span>
+                                                <span>{money(row.total_margin)}</span>
+                                                <span class={format!("me-class me-{}", row.classification.to_lowercase())}>{row.classification.clone()}</span>
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                            }
+                        })}
+                    </div>
+                    <div class="panel">
+                        <strong>"Theoretical vs Actual Food Cost"</strong>
+                        <div class="status">
+                            "Theoretical usage is recipes \u{d7} units sold; actual usage is opening count + purchases \u{2212} closing count, all in each item's purchase unit."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"From"</label>
+                                <input type="date" prop:value=move || var_date_from.get() on:input=move |ev| set_var_date_from.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"To"</label>
+                                <input type="date" prop:value=move || var_date_to.get() on:input=move |ev| set_var_date_to.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_variance()>"Run"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || var_status.get()}</div>
+                        {move || var_report.get().map(|report| {
+                            let pct = |p: Option<f64>| p.map(|v| format!("{:.1}%", v)).unwrap_or_else(|| "-".to_string());
+                            let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
+                            let unresolved = report.unresolved.clone();
+                            view! {
+                                <div class="fc-summary-cards" style="margin-top: 10px;">
+                                    <div class="card fc-card">
+                                        <h4>"Net Sales"</h4>
+                                        <p class="fc-big-number">{format_money(report.net_sales)}</p>
+                                    </div>
+                                    <div class="card fc-card">
+                                        <h4>"Theoretical Food Cost"</h4>
+                                        <p class="fc-big-number">{pct(report.theoretical_pct)}</p>
+                                        <p class="fc-sub">{format_money(report.theoretical_cost)}</p>
+                                    </div>
+                                    <div class="card fc-card fc-card-highlight">
+                                        <h4>"Actual Food Cost"</h4>
+                                        <p class="fc-big-number">{pct(report.actual_pct)}</p>
+                                        <p class="fc-sub">{format_money(report.actual_cost)}</p>
+                                    </div>
+                                    <div class="card fc-card">
+                                        <h4>"Variance"</h4>
+                                        <p class="fc-big-number">{format_money(report.actual_cost - report.theoretical_cost)}</p>
+                                    </div>
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-4">
+                                        <span>"Category"</span>
+                                        <span>"Theoretical"</span>
+                                        <span>"Actual"</span>
+                                        <span>"Variance"</span>
+                                    </div>
+                                    {report.categories.into_iter().map(|c| {
+                                        let class = if c.variance_cost > 0.005 { "diff-up" } else if c.variance_cost < -0.005 { "diff-down" } else { "diff-zero" };
+                                        view! {
+                                            <div class="data-row data-cols-4">
+                                                <span>{c.category}</span>
+                                                <span>{format_money(c.theoretical_cost)}</span>
+                                                <span>{format_money(c.actual_cost)}</span>
+                                                <span class=class>{format_money(c.variance_cost)}</span>
                                             </div>
                                         }
-                                    })
-                                })}
-                            </Show>
-                            <Show when=move || invoice_detail_loading.get()>
-                                <div class="status">"Loading invoice..."</div>
-                            </Show>
-                        </div>
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-7">
+                                        <span>"Item"</span>
+                                        <span>"Theoretical"</span>
+                                        <span>"Actual"</span>
+                                        <span>"Variance"</span>
+                                        <span>"Var %"</span>
+                                        <span>"Variance $"</span>
+                                        <span>"Basis"</span>
+                                    </div>
+                                    {report.lines.into_iter().map(|l| {
+                                        let class = match l.variance_cost {
+                                            Some(v) if v > 0.005 => "diff-up",
+                                            Some(v) if v < -0.005 => "diff-down",
+                                            _ => "diff-zero",
+                                        };
+                                        let counts = match (l.begin_qty, l.end_qty) {
+                                            (Some(b), Some(e)) => format!(
+                                                "Open {:.2} + purchased {:.2} \u{2212} close {:.2}; sales and purchases {} to {}",
+                                                b, l.purchased_qty, e, l.usage_from, l.usage_to
+                                            ),
+                                            _ => format!("Purchased {:.2}; no opening/closing count", l.purchased_qty),
+                                        };
+                                        let basis = if l.basis == "Counted" {
+                                            format!("{} {} \u{2192} {}", l.basis, l.usage_from, l.usage_to)
+                                        } else {
+                                            l.basis.clone()
+                                        };
+                                        view! {
+                                            <div class="data-row data-cols-7" title=counts>
+                                                <span>{format!("{} ({})", l.item_name, l.unit_name)}</span>
+                                                <span>{format!("{:.2}", l.theoretical_qty)}</span>
+                                                <span>{format!("{:.2}", l.actual_qty)}</span>
+                                                <span>{format!("{:+.2}", l.variance_qty)}</span>
+                                                <span>{pct(l.variance_pct)}</span>
+                                                <span class=class>{money(l.variance_cost)}</span>
+                                                <span>{basis}</span>
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                                <Show when=move || !unresolved.is_empty()>
+                                    <div class="status">
+                                        {format!("Not included (missing servings, purchase units or conversions): {}", report.unresolved.join(", "))}
+                                    </div>
+                                </Show>
+                            }
+                        })}
                     </div>
                 </Show>
-
-                <Show when=move || active_panel.get() == "reports">
+                <Show when=move || active_panel.get() == "bids">
                     <div class="panel">
-                        <div class="row">
-                            <div>
-                                <strong>"Missing Data Report"</strong>
-                                <div class="status">
-                                    {move || format!("Rows: {}", missing_data_total.get())}
-                                </div>
-                            </div>
+                        <strong>"Bid Requests"</strong>
+                        <div class="status">
+                            "Tick vendors and optionally a category, export the sheet for them to fill in, then import the returned CSV."
                         </div>
-                        <div class="data-table">
-                            <div class="data-header data-cols-5">
-                                <span>"Recipe"</span>
-                                <span>"Recipe ID"</span>
-                                <span>"Missing Costs"</span>
-                                <span>"Missing Conversions"</span>
-                                <span>"Missing Units"</span>
-                            </div>
+                        <div class="row" style="margin-top: 10px; flex-wrap: wrap;">
                             <For
-                                each=move || missing_data_rows.get()
-                                key=|row| row.recipe_id
-                                children=move |row| view! {
-                                    <div class="data-row data-cols-5">
-                                        <span>{row.recipe_name}</span>
-                                        <span>{row.recipe_id}</span>
-                                        <span>{row.missing_a.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                        <span>{row.missing_b.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                        <span>{row.missing_c.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                    </div>
+                                each=move || invoice_vendor_options.get()
+                                key=|v| v.vendor_id
+                                children=move |v| {
+                                    let vendor_id = v.vendor_id;
+                                    view! {
+                                        <label style="display: inline-flex; gap: 6px; align-items: center;">
+                                            <input
+                                                type="checkbox"
+                                                prop:checked=move || bid_req_vendors.get().contains(&vendor_id)
+                                                on:change=move |ev| {
+                                                    let checked = event_target_checked(&ev);
+                                                    set_bid_req_vendors.update(|ids| {
+                                                        ids.retain(|id| *id != vendor_id);
+                                                        if checked {
+                                                            ids.push(vendor_id);
+                                                        }
+                                                    });
+                                                }
+                                            />
+                                            <span>{v.name}</span>
+                                        </label>
+                                    }
                                 }
                             />
                         </div>
-                    </div>
-
-                    <div class="panel">
-                        <div class="row">
-                            <div>
-                                <strong>"Missing Purchase Units"</strong>
-                                <div class="status">
-                                    {move || format!("Rows: {}", missing_purch_total.get())}
-                                </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Category"</label>
+                                <input placeholder="All purchased items" prop:value=move || bid_category.get() on:input=move |ev| set_bid_category.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_bid_request(true)>"Request PDF"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| export_bid_request(false)>"Request CSV"</button>
                             </div>
                         </div>
                         <div class="row" style="margin-top: 10px;">
                             <div class="input">
-                                <label>"Item ID"</label>
-                                <input
-                                    type="text"
-                                    prop:value=report_purch_item_id
-                                    on:input=move |ev| {
-                                        set_report_purch_item_id.set(event_target_value(&ev));
-                                    }
-                                />
+                                <label>"Valid From"</label>
+                                <input type="date" prop:value=move || bid_valid_from.get() on:input=move |ev| set_bid_valid_from.set(event_target_value(&ev)) />
                             </div>
                             <div class="input">
-                                <label>"Purchase Unit"</label>
-                                <select
-                                    prop:value=purch_unit_selected_id
-                                    on:change=move |ev| {
-                                        set_purch_unit_selected_id.set(event_target_value(&ev));
-                                    }
-                                >
-                                    <option value="">"Select unit"</option>
+                                <label>"Valid To"</label>
+                                <input type="date" prop:value=move || bid_valid_to.get() on:input=move |ev| set_bid_valid_to.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| import_bid_sheet()>"Import Returned Sheet"</button>
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button secondary" on:click=move |_| run_bid_action("import_legacy_bids", to_value(&PingArgs {}).unwrap())>"Reload Imported Bids"</button>
+                            </div>
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Vendor"</label>
+                                <select prop:value=move || bid_new_vendor.get() on:change=move |ev| set_bid_new_vendor.set(event_target_value(&ev))>
+                                    <option value="">"Choose vendor"</option>
                                     <For
-                                        each=move || unit_options.get()
-                                        key=|unit| unit.unit_id
-                                        children=move |unit| view! {
-                                            <option value={unit.unit_id.to_string()}>{format!("{} - {}", unit.unit_id, unit.sing)}</option>
+                                        each=move || invoice_vendor_options.get()
+                                        key=|v| v.vendor_id
+                                        children=move |v| view! {
+                                            <option value={v.vendor_id.to_string()}>{v.name}</option>
                                         }
                                     />
                                 </select>
                             </div>
-                            <div class="input" style="flex: 0 0 150px;">
-                                <label>"Default"</label>
-                                <label style="display: inline-flex; gap: 8px; align-items: center;">
-                                    <input
-                                        type="checkbox"
-                                        prop:checked=purch_unit_set_default
-                                        on:change=move |ev| {
-                                            set_purch_unit_set_default.set(event_target_checked(&ev));
+                            <div class="input">
+                                <label>"Item"</label>
+                                <select prop:value=move || bid_new_item.get() on:change=move |ev| set_bid_new_item.set(event_target_value(&ev))>
+                                    <option value="">"Choose item"</option>
+                                    <For
+                                        each=move || po_item_options.get()
+                                        key=|i| i.item_id
+                                        children=move |i| view! {
+                                            <option value={i.item_id.to_string()}>{i.name}</option>
                                         }
                                     />
-                                    <span>"Set default"</span>
-                                </label>
+                                </select>
                             </div>
-                            <div class="input" style="align-self: end; flex: 0 0 auto;">
-                                <button class="button" on:click=move |_| {
-                                    match report_purch_item_id.get().trim().parse::<i64>() {
-                                        Ok(item_id) => assign_purch_unit(item_id),
-                                        Err(_) => set_purch_unit_status.set("Enter a valid item ID".to_string()),
-                                    }
-                                }>
-                                    "Assign unit"
-                                </button>
+                            <div class="input">
+                                <label>"Price"</label>
+                                <input type="number" step="any" min="0" prop:value=move || bid_new_price.get() on:input=move |ev| set_bid_new_price.set(event_target_value(&ev)) />
                             </div>
-                        </div>
-                        <div class="status">{move || purch_unit_status.get()}</div>
-                        <div class="data-table">
-                            <div class="data-header data-cols-3">
-                                <span>"Item"</span>
-                                <span>"Item ID"</span>
-                                <span>"Usage Count"</span>
+                            <div class="input">
+                                <label>"Per Unit"</label>
+                                <select prop:value=move || bid_new_unit.get() on:change=move |ev| set_bid_new_unit.set(event_target_value(&ev))>
+                                    <option value="">"Purchase unit"</option>
+                                    <For
+                                        each=move || po_unit_options.get()
+                                        key=|u| u.unit_id
+                                        children=move |u| view! {
+                                            <option value={u.unit_id.to_string()}>{u.sing}</option>
+                                        }
+                                    />
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Pack"</label>
+                                <input prop:value=move || bid_new_pack.get() on:input=move |ev| set_bid_new_pack.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| add_bid()>"Add Bid"</button>
                             </div>
-                            <For
-                                each=move || missing_purch_rows.get()
-                                key=|row| row.item_id
-                                children=move |row| {
-                                    let row_item_id = row.item_id;
-                                    view! {
-                                        <div class="data-row data-cols-3" on:click=move |_| {
-                                            set_report_purch_item_id.set(row_item_id.to_string());
-                                        }>
-                                            <span>{row.item_name}</span>
-                                            <span>{row.item_id}</span>
-                                            <span>{row.usage_count.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())}</span>
-                                        </div>
-                                    }
-                                }
-                            />
                         </div>
-                        <div class="status">{move || report_status.get()}</div>
+                        <div class="status">{move || bid_status.get()}</div>
                     </div>
-                </Show>
 
-                // ── Food Cost Panel ──
-                <Show when=move || active_panel.get() == "foodcost">
                     <div class="panel">
-                        <div class="row" style="align-items: center; gap: 12px; margin-bottom: 12px;">
-                            <div class="input" style="flex: 1;">
-                                <label>"Dish name (optional)"</label>
-                                <input
-                                    type="text"
-                                    placeholder="e.g. Grilled Salmon Plate"
-                                    prop:value=fc_dish_name
-                                    on:input=move |ev| set_fc_dish_name.set(event_target_value(&ev))
-                                />
+                        <strong>"Bid Comparison"</strong>
+                        <div class="status">"Bids valid on the date, priced per purchase unit through each vendor's conversions. Awarding sets the preferred vendor price for the bid's dates."</div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Valid On"</label>
+                                <input type="date" prop:value=move || bid_compare_date.get() on:input=move |ev| set_bid_compare_date.set(event_target_value(&ev)) />
                             </div>
-                            <div class="input" style="max-width: 160px;">
-                                <label>"Target food cost %"</label>
-                                <input
-                                    type="number"
-                                    min="1"
-                                    max="100"
-                                    step="0.5"
-                                    prop:value=move || format!("{}", fc_target_pct.get())
-                                    on:input=move |ev| {
-                                        if let Ok(v) = event_target_value(&ev).parse::<f64>() {
-                                            set_fc_target_pct.set(v);
-                                        }
-                                    }
-                                />
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| run_bid_comparison()>"Compare"</button>
                             </div>
                         </div>
+                        <Show when=move || bid_compare.get().is_empty()>
+                            <div class="status">"No bids are valid on this date."</div>
+                        </Show>
+                        {move || bid_compare.get().into_iter().map(|c| {
+                            let current = match c.current_price {
+                                Some(p) => format!("now {} from {} per {}", format_money(p), c.current_vendor, c.purch_unit_name),
+                                None => "no current price".to_string(),
+                            };
+                            let best = c.best_bid_id;
+                            view! {
+                                <div class="detail-block">
+                                    <strong>{format!("{} \u{2014} {}", c.item_name, current)}</strong>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-7">
+                                            <span>"Vendor"</span>
+                                            <span>"Quote"</span>
+                                            <span>"Pack"</span>
+                                            <span>{format!("Per {}", c.purch_unit_name)}</span>
+                                            <span>"Valid"</span>
+                                            <span>"Status"</span>
+                                            <span></span>
+                                        </div>
+                                        {c.bids.into_iter().map(|b| {
+                                            let bid_id = b.bid_id;
+                                            let open = b.status == "open";
+                                            let norm_class = if Some(bid_id) == best { "diff-down" } else { "" };
+                                            view! {
+                                                <div class="data-row data-cols-7">
+                                                    <span>{b.vendor_name}</span>
+                                                    <span>{format!("{} / {}", format_money(b.price), if b.unit_name.is_empty() { b.purch_unit_name.clone() } else { b.unit_name })}</span>
+                                                    <span>{b.pack}</span>
+                                                    <span class=norm_class>{b.norm_price.map(format_money).unwrap_or_else(|| "No conversion".to_string())}</span>
+                                                    <span>{format!("{} \u{2013} {}", b.valid_from, b.valid_to)}</span>
+                                                    <span>{b.status}</span>
+                                                    <span class="action-cell">
+                                                        <Show when=move || open>
+                                                            <button class="button tiny" on:click=move |_| {
+                                                                run_bid_action("award_vendor_bid", to_value(&BidIdArgs { bid_id }).unwrap());
+                                                            }>"Award"</button>
+                                                        </Show>
+                                                    </span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </div>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </div>
 
-                        <div class="fc-add-row">
-                            <div class="input" style="flex: 2;">
-                                <label>"Ingredient"</label>
+                    <div class="panel">
+                        <div class="row">
+                            <strong>"All Bids"</strong>
+                            <div class="input" style="margin-left: auto; flex: 0 0 auto;">
                                 <select
-                                    prop:value=fc_add_item_id
-                                    on:change=move |ev| set_fc_add_item_id.set(event_target_value(&ev))
+                                    prop:value=move || bid_status_filter.get()
+                                    on:change=move |ev| {
+                                        set_bid_status_filter.set(event_target_value(&ev));
+                                        load_bids();
+                                    }
                                 >
-                                    <option value="">"-- Select item --"</option>
-                                    <For
-                                        each=move || fc_item_options.get()
-                                        key=|item| item.item_id
-                                        children=move |item| {
-                                            let val = item.item_id.to_string();
-                                            view! { <option value={val.clone()}>{format!("{} ({})", item.name, item.item_id)}</option> }
-                                        }
-                                    />
+                                    <option value="">"All"</option>
+                                    <option value="open">"Open"</option>
+                                    <option value="awarded">"Awarded"</option>
+                                    <option value="lost">"Lost"</option>
+                                    <option value="superseded">"Superseded"</option>
+                                    <option value="expired">"Expired"</option>
                                 </select>
                             </div>
-                            <div class="input" style="flex: 1;">
-                                <label>"Unit"</label>
-                                <select
-                                    prop:value=fc_add_unit_id
-                                    on:change=move |ev| set_fc_add_unit_id.set(event_target_value(&ev))
-                                >
-                                    <option value="">"-- Unit --"</option>
-                                    <For
-                                        each=move || fc_unit_options.get()
-                                        key=|u| u.unit_id
-                                        children=move |u| {
-                                            let val = u.unit_id.to_string();
-                                            view! { <option value={val.clone()}>{u.sing.clone()}</option> }
-                                        }
-                                    />
-                                </select>
+                        </div>
+                        <div class="data-table">
+                            <div class="data-header data-cols-8">
+                                <span>"Source"</span>
+                                <span>"Item"</span>
+                                <span>"Vendor"</span>
+                                <span>"Quote"</span>
+                                <span>"Per Purch."</span>
+                                <span>"Valid"</span>
+                                <span>"Status"</span>
+                                <span></span>
                             </div>
-                            <div class="input" style="max-width: 100px;">
-                                <label>"Qty"</label>
-                                <input
-                                    type="number"
-                                    step="0.01"
-                                    min="0"
-                                    placeholder="0.00"
-                                    prop:value=fc_add_qty
-                                    on:input=move |ev| set_fc_add_qty.set(event_target_value(&ev))
-                                />
+                            <For
+                                each=move || bid_list.get()
+                                key=|b| (b.bid_id, b.status.clone(), b.price.to_bits())
+                                children=move |b| {
+                                    let bid_id = b.bid_id;
+                                    let awarded = b.status == "awarded";
+                                    view! {
+                                        <div class="data-row data-cols-8">
+                                            <span>{b.source}</span>
+                                            <span>{b.item_name}</span>
+                                            <span>{b.vendor_name}</span>
+                                            <span>{format!("{} / {}", format_money(b.price), if b.unit_name.is_empty() { b.purch_unit_name.clone() } else { b.unit_name })}</span>
+                                            <span>{b.norm_price.map(|p| format!("{} / {}", format_money(p), b.purch_unit_name)).unwrap_or_else(|| "-".to_string())}</span>
+                                            <span>{format!("{} \u{2013} {}", b.valid_from, b.valid_to)}</span>
+                                            <span>{b.status}</span>
+                                            <span class="action-cell">
+                                                <Show when=move || !awarded>
+                                                    <button class="button tiny secondary" on:click=move |_| {
+                                                        run_bid_action("delete_vendor_bid", to_value(&BidIdArgs { bid_id }).unwrap());
+                                                    }>"Delete"</button>
+                                                </Show>
+                                            </span>
+                                        </div>
+                                    }
+                                }
+                            />
+                        </div>
+                    </div>
+                </Show>
+                <Show when=move || active_panel.get() == "counts">
+                    <div class="panel">
+                        <strong>"Count Sessions"</strong>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Name"</label>
+                                <input type="text" placeholder="Month-end count" prop:value=move || new_count_name.get() on:input=move |ev| set_new_count_name.set(event_target_value(&ev)) />
                             </div>
                             <div class="input">
-                                <label style="visibility: hidden;">{"\u{00A0}"}</label>
-                                <button class="button secondary" on:click=move |_| fc_add_line()>
-                                    "+ Add"
-                                </button>
+                                <label>"Count Date"</label>
+                                <input type="date" prop:value=move || new_count_date.get() on:input=move |ev| set_new_count_date.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| create_count_session()>"Start Session"</button>
+                            </div>
+                        </div>
+                        <div class="status">{move || count_status.get()}</div>
+                        <div class="data-table" style="margin-top: 10px;">
+                            <div class="data-header data-cols-6">
+                                <span>"Session"</span>
+                                <span>"Count Date"</span>
+                                <span>"Status"</span>
+                                <span>"Items"</span>
+                                <span>"Value"</span>
+                                <span></span>
                             </div>
+                            <For
+                                each=move || count_sessions.get()
+                                key=|s| (s.session_id, s.status.clone(), s.item_count)
+                                children=move |s| {
+                                    let session_id = s.session_id;
+                                    let open = s.status != "closed";
+                                    view! {
+                                        <div class="data-row data-cols-6">
+                                            <span>
+                                                <a href="#" on:click=move |ev: leptos::ev::MouseEvent| {
+                                                    ev.prevent_default();
+                                                    open_count_session(session_id);
+                                                }>{s.name.clone()}</a>
+                                            </span>
+                                            <span>{s.count_date.clone()}</span>
+                                            <span>{if open { "Open".to_string() } else { format!("Closed {}", s.closed_at) }}</span>
+                                            <span>{s.item_count}</span>
+                                            <span title=if open { "Open the session to value it at current prices" } else { "" }>
+                                                {s.total_value.map(format_money).unwrap_or_else(|| "-".to_string())}
+                                            </span>
+                                            <span class="action-cell">
+                                                <Show when=move || open>
+                                                    <button class="button tiny" on:click=move |_| count_session_action("close_count_session", session_id)>"Close"</button>
+                                                    <button class="button tiny secondary" on:click=move |_| count_session_action("delete_count_session", session_id)>"Delete"</button>
+                                                </Show>
+                                            </span>
+                                        </div>
+                                    }
+                                }
+                            />
                         </div>
+                    </div>
 
-                        // ingredient lines table
-                        <Show when=move || !fc_lines.get().is_empty()>
-                            <div class="data-table" style="margin-top: 12px;">
-                                <div class="data-header data-cols-4">
-                                    <span>"Ingredient"</span>
-                                    <span>"Unit"</span>
-                                    <span>"Qty"</span>
-                                    <span></span>
+                    {move || count_sheet.get().map(|sheet| {
+                        let session_id = sheet.session.session_id;
+                        let closed = sheet.session.status == "closed";
+                        let heading = format!(
+                            "Count Sheet \u{2014} {} ({}){}{}",
+                            sheet.session.name,
+                            sheet.session.count_date,
+                            if closed { ", closed" } else { "" },
+                            sheet.session.total_value.map(|v| format!(", {}", format_money(v))).unwrap_or_default()
+                        );
+                        let mut rows = Vec::new();
+                        let mut last_storage: Option<String> = None;
+                        for line in sheet.lines {
+                            let storage = if line.storage_type.is_empty() { "Unassigned".to_string() } else { line.storage_type.clone() };
+                            if last_storage.as_ref() != Some(&storage) {
+                                rows.push(view! {
+                                    <div class="data-row count-group"><strong>{storage.clone()}</strong></div>
+                                }.into_any());
+                                last_storage = Some(storage);
+                            }
+                            let item_id = line.item_id;
+                            let (qty, set_qty) = signal(line.qty.map(|q| q.to_string()).unwrap_or_default());
+                            let (unit, set_unit) = signal(line.unit_id.or(line.purch_unit_id).map(|u| u.to_string()).unwrap_or_default());
+                            let (shelf, set_shelf) = signal(line.shelf_order.map(|o| o.to_string()).unwrap_or_default());
+                            let converted = match (line.qty, line.purch_qty) {
+                                (Some(_), Some(pq)) => format!("{:.2} {}", pq, line.purch_unit_name),
+                                (Some(_), None) => "No conversion".to_string(),
+                                _ => "-".to_string(),
+                            };
+                            let value = line.value.map(format_money).unwrap_or_else(|| "-".to_string());
+                            let no_units = line.units.is_empty();
+                            let units = line.units.clone();
+                            rows.push(view! {
+                                <div class="data-row data-cols-count-sheet">
+                                    <span title={line.category.clone()}>{line.item_name.clone()}</span>
+                                    <span>
+                                        <input
+                                            class="inline-input"
+                                            type="number"
+                                            step="1"
+                                            prop:value=move || shelf.get()
+                                            on:input=move |ev| set_shelf.set(event_target_value(&ev))
+                                            on:change=move |_| save_shelf_order(session_id, item_id, shelf.get_untracked())
+                                        />
+                                    </span>
+                                    <span>
+                                        <input
+                                            class="inline-input"
+                                            type="number"
+                                            step="any"
+                                            min="0"
+                                            disabled=closed || no_units
+                                            prop:value=move || qty.get()
+                                            on:input=move |ev| set_qty.set(event_target_value(&ev))
+                                        />
+                                    </span>
+                                    <span>
+                                        <select
+                                            disabled=closed || no_units
+                                            prop:value=move || unit.get()
+                                            on:change=move |ev| set_unit.set(event_target_value(&ev))
+                                        >
+                                            {units.into_iter().map(|u| view! {
+                                                <option value={u.unit_id.to_string()}>{u.unit_name}</option>
+                                            }).collect::<Vec<_>>()}
+                                        </select>
+                                    </span>
+                                    <span>{converted}</span>
+                                    <span>{value}</span>
+                                    <span class="action-cell">
+                                        <Show when=move || !closed && !no_units fallback=move || view! {
+                                            <span class="inline-status">{if no_units { "No purchase unit" } else { "" }}</span>
+                                        }>
+                                            <button class="button tiny" on:click=move |_| {
+                                                let text = qty.get_untracked();
+                                                save_count_line(SaveCountLineArgs {
+                                                    session_id,
+                                                    item_id,
+                                                    qty: text.trim().parse::<f64>().ok(),
+                                                    unit_id: unit.get_untracked().parse::<i64>().ok(),
+                                                });
+                                            }>"Save"</button>
+                                        </Show>
+                                    </span>
                                 </div>
-                                <For
-                                    each=move || {
-                                        fc_lines.get().iter().enumerate().map(|(i, l)| (i, l.clone())).collect::<Vec<_>>()
-                                    }
-                                    key=|(i, _)| *i
-                                    children=move |(idx, line)| {
-                                        let item_name = fc_item_options.get().iter()
-                                            .find(|it| it.item_id == line.item_id)
-                                            .map(|it| it.name.clone())
-                                            .unwrap_or_else(|| format!("Item {}", line.item_id));
-                                        let unit_name = line.unit_id
-                                            .and_then(|uid| fc_unit_options.get().iter().find(|u| u.unit_id == uid).map(|u| u.sing.clone()))
-                                            .unwrap_or_else(|| "-".to_string());
+                            }.into_any());
+                        }
+                        view! {
+                            <div class="panel">
+                                <div class="row" style="align-items: center;">
+                                    <strong>{heading}</strong>
+                                    <button class="button secondary" style="margin-left: auto;" on:click=move |_| export_count_pdf(false)>"Count Sheet PDF"</button>
+                                </div>
+                                <div class="status">
+                                    "Items are listed by storage type and shelf order. Counts can be entered in any unit the item converts to; clear a count and save to remove it."
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-count-sheet">
+                                        <span>"Item"</span>
+                                        <span>"Shelf"</span>
+                                        <span>"Count"</span>
+                                        <span>"Unit"</span>
+                                        <span>"Purchase Units"</span>
+                                        <span>"Value"</span>
+                                        <span></span>
+                                    </div>
+                                    {rows}
+                                </div>
+                            </div>
+                        }
+                    })}
+
+                    {move || count_valuation.get().map(|report| {
+                        let session_id = report.session.session_id;
+                        let current_id = report.prior.as_ref().map(|p| p.session_id.to_string()).unwrap_or_default();
+                        let others: Vec<CountSession> = count_sessions
+                            .get_untracked()
+                            .into_iter()
+                            .filter(|s| s.session_id != session_id)
+                            .collect();
+                        let money = |v: Option<f64>| v.map(format_money).unwrap_or_else(|| "-".to_string());
+                        let diff_class = |v: f64| if v > 0.005 { "diff-up" } else if v < -0.005 { "diff-down" } else { "diff-zero" };
+                        let has_prior = report.prior.is_some();
+                        let change = report.total_value - report.prior_total;
+                        let unpriced = report.unpriced.clone();
+                        view! {
+                            <div class="panel">
+                                <div class="row" style="align-items: center;">
+                                    <strong>"Inventory Valuation"</strong>
+                                    <div class="input" style="margin-left: auto; flex: 0 0 auto;">
+                                        <label>"Compare with"</label>
+                                        <select
+                                            prop:value=current_id
+                                            on:change=move |ev| {
+                                                set_count_compare_id.set(event_target_value(&ev));
+                                                run_valuation(session_id);
+                                            }
+                                        >
+                                            <option value="">"Previous closed session"</option>
+                                            {others.into_iter().map(|s| view! {
+                                                <option value={s.session_id.to_string()}>{format!("{} ({})", s.name, s.count_date)}</option>
+                                            }).collect::<Vec<_>>()}
+                                        </select>
+                                    </div>
+                                    <button class="button secondary" on:click=move |_| export_count_pdf(true)>"Valuation PDF"</button>
+                                </div>
+                                <div class="fc-summary-cards" style="margin-top: 10px;">
+                                    <div class="card fc-card fc-card-highlight">
+                                        <h4>{report.session.name.clone()}</h4>
+                                        <p class="fc-big-number">{format_money(report.total_value)}</p>
+                                        <p class="fc-sub">{report.session.count_date.clone()}</p>
+                                    </div>
+                                    <div class="card fc-card">
+                                        <h4>{report.prior.as_ref().map(|p| p.name.clone()).unwrap_or_else(|| "No prior session".to_string())}</h4>
+                                        <p class="fc-big-number">{if has_prior { format_money(report.prior_total) } else { "-".to_string() }}</p>
+                                        <p class="fc-sub">{report.prior.as_ref().map(|p| p.count_date.clone()).unwrap_or_default()}</p>
+                                    </div>
+                                    <div class="card fc-card">
+                                        <h4>"Change"</h4>
+                                        <p class={format!("fc-big-number {}", diff_class(change))}>{if has_prior { format_money(change) } else { "-".to_string() }}</p>
+                                    </div>
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-4">
+                                        <span>"Storage"</span>
+                                        <span>"Value"</span>
+                                        <span>"Prior"</span>
+                                        <span>"Change"</span>
+                                    </div>
+                                    {report.storage_groups.into_iter().map(|g| {
+                                        let delta = g.value - g.prior_value;
                                         view! {
                                             <div class="data-row data-cols-4">
-                                                <span>{item_name}</span>
-                                                <span>{unit_name}</span>
-                                                <span>{format!("{:.2}", line.qty)}</span>
-                                                <span>
-                                                    <button class="button-link danger" on:click=move |_| fc_remove_line(idx)>
-                                                        "\u{2715}"
-                                                    </button>
-                                                </span>
+                                                <span>{g.name}</span>
+                                                <span>{format_money(g.value)}</span>
+                                                <span>{format_money(g.prior_value)}</span>
+                                                <span class=diff_class(delta)>{format_money(delta)}</span>
                                             </div>
                                         }
-                                    }
-                                />
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-4">
+                                        <span>"Category"</span>
+                                        <span>"Value"</span>
+                                        <span>"Prior"</span>
+                                        <span>"Change"</span>
+                                    </div>
+                                    {report.categories.into_iter().map(|g| {
+                                        let delta = g.value - g.prior_value;
+                                        view! {
+                                            <div class="data-row data-cols-4">
+                                                <span>{g.name}</span>
+                                                <span>{format_money(g.value)}</span>
+                                                <span>{format_money(g.prior_value)}</span>
+                                                <span class=diff_class(delta)>{format_money(delta)}</span>
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-7">
+                                        <span>"Item"</span>
+                                        <span>"Storage"</span>
+                                        <span>"Qty"</span>
+                                        <span>"Price"</span>
+                                        <span>"Value"</span>
+                                        <span>"Prior"</span>
+                                        <span>"Change"</span>
+                                    </div>
+                                    {report.lines.into_iter().map(|l| {
+                                        view! {
+                                            <div class="data-row data-cols-7">
+                                                <span>{format!("{} ({})", l.item_name, l.unit_name)}</span>
+                                                <span>{if l.storage_type.is_empty() { "-".to_string() } else { l.storage_type.clone() }}</span>
+                                                <span>{l.qty.map(|q| format!("{:.2}", q)).unwrap_or_else(|| "-".to_string())}</span>
+                                                <span>{money(l.unit_price)}</span>
+                                                <span>{money(l.value)}</span>
+                                                <span>{money(l.prior_value)}</span>
+                                                <span class=diff_class(l.change_value)>{format_money(l.change_value)}</span>
+                                            </div>
+                                        }
+                                    }).collect::<Vec<_>>()}
+                                </div>
+                                <Show when=move || !unpriced.is_empty()>
+                                    <div class="status">
+                                        {format!("Counted but not valued (no price or conversion): {}", report.unpriced.join(", "))}
+                                    </div>
+                                </Show>
                             </div>
-
-                            <div class="row" style="margin-top: 12px; gap: 8px;">
-                                <button class="button" on:click=move |_| fc_calculate() disabled=move || fc_loading.get()>
-                                    {move || if fc_loading.get() { "Calculating\u{2026}" } else { "Calculate Cost" }}
-                                </button>
-                                <button class="button secondary" on:click=move |_| fc_clear()>
-                                    "Clear All"
+                        }
+                    })}
+                    <div class="panel">
+                        <strong>"Period COGS"</strong>
+                        <div class="status">
+                            "Cost of goods sold = opening inventory + purchases in the period \u{2212} closing inventory, by food, beverage and paper. Counts default to the latest closed session on or before each date."
+                        </div>
+                        <div class="row" style="margin-top: 10px;">
+                            <div class="input">
+                                <label>"Period"</label>
+                                <input type="text" placeholder="March 2026" prop:value=move || ap_name.get() on:input=move |ev| set_ap_name.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"Start"</label>
+                                <input type="date" prop:value=move || ap_start.get() on:input=move |ev| set_ap_start.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"End"</label>
+                                <input type="date" prop:value=move || ap_end.get() on:input=move |ev| set_ap_end.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input">
+                                <label>"Opening Count"</label>
+                                <select prop:value=move || ap_opening.get() on:change=move |ev| set_ap_opening.set(event_target_value(&ev))>
+                                    <option value="">"Latest before start"</option>
+                                    {move || count_sessions.get().into_iter().map(|s| view! {
+                                        <option value={s.session_id.to_string()}>{format!("{} ({})", s.name, s.count_date)}</option>
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Closing Count"</label>
+                                <select prop:value=move || ap_closing.get() on:change=move |ev| set_ap_closing.set(event_target_value(&ev))>
+                                    <option value="">"Latest before end"</option>
+                                    {move || count_sessions.get().into_iter().map(|s| view! {
+                                        <option value={s.session_id.to_string()}>{format!("{} ({})", s.name, s.count_date)}</option>
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </div>
+                            <div class="input">
+                                <label>"Notes"</label>
+                                <input type="text" prop:value=move || ap_notes.get() on:input=move |ev| set_ap_notes.set(event_target_value(&ev)) />
+                            </div>
+                            <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                <button class="button" on:click=move |_| save_period()>
+                                    {move || if ap_edit_id.get().is_some() { "Update Period" } else { "Add Period" }}
                                 </button>
                             </div>
+                            <Show when=move || ap_edit_id.get().is_some()>
+                                <div class="input" style="align-self: end; flex: 0 0 auto;">
+                                    <button class="button secondary" on:click=move |_| reset_period_form()>"Cancel"</button>
+                                </div>
+                            </Show>
+                        </div>
+                        <Show when=move || !ap_status.get().is_empty()>
+                            <div class="status">{move || ap_status.get()}</div>
                         </Show>
-
-                        <div class="status">{move || fc_status.get()}</div>
-
-                        // results
-                        <Show when=move || fc_result.get().is_some()>
-                            {move || {
-                                let result = fc_result.get().unwrap_or_default();
-                                let total = result.total_cost;
-                                let missing = result.missing_costs;
-                                let pct = fc_target_pct.get();
-                                let menu_price = if pct > 0.0 { total / (pct / 100.0) } else { 0.0 };
-                                let dish = fc_dish_name.get();
-                                let dish_label = if dish.trim().is_empty() { "This dish".to_string() } else { dish };
-
-                                view! {
-                                    <div class="fc-results">
-                                        <div class="fc-summary-cards">
-                                            <div class="card fc-card">
-                                                <h4>"Total Ingredient Cost"</h4>
-                                                <p class="fc-big-number">{format_money(total)}</p>
-                                            </div>
-                                            <div class="card fc-card fc-card-highlight">
-                                                <h4>"Suggested Menu Price"</h4>
-                                                <p class="fc-big-number">{format_money(menu_price)}</p>
-                                                <p class="fc-sub">{format!("at {}% food cost", pct)}</p>
-                                            </div>
-                                            <div class="card fc-card">
-                                                <h4>"Profit per Plate"</h4>
-                                                <p class="fc-big-number">{format_money(menu_price - total)}</p>
-                                            </div>
+                        <div class="data-table" style="margin-top: 10px;">
+                            <div class="data-header data-cols-4">
+                                <span>"Period"</span>
+                                <span>"Dates"</span>
+                                <span>"Status"</span>
+                                <span></span>
+                            </div>
+                            <For
+                                each=move || ap_periods.get()
+                                key=|p| (p.period_id, p.status.clone(), p.name.clone(), p.start_date.clone(), p.end_date.clone())
+                                children=move |p| {
+                                    let period_id = p.period_id;
+                                    let open = p.status != "closed";
+                                    let edit = p.clone();
+                                    view! {
+                                        <div class="data-row data-cols-4">
+                                            <span>
+                                                <a href="#" on:click=move |ev: leptos::ev::MouseEvent| {
+                                                    ev.prevent_default();
+                                                    load_cogs(period_id);
+                                                }>{p.name.clone()}</a>
+                                            </span>
+                                            <span>{format!("{} \u{2013} {}", p.start_date, p.end_date)}</span>
+                                            <span>{if open { "Open" } else { "Closed" }}</span>
+                                            <span class="action-cell">
+                                                <Show when=move || open>
+                                                    <button class="button tiny secondary" on:click={
+                                                        let edit = edit.clone();
+                                                        move |_| edit_period(edit.clone())
+                                                    }>"Edit"</button>
+                                                    <button class="button tiny secondary" on:click=move |_| period_action("delete_accounting_period", period_id)>"Delete"</button>
+                                                </Show>
+                                            </span>
                                         </div>
+                                    }
+                                }
+                            />
+                        </div>
+                    </div>
 
-                                        <Show when=move || { missing > 0 }>
-                                            <div class="fc-warning">
-                                                {format!("\u{26A0} {} ingredient(s) could not be costed. The total may be understated.", missing)}
-                                            </div>
+                    {move || ap_report.get().map(|report| {
+                        let period_id = report.period.period_id;
+                        let closed = report.period.status == "closed";
+                        let open = !closed;
+                        let has_warnings = !report.warnings.is_empty();
+                        let has_mapping = !report.mapping.is_empty() && open;
+                        let revenue_of = |category: &str| report
+                            .lines
+                            .iter()
+                            .find(|l| l.category == category)
+                            .and_then(|l| l.revenue)
+                            .map(|r| format!("{:.2}", r))
+                            .unwrap_or_default();
+                        let food_revenue = revenue_of("food");
+                        let beverage_revenue = revenue_of("beverage");
+                        let pct = |p: Option<f64>| p.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "-".to_string());
+                        let cogs_row = move |line: CogsLine, total: bool| {
+                            let label = if total {
+                                "Total".to_string()
+                            } else {
+                                let mut c = line.category.chars();
+                                c.next().map(|f| f.to_uppercase().chain(c).collect()).unwrap_or_default()
+                            };
+                            view! {
+                                <div class="data-row data-cols-7" style=if total { "font-weight: 600;" } else { "" }>
+                                    <span>{label}</span>
+                                    <span>{format_money(line.opening)}</span>
+                                    <span>{format_money(line.purchases)}</span>
+                                    <span>{format_money(line.closing)}</span>
+                                    <span>{format_money(line.cogs)}</span>
+                                    <span>{line.revenue.map(format_money).unwrap_or_else(|| "-".to_string())}</span>
+                                    <span>{pct(line.cost_pct)}</span>
+                                </div>
+                            }
+                        };
+                        let total_line = report.total.clone();
+                        view! {
+                            <div class="panel">
+                                <div class="row" style="align-items: center;">
+                                    <strong>{format!(
+                                        "{} \u{00b7} {} to {} \u{00b7} {}",
+                                        report.period.name,
+                                        report.period.start_date,
+                                        report.period.end_date,
+                                        if closed { "Closed" } else { "Open" },
+                                    )}</strong>
+                                    <div style="margin-left: auto; display: flex; gap: 6px;">
+                                        <Show when=move || open>
+                                            <button class="button tiny secondary" on:click=move |_| period_action("import_period_revenue", period_id)>"Import POS Revenue"</button>
+                                            <button class="button tiny" on:click=move |_| period_action("close_accounting_period", period_id)>"Close Period"</button>
                                         </Show>
-
-                                        <div class="fc-detail-label">
-                                            {format!("\u{1F4CB} {} \u{2014} ingredient breakdown", dish_label)}
+                                        <Show when=move || closed>
+                                            <button class="button tiny secondary" on:click=move |_| period_action("reopen_accounting_period", period_id)>"Reopen"</button>
+                                        </Show>
+                                        <button class="button tiny secondary" on:click=move |_| export_cogs(period_id, true)>"PDF"</button>
+                                        <button class="button tiny secondary" on:click=move |_| export_cogs(period_id, false)>"CSV"</button>
+                                    </div>
+                                </div>
+                                <div class="status">
+                                    {format!(
+                                        "Opening: {} \u{00b7} Closing: {}",
+                                        report.opening_session.clone().unwrap_or_else(|| "none".to_string()),
+                                        report.closing_session.clone().unwrap_or_else(|| "none".to_string()),
+                                    )}
+                                </div>
+                                <Show when=move || has_warnings>
+                                    <div class="status">{report.warnings.join(" \u{00b7} ")}</div>
+                                </Show>
+                                <Show when=move || open>
+                                    <div class="row" style="margin-top: 10px;">
+                                        <div class="input">
+                                            <label>"Food Revenue"</label>
+                                            <input
+                                                type="text"
+                                                placeholder="0.00"
+                                                value=food_revenue.clone()
+                                                on:change=move |ev| save_period_revenue(period_id, "food".to_string(), event_target_value(&ev))
+                                            />
                                         </div>
-                                        <div class="data-table">
-                                            <div class="data-header data-cols-5">
-                                                <span>"Ingredient"</span>
-                                                <span>"Qty"</span>
-                                                <span>"Unit Price"</span>
-                                                <span>"Line Cost"</span>
-                                                <span>"Status"</span>
-                                            </div>
-                                            <For
-                                                each=move || result.lines.clone()
-                                                key=|line| line.item_id
-                                                children=move |line| {
-                                                    let status_class = if line.cost_status == "OK" { "fc-status-ok" } else { "fc-status-warn" };
-                                                    view! {
-                                                        <div class="data-row data-cols-5">
-                                                            <span>{format!("{} ({})", line.item_name, line.unit_name)}</span>
-                                                            <span>{format!("{:.2}", line.qty)}</span>
-                                                            <span>{line.price.map(|p| format_money(p)).unwrap_or_else(|| "\u{2014}".to_string())}</span>
-                                                            <span>{line.extended_cost.map(|c| format_money(c)).unwrap_or_else(|| "\u{2014}".to_string())}</span>
-                                                            <span class={status_class}>{line.cost_status.clone()}</span>
-                                                        </div>
-                                                    }
-                                                }
+                                        <div class="input">
+                                            <label>"Beverage Revenue"</label>
+                                            <input
+                                                type="text"
+                                                placeholder="0.00"
+                                                value=beverage_revenue.clone()
+                                                on:change=move |ev| save_period_revenue(period_id, "beverage".to_string(), event_target_value(&ev))
                                             />
                                         </div>
                                     </div>
-                                }
-                            }}
-                        </Show>
-                    </div>
+                                </Show>
+                                <div class="data-table" style="margin-top: 10px;">
+                                    <div class="data-header data-cols-7">
+                                        <span>"Category"</span>
+                                        <span>"Opening"</span>
+                                        <span>"Purchases"</span>
+                                        <span>"Closing"</span>
+                                        <span>"COGS"</span>
+                                        <span>"Revenue"</span>
+                                        <span>"Cost %"</span>
+                                    </div>
+                                    {report.lines.clone().into_iter().map(|line| cogs_row(line, false)).collect::<Vec<_>>()}
+                                    {cogs_row(total_line, true)}
+                                </div>
+                                <Show when=move || has_mapping>
+                                    <div class="status" style="margin-top: 10px;">
+                                        "Food categories and the COGS line they report under. Paper and other are measured against total revenue."
+                                    </div>
+                                    <div class="data-table">
+                                        <div class="data-header data-cols-3">
+                                            <span>"Food Category"</span>
+                                            <span>"Reports As"</span>
+                                            <span></span>
+                                        </div>
+                                        {report.mapping.clone().into_iter().map(|m| {
+                                            let food_category = m.food_category.clone();
+                                            view! {
+                                                <div class="data-row data-cols-3">
+                                                    <span>{m.food_category.clone()}</span>
+                                                    <span>
+                                                        <select
+                                                            prop:value=m.cogs_category.clone()
+                                                            on:change=move |ev| save_cogs_category(period_id, food_category.clone(), event_target_value(&ev))
+                                                        >
+                                                            <option value="food">"Food"</option>
+                                                            <option value="beverage">"Beverage"</option>
+                                                            <option value="paper">"Paper"</option>
+                                                            <option value="other">"Other"</option>
+                                                        </select>
+                                                    </span>
+                                                    <span></span>
+                                                </div>
+                                            }
+                                        }).collect::<Vec<_>>()}
+                                    </div>
+                                </Show>
+                            </div>
+                        }
+                    })}
                 </Show>
                 <Show when=move || active_panel.get() == "settings">
                     <div class="panel">
@@ -5509,6 +13310,80 @@ pub fn App() -> impl IntoView {
                                     }).collect::<Vec<_>>()}
                                 </div>
                             </div>
+                            <div class="settings-section">
+                                <h3>"Menu Pricing"</h3>
+                                <div class="row">
+                                    <div class="input">
+                                        <label>"Target Food Cost %"</label>
+                                        <input
+                                            type="number"
+                                            step="any"
+                                            placeholder="30"
+                                            prop:value=move || settings_target_pct.get()
+                                            on:input=move |ev| set_settings_target_pct.set(event_target_value(&ev))
+                                        />
+                                    </div>
+                                    <div class="input">
+                                        <label>"Price Rounding"</label>
+                                        <select
+                                            prop:value=move || settings_price_rounding.get()
+                                            on:change=move |ev| set_settings_price_rounding.set(event_target_value(&ev))
+                                        >
+                                            <option value="">"None"</option>
+                                            <option value="0.05">"Nearest $0.05"</option>
+                                            <option value="0.25">"Nearest $0.25"</option>
+                                            <option value=".95">"Ends in .95"</option>
+                                            <option value=".99">"Ends in .99"</option>
+                                            <option value=".49/.95">"Ends in .49 or .95"</option>
+                                        </select>
+                                    </div>
+                                </div>
+                            </div>
+                            <div class="settings-section">
+                                <h3>"Purchasing"</h3>
+                                <div class="row">
+                                    <div class="input">
+                                        <label>"Price Alert Threshold %"</label>
+                                        <input
+                                            type="number"
+                                            step="any"
+                                            min="0"
+                                            placeholder="5"
+                                            prop:value=move || settings_alert_pct.get()
+                                            on:input=move |ev| set_settings_alert_pct.set(event_target_value(&ev))
+                                        />
+                                    </div>
+                                    <div class="input">
+                                        <label>"Allocate Freight & Fees By"</label>
+                                        <select
+                                            prop:value=move || settings_alloc_method.get()
+                                            on:change=move |ev| set_settings_alloc_method.set(event_target_value(&ev))
+                                        >
+                                            <option value="value">"Line value"</option>
+                                            <option value="weight">"Weight"</option>
+                                            <option value="qty">"Quantity"</option>
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="row">
+                                    <label class="settings-radio">
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=move || settings_tax_cost.get()
+                                            on:change=move |ev| set_settings_tax_cost.set(event_target_checked(&ev))
+                                        />
+                                        "Sales tax is part of item cost (not recoverable)"
+                                    </label>
+                                    <label class="settings-radio">
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=move || settings_use_landed.get()
+                                            on:change=move |ev| set_settings_use_landed.set(event_target_checked(&ev))
+                                        />
+                                        "Cost recipes at landed cost"
+                                    </label>
+                                </div>
+                            </div>
                         </div>
                         <div class="row" style="margin-top: 20px; gap: 8px;">
                             <button